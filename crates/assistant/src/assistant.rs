@@ -247,6 +247,10 @@ fn register_slash_commands(cx: &mut App) {
     slash_command_registry
         .register_command(assistant_slash_commands::DiagnosticsSlashCommand, true);
     slash_command_registry.register_command(assistant_slash_commands::FetchSlashCommand, true);
+    slash_command_registry.register_command(assistant_slash_commands::NoteSlashCommand, true);
+    slash_command_registry.register_command(assistant_slash_commands::BacklinksSlashCommand, false);
+    slash_command_registry.register_command(assistant_slash_commands::TagSlashCommand, false);
+    slash_command_registry.register_command(assistant_slash_commands::DailySlashCommand, false);
 
     cx.observe_flag::<assistant_slash_commands::StreamingExampleSlashCommandFeatureFlag, _>({
         let slash_command_registry = slash_command_registry.clone();
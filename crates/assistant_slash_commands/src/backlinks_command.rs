@@ -0,0 +1,131 @@
+use anyhow::{Context as _, Result, anyhow};
+use assistant_slash_command::{
+    ArgumentCompletion, SlashCommand, SlashCommandOutput, SlashCommandOutputSection,
+    SlashCommandResult,
+};
+use gpui::{Task, WeakEntity};
+use language::{BufferSnapshot, LspAdapterDelegate};
+use std::{
+    fmt::Write,
+    sync::{Arc, atomic::AtomicBool},
+};
+use ui::prelude::*;
+use vault::{backlinks, is_note};
+use workspace::Workspace;
+
+use crate::vault_notes::WorkspaceVault;
+
+pub struct BacklinksSlashCommand;
+
+impl SlashCommand for BacklinksSlashCommand {
+    fn name(&self) -> String {
+        "backlinks".into()
+    }
+
+    fn description(&self) -> String {
+        "Insert notes linking to a note".into()
+    }
+
+    fn icon(&self) -> IconName {
+        IconName::Link
+    }
+
+    fn menu_text(&self) -> String {
+        self.description()
+    }
+
+    fn requires_argument(&self) -> bool {
+        false
+    }
+
+    fn accepts_arguments(&self) -> bool {
+        true
+    }
+
+    fn complete_argument(
+        self: Arc<Self>,
+        arguments: &[String],
+        cancellation_flag: Arc<AtomicBool>,
+        workspace: Option<WeakEntity<Workspace>>,
+        _: &mut Window,
+        cx: &mut App,
+    ) -> Task<Result<Vec<ArgumentCompletion>>> {
+        let Some(workspace) = workspace.and_then(|workspace| workspace.upgrade()) else {
+            return Task::ready(Err(anyhow!("workspace was dropped")));
+        };
+        match WorkspaceVault::for_workspace(&workspace, cx) {
+            Ok(vault) => vault.complete_note_name(arguments.join(" "), cancellation_flag, cx),
+            Err(error) => Task::ready(Err(error)),
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        arguments: &[String],
+        _context_slash_command_output_sections: &[SlashCommandOutputSection<language::Anchor>],
+        _context_buffer: BufferSnapshot,
+        workspace: WeakEntity<Workspace>,
+        _delegate: Option<Arc<dyn LspAdapterDelegate>>,
+        _: &mut Window,
+        cx: &mut App,
+    ) -> Task<SlashCommandResult> {
+        let Some(workspace) = workspace.upgrade() else {
+            return Task::ready(Err(anyhow!("workspace was dropped")));
+        };
+        let vault = match WorkspaceVault::for_workspace(&workspace, cx) {
+            Ok(vault) => vault,
+            Err(error) => return Task::ready(Err(error)),
+        };
+
+        // Without an argument, list the backlinks of the note being edited.
+        let name = arguments.join(" ");
+        let target = if name.trim().is_empty() {
+            workspace
+                .read(cx)
+                .active_item(cx)
+                .and_then(|item| item.project_path(cx))
+                .filter(|project_path| is_note(&project_path.path))
+                .map(|project_path| project_path.path)
+                .context("no note name given and no note is open")
+        } else {
+            vault.resolve(&name).map(|(path, _)| path)
+        };
+        let target = match target {
+            Ok(target) => target,
+            Err(error) => return Task::ready(Err(error)),
+        };
+
+        cx.background_spawn(async move {
+            let links = backlinks(&target, &vault.root, &vault.resolver, vault.fs.as_ref()).await;
+            let target_name = vault.resolver.link_target(&target);
+            let mut text = String::new();
+            if links.is_empty() {
+                writeln!(text, "No notes link to [[{target_name}]].")?;
+            } else {
+                writeln!(text, "Notes linking to [[{target_name}]]:")?;
+                for link in links {
+                    writeln!(
+                        text,
+                        "- [[{}]] (line {}): {}",
+                        vault.resolver.link_target(&link.source),
+                        link.row + 1,
+                        link.line
+                    )?;
+                }
+            }
+
+            let range = 0..text.len();
+            Ok(SlashCommandOutput {
+                text,
+                sections: vec![SlashCommandOutputSection {
+                    range,
+                    icon: IconName::Link,
+                    label: format!("Backlinks to {target_name}").into(),
+                    metadata: None,
+                }],
+                run_commands_in_text: false,
+            }
+            .to_event_stream())
+        })
+    }
+}
@@ -0,0 +1,132 @@
+use anyhow::{Context as _, Result, anyhow};
+use assistant_slash_command::{
+    ArgumentCompletion, SlashCommand, SlashCommandOutputSection, SlashCommandResult,
+};
+use chrono::{Days, Local, NaiveDate};
+use gpui::{Task, WeakEntity};
+use language::{BufferSnapshot, LspAdapterDelegate};
+use std::sync::{Arc, atomic::AtomicBool};
+use ui::prelude::*;
+use workspace::Workspace;
+
+use crate::vault_notes::WorkspaceVault;
+
+/// Daily notes are named after their date in this format.
+const DAILY_NOTE_FORMAT: &str = "%Y-%m-%d";
+
+pub struct DailySlashCommand;
+
+impl SlashCommand for DailySlashCommand {
+    fn name(&self) -> String {
+        "daily".into()
+    }
+
+    fn description(&self) -> String {
+        "Insert daily note".into()
+    }
+
+    fn icon(&self) -> IconName {
+        IconName::CountdownTimer
+    }
+
+    fn menu_text(&self) -> String {
+        self.description()
+    }
+
+    fn requires_argument(&self) -> bool {
+        false
+    }
+
+    fn accepts_arguments(&self) -> bool {
+        true
+    }
+
+    fn complete_argument(
+        self: Arc<Self>,
+        arguments: &[String],
+        _cancel: Arc<AtomicBool>,
+        _workspace: Option<WeakEntity<Workspace>>,
+        _window: &mut Window,
+        _cx: &mut App,
+    ) -> Task<Result<Vec<ArgumentCompletion>>> {
+        let query = arguments.last().cloned().unwrap_or_default();
+        Task::ready(Ok(["today", "yesterday", "tomorrow"]
+            .into_iter()
+            .filter(|day| day.starts_with(&query))
+            .map(|day| ArgumentCompletion {
+                label: day.into(),
+                new_text: day.to_string(),
+                after_completion: true.into(),
+                replace_previous_arguments: true,
+            })
+            .collect()))
+    }
+
+    fn run(
+        self: Arc<Self>,
+        arguments: &[String],
+        _context_slash_command_output_sections: &[SlashCommandOutputSection<language::Anchor>],
+        _context_buffer: BufferSnapshot,
+        workspace: WeakEntity<Workspace>,
+        _delegate: Option<Arc<dyn LspAdapterDelegate>>,
+        _: &mut Window,
+        cx: &mut App,
+    ) -> Task<SlashCommandResult> {
+        let Some(workspace) = workspace.upgrade() else {
+            return Task::ready(Err(anyhow!("workspace was dropped")));
+        };
+        let date = match parse_date(
+            arguments.first().map_or("today", String::as_str),
+            Local::now().date_naive(),
+        ) {
+            Ok(date) => date,
+            Err(error) => return Task::ready(Err(error)),
+        };
+        let vault = match WorkspaceVault::for_workspace(&workspace, cx) {
+            Ok(vault) => vault,
+            Err(error) => return Task::ready(Err(error)),
+        };
+
+        let name = date.format(DAILY_NOTE_FORMAT).to_string();
+        match vault.resolve(&name) {
+            Ok((path, _)) => vault.insert_note(path, None, cx),
+            Err(_) => Task::ready(Err(anyhow!("there is no daily note for {name}"))),
+        }
+    }
+}
+
+/// Parses `today`, `yesterday`, `tomorrow` or a `YYYY-MM-DD` date.
+fn parse_date(argument: &str, today: NaiveDate) -> Result<NaiveDate> {
+    match argument.trim().to_lowercase().as_str() {
+        "" | "today" => Ok(today),
+        "yesterday" => today.checked_sub_days(Days::new(1)).context("invalid date"),
+        "tomorrow" => today.checked_add_days(Days::new(1)).context("invalid date"),
+        date => NaiveDate::parse_from_str(date, DAILY_NOTE_FORMAT).with_context(|| {
+            format!("invalid date {date:?}, expected today, yesterday, tomorrow or YYYY-MM-DD")
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_date() {
+        let today = NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+        assert_eq!(parse_date("today", today).unwrap(), today);
+        assert_eq!(
+            parse_date("Yesterday", today).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 2, 28).unwrap()
+        );
+        assert_eq!(
+            parse_date("tomorrow", today).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 3, 2).unwrap()
+        );
+        assert_eq!(
+            parse_date("2024-12-31", today).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 12, 31).unwrap()
+        );
+        assert!(parse_date("next week", today).is_err());
+    }
+}
@@ -0,0 +1,80 @@
+use anyhow::{Result, anyhow};
+use assistant_slash_command::{
+    ArgumentCompletion, SlashCommand, SlashCommandOutputSection, SlashCommandResult,
+};
+use gpui::{Task, WeakEntity};
+use language::{BufferSnapshot, LspAdapterDelegate};
+use std::sync::{Arc, atomic::AtomicBool};
+use ui::prelude::*;
+use workspace::Workspace;
+
+use crate::vault_notes::WorkspaceVault;
+
+pub struct NoteSlashCommand;
+
+impl SlashCommand for NoteSlashCommand {
+    fn name(&self) -> String {
+        "note".into()
+    }
+
+    fn description(&self) -> String {
+        "Insert note by name, with embeds".into()
+    }
+
+    fn icon(&self) -> IconName {
+        IconName::FileText
+    }
+
+    fn menu_text(&self) -> String {
+        self.description()
+    }
+
+    fn requires_argument(&self) -> bool {
+        true
+    }
+
+    fn complete_argument(
+        self: Arc<Self>,
+        arguments: &[String],
+        cancellation_flag: Arc<AtomicBool>,
+        workspace: Option<WeakEntity<Workspace>>,
+        _: &mut Window,
+        cx: &mut App,
+    ) -> Task<Result<Vec<ArgumentCompletion>>> {
+        let Some(workspace) = workspace.and_then(|workspace| workspace.upgrade()) else {
+            return Task::ready(Err(anyhow!("workspace was dropped")));
+        };
+        match WorkspaceVault::for_workspace(&workspace, cx) {
+            Ok(vault) => vault.complete_note_name(arguments.join(" "), cancellation_flag, cx),
+            Err(error) => Task::ready(Err(error)),
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        arguments: &[String],
+        _context_slash_command_output_sections: &[SlashCommandOutputSection<language::Anchor>],
+        _context_buffer: BufferSnapshot,
+        workspace: WeakEntity<Workspace>,
+        _delegate: Option<Arc<dyn LspAdapterDelegate>>,
+        _: &mut Window,
+        cx: &mut App,
+    ) -> Task<SlashCommandResult> {
+        let Some(workspace) = workspace.upgrade() else {
+            return Task::ready(Err(anyhow!("workspace was dropped")));
+        };
+        let name = arguments.join(" ");
+        if name.trim().is_empty() {
+            return Task::ready(Err(anyhow!("missing note name")));
+        }
+
+        let vault = match WorkspaceVault::for_workspace(&workspace, cx) {
+            Ok(vault) => vault,
+            Err(error) => return Task::ready(Err(error)),
+        };
+        match vault.resolve(&name) {
+            Ok((path, subpath)) => vault.insert_note(path, subpath, cx),
+            Err(error) => Task::ready(Err(error)),
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use anyhow::{Result, anyhow};
+use assistant_slash_command::{
+    ArgumentCompletion, SlashCommand, SlashCommandOutput, SlashCommandOutputSection,
+    SlashCommandResult,
+};
+use gpui::{Task, WeakEntity};
+use language::{BufferSnapshot, LspAdapterDelegate};
+use std::{
+    fmt::Write,
+    sync::{Arc, atomic::AtomicBool},
+};
+use ui::prelude::*;
+use vault::notes_with_tag;
+use workspace::Workspace;
+
+use crate::vault_notes::WorkspaceVault;
+
+pub struct TagSlashCommand;
+
+impl SlashCommand for TagSlashCommand {
+    fn name(&self) -> String {
+        "tag".into()
+    }
+
+    fn description(&self) -> String {
+        "Insert notes with a tag".into()
+    }
+
+    fn icon(&self) -> IconName {
+        IconName::Hash
+    }
+
+    fn menu_text(&self) -> String {
+        self.description()
+    }
+
+    fn requires_argument(&self) -> bool {
+        true
+    }
+
+    fn complete_argument(
+        self: Arc<Self>,
+        _arguments: &[String],
+        _cancel: Arc<AtomicBool>,
+        _workspace: Option<WeakEntity<Workspace>>,
+        _window: &mut Window,
+        _cx: &mut App,
+    ) -> Task<Result<Vec<ArgumentCompletion>>> {
+        Task::ready(Ok(Vec::new()))
+    }
+
+    fn run(
+        self: Arc<Self>,
+        arguments: &[String],
+        _context_slash_command_output_sections: &[SlashCommandOutputSection<language::Anchor>],
+        _context_buffer: BufferSnapshot,
+        workspace: WeakEntity<Workspace>,
+        _delegate: Option<Arc<dyn LspAdapterDelegate>>,
+        _: &mut Window,
+        cx: &mut App,
+    ) -> Task<SlashCommandResult> {
+        let Some(workspace) = workspace.upgrade() else {
+            return Task::ready(Err(anyhow!("workspace was dropped")));
+        };
+        let Some(tag) = arguments
+            .first()
+            .map(|tag| tag.trim_start_matches('#').to_string())
+        else {
+            return Task::ready(Err(anyhow!("missing tag")));
+        };
+        let vault = match WorkspaceVault::for_workspace(&workspace, cx) {
+            Ok(vault) => vault,
+            Err(error) => return Task::ready(Err(error)),
+        };
+
+        cx.background_spawn(async move {
+            let notes = notes_with_tag(&tag, &vault.root, &vault.resolver, vault.fs.as_ref()).await;
+            let mut text = String::new();
+            if notes.is_empty() {
+                writeln!(text, "No notes are tagged #{tag}.")?;
+            } else {
+                writeln!(text, "Notes tagged #{tag}:")?;
+                for (path, tags) in notes {
+                    let tags = tags
+                        .iter()
+                        .map(|tag| format!("#{tag}"))
+                        .collect::<Vec<_>>()
+                        .join(" ");
+                    writeln!(text, "- [[{}]] {tags}", vault.resolver.link_target(&path))?;
+                }
+            }
+
+            let range = 0..text.len();
+            Ok(SlashCommandOutput {
+                text,
+                sections: vec![SlashCommandOutputSection {
+                    range,
+                    icon: IconName::Hash,
+                    label: format!("#{tag}").into(),
+                    metadata: None,
+                }],
+                run_commands_in_text: false,
+            }
+            .to_event_stream())
+        })
+    }
+}
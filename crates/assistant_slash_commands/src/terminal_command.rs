@@ -83,15 +83,23 @@ impl SlashCommand for TerminalSlashCommand {
             .and_then(|s| s.parse::<usize>().ok())
             .unwrap_or(DEFAULT_CONTEXT_LINES);
 
-        let lines = active_terminal
-            .read(cx)
-            .entity()
-            .read(cx)
-            .last_n_non_empty_lines(line_count);
-
+        let terminal = active_terminal.read(cx).entity().read(cx);
         let mut text = String::new();
-        text.push_str("Terminal output:\n");
-        text.push_str(&lines.join("\n"));
+        // With shell integration, the last command's output is known exactly.
+        // Otherwise, fall back to the last lines of the terminal.
+        if let Some(command) = terminal.recent_commands(1).pop() {
+            text.push_str(&format!("Terminal command: `{}`\n", command.command));
+            if let Some(exit_status) = command.exit_status {
+                text.push_str(&format!("Exit status: {exit_status}\n"));
+            }
+            let lines = command.output.lines().collect::<Vec<_>>();
+            text.push_str("Output:\n");
+            text.push_str(&lines[lines.len().saturating_sub(line_count)..].join("\n"));
+        } else {
+            let lines = terminal.last_n_non_empty_lines(line_count);
+            text.push_str("Terminal output:\n");
+            text.push_str(&lines.join("\n"));
+        }
         let range = 0..text.len();
 
         Task::ready(Ok(SlashCommandOutput {
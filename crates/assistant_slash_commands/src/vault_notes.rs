@@ -0,0 +1,142 @@
+use anyhow::{Context as _, Result};
+use assistant_slash_command::{
+    ArgumentCompletion, SlashCommandOutput, SlashCommandOutputSection, SlashCommandResult,
+};
+use fs::Fs;
+use gpui::{App, Entity, Task};
+use std::{
+    ops::Deref,
+    path::Path,
+    sync::{Arc, atomic::AtomicBool},
+};
+use ui::IconName;
+use vault::{LinkSubpath, Vault};
+use workspace::Workspace;
+
+/// The vault the note commands work in: the first visible worktree of the
+/// workspace's project.
+pub(crate) struct WorkspaceVault {
+    pub vault: Vault,
+    pub fs: Arc<dyn Fs>,
+}
+
+impl WorkspaceVault {
+    pub fn for_workspace(workspace: &Entity<Workspace>, cx: &App) -> Result<Self> {
+        let workspace = workspace.read(cx);
+        let worktree = workspace
+            .visible_worktrees(cx)
+            .next()
+            .context("no vault folder is open")?;
+        let worktree = worktree.read(cx);
+        let vault = Vault::new(
+            worktree.abs_path(),
+            worktree
+                .snapshot()
+                .files(false, 0)
+                .map(|entry| entry.path.clone()),
+        );
+        Ok(Self {
+            vault,
+            fs: workspace.app_state().fs.clone(),
+        })
+    }
+
+    /// Completes a note name, matching `query` against the names that wiki
+    /// links would use for every note.
+    pub fn complete_note_name(
+        &self,
+        query: String,
+        cancellation_flag: Arc<AtomicBool>,
+        cx: &App,
+    ) -> Task<Result<Vec<ArgumentCompletion>>> {
+        let candidates = self
+            .resolver
+            .notes()
+            .enumerate()
+            .map(|(id, path)| {
+                fuzzy::StringMatchCandidate::new(id, &self.resolver.link_target(path))
+            })
+            .collect::<Vec<_>>();
+        let executor = cx.background_executor().clone();
+        cx.background_executor().spawn(async move {
+            let matches = fuzzy::match_strings(
+                &candidates,
+                &query,
+                false,
+                100,
+                &cancellation_flag,
+                executor,
+            )
+            .await;
+            Ok(matches
+                .into_iter()
+                .map(|name| ArgumentCompletion {
+                    label: name.string.clone().into(),
+                    new_text: name.string,
+                    after_completion: true.into(),
+                    replace_previous_arguments: true,
+                })
+                .collect())
+        })
+    }
+
+    /// Inserts the note at `path`, narrowed to `subpath` if there is one, with
+    /// its `![[embeds]]` replaced by the content they refer to.
+    pub fn insert_note(
+        self,
+        path: Arc<Path>,
+        subpath: Option<LinkSubpath>,
+        cx: &App,
+    ) -> Task<SlashCommandResult> {
+        cx.background_executor().spawn(async move {
+            let text = self.fs.load(&self.root.join(&path)).await?;
+            let text = match &subpath {
+                Some(subpath) => {
+                    let body = &text[vault::body_offset(&text)..];
+                    let range = match subpath {
+                        LinkSubpath::Heading(heading) => vault::heading_section(body, heading)
+                            .with_context(|| format!("no heading {heading:?} in {path:?}"))?,
+                        LinkSubpath::Block(block_id) => vault::block_range(body, block_id)
+                            .with_context(|| format!("no block ^{block_id} in {path:?}"))?,
+                    };
+                    body[range].to_string()
+                }
+                None => text,
+            };
+
+            let mut text =
+                vault::expand_embeds(&text, &path, &self.root, &self.resolver, self.fs.as_ref())
+                    .await;
+            if !text.ends_with('\n') {
+                text.push('\n');
+            }
+            let mut label = self.resolver.link_target(&path);
+            match &subpath {
+                Some(LinkSubpath::Heading(heading)) => label = format!("{label} > {heading}"),
+                Some(LinkSubpath::Block(block_id)) => label = format!("{label} ^{block_id}"),
+                None => {}
+            }
+
+            let range = 0..text.len();
+            Ok(SlashCommandOutput {
+                text,
+                sections: vec![SlashCommandOutputSection {
+                    range,
+                    icon: IconName::FileText,
+                    label: label.into(),
+                    metadata: None,
+                }],
+                run_commands_in_text: false,
+            }
+            .to_event_stream())
+        })
+    }
+}
+
+impl Deref for WorkspaceVault {
+    type Target = Vault;
+
+    fn deref(&self) -> &Vault {
+        &self.vault
+    }
+}
@@ -5,21 +5,26 @@ mod code_symbols_tool;
 mod copy_path_tool;
 mod create_directory_tool;
 mod create_file_tool;
+mod create_note_tool;
 mod delete_path_tool;
 mod diagnostics_tool;
 mod fetch_tool;
 mod find_replace_file_tool;
+mod list_backlinks_tool;
 mod list_directory_tool;
 mod move_path_tool;
 mod now_tool;
 mod open_tool;
 mod path_search_tool;
 mod read_file_tool;
+mod read_note_tool;
 mod regex_search_tool;
 mod replace;
 mod schema;
 mod symbol_info_tool;
 mod thinking_tool;
+mod vault_notes;
+mod vault_search_tool;
 
 use std::sync::Arc;
 
@@ -34,18 +39,22 @@ use crate::batch_tool::BatchTool;
 use crate::code_symbols_tool::CodeSymbolsTool;
 use crate::create_directory_tool::CreateDirectoryTool;
 use crate::create_file_tool::CreateFileTool;
+use crate::create_note_tool::CreateNoteTool;
 use crate::delete_path_tool::DeletePathTool;
 use crate::diagnostics_tool::DiagnosticsTool;
 use crate::fetch_tool::FetchTool;
 use crate::find_replace_file_tool::FindReplaceFileTool;
+use crate::list_backlinks_tool::ListBacklinksTool;
 use crate::list_directory_tool::ListDirectoryTool;
 use crate::now_tool::NowTool;
 use crate::open_tool::OpenTool;
 use crate::path_search_tool::PathSearchTool;
 use crate::read_file_tool::ReadFileTool;
+use crate::read_note_tool::ReadNoteTool;
 use crate::regex_search_tool::RegexSearchTool;
 use crate::symbol_info_tool::SymbolInfoTool;
 use crate::thinking_tool::ThinkingTool;
+use crate::vault_search_tool::VaultSearchTool;
 
 pub fn init(http_client: Arc<HttpClientWithUrl>, cx: &mut App) {
     assistant_tool::init(cx);
@@ -70,4 +79,8 @@ pub fn init(http_client: Arc<HttpClientWithUrl>, cx: &mut App) {
     registry.register_tool(RegexSearchTool);
     registry.register_tool(ThinkingTool);
     registry.register_tool(FetchTool::new(http_client));
+    registry.register_tool(VaultSearchTool);
+    registry.register_tool(ReadNoteTool);
+    registry.register_tool(ListBacklinksTool);
+    registry.register_tool(CreateNoteTool);
 }
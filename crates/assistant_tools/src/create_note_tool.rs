@@ -0,0 +1,186 @@
+use std::collections::BTreeMap;
+use std::path::{Component, PathBuf};
+use std::sync::Arc;
+
+use crate::schema::json_schema_for;
+use crate::vault_notes::ProjectVault;
+use anyhow::{Result, anyhow};
+use assistant_tool::{ActionLog, Tool};
+use gpui::{App, Entity, Task};
+use language_model::{LanguageModelRequestMessage, LanguageModelToolSchemaFormat};
+use project::Project;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use ui::IconName;
+use util::markdown::MarkdownString;
+use vault::{Frontmatter, PropertyValue, unique_note_path};
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CreateNoteToolInput {
+    /// The title of the note, which becomes its file name.
+    pub title: String,
+
+    /// Optional folder to create the note in, relative to the root of the vault.
+    /// Defaults to the root of the vault.
+    ///
+    /// <example>
+    /// To create the note in the `Projects/Garden` folder, use "Projects/Garden".
+    /// </example>
+    #[serde(default)]
+    pub folder: Option<String>,
+
+    /// Optional frontmatter properties. Values are either strings or lists of strings.
+    ///
+    /// <example>
+    /// { "tags": ["garden", "todo"], "status": "draft" }
+    /// </example>
+    #[serde(default)]
+    pub properties: BTreeMap<String, serde_json::Value>,
+
+    /// The markdown body of the note, without frontmatter.
+    #[serde(default)]
+    pub body: String,
+}
+
+pub struct CreateNoteTool;
+
+impl Tool for CreateNoteTool {
+    fn name(&self) -> String {
+        "create_note".into()
+    }
+
+    fn needs_confirmation(&self) -> bool {
+        false
+    }
+
+    fn description(&self) -> String {
+        include_str!("./create_note_tool/description.md").into()
+    }
+
+    fn icon(&self) -> IconName {
+        IconName::FileCreate
+    }
+
+    fn input_schema(&self, format: LanguageModelToolSchemaFormat) -> serde_json::Value {
+        json_schema_for::<CreateNoteToolInput>(format)
+    }
+
+    fn ui_text(&self, input: &serde_json::Value) -> String {
+        match serde_json::from_value::<CreateNoteToolInput>(input.clone()) {
+            Ok(input) => format!("Create note {}", MarkdownString::inline_code(&input.title)),
+            Err(_) => "Create note".to_string(),
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        _messages: &[LanguageModelRequestMessage],
+        project: Entity<Project>,
+        action_log: Entity<ActionLog>,
+        cx: &mut App,
+    ) -> Task<Result<String>> {
+        let input = match serde_json::from_value::<CreateNoteToolInput>(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let vault = match ProjectVault::for_project(&project, cx) {
+            Ok(vault) => vault,
+            Err(err) => return Task::ready(Err(err)),
+        };
+        let folder = PathBuf::from(input.folder.as_deref().unwrap_or_default());
+        if !folder
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+        {
+            return Task::ready(Err(anyhow!(
+                "Folder {} must be relative to the root of the vault",
+                folder.display()
+            )));
+        }
+
+        let path = unique_note_path(&folder, &input.title, |path| {
+            vault.snapshot.entry_for_path(path).is_some()
+        });
+        let display_path = vault.display_path(&path);
+        let project_path = vault.project_path(&path);
+        let contents: Arc<str> = note_contents(&input.properties, &input.body).into();
+
+        cx.spawn(async move |cx| {
+            let buffer = project
+                .update(cx, |project, cx| project.open_buffer(project_path, cx))?
+                .await
+                .map_err(|err| anyhow!("Unable to open buffer for {display_path}: {err}"))?;
+            cx.update(|cx| {
+                buffer.update(cx, |buffer, cx| buffer.set_text(contents, cx));
+                action_log.update(cx, |action_log, cx| {
+                    action_log.will_create_buffer(buffer.clone(), cx)
+                });
+            })?;
+
+            project
+                .update(cx, |project, cx| project.save_buffer(buffer, cx))?
+                .await
+                .map_err(|err| anyhow!("Unable to save buffer for {display_path}: {err}"))?;
+
+            Ok(format!("Created note {display_path}"))
+        })
+    }
+}
+
+/// Renders the properties as frontmatter followed by the body. Lists become
+/// YAML lists, other values are written as scalars and nulls are dropped.
+fn note_contents(properties: &BTreeMap<String, serde_json::Value>, body: &str) -> String {
+    let mut frontmatter = Frontmatter::new();
+    for (key, value) in properties {
+        let value = match value {
+            serde_json::Value::Null => continue,
+            serde_json::Value::String(text) => PropertyValue::Text(text.clone()),
+            serde_json::Value::Array(items) => {
+                PropertyValue::List(items.iter().map(property_text).collect())
+            }
+            value => PropertyValue::Text(property_text(value)),
+        };
+        frontmatter.set(key.trim(), value);
+    }
+
+    let mut contents = if frontmatter.is_empty() {
+        String::new()
+    } else {
+        frontmatter.to_yaml()
+    };
+    contents.push_str(body.trim_start_matches('\n'));
+    if !contents.is_empty() && !contents.ends_with('\n') {
+        contents.push('\n');
+    }
+    contents
+}
+
+fn property_text(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(text) => text.clone(),
+        value => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_note_contents() {
+        let properties = serde_json::from_value(json!({
+            "tags": ["garden", "todo"],
+            "status": "draft",
+            "priority": 2,
+            "due": null,
+        }))
+        .unwrap();
+        assert_eq!(
+            note_contents(&properties, "# Plan\nSow in [[March]]."),
+            "---\npriority: 2\nstatus: draft\ntags:\n  - garden\n  - todo\n---\n# Plan\nSow in [[March]].\n"
+        );
+        assert_eq!(note_contents(&BTreeMap::new(), "Plain"), "Plain\n");
+    }
+}
@@ -0,0 +1,101 @@
+use std::fmt::Write as _;
+use std::sync::Arc;
+
+use crate::schema::json_schema_for;
+use crate::vault_notes::ProjectVault;
+use anyhow::{Result, anyhow};
+use assistant_tool::{ActionLog, Tool};
+use gpui::{App, AppContext, Entity, Task};
+use language_model::{LanguageModelRequestMessage, LanguageModelToolSchemaFormat};
+use project::Project;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use ui::IconName;
+use util::markdown::MarkdownString;
+use vault::backlinks;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ListBacklinksToolInput {
+    /// The name of the note, as a wiki link would refer to it.
+    ///
+    /// <example>
+    /// To find the notes linking to `[[Tomatoes]]`, use "Tomatoes".
+    /// </example>
+    pub name: String,
+}
+
+pub struct ListBacklinksTool;
+
+impl Tool for ListBacklinksTool {
+    fn name(&self) -> String {
+        "list_backlinks".into()
+    }
+
+    fn needs_confirmation(&self) -> bool {
+        false
+    }
+
+    fn description(&self) -> String {
+        include_str!("./list_backlinks_tool/description.md").into()
+    }
+
+    fn icon(&self) -> IconName {
+        IconName::Link
+    }
+
+    fn input_schema(&self, format: LanguageModelToolSchemaFormat) -> serde_json::Value {
+        json_schema_for::<ListBacklinksToolInput>(format)
+    }
+
+    fn ui_text(&self, input: &serde_json::Value) -> String {
+        match serde_json::from_value::<ListBacklinksToolInput>(input.clone()) {
+            Ok(input) => format!(
+                "List backlinks to {}",
+                MarkdownString::inline_code(&input.name)
+            ),
+            Err(_) => "List backlinks".to_string(),
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        _messages: &[LanguageModelRequestMessage],
+        project: Entity<Project>,
+        _action_log: Entity<ActionLog>,
+        cx: &mut App,
+    ) -> Task<Result<String>> {
+        let input = match serde_json::from_value::<ListBacklinksToolInput>(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let vault = match ProjectVault::for_project(&project, cx) {
+            Ok(vault) => vault,
+            Err(err) => return Task::ready(Err(err)),
+        };
+        let (target, _) = match vault.resolve(&input.name) {
+            Ok(resolved) => resolved,
+            Err(err) => return Task::ready(Err(err)),
+        };
+        let fs = project.read(cx).fs().clone();
+
+        cx.background_spawn(async move {
+            let links = backlinks(&target, &vault.root, &vault.resolver, fs.as_ref()).await;
+            if links.is_empty() {
+                return Ok(format!("No notes link to {}.", input.name));
+            }
+
+            let mut output = String::new();
+            for backlink in links {
+                writeln!(
+                    output,
+                    "- {}:{}: {}",
+                    vault.display_path(&backlink.source),
+                    backlink.row + 1,
+                    backlink.line
+                )?;
+            }
+            Ok(output)
+        })
+    }
+}
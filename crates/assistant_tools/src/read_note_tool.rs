@@ -0,0 +1,131 @@
+use std::fmt::Write as _;
+use std::sync::Arc;
+
+use crate::schema::json_schema_for;
+use crate::vault_notes::ProjectVault;
+use anyhow::{Context as _, Result, anyhow};
+use assistant_tool::{ActionLog, Tool};
+use collections::HashSet;
+use gpui::{App, Entity, Task};
+use language_model::{LanguageModelRequestMessage, LanguageModelToolSchemaFormat};
+use project::Project;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use ui::IconName;
+use util::markdown::MarkdownString;
+use vault::{LinkSubpath, block_range, body_offset, heading_section, wiki_links};
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ReadNoteToolInput {
+    /// The name of the note, as a wiki link would refer to it.
+    ///
+    /// <example>
+    /// To read the note linked as `[[Tomatoes]]`, use "Tomatoes".
+    /// To read only its "Watering" section, use "Tomatoes#Watering".
+    /// If several notes share a name, include the folder: "Garden/Tomatoes".
+    /// </example>
+    pub name: String,
+}
+
+pub struct ReadNoteTool;
+
+impl Tool for ReadNoteTool {
+    fn name(&self) -> String {
+        "read_note".into()
+    }
+
+    fn needs_confirmation(&self) -> bool {
+        false
+    }
+
+    fn description(&self) -> String {
+        include_str!("./read_note_tool/description.md").into()
+    }
+
+    fn icon(&self) -> IconName {
+        IconName::FileText
+    }
+
+    fn input_schema(&self, format: LanguageModelToolSchemaFormat) -> serde_json::Value {
+        json_schema_for::<ReadNoteToolInput>(format)
+    }
+
+    fn ui_text(&self, input: &serde_json::Value) -> String {
+        match serde_json::from_value::<ReadNoteToolInput>(input.clone()) {
+            Ok(input) => format!("Read note {}", MarkdownString::inline_code(&input.name)),
+            Err(_) => "Read note".to_string(),
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        _messages: &[LanguageModelRequestMessage],
+        project: Entity<Project>,
+        action_log: Entity<ActionLog>,
+        cx: &mut App,
+    ) -> Task<Result<String>> {
+        let input = match serde_json::from_value::<ReadNoteToolInput>(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let vault = match ProjectVault::for_project(&project, cx) {
+            Ok(vault) => vault,
+            Err(err) => return Task::ready(Err(err)),
+        };
+        let (path, subpath) = match vault.resolve(&input.name) {
+            Ok(resolved) => resolved,
+            Err(err) => return Task::ready(Err(err)),
+        };
+        let project_path = vault.project_path(&path);
+
+        cx.spawn(async move |cx| {
+            let buffer = project
+                .update(cx, |project, cx| project.open_buffer(project_path, cx))?
+                .await?;
+            let text = buffer.read_with(cx, |buffer, _cx| buffer.text())?;
+            action_log.update(cx, |log, cx| {
+                log.buffer_read(buffer, cx);
+            })?;
+
+            let content = match &subpath {
+                Some(subpath) => {
+                    let body = &text[body_offset(&text)..];
+                    let range = match subpath {
+                        LinkSubpath::Heading(heading) => heading_section(body, heading)
+                            .with_context(|| format!("no heading {heading:?} in {}", input.name))?,
+                        LinkSubpath::Block(block_id) => block_range(body, block_id)
+                            .with_context(|| format!("no block ^{block_id} in {}", input.name))?,
+                    };
+                    &body[range]
+                }
+                None => text.as_str(),
+            };
+
+            let mut output = format!("# {}\n\n{}", vault.display_path(&path), content);
+            let mut seen_targets = HashSet::default();
+            let mut links = String::new();
+            for link in wiki_links(content) {
+                if link.target.is_empty() || !seen_targets.insert(link.target.clone()) {
+                    continue;
+                }
+                match vault.resolver.resolve(&link.target, Some(path.as_ref())) {
+                    Some(target) => writeln!(
+                        links,
+                        "- [[{}]]: {}",
+                        link.target,
+                        vault.display_path(&target)
+                    )?,
+                    None => writeln!(links, "- [[{}]]: not created yet", link.target)?,
+                }
+            }
+            if !links.is_empty() {
+                if !output.ends_with('\n') {
+                    output.push('\n');
+                }
+                write!(output, "\nLinks to:\n{links}")?;
+            }
+            Ok(output)
+        })
+    }
+}
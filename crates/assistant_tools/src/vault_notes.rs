@@ -0,0 +1,54 @@
+use std::ops::Deref;
+use std::path::Path;
+
+use anyhow::{Context as _, Result};
+use gpui::{App, Entity};
+use project::{Project, ProjectPath};
+use vault::Vault;
+use worktree::Snapshot;
+
+/// The vault the note tools work in: the project's first visible worktree.
+pub(crate) struct ProjectVault {
+    pub vault: Vault,
+    pub snapshot: Snapshot,
+}
+
+impl ProjectVault {
+    pub fn for_project(project: &Entity<Project>, cx: &App) -> Result<Self> {
+        let worktree = project
+            .read(cx)
+            .visible_worktrees(cx)
+            .next()
+            .context("no folder is open in the project")?;
+        let snapshot = worktree.read(cx).snapshot();
+        let vault = Vault::new(
+            snapshot.abs_path().clone(),
+            snapshot.files(false, 0).map(|entry| entry.path.clone()),
+        );
+        Ok(Self { vault, snapshot })
+    }
+
+    pub fn project_path(&self, path: &Path) -> ProjectPath {
+        ProjectPath {
+            worktree_id: self.snapshot.id(),
+            path: path.into(),
+        }
+    }
+
+    /// The path of a note as the other tools expect it, starting with the
+    /// name of the root folder.
+    pub fn display_path(&self, path: &Path) -> String {
+        Path::new(self.snapshot.root_name())
+            .join(path)
+            .display()
+            .to_string()
+    }
+}
+
+impl Deref for ProjectVault {
+    type Target = Vault;
+
+    fn deref(&self) -> &Vault {
+        &self.vault
+    }
+}
@@ -0,0 +1,110 @@
+use std::fmt::Write as _;
+use std::sync::Arc;
+
+use crate::schema::json_schema_for;
+use crate::vault_notes::ProjectVault;
+use anyhow::{Result, anyhow};
+use assistant_tool::{ActionLog, Tool};
+use gpui::{App, AppContext, Entity, Task};
+use language_model::{LanguageModelRequestMessage, LanguageModelToolSchemaFormat};
+use project::Project;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use ui::IconName;
+use util::markdown::MarkdownString;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct VaultSearchToolInput {
+    /// A description of what you're looking for, in natural language.
+    ///
+    /// <example>
+    /// To find notes about watering plants, use "how often to water the tomatoes".
+    /// </example>
+    pub query: String,
+
+    /// Optional maximum number of passages to return. Defaults to 10.
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+pub struct VaultSearchTool;
+
+impl Tool for VaultSearchTool {
+    fn name(&self) -> String {
+        "vault_search".into()
+    }
+
+    fn needs_confirmation(&self) -> bool {
+        false
+    }
+
+    fn description(&self) -> String {
+        include_str!("./vault_search_tool/description.md").into()
+    }
+
+    fn icon(&self) -> IconName {
+        IconName::MagnifyingGlass
+    }
+
+    fn input_schema(&self, format: LanguageModelToolSchemaFormat) -> serde_json::Value {
+        json_schema_for::<VaultSearchToolInput>(format)
+    }
+
+    fn ui_text(&self, input: &serde_json::Value) -> String {
+        match serde_json::from_value::<VaultSearchToolInput>(input.clone()) {
+            Ok(input) => format!(
+                "Search notes for {}",
+                MarkdownString::inline_code(&input.query)
+            ),
+            Err(_) => "Search notes".to_string(),
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        _messages: &[LanguageModelRequestMessage],
+        project: Entity<Project>,
+        _action_log: Entity<ActionLog>,
+        cx: &mut App,
+    ) -> Task<Result<String>> {
+        let input = match serde_json::from_value::<VaultSearchToolInput>(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let vault = match ProjectVault::for_project(&project, cx) {
+            Ok(vault) => vault,
+            Err(err) => return Task::ready(Err(err)),
+        };
+
+        let fs = project.read(cx).fs().clone();
+        let search = vault_search::search(
+            vault.root.clone(),
+            fs,
+            input.query.clone(),
+            input.limit.unwrap_or(10),
+            cx,
+        );
+        cx.background_spawn(async move {
+            let results = search.await?;
+            if results.is_empty() {
+                return Ok(format!("No notes matched {:?}.", input.query));
+            }
+
+            let mut output = String::new();
+            for result in results {
+                write!(
+                    output,
+                    "- {}:{}",
+                    vault.display_path(&result.path),
+                    result.row + 1
+                )?;
+                if !result.headings.is_empty() {
+                    write!(output, " ({})", result.headings.join(" > "))?;
+                }
+                writeln!(output, "\n  {}", result.preview)?;
+            }
+            Ok(output)
+        })
+    }
+}
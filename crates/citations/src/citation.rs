@@ -0,0 +1,269 @@
+use std::ops::Range;
+
+use collections::HashSet;
+use vault::code_ranges;
+
+use crate::library::Library;
+
+/// A pandoc-style citation like `[@smith2020]` or `[see @smith2020, p. 3;
+/// -@doe2021]`, as byte ranges into a note.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Citation {
+    /// The whole citation, including the brackets.
+    pub range: Range<usize>,
+    pub items: Vec<CitationItem>,
+}
+
+/// One of the references a citation cites.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CitationItem {
+    pub key: String,
+    /// The key, without the `@`.
+    pub key_range: Range<usize>,
+    /// The text before the key, like "see".
+    pub prefix: String,
+    /// The text after the key, like "p. 3".
+    pub locator: String,
+    /// Whether the key was written as `-@key`, which omits the author.
+    pub suppress_author: bool,
+}
+
+/// Returns the citations in `text`, skipping code.
+pub fn citations(text: &str) -> Vec<Citation> {
+    let code = code_ranges(text);
+    let mut citations = Vec::new();
+    let mut search_from = 0;
+    while let Some(open) = text[search_from..].find('[').map(|ix| ix + search_from) {
+        search_from = open + 1;
+        if code.iter().any(|range| range.contains(&open)) || text[..open].ends_with('\\') {
+            continue;
+        }
+        let Some(close) = text[open + 1..]
+            .find([']', '[', '\n'])
+            .map(|ix| ix + open + 1)
+            .filter(|close| text[*close..].starts_with(']'))
+        else {
+            continue;
+        };
+        // `[@key](url)` and `[@key]: url` are links, not citations.
+        if text[close + 1..].starts_with(['(', ':']) {
+            continue;
+        }
+        if let Some(items) = parse_items(text, open + 1..close) {
+            citations.push(Citation {
+                range: open..close + 1,
+                items,
+            });
+            search_from = close + 1;
+        }
+    }
+    citations
+}
+
+fn parse_items(text: &str, range: Range<usize>) -> Option<Vec<CitationItem>> {
+    let mut items = Vec::new();
+    let mut item_start = range.start;
+    for part in text[range].split(';') {
+        let part_start = item_start;
+        item_start += part.len() + 1;
+
+        let at = part
+            .match_indices('@')
+            .map(|(ix, _)| ix)
+            .find(|ix| part[..*ix].is_empty() || part[..*ix].ends_with([' ', '-']))?;
+        let key_len = key_len(&part[at + 1..]);
+        if key_len == 0 {
+            return None;
+        }
+        let suppress_author = part[..at].ends_with('-');
+        let prefix = part[..at].trim_end_matches('-').trim();
+        let locator = part[at + 1 + key_len..].trim_start_matches(',').trim();
+        let key_start = part_start + at + 1;
+        items.push(CitationItem {
+            key: part[at + 1..at + 1 + key_len].to_string(),
+            key_range: key_start..key_start + key_len,
+            prefix: prefix.to_string(),
+            locator: locator.to_string(),
+            suppress_author,
+        });
+    }
+    Some(items)
+}
+
+/// The length of the citation key at the start of `text`. Keys start with a
+/// letter, digit or underscore and may contain punctuation in the middle.
+fn key_len(text: &str) -> usize {
+    if !text.starts_with(|c: char| c.is_alphanumeric() || c == '_') {
+        return 0;
+    }
+    let len = text.find(|c: char| !is_key_char(c)).unwrap_or(text.len());
+    text[..len]
+        .trim_end_matches(|c: char| !c.is_alphanumeric() && c != '_')
+        .len()
+}
+
+fn is_key_char(c: char) -> bool {
+    c.is_alphanumeric() || "_-:.#$%&+?<>~/".contains(c)
+}
+
+/// Returns the citation key around `offset` in `text`, with its range.
+pub fn key_at_offset(text: &str, offset: usize) -> Option<(Range<usize>, String)> {
+    citations(text)
+        .into_iter()
+        .find(|citation| citation.range.contains(&offset))?
+        .items
+        .into_iter()
+        .find(|item| item.key_range.start <= offset + 1 && offset <= item.key_range.end)
+        .map(|item| (item.key_range, item.key))
+}
+
+/// If `line` ends in a citation key being typed after `[@` or `; @`, returns
+/// the offset where the key starts.
+pub fn key_query_start(line: &str) -> Option<usize> {
+    let query_len = line.len() - line.trim_end_matches(is_key_char).len();
+    let before_key = &line[..line.len() - query_len];
+    let before_at = before_key.strip_suffix('@')?;
+    let before_at = before_at.strip_suffix('-').unwrap_or(before_at);
+    let in_citation = before_at.ends_with('[')
+        || (before_at.trim_end().ends_with(';') && before_at.rfind('[') > before_at.rfind(']'));
+    in_citation.then_some(before_key.len())
+}
+
+/// Formats a citation in author-date style, like "(Smith 2020, p. 3)".
+/// Returns `None` if none of its keys are in the library.
+pub fn format_citation(citation: &Citation, library: &Library) -> Option<String> {
+    if citation
+        .items
+        .iter()
+        .all(|item| library.get(&item.key).is_none())
+    {
+        return None;
+    }
+    let items = citation
+        .items
+        .iter()
+        .map(|item| {
+            let mut text = item.prefix.clone();
+            if !text.is_empty() {
+                text.push(' ');
+            }
+            match library.get(&item.key) {
+                Some(reference) if item.suppress_author => text.push_str(reference.year_label()),
+                Some(reference) => text.push_str(&reference.short_label()),
+                None => text.push_str(&format!("@{}?", item.key)),
+            }
+            if !item.locator.is_empty() {
+                text.push_str(", ");
+                text.push_str(&item.locator);
+            }
+            text
+        })
+        .collect::<Vec<_>>();
+    Some(format!("({})", items.join("; ")))
+}
+
+/// Returns a markdown "References" section listing every reference cited in
+/// `text`, sorted by author and year, or `None` if nothing is cited.
+pub fn bibliography(text: &str, library: &Library) -> Option<String> {
+    let mut seen = HashSet::default();
+    let mut references = citations(text)
+        .into_iter()
+        .flat_map(|citation| citation.items)
+        .filter(|item| seen.insert(item.key.clone()))
+        .filter_map(|item| library.get(&item.key))
+        .collect::<Vec<_>>();
+    if references.is_empty() {
+        return None;
+    }
+    references.sort_by_cached_key(|reference| {
+        (
+            reference.author_label().to_lowercase(),
+            reference.year.clone(),
+        )
+    });
+    let mut section = String::from("## References\n");
+    for reference in references {
+        section.push('\n');
+        section.push_str(&reference.format());
+        section.push('\n');
+    }
+    Some(section)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_citations() {
+        let text = indoc! {"
+            As shown [see @smith2020, p. 3; -@doe2021]. Mail me@example.com.
+            Not [a citation] or `[@code]` or [@link](https://example.com) or \\[@escaped].
+            [@missing] and [@smith2020.]
+        "};
+        let citations = citations(text);
+        assert_eq!(
+            citations
+                .iter()
+                .map(|citation| &text[citation.range.clone()])
+                .collect::<Vec<_>>(),
+            [
+                "[see @smith2020, p. 3; -@doe2021]",
+                "[@missing]",
+                "[@smith2020.]"
+            ]
+        );
+        let items = &citations[0].items;
+        assert_eq!(&text[items[1].key_range.clone()], "doe2021");
+        assert_eq!(items[0].prefix, "see");
+        assert_eq!(items[0].locator, "p. 3");
+        assert!(items[1].suppress_author);
+        assert_eq!(citations[2].items[0].key, "smith2020");
+
+        let offset = text.find("doe2021").unwrap();
+        assert_eq!(
+            key_at_offset(text, offset + 2),
+            Some((offset..offset + 7, "doe2021".to_string()))
+        );
+        assert_eq!(key_at_offset(text, 0), None);
+
+        assert_eq!(key_query_start("See [@smi"), Some(6));
+        assert_eq!(key_query_start("See [@"), Some(6));
+        assert_eq!(key_query_start("[@a; @b"), Some(6));
+        assert_eq!(key_query_start("mail me@exa"), None);
+        assert_eq!(key_query_start("[x]; @b"), None);
+    }
+
+    #[test]
+    fn test_formatting() {
+        let library = Library::from_bibtex(indoc! {"
+            @article{smith2020, author = {Smith, John}, title = {Notes}, year = {2020}}
+            @book{doe2021, author = {Doe, Alex and Roe, Sam}, title = {Links}, year = {2021}}
+        "});
+        let text = "Cited [see @smith2020, p. 3; -@doe2021] and [@nope] and [@doe2021; @nope].";
+        let formatted = citations(text)
+            .iter()
+            .map(|citation| format_citation(citation, &library))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            formatted,
+            [
+                Some("(see Smith 2020, p. 3; 2021)".to_string()),
+                None,
+                Some("(Doe and Roe 2021; @nope?)".to_string()),
+            ]
+        );
+        assert_eq!(
+            bibliography(text, &library).unwrap(),
+            indoc! {"
+                ## References
+
+                Doe, A., & Roe, S. (2021). *Links*.
+
+                Smith, J. (2020). *Notes*.
+            "}
+        );
+    }
+}
@@ -0,0 +1,300 @@
+use std::cell::RefCell;
+use std::ops::Range;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use anyhow::Result;
+use editor::{
+    CompletionContext, CompletionProvider, Editor, EditorMode, ExcerptId, GotoDefinitionKind,
+    SemanticsProvider,
+};
+use gpui::{App, Context, Entity, Task, Window};
+use language::{Anchor, Buffer, CodeLabel, Point, ToOffset as _, ToPoint as _};
+use lsp::LanguageServerId;
+use project::{
+    Completion, CompletionSource, DocumentHighlight, Hover, HoverBlock, HoverBlockKind, InlayHint,
+    LocationLink, Project, ProjectTransaction, lsp_store::CompletionDocumentation,
+};
+
+use crate::library_for_project;
+use crate::{Library, key_at_offset, key_query_start};
+
+/// Adds citation completions and hovers to editors of notes in a project.
+pub(crate) fn register(editor: &mut Editor, _: Option<&mut Window>, cx: &mut Context<Editor>) {
+    if editor.mode() != EditorMode::Full {
+        return;
+    }
+    let Some(project) = editor.project.clone() else {
+        return;
+    };
+    // Start loading the library so it's ready by the time it's needed.
+    library_for_project(&project, cx);
+
+    editor.set_completion_provider(Some(Box::new(CitationCompletionProvider {
+        project: project.clone(),
+        fallback: Box::new(project.clone()),
+    })));
+    if let Some(fallback) = editor.semantics_provider() {
+        editor.set_semantics_provider(Some(Rc::new(CitationSemanticsProvider {
+            project,
+            fallback,
+        })));
+    }
+}
+
+fn is_note(buffer: &Buffer) -> bool {
+    buffer
+        .language()
+        .is_some_and(|language| language.name().as_ref() == "Markdown")
+}
+
+fn library(
+    project: &Entity<Project>,
+    buffer: &Entity<Buffer>,
+    cx: &mut App,
+) -> Option<Arc<Library>> {
+    if !is_note(buffer.read(cx)) {
+        return None;
+    }
+    library_for_project(project, cx)?.read(cx).library()
+}
+
+/// The line of `buffer` that `position` is on, and the position's column.
+fn line_at(buffer: &Buffer, position: Anchor) -> (Point, String, usize) {
+    let point = position.to_point(buffer);
+    let line_start = Point::new(point.row, 0);
+    let line_end = Point::new(point.row, buffer.line_len(point.row));
+    let line = buffer
+        .text_for_range(line_start..line_end)
+        .collect::<String>();
+    let column = position.to_offset(buffer) - line_start.to_offset(buffer);
+    (line_start, line, column)
+}
+
+/// Completes citation keys after `[@` and `; @` in notes, and otherwise
+/// falls back to the project's completions.
+struct CitationCompletionProvider {
+    project: Entity<Project>,
+    fallback: Box<dyn CompletionProvider>,
+}
+
+impl CitationCompletionProvider {
+    fn citation_completions(
+        &self,
+        buffer: &Entity<Buffer>,
+        position: Anchor,
+        cx: &mut Context<Editor>,
+    ) -> Option<Vec<Completion>> {
+        let library = library(&self.project, buffer, cx)?;
+        let buffer = buffer.read(cx);
+        let (line_start, line, column) = line_at(buffer, position);
+        let key_start = key_query_start(&line[..column])?;
+        let key_start = line_start.to_offset(buffer) + key_start;
+        let old_range = buffer.anchor_before(key_start)..buffer.anchor_after(position);
+
+        let completions = library
+            .references()
+            .iter()
+            .map(|reference| {
+                let label = format!(
+                    "{} {} ({})",
+                    reference.key,
+                    reference.author_label(),
+                    reference.year_label()
+                );
+                Completion {
+                    old_range: old_range.clone(),
+                    new_text: reference.key.clone(),
+                    label: CodeLabel::plain(label, None),
+                    documentation: reference
+                        .title
+                        .clone()
+                        .map(|title| CompletionDocumentation::SingleLine(title.into())),
+                    source: CompletionSource::Custom,
+                    icon_path: None,
+                    confirm: None,
+                }
+            })
+            .collect();
+        Some(completions)
+    }
+}
+
+impl CompletionProvider for CitationCompletionProvider {
+    fn completions(
+        &self,
+        excerpt_id: ExcerptId,
+        buffer: &Entity<Buffer>,
+        buffer_position: Anchor,
+        trigger: CompletionContext,
+        window: &mut Window,
+        cx: &mut Context<Editor>,
+    ) -> Task<Result<Option<Vec<Completion>>>> {
+        if let Some(completions) = self.citation_completions(buffer, buffer_position, cx) {
+            return Task::ready(Ok(Some(completions)));
+        }
+        self.fallback
+            .completions(excerpt_id, buffer, buffer_position, trigger, window, cx)
+    }
+
+    fn resolve_completions(
+        &self,
+        buffer: Entity<Buffer>,
+        completion_indices: Vec<usize>,
+        completions: Rc<RefCell<Box<[Completion]>>>,
+        cx: &mut Context<Editor>,
+    ) -> Task<Result<bool>> {
+        self.fallback
+            .resolve_completions(buffer, completion_indices, completions, cx)
+    }
+
+    fn apply_additional_edits_for_completion(
+        &self,
+        buffer: Entity<Buffer>,
+        completions: Rc<RefCell<Box<[Completion]>>>,
+        completion_index: usize,
+        push_to_history: bool,
+        cx: &mut Context<Editor>,
+    ) -> Task<Result<Option<language::Transaction>>> {
+        self.fallback.apply_additional_edits_for_completion(
+            buffer,
+            completions,
+            completion_index,
+            push_to_history,
+            cx,
+        )
+    }
+
+    fn is_completion_trigger(
+        &self,
+        buffer: &Entity<Buffer>,
+        position: Anchor,
+        text: &str,
+        trigger_in_words: bool,
+        cx: &mut Context<Editor>,
+    ) -> bool {
+        if text == "@" && is_note(buffer.read(cx)) {
+            let (_, line, column) = line_at(buffer.read(cx), position);
+            if key_query_start(&line[..column]).is_some() {
+                return true;
+            }
+        }
+        self.fallback
+            .is_completion_trigger(buffer, position, text, trigger_in_words, cx)
+    }
+}
+
+/// Shows the full reference when hovering a citation key in a note, on top
+/// of whatever the project shows.
+struct CitationSemanticsProvider {
+    project: Entity<Project>,
+    fallback: Rc<dyn SemanticsProvider>,
+}
+
+impl CitationSemanticsProvider {
+    fn citation_hover(
+        &self,
+        buffer: &Entity<Buffer>,
+        position: Anchor,
+        cx: &mut App,
+    ) -> Option<Hover> {
+        let library = library(&self.project, buffer, cx)?;
+        let buffer = buffer.read(cx);
+        let (line_start, line, column) = line_at(buffer, position);
+        let (key_range, key) = key_at_offset(&line, column)?;
+        let reference = library.get(&key)?;
+        let line_start = line_start.to_offset(buffer);
+        let range: Range<usize> = line_start + key_range.start..line_start + key_range.end;
+        Some(Hover {
+            contents: vec![HoverBlock {
+                text: reference.format(),
+                kind: HoverBlockKind::Markdown,
+            }],
+            range: Some(buffer.anchor_before(range.start)..buffer.anchor_after(range.end)),
+            language: None,
+        })
+    }
+}
+
+impl SemanticsProvider for CitationSemanticsProvider {
+    fn hover(
+        &self,
+        buffer: &Entity<Buffer>,
+        position: Anchor,
+        cx: &mut App,
+    ) -> Option<Task<Vec<Hover>>> {
+        let fallback = self.fallback.hover(buffer, position, cx);
+        let Some(hover) = self.citation_hover(buffer, position, cx) else {
+            return fallback;
+        };
+        Some(cx.background_spawn(async move {
+            let mut hovers = vec![hover];
+            if let Some(fallback) = fallback {
+                hovers.extend(fallback.await);
+            }
+            hovers
+        }))
+    }
+
+    fn inlay_hints(
+        &self,
+        buffer_handle: Entity<Buffer>,
+        range: Range<Anchor>,
+        cx: &mut App,
+    ) -> Option<Task<Result<Vec<InlayHint>>>> {
+        self.fallback.inlay_hints(buffer_handle, range, cx)
+    }
+
+    fn resolve_inlay_hint(
+        &self,
+        hint: InlayHint,
+        buffer_handle: Entity<Buffer>,
+        server_id: LanguageServerId,
+        cx: &mut App,
+    ) -> Option<Task<Result<InlayHint>>> {
+        self.fallback
+            .resolve_inlay_hint(hint, buffer_handle, server_id, cx)
+    }
+
+    fn supports_inlay_hints(&self, buffer: &Entity<Buffer>, cx: &mut App) -> bool {
+        self.fallback.supports_inlay_hints(buffer, cx)
+    }
+
+    fn document_highlights(
+        &self,
+        buffer: &Entity<Buffer>,
+        position: Anchor,
+        cx: &mut App,
+    ) -> Option<Task<Result<Vec<DocumentHighlight>>>> {
+        self.fallback.document_highlights(buffer, position, cx)
+    }
+
+    fn definitions(
+        &self,
+        buffer: &Entity<Buffer>,
+        position: Anchor,
+        kind: GotoDefinitionKind,
+        cx: &mut App,
+    ) -> Option<Task<Result<Vec<LocationLink>>>> {
+        self.fallback.definitions(buffer, position, kind, cx)
+    }
+
+    fn range_for_rename(
+        &self,
+        buffer: &Entity<Buffer>,
+        position: Anchor,
+        cx: &mut App,
+    ) -> Option<Task<Result<Option<Range<Anchor>>>>> {
+        self.fallback.range_for_rename(buffer, position, cx)
+    }
+
+    fn perform_rename(
+        &self,
+        buffer: &Entity<Buffer>,
+        position: Anchor,
+        new_name: String,
+        cx: &mut App,
+    ) -> Option<Task<Result<ProjectTransaction>>> {
+        self.fallback.perform_rename(buffer, position, new_name, cx)
+    }
+}
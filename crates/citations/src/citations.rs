@@ -0,0 +1,420 @@
+//! Citations from a local BibTeX or CSL-JSON library: citekey completion and
+//! hovers in notes, formatted citations for the markdown preview and
+//! literature notes created from a template. Everything is parsed locally.
+
+mod citation;
+mod citation_picker;
+mod citation_provider;
+mod library;
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context as _, Result, anyhow};
+use collections::HashMap;
+use editor::{Editor, ToOffset as _, ToPoint as _};
+use fs::Fs;
+use futures::StreamExt as _;
+use gpui::{App, AppContext as _, Context, Entity, Global, Task, Window, actions};
+use language::Point;
+use project::Project;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+use vault::{NOTE_EXTENSION, sanitize_file_name};
+use workspace::{OpenOptions, Workspace, notifications::NotifyTaskExt as _};
+
+pub use citation::{
+    Citation, CitationItem, bibliography, citations, format_citation, key_at_offset,
+    key_query_start,
+};
+pub use citation_picker::CitationPicker;
+pub use library::{Library, Person, Reference};
+
+actions!(citations, [NewLiteratureNote]);
+
+const LIBRARY_WATCH_LATENCY: Duration = Duration::from_millis(100);
+
+const DEFAULT_LITERATURE_NOTE_TEMPLATE: &str = "---
+citekey: {{citekey}}
+year: {{year}}
+---
+# {{title}}
+
+{{reference}}
+
+## Notes
+
+";
+
+/// Settings for citing references from a bibliography library.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct CitationSettings {
+    /// The BibTeX (`.bib`) or CSL-JSON (`.json`) file to cite references
+    /// from, absolute or relative to the vault root.
+    ///
+    /// Default: null
+    pub library: Option<String>,
+    /// The folder, relative to the vault root, that literature notes are
+    /// created in.
+    ///
+    /// Default: "References"
+    pub literature_note_folder: Option<String>,
+    /// The note, relative to the vault root, used as the template for new
+    /// literature notes. `{{citekey}}`, `{{title}}`, `{{authors}}`,
+    /// `{{year}}`, `{{container}}`, `{{publisher}}`, `{{doi}}`, `{{url}}` and
+    /// `{{reference}}` are replaced with the reference's details.
+    ///
+    /// Default: null
+    pub literature_note_template: Option<String>,
+}
+
+impl settings::Settings for CitationSettings {
+    const KEY: Option<&'static str> = Some("citations");
+
+    type FileContent = Self;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _: &mut App) -> Result<Self> {
+        sources.json_merge()
+    }
+}
+
+/// A library file, reloaded whenever it changes on disk.
+pub struct CitationLibrary {
+    path: Arc<Path>,
+    library: Option<Arc<Library>>,
+    _load: Task<()>,
+}
+
+impl CitationLibrary {
+    fn new(path: Arc<Path>, fs: Arc<dyn Fs>, cx: &mut Context<Self>) -> Self {
+        let load = cx.spawn({
+            let path = path.clone();
+            async move |this, cx| {
+                let (mut events, _watcher) = fs.watch(&path, LIBRARY_WATCH_LATENCY).await;
+                loop {
+                    let library = match fs.load(&path).await {
+                        Ok(text) => {
+                            let path = path.clone();
+                            cx.background_spawn(async move { Library::parse(&path, &text) })
+                                .await
+                        }
+                        Err(error) => Err(error),
+                    };
+                    let updated = this.update(cx, |this, cx| match library {
+                        Ok(library) => {
+                            this.library = Some(Arc::new(library));
+                            cx.notify();
+                        }
+                        Err(error) => {
+                            log::error!("failed to load citation library {path:?}: {error:#}")
+                        }
+                    });
+                    if updated.is_err() || events.next().await.is_none() {
+                        break;
+                    }
+                }
+            }
+        });
+        Self {
+            path,
+            library: None,
+            _load: load,
+        }
+    }
+
+    pub fn path(&self) -> &Arc<Path> {
+        &self.path
+    }
+
+    /// The parsed library, or `None` until it has loaded.
+    pub fn library(&self) -> Option<Arc<Library>> {
+        self.library.clone()
+    }
+}
+
+/// The libraries that have been loaded so far, by path.
+#[derive(Default)]
+struct CitationLibraries {
+    libraries: HashMap<Arc<Path>, Entity<CitationLibrary>>,
+}
+
+impl Global for CitationLibraries {}
+
+pub fn init(cx: &mut App) {
+    CitationSettings::register(cx);
+
+    cx.observe_new(|workspace: &mut Workspace, _window, _cx| {
+        workspace.register_action(new_literature_note);
+    })
+    .detach();
+
+    cx.observe_new(citation_provider::register).detach();
+}
+
+/// Returns the library configured for the vault open in `project`, starting
+/// to load it on first use.
+pub fn library_for_project(
+    project: &Entity<Project>,
+    cx: &mut App,
+) -> Option<Entity<CitationLibrary>> {
+    let library = CitationSettings::get_global(cx).library.as_deref()?;
+    let project = project.read(cx);
+    let path: Arc<Path> = if Path::new(library).is_absolute() {
+        Path::new(library).into()
+    } else {
+        let vault_root = project.visible_worktrees(cx).next()?.read(cx).abs_path();
+        vault_root.join(library).into()
+    };
+    if let Some(library) = cx
+        .try_global::<CitationLibraries>()
+        .and_then(|libraries| libraries.libraries.get(&path))
+    {
+        return Some(library.clone());
+    }
+
+    let fs = project.fs().clone();
+    let library = cx.new(|cx| CitationLibrary::new(path.clone(), fs, cx));
+    cx.default_global::<CitationLibraries>()
+        .libraries
+        .insert(path, library.clone());
+    Some(library)
+}
+
+/// Creates a literature note for the reference cited under the cursor, or for
+/// one picked from the library, and opens it.
+fn new_literature_note(
+    workspace: &mut Workspace,
+    _: &NewLiteratureNote,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let Some(library) = library_for_project(workspace.project(), cx) else {
+        workspace.show_error(
+            &anyhow!("Set `citations.library` to a BibTeX or CSL-JSON file to cite from"),
+            cx,
+        );
+        return;
+    };
+    let Some(library) = library.read(cx).library() else {
+        workspace.show_error(&anyhow!("The citation library hasn't loaded yet"), cx);
+        return;
+    };
+
+    let cited_reference = workspace
+        .active_item(cx)
+        .and_then(|item| item.act_as::<Editor>(cx))
+        .and_then(|editor| {
+            let editor = editor.read(cx);
+            let buffer = editor.buffer().read(cx).snapshot(cx);
+            let head = editor.selections.newest_anchor().head();
+            let row = head.to_point(&buffer).row;
+            let line_start = Point::new(row, 0);
+            let line_end = Point::new(row + 1, 0).min(buffer.max_point());
+            let line = buffer
+                .text_for_range(line_start..line_end)
+                .collect::<String>();
+            let column = head.to_offset(&buffer) - buffer.point_to_offset(line_start);
+            let (_, key) = key_at_offset(&line, column)?;
+            library.get(&key).cloned()
+        });
+    if let Some(reference) = cited_reference {
+        open_literature_note(workspace, reference, window, cx);
+        return;
+    }
+
+    let workspace_handle = cx.entity().downgrade();
+    workspace.toggle_modal(window, cx, |window, cx| {
+        CitationPicker::new(library, workspace_handle, window, cx)
+    });
+}
+
+/// Opens the literature note for `reference`, creating it from the template
+/// if it doesn't exist yet.
+pub fn open_literature_note(
+    workspace: &mut Workspace,
+    reference: Reference,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let Some(vault_root) = workspace
+        .visible_worktrees(cx)
+        .next()
+        .map(|worktree| worktree.read(cx).abs_path().to_path_buf())
+    else {
+        workspace.show_error(
+            &anyhow!("Open a vault folder to create literature notes in"),
+            cx,
+        );
+        return;
+    };
+    let fs = workspace.app_state().fs.clone();
+    let settings = CitationSettings::get_global(cx).clone();
+
+    cx.spawn_in(window, async move |workspace, cx| {
+        let note_path =
+            create_literature_note(&reference, &vault_root, &settings, fs.as_ref()).await?;
+        workspace
+            .update_in(cx, |workspace, window, cx| {
+                workspace.open_abs_path(note_path, OpenOptions::default(), window, cx)
+            })?
+            .await?;
+        anyhow::Ok(())
+    })
+    .detach_and_notify_err(window, cx);
+}
+
+/// Returns the path of the literature note for `reference` in the vault at
+/// `vault_root`, first creating it from the template if needed.
+pub async fn create_literature_note(
+    reference: &Reference,
+    vault_root: &Path,
+    settings: &CitationSettings,
+    fs: &dyn Fs,
+) -> Result<PathBuf> {
+    let folder = vault_root.join(
+        settings
+            .literature_note_folder
+            .as_deref()
+            .unwrap_or("References"),
+    );
+    let note_path = folder.join(format!(
+        "{}.{NOTE_EXTENSION}",
+        sanitize_file_name(&format!("@{}", reference.key))
+    ));
+    if fs.is_file(&note_path).await {
+        return Ok(note_path);
+    }
+
+    let template = match &settings.literature_note_template {
+        Some(template) => {
+            let template_path = vault_root.join(template);
+            fs.load(&template_path)
+                .await
+                .with_context(|| format!("loading literature note template {template_path:?}"))?
+        }
+        None => DEFAULT_LITERATURE_NOTE_TEMPLATE.to_string(),
+    };
+    fs.create_dir(&folder).await?;
+    fs.atomic_write(note_path.clone(), render_template(&template, reference))
+        .await?;
+    Ok(note_path)
+}
+
+/// Replaces the `{{placeholders}}` in a literature note template, leaving
+/// unknown ones as they are.
+fn render_template(template: &str, reference: &Reference) -> String {
+    let authors = reference
+        .authors
+        .iter()
+        .map(|person| match &person.given {
+            Some(given) => format!("{given} {}", person.family),
+            None => person.family.clone(),
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    let optional = |value: &Option<String>| value.clone().unwrap_or_default();
+    let placeholders = [
+        ("citekey", reference.key.clone()),
+        ("title", optional(&reference.title)),
+        ("authors", authors),
+        ("year", optional(&reference.year)),
+        ("container", optional(&reference.container)),
+        ("publisher", optional(&reference.publisher)),
+        ("doi", optional(&reference.doi)),
+        ("url", optional(&reference.url)),
+        ("reference", reference.format()),
+    ];
+
+    let mut note = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        note.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find("}}").map(|end| start + end) else {
+            break;
+        };
+        let name = rest[start + 2..end].trim();
+        match placeholders
+            .iter()
+            .find(|(placeholder, _)| *placeholder == name)
+        {
+            Some((_, value)) => note.push_str(value),
+            None => note.push_str(&rest[start..end + 2]),
+        }
+        rest = &rest[end + 2..];
+    }
+    note.push_str(rest);
+    note
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    #[gpui::test]
+    async fn test_create_literature_note(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/vault",
+            json!({
+                "templates": {
+                    "literature.md": "# {{ title }} ({{year}})\n{{authors}} {{unknown}}\n",
+                },
+            }),
+        )
+        .await;
+        let library = Library::from_bibtex(
+            "@book{smith2020, author = {Smith, Jo and Doe, Al}, title = {Notes}, year = 2020}",
+        );
+        let reference = library.get("smith2020").unwrap();
+
+        let settings = CitationSettings::default();
+        let path = create_literature_note(reference, Path::new("/vault"), &settings, fs.as_ref())
+            .await
+            .unwrap();
+        assert_eq!(path, Path::new("/vault/References/@smith2020.md"));
+        assert_eq!(
+            fs.load(&path).await.unwrap(),
+            indoc! {"
+                ---
+                citekey: smith2020
+                year: 2020
+                ---
+                # Notes
+
+                Smith, J., & Doe, A. (2020). *Notes*.
+
+                ## Notes
+
+            "}
+        );
+
+        // Existing notes are opened rather than overwritten.
+        fs.atomic_write(path.clone(), "edited".into())
+            .await
+            .unwrap();
+        let settings = CitationSettings {
+            literature_note_template: Some("templates/literature.md".into()),
+            ..Default::default()
+        };
+        let path = create_literature_note(reference, Path::new("/vault"), &settings, fs.as_ref())
+            .await
+            .unwrap();
+        assert_eq!(fs.load(&path).await.unwrap(), "edited");
+
+        fs.remove_file(&path, Default::default()).await.unwrap();
+        create_literature_note(reference, Path::new("/vault"), &settings, fs.as_ref())
+            .await
+            .unwrap();
+        assert_eq!(
+            fs.load(&path).await.unwrap(),
+            "# Notes (2020)\nJo Smith, Al Doe {{unknown}}\n"
+        );
+    }
+}
@@ -0,0 +1,699 @@
+use std::path::Path;
+
+use anyhow::{Context as _, Result, anyhow};
+use collections::HashMap;
+use serde_json::Value;
+
+/// A person who authored or edited a reference.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Person {
+    /// The family name, or the whole name of an organization.
+    pub family: String,
+    pub given: Option<String>,
+}
+
+/// An entry of a bibliography library.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Reference {
+    /// The key notes cite the reference with, as in `[@key]`.
+    pub key: String,
+    /// The entry type, such as `article` or `book`.
+    pub kind: String,
+    pub authors: Vec<Person>,
+    pub editors: Vec<Person>,
+    pub title: Option<String>,
+    pub year: Option<String>,
+    /// The journal, book or proceedings the reference was published in.
+    pub container: Option<String>,
+    pub publisher: Option<String>,
+    pub volume: Option<String>,
+    pub issue: Option<String>,
+    pub pages: Option<String>,
+    pub doi: Option<String>,
+    pub url: Option<String>,
+}
+
+/// The references of a BibTeX or CSL-JSON file.
+#[derive(Clone, Debug, Default)]
+pub struct Library {
+    references: Vec<Reference>,
+    by_key: HashMap<String, usize>,
+}
+
+impl Library {
+    /// Parses the library at `path`, which is read as CSL-JSON if it has a
+    /// `.json` extension and as BibTeX otherwise.
+    pub fn parse(path: &Path, text: &str) -> Result<Self> {
+        let is_json = path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("json"));
+        if is_json {
+            Self::from_csl_json(text).with_context(|| format!("parsing {path:?} as CSL-JSON"))
+        } else {
+            Ok(Self::from_bibtex(text))
+        }
+    }
+
+    /// Parses a BibTeX file. Malformed entries are skipped rather than
+    /// failing the whole library, as reference managers often export a few.
+    pub fn from_bibtex(text: &str) -> Self {
+        Self::new(BibtexParser::new(text).parse())
+    }
+
+    /// Parses a CSL-JSON file, which is an array of items.
+    pub fn from_csl_json(text: &str) -> Result<Self> {
+        let items: Vec<Value> = serde_json::from_str(text)?;
+        let references = items
+            .iter()
+            .map(csl_reference)
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self::new(references))
+    }
+
+    fn new(references: Vec<Reference>) -> Self {
+        let mut by_key = HashMap::default();
+        for (ix, reference) in references.iter().enumerate() {
+            by_key.entry(reference.key.clone()).or_insert(ix);
+        }
+        Self { references, by_key }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Reference> {
+        self.by_key.get(key).map(|ix| &self.references[*ix])
+    }
+
+    pub fn references(&self) -> &[Reference] {
+        &self.references
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.references.is_empty()
+    }
+}
+
+impl Reference {
+    /// The authors as they're named in an author-date citation: "Smith",
+    /// "Smith and Jones" or "Smith et al.".
+    pub fn author_label(&self) -> String {
+        let people = if self.authors.is_empty() {
+            &self.editors
+        } else {
+            &self.authors
+        };
+        match people.as_slice() {
+            [] => self.title.clone().unwrap_or_else(|| self.key.clone()),
+            [person] => person.family.clone(),
+            [first, second] => format!("{} and {}", first.family, second.family),
+            [first, ..] => format!("{} et al.", first.family),
+        }
+    }
+
+    /// The year, or "n.d." for undated references.
+    pub fn year_label(&self) -> &str {
+        self.year.as_deref().unwrap_or("n.d.")
+    }
+
+    /// The author-date label of the reference, like "Smith 2020".
+    pub fn short_label(&self) -> String {
+        format!("{} {}", self.author_label(), self.year_label())
+    }
+
+    /// The full reference as a markdown bibliography entry, roughly in APA
+    /// style.
+    pub fn format(&self) -> String {
+        let mut entry = String::new();
+        let people = if self.authors.is_empty() {
+            &self.editors
+        } else {
+            &self.authors
+        };
+        if !people.is_empty() {
+            entry.push_str(&format_people(people));
+            if self.authors.is_empty() {
+                entry.push_str(if people.len() == 1 {
+                    " (Ed.)"
+                } else {
+                    " (Eds.)"
+                });
+            }
+            push_sentence_end(&mut entry);
+            entry.push(' ');
+        }
+        entry.push_str(&format!("({}).", self.year_label()));
+
+        if let Some(title) = &self.title {
+            entry.push(' ');
+            if self.container.is_some() {
+                entry.push_str(title);
+            } else {
+                entry.push_str(&format!("*{title}*"));
+            }
+            push_sentence_end(&mut entry);
+        }
+
+        if let Some(container) = &self.container {
+            entry.push_str(&format!(" *{container}*"));
+            if let Some(volume) = &self.volume {
+                entry.push_str(&format!(", *{volume}*"));
+                if let Some(issue) = &self.issue {
+                    entry.push_str(&format!("({issue})"));
+                }
+            }
+            if let Some(pages) = &self.pages {
+                entry.push_str(&format!(", {}", pages.replace("--", "–")));
+            }
+            entry.push('.');
+        }
+
+        if let Some(publisher) = &self.publisher {
+            entry.push_str(&format!(" {publisher}"));
+            push_sentence_end(&mut entry);
+        }
+
+        if let Some(doi) = &self.doi {
+            let doi = doi
+                .trim_start_matches("https://doi.org/")
+                .trim_start_matches("doi:");
+            entry.push_str(&format!(" https://doi.org/{doi}"));
+        } else if let Some(url) = &self.url {
+            entry.push_str(&format!(" {url}"));
+        }
+        entry
+    }
+}
+
+impl Person {
+    /// The name as "Family, G. H.".
+    fn format_initialed(&self) -> String {
+        let Some(given) = &self.given else {
+            return self.family.clone();
+        };
+        let initials = given
+            .split(|c: char| c.is_whitespace() || c == '.')
+            .filter_map(|name| name.chars().next())
+            .map(|initial| format!("{initial}."))
+            .collect::<Vec<_>>()
+            .join(" ");
+        format!("{}, {initials}", self.family)
+    }
+}
+
+fn format_people(people: &[Person]) -> String {
+    let names = people
+        .iter()
+        .map(Person::format_initialed)
+        .collect::<Vec<_>>();
+    match names.as_slice() {
+        [] => String::new(),
+        [name] => name.clone(),
+        [rest @ .., last] => format!("{}, & {last}", rest.join(", ")),
+    }
+}
+
+fn push_sentence_end(text: &mut String) {
+    if !text.ends_with(['.', '?', '!']) {
+        text.push('.');
+    }
+}
+
+struct BibtexParser<'a> {
+    text: &'a str,
+    offset: usize,
+    /// The abbreviations defined with `@string`.
+    strings: HashMap<String, String>,
+}
+
+impl<'a> BibtexParser<'a> {
+    fn new(text: &'a str) -> Self {
+        let strings = [
+            "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+        ]
+        .iter()
+        .enumerate()
+        .map(|(ix, month)| (month.to_string(), (ix + 1).to_string()))
+        .collect();
+        Self {
+            text,
+            offset: 0,
+            strings,
+        }
+    }
+
+    fn parse(mut self) -> Vec<Reference> {
+        let mut references = Vec::new();
+        while let Some(at) = self.text[self.offset..].find('@') {
+            self.offset += at + 1;
+            let entry_start = self.offset;
+            match self.parse_entry() {
+                Some(Some(reference)) => references.push(reference),
+                Some(None) => {}
+                None => {
+                    log::debug!("skipping malformed BibTeX entry at byte {entry_start}");
+                    self.offset = entry_start;
+                }
+            }
+        }
+        references
+    }
+
+    /// Parses the entry after an `@`, returning `Some(None)` for entries that
+    /// aren't references and `None` if it's malformed.
+    fn parse_entry(&mut self) -> Option<Option<Reference>> {
+        let kind = self.take_while(|c| c.is_ascii_alphanumeric() || c == '_')?;
+        let kind = kind.to_ascii_lowercase();
+        self.skip_whitespace();
+        let close = match self.next_char()? {
+            '{' => '}',
+            '(' => ')',
+            _ => return None,
+        };
+
+        match kind.as_str() {
+            "comment" | "preamble" => {
+                self.offset -= 1;
+                self.take_delimited()?;
+                return Some(None);
+            }
+            "string" => {
+                let (name, value) = self.parse_field()?;
+                self.skip_whitespace();
+                (self.next_char()? == close).then_some(())?;
+                self.strings.insert(name, value);
+                return Some(None);
+            }
+            _ => {}
+        }
+
+        let key = self.take_while(|c| c != ',' && c != close && !c.is_whitespace())?;
+        let mut reference = Reference {
+            key: key.to_string(),
+            kind,
+            ..Default::default()
+        };
+        let mut fields = HashMap::default();
+        loop {
+            self.skip_whitespace();
+            match self.peek_char()? {
+                ',' => {
+                    self.offset += 1;
+                }
+                c if c == close => {
+                    self.offset += 1;
+                    break;
+                }
+                _ => {
+                    let (name, value) = self.parse_field()?;
+                    fields.insert(name, value);
+                }
+            }
+        }
+
+        reference.authors = take_people(&mut fields, "author");
+        reference.editors = take_people(&mut fields, "editor");
+        let mut take = |name: &str| {
+            fields
+                .remove(name)
+                .map(|value| clean_latex(&value))
+                .filter(|value| !value.is_empty())
+        };
+        reference.title = take("title");
+        reference.year =
+            take("year").or_else(|| take("date").map(|date| date.chars().take(4).collect()));
+        reference.container = take("journal")
+            .or_else(|| take("journaltitle"))
+            .or_else(|| take("booktitle"));
+        reference.publisher = take("publisher").or_else(|| take("institution"));
+        reference.volume = take("volume");
+        reference.issue = take("number").or_else(|| take("issue"));
+        reference.pages = take("pages");
+        reference.doi = take("doi");
+        reference.url = take("url");
+        Some(Some(reference))
+    }
+
+    /// Parses `name = value # value ...`, returning the lowercase name and the
+    /// concatenated raw value.
+    fn parse_field(&mut self) -> Option<(String, String)> {
+        self.skip_whitespace();
+        let name = self.take_while(|c| c.is_ascii_alphanumeric() || "_-:.".contains(c))?;
+        let name = name.to_ascii_lowercase();
+        self.skip_whitespace();
+        (self.next_char()? == '=').then_some(())?;
+
+        let mut value = String::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek_char()? {
+                '{' => value.push_str(self.take_delimited()?),
+                '"' => {
+                    self.offset += 1;
+                    let start = self.offset;
+                    let mut depth = 0;
+                    loop {
+                        match self.next_char()? {
+                            '{' => depth += 1,
+                            '}' => depth -= 1,
+                            '"' if depth == 0 => break,
+                            _ => {}
+                        }
+                    }
+                    value.push_str(&self.text[start..self.offset - 1]);
+                }
+                _ => {
+                    let word =
+                        self.take_while(|c| c.is_ascii_alphanumeric() || "_-:.".contains(c))?;
+                    let expansion = self.strings.get(&word.to_ascii_lowercase());
+                    value.push_str(expansion.map_or(word, |expansion| expansion.as_str()));
+                }
+            }
+            self.skip_whitespace();
+            if self.peek_char() == Some('#') {
+                self.offset += 1;
+            } else {
+                break;
+            }
+        }
+        Some((name, value))
+    }
+
+    /// Takes the contents of the balanced braces at the current offset.
+    fn take_delimited(&mut self) -> Option<&'a str> {
+        let (open, close) = match self.next_char()? {
+            '{' => ('{', '}'),
+            '(' => ('(', ')'),
+            _ => return None,
+        };
+        let start = self.offset;
+        let mut depth = 1;
+        while depth > 0 {
+            match self.next_char()? {
+                c if c == open => depth += 1,
+                c if c == close => depth -= 1,
+                _ => {}
+            }
+        }
+        Some(&self.text[start..self.offset - 1])
+    }
+
+    fn take_while(&mut self, predicate: impl Fn(char) -> bool) -> Option<&'a str> {
+        let rest = &self.text[self.offset..];
+        let len = rest.find(|c| !predicate(c)).unwrap_or(rest.len());
+        self.offset += len;
+        (len > 0).then(|| &rest[..len])
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.take_while(char::is_whitespace);
+    }
+
+    fn peek_char(&self) -> Option<char> {
+        self.text[self.offset..].chars().next()
+    }
+
+    fn next_char(&mut self) -> Option<char> {
+        let c = self.peek_char()?;
+        self.offset += c.len_utf8();
+        Some(c)
+    }
+}
+
+fn take_people(fields: &mut HashMap<String, String>, name: &str) -> Vec<Person> {
+    fields
+        .remove(name)
+        .map(|names| parse_bibtex_names(&names))
+        .unwrap_or_default()
+}
+
+/// Parses a BibTeX name list like `Smith, John and {World Health
+/// Organization} and Jane van der Berg`.
+fn parse_bibtex_names(names: &str) -> Vec<Person> {
+    split_top_level(names, " and ")
+        .into_iter()
+        .map(str::trim)
+        .filter(|name| !name.is_empty() && *name != "others")
+        .map(|name| {
+            if name.starts_with('{') && name.ends_with('}') && !name[1..].contains('{') {
+                return Person {
+                    family: clean_latex(name),
+                    given: None,
+                };
+            }
+            let parts = split_top_level(name, ",");
+            if parts.len() > 1 {
+                let given = clean_latex(parts[parts.len() - 1]);
+                return Person {
+                    family: clean_latex(parts[0]),
+                    given: (!given.is_empty()).then_some(given),
+                };
+            }
+
+            // "First von Last": the family name starts at the first
+            // lowercase word, or is the last word.
+            let words = split_top_level(name, " ");
+            let words = words
+                .into_iter()
+                .filter(|word| !word.is_empty())
+                .collect::<Vec<_>>();
+            let last = words.len() - 1;
+            let family_start = words[..last]
+                .iter()
+                .position(|word| word.starts_with(|c: char| c.is_lowercase()))
+                .unwrap_or(last);
+            let given = clean_latex(&words[..family_start].join(" "));
+            Person {
+                family: clean_latex(&words[family_start..].join(" ")),
+                given: (!given.is_empty()).then_some(given),
+            }
+        })
+        .collect()
+}
+
+/// Splits `text` at `separator`s that aren't inside braces.
+fn split_top_level<'a>(text: &'a str, separator: &str) -> Vec<&'a str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    let mut ix = 0;
+    while ix < text.len() {
+        let rest = &text[ix..];
+        if depth == 0 && rest.starts_with(separator) {
+            parts.push(&text[start..ix]);
+            ix += separator.len();
+            start = ix;
+            continue;
+        }
+        let c = rest.chars().next().unwrap();
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            _ => {}
+        }
+        ix += c.len_utf8();
+    }
+    parts.push(&text[start..]);
+    parts
+}
+
+/// Turns a BibTeX value into plain text, resolving common escapes, accents
+/// and commands and dropping the braces that protect capitalization.
+fn clean_latex(value: &str) -> String {
+    let mut text = String::new();
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' | '}' => {}
+            '~' => text.push('\u{a0}'),
+            '\\' => {
+                let Some(command) = chars.next() else {
+                    break;
+                };
+                let accent = match command {
+                    '\'' => Some('\u{301}'),
+                    '`' => Some('\u{300}'),
+                    '^' => Some('\u{302}'),
+                    '"' => Some('\u{308}'),
+                    '~' => Some('\u{303}'),
+                    '=' => Some('\u{304}'),
+                    '.' => Some('\u{307}'),
+                    _ => None,
+                };
+                if let Some(accent) = accent {
+                    while chars.peek() == Some(&'{') {
+                        chars.next();
+                    }
+                    if let Some(letter) = chars.next() {
+                        text.push(letter);
+                        text.push(accent);
+                    }
+                } else if command.is_ascii_alphabetic() {
+                    // Commands like `\emph{...}` keep their argument.
+                    let mut name = String::from(command);
+                    while let Some(c) = chars.next_if(char::is_ascii_alphabetic) {
+                        name.push(c);
+                    }
+                    match name.as_str() {
+                        "ss" => text.push('ß'),
+                        "o" => text.push('ø'),
+                        "O" => text.push('Ø'),
+                        "ae" => text.push('æ'),
+                        "aa" => text.push('å'),
+                        "l" => text.push('ł'),
+                        _ => {}
+                    }
+                    chars.next_if_eq(&' ');
+                } else {
+                    text.push(command);
+                }
+            }
+            _ => text.push(c),
+        }
+    }
+    let text = text.replace("---", "—").replace("--", "–");
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn csl_reference(item: &Value) -> Result<Reference> {
+    let key = csl_string(item, "id").ok_or_else(|| anyhow!("CSL-JSON item without an id"))?;
+    let year = item
+        .get("issued")
+        .and_then(|issued| {
+            issued
+                .pointer("/date-parts/0/0")
+                .and_then(value_to_string)
+                .or_else(|| csl_string(issued, "raw"))
+                .or_else(|| csl_string(issued, "literal"))
+        })
+        .map(|date| date.chars().take(4).collect());
+    Ok(Reference {
+        key,
+        kind: csl_string(item, "type").unwrap_or_default(),
+        authors: csl_people(item, "author"),
+        editors: csl_people(item, "editor"),
+        title: csl_string(item, "title"),
+        year,
+        container: csl_string(item, "container-title"),
+        publisher: csl_string(item, "publisher"),
+        volume: csl_string(item, "volume"),
+        issue: csl_string(item, "issue"),
+        pages: csl_string(item, "page"),
+        doi: csl_string(item, "DOI"),
+        url: csl_string(item, "URL"),
+    })
+}
+
+fn csl_people(item: &Value, field: &str) -> Vec<Person> {
+    let Some(people) = item.get(field).and_then(Value::as_array) else {
+        return Vec::new();
+    };
+    people
+        .iter()
+        .filter_map(|person| {
+            let family = csl_string(person, "family").or_else(|| csl_string(person, "literal"))?;
+            Some(Person {
+                family,
+                given: csl_string(person, "given"),
+            })
+        })
+        .collect()
+}
+
+/// Returns a field of a CSL item, which may be a string or a number.
+fn csl_string(item: &Value, field: &str) -> Option<String> {
+    item.get(field)
+        .and_then(value_to_string)
+        .filter(|value| !value.is_empty())
+}
+
+fn value_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(string) => Some(string.trim().to_string()),
+        Value::Number(number) => Some(number.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_bibtex() {
+        let library = Library::from_bibtex(
+            r#"
+            @string{nat = "Nature"}
+            @comment{ignored {braces}}
+
+            @article{smith2020,
+              author = {Smith, John A. and Jane van der Berg and M{\"u}ller, Karl},
+              title = {Deep {L}earning for {Notes}},
+              journal = nat # " Reviews",
+              year = 2020,
+              volume = {12}, number = "3",
+              pages = {45--67},
+              doi = {10.1000/xyz},
+            }
+
+            @book{who2019,
+              author = {{World Health Organization}},
+              title = "Guidelines",
+              publisher = {WHO Press},
+              date = {2019-05-01}
+            }
+
+            @misc{broken, title = {unclosed
+            "#,
+        );
+
+        assert_eq!(library.references().len(), 2);
+        let smith = library.get("smith2020").unwrap();
+        assert_eq!(smith.title.as_deref(), Some("Deep Learning for Notes"));
+        assert_eq!(smith.container.as_deref(), Some("Nature Reviews"));
+        assert_eq!(smith.authors[1].family, "van der Berg");
+        assert_eq!(smith.authors[2].family, "Mu\u{308}ller");
+        assert_eq!(smith.short_label(), "Smith et al. 2020");
+        assert_eq!(
+            smith.format(),
+            "Smith, J. A., van der Berg, J., & Mu\u{308}ller, K. (2020). Deep Learning for Notes. *Nature Reviews*, *12*(3), 45–67. https://doi.org/10.1000/xyz"
+        );
+
+        let who = library.get("who2019").unwrap();
+        assert_eq!(who.short_label(), "World Health Organization 2019");
+        assert_eq!(
+            who.format(),
+            "World Health Organization. (2019). *Guidelines*. WHO Press."
+        );
+    }
+
+    #[test]
+    fn test_csl_json() {
+        let library = Library::parse(
+            Path::new("library.json"),
+            r#"[
+                {
+                    "id": "doe2021",
+                    "type": "chapter",
+                    "title": "Linking Your Thinking?",
+                    "author": [{"family": "Doe", "given": "Alex"}, {"family": "Roe", "given": "Sam"}],
+                    "container-title": "Collected Essays",
+                    "issued": {"date-parts": [[2021, 4]]},
+                    "URL": "https://example.com"
+                },
+                {"id": 42, "editor": [{"family": "Poe"}]}
+            ]"#,
+        )
+        .unwrap();
+
+        let doe = library.get("doe2021").unwrap();
+        assert_eq!(doe.short_label(), "Doe and Roe 2021");
+        assert_eq!(
+            doe.format(),
+            "Doe, A., & Roe, S. (2021). Linking Your Thinking? *Collected Essays*. https://example.com"
+        );
+        let poe = library.get("42").unwrap();
+        assert_eq!(poe.short_label(), "Poe n.d.");
+        assert_eq!(poe.format(), "Poe (Ed.). (n.d.).");
+
+        assert!(Library::parse(Path::new("library.json"), "{").is_err());
+    }
+}
@@ -17,6 +17,11 @@ pub enum CliRequest {
         open_new_workspace: Option<bool>,
         env: Option<HashMap<String, String>>,
     },
+    /// Serves the notes of the project containing `path`, or of any open
+    /// project, over the Model Context Protocol.
+    Mcp { path: Option<String> },
+    /// A JSON-RPC message from the MCP client, relayed after [`CliRequest::Mcp`].
+    McpMessage { message: String },
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -68,6 +68,12 @@ struct Args {
     /// Run zed in the foreground (useful for debugging)
     #[arg(long)]
     foreground: bool,
+    /// Serve the notes of the project open in Zed over the Model Context Protocol,
+    /// reading messages from stdin and writing them to stdout.
+    ///
+    /// Pass a path to pick the project containing it.
+    #[arg(long)]
+    mcp: bool,
     /// Custom path to Zed.app or the zed binary
     #[arg(long)]
     zed: Option<PathBuf>,
@@ -250,13 +256,34 @@ fn main() -> Result<()> {
             let (_, handshake) = server.accept().context("Handshake after Zed spawn")?;
             let (tx, rx) = (handshake.requests, handshake.responses);
 
-            tx.send(CliRequest::Open {
-                paths,
-                urls,
-                wait: args.wait,
-                open_new_workspace,
-                env,
-            })?;
+            if args.mcp {
+                tx.send(CliRequest::Mcp {
+                    path: paths.into_iter().next(),
+                })?;
+                // Relay the client's messages to Zed, one per line. Zed stops
+                // serving once stdin is closed and this sender is dropped.
+                thread::spawn(move || {
+                    for line in io::stdin().lines() {
+                        let Ok(message) = line else {
+                            break;
+                        };
+                        if message.trim().is_empty() {
+                            continue;
+                        }
+                        if tx.send(CliRequest::McpMessage { message }).is_err() {
+                            break;
+                        }
+                    }
+                });
+            } else {
+                tx.send(CliRequest::Open {
+                    paths,
+                    urls,
+                    wait: args.wait,
+                    open_new_workspace,
+                    env,
+                })?;
+            }
 
             while let Ok(response) = rx.recv() {
                 match response {
@@ -20,7 +20,7 @@ use util::TryFutureExt;
 
 use crate::transport::{StdioTransport, Transport};
 
-const JSON_RPC_VERSION: &str = "2.0";
+pub(crate) const JSON_RPC_VERSION: &str = "2.0";
 const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
 
 // Standard JSON-RPC error codes
@@ -107,11 +107,11 @@ enum CspResult<T> {
 }
 
 #[derive(Serialize, Deserialize)]
-struct Notification<'a, T> {
-    jsonrpc: &'static str,
+pub(crate) struct Notification<'a, T> {
+    pub(crate) jsonrpc: &'static str,
     #[serde(borrow)]
-    method: &'a str,
-    params: T,
+    pub(crate) method: &'a str,
+    pub(crate) params: T,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -158,7 +158,21 @@ impl Client {
             .unwrap_or_else(String::new);
 
         let transport = Arc::new(StdioTransport::new(binary, &cx)?);
+        Ok(Self::from_transport(
+            server_id,
+            server_name.into(),
+            transport,
+            cx,
+        ))
+    }
 
+    /// Creates a new Client instance that talks to a context server over the given transport.
+    pub fn from_transport(
+        server_id: ContextServerId,
+        name: Arc<str>,
+        transport: Arc<dyn Transport>,
+        cx: AsyncApp,
+    ) -> Self {
         let (outbound_tx, outbound_rx) = channel::unbounded::<String>();
         let (output_done_tx, output_done_rx) = barrier::channel();
 
@@ -197,18 +211,18 @@ impl Client {
             .log_err()
         });
 
-        Ok(Self {
+        Self {
             server_id,
             notification_handlers,
             response_handlers,
-            name: server_name.into(),
+            name,
             next_id: Default::default(),
             outbound_tx,
             executor: cx.background_executor().clone(),
             io_tasks: Mutex::new(Some((input_task, output_task))),
             output_done_rx: Mutex::new(Some(output_done_rx)),
             transport,
-        })
+        }
     }
 
     /// Handles input from the server's stdout.
@@ -4,7 +4,8 @@ mod extension_context_server;
 pub mod manager;
 pub mod protocol;
 mod registry;
-mod transport;
+pub mod server;
+pub mod transport;
 pub mod types;
 
 use command_palette_hooks::CommandPaletteFilter;
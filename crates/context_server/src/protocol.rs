@@ -7,6 +7,8 @@
 
 use anyhow::Result;
 use collections::HashMap;
+use gpui::AsyncApp;
+use url::Url;
 
 use crate::client::Client;
 use crate::types;
@@ -136,6 +138,33 @@ impl InitializedContextServerProtocol {
         Ok(response)
     }
 
+    /// Reads the contents of an MCP resource.
+    pub async fn read_resource(&self, uri: Url) -> Result<types::ResourcesReadResponse> {
+        self.check_capability(ServerCapability::Resources)?;
+
+        let params = types::ResourcesReadParams { uri, meta: None };
+
+        let response: types::ResourcesReadResponse = self
+            .inner
+            .request(types::RequestType::ResourcesRead.as_str(), params)
+            .await?;
+
+        Ok(response)
+    }
+
+    /// Subscribes to `notifications/resources/updated` for an MCP resource.
+    pub async fn subscribe_resource(&self, uri: Url) -> Result<()> {
+        self.check_capability(ServerCapability::Resources)?;
+
+        let params = types::ResourcesSubscribeParams { uri, meta: None };
+
+        self.inner
+            .request::<serde_json::Value>(types::RequestType::ResourcesSubscribe.as_str(), params)
+            .await?;
+
+        Ok(())
+    }
+
     /// Executes a prompt with the given arguments and returns the result.
     pub async fn run_prompt<P: AsRef<str>>(
         &self,
@@ -224,6 +253,13 @@ impl InitializedContextServerProtocol {
 }
 
 impl InitializedContextServerProtocol {
+    pub fn on_notification<F>(&self, method: &'static str, f: F)
+    where
+        F: 'static + Send + FnMut(serde_json::Value, AsyncApp),
+    {
+        self.inner.on_notification(method, f);
+    }
+
     pub async fn request<R: serde::de::DeserializeOwned>(
         &self,
         method: &str,
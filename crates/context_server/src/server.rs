@@ -0,0 +1,258 @@
+//! A generic JSON-RPC server, used to expose Zed itself as a context server.
+//!
+//! It is the counterpart of the client in client.rs: it reads requests from a
+//! [`Transport`], dispatches them to typed handlers on the main thread, and
+//! writes their responses back, along with any notifications sent through a
+//! [`ServerNotifier`].
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use collections::HashMap;
+use futures::{StreamExt as _, select_biased, stream::FuturesUnordered};
+use gpui::{App, AppContext as _, AsyncApp, Task};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::Value;
+use smol::channel;
+use util::ResultExt as _;
+
+use crate::client::{
+    INTERNAL_ERROR, INVALID_PARAMS, INVALID_REQUEST, JSON_RPC_VERSION, METHOD_NOT_FOUND,
+    Notification, PARSE_ERROR, RequestId,
+};
+use crate::transport::Transport;
+
+type RequestHandler = Box<dyn Fn(Value, &mut App) -> Task<Result<Value, ResponseError>>>;
+
+pub struct Server {
+    request_handlers: HashMap<&'static str, RequestHandler>,
+    outbound_tx: channel::Sender<String>,
+    outbound_rx: channel::Receiver<String>,
+}
+
+/// Sends notifications to the peer of a [`Server`].
+#[derive(Clone)]
+pub struct ServerNotifier {
+    outbound_tx: channel::Sender<String>,
+}
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct IncomingRequest {
+    jsonrpc: String,
+    #[serde(default)]
+    id: Option<RequestId>,
+    method: String,
+    #[serde(default)]
+    params: Option<Value>,
+}
+
+#[derive(Serialize)]
+struct OutgoingResponse {
+    jsonrpc: &'static str,
+    id: Option<RequestId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<ResponseError>,
+}
+
+#[derive(Debug, Serialize)]
+struct ResponseError {
+    code: i32,
+    message: String,
+}
+
+impl Default for Server {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Server {
+    pub fn new() -> Self {
+        let (outbound_tx, outbound_rx) = channel::unbounded();
+        Self {
+            request_handlers: HashMap::default(),
+            outbound_tx,
+            outbound_rx,
+        }
+    }
+
+    pub fn notifier(&self) -> ServerNotifier {
+        ServerNotifier {
+            outbound_tx: self.outbound_tx.clone(),
+        }
+    }
+
+    /// Registers the handler for requests with the given method.
+    ///
+    /// Params that fail to deserialize are answered with an invalid params error,
+    /// and errors returned by the handler with an internal error.
+    pub fn handle_request<P, R, F>(&mut self, method: &'static str, handler: F)
+    where
+        P: DeserializeOwned,
+        R: Serialize + 'static,
+        F: 'static + Fn(P, &mut App) -> Task<Result<R>>,
+    {
+        self.request_handlers.insert(
+            method,
+            Box::new(move |params: Value, cx: &mut App| {
+                let params = match serde_json::from_value(params) {
+                    Ok(params) => params,
+                    Err(error) => {
+                        return Task::ready(Err(ResponseError {
+                            code: INVALID_PARAMS,
+                            message: error.to_string(),
+                        }));
+                    }
+                };
+                let task = handler(params, cx);
+                cx.foreground_executor().spawn(async move {
+                    let result = task.await.map_err(|error| ResponseError {
+                        code: INTERNAL_ERROR,
+                        message: format!("{error:#}"),
+                    })?;
+                    serde_json::to_value(result).map_err(|error| ResponseError {
+                        code: INTERNAL_ERROR,
+                        message: error.to_string(),
+                    })
+                })
+            }),
+        );
+    }
+
+    /// Serves requests read from the transport until the peer closes it.
+    ///
+    /// Requests are handled concurrently. Once the input ends, the returned task
+    /// waits for the outstanding responses to be written before completing.
+    pub fn run(self, transport: Arc<dyn Transport>, cx: &mut App) -> Task<()> {
+        let Self {
+            request_handlers,
+            outbound_tx,
+            outbound_rx,
+        } = self;
+
+        let output_task = cx.background_spawn({
+            let transport = transport.clone();
+            async move {
+                while let Ok(message) = outbound_rx.recv().await {
+                    log::trace!("outgoing message: {}", message);
+                    if transport.send(message).await.log_err().is_none() {
+                        break;
+                    }
+                }
+            }
+        });
+
+        cx.spawn(async move |cx| {
+            let mut incoming = transport.receive().fuse();
+            let mut pending_responses = FuturesUnordered::<Task<()>>::new();
+            loop {
+                select_biased! {
+                    _ = pending_responses.select_next_some() => {}
+                    message = incoming.next() => {
+                        let Some(message) = message else {
+                            break;
+                        };
+                        if let Some(response) =
+                            Self::handle_message(&message, &request_handlers, &outbound_tx, cx)
+                        {
+                            pending_responses.push(response);
+                        }
+                    }
+                }
+            }
+
+            while pending_responses.next().await.is_some() {}
+            outbound_tx.close();
+            output_task.await;
+        })
+    }
+
+    fn handle_message(
+        message: &str,
+        request_handlers: &HashMap<&'static str, RequestHandler>,
+        outbound_tx: &channel::Sender<String>,
+        cx: &mut AsyncApp,
+    ) -> Option<Task<()>> {
+        log::trace!("incoming message: {}", message);
+
+        let request = match serde_json::from_str::<IncomingRequest>(message) {
+            Ok(request) => request,
+            Err(error) => {
+                let code = if serde_json::from_str::<Value>(message).is_ok() {
+                    INVALID_REQUEST
+                } else {
+                    PARSE_ERROR
+                };
+                send_response(
+                    outbound_tx,
+                    None,
+                    Err(ResponseError {
+                        code,
+                        message: error.to_string(),
+                    }),
+                );
+                return None;
+            }
+        };
+
+        // Clients only send informational notifications, such as `notifications/initialized`.
+        let Some(id) = request.id else {
+            log::trace!("ignoring notification {:?}", request.method);
+            return None;
+        };
+
+        let Some(handler) = request_handlers.get(request.method.as_str()) else {
+            send_response(
+                outbound_tx,
+                Some(id),
+                Err(ResponseError {
+                    code: METHOD_NOT_FOUND,
+                    message: format!("method not found: {}", request.method),
+                }),
+            );
+            return None;
+        };
+
+        let params = request.params.unwrap_or(Value::Null);
+        let task = cx.update(|cx| handler(params, cx)).log_err()?;
+        let outbound_tx = outbound_tx.clone();
+        Some(cx.spawn(async move |_| {
+            send_response(&outbound_tx, Some(id), task.await);
+        }))
+    }
+}
+
+impl ServerNotifier {
+    /// Sends a notification to the peer without expecting a response.
+    pub fn notify(&self, method: &str, params: impl Serialize) -> Result<()> {
+        let notification = serde_json::to_string(&Notification {
+            jsonrpc: JSON_RPC_VERSION,
+            method,
+            params,
+        })?;
+        self.outbound_tx.try_send(notification)?;
+        Ok(())
+    }
+}
+
+fn send_response(
+    outbound_tx: &channel::Sender<String>,
+    id: Option<RequestId>,
+    result: Result<Value, ResponseError>,
+) {
+    let (result, error) = match result {
+        Ok(result) => (Some(result), None),
+        Err(error) => (None, Some(error)),
+    };
+    let response = serde_json::to_string(&OutgoingResponse {
+        jsonrpc: JSON_RPC_VERSION,
+        id,
+        result,
+        error,
+    })
+    .unwrap();
+    outbound_tx.try_send(response).log_err();
+}
@@ -0,0 +1,43 @@
+use std::pin::Pin;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use futures::{Stream, stream};
+use smol::channel;
+
+use crate::transport::Transport;
+
+/// A transport over in-process channels, for peers that aren't a child
+/// process, such as an external agent whose messages are relayed by the CLI.
+pub struct ChannelTransport {
+    outgoing: channel::Sender<String>,
+    incoming: channel::Receiver<String>,
+}
+
+impl ChannelTransport {
+    pub fn new(outgoing: channel::Sender<String>, incoming: channel::Receiver<String>) -> Self {
+        Self { outgoing, incoming }
+    }
+
+    /// Returns two transports connected to each other.
+    pub fn pair() -> (Self, Self) {
+        let (a_tx, a_rx) = channel::unbounded();
+        let (b_tx, b_rx) = channel::unbounded();
+        (Self::new(a_tx, b_rx), Self::new(b_tx, a_rx))
+    }
+}
+
+#[async_trait]
+impl Transport for ChannelTransport {
+    async fn send(&self, message: String) -> Result<()> {
+        Ok(self.outgoing.send(message).await?)
+    }
+
+    fn receive(&self) -> Pin<Box<dyn Stream<Item = String> + Send>> {
+        Box::pin(self.incoming.clone())
+    }
+
+    fn receive_err(&self) -> Pin<Box<dyn Stream<Item = String> + Send>> {
+        Box::pin(stream::empty())
+    }
+}
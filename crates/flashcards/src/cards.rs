@@ -0,0 +1,365 @@
+use std::ops::Range;
+
+use sha2::{Digest as _, Sha256};
+use vault::{body_offset, code_ranges, inline_tags, tag_matches};
+
+/// The tag that marks a note as containing flashcards. Nested tags like
+/// `#flashcards/spanish` put the note's cards in their own deck.
+pub const FLASHCARD_TAG: &str = "flashcards";
+
+const CLOZE_PLACEHOLDER: &str = "[...]";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CardKind {
+    /// A `question :: answer` line.
+    Basic,
+    /// Question lines, a line with just `?`, then answer lines.
+    MultiLine,
+    /// A line with a `{{deletion}}`. Lines with several deletions make one
+    /// card per deletion.
+    Cloze,
+}
+
+impl CardKind {
+    fn hash_prefix(&self) -> &'static str {
+        match self {
+            CardKind::Basic => "basic",
+            CardKind::MultiLine => "multiline",
+            CardKind::Cloze => "cloze",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Card {
+    pub kind: CardKind,
+    pub front: String,
+    pub back: String,
+    /// The row of the note the card starts on.
+    pub row: u32,
+    /// Identifies the card's review state. It's derived from the question
+    /// alone, so fixing an answer or moving the card keeps its progress.
+    pub hash: String,
+}
+
+/// Returns the flashcards in a note, skipping its frontmatter and code.
+pub fn parse_cards(text: &str) -> Vec<Card> {
+    let code = code_ranges(text);
+    let in_code = |offset: usize| code.iter().any(|range| range.contains(&offset));
+
+    let mut cards = Vec::new();
+    for paragraph in paragraphs(text, &in_code) {
+        let separator = paragraph
+            .iter()
+            .position(|line| line.text.trim() == "?" && !in_code(line.offset));
+        if let Some(separator) = separator {
+            let front = join_lines(&paragraph[..separator]);
+            let back = join_lines(&paragraph[separator + 1..]);
+            if !front.is_empty() && !back.is_empty() {
+                cards.push(Card::new(
+                    CardKind::MultiLine,
+                    &front,
+                    front.clone(),
+                    back,
+                    paragraph[0].row,
+                ));
+            }
+            continue;
+        }
+
+        for line in &paragraph {
+            if in_code(line.offset) {
+                continue;
+            }
+            let deletions = cloze_deletions(line, &in_code);
+            if !deletions.is_empty() {
+                cards.extend(cloze_cards(line, &deletions));
+            } else if let Some(card) = basic_card(line, &in_code) {
+                cards.push(card);
+            }
+        }
+    }
+    cards
+}
+
+struct Line<'a> {
+    text: &'a str,
+    offset: usize,
+    row: u32,
+}
+
+/// Groups the lines of the note's body into blocks separated by blank lines.
+/// Blank lines inside code blocks don't end a block.
+fn paragraphs<'a>(text: &'a str, in_code: &impl Fn(usize) -> bool) -> Vec<Vec<Line<'a>>> {
+    let body_start = body_offset(text);
+    let mut paragraphs = Vec::new();
+    let mut paragraph = Vec::new();
+    let mut offset = 0;
+    for (row, line) in text.split_inclusive('\n').enumerate() {
+        let line_offset = offset;
+        offset += line.len();
+        if line_offset < body_start {
+            continue;
+        }
+        let line = line.trim_end_matches(['\n', '\r']);
+        if line.trim().is_empty() && !in_code(line_offset) {
+            if !paragraph.is_empty() {
+                paragraphs.push(std::mem::take(&mut paragraph));
+            }
+            continue;
+        }
+        paragraph.push(Line {
+            text: line,
+            offset: line_offset,
+            row: row as u32,
+        });
+    }
+    if !paragraph.is_empty() {
+        paragraphs.push(paragraph);
+    }
+    paragraphs
+}
+
+fn join_lines(lines: &[Line]) -> String {
+    let text = lines
+        .iter()
+        .map(|line| line.text)
+        .collect::<Vec<_>>()
+        .join("\n");
+    strip_flashcard_tags(&text).trim().to_string()
+}
+
+fn basic_card(line: &Line, in_code: &impl Fn(usize) -> bool) -> Option<Card> {
+    let separator = line
+        .text
+        .match_indices("::")
+        .map(|(ix, _)| ix)
+        .find(|ix| !in_code(line.offset + ix))?;
+    let front = strip_flashcard_tags(strip_list_marker(&line.text[..separator]));
+    let back = strip_flashcard_tags(&line.text[separator + 2..]);
+    let (front, back) = (front.trim(), back.trim());
+    if front.is_empty() || back.is_empty() {
+        return None;
+    }
+    Some(Card::new(
+        CardKind::Basic,
+        front,
+        front.to_string(),
+        back.to_string(),
+        line.row,
+    ))
+}
+
+/// The ranges of the `{{deletions}}` on a line, including the braces.
+fn cloze_deletions(line: &Line, in_code: &impl Fn(usize) -> bool) -> Vec<Range<usize>> {
+    let mut deletions = Vec::new();
+    let mut search_from = 0;
+    while let Some(open) = line.text[search_from..]
+        .find("{{")
+        .map(|ix| ix + search_from)
+    {
+        let Some(close) = line.text[open + 2..].find("}}").map(|ix| ix + open + 2) else {
+            break;
+        };
+        search_from = close + 2;
+        if !in_code(line.offset + open) && !line.text[open + 2..close].trim().is_empty() {
+            deletions.push(open..close + 2);
+        }
+    }
+    deletions
+}
+
+fn cloze_cards(line: &Line, deletions: &[Range<usize>]) -> Vec<Card> {
+    let text = strip_list_marker(line.text);
+    let marker_len = line.text.len() - text.len();
+    let deletions = deletions
+        .iter()
+        .filter(|deletion| deletion.start >= marker_len)
+        .map(|deletion| deletion.start - marker_len..deletion.end - marker_len)
+        .collect::<Vec<_>>();
+
+    // Shows the line with the given deletion hidden and the rest revealed.
+    let render = |hidden: Option<usize>| {
+        let mut rendered = String::new();
+        let mut last_end = 0;
+        for (ix, deletion) in deletions.iter().enumerate() {
+            rendered.push_str(&text[last_end..deletion.start]);
+            if hidden == Some(ix) {
+                rendered.push_str(CLOZE_PLACEHOLDER);
+            } else {
+                rendered.push_str(&text[deletion.start + 2..deletion.end - 2]);
+            }
+            last_end = deletion.end;
+        }
+        rendered.push_str(&text[last_end..]);
+        strip_flashcard_tags(&rendered).trim().to_string()
+    };
+
+    let revealed = render(None);
+    deletions
+        .iter()
+        .enumerate()
+        .map(|(ix, deletion)| {
+            let deleted = text[deletion.start + 2..deletion.end - 2].trim();
+            // Deleting the same text twice on a line makes distinct cards.
+            let occurrence = deletions[..ix]
+                .iter()
+                .filter(|other| text[other.start + 2..other.end - 2].trim() == deleted)
+                .count();
+            Card::new(
+                CardKind::Cloze,
+                &format!("{revealed}\0{deleted}\0{occurrence}"),
+                render(Some(ix)),
+                revealed.clone(),
+                line.row,
+            )
+        })
+        .collect()
+}
+
+impl Card {
+    fn new(kind: CardKind, key: &str, front: String, back: String, row: u32) -> Self {
+        Self {
+            kind,
+            front,
+            back,
+            row,
+            hash: card_hash(kind, key),
+        }
+    }
+}
+
+/// Hashes a card's kind and question, ignoring differences in whitespace.
+fn card_hash(kind: CardKind, key: &str) -> String {
+    let key = key.split_whitespace().collect::<Vec<_>>().join(" ");
+    let mut hasher = Sha256::new();
+    hasher.update(kind.hash_prefix());
+    hasher.update([0]);
+    hasher.update(key);
+    hasher.finalize()[..16]
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+fn strip_list_marker(line: &str) -> &str {
+    let trimmed = line.trim_start();
+    for marker in ["- [ ] ", "- [x] ", "- ", "* ", "+ "] {
+        if let Some(rest) = trimmed.strip_prefix(marker) {
+            return rest;
+        }
+    }
+    let digits = trimmed.len()
+        - trimmed
+            .trim_start_matches(|c: char| c.is_ascii_digit())
+            .len();
+    if digits > 0 {
+        if let Some(rest) = trimmed[digits..].strip_prefix(". ") {
+            return rest;
+        }
+    }
+    line
+}
+
+/// Removes `#flashcards` tags from card text, since they only mark the note.
+fn strip_flashcard_tags(text: &str) -> String {
+    let mut stripped = String::new();
+    let mut last_end = 0;
+    for (range, tag) in inline_tags(text) {
+        if tag_matches(&tag, FLASHCARD_TAG) {
+            stripped.push_str(&text[last_end..range.start]);
+            last_end = range.end;
+        }
+    }
+    stripped.push_str(&text[last_end..]);
+    stripped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_parse_cards() {
+        let text = indoc! {"
+            ---
+            tags: [flashcards]
+            question :: not a card
+            ---
+            # Rust #flashcards
+
+            - What does `a::b` mean? :: A path #flashcards/rust
+            Borrowing :: Taking a reference
+
+            What are the two kinds
+            of references?
+            ?
+            Shared and
+            exclusive
+
+            The {{borrow checker}} enforces {{aliasing}} rules.
+
+            ```rust
+            std::mem::swap(a, b);
+
+            let x = {{y}};
+            ```
+            Not a card :: `inline::code` is fine
+        "};
+        let cards = parse_cards(text);
+        assert_eq!(
+            cards
+                .iter()
+                .map(|card| (card.kind, card.front.as_str(), card.back.as_str(), card.row))
+                .collect::<Vec<_>>(),
+            [
+                (CardKind::Basic, "What does `a::b` mean?", "A path", 6),
+                (CardKind::Basic, "Borrowing", "Taking a reference", 7),
+                (
+                    CardKind::MultiLine,
+                    "What are the two kinds\nof references?",
+                    "Shared and\nexclusive",
+                    9
+                ),
+                (
+                    CardKind::Cloze,
+                    "The [...] enforces aliasing rules.",
+                    "The borrow checker enforces aliasing rules.",
+                    15
+                ),
+                (
+                    CardKind::Cloze,
+                    "The borrow checker enforces [...] rules.",
+                    "The borrow checker enforces aliasing rules.",
+                    15
+                ),
+                (CardKind::Basic, "Not a card", "`inline::code` is fine", 22),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_card_hashes_survive_edits() {
+        let hashes = |text: &str| {
+            parse_cards(text)
+                .into_iter()
+                .map(|card| card.hash)
+                .collect::<Vec<_>>()
+        };
+        let original = hashes("Capital of France :: Paris\n\nThe {{sun}} is a {{star}}.\n");
+        assert_eq!(original.len(), 3);
+        assert_ne!(original[1], original[2]);
+
+        // Answers, whitespace and position don't affect the hash.
+        let edited = hashes(
+            "Intro.\n\n- Capital of  France :: Paris, France\n\nThe {{sun}} is a {{star}}.\n",
+        );
+        assert_eq!(edited, original);
+
+        // Changing the question makes a new card.
+        let reworded = hashes("Capital of Italy :: Rome\n");
+        assert_ne!(reworded[0], original[0]);
+    }
+}
@@ -0,0 +1,365 @@
+//! Spaced-repetition flashcards written in notes tagged `#flashcards`.
+//! Review state is kept in the local database, keyed by a hash of each
+//! card's question so editing notes doesn't reset progress.
+
+mod cards;
+mod persistence;
+mod review_modal;
+mod scheduler;
+
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{Result, anyhow};
+use chrono::NaiveDate;
+use collections::{HashMap, HashSet};
+use fs::Fs;
+use gpui::{App, actions};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+use vault::{NoteResolver, note_tags, tag_matches};
+use workspace::Workspace;
+
+pub use cards::{Card, CardKind, FLASHCARD_TAG, parse_cards};
+pub use persistence::{FLASHCARDS_DB, FlashcardsDb};
+pub use review_modal::FlashcardReview;
+pub use scheduler::{Rating, ReviewState, SchedulingAlgorithm, schedule};
+
+actions!(
+    flashcards,
+    [Review, ShowAnswer, RateAgain, RateHard, RateGood, RateEasy]
+);
+
+/// Settings for reviewing flashcards.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct FlashcardSettings {
+    /// The algorithm that schedules reviews, either "fsrs" or "sm2".
+    ///
+    /// Default: "fsrs"
+    pub algorithm: Option<SchedulingAlgorithm>,
+    /// The chance of still remembering a card when it comes due, between 0.7
+    /// and 0.99. Higher values mean more frequent reviews. Only used by FSRS.
+    ///
+    /// Default: 0.9
+    pub desired_retention: Option<f64>,
+    /// The number of new cards to introduce from each deck per day.
+    ///
+    /// Default: 20
+    pub new_cards_per_day: Option<usize>,
+}
+
+impl FlashcardSettings {
+    pub fn algorithm(&self) -> SchedulingAlgorithm {
+        self.algorithm.unwrap_or_default()
+    }
+
+    pub fn desired_retention(&self) -> f64 {
+        self.desired_retention.unwrap_or(0.9).clamp(0.7, 0.99)
+    }
+
+    pub fn new_cards_per_day(&self) -> usize {
+        self.new_cards_per_day.unwrap_or(20)
+    }
+}
+
+impl settings::Settings for FlashcardSettings {
+    const KEY: Option<&'static str> = Some("flashcards");
+
+    type FileContent = Self;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _: &mut App) -> Result<Self> {
+        sources.json_merge()
+    }
+}
+
+pub fn init(cx: &mut App) {
+    FlashcardSettings::register(cx);
+
+    cx.observe_new(|workspace: &mut Workspace, _window, _cx| {
+        workspace.register_action(|workspace, _: &Review, window, cx| {
+            let Some(worktree) = workspace.visible_worktrees(cx).next() else {
+                workspace.show_error(&anyhow!("Open a vault folder to review flashcards"), cx);
+                return;
+            };
+            let worktree = worktree.read(cx);
+            let root = worktree.abs_path();
+            let resolver = NoteResolver::new(
+                worktree
+                    .snapshot()
+                    .files(false, 0)
+                    .map(|entry| entry.path.clone()),
+            );
+            let fs = workspace.app_state().fs.clone();
+            workspace.toggle_modal(window, cx, |window, cx| {
+                FlashcardReview::new(root, resolver, fs, window, cx)
+            });
+        });
+    })
+    .detach();
+}
+
+/// A card along with the note it's written in.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeckCard {
+    pub note: Arc<Path>,
+    pub card: Card,
+}
+
+/// The cards of the notes tagged with `#flashcards` or one of its nested
+/// tags.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Deck {
+    pub name: String,
+    pub cards: Vec<DeckCard>,
+}
+
+/// How far along the cards of a deck are.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DeckStats {
+    /// Cards that haven't been reviewed yet.
+    pub new: usize,
+    /// Reviewed cards that are due today.
+    pub due: usize,
+    /// Reviewed cards with short intervals.
+    pub young: usize,
+    /// Reviewed cards with intervals of three weeks or more.
+    pub mature: usize,
+    /// The number of reviews done today.
+    pub reviewed_today: usize,
+}
+
+/// What's known about past reviews when starting a review session.
+#[derive(Clone, Debug, Default)]
+pub struct ReviewHistory {
+    pub states: HashMap<String, ReviewState>,
+    /// The hash of the card of each review done today.
+    pub reviewed_today: Vec<String>,
+    /// The cards reviewed for the first time today.
+    pub introduced_today: HashSet<String>,
+}
+
+impl ReviewHistory {
+    pub fn load(db: &FlashcardsDb, today: NaiveDate) -> Result<Self> {
+        Ok(Self {
+            states: db.review_states()?.into_iter().collect(),
+            reviewed_today: db.reviews_on(today)?,
+            introduced_today: db.cards_introduced_on(today)?.into_iter().collect(),
+        })
+    }
+}
+
+impl Deck {
+    pub fn stats(&self, history: &ReviewHistory, today: NaiveDate) -> DeckStats {
+        let mut stats = DeckStats::default();
+        for card in &self.cards {
+            match history.states.get(&card.card.hash) {
+                None => stats.new += 1,
+                Some(state) => {
+                    if state.is_due(today) {
+                        stats.due += 1;
+                    }
+                    if state.is_mature() {
+                        stats.mature += 1;
+                    } else {
+                        stats.young += 1;
+                    }
+                }
+            }
+        }
+        let hashes = self
+            .cards
+            .iter()
+            .map(|card| card.card.hash.as_str())
+            .collect::<HashSet<_>>();
+        stats.reviewed_today = history
+            .reviewed_today
+            .iter()
+            .filter(|hash| hashes.contains(hash.as_str()))
+            .count();
+        stats
+    }
+
+    /// The indices of the cards to review today: due cards, most overdue
+    /// first, then new cards up to the daily limit in the order they're
+    /// written.
+    pub fn review_queue(
+        &self,
+        history: &ReviewHistory,
+        new_cards_per_day: usize,
+        today: NaiveDate,
+    ) -> Vec<usize> {
+        let mut due = self
+            .cards
+            .iter()
+            .enumerate()
+            .filter_map(|(ix, card)| {
+                let state = history.states.get(&card.card.hash)?;
+                state.is_due(today).then_some((state.due, ix))
+            })
+            .collect::<Vec<_>>();
+        due.sort();
+
+        let introduced_today = self
+            .cards
+            .iter()
+            .filter(|card| history.introduced_today.contains(&card.card.hash))
+            .count();
+        let new = self
+            .cards
+            .iter()
+            .enumerate()
+            .filter(|(_, card)| !history.states.contains_key(&card.card.hash))
+            .map(|(ix, _)| ix)
+            .take(new_cards_per_day.saturating_sub(introduced_today));
+
+        due.into_iter().map(|(_, ix)| ix).chain(new).collect()
+    }
+}
+
+/// Loads the flashcards of the vault at `root`, grouped into decks and
+/// sorted by deck name.
+pub async fn load_decks(root: &Path, resolver: &NoteResolver, fs: &dyn Fs) -> Vec<Deck> {
+    let mut decks: Vec<Deck> = Vec::new();
+    let mut seen = HashSet::default();
+    for path in resolver.notes() {
+        let text = match fs.load(&root.join(path)).await {
+            Ok(text) => text,
+            Err(error) => {
+                log::warn!("failed to load note {path:?}: {error}");
+                continue;
+            }
+        };
+        let tags = note_tags(&text)
+            .into_iter()
+            .filter(|tag| tag_matches(tag, FLASHCARD_TAG))
+            .collect::<Vec<_>>();
+        if tags.is_empty() {
+            continue;
+        }
+        let name = deck_name(path, &tags);
+        let cards = parse_cards(&text)
+            .into_iter()
+            // A card written twice shares its review state, so only keep one.
+            .filter(|card| seen.insert(card.hash.clone()))
+            .map(|card| DeckCard {
+                note: path.clone(),
+                card,
+            })
+            .collect::<Vec<_>>();
+        if cards.is_empty() {
+            continue;
+        }
+        match decks.iter_mut().find(|deck| deck.name == name) {
+            Some(deck) => deck.cards.extend(cards),
+            None => decks.push(Deck { name, cards }),
+        }
+    }
+    decks.sort_by_cached_key(|deck| deck.name.to_lowercase());
+    decks
+}
+
+/// Notes tagged `#flashcards/spanish` go in the "spanish" deck, and notes
+/// tagged with just `#flashcards` get a deck of their own.
+fn deck_name(path: &Path, tags: &[String]) -> String {
+    tags.iter()
+        .find_map(|tag| {
+            let (_, deck) = tag.split_once('/')?;
+            (!deck.is_empty()).then(|| deck.to_string())
+        })
+        .unwrap_or_else(|| {
+            path.file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use util::path;
+
+    #[gpui::test]
+    async fn test_decks(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/vault"),
+            json!({
+                "Onboarding.md": "#flashcards\n\nWho reviews PRs? :: Core contributors\nWhere is the build? :: CI\n",
+                "spanish": {
+                    "verbs.md": "---\ntags: [flashcards/spanish]\n---\nser :: to be\n",
+                    "nouns.md": "#flashcards/spanish\n\nel perro :: the dog\nser :: to be\n",
+                },
+                "Untagged.md": "Not reviewed :: ever\n",
+            }),
+        )
+        .await;
+        let resolver = NoteResolver::new(
+            [
+                "Onboarding.md",
+                "spanish/nouns.md",
+                "spanish/verbs.md",
+                "Untagged.md",
+            ]
+            .into_iter()
+            .map(|path| Arc::from(Path::new(path))),
+        );
+
+        let decks = load_decks(Path::new(path!("/vault")), &resolver, fs.as_ref()).await;
+        assert_eq!(
+            decks
+                .iter()
+                .map(|deck| (
+                    deck.name.as_str(),
+                    deck.cards
+                        .iter()
+                        .map(|card| card.card.front.as_str())
+                        .collect::<Vec<_>>()
+                ))
+                .collect::<Vec<_>>(),
+            [
+                (
+                    "Onboarding",
+                    vec!["Who reviews PRs?", "Where is the build?"]
+                ),
+                ("spanish", vec!["el perro", "ser"]),
+            ]
+        );
+
+        let today = NaiveDate::from_ymd_opt(2025, 5, 1).unwrap();
+        let onboarding = &decks[0];
+        let mut history = ReviewHistory::default();
+        let reviewed = schedule(SchedulingAlgorithm::Sm2, None, Rating::Good, today, 0.9);
+        history
+            .states
+            .insert(onboarding.cards[0].card.hash.clone(), reviewed);
+        history
+            .reviewed_today
+            .push(onboarding.cards[0].card.hash.clone());
+        history
+            .introduced_today
+            .insert(onboarding.cards[0].card.hash.clone());
+        assert_eq!(
+            onboarding.stats(&history, today),
+            DeckStats {
+                new: 1,
+                due: 0,
+                young: 1,
+                mature: 0,
+                reviewed_today: 1,
+            }
+        );
+        assert_eq!(onboarding.review_queue(&history, 20, today), [1]);
+        assert_eq!(onboarding.review_queue(&history, 1, today), []);
+
+        let tomorrow = today.succ_opt().unwrap();
+        history.reviewed_today.clear();
+        history.introduced_today.clear();
+        assert_eq!(onboarding.review_queue(&history, 20, tomorrow), [0, 1]);
+        assert_eq!(onboarding.stats(&history, tomorrow).due, 1);
+    }
+}
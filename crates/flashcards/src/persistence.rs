@@ -0,0 +1,202 @@
+use anyhow::{Context as _, Result};
+use chrono::NaiveDate;
+use db::{
+    define_connection, query,
+    sqlez::{
+        bindable::{Bind, Column, StaticColumnCount},
+        statement::Statement,
+    },
+    sqlez_macros::sql,
+};
+
+use crate::{Rating, ReviewState};
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+fn bind_date(date: NaiveDate, statement: &Statement, start_index: i32) -> Result<i32> {
+    date.format(DATE_FORMAT)
+        .to_string()
+        .bind(statement, start_index)
+}
+
+fn column_date(statement: &mut Statement, start_index: i32) -> Result<(NaiveDate, i32)> {
+    let (date, next_index): (String, i32) = Column::column(statement, start_index)?;
+    let date = NaiveDate::parse_from_str(&date, DATE_FORMAT)
+        .with_context(|| format!("invalid review date {date:?}"))?;
+    Ok((date, next_index))
+}
+
+impl StaticColumnCount for ReviewState {
+    fn column_count() -> usize {
+        8
+    }
+}
+
+impl Bind for ReviewState {
+    fn bind(&self, statement: &Statement, start_index: i32) -> Result<i32> {
+        let next_index = bind_date(self.due, statement, start_index)?;
+        let next_index = self.interval.bind(statement, next_index)?;
+        let next_index = self.repetitions.bind(statement, next_index)?;
+        let next_index = self.lapses.bind(statement, next_index)?;
+        let next_index = bind_date(self.last_review, statement, next_index)?;
+        let next_index = self.ease.bind(statement, next_index)?;
+        let next_index = self.stability.bind(statement, next_index)?;
+        self.difficulty.bind(statement, next_index)
+    }
+}
+
+impl Column for ReviewState {
+    fn column(statement: &mut Statement, start_index: i32) -> Result<(Self, i32)> {
+        let (due, next_index) = column_date(statement, start_index)?;
+        let (interval, next_index) = u32::column(statement, next_index)?;
+        let (repetitions, next_index) = u32::column(statement, next_index)?;
+        let (lapses, next_index) = u32::column(statement, next_index)?;
+        let (last_review, next_index) = column_date(statement, next_index)?;
+        let (ease, next_index) = f64::column(statement, next_index)?;
+        let (stability, next_index) = f64::column(statement, next_index)?;
+        let (difficulty, next_index) = f64::column(statement, next_index)?;
+        Ok((
+            Self {
+                due,
+                interval,
+                repetitions,
+                lapses,
+                last_review,
+                ease,
+                stability,
+                difficulty,
+            },
+            next_index,
+        ))
+    }
+}
+
+define_connection!(pub static ref FLASHCARDS_DB: FlashcardsDb<()> =
+    &[sql!(
+        CREATE TABLE IF NOT EXISTS flashcard_reviews(
+            card_hash TEXT PRIMARY KEY,
+            due TEXT NOT NULL,
+            interval INTEGER NOT NULL,
+            repetitions INTEGER NOT NULL,
+            lapses INTEGER NOT NULL,
+            last_review TEXT NOT NULL,
+            ease REAL NOT NULL,
+            stability REAL NOT NULL,
+            difficulty REAL NOT NULL
+        ) STRICT;
+
+        CREATE TABLE IF NOT EXISTS flashcard_review_log(
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            card_hash TEXT NOT NULL,
+            rating INTEGER NOT NULL,
+            reviewed_on TEXT NOT NULL
+        ) STRICT;
+    )];
+);
+
+impl FlashcardsDb {
+    /// Saves the state of a card after a review, and logs the review.
+    pub async fn record_review(
+        &self,
+        card_hash: String,
+        rating: Rating,
+        state: ReviewState,
+    ) -> Result<()> {
+        let reviewed_on = state.last_review.format(DATE_FORMAT).to_string();
+        self.save_review_state(card_hash.clone(), state).await?;
+        self.log_review(card_hash, rating as u8 as u32, reviewed_on)
+            .await
+    }
+
+    /// The hashes of the cards reviewed on `date`, once per review.
+    pub fn reviews_on(&self, date: NaiveDate) -> Result<Vec<String>> {
+        self.card_hashes_reviewed_on(date.format(DATE_FORMAT).to_string())
+    }
+
+    /// The hashes of the cards reviewed for the first time on `date`.
+    pub fn cards_introduced_on(&self, date: NaiveDate) -> Result<Vec<String>> {
+        self.card_hashes_first_reviewed_on(date.format(DATE_FORMAT).to_string())
+    }
+
+    query! {
+        pub fn review_states() -> Result<Vec<(String, ReviewState)>> {
+            SELECT card_hash, due, interval, repetitions, lapses, last_review, ease, stability, difficulty
+            FROM flashcard_reviews
+        }
+    }
+
+    query! {
+        async fn save_review_state(card_hash: String, state: ReviewState) -> Result<()> {
+            INSERT INTO flashcard_reviews
+                (card_hash, due, interval, repetitions, lapses, last_review, ease, stability, difficulty)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+            ON CONFLICT DO UPDATE SET
+                due = ?2,
+                interval = ?3,
+                repetitions = ?4,
+                lapses = ?5,
+                last_review = ?6,
+                ease = ?7,
+                stability = ?8,
+                difficulty = ?9
+        }
+    }
+
+    query! {
+        async fn log_review(card_hash: String, rating: u32, reviewed_on: String) -> Result<()> {
+            INSERT INTO flashcard_review_log (card_hash, rating, reviewed_on) VALUES (?, ?, ?)
+        }
+    }
+
+    query! {
+        fn card_hashes_reviewed_on(reviewed_on: String) -> Result<Vec<String>> {
+            SELECT card_hash FROM flashcard_review_log WHERE reviewed_on = (?)
+        }
+    }
+
+    query! {
+        fn card_hashes_first_reviewed_on(reviewed_on: String) -> Result<Vec<String>> {
+            SELECT card_hash FROM flashcard_review_log
+            GROUP BY card_hash
+            HAVING MIN(reviewed_on) = (?)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SchedulingAlgorithm, schedule};
+    use pretty_assertions::assert_eq;
+
+    #[gpui::test]
+    async fn test_saves_review_states() {
+        let db = FlashcardsDb(db::open_test_db("test_saves_review_states").await);
+        assert_eq!(db.review_states().unwrap(), []);
+
+        let today = NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+        let first = schedule(SchedulingAlgorithm::Fsrs, None, Rating::Good, today, 0.9);
+        db.record_review("abc".into(), Rating::Good, first.clone())
+            .await
+            .unwrap();
+        assert_eq!(db.review_states().unwrap(), [("abc".to_string(), first)]);
+
+        let second = schedule(
+            SchedulingAlgorithm::Fsrs,
+            Some(&first),
+            Rating::Again,
+            today,
+            0.9,
+        );
+        db.record_review("abc".into(), Rating::Again, second.clone())
+            .await
+            .unwrap();
+        assert_eq!(db.review_states().unwrap(), [("abc".to_string(), second)]);
+        assert_eq!(db.reviews_on(today).unwrap(), ["abc", "abc"]);
+        assert_eq!(db.cards_introduced_on(today).unwrap(), ["abc"]);
+        assert_eq!(
+            db.reviews_on(today.succ_opt().unwrap()).unwrap(),
+            Vec::<String>::new()
+        );
+    }
+}
@@ -0,0 +1,385 @@
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::Arc;
+
+use chrono::{Local, NaiveDate};
+use fs::Fs;
+use gpui::{
+    Action, App, Context, DismissEvent, EventEmitter, FocusHandle, Focusable, Render, Task, Window,
+};
+use settings::Settings as _;
+use ui::{KeyBinding, ListItem, ListItemSpacing, prelude::*};
+use util::ResultExt as _;
+use vault::NoteResolver;
+use workspace::ModalView;
+
+use crate::{
+    Deck, FLASHCARDS_DB, FlashcardSettings, RateAgain, RateEasy, RateGood, RateHard, Rating,
+    ReviewHistory, ShowAnswer, load_decks, schedule,
+};
+
+/// A modal listing the vault's decks with their statistics, and reviewing
+/// the due cards of a deck one at a time.
+pub struct FlashcardReview {
+    focus_handle: FocusHandle,
+    today: NaiveDate,
+    /// The decks, or `None` while the notes are loading.
+    decks: Option<Vec<Deck>>,
+    history: ReviewHistory,
+    selected_deck: usize,
+    session: Option<ReviewSession>,
+    _load: Task<()>,
+}
+
+struct ReviewSession {
+    deck_ix: usize,
+    /// The cards left to review, as indices into the deck. Cards rated
+    /// "again" go back to the end.
+    queue: VecDeque<usize>,
+    revealed: bool,
+    reviewed: usize,
+}
+
+impl FlashcardReview {
+    pub fn new(
+        root: Arc<Path>,
+        resolver: NoteResolver,
+        fs: Arc<dyn Fs>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let today = Local::now().date_naive();
+        let load = cx.spawn(async move |this, cx| {
+            let decks = load_decks(&root, &resolver, fs.as_ref()).await;
+            let history = cx
+                .background_spawn(async move { ReviewHistory::load(&FLASHCARDS_DB, today) })
+                .await
+                .log_err()
+                .unwrap_or_default();
+            this.update(cx, |this, cx| {
+                this.decks = Some(decks);
+                this.history = history;
+                cx.notify();
+            })
+            .ok();
+        });
+        let focus_handle = cx.focus_handle();
+        window.focus(&focus_handle);
+        Self {
+            focus_handle,
+            today,
+            decks: None,
+            history: ReviewHistory::default(),
+            selected_deck: 0,
+            session: None,
+            _load: load,
+        }
+    }
+
+    fn deck_count(&self) -> usize {
+        self.decks.as_ref().map_or(0, |decks| decks.len())
+    }
+
+    fn select_next(&mut self, _: &menu::SelectNext, _: &mut Window, cx: &mut Context<Self>) {
+        if self.session.is_none() && self.deck_count() > 0 {
+            self.selected_deck = (self.selected_deck + 1) % self.deck_count();
+            cx.notify();
+        }
+    }
+
+    fn select_previous(
+        &mut self,
+        _: &menu::SelectPrevious,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.session.is_none() && self.deck_count() > 0 {
+            self.selected_deck = (self.selected_deck + self.deck_count() - 1) % self.deck_count();
+            cx.notify();
+        }
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        if self.session.is_some() {
+            self.show_answer(&ShowAnswer, window, cx);
+        } else {
+            self.start_session(self.selected_deck, cx);
+        }
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, _: &mut Window, cx: &mut Context<Self>) {
+        if self.session.take().is_some() {
+            cx.notify();
+        } else {
+            cx.emit(DismissEvent);
+        }
+    }
+
+    fn start_session(&mut self, deck_ix: usize, cx: &mut Context<Self>) {
+        let Some(deck) = self.decks.as_ref().and_then(|decks| decks.get(deck_ix)) else {
+            return;
+        };
+        let new_cards_per_day = FlashcardSettings::get_global(cx).new_cards_per_day();
+        let queue = deck.review_queue(&self.history, new_cards_per_day, self.today);
+        self.selected_deck = deck_ix;
+        self.session = Some(ReviewSession {
+            deck_ix,
+            queue: queue.into(),
+            revealed: false,
+            reviewed: 0,
+        });
+        cx.notify();
+    }
+
+    fn show_answer(&mut self, _: &ShowAnswer, _: &mut Window, cx: &mut Context<Self>) {
+        if let Some(session) = &mut self.session {
+            if !session.queue.is_empty() {
+                session.revealed = true;
+                cx.notify();
+            }
+        }
+    }
+
+    fn rate(&mut self, rating: Rating, cx: &mut Context<Self>) {
+        let Some(session) = &mut self.session else {
+            return;
+        };
+        if !session.revealed {
+            return;
+        }
+        let Some(card) = self
+            .decks
+            .as_ref()
+            .and_then(|decks| decks.get(session.deck_ix))
+            .zip(session.queue.front())
+            .and_then(|(deck, card_ix)| deck.cards.get(*card_ix))
+        else {
+            return;
+        };
+        let hash = card.card.hash.clone();
+
+        let settings = FlashcardSettings::get_global(cx);
+        let previous = self.history.states.get(&hash);
+        if previous.is_none() {
+            self.history.introduced_today.insert(hash.clone());
+        }
+        let state = schedule(
+            settings.algorithm(),
+            previous,
+            rating,
+            self.today,
+            settings.desired_retention(),
+        );
+
+        let card_ix = session.queue.pop_front();
+        if state.is_due(self.today) {
+            session.queue.extend(card_ix);
+        }
+        session.revealed = false;
+        session.reviewed += 1;
+        self.history.states.insert(hash.clone(), state.clone());
+        self.history.reviewed_today.push(hash.clone());
+
+        cx.background_spawn(async move { FLASHCARDS_DB.record_review(hash, rating, state).await })
+            .detach_and_log_err(cx);
+        cx.notify();
+    }
+
+    fn render_decks(&self, cx: &mut Context<Self>) -> AnyElement {
+        let Some(decks) = &self.decks else {
+            return Label::new("Loading flashcards…")
+                .color(Color::Muted)
+                .into_any_element();
+        };
+        if decks.is_empty() {
+            return Label::new("Tag notes with #flashcards to review the cards in them")
+                .color(Color::Muted)
+                .into_any_element();
+        }
+
+        v_flex()
+            .children(decks.iter().enumerate().map(|(ix, deck)| {
+                let stats = deck.stats(&self.history, self.today);
+                ListItem::new(ix)
+                    .inset(true)
+                    .spacing(ListItemSpacing::Sparse)
+                    .toggle_state(ix == self.selected_deck)
+                    .on_click(cx.listener(move |this, _, _, cx| this.start_session(ix, cx)))
+                    .child(
+                        v_flex().child(Label::new(deck.name.clone())).child(
+                            Label::new(format!(
+                                "{} cards · {} young · {} mature · {} reviewed today",
+                                deck.cards.len(),
+                                stats.young,
+                                stats.mature,
+                                stats.reviewed_today
+                            ))
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                        ),
+                    )
+                    .end_slot(
+                        h_flex()
+                            .gap_2()
+                            .child(
+                                Label::new(format!("{} due", stats.due))
+                                    .size(LabelSize::Small)
+                                    .color(if stats.due > 0 {
+                                        Color::Accent
+                                    } else {
+                                        Color::Muted
+                                    }),
+                            )
+                            .child(
+                                Label::new(format!("{} new", stats.new))
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted),
+                            ),
+                    )
+            }))
+            .into_any_element()
+    }
+
+    fn render_session(
+        &self,
+        session: &ReviewSession,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> AnyElement {
+        let Some(card) = self
+            .decks
+            .as_ref()
+            .and_then(|decks| decks.get(session.deck_ix))
+            .zip(session.queue.front())
+            .and_then(|(deck, card_ix)| deck.cards.get(*card_ix))
+        else {
+            let message = if session.reviewed == 0 {
+                "Nothing to review in this deck today".to_string()
+            } else {
+                format!("Done for today: reviewed {} cards", session.reviewed)
+            };
+            return Label::new(message).color(Color::Muted).into_any_element();
+        };
+
+        let note_name = card
+            .note
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let mut content = v_flex()
+            .gap_2()
+            .child(
+                Label::new(format!("{} left · {note_name}", session.queue.len()))
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+            .child(div().text_ui(cx).child(card.card.front.clone()));
+        if !session.revealed {
+            return content
+                .child(
+                    h_flex().justify_end().child(
+                        Button::new("show-answer", "Show Answer")
+                            .key_binding(KeyBinding::for_action_in(
+                                &ShowAnswer,
+                                &self.focus_handle,
+                                window,
+                                cx,
+                            ))
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.show_answer(&ShowAnswer, window, cx)
+                            })),
+                    ),
+                )
+                .into_any_element();
+        }
+
+        let settings = FlashcardSettings::get_global(cx);
+        let previous = self.history.states.get(&card.card.hash);
+        let buttons = Rating::ALL.map(|rating| {
+            let interval = schedule(
+                settings.algorithm(),
+                previous,
+                rating,
+                self.today,
+                settings.desired_retention(),
+            )
+            .interval;
+            let action: Box<dyn Action> = match rating {
+                Rating::Again => Box::new(RateAgain),
+                Rating::Hard => Box::new(RateHard),
+                Rating::Good => Box::new(RateGood),
+                Rating::Easy => Box::new(RateEasy),
+            };
+            let label = match interval {
+                0 => format!("{} · today", rating.label()),
+                1 => format!("{} · 1 day", rating.label()),
+                days => format!("{} · {days} days", rating.label()),
+            };
+            Button::new(rating.label(), label)
+                .key_binding(KeyBinding::for_action_in(
+                    action.as_ref(),
+                    &self.focus_handle,
+                    window,
+                    cx,
+                ))
+                .on_click(cx.listener(move |this, _, _, cx| this.rate(rating, cx)))
+        });
+        content = content
+            .child(
+                div()
+                    .border_t_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .pt_2()
+                    .text_ui(cx)
+                    .child(card.card.back.clone()),
+            )
+            .child(h_flex().justify_end().gap_1().children(buttons));
+        content.into_any_element()
+    }
+}
+
+impl EventEmitter<DismissEvent> for FlashcardReview {}
+
+impl ModalView for FlashcardReview {}
+
+impl Focusable for FlashcardReview {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for FlashcardReview {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let (title, body) = match &self.session {
+            Some(session) => {
+                let deck_name = self
+                    .decks
+                    .as_ref()
+                    .and_then(|decks| decks.get(session.deck_ix))
+                    .map(|deck| deck.name.clone())
+                    .unwrap_or_default();
+                (deck_name, self.render_session(session, window, cx))
+            }
+            None => ("Flashcards".to_string(), self.render_decks(cx)),
+        };
+
+        v_flex()
+            .key_context("FlashcardReview")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::select_next))
+            .on_action(cx.listener(Self::select_previous))
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::cancel))
+            .on_action(cx.listener(Self::show_answer))
+            .on_action(cx.listener(|this, _: &RateAgain, _, cx| this.rate(Rating::Again, cx)))
+            .on_action(cx.listener(|this, _: &RateHard, _, cx| this.rate(Rating::Hard, cx)))
+            .on_action(cx.listener(|this, _: &RateGood, _, cx| this.rate(Rating::Good, cx)))
+            .on_action(cx.listener(|this, _: &RateEasy, _, cx| this.rate(Rating::Easy, cx)))
+            .w(rems(40.))
+            .elevation_3(cx)
+            .p_3()
+            .gap_2()
+            .child(Headline::new(title).size(HeadlineSize::Small))
+            .child(body)
+    }
+}
@@ -0,0 +1,311 @@
+use chrono::NaiveDate;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// The algorithm that decides when cards are next due.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SchedulingAlgorithm {
+    /// The Free Spaced Repetition Scheduler (FSRS-4.5), which models how
+    /// well each card is remembered.
+    #[default]
+    Fsrs,
+    /// SuperMemo 2, which grows each card's interval by its ease factor.
+    Sm2,
+}
+
+/// How well a card was remembered.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rating {
+    Again = 1,
+    Hard = 2,
+    Good = 3,
+    Easy = 4,
+}
+
+impl Rating {
+    pub const ALL: [Rating; 4] = [Rating::Again, Rating::Hard, Rating::Good, Rating::Easy];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Rating::Again => "Again",
+            Rating::Hard => "Hard",
+            Rating::Good => "Good",
+            Rating::Easy => "Easy",
+        }
+    }
+}
+
+/// When a card is due, and what each algorithm knows about it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReviewState {
+    pub due: NaiveDate,
+    /// The number of days between the last review and `due`.
+    pub interval: u32,
+    /// The number of successful reviews in a row.
+    pub repetitions: u32,
+    /// The number of times the card was forgotten after being learned.
+    pub lapses: u32,
+    pub last_review: NaiveDate,
+    /// The SM-2 ease factor.
+    pub ease: f64,
+    /// The FSRS stability, in days.
+    pub stability: f64,
+    /// The FSRS difficulty, from 1 to 10.
+    pub difficulty: f64,
+}
+
+impl ReviewState {
+    pub fn is_due(&self, today: NaiveDate) -> bool {
+        self.due <= today
+    }
+
+    /// Whether the card is remembered well enough to be reviewed at most
+    /// every three weeks.
+    pub fn is_mature(&self) -> bool {
+        self.interval >= 21
+    }
+}
+
+const INITIAL_EASE: f64 = 2.5;
+const MINIMUM_EASE: f64 = 1.3;
+const MAXIMUM_INTERVAL: f64 = 36500.;
+
+/// Returns the state of a card after reviewing it on `today`, given its
+/// previous state if it was reviewed before. Cards rated "again" are due
+/// again the same day.
+pub fn schedule(
+    algorithm: SchedulingAlgorithm,
+    state: Option<&ReviewState>,
+    rating: Rating,
+    today: NaiveDate,
+    desired_retention: f64,
+) -> ReviewState {
+    let mut next = state.cloned().unwrap_or(ReviewState {
+        due: today,
+        interval: 0,
+        repetitions: 0,
+        lapses: 0,
+        last_review: today,
+        ease: INITIAL_EASE,
+        stability: 0.,
+        difficulty: 0.,
+    });
+    let elapsed_days = state.map_or(0, |state| {
+        (today - state.last_review).num_days().max(0) as u32
+    });
+
+    match algorithm {
+        SchedulingAlgorithm::Sm2 => sm2::schedule(&mut next, rating),
+        SchedulingAlgorithm::Fsrs => {
+            fsrs::schedule(&mut next, rating, elapsed_days, desired_retention)
+        }
+    }
+
+    if rating == Rating::Again {
+        if next.repetitions > 0 {
+            next.lapses += 1;
+        }
+        next.repetitions = 0;
+        next.interval = 0;
+    } else {
+        next.repetitions += 1;
+    }
+    next.last_review = today;
+    next.due = today + chrono::Days::new(next.interval as u64);
+    next
+}
+
+mod sm2 {
+    use super::*;
+
+    pub(super) fn schedule(state: &mut ReviewState, rating: Rating) {
+        let quality = match rating {
+            Rating::Again => 1.,
+            Rating::Hard => 3.,
+            Rating::Good => 4.,
+            Rating::Easy => 5.,
+        };
+        // Forgotten cards start over, keeping their ease.
+        if rating == Rating::Again {
+            return;
+        }
+        state.ease =
+            (state.ease + 0.1 - (5. - quality) * (0.08 + (5. - quality) * 0.02)).max(MINIMUM_EASE);
+        state.interval = match state.repetitions {
+            0 => 1,
+            1 => 6,
+            _ => (state.interval.max(1) as f64 * state.ease)
+                .round()
+                .min(MAXIMUM_INTERVAL) as u32,
+        };
+    }
+}
+
+mod fsrs {
+    use super::*;
+
+    /// The default FSRS-4.5 parameters.
+    const WEIGHTS: [f64; 17] = [
+        0.4872, 1.4003, 3.7145, 13.8206, 5.1618, 1.2298, 0.8975, 0.031, 1.6474, 0.1367, 1.0461,
+        2.1072, 0.0793, 0.3246, 1.587, 0.2272, 2.8755,
+    ];
+    const DECAY: f64 = -0.5;
+    const FACTOR: f64 = 19. / 81.;
+
+    pub(super) fn schedule(
+        state: &mut ReviewState,
+        rating: Rating,
+        elapsed_days: u32,
+        desired_retention: f64,
+    ) {
+        let grade = rating as u8 as f64;
+        if state.stability <= 0. {
+            if state.repetitions == 0 && state.lapses == 0 {
+                state.stability = WEIGHTS[rating as usize - 1];
+                state.difficulty = initial_difficulty(grade);
+                state.interval = next_interval(state.stability, desired_retention);
+                return;
+            }
+            // The card was reviewed with SM-2 before, so estimate its memory
+            // from the interval it got.
+            state.stability = (state.interval as f64).max(WEIGHTS[2]);
+            state.difficulty = initial_difficulty(3.);
+        }
+
+        let retrievability = (1. + FACTOR * elapsed_days as f64 / state.stability).powf(DECAY);
+        let difficulty = state.difficulty;
+        state.stability = if rating == Rating::Again {
+            (WEIGHTS[11]
+                * difficulty.powf(-WEIGHTS[12])
+                * ((state.stability + 1.).powf(WEIGHTS[13]) - 1.)
+                * (WEIGHTS[14] * (1. - retrievability)).exp())
+            .min(state.stability)
+        } else {
+            let hard_penalty = if rating == Rating::Hard {
+                WEIGHTS[15]
+            } else {
+                1.
+            };
+            let easy_bonus = if rating == Rating::Easy {
+                WEIGHTS[16]
+            } else {
+                1.
+            };
+            state.stability
+                * (1.
+                    + WEIGHTS[8].exp()
+                        * (11. - difficulty)
+                        * state.stability.powf(-WEIGHTS[9])
+                        * ((WEIGHTS[10] * (1. - retrievability)).exp() - 1.)
+                        * hard_penalty
+                        * easy_bonus)
+        };
+        // Difficulty moves with the rating and reverts towards the initial
+        // difficulty of a "good" card.
+        state.difficulty = (WEIGHTS[7] * initial_difficulty(3.)
+            + (1. - WEIGHTS[7]) * (difficulty - WEIGHTS[6] * (grade - 3.)))
+            .clamp(1., 10.);
+        state.interval = next_interval(state.stability, desired_retention);
+    }
+
+    fn initial_difficulty(grade: f64) -> f64 {
+        (WEIGHTS[4] - (grade - 3.) * WEIGHTS[5]).clamp(1., 10.)
+    }
+
+    /// The number of days until the chance of remembering a card drops to
+    /// the desired retention.
+    fn next_interval(stability: f64, desired_retention: f64) -> u32 {
+        let interval = stability / FACTOR * (desired_retention.powf(1. / DECAY) - 1.);
+        interval.round().clamp(1., MAXIMUM_INTERVAL) as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn review(
+        algorithm: SchedulingAlgorithm,
+        ratings: &[Rating],
+        start: NaiveDate,
+    ) -> Vec<ReviewState> {
+        let mut states: Vec<ReviewState> = Vec::new();
+        let mut today = start;
+        for rating in ratings {
+            let state = schedule(algorithm, states.last(), *rating, today, 0.9);
+            today = state.due;
+            states.push(state);
+        }
+        states
+    }
+
+    #[test]
+    fn test_sm2() {
+        let start = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let states = review(
+            SchedulingAlgorithm::Sm2,
+            &[
+                Rating::Good,
+                Rating::Good,
+                Rating::Good,
+                Rating::Easy,
+                Rating::Again,
+                Rating::Hard,
+            ],
+            start,
+        );
+        assert_eq!(
+            states
+                .iter()
+                .map(|state| (state.interval, state.repetitions, state.lapses))
+                .collect::<Vec<_>>(),
+            [
+                (1, 1, 0),
+                (6, 2, 0),
+                (15, 3, 0),
+                (39, 4, 0),
+                (0, 0, 1),
+                (1, 1, 1)
+            ]
+        );
+        assert_eq!(states[3].ease, 2.6);
+        assert_eq!(states[4].ease, 2.6);
+        assert_eq!(states[4].due, states[3].due);
+        assert_eq!(states[2].due, NaiveDate::from_ymd_opt(2025, 1, 23).unwrap());
+    }
+
+    #[test]
+    fn test_fsrs() {
+        let start = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let first_intervals = Rating::ALL
+            .map(|rating| schedule(SchedulingAlgorithm::Fsrs, None, rating, start, 0.9).interval);
+        assert_eq!(first_intervals, [0, 1, 4, 14]);
+
+        let states = review(
+            SchedulingAlgorithm::Fsrs,
+            &[Rating::Good, Rating::Good, Rating::Good, Rating::Again],
+            start,
+        );
+        let intervals = states
+            .iter()
+            .map(|state| state.interval)
+            .collect::<Vec<_>>();
+        assert!(intervals[0] < intervals[1] && intervals[1] < intervals[2]);
+        assert_eq!(intervals[3], 0);
+        assert!(states[3].stability < states[2].stability);
+        assert!(states[3].difficulty > states[2].difficulty);
+        assert_eq!(states[3].lapses, 1);
+
+        // A higher desired retention means reviewing sooner.
+        let strict = schedule(
+            SchedulingAlgorithm::Fsrs,
+            Some(&states[1]),
+            Rating::Good,
+            states[1].due,
+            0.95,
+        );
+        assert!(strict.interval < states[2].interval);
+    }
+}
@@ -1,12 +1,14 @@
-use crate::FakeFs;
+use crate::{FakeFs, Fs, RemoveOptions};
 use anyhow::{Context as _, Result, anyhow};
 use collections::{HashMap, HashSet};
 use futures::future::{self, BoxFuture};
 use git::{
     blame::Blame,
     repository::{
-        AskPassDelegate, Branch, CommitDetails, GitRepository, GitRepositoryCheckpoint,
-        PushOptions, Remote, RepoPath, ResetMode,
+        AskPassDelegate, Branch, CommitDetails, ConflictVersions, FileHistoryEntry, GitRepository,
+        GitRepositoryCheckpoint, GitWorktree, LogEntry, LogOptions, PushOptions, RebaseState,
+        RebaseTodoEntry, Remote, RepoPath, ResetMode, StashEntry, StashScope, Submodule,
+        SubmoduleStatus, WORK_DIRECTORY_REPO_PATH,
     },
     status::{FileStatus, GitStatus, StatusCode, TrackedStatus, UnmergedStatus},
 };
@@ -34,6 +36,23 @@ pub struct FakeGitRepositoryState {
     pub current_branch_name: Option<String>,
     pub branches: HashSet<String>,
     pub simulated_index_write_error_message: Option<String>,
+    /// Stash entries, most recent first.
+    pub stashes: Vec<FakeStash>,
+    /// The number of stashes ever pushed, which gives each one a unique sha.
+    pub stashes_pushed: usize,
+    pub rebase_state: Option<RebaseState>,
+    /// The repository's linked worktrees, not including the main one.
+    pub worktrees: Vec<GitWorktree>,
+    pub submodules: Vec<Submodule>,
+}
+
+#[derive(Debug, Clone)]
+pub struct FakeStash {
+    pub sha: String,
+    pub message: String,
+    /// The stashed index and working copy contents of each path, or `None`
+    /// where the path didn't exist.
+    pub contents: HashMap<RepoPath, (Option<String>, Option<String>)>,
 }
 
 impl FakeGitRepositoryState {
@@ -48,10 +67,25 @@ impl FakeGitRepositoryState {
             current_branch_name: Default::default(),
             branches: Default::default(),
             simulated_index_write_error_message: Default::default(),
+            stashes: Default::default(),
+            stashes_pushed: Default::default(),
+            rebase_state: Default::default(),
+            worktrees: Default::default(),
+            submodules: Default::default(),
         }
     }
 }
 
+impl FakeGitRepositoryState {
+    /// Returns the listed stash entry, if it's still at its index.
+    fn stash(&self, entry: &StashEntry) -> Result<&FakeStash> {
+        self.stashes
+            .get(entry.index)
+            .filter(|stash| stash.sha == entry.sha.as_ref())
+            .with_context(|| format!("stash@{{{}}} has changed since it was listed", entry.index))
+    }
+}
+
 impl FakeGitRepository {
     fn with_state<F, T>(&self, f: F) -> T
     where
@@ -76,6 +110,62 @@ impl FakeGitRepository {
         }
         .boxed()
     }
+
+    async fn write_working_copy(&self, contents: HashMap<RepoPath, Option<String>>) -> Result<()> {
+        let workdir_path = self.dot_git_path.parent().unwrap();
+        for (path, content) in contents {
+            let abs_path = workdir_path.join(&path);
+            match content {
+                Some(content) => self
+                    .fs
+                    .write_file_internal(abs_path, content.into(), false)?,
+                None => {
+                    self.fs
+                        .remove_file(
+                            &abs_path,
+                            RemoveOptions {
+                                ignore_if_not_exists: true,
+                                ..Default::default()
+                            },
+                        )
+                        .await?
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn unstash(&self, entry: StashEntry, drop: bool) -> BoxFuture<Result<()>> {
+        async move {
+            let stash = self
+                .with_state_async(true, move |state| {
+                    let index = entry.index;
+                    let stash = state.stash(&entry)?.clone();
+                    for (path, (index_content, _)) in &stash.contents {
+                        match index_content {
+                            Some(content) => {
+                                state.index_contents.insert(path.clone(), content.clone())
+                            }
+                            None => state.index_contents.remove(path),
+                        };
+                    }
+                    if drop {
+                        state.stashes.remove(index);
+                    }
+                    Ok(stash)
+                })
+                .await?;
+            self.write_working_copy(
+                stash
+                    .contents
+                    .into_iter()
+                    .map(|(path, (_, content))| (path, content))
+                    .collect(),
+            )
+            .await
+        }
+        .boxed()
+    }
 }
 
 impl GitRepository for FakeGitRepository {
@@ -111,6 +201,10 @@ impl GitRepository for FakeGitRepository {
         .boxed()
     }
 
+    fn load_conflict_versions(&self, _path: RepoPath) -> BoxFuture<Result<ConflictVersions>> {
+        unimplemented!()
+    }
+
     fn load_commit(
         &self,
         _commit: String,
@@ -149,6 +243,10 @@ impl GitRepository for FakeGitRepository {
         vec![]
     }
 
+    fn rebase_state(&self) -> Option<RebaseState> {
+        self.with_state(|state| state.rebase_state.clone())
+    }
+
     fn show(&self, _commit: String) -> BoxFuture<Result<CommitDetails>> {
         unimplemented!()
     }
@@ -414,6 +512,293 @@ impl GitRepository for FakeGitRepository {
         unimplemented!()
     }
 
+    fn continue_rebase(&self, _env: Arc<HashMap<String, String>>) -> BoxFuture<Result<()>> {
+        unimplemented!()
+    }
+
+    fn abort_rebase(&self, _env: Arc<HashMap<String, String>>) -> BoxFuture<Result<()>> {
+        unimplemented!()
+    }
+
+    fn rebase_commits(&self, _base: String) -> BoxFuture<Result<Vec<LogEntry>>> {
+        unimplemented!()
+    }
+
+    fn rebase_interactive(
+        &self,
+        _base: String,
+        _todo: Vec<RebaseTodoEntry>,
+        _env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<Result<()>> {
+        unimplemented!()
+    }
+
+    fn log(
+        &self,
+        _options: LogOptions,
+        _skip: usize,
+        _limit: usize,
+    ) -> BoxFuture<Result<Vec<LogEntry>>> {
+        unimplemented!()
+    }
+
+    fn stash_push(
+        &self,
+        scope: StashScope,
+        message: Option<String>,
+        _env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<Result<()>> {
+        async move {
+            let status = self.status(&[WORK_DIRECTORY_REPO_PATH.clone()]).await?;
+            let workdir_path = self.dot_git_path.parent().unwrap();
+            let working_copy = status
+                .entries
+                .iter()
+                .filter(|(path, status)| match &scope {
+                    StashScope::All => true,
+                    StashScope::Staged => status.staging().has_staged(),
+                    StashScope::Paths(paths) => paths.contains(path),
+                })
+                .map(|(path, _)| {
+                    let content = self
+                        .fs
+                        .read_file_sync(workdir_path.join(path))
+                        .ok()
+                        .and_then(|content| String::from_utf8(content).ok());
+                    (path.clone(), content)
+                })
+                .collect::<HashMap<_, _>>();
+            let reverted = self
+                .with_state_async(true, move |state| {
+                    if working_copy.is_empty() {
+                        return Err(anyhow!("No local changes to save"));
+                    }
+                    let mut contents = HashMap::default();
+                    let mut reverted = HashMap::default();
+                    for (path, worktree_content) in working_copy {
+                        let head_content = state.head_contents.get(&path).cloned();
+                        let index_content = state.index_contents.get(&path).cloned();
+                        // Only staged changes are stashed, so unstaged ones stay in the working copy.
+                        let stashed_content = if scope == StashScope::Staged {
+                            index_content.clone()
+                        } else {
+                            worktree_content.clone()
+                        };
+                        if scope != StashScope::Staged || worktree_content == index_content {
+                            reverted.insert(path.clone(), head_content.clone());
+                        }
+                        match head_content {
+                            Some(content) => state.index_contents.insert(path.clone(), content),
+                            None => state.index_contents.remove(&path),
+                        };
+                        contents.insert(path, (index_content, stashed_content));
+                    }
+                    let branch = state
+                        .current_branch_name
+                        .clone()
+                        .unwrap_or_else(|| "(no branch)".into());
+                    let message = match message {
+                        Some(message) => format!("On {branch}: {message}"),
+                        None => format!("WIP on {branch}"),
+                    };
+                    state.stashes_pushed += 1;
+                    let sha = format!("{:040x}", state.stashes_pushed);
+                    state.stashes.insert(
+                        0,
+                        FakeStash {
+                            sha,
+                            message,
+                            contents,
+                        },
+                    );
+                    Ok(reverted)
+                })
+                .await?;
+            self.write_working_copy(reverted).await
+        }
+        .boxed()
+    }
+
+    fn stash_entries(&self) -> BoxFuture<Result<Vec<StashEntry>>> {
+        self.with_state_async(false, |state| {
+            Ok(state
+                .stashes
+                .iter()
+                .enumerate()
+                .map(|(index, stash)| StashEntry {
+                    index,
+                    sha: stash.sha.clone().into(),
+                    message: stash.message.clone().into(),
+                    commit_timestamp: 0,
+                })
+                .collect())
+        })
+    }
+
+    fn stash_apply(
+        &self,
+        stash: StashEntry,
+        _env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<Result<()>> {
+        self.unstash(stash, false)
+    }
+
+    fn stash_pop(
+        &self,
+        stash: StashEntry,
+        _env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<Result<()>> {
+        self.unstash(stash, true)
+    }
+
+    fn stash_drop(
+        &self,
+        stash: StashEntry,
+        _env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<Result<()>> {
+        self.with_state_async(true, move |state| {
+            state.stash(&stash)?;
+            state.stashes.remove(stash.index);
+            Ok(())
+        })
+    }
+
+    fn worktrees(&self) -> BoxFuture<Result<Vec<GitWorktree>>> {
+        let workdir_path = self.dot_git_path.parent().unwrap().to_path_buf();
+        self.with_state_async(false, move |state| {
+            let main = GitWorktree {
+                path: workdir_path,
+                sha: None,
+                branch: state.current_branch_name.clone().map(Into::into),
+                is_main: true,
+                is_locked: false,
+                is_prunable: false,
+            };
+            Ok(std::iter::once(main)
+                .chain(state.worktrees.iter().cloned())
+                .collect())
+        })
+    }
+
+    fn create_worktree(
+        &self,
+        path: PathBuf,
+        branch: Option<String>,
+        _env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<Result<()>> {
+        let fs = self.fs.clone();
+        let worktree_path = path.clone();
+        let add_worktree = self.with_state_async(true, move |state| {
+            if state.worktrees.iter().any(|worktree| worktree.path == path) {
+                return Err(anyhow!("'{}' already exists", path.display()));
+            }
+            let branch = match branch {
+                Some(branch) => branch,
+                None => {
+                    let name = path
+                        .file_name()
+                        .context("worktree path has no file name")?
+                        .to_string_lossy()
+                        .to_string();
+                    state.branches.insert(name.clone());
+                    name
+                }
+            };
+            state.worktrees.push(GitWorktree {
+                path,
+                sha: None,
+                branch: Some(branch.into()),
+                is_main: false,
+                is_locked: false,
+                is_prunable: false,
+            });
+            Ok(())
+        });
+        async move {
+            add_worktree.await?;
+            fs.create_dir(&worktree_path).await
+        }
+        .boxed()
+    }
+
+    fn remove_worktree(
+        &self,
+        path: PathBuf,
+        _force: bool,
+        _env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<Result<()>> {
+        let fs = self.fs.clone();
+        let worktree_path = path.clone();
+        let remove_worktree = self.with_state_async(true, move |state| {
+            let ix = state
+                .worktrees
+                .iter()
+                .position(|worktree| worktree.path == path)
+                .with_context(|| format!("'{}' is not a working tree", path.display()))?;
+            state.worktrees.remove(ix);
+            Ok(())
+        });
+        async move {
+            remove_worktree.await?;
+            fs.remove_dir(
+                &worktree_path,
+                RemoveOptions {
+                    recursive: true,
+                    ignore_if_not_exists: true,
+                },
+            )
+            .await
+        }
+        .boxed()
+    }
+
+    fn submodules(&self) -> BoxFuture<Result<Vec<Submodule>>> {
+        self.with_state_async(false, |state| Ok(state.submodules.clone()))
+    }
+
+    fn submodule_init(
+        &self,
+        _paths: Vec<RepoPath>,
+        _env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<Result<()>> {
+        future::ready(Ok(())).boxed()
+    }
+
+    fn submodule_update(
+        &self,
+        paths: Vec<RepoPath>,
+        _env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<Result<()>> {
+        self.with_state_async(true, move |state| {
+            for submodule in &mut state.submodules {
+                if paths.is_empty() || paths.contains(&submodule.path) {
+                    submodule.status = SubmoduleStatus::UpToDate;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    fn submodule_sync(
+        &self,
+        _paths: Vec<RepoPath>,
+        _env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<Result<()>> {
+        future::ready(Ok(())).boxed()
+    }
+
+    fn file_history(&self, _path: RepoPath) -> BoxFuture<Result<Vec<FileHistoryEntry>>> {
+        unimplemented!()
+    }
+
+    fn load_text_at_commit(
+        &self,
+        _commit: String,
+        _path: RepoPath,
+    ) -> BoxFuture<Result<Option<String>>> {
+        unimplemented!()
+    }
+
     fn check_for_pushed_commit(&self) -> BoxFuture<Result<Vec<gpui::SharedString>>> {
         future::ready(Ok(Vec::new())).boxed()
     }
@@ -0,0 +1,145 @@
+use std::ops::Range;
+
+/// A region of a file with conflict markers, left by a merge, rebase or
+/// cherry-pick where both sides changed the same lines.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConflictRegion {
+    /// The whole region, including the markers.
+    pub range: Range<usize>,
+    /// The lines between the `<<<<<<<` marker and the next one.
+    pub ours: Range<usize>,
+    /// The common ancestor, when conflicts are written in the `diff3` style.
+    pub base: Option<Range<usize>>,
+    /// The lines between the `=======` and `>>>>>>>` markers.
+    pub theirs: Range<usize>,
+}
+
+/// Returns the conflict regions in `text`, ignoring incomplete ones.
+pub fn conflict_regions(text: &str) -> Vec<ConflictRegion> {
+    enum State {
+        Outside,
+        Ours {
+            start: usize,
+            ours_start: usize,
+        },
+        Base {
+            start: usize,
+            ours: Range<usize>,
+            base_start: usize,
+        },
+        Theirs {
+            start: usize,
+            ours: Range<usize>,
+            base: Option<Range<usize>>,
+            theirs_start: usize,
+        },
+    }
+
+    let mut regions = Vec::new();
+    let mut state = State::Outside;
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let line_start = offset;
+        offset += line.len();
+        state = match state {
+            State::Outside if is_marker(line, '<') => State::Ours {
+                start: line_start,
+                ours_start: offset,
+            },
+            State::Ours { start, ours_start } if is_marker(line, '|') => State::Base {
+                start,
+                ours: ours_start..line_start,
+                base_start: offset,
+            },
+            State::Ours { start, ours_start } if is_marker(line, '=') => State::Theirs {
+                start,
+                ours: ours_start..line_start,
+                base: None,
+                theirs_start: offset,
+            },
+            State::Base {
+                start,
+                ours,
+                base_start,
+            } if is_marker(line, '=') => State::Theirs {
+                start,
+                ours,
+                base: Some(base_start..line_start),
+                theirs_start: offset,
+            },
+            State::Theirs {
+                start,
+                ours,
+                base,
+                theirs_start,
+            } if is_marker(line, '>') => {
+                regions.push(ConflictRegion {
+                    range: start..offset,
+                    ours,
+                    base,
+                    theirs: theirs_start..line_start,
+                });
+                State::Outside
+            }
+            state => state,
+        };
+    }
+    regions
+}
+
+fn is_marker(line: &str, marker: char) -> bool {
+    let line = line.trim_end_matches(['\n', '\r']);
+    let rest = line.trim_start_matches(marker);
+    line.len() - rest.len() == 7 && (rest.is_empty() || (marker != '=' && rest.starts_with(' ')))
+}
+
+/// Whether `text` still has conflict markers in it.
+pub fn has_conflicts(text: &str) -> bool {
+    !conflict_regions(text).is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_conflict_regions() {
+        let text = concat!(
+            "fn main() {\n",
+            "<<<<<<< HEAD\n",
+            "    println!(\"ours\");\n",
+            "||||||| merged common ancestors\n",
+            "    println!(\"base\");\n",
+            "=======\n",
+            "    println!(\"theirs\");\n",
+            ">>>>>>> feature\n",
+            "}\n",
+            "<<<<<<< HEAD\r\n",
+            "=======\r\n",
+            "added\r\n",
+            ">>>>>>>\r\n",
+            "<<<<<<< HEAD\n",
+            "unfinished\n",
+        );
+        let regions = conflict_regions(text);
+        assert_eq!(regions.len(), 2);
+        assert_eq!(
+            &text[regions[0].range.clone()],
+            &text[12..text.find("}\n").unwrap()]
+        );
+        assert_eq!(&text[regions[0].ours.clone()], "    println!(\"ours\");\n");
+        assert_eq!(
+            regions[0].base.clone().map(|base| &text[base]),
+            Some("    println!(\"base\");\n")
+        );
+        assert_eq!(
+            &text[regions[0].theirs.clone()],
+            "    println!(\"theirs\");\n"
+        );
+        assert!(regions[1].ours.is_empty());
+        assert_eq!(regions[1].base, None);
+        assert_eq!(&text[regions[1].theirs.clone()], "added\r\n");
+        assert!(!has_conflicts("======= not a marker\n"));
+    }
+}
@@ -1,5 +1,6 @@
 pub mod blame;
 pub mod commit;
+pub mod conflict;
 mod hosting_provider;
 mod remote;
 pub mod repository;
@@ -39,6 +40,8 @@ actions!(
         // per-file
         StageFile,
         UnstageFile,
+        StashFile,
+        OpenMergeEditor,
         // repo-wide
         StageAll,
         UnstageAll,
@@ -53,6 +56,23 @@ actions!(
         ExpandCommitEditor,
         GenerateCommitMessage,
         Init,
+        StashAll,
+        StashStaged,
+        StashPop,
+        StashApply,
+        StashDrop,
+        ViewStash,
+        ViewHistory,
+        ViewFileHistory,
+        InteractiveRebase,
+        ContinueRebase,
+        AbortRebase,
+        AddWorktree,
+        OpenWorktree,
+        RemoveWorktree,
+        InitSubmodules,
+        UpdateSubmodules,
+        SyncSubmodules,
     ]
 );
 
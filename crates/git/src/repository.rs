@@ -149,6 +149,234 @@ pub struct CommitFile {
     pub new_text: Option<String>,
 }
 
+/// A commit that changed a file, as listed by [`GitRepository::file_history`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileHistoryEntry {
+    pub sha: SharedString,
+    pub subject: SharedString,
+    /// This is a unix timestamp
+    pub commit_timestamp: i64,
+    pub author_name: SharedString,
+    /// The file's path as of this commit, which differs from its current path
+    /// if it has been renamed since.
+    pub path: RepoPath,
+}
+
+/// A commit listed by [`GitRepository::log`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LogEntry {
+    pub sha: SharedString,
+    pub parents: Vec<SharedString>,
+    pub subject: SharedString,
+    pub author_name: SharedString,
+    pub author_email: SharedString,
+    /// This is a unix timestamp
+    pub commit_timestamp: i64,
+    /// The refs pointing at the commit, as `git log` decorates them, e.g.
+    /// `HEAD -> main`, `origin/main` or `tag: v1.0`.
+    pub refs: Vec<SharedString>,
+}
+
+/// Which commits [`GitRepository::log`] lists.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LogOptions {
+    /// Whether to list the commits of every branch, remote branch and tag,
+    /// rather than only those reachable from HEAD.
+    pub all_refs: bool,
+    /// Only list commits whose author contains this text.
+    pub author: Option<String>,
+    /// Only list commits whose message contains this text.
+    pub message: Option<String>,
+    /// Only list commits that changed this path.
+    pub path: Option<RepoPath>,
+}
+
+impl LogOptions {
+    /// Whether any of the filters are set, so that the listed commits'
+    /// parents may be missing from the log.
+    pub fn is_filtered(&self) -> bool {
+        self.author.is_some() || self.message.is_some() || self.path.is_some()
+    }
+}
+
+/// An entry in the repository's stash, as listed by [`GitRepository::stash_entries`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StashEntry {
+    /// The entry's position in the stash, where `0` is the most recent (`stash@{0}`).
+    pub index: usize,
+    pub sha: SharedString,
+    /// The stash's description, e.g. `On main: half-finished outline`.
+    pub message: SharedString,
+    /// This is a unix timestamp
+    pub commit_timestamp: i64,
+}
+
+/// Which changes [`GitRepository::stash_push`] should stash.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StashScope {
+    /// All changes, including untracked files.
+    All,
+    /// Only the changes that have been staged.
+    Staged,
+    /// All changes to the given paths, including untracked files.
+    Paths(Vec<RepoPath>),
+}
+
+/// What an interactive rebase does with a commit.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum RebaseAction {
+    /// Replays the commit as it is.
+    #[default]
+    Pick,
+    /// Replays the commit with a new message.
+    Reword,
+    /// Replays the commit, then stops so that it can be amended.
+    Edit,
+    /// Combines the commit into the one before it, keeping both messages.
+    Squash,
+    /// Combines the commit into the one before it, keeping only that one's message.
+    Fixup,
+    /// Leaves the commit out.
+    Drop,
+}
+
+impl RebaseAction {
+    pub const ALL: [RebaseAction; 6] = [
+        RebaseAction::Pick,
+        RebaseAction::Reword,
+        RebaseAction::Edit,
+        RebaseAction::Squash,
+        RebaseAction::Fixup,
+        RebaseAction::Drop,
+    ];
+
+    /// The command for the action in a rebase's todo list.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RebaseAction::Pick => "pick",
+            RebaseAction::Reword => "reword",
+            RebaseAction::Edit => "edit",
+            RebaseAction::Squash => "squash",
+            RebaseAction::Fixup => "fixup",
+            RebaseAction::Drop => "drop",
+        }
+    }
+}
+
+/// A commit in the todo list of [`GitRepository::rebase_interactive`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RebaseTodoEntry {
+    pub action: RebaseAction,
+    pub sha: SharedString,
+    /// The commit's new message, when its action is [`RebaseAction::Reword`].
+    pub message: Option<String>,
+}
+
+/// A rebase that has stopped partway through, on conflicts or to edit a commit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RebaseState {
+    /// The branch being rebased, or `None` if HEAD was detached when the rebase started.
+    pub branch: Option<SharedString>,
+    /// The commit the branch is being rebased onto.
+    pub onto: SharedString,
+    /// The commit the rebase stopped at.
+    pub stopped_sha: Option<SharedString>,
+    /// The number of todo list steps that have been started, out of `total`.
+    pub step: usize,
+    pub total: usize,
+}
+
+impl RebaseState {
+    /// Reads the state of the rebase in progress, if any, from a repository's git directory.
+    pub fn read(git_dir: &Path) -> Option<Self> {
+        let read = |path: PathBuf| {
+            std::fs::read_to_string(path)
+                .ok()
+                .map(|contents| contents.trim().to_string())
+        };
+        let (dir, step_file, total_file) = if git_dir.join("rebase-merge").is_dir() {
+            (git_dir.join("rebase-merge"), "msgnum", "end")
+        } else if git_dir.join("rebase-apply/rebasing").exists() {
+            // `git am` uses the same directory, without the `rebasing` file.
+            (git_dir.join("rebase-apply"), "next", "last")
+        } else {
+            return None;
+        };
+        let branch = read(dir.join("head-name"))
+            .filter(|name| name != "detached HEAD")
+            .map(|name| {
+                name.strip_prefix("refs/heads/")
+                    .unwrap_or(&name)
+                    .to_string()
+                    .into()
+            });
+        let stopped_sha = read(dir.join("stopped-sha"))
+            .or_else(|| read(git_dir.join("REBASE_HEAD")))
+            .map(SharedString::from);
+        Some(RebaseState {
+            branch,
+            onto: read(dir.join("onto")).unwrap_or_default().into(),
+            stopped_sha,
+            step: read(dir.join(step_file))
+                .and_then(|step| step.parse().ok())
+                .unwrap_or(0),
+            total: read(dir.join(total_file))
+                .and_then(|total| total.parse().ok())
+                .unwrap_or(0),
+        })
+    }
+}
+
+/// The versions of a conflicted file recorded in the index, each `None` if
+/// the file doesn't exist on that side.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConflictVersions {
+    /// The version in the common ancestor.
+    pub base: Option<String>,
+    /// The version on the current branch.
+    pub ours: Option<String>,
+    /// The version being merged in.
+    pub theirs: Option<String>,
+}
+
+/// A working tree of a repository, as listed by `git worktree list`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GitWorktree {
+    pub path: PathBuf,
+    /// The commit checked out in the worktree, or `None` if there are no commits yet.
+    pub sha: Option<SharedString>,
+    /// The branch checked out in the worktree, or `None` if HEAD is detached.
+    pub branch: Option<SharedString>,
+    /// Whether this is the repository's main worktree, rather than a linked one.
+    pub is_main: bool,
+    pub is_locked: bool,
+    /// Whether the worktree's directory is gone, so that it can be pruned.
+    pub is_prunable: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SubmoduleStatus {
+    /// The submodule hasn't been initialized and checked out.
+    Uninitialized,
+    /// The submodule has the commit recorded in the superproject checked out.
+    UpToDate,
+    /// The submodule has a different commit checked out than the one recorded
+    /// in the superproject.
+    Modified,
+    /// The submodule's recorded commit has merge conflicts.
+    Conflicted,
+}
+
+/// A submodule of a repository, as listed by `git submodule status`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Submodule {
+    pub path: RepoPath,
+    /// The commit checked out in the submodule, or the one recorded in the
+    /// superproject if it isn't initialized.
+    pub sha: SharedString,
+    pub status: SubmoduleStatus,
+}
+
 impl CommitDetails {
     pub fn short_sha(&self) -> SharedString {
         self.sha[..SHORT_SHA_LENGTH].to_string().into()
@@ -182,6 +410,9 @@ pub trait GitRepository: Send + Sync {
     /// Also returns `None` for symlinks.
     fn load_committed_text(&self, path: RepoPath) -> BoxFuture<Option<String>>;
 
+    /// Returns the base, ours and theirs versions of a file with merge conflicts.
+    fn load_conflict_versions(&self, path: RepoPath) -> BoxFuture<Result<ConflictVersions>>;
+
     fn set_index_text(
         &self,
         path: RepoPath,
@@ -197,6 +428,9 @@ pub trait GitRepository: Send + Sync {
 
     fn merge_head_shas(&self) -> Vec<String>;
 
+    /// Returns the state of the rebase in progress, if any.
+    fn rebase_state(&self) -> Option<RebaseState>;
+
     fn merge_message(&self) -> BoxFuture<Option<String>>;
 
     fn status(&self, path_prefixes: &[RepoPath]) -> BoxFuture<Result<GitStatus>>;
@@ -295,6 +529,135 @@ pub trait GitRepository: Send + Sync {
 
     fn get_remotes(&self, branch_name: Option<String>) -> BoxFuture<Result<Vec<Remote>>>;
 
+    /// Continues a rebase that stopped on conflicts, once they've been resolved and staged.
+    fn continue_rebase(&self, env: Arc<HashMap<String, String>>) -> BoxFuture<Result<()>>;
+
+    /// Stops a rebase in progress, returning the branch to where it was before the rebase.
+    fn abort_rebase(&self, env: Arc<HashMap<String, String>>) -> BoxFuture<Result<()>>;
+
+    /// Returns the commits that an interactive rebase onto `base` would replay, oldest first.
+    fn rebase_commits(&self, base: String) -> BoxFuture<Result<Vec<LogEntry>>>;
+
+    /// Rebases the current branch onto `base`, replaying the commits in `todo` in order.
+    ///
+    /// Returns an error if the rebase stops on conflicts, leaving it in progress to be
+    /// continued or aborted. Stopping at a commit to be edited isn't an error.
+    fn rebase_interactive(
+        &self,
+        base: String,
+        todo: Vec<RebaseTodoEntry>,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<Result<()>>;
+
+    /// Returns up to `limit` commits, newest first, after skipping the first `skip`.
+    ///
+    /// Successive pages of a log can be loaded by calling this with increasing
+    /// values of `skip`, so that large histories don't have to be loaded at once.
+    /// Loading the page right after the previous one continues reading where it
+    /// stopped, rather than walking the skipped commits again.
+    fn log(
+        &self,
+        options: LogOptions,
+        skip: usize,
+        limit: usize,
+    ) -> BoxFuture<Result<Vec<LogEntry>>>;
+
+    /// Stashes changes and reverts them in the index and working copy.
+    fn stash_push(
+        &self,
+        scope: StashScope,
+        message: Option<String>,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<Result<()>>;
+
+    /// Returns the entries in the stash, most recent first.
+    fn stash_entries(&self) -> BoxFuture<Result<Vec<StashEntry>>>;
+
+    /// Applies a stash entry to the working copy, keeping it in the stash.
+    ///
+    /// The entries' indices shift as stashes are pushed and dropped, so this
+    /// fails if the entry is no longer at its listed index.
+    fn stash_apply(
+        &self,
+        stash: StashEntry,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<Result<()>>;
+
+    /// Applies a stash entry to the working copy and removes it from the stash.
+    ///
+    /// The entry is kept if applying it causes conflicts. Like
+    /// [`GitRepository::stash_apply`], this fails if the entry has moved.
+    fn stash_pop(
+        &self,
+        stash: StashEntry,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<Result<()>>;
+
+    /// Removes a stash entry without applying it, failing if the entry has moved.
+    fn stash_drop(
+        &self,
+        stash: StashEntry,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<Result<()>>;
+
+    /// Returns the repository's worktrees, starting with the main one.
+    fn worktrees(&self) -> BoxFuture<Result<Vec<GitWorktree>>>;
+
+    /// Creates a linked worktree at `path` with `branch` checked out, or with a
+    /// new branch named after the worktree's directory.
+    fn create_worktree(
+        &self,
+        path: PathBuf,
+        branch: Option<String>,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<Result<()>>;
+
+    /// Removes the linked worktree at `path`. Unless `force` is set, this fails
+    /// if the worktree has uncommitted changes.
+    fn remove_worktree(
+        &self,
+        path: PathBuf,
+        force: bool,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<Result<()>>;
+
+    /// Returns the repository's submodules, not including nested ones.
+    fn submodules(&self) -> BoxFuture<Result<Vec<Submodule>>>;
+
+    /// Registers the submodules at `paths`, or all of them if it's empty, in
+    /// the repository's config.
+    fn submodule_init(
+        &self,
+        paths: Vec<RepoPath>,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<Result<()>>;
+
+    /// Checks out the commits recorded in the superproject in the submodules
+    /// at `paths`, or all initialized ones if it's empty.
+    fn submodule_update(
+        &self,
+        paths: Vec<RepoPath>,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<Result<()>>;
+
+    /// Updates the remote URLs of the submodules at `paths`, or all of them if
+    /// it's empty, to match `.gitmodules`.
+    fn submodule_sync(
+        &self,
+        paths: Vec<RepoPath>,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<Result<()>>;
+
+    /// Returns the commits that changed the file at `path`, newest first, following renames.
+    fn file_history(&self, path: RepoPath) -> BoxFuture<Result<Vec<FileHistoryEntry>>>;
+
+    /// Returns the contents of a file as of the given commit, or None if it didn't exist in that commit.
+    fn load_text_at_commit(
+        &self,
+        commit: String,
+        path: RepoPath,
+    ) -> BoxFuture<Result<Option<String>>>;
+
     /// returns a list of remote branches that contain HEAD
     fn check_for_pushed_commit(&self) -> BoxFuture<Result<Vec<SharedString>>>;
 
@@ -346,6 +709,8 @@ pub struct RealGitRepository {
     pub repository: Arc<Mutex<git2::Repository>>,
     pub git_binary_path: PathBuf,
     executor: BackgroundExecutor,
+    /// The `git log` process that the last page of the log was read from.
+    log_stream: Arc<smol::lock::Mutex<Option<LogStream>>>,
 }
 
 impl RealGitRepository {
@@ -360,6 +725,7 @@ impl RealGitRepository {
             repository: Arc::new(Mutex::new(repository)),
             git_binary_path: git_binary_path.unwrap_or_else(|| PathBuf::from("git")),
             executor,
+            log_stream: Default::default(),
         })
     }
 
@@ -370,6 +736,76 @@ impl RealGitRepository {
             .context("failed to read git work directory")
             .map(Path::to_path_buf)
     }
+
+    fn run_git_command(
+        &self,
+        args: Vec<OsString>,
+        env: Arc<HashMap<String, String>>,
+        description: &'static str,
+    ) -> BoxFuture<Result<()>> {
+        let working_directory = self.working_directory();
+        let git_binary_path = self.git_binary_path.clone();
+        self.executor
+            .spawn(async move {
+                let output = new_smol_command(&git_binary_path)
+                    .current_dir(&working_directory?)
+                    .envs(env.iter())
+                    .args(args)
+                    .output()
+                    .await?;
+                if !output.status.success() {
+                    return Err(anyhow!(
+                        "Failed to {description}:\n{}",
+                        String::from_utf8_lossy(&output.stderr)
+                    ));
+                }
+                Ok(())
+            })
+            .boxed()
+    }
+
+    /// Runs `git stash <command>` on a stash entry, after checking that its
+    /// index still refers to the same stash.
+    fn run_stash_command(
+        &self,
+        command: &'static str,
+        stash: StashEntry,
+        env: Arc<HashMap<String, String>>,
+        description: &'static str,
+    ) -> BoxFuture<Result<()>> {
+        let working_directory = self.working_directory();
+        let git_binary_path = self.git_binary_path.clone();
+        self.executor
+            .spawn(async move {
+                let working_directory = working_directory?;
+                let reference = format!("stash@{{{}}}", stash.index);
+                let output = new_smol_command(&git_binary_path)
+                    .current_dir(&working_directory)
+                    .args(["rev-parse", "--verify", "--quiet", &reference])
+                    .output()
+                    .await?;
+                if String::from_utf8_lossy(&output.stdout).trim() != stash.sha.as_ref() {
+                    return Err(anyhow!(
+                        "Failed to {description}: the stash has changed since it was listed"
+                    ));
+                }
+
+                let output = new_smol_command(&git_binary_path)
+                    .current_dir(&working_directory)
+                    .envs(env.iter())
+                    .args(["stash", command, &reference])
+                    .output()
+                    .await?;
+                if !output.status.success() {
+                    return Err(anyhow!(
+                        "Failed to {description}:\n{}",
+                        String::from_utf8_lossy(&output.stderr)
+                    ));
+                }
+                Ok(())
+            })
+            .boxed()
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -436,6 +872,8 @@ impl GitRepository for RealGitRepository {
                     "-z",
                     "--no-renames",
                     "--name-status",
+                    // Stashes are merge commits; show them relative to the commit they were made on.
+                    "--diff-merges=first-parent",
                 ])
                 .arg(&commit)
                 .stdin(Stdio::null())
@@ -446,8 +884,25 @@ impl GitRepository for RealGitRepository {
 
             let show_stdout = String::from_utf8_lossy(&show_output.stdout);
             let mut lines = show_stdout.split('\n');
-            let parent_sha = lines.next().unwrap().trim().trim_end_matches('\0');
-            let changes = parse_git_diff_name_status(lines.next().unwrap_or(""));
+            let parents = lines
+                .next()
+                .unwrap()
+                .trim()
+                .trim_end_matches('\0')
+                .split(' ')
+                .collect::<Vec<_>>();
+            let parent_sha = parents.first().copied().unwrap_or_default();
+            let mut changes = parse_git_diff_name_status(lines.next().unwrap_or(""))
+                .map(|(path, status_code)| (path.to_path_buf(), status_code, commit.as_str()))
+                .collect::<Vec<_>>();
+
+            // Stashes pushed with `--include-untracked` keep the untracked files
+            // in a third parent, which the first-parent diff leaves out.
+            if let Some(untracked_sha) = parents.get(2).copied() {
+                for path in stash_untracked_files(&working_directory, untracked_sha)? {
+                    changes.push((path, StatusCode::Added, untracked_sha));
+                }
+            }
 
             let mut cat_file_process = util::command::new_std_command("git")
                 .current_dir(&working_directory)
@@ -464,14 +919,14 @@ impl GitRepository for RealGitRepository {
             let mut stdout = BufReader::new(cat_file_process.stdout.take().unwrap());
             let mut info_line = String::new();
             let mut newline = [b'\0'];
-            for (path, status_code) in changes {
+            for (path, status_code, new_sha) in changes {
                 match status_code {
                     StatusCode::Modified => {
-                        writeln!(&mut stdin, "{commit}:{}", path.display())?;
+                        writeln!(&mut stdin, "{new_sha}:{}", path.display())?;
                         writeln!(&mut stdin, "{parent_sha}:{}", path.display())?;
                     }
                     StatusCode::Added => {
-                        writeln!(&mut stdin, "{commit}:{}", path.display())?;
+                        writeln!(&mut stdin, "{new_sha}:{}", path.display())?;
                     }
                     StatusCode::Deleted => {
                         writeln!(&mut stdin, "{parent_sha}:{}", path.display())?;
@@ -512,7 +967,7 @@ impl GitRepository for RealGitRepository {
                 }
 
                 files.push(CommitFile {
-                    path: path.into(),
+                    path: path.as_path().into(),
                     old_text,
                     new_text,
                 })
@@ -618,6 +1073,39 @@ impl GitRepository for RealGitRepository {
             .boxed()
     }
 
+    fn load_conflict_versions(&self, path: RepoPath) -> BoxFuture<Result<ConflictVersions>> {
+        let repo = self.repository.clone();
+        self.executor
+            .spawn(async move {
+                check_path_to_repo_path_errors(&path)?;
+                let repo = repo.lock();
+                let mut index = repo.index()?;
+                index.read(false)?;
+
+                let load_stage = |stage: i32| -> Result<Option<String>> {
+                    let Some(entry) = index.get_path(&path, stage) else {
+                        return Ok(None);
+                    };
+                    let content = repo.find_blob(entry.id)?.content().to_owned();
+                    Ok(Some(String::from_utf8(content)?))
+                };
+
+                const STAGE_BASE: i32 = 1;
+                const STAGE_OURS: i32 = 2;
+                const STAGE_THEIRS: i32 = 3;
+                let versions = ConflictVersions {
+                    base: load_stage(STAGE_BASE)?,
+                    ours: load_stage(STAGE_OURS)?,
+                    theirs: load_stage(STAGE_THEIRS)?,
+                };
+                if versions.ours.is_none() && versions.theirs.is_none() {
+                    return Err(anyhow!("{} has no merge conflicts", path.display()));
+                }
+                Ok(versions)
+            })
+            .boxed()
+    }
+
     fn load_committed_text(&self, path: RepoPath) -> BoxFuture<Option<String>> {
         let repo = self.repository.clone();
         self.executor
@@ -732,6 +1220,10 @@ impl GitRepository for RealGitRepository {
         shas
     }
 
+    fn rebase_state(&self) -> Option<RebaseState> {
+        RebaseState::read(&self.path())
+    }
+
     fn merge_message(&self) -> BoxFuture<Option<String>> {
         let path = self.path().join("MERGE_MSG");
         async move { std::fs::read_to_string(&path).ok() }.boxed()
@@ -1126,59 +1618,431 @@ impl GitRepository for RealGitRepository {
             .boxed()
     }
 
-    fn check_for_pushed_commit(&self) -> BoxFuture<Result<Vec<SharedString>>> {
+    fn continue_rebase(&self, env: Arc<HashMap<String, String>>) -> BoxFuture<Result<()>> {
         let working_directory = self.working_directory();
         let git_binary_path = self.git_binary_path.clone();
         self.executor
             .spawn(async move {
-                let working_directory = working_directory?;
-                let git_cmd = async |args: &[&str]| -> Result<String> {
-                    let output = new_smol_command(&git_binary_path)
-                        .current_dir(&working_directory)
-                        .args(args)
-                        .output()
-                        .await?;
-                    if output.status.success() {
-                        Ok(String::from_utf8(output.stdout)?)
-                    } else {
-                        Err(anyhow!(String::from_utf8_lossy(&output.stderr).to_string()))
-                    }
-                };
-
-                let head = git_cmd(&["rev-parse", "HEAD"])
-                    .await
-                    .context("Failed to get HEAD")?
-                    .trim()
-                    .to_owned();
-
-                let mut remote_branches = vec![];
-                let mut add_if_matching = async |remote_head: &str| {
-                    if let Ok(merge_base) = git_cmd(&["merge-base", &head, remote_head]).await {
-                        if merge_base.trim() == head {
-                            if let Some(s) = remote_head.strip_prefix("refs/remotes/") {
-                                remote_branches.push(s.to_owned().into());
-                            }
-                        }
-                    }
-                };
-
-                // check the main branch of each remote
-                let remotes = git_cmd(&["remote"])
-                    .await
-                    .context("Failed to get remotes")?;
-                for remote in remotes.lines() {
-                    if let Ok(remote_head) =
-                        git_cmd(&["symbolic-ref", &format!("refs/remotes/{remote}/HEAD")]).await
-                    {
-                        add_if_matching(remote_head.trim()).await;
-                    }
+                // Keep the messages of the commits being replayed instead of opening an editor.
+                let output = new_smol_command(&git_binary_path)
+                    .current_dir(&working_directory?)
+                    .envs(env.iter())
+                    .args(["-c", "core.editor=true", "rebase", "--continue"])
+                    .output()
+                    .await?;
+                if !output.status.success() {
+                    return Err(anyhow!(
+                        "Failed to continue rebase:\n{}",
+                        String::from_utf8_lossy(&output.stderr)
+                    ));
                 }
+                Ok(())
+            })
+            .boxed()
+    }
 
-                // ... and the remote branch that the checked-out one is tracking
-                if let Ok(remote_head) =
-                    git_cmd(&["rev-parse", "--symbolic-full-name", "@{u}"]).await
-                {
-                    add_if_matching(remote_head.trim()).await;
+    fn abort_rebase(&self, env: Arc<HashMap<String, String>>) -> BoxFuture<Result<()>> {
+        let working_directory = self.working_directory();
+        let git_binary_path = self.git_binary_path.clone();
+        self.executor
+            .spawn(async move {
+                let output = new_smol_command(&git_binary_path)
+                    .current_dir(&working_directory?)
+                    .envs(env.iter())
+                    .args(["rebase", "--abort"])
+                    .output()
+                    .await?;
+                if !output.status.success() {
+                    return Err(anyhow!(
+                        "Failed to abort rebase:\n{}",
+                        String::from_utf8_lossy(&output.stderr)
+                    ));
+                }
+                Ok(())
+            })
+            .boxed()
+    }
+
+    fn rebase_commits(&self, base: String) -> BoxFuture<Result<Vec<LogEntry>>> {
+        let working_directory = self.working_directory();
+        let git_binary_path = self.git_binary_path.clone();
+        self.executor
+            .spawn(async move {
+                // Merge commits are left out, as `git rebase` leaves them out by default.
+                let output = new_smol_command(&git_binary_path)
+                    .current_dir(&working_directory?)
+                    .args([
+                        "--no-optional-locks",
+                        "log",
+                        "--reverse",
+                        "--topo-order",
+                        "--no-merges",
+                        "--decorate=short",
+                        LOG_FORMAT,
+                    ])
+                    .arg(format!("{base}..HEAD"))
+                    .arg("--")
+                    .output()
+                    .await?;
+                if !output.status.success() {
+                    return Err(anyhow!(
+                        "Failed to list commits to rebase:\n{}",
+                        String::from_utf8_lossy(&output.stderr)
+                    ));
+                }
+                parse_log(&String::from_utf8_lossy(&output.stdout))
+            })
+            .boxed()
+    }
+
+    fn rebase_interactive(
+        &self,
+        base: String,
+        todo: Vec<RebaseTodoEntry>,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<Result<()>> {
+        let working_directory = self.working_directory();
+        let git_binary_path = self.git_binary_path.clone();
+        self.executor
+            .spawn(async move {
+                use std::io::Write as _;
+
+                let todo = rebase_todo(&todo, &git_binary_path)?;
+                let mut todo_file = tempfile::NamedTempFile::new()?;
+                todo_file.write_all(todo.as_bytes())?;
+                // Git hands its own todo list to the sequence editor, which
+                // replaces it with ours.
+                let sequence_editor =
+                    format!("cp {}", shell_quote(&todo_file.path().to_string_lossy()));
+                let output = new_smol_command(&git_binary_path)
+                    .current_dir(&working_directory?)
+                    .envs(env.iter())
+                    .env("GIT_SEQUENCE_EDITOR", sequence_editor)
+                    // Keep the combined messages of squashed commits instead of opening an editor.
+                    .args([
+                        "-c",
+                        "core.editor=true",
+                        "rebase",
+                        "--interactive",
+                        "--no-autosquash",
+                    ])
+                    .arg(&base)
+                    .output()
+                    .await?;
+                if !output.status.success() {
+                    return Err(anyhow!(
+                        "Failed to rebase:\n{}",
+                        String::from_utf8_lossy(&output.stderr)
+                    ));
+                }
+                Ok(())
+            })
+            .boxed()
+    }
+
+    fn log(
+        &self,
+        options: LogOptions,
+        skip: usize,
+        limit: usize,
+    ) -> BoxFuture<Result<Vec<LogEntry>>> {
+        let working_directory = self.working_directory();
+        let git_binary_path = self.git_binary_path.clone();
+        let log_stream = self.log_stream.clone();
+        self.executor
+            .spawn(async move {
+                let mut log_stream = log_stream.lock().await;
+                // The first page is always read from a new process, so that a
+                // reloaded log includes new commits.
+                let stream = match log_stream.take() {
+                    Some(stream)
+                        if skip > 0 && stream.options == options && stream.position == skip =>
+                    {
+                        stream
+                    }
+                    _ => LogStream::spawn(&git_binary_path, &working_directory?, options, skip)?,
+                };
+                let stream = log_stream.insert(stream);
+                let page = stream.next_page(limit).await;
+                if !page.as_ref().is_ok_and(|page| page.len() == limit) {
+                    *log_stream = None;
+                }
+                page
+            })
+            .boxed()
+    }
+
+    fn stash_push(
+        &self,
+        scope: StashScope,
+        message: Option<String>,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<Result<()>> {
+        let mut args = vec!["stash".into(), "push".into()];
+        if let Some(message) = message {
+            args.extend(["--message".into(), message.into()]);
+        }
+        match scope {
+            StashScope::All => args.push("--include-untracked".into()),
+            StashScope::Staged => args.push("--staged".into()),
+            StashScope::Paths(paths) => {
+                args.extend(["--include-untracked".into(), "--".into()]);
+                args.extend(paths.iter().map(|path| path.as_os_str().to_owned()));
+            }
+        }
+        self.run_git_command(args, env, "stash changes")
+    }
+
+    fn stash_entries(&self) -> BoxFuture<Result<Vec<StashEntry>>> {
+        let working_directory = self.working_directory();
+        let git_binary_path = self.git_binary_path.clone();
+        self.executor
+            .spawn(async move {
+                let output = new_smol_command(&git_binary_path)
+                    .current_dir(&working_directory?)
+                    .args([
+                        "--no-optional-locks",
+                        "stash",
+                        "list",
+                        "--format=%H%x00%ct%x00%gs",
+                    ])
+                    .output()
+                    .await?;
+                if !output.status.success() {
+                    return Err(anyhow!(
+                        "Failed to list stashes:\n{}",
+                        String::from_utf8_lossy(&output.stderr)
+                    ));
+                }
+                parse_stash_list(&String::from_utf8_lossy(&output.stdout))
+            })
+            .boxed()
+    }
+
+    fn stash_apply(
+        &self,
+        stash: StashEntry,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<Result<()>> {
+        self.run_stash_command("apply", stash, env, "apply stash")
+    }
+
+    fn stash_pop(
+        &self,
+        stash: StashEntry,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<Result<()>> {
+        self.run_stash_command("pop", stash, env, "pop stash")
+    }
+
+    fn stash_drop(
+        &self,
+        stash: StashEntry,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<Result<()>> {
+        self.run_stash_command("drop", stash, env, "drop stash")
+    }
+
+    fn worktrees(&self) -> BoxFuture<Result<Vec<GitWorktree>>> {
+        let working_directory = self.working_directory();
+        let git_binary_path = self.git_binary_path.clone();
+        self.executor
+            .spawn(async move {
+                let output = new_smol_command(&git_binary_path)
+                    .current_dir(&working_directory?)
+                    .args(["--no-optional-locks", "worktree", "list", "--porcelain"])
+                    .output()
+                    .await?;
+                if !output.status.success() {
+                    return Err(anyhow!(
+                        "Failed to list worktrees:\n{}",
+                        String::from_utf8_lossy(&output.stderr)
+                    ));
+                }
+                parse_worktree_list(&String::from_utf8_lossy(&output.stdout))
+            })
+            .boxed()
+    }
+
+    fn create_worktree(
+        &self,
+        path: PathBuf,
+        branch: Option<String>,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<Result<()>> {
+        let mut args: Vec<OsString> = vec!["worktree".into(), "add".into(), path.into()];
+        args.extend(branch.map(OsString::from));
+        self.run_git_command(args, env, "create worktree")
+    }
+
+    fn remove_worktree(
+        &self,
+        path: PathBuf,
+        force: bool,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<Result<()>> {
+        let mut args: Vec<OsString> = vec!["worktree".into(), "remove".into()];
+        if force {
+            args.push("--force".into());
+        }
+        args.push(path.into());
+        self.run_git_command(args, env, "remove worktree")
+    }
+
+    fn submodules(&self) -> BoxFuture<Result<Vec<Submodule>>> {
+        let working_directory = self.working_directory();
+        let git_binary_path = self.git_binary_path.clone();
+        self.executor
+            .spawn(async move {
+                let working_directory = working_directory?;
+                // Avoids running git on every scan of repositories without submodules.
+                if !working_directory.join(".gitmodules").exists() {
+                    return Ok(Vec::new());
+                }
+                let output = new_smol_command(&git_binary_path)
+                    .current_dir(&working_directory)
+                    .args(["--no-optional-locks", "submodule", "status"])
+                    .output()
+                    .await?;
+                if !output.status.success() {
+                    return Err(anyhow!(
+                        "Failed to list submodules:\n{}",
+                        String::from_utf8_lossy(&output.stderr)
+                    ));
+                }
+                parse_submodule_status(&String::from_utf8_lossy(&output.stdout))
+            })
+            .boxed()
+    }
+
+    fn submodule_init(
+        &self,
+        paths: Vec<RepoPath>,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<Result<()>> {
+        let args = submodule_args(&["init"], &paths);
+        self.run_git_command(args, env, "initialize submodules")
+    }
+
+    fn submodule_update(
+        &self,
+        paths: Vec<RepoPath>,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<Result<()>> {
+        let args = submodule_args(&["update", "--recursive"], &paths);
+        self.run_git_command(args, env, "update submodules")
+    }
+
+    fn submodule_sync(
+        &self,
+        paths: Vec<RepoPath>,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<Result<()>> {
+        let args = submodule_args(&["sync", "--recursive"], &paths);
+        self.run_git_command(args, env, "sync submodules")
+    }
+
+    fn file_history(&self, path: RepoPath) -> BoxFuture<Result<Vec<FileHistoryEntry>>> {
+        let working_directory = self.working_directory();
+        let git_binary_path = self.git_binary_path.clone();
+        self.executor
+            .spawn(async move {
+                let output = new_smol_command(&git_binary_path)
+                    .current_dir(&working_directory?)
+                    .args([
+                        "--no-optional-locks",
+                        "log",
+                        "--follow",
+                        "--name-only",
+                        "--format=%x00%H%x00%ct%x00%an%x00%s",
+                        "--",
+                    ])
+                    .arg(path.as_ref())
+                    .output()
+                    .await?;
+                if !output.status.success() {
+                    return Err(anyhow!(
+                        "Failed to load file history:\n{}",
+                        String::from_utf8_lossy(&output.stderr)
+                    ));
+                }
+                parse_file_history(&String::from_utf8_lossy(&output.stdout))
+            })
+            .boxed()
+    }
+
+    fn load_text_at_commit(
+        &self,
+        commit: String,
+        path: RepoPath,
+    ) -> BoxFuture<Result<Option<String>>> {
+        let repo = self.repository.clone();
+        self.executor
+            .spawn(async move {
+                let repo = repo.lock();
+                let tree = repo.revparse_single(&commit)?.peel_to_tree()?;
+                let Ok(entry) = tree.get_path(&path) else {
+                    return Ok(None);
+                };
+                let content = repo.find_blob(entry.id())?.content().to_owned();
+                Ok(Some(String::from_utf8(content)?))
+            })
+            .boxed()
+    }
+
+    fn check_for_pushed_commit(&self) -> BoxFuture<Result<Vec<SharedString>>> {
+        let working_directory = self.working_directory();
+        let git_binary_path = self.git_binary_path.clone();
+        self.executor
+            .spawn(async move {
+                let working_directory = working_directory?;
+                let git_cmd = async |args: &[&str]| -> Result<String> {
+                    let output = new_smol_command(&git_binary_path)
+                        .current_dir(&working_directory)
+                        .args(args)
+                        .output()
+                        .await?;
+                    if output.status.success() {
+                        Ok(String::from_utf8(output.stdout)?)
+                    } else {
+                        Err(anyhow!(String::from_utf8_lossy(&output.stderr).to_string()))
+                    }
+                };
+
+                let head = git_cmd(&["rev-parse", "HEAD"])
+                    .await
+                    .context("Failed to get HEAD")?
+                    .trim()
+                    .to_owned();
+
+                let mut remote_branches = vec![];
+                let mut add_if_matching = async |remote_head: &str| {
+                    if let Ok(merge_base) = git_cmd(&["merge-base", &head, remote_head]).await {
+                        if merge_base.trim() == head {
+                            if let Some(s) = remote_head.strip_prefix("refs/remotes/") {
+                                remote_branches.push(s.to_owned().into());
+                            }
+                        }
+                    }
+                };
+
+                // check the main branch of each remote
+                let remotes = git_cmd(&["remote"])
+                    .await
+                    .context("Failed to get remotes")?;
+                for remote in remotes.lines() {
+                    if let Ok(remote_head) =
+                        git_cmd(&["symbolic-ref", &format!("refs/remotes/{remote}/HEAD")]).await
+                    {
+                        add_if_matching(remote_head.trim()).await;
+                    }
+                }
+
+                // ... and the remote branch that the checked-out one is tracking
+                if let Ok(remote_head) =
+                    git_cmd(&["rev-parse", "--symbolic-full-name", "@{u}"]).await
+                {
+                    add_if_matching(remote_head.trim()).await;
                 }
 
                 Ok(remote_branches)
@@ -1652,27 +2516,387 @@ fn parse_branch_input(input: &str) -> Result<Vec<Branch>> {
             .to_string()
             .into();
 
-        branches.push(Branch {
-            is_head: is_current_branch,
-            name: ref_name,
-            most_recent_commit: Some(CommitSummary {
-                sha: head_sha,
-                subject,
-                commit_timestamp: commiterdate,
-                has_parent: !parent_sha.is_empty(),
-            }),
-            upstream: if upstream_name.is_empty() {
-                None
-            } else {
-                Some(Upstream {
-                    ref_name: upstream_name.into(),
-                    tracking: upstream_tracking,
-                })
-            },
+        branches.push(Branch {
+            is_head: is_current_branch,
+            name: ref_name,
+            most_recent_commit: Some(CommitSummary {
+                sha: head_sha,
+                subject,
+                commit_timestamp: commiterdate,
+                has_parent: !parent_sha.is_empty(),
+            }),
+            upstream: if upstream_name.is_empty() {
+                None
+            } else {
+                Some(Upstream {
+                    ref_name: upstream_name.into(),
+                    tracking: upstream_tracking,
+                })
+            },
+        })
+    }
+
+    Ok(branches)
+}
+
+fn parse_file_history(input: &str) -> Result<Vec<FileHistoryEntry>> {
+    let mut entries = Vec::new();
+    let mut fields = input.split('\0').skip(1);
+    while let Some(sha) = fields.next() {
+        let (Some(timestamp), Some(author_name), Some(rest)) =
+            (fields.next(), fields.next(), fields.next())
+        else {
+            return Err(anyhow!("unexpected file history output: {input:?}"));
+        };
+        // The subject is followed by a blank line and the file's path.
+        let mut lines = rest.lines();
+        let subject = lines.next().unwrap_or_default();
+        let Some(path) = lines.find(|line| !line.is_empty()) else {
+            continue;
+        };
+        entries.push(FileHistoryEntry {
+            sha: sha.to_string().into(),
+            subject: subject.to_string().into(),
+            commit_timestamp: timestamp.parse()?,
+            author_name: author_name.to_string().into(),
+            path: RepoPath::from_str(path),
+        });
+    }
+    Ok(entries)
+}
+
+/// Writes the todo list for an interactive rebase.
+///
+/// Rewording is done by amending the commit's message right after it's picked,
+/// so that the rebase doesn't need to open an editor for it.
+fn rebase_todo(entries: &[RebaseTodoEntry], git_binary_path: &Path) -> Result<String> {
+    if entries
+        .iter()
+        .find(|entry| entry.action != RebaseAction::Drop)
+        .is_some_and(|entry| matches!(entry.action, RebaseAction::Squash | RebaseAction::Fixup))
+    {
+        return Err(anyhow!(
+            "The first commit can't be squashed, as there's no commit before it to combine it with"
+        ));
+    }
+    let git = shell_quote(&git_binary_path.to_string_lossy());
+    let mut todo = String::new();
+    for entry in entries {
+        match (entry.action, &entry.message) {
+            (RebaseAction::Reword, Some(message)) => {
+                // The todo list has one command per line, so each line of the
+                // message is passed to printf as an argument of its own.
+                let lines = message
+                    .lines()
+                    .map(shell_quote)
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                todo.push_str(&format!("pick {}\n", entry.sha));
+                todo.push_str(&format!(
+                    "exec printf '%s\\n' {lines} | {git} commit --amend --only --allow-empty --file=-\n"
+                ));
+            }
+            (action, _) => todo.push_str(&format!("{} {}\n", action.as_str(), entry.sha)),
+        }
+    }
+    Ok(todo)
+}
+
+/// Quotes an argument for the POSIX shell git runs editors and `exec` commands with.
+fn shell_quote(argument: &str) -> String {
+    format!("'{}'", argument.replace('\'', "'\\''"))
+}
+
+/// A `git log` process whose output is read a page at a time.
+struct LogStream {
+    options: LogOptions,
+    /// The number of commits listed before the next one to be read.
+    position: usize,
+    child: smol::process::Child,
+    stdout: smol::io::BufReader<smol::process::ChildStdout>,
+}
+
+impl LogStream {
+    fn spawn(
+        git_binary_path: &Path,
+        working_directory: &Path,
+        options: LogOptions,
+        skip: usize,
+    ) -> Result<Self> {
+        let mut command = new_smol_command(git_binary_path);
+        command
+            .current_dir(working_directory)
+            .args([
+                "--no-optional-locks",
+                "log",
+                "--date-order",
+                "--decorate=short",
+                LOG_FORMAT,
+            ])
+            .arg(format!("--skip={skip}"));
+        if options.author.is_some() || options.message.is_some() {
+            command.args(["--regexp-ignore-case", "--fixed-strings"]);
+        }
+        if let Some(author) = &options.author {
+            command.arg(format!("--author={author}"));
+        }
+        if let Some(message) = &options.message {
+            command.arg(format!("--grep={message}"));
+        }
+        if options.all_refs {
+            command.args(["--exclude=refs/stash", "--all"]);
+        }
+        command.arg("--");
+        if let Some(path) = &options.path {
+            command.arg(path.as_os_str());
+        }
+        let mut child = command
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .context("failed to start git log")?;
+        let stdout = child.stdout.take().context("failed to read git log")?;
+        Ok(Self {
+            options,
+            position: skip,
+            child,
+            stdout: smol::io::BufReader::new(stdout),
+        })
+    }
+
+    /// Reads up to `limit` more commits, returning fewer once the log ends.
+    async fn next_page(&mut self, limit: usize) -> Result<Vec<LogEntry>> {
+        use smol::io::{AsyncBufReadExt as _, AsyncReadExt as _};
+
+        let mut entries = Vec::new();
+        let mut record = Vec::new();
+        while entries.len() < limit {
+            // Each record starts with a separator, so reading up to the next
+            // one yields a whole record.
+            record.clear();
+            if self.stdout.read_until(b'\x1e', &mut record).await? == 0 {
+                let status = self.child.status().await?;
+                if !status.success() {
+                    let mut stderr = String::new();
+                    if let Some(mut child_stderr) = self.child.stderr.take() {
+                        child_stderr.read_to_string(&mut stderr).await?;
+                    }
+                    // A repository without commits has no log.
+                    if stderr.contains("does not have any commits yet") {
+                        break;
+                    }
+                    return Err(anyhow!("Failed to load git log:\n{stderr}"));
+                }
+                break;
+            }
+            let record = String::from_utf8_lossy(&record);
+            let record = record.trim_end_matches('\x1e');
+            if !record.is_empty() {
+                entries.push(parse_log_entry(record)?);
+            }
+        }
+        self.position += entries.len();
+        Ok(entries)
+    }
+}
+
+/// The `git log` format that [`parse_log`] parses.
+const LOG_FORMAT: &str = "--format=%x1e%H%x00%P%x00%an%x00%ae%x00%ct%x00%D%x00%s";
+
+fn parse_log(input: &str) -> Result<Vec<LogEntry>> {
+    input.split('\x1e').skip(1).map(parse_log_entry).collect()
+}
+
+fn parse_log_entry(record: &str) -> Result<LogEntry> {
+    let mut fields = record.trim_end_matches('\n').split('\0');
+    let (
+        Some(sha),
+        Some(parents),
+        Some(author_name),
+        Some(author_email),
+        Some(timestamp),
+        Some(refs),
+        Some(subject),
+    ) = (
+        fields.next(),
+        fields.next(),
+        fields.next(),
+        fields.next(),
+        fields.next(),
+        fields.next(),
+        fields.next(),
+    )
+    else {
+        return Err(anyhow!("unexpected git log output: {record:?}"));
+    };
+    Ok(LogEntry {
+        sha: sha.to_string().into(),
+        parents: parents
+            .split(' ')
+            .filter(|parent| !parent.is_empty())
+            .map(|parent| parent.to_string().into())
+            .collect(),
+        subject: subject.to_string().into(),
+        author_name: author_name.to_string().into(),
+        author_email: author_email.to_string().into(),
+        commit_timestamp: timestamp.parse()?,
+        refs: refs
+            .split(", ")
+            .filter(|name| !name.is_empty())
+            .map(|name| name.to_string().into())
+            .collect(),
+    })
+}
+
+fn submodule_args(command: &[&str], paths: &[RepoPath]) -> Vec<OsString> {
+    let mut args: Vec<OsString> = vec!["submodule".into()];
+    args.extend(command.iter().map(OsString::from));
+    args.push("--".into());
+    args.extend(paths.iter().map(|path| path.as_os_str().to_owned()));
+    args
+}
+
+fn parse_worktree_list(input: &str) -> Result<Vec<GitWorktree>> {
+    let mut worktrees = Vec::new();
+    let records = input
+        .split("\n\n")
+        .filter(|record| !record.trim().is_empty());
+    for (ix, record) in records.enumerate() {
+        let mut lines = record.lines();
+        let first_line = lines.next().unwrap_or_default();
+        let path = first_line
+            .strip_prefix("worktree ")
+            .ok_or_else(|| anyhow!("unexpected worktree list line: {first_line}"))?;
+        let mut worktree = GitWorktree {
+            path: PathBuf::from(path),
+            sha: None,
+            branch: None,
+            is_main: ix == 0,
+            is_locked: false,
+            is_prunable: false,
+        };
+        let mut is_bare = false;
+        for line in lines {
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            match key {
+                "HEAD" if value.bytes().any(|byte| byte != b'0') => {
+                    worktree.sha = Some(value.to_string().into())
+                }
+                "branch" => {
+                    let branch = value.strip_prefix("refs/heads/").unwrap_or(value);
+                    worktree.branch = Some(branch.to_string().into());
+                }
+                "bare" => is_bare = true,
+                "locked" => worktree.is_locked = true,
+                "prunable" => worktree.is_prunable = true,
+                _ => {}
+            }
+        }
+        // A bare repository's main worktree has nothing checked out.
+        if !is_bare {
+            worktrees.push(worktree);
+        }
+    }
+    Ok(worktrees)
+}
+
+fn parse_submodule_status(input: &str) -> Result<Vec<Submodule>> {
+    input
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let status = match line.as_bytes()[0] {
+                b' ' => SubmoduleStatus::UpToDate,
+                b'-' => SubmoduleStatus::Uninitialized,
+                b'+' => SubmoduleStatus::Modified,
+                b'U' => SubmoduleStatus::Conflicted,
+                _ => return Err(anyhow!("unexpected submodule status line: {line}")),
+            };
+            let (sha, path) = line[1..]
+                .split_once(' ')
+                .ok_or_else(|| anyhow!("unexpected submodule status line: {line}"))?;
+            // Checked out submodules are followed by a description of their commit.
+            let path = match path.rfind(" (") {
+                Some(ix) if path.ends_with(')') => &path[..ix],
+                _ => path,
+            };
+            Ok(Submodule {
+                path: RepoPath::from(path),
+                sha: sha.to_string().into(),
+                status,
+            })
         })
-    }
+        .collect()
+}
 
-    Ok(branches)
+fn parse_stash_list(input: &str) -> Result<Vec<StashEntry>> {
+    input
+        .lines()
+        .filter(|line| !line.is_empty())
+        .enumerate()
+        .map(|(index, line)| {
+            let mut fields = line.split('\0');
+            let (Some(sha), Some(timestamp), Some(message)) =
+                (fields.next(), fields.next(), fields.next())
+            else {
+                return Err(anyhow!("unexpected stash list output: {line:?}"));
+            };
+            Ok(StashEntry {
+                index,
+                sha: sha.to_string().into(),
+                message: message.to_string().into(),
+                commit_timestamp: timestamp.parse()?,
+            })
+        })
+        .collect()
+}
+
+/// Returns the files in the commit where `git stash --include-untracked` keeps
+/// the untracked files, or nothing if `sha` is some other commit.
+fn stash_untracked_files(working_directory: &Path, sha: &str) -> Result<Vec<PathBuf>> {
+    let output = new_std_command("git")
+        .current_dir(working_directory)
+        .args([
+            "--no-optional-locks",
+            "show",
+            "-s",
+            "--format=%P%x00%s",
+            sha,
+        ])
+        .output()?;
+    let summary = String::from_utf8_lossy(&output.stdout);
+    let (parents, subject) = summary.trim_end().split_once('\0').unwrap_or_default();
+    if !output.status.success()
+        || !parents.is_empty()
+        || !subject.starts_with("untracked files on ")
+    {
+        return Ok(Vec::new());
+    }
+
+    let output = new_std_command("git")
+        .current_dir(working_directory)
+        .args([
+            "--no-optional-locks",
+            "ls-tree",
+            "-r",
+            "-z",
+            "--name-only",
+            sha,
+        ])
+        .output()?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "Failed to list untracked files of stash:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .split('\0')
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+        .collect())
 }
 
 fn parse_upstream_track(upstream_track: &str) -> Result<UpstreamTracking> {
@@ -1913,6 +3137,500 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    async fn test_file_history(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+
+        let repo_dir = tempfile::tempdir().unwrap();
+        git2::Repository::init(repo_dir.path()).unwrap();
+        let repo =
+            RealGitRepository::new(&repo_dir.path().join(".git"), None, cx.executor()).unwrap();
+        let env = Arc::new(checkpoint_author_envs());
+
+        smol::fs::write(repo_dir.path().join("draft.md"), "first draft\n")
+            .await
+            .unwrap();
+        smol::fs::write(repo_dir.path().join("other.md"), "unrelated\n")
+            .await
+            .unwrap();
+        repo.stage_paths(
+            vec![
+                RepoPath::from_str("draft.md"),
+                RepoPath::from_str("other.md"),
+            ],
+            env.clone(),
+        )
+        .await
+        .unwrap();
+        repo.commit("Start a draft".into(), None, env.clone())
+            .await
+            .unwrap();
+
+        smol::fs::rename(
+            repo_dir.path().join("draft.md"),
+            repo_dir.path().join("essay.md"),
+        )
+        .await
+        .unwrap();
+        smol::fs::write(repo_dir.path().join("essay.md"), "first draft\n\nmore\n")
+            .await
+            .unwrap();
+        repo.stage_paths(
+            vec![
+                RepoPath::from_str("draft.md"),
+                RepoPath::from_str("essay.md"),
+            ],
+            env.clone(),
+        )
+        .await
+        .unwrap();
+        repo.commit("Rename the draft".into(), None, env.clone())
+            .await
+            .unwrap();
+
+        let history = repo
+            .file_history(RepoPath::from_str("essay.md"))
+            .await
+            .unwrap();
+        assert_eq!(
+            history
+                .iter()
+                .map(|entry| (entry.subject.as_ref(), entry.path.to_string()))
+                .collect::<Vec<_>>(),
+            [
+                ("Rename the draft", "essay.md".to_string()),
+                ("Start a draft", "draft.md".to_string()),
+            ]
+        );
+        assert_eq!(
+            repo.load_text_at_commit(history[1].sha.to_string(), history[1].path.clone())
+                .await
+                .unwrap()
+                .as_deref(),
+            Some("first draft\n")
+        );
+        assert_eq!(
+            repo.load_text_at_commit(history[1].sha.to_string(), RepoPath::from_str("essay.md"))
+                .await
+                .unwrap(),
+            None
+        );
+    }
+
+    #[gpui::test]
+    async fn test_log(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+
+        let repo_dir = tempfile::tempdir().unwrap();
+        git2::Repository::init(repo_dir.path()).unwrap();
+        let repo =
+            RealGitRepository::new(&repo_dir.path().join(".git"), None, cx.executor()).unwrap();
+        let env = Arc::new(checkpoint_author_envs());
+
+        assert!(
+            repo.log(LogOptions::default(), 0, 10)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        for (path, subject) in [("a.md", "Add a"), ("b.md", "Add b")] {
+            smol::fs::write(repo_dir.path().join(path), subject)
+                .await
+                .unwrap();
+            repo.stage_paths(vec![RepoPath::from_str(path)], env.clone())
+                .await
+                .unwrap();
+            repo.commit(subject.into(), None, env.clone())
+                .await
+                .unwrap();
+        }
+        let main_branch = repo
+            .branches()
+            .await
+            .unwrap()
+            .into_iter()
+            .find(|branch| branch.is_head)
+            .unwrap()
+            .name;
+        repo.create_branch("side".into()).await.unwrap();
+        repo.change_branch("side".into()).await.unwrap();
+        smol::fs::write(repo_dir.path().join("c.md"), "Side work")
+            .await
+            .unwrap();
+        repo.stage_paths(vec![RepoPath::from_str("c.md")], env.clone())
+            .await
+            .unwrap();
+        repo.commit("Side work".into(), None, env.clone())
+            .await
+            .unwrap();
+        repo.change_branch(main_branch.to_string()).await.unwrap();
+
+        let log = repo.log(LogOptions::default(), 0, 10).await.unwrap();
+        assert_eq!(
+            log.iter()
+                .map(|entry| entry.subject.as_ref())
+                .collect::<Vec<_>>(),
+            ["Add b", "Add a"]
+        );
+        assert_eq!(log[0].parents, [log[1].sha.clone()]);
+        assert!(log[1].parents.is_empty());
+        assert_eq!(
+            log[0].refs,
+            [SharedString::from(format!("HEAD -> {main_branch}"))]
+        );
+
+        let all_refs = repo
+            .log(
+                LogOptions {
+                    all_refs: true,
+                    ..Default::default()
+                },
+                0,
+                10,
+            )
+            .await
+            .unwrap();
+        let side_work = all_refs
+            .iter()
+            .find(|entry| entry.subject == "Side work")
+            .unwrap();
+        assert_eq!(all_refs.len(), 3);
+        assert_eq!(side_work.parents, [log[0].sha.clone()]);
+        assert_eq!(side_work.refs, [SharedString::from("side")]);
+
+        let page = repo.log(LogOptions::default(), 1, 1).await.unwrap();
+        assert_eq!(page, [log[1].clone()]);
+
+        // Each page continues the `git log` process that the previous one
+        // was read from.
+        let first_page = repo.log(LogOptions::default(), 0, 1).await.unwrap();
+        assert_eq!(first_page, [log[0].clone()]);
+        assert_eq!(repo.log_stream.lock().await.as_ref().unwrap().position, 1);
+        let second_page = repo.log(LogOptions::default(), 1, 1).await.unwrap();
+        assert_eq!(second_page, [log[1].clone()]);
+        assert_eq!(repo.log_stream.lock().await.as_ref().unwrap().position, 2);
+        assert!(
+            repo.log(LogOptions::default(), 2, 1)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert!(repo.log_stream.lock().await.is_none());
+
+        let by_path = repo
+            .log(
+                LogOptions {
+                    path: Some(RepoPath::from_str("a.md")),
+                    ..Default::default()
+                },
+                0,
+                10,
+            )
+            .await
+            .unwrap();
+        assert_eq!(by_path, [log[1].clone()]);
+
+        let by_message = repo
+            .log(
+                LogOptions {
+                    message: Some("ADD B".into()),
+                    author: Some("zed".into()),
+                    ..Default::default()
+                },
+                0,
+                10,
+            )
+            .await
+            .unwrap();
+        assert_eq!(by_message, [log[0].clone()]);
+    }
+
+    #[gpui::test]
+    async fn test_interactive_rebase(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+
+        let repo_dir = tempfile::tempdir().unwrap();
+        git2::Repository::init(repo_dir.path()).unwrap();
+        let repo =
+            RealGitRepository::new(&repo_dir.path().join(".git"), None, cx.executor()).unwrap();
+        let env = Arc::new(checkpoint_author_envs());
+        let commit = async |path: &str, contents: &str, message: &str| {
+            smol::fs::write(repo_dir.path().join(path), contents)
+                .await
+                .unwrap();
+            repo.stage_paths(vec![RepoPath::from_str(path)], env.clone())
+                .await
+                .unwrap();
+            repo.commit(message.to_string().into(), None, env.clone())
+                .await
+                .unwrap();
+            repo.head_sha().unwrap()
+        };
+        let subjects = async || {
+            repo.log(LogOptions::default(), 0, 100)
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|entry| entry.subject.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let base = commit("a.md", "1\n", "Add a").await;
+        commit("b.md", "b\n", "Add b").await;
+        commit("c.md", "c\n", "Add c").await;
+        commit("d.md", "d\n", "Add d").await;
+        commit("a.md", "2\n", "Edit a").await;
+
+        let commits = repo.rebase_commits(base.clone()).await.unwrap();
+        assert_eq!(
+            commits
+                .iter()
+                .map(|entry| entry.subject.as_ref())
+                .collect::<Vec<_>>(),
+            ["Add b", "Add c", "Add d", "Edit a"]
+        );
+
+        // Reorder, reword, fixup and drop commits.
+        let entry = |ix: usize, action: RebaseAction| RebaseTodoEntry {
+            action,
+            sha: commits[ix].sha.clone(),
+            message: None,
+        };
+        let todo = vec![
+            RebaseTodoEntry {
+                message: Some("-Rewrite a\n\nWith 100% more 'quotes' and \\n".into()),
+                ..entry(3, RebaseAction::Reword)
+            },
+            entry(2, RebaseAction::Pick),
+            entry(0, RebaseAction::Fixup),
+            entry(1, RebaseAction::Drop),
+        ];
+        repo.rebase_interactive(base.clone(), todo, env.clone())
+            .await
+            .unwrap();
+        assert_eq!(subjects().await, ["Add d", "-Rewrite a", "Add a"]);
+        let reworded = repo.show("HEAD~1".into()).await.unwrap();
+        assert_eq!(
+            reworded.message.trim_end(),
+            "-Rewrite a\n\nWith 100% more 'quotes' and \\n"
+        );
+        assert!(repo_dir.path().join("b.md").exists());
+        assert!(!repo_dir.path().join("c.md").exists());
+        assert_eq!(repo.rebase_state(), None);
+
+        // Swapping two edits of the same line stops on a conflict.
+        let base = repo.head_sha().unwrap();
+        let first = commit("a.md", "3\n", "Change a to 3").await;
+        let second = commit("a.md", "4\n", "Change a to 4").await;
+        let todo = vec![
+            RebaseTodoEntry {
+                action: RebaseAction::Pick,
+                sha: second.clone().into(),
+                message: None,
+            },
+            RebaseTodoEntry {
+                action: RebaseAction::Pick,
+                sha: first.clone().into(),
+                message: None,
+            },
+        ];
+        repo.rebase_interactive(base.clone(), todo, env.clone())
+            .await
+            .unwrap_err();
+        let state = repo.rebase_state().unwrap();
+        assert_eq!(state.onto.as_ref(), base);
+        assert_eq!(state.stopped_sha.as_deref(), Some(second.as_str()));
+        assert_eq!((state.step, state.total), (1, 2));
+        let status = repo.status(&[]).await.unwrap();
+        assert!(status.entries.iter().any(|(path, status)| {
+            path == &RepoPath::from_str("a.md") && status.is_conflicted()
+        }));
+
+        repo.abort_rebase(env.clone()).await.unwrap();
+        assert_eq!(repo.rebase_state(), None);
+        assert_eq!(repo.head_sha().unwrap(), second);
+
+        // Stopping to edit a commit isn't an error.
+        let todo = vec![
+            RebaseTodoEntry {
+                action: RebaseAction::Edit,
+                sha: first.clone().into(),
+                message: None,
+            },
+            RebaseTodoEntry {
+                action: RebaseAction::Pick,
+                sha: second.clone().into(),
+                message: None,
+            },
+        ];
+        repo.rebase_interactive(base, todo, env.clone())
+            .await
+            .unwrap();
+        let state = repo.rebase_state().unwrap();
+        assert_eq!(state.stopped_sha.as_deref(), Some(first.as_str()));
+        repo.continue_rebase(env.clone()).await.unwrap();
+        assert_eq!(repo.rebase_state(), None);
+        assert_eq!(repo.head_sha().unwrap(), second);
+    }
+
+    #[test]
+    fn test_rebase_todo() {
+        let entry = |sha: &str, action: RebaseAction| RebaseTodoEntry {
+            action,
+            sha: sha.to_string().into(),
+            message: None,
+        };
+        let todo = rebase_todo(
+            &[
+                entry("aaa", RebaseAction::Drop),
+                RebaseTodoEntry {
+                    message: Some("It's\n100% \\n".into()),
+                    ..entry("bbb", RebaseAction::Reword)
+                },
+                entry("ccc", RebaseAction::Squash),
+                RebaseTodoEntry {
+                    message: Some("-n starts with a dash".into()),
+                    ..entry("ddd", RebaseAction::Reword)
+                },
+            ],
+            Path::new("git"),
+        )
+        .unwrap();
+        assert_eq!(
+            todo,
+            "drop aaa\n\
+             pick bbb\n\
+             exec printf '%s\\n' 'It'\\''s' '100% \\n' | 'git' commit --amend --only --allow-empty --file=-\n\
+             squash ccc\n\
+             pick ddd\n\
+             exec printf '%s\\n' '-n starts with a dash' | 'git' commit --amend --only --allow-empty --file=-\n"
+        );
+
+        // The first commit that's kept can't be combined with a previous one.
+        rebase_todo(
+            &[
+                entry("aaa", RebaseAction::Drop),
+                entry("bbb", RebaseAction::Fixup),
+            ],
+            Path::new("git"),
+        )
+        .unwrap_err();
+    }
+
+    #[gpui::test]
+    async fn test_stash(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+
+        let repo_dir = tempfile::tempdir().unwrap();
+        git2::Repository::init(repo_dir.path()).unwrap();
+        let repo =
+            RealGitRepository::new(&repo_dir.path().join(".git"), None, cx.executor()).unwrap();
+        let env = Arc::new(checkpoint_author_envs());
+
+        smol::fs::write(repo_dir.path().join("note.md"), "first\n")
+            .await
+            .unwrap();
+        repo.stage_paths(vec![RepoPath::from_str("note.md")], env.clone())
+            .await
+            .unwrap();
+        repo.commit("Add a note".into(), None, env.clone())
+            .await
+            .unwrap();
+
+        smol::fs::write(repo_dir.path().join("note.md"), "second\n")
+            .await
+            .unwrap();
+        smol::fs::write(repo_dir.path().join("new.md"), "new\n")
+            .await
+            .unwrap();
+        repo.stash_push(StashScope::All, Some("Rewrite".into()), env.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            smol::fs::read_to_string(repo_dir.path().join("note.md"))
+                .await
+                .unwrap(),
+            "first\n"
+        );
+        assert!(!repo_dir.path().join("new.md").exists());
+
+        smol::fs::write(repo_dir.path().join("note.md"), "third\n")
+            .await
+            .unwrap();
+        repo.stash_push(
+            StashScope::Paths(vec![RepoPath::from_str("note.md")]),
+            None,
+            env.clone(),
+        )
+        .await
+        .unwrap();
+
+        let entries = repo.stash_entries().await.unwrap();
+        assert_eq!(
+            entries.iter().map(|entry| entry.index).collect::<Vec<_>>(),
+            [0, 1]
+        );
+        assert!(entries[0].message.starts_with("WIP on "));
+        assert!(entries[1].message.ends_with(": Rewrite"));
+
+        let diff = repo
+            .load_commit(entries[1].sha.to_string(), cx.to_async())
+            .await
+            .unwrap();
+        assert_eq!(
+            diff.files
+                .iter()
+                .map(|file| (file.path.to_string(), file.new_text.as_deref()))
+                .collect::<Vec<_>>(),
+            [
+                ("note.md".to_string(), Some("second\n")),
+                ("new.md".to_string(), Some("new\n"))
+            ]
+        );
+
+        // Entries that have moved since they were listed are left alone.
+        smol::fs::write(repo_dir.path().join("note.md"), "fourth\n")
+            .await
+            .unwrap();
+        repo.stash_push(StashScope::All, None, env.clone())
+            .await
+            .unwrap();
+        repo.stash_drop(entries[0].clone(), env.clone())
+            .await
+            .unwrap_err();
+        let entries = repo.stash_entries().await.unwrap();
+        assert_eq!(entries.len(), 3);
+
+        repo.stash_drop(entries[0].clone(), env.clone())
+            .await
+            .unwrap();
+        repo.stash_drop(entries[1].clone(), env.clone())
+            .await
+            .unwrap_err();
+        let entries = repo.stash_entries().await.unwrap();
+        repo.stash_drop(entries[0].clone(), env.clone())
+            .await
+            .unwrap();
+        let entries = repo.stash_entries().await.unwrap();
+        repo.stash_pop(entries[0].clone(), env.clone())
+            .await
+            .unwrap();
+        assert!(repo.stash_entries().await.unwrap().is_empty());
+        assert_eq!(
+            smol::fs::read_to_string(repo_dir.path().join("note.md"))
+                .await
+                .unwrap(),
+            "second\n"
+        );
+        assert_eq!(
+            smol::fs::read_to_string(repo_dir.path().join("new.md"))
+                .await
+                .unwrap(),
+            "new\n"
+        );
+    }
+
     #[test]
     fn test_branches_parsing() {
         // suppress "help: octal escapes are not supported, `\0` is always null"
@@ -1940,6 +3658,62 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_worktree_and_submodule_parsing() {
+        let sha = "a90da830ca6dcebb6fa1368b08af52cd1cffc256";
+        let input = format!(
+            "worktree /work/main\nHEAD {sha}\nbranch refs/heads/main\n\n\
+             worktree /work/detached\nHEAD {sha}\ndetached\nlocked\n\n\
+             worktree /work/gone\nHEAD {sha}\nbranch refs/heads/gone\n\
+             prunable gitdir file points to non-existent location\n\n"
+        );
+        let worktree = |path: &str, branch: Option<&str>| GitWorktree {
+            path: PathBuf::from(path),
+            sha: Some(sha.into()),
+            branch: branch.map(|branch| branch.to_string().into()),
+            is_main: false,
+            is_locked: false,
+            is_prunable: false,
+        };
+        assert_eq!(
+            parse_worktree_list(&input).unwrap(),
+            vec![
+                GitWorktree {
+                    is_main: true,
+                    ..worktree("/work/main", Some("main"))
+                },
+                GitWorktree {
+                    is_locked: true,
+                    ..worktree("/work/detached", None)
+                },
+                GitWorktree {
+                    is_prunable: true,
+                    ..worktree("/work/gone", Some("gone"))
+                },
+            ]
+        );
+        assert_eq!(
+            parse_worktree_list("worktree /work/bare.git\nbare\n\n").unwrap(),
+            vec![]
+        );
+
+        let input =
+            format!("+{sha} vendor/my lib (heads/main)\n-{sha} other\n {sha} third (v1.0)\n");
+        let submodule = |path: &str, status: SubmoduleStatus| Submodule {
+            path: RepoPath::from(path),
+            sha: sha.into(),
+            status,
+        };
+        assert_eq!(
+            parse_submodule_status(&input).unwrap(),
+            vec![
+                submodule("vendor/my lib", SubmoduleStatus::Modified),
+                submodule("other", SubmoduleStatus::Uninitialized),
+                submodule("third", SubmoduleStatus::UpToDate),
+            ]
+        );
+    }
+
     impl RealGitRepository {
         /// Force a Git garbage collection on the repository.
         fn gc(&self) -> BoxFuture<Result<()>> {
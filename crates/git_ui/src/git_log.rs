@@ -0,0 +1,597 @@
+use std::time::Duration;
+
+use editor::{Editor, EditorEvent};
+use git::SHORT_SHA_LENGTH;
+use git::repository::{CommitSummary, LogEntry, LogOptions, RepoPath};
+use gpui::{
+    AnyElement, App, ClickEvent, Entity, EventEmitter, FocusHandle, Focusable, KeyContext,
+    PathBuilder, ScrollStrategy, Subscription, Task, UniformListScrollHandle, WeakEntity, canvas,
+    fill, point, size, uniform_list,
+};
+use menu::{Confirm, SelectFirst, SelectLast, SelectNext, SelectPrevious};
+use project::git_store::Repository;
+use time::OffsetDateTime;
+use time_format::format_local_timestamp;
+use ui::{Tooltip, prelude::*};
+use workspace::{Workspace, item::Item};
+
+use crate::commit_view::CommitView;
+use crate::log_graph::{GraphRow, LogGraph};
+use crate::rebase_editor::RebaseEditor;
+
+/// How many commits are loaded at a time, as the log is scrolled.
+const PAGE_SIZE: usize = 200;
+const ROW_HEIGHT: f32 = 28.;
+const LANE_WIDTH: f32 = 14.;
+const FILTER_DEBOUNCE: Duration = Duration::from_millis(300);
+
+pub fn register(workspace: &mut Workspace) {
+    workspace.register_action(|workspace, _: &git::ViewHistory, window, cx| {
+        let Some(repository) = workspace.project().read(cx).active_repository(cx) else {
+            return;
+        };
+        GitLog::open(repository, None, workspace, window, cx);
+    });
+    workspace.register_action(|workspace, _: &git::ViewFileHistory, window, cx| {
+        let Some(editor) = workspace.active_item_as::<Editor>(cx) else {
+            return;
+        };
+        let Some(buffer) = editor.read(cx).buffer().read(cx).as_singleton() else {
+            return;
+        };
+        let Some((repository, path)) = workspace
+            .project()
+            .read(cx)
+            .git_store()
+            .read(cx)
+            .repository_and_path_for_buffer_id(buffer.read(cx).remote_id(), cx)
+        else {
+            return;
+        };
+        GitLog::open(repository, Some(path), workspace, window, cx);
+    });
+}
+
+/// A tab listing a repository's commits, or those that changed one file, with
+/// a graph of the branches and merges between them.
+///
+/// The log is loaded a page at a time as it's scrolled, so that opening it
+/// stays fast in repositories with long histories.
+pub struct GitLog {
+    repository: Entity<Repository>,
+    workspace: WeakEntity<Workspace>,
+    /// The file whose history is shown, or `None` for the whole repository's.
+    path: Option<RepoPath>,
+    all_refs: bool,
+    author_editor: Entity<Editor>,
+    message_editor: Entity<Editor>,
+    /// The options the entries were loaded with.
+    options: LogOptions,
+    entries: Vec<(LogEntry, GraphRow)>,
+    graph: LogGraph,
+    graph_width: usize,
+    /// Whether every page of the log has been loaded.
+    complete: bool,
+    error: Option<SharedString>,
+    selected: Option<usize>,
+    scroll_handle: UniformListScrollHandle,
+    focus_handle: FocusHandle,
+    load_page: Option<Task<()>>,
+    _update_filter: Task<()>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl GitLog {
+    /// Opens the log, or activates it if it's already open in the active pane.
+    pub fn open(
+        repository: Entity<Repository>,
+        path: Option<RepoPath>,
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let existing = workspace
+            .active_pane()
+            .read(cx)
+            .items_of_type::<GitLog>()
+            .find(|log| {
+                let log = log.read(cx);
+                log.repository == repository && log.path == path
+            });
+        if let Some(existing) = existing {
+            workspace.activate_item(&existing, true, true, window, cx);
+            return;
+        }
+
+        let workspace_handle = cx.weak_entity();
+        let log = cx.new(|cx| GitLog::new(repository, path, workspace_handle, window, cx));
+        workspace.add_item_to_active_pane(Box::new(log), None, true, window, cx);
+    }
+
+    fn new(
+        repository: Entity<Repository>,
+        path: Option<RepoPath>,
+        workspace: WeakEntity<Workspace>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let author_editor = cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            editor.set_placeholder_text("Filter by author…", cx);
+            editor
+        });
+        let message_editor = cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            editor.set_placeholder_text("Filter by message…", cx);
+            editor
+        });
+        let subscriptions = [&author_editor, &message_editor]
+            .into_iter()
+            .map(|editor| {
+                cx.subscribe(editor, |this, _, event, cx| {
+                    if let EditorEvent::BufferEdited = event {
+                        this.schedule_reload(cx);
+                    }
+                })
+            })
+            .collect();
+
+        let mut this = Self {
+            repository,
+            workspace,
+            path,
+            all_refs: true,
+            author_editor,
+            message_editor,
+            options: LogOptions::default(),
+            entries: Vec::new(),
+            graph: LogGraph::default(),
+            graph_width: 0,
+            complete: false,
+            error: None,
+            selected: None,
+            scroll_handle: UniformListScrollHandle::new(),
+            focus_handle: cx.focus_handle(),
+            load_page: None,
+            _update_filter: Task::ready(()),
+            _subscriptions: subscriptions,
+        };
+        this.reload(cx);
+        this
+    }
+
+    fn current_options(&self, cx: &App) -> LogOptions {
+        let filter = |editor: &Entity<Editor>| {
+            let text = editor.read(cx).text(cx);
+            let text = text.trim();
+            (!text.is_empty()).then(|| text.to_string())
+        };
+        LogOptions {
+            all_refs: self.all_refs && self.path.is_none(),
+            author: filter(&self.author_editor),
+            message: filter(&self.message_editor),
+            path: self.path.clone(),
+        }
+    }
+
+    fn schedule_reload(&mut self, cx: &mut Context<Self>) {
+        self._update_filter = cx.spawn(async move |this, cx| {
+            cx.background_executor().timer(FILTER_DEBOUNCE).await;
+            this.update(cx, |this, cx| this.reload(cx)).ok();
+        });
+    }
+
+    fn reload(&mut self, cx: &mut Context<Self>) {
+        // Dropping the task for a page that's still loading discards it.
+        self.load_page = None;
+        self.options = self.current_options(cx);
+        self.entries.clear();
+        self.graph = LogGraph::default();
+        self.graph_width = 0;
+        self.complete = false;
+        self.error = None;
+        self.selected = None;
+        self.load_next_page(cx);
+        cx.notify();
+    }
+
+    fn load_next_page(&mut self, cx: &mut Context<Self>) {
+        if self.complete || self.load_page.is_some() {
+            return;
+        }
+        let page =
+            self.repository
+                .read(cx)
+                .log(self.options.clone(), self.entries.len(), PAGE_SIZE);
+        self.load_page = Some(cx.spawn(async move |this, cx| {
+            let page = page.await;
+            this.update(cx, |this, cx| {
+                this.load_page = None;
+                match page {
+                    Ok(Ok(page)) => {
+                        this.complete = page.len() < PAGE_SIZE;
+                        for entry in page {
+                            let row = this.graph.push(&entry.sha, &entry.parents);
+                            this.graph_width = this.graph_width.max(row.width());
+                            this.entries.push((entry, row));
+                        }
+                    }
+                    Ok(Err(error)) => {
+                        this.complete = true;
+                        this.error = Some(error.to_string().into());
+                    }
+                    Err(_) => this.complete = true,
+                }
+                cx.notify();
+            })
+            .ok();
+        }));
+    }
+
+    /// Whether to draw the graph. It's left out when the log is filtered,
+    /// since the commits between those listed are missing from it.
+    fn show_graph(&self) -> bool {
+        !self.options.is_filtered()
+    }
+
+    fn toggle_all_refs(&mut self, cx: &mut Context<Self>) {
+        self.all_refs = !self.all_refs;
+        self.reload(cx);
+    }
+
+    fn select(&mut self, ix: usize, cx: &mut Context<Self>) {
+        self.selected = Some(ix);
+        self.scroll_handle
+            .scroll_to_item(ix, ScrollStrategy::Center);
+        cx.notify();
+    }
+
+    fn select_next(&mut self, _: &SelectNext, _: &mut Window, cx: &mut Context<Self>) {
+        if self.entries.is_empty() {
+            return;
+        }
+        let ix = self
+            .selected
+            .map_or(0, |ix| (ix + 1).min(self.entries.len() - 1));
+        self.select(ix, cx);
+    }
+
+    fn select_previous(&mut self, _: &SelectPrevious, _: &mut Window, cx: &mut Context<Self>) {
+        if self.entries.is_empty() {
+            return;
+        }
+        let ix = self.selected.map_or(0, |ix| ix.saturating_sub(1));
+        self.select(ix, cx);
+    }
+
+    fn select_first(&mut self, _: &SelectFirst, _: &mut Window, cx: &mut Context<Self>) {
+        if !self.entries.is_empty() {
+            self.select(0, cx);
+        }
+    }
+
+    fn select_last(&mut self, _: &SelectLast, _: &mut Window, cx: &mut Context<Self>) {
+        if !self.entries.is_empty() {
+            self.select(self.entries.len() - 1, cx);
+        }
+    }
+
+    /// Starts planning an interactive rebase of the current branch onto the selected commit.
+    fn interactive_rebase(
+        &mut self,
+        _: &git::InteractiveRebase,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some((entry, _)) = self.selected.and_then(|ix| self.entries.get(ix)) else {
+            cx.propagate();
+            return;
+        };
+        let base = entry.sha.to_string();
+        let repository = self.repository.clone();
+        self.workspace
+            .update(cx, |workspace, cx| {
+                RebaseEditor::open(repository, base, workspace, window, cx)
+            })
+            .ok();
+    }
+
+    fn confirm(&mut self, _: &Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(ix) = self.selected {
+            self.open_commit(ix, window, cx);
+        }
+    }
+
+    fn open_commit(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let Some((entry, _)) = self.entries.get(ix) else {
+            return;
+        };
+        CommitView::open(
+            CommitSummary {
+                sha: entry.sha.clone(),
+                subject: entry.subject.clone(),
+                commit_timestamp: entry.commit_timestamp,
+                has_parent: !entry.parents.is_empty(),
+            },
+            self.repository.downgrade(),
+            self.workspace.clone(),
+            window,
+            cx,
+        );
+    }
+
+    fn dispatch_context(&self, window: &Window) -> KeyContext {
+        let mut dispatch_context = KeyContext::new_with_defaults();
+        dispatch_context.add("GitLog");
+        if self.focus_handle.is_focused(window) {
+            dispatch_context.add("menu");
+        }
+        dispatch_context
+    }
+
+    fn render_toolbar(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let border_color = cx.theme().colors().border_variant;
+        let filter_input = |editor: &Entity<Editor>| {
+            div()
+                .w(px(220.))
+                .px_2()
+                .py_1()
+                .rounded_sm()
+                .border_1()
+                .border_color(border_color)
+                .child(editor.clone())
+        };
+        h_flex()
+            .p_2()
+            .gap_2()
+            .border_b_1()
+            .border_color(border_color)
+            .child(filter_input(&self.author_editor))
+            .child(filter_input(&self.message_editor))
+            .when_some(self.path.as_ref(), |this, path| {
+                this.child(
+                    Label::new(format!("History of {}", path.display()))
+                        .color(Color::Muted)
+                        .truncate(),
+                )
+            })
+            .when(self.path.is_none(), |this| {
+                this.child(
+                    ui::Checkbox::new("git-log-all-refs", self.all_refs.into())
+                        .label("All Branches")
+                        .on_click(cx.listener(|this, _, _, cx| this.toggle_all_refs(cx))),
+                )
+            })
+            .child(div().flex_1())
+            .when(self.path.is_none() && self.selected.is_some(), |this| {
+                this.child(
+                    Button::new("git-log-rebase", "Rebase onto Selected")
+                        .tooltip(Tooltip::for_action_title(
+                            "Interactive Rebase",
+                            &git::InteractiveRebase,
+                        ))
+                        .on_click(cx.listener(|this, _, window, cx| {
+                            this.interactive_rebase(&git::InteractiveRebase, window, cx)
+                        })),
+                )
+            })
+    }
+
+    fn render_entry(&self, ix: usize, cx: &mut Context<Self>) -> AnyElement {
+        let (entry, row) = &self.entries[ix];
+        let colors = cx.theme().colors();
+        let commit_time = OffsetDateTime::from_unix_timestamp(entry.commit_timestamp)
+            .unwrap_or_else(|_| OffsetDateTime::now_utc());
+        let commit_time = format_local_timestamp(
+            commit_time,
+            OffsetDateTime::now_utc(),
+            time_format::TimestampFormat::Relative,
+        );
+
+        h_flex()
+            .id(("git-log-entry", ix))
+            .h(px(ROW_HEIGHT))
+            .w_full()
+            .px_2()
+            .gap_2()
+            .cursor_pointer()
+            .when(self.selected == Some(ix), |this| {
+                this.bg(colors.element_selected)
+            })
+            .hover(|style| style.bg(colors.element_hover))
+            .on_click(cx.listener(move |this, event: &ClickEvent, window, cx| {
+                this.select(ix, cx);
+                if event.down.click_count > 1 {
+                    this.open_commit(ix, window, cx);
+                }
+            }))
+            .when(self.show_graph(), |this| {
+                this.child(render_graph(row.clone(), self.graph_width, cx))
+            })
+            .child(
+                h_flex()
+                    .flex_1()
+                    .min_w_0()
+                    .gap_1()
+                    .children(entry.refs.iter().map(|name| render_ref(name, cx)))
+                    .child(Label::new(entry.subject.clone()).truncate()),
+            )
+            .child(
+                Label::new(entry.author_name.clone())
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+            .child(
+                Label::new(commit_time)
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+            .child(
+                Label::new(
+                    entry
+                        .sha
+                        .get(..SHORT_SHA_LENGTH)
+                        .unwrap_or(&entry.sha)
+                        .to_string(),
+                )
+                .size(LabelSize::Small)
+                .color(Color::Muted)
+                .buffer_font(cx),
+            )
+            .into_any_element()
+    }
+}
+
+/// Draws one row of the graph: the lines passing through it and the commit's node.
+fn render_graph(row: GraphRow, lane_count: usize, cx: &App) -> impl IntoElement {
+    let accents = cx.theme().accents().clone();
+    canvas(
+        |_, _, _| {},
+        move |bounds, _, window, _| {
+            let lane_x = |lane: usize| bounds.left() + px(LANE_WIDTH * (lane as f32 + 0.5));
+            let top = bounds.top();
+            let middle = bounds.center().y;
+            let bottom = bounds.bottom();
+            let lines = row
+                .incoming
+                .iter()
+                .map(|&(from, to)| (point(lane_x(from), top), point(lane_x(to), middle), from))
+                .chain(row.outgoing.iter().map(|&(from, to)| {
+                    (point(lane_x(from), middle), point(lane_x(to), bottom), to)
+                }));
+            for (start, end, lane) in lines {
+                let mut builder = PathBuilder::stroke(px(1.5));
+                builder.move_to(start);
+                builder.line_to(end);
+                if let Ok(path) = builder.build() {
+                    window.paint_path(path, accents.color_for_index(lane as u32));
+                }
+            }
+            let node =
+                gpui::Bounds::centered_at(point(lane_x(row.lane), middle), size(px(8.), px(8.)));
+            window.paint_quad(
+                fill(node, accents.color_for_index(row.lane as u32)).corner_radii(px(4.)),
+            );
+        },
+    )
+    .flex_none()
+    .w(px(LANE_WIDTH * lane_count as f32))
+    .h_full()
+}
+
+/// Renders a ref decorating a commit, as `git log` lists it, e.g. `HEAD -> main`
+/// or `tag: v1.0`.
+fn render_ref(name: &SharedString, cx: &App) -> impl IntoElement {
+    let (label, is_head, is_tag) = if let Some(branch) = name.strip_prefix("HEAD -> ") {
+        (branch, true, false)
+    } else if let Some(tag) = name.strip_prefix("tag: ") {
+        (tag, false, true)
+    } else {
+        (name.as_ref(), name == "HEAD", false)
+    };
+    let colors = cx.theme().colors();
+    h_flex()
+        .flex_none()
+        .px_1()
+        .gap_0p5()
+        .rounded_sm()
+        .border_1()
+        .border_color(if is_head {
+            colors.border_focused
+        } else {
+            colors.border_variant
+        })
+        .when(!is_tag, |this| {
+            this.child(
+                Icon::new(IconName::GitBranchSmall)
+                    .size(IconSize::XSmall)
+                    .color(Color::Muted),
+            )
+        })
+        .child(
+            Label::new(label.to_string())
+                .size(LabelSize::XSmall)
+                .color(if is_tag {
+                    Color::Accent
+                } else {
+                    Color::Default
+                }),
+        )
+}
+
+impl EventEmitter<()> for GitLog {}
+
+impl Focusable for GitLog {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for GitLog {
+    type Event = ();
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::GitBranch))
+    }
+
+    fn tab_content_text(&self, _window: &Window, _cx: &App) -> Option<SharedString> {
+        let file_name = self
+            .path
+            .as_ref()
+            .and_then(|path| path.file_name())
+            .map(|name| name.to_string_lossy().into_owned());
+        Some(match file_name {
+            Some(file_name) => format!("History: {file_name}").into(),
+            None => "Git History".into(),
+        })
+    }
+}
+
+impl Render for GitLog {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let body = if self.entries.is_empty() {
+            let message: SharedString = if let Some(error) = &self.error {
+                error.clone()
+            } else if self.complete {
+                "No commits".into()
+            } else {
+                "Loading history…".into()
+            };
+            h_flex()
+                .size_full()
+                .justify_center()
+                .child(Label::new(message).color(Color::Muted))
+                .into_any_element()
+        } else {
+            uniform_list(
+                cx.entity().clone(),
+                "git-log-entries",
+                self.entries.len(),
+                |this, range, _window, cx| {
+                    // Load the next page before the end of the loaded log is reached.
+                    if range.end + PAGE_SIZE / 2 >= this.entries.len() {
+                        this.load_next_page(cx);
+                    }
+                    range.map(|ix| this.render_entry(ix, cx)).collect()
+                },
+            )
+            .size_full()
+            .track_scroll(self.scroll_handle.clone())
+            .into_any_element()
+        };
+
+        v_flex()
+            .key_context(self.dispatch_context(window))
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::select_next))
+            .on_action(cx.listener(Self::select_previous))
+            .on_action(cx.listener(Self::select_first))
+            .on_action(cx.listener(Self::select_last))
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::interactive_rebase))
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(self.render_toolbar(cx))
+            .child(div().flex_1().min_h_0().child(body))
+    }
+}
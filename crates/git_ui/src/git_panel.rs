@@ -3,6 +3,7 @@ use crate::commit_modal::CommitModal;
 use crate::commit_tooltip::CommitTooltip;
 use crate::commit_view::CommitView;
 use crate::git_panel_settings::StatusStyle;
+use crate::merge_editor::MergeEditor;
 use crate::project_diff::{self, Diff, ProjectDiff};
 use crate::remote_output::{self, RemoteAction, SuccessMessage};
 use crate::{branch_picker, picker_prompt, render_remote_button};
@@ -19,14 +20,18 @@ use editor::{
     scroll::ScrollbarAutoHide,
 };
 use futures::StreamExt as _;
+use futures::channel::oneshot;
 use git::blame::ParsedCommitMessage;
 use git::repository::{
     Branch, CommitDetails, CommitSummary, DiffType, PushOptions, Remote, RemoteCommandOutput,
-    ResetMode, Upstream, UpstreamTracking, UpstreamTrackingStatus,
+    ResetMode, StashScope, SubmoduleStatus, Upstream, UpstreamTracking, UpstreamTrackingStatus,
 };
 use git::status::StageStatus;
 use git::{Commit, ToggleStaged, repository::RepoPath, status::FileStatus};
-use git::{ExpandCommitEditor, RestoreTrackedFiles, StageAll, TrashUntrackedFiles, UnstageAll};
+use git::{
+    ExpandCommitEditor, RestoreTrackedFiles, SHORT_SHA_LENGTH, StageAll, StashAll, StashStaged,
+    TrashUntrackedFiles, UnstageAll,
+};
 use gpui::{
     Action, Animation, AnimationExt as _, Axis, ClickEvent, Corner, DismissEvent, Entity,
     EventEmitter, FocusHandle, Focusable, KeyContext, ListHorizontalSizingBehavior,
@@ -109,6 +114,8 @@ enum TrashCancel {
 
 fn git_panel_context_menu(
     focus_handle: FocusHandle,
+    runs_git_locally: bool,
+    has_submodules: bool,
     window: &mut Window,
     cx: &mut App,
 ) -> Entity<ContextMenu> {
@@ -122,9 +129,39 @@ fn git_panel_context_menu(
             .separator()
             .action("Discard Tracked Changes", RestoreTrackedFiles.boxed_clone())
             .action("Trash Untracked Files", TrashUntrackedFiles.boxed_clone())
+            .when(runs_git_locally, |menu| {
+                menu.separator()
+                    .action("Stash All", StashAll.boxed_clone())
+                    .action("Stash Staged", StashStaged.boxed_clone())
+                    .action("Pop Stash", git::StashPop.boxed_clone())
+                    .action("View Stash", git::ViewStash.boxed_clone())
+            })
+            .separator()
+            .action("View History", git::ViewHistory.boxed_clone())
+            .action("Interactive Rebase", git::InteractiveRebase.boxed_clone())
+            .when(runs_git_locally, |menu| {
+                menu.separator()
+                    .action("Add Worktree", git::AddWorktree.boxed_clone())
+                    .action("Open Worktree", git::OpenWorktree.boxed_clone())
+            })
+            .when(runs_git_locally && has_submodules, |menu| {
+                menu.separator()
+                    .action("Initialize Submodules", git::InitSubmodules.boxed_clone())
+                    .action("Update Submodules", git::UpdateSubmodules.boxed_clone())
+                    .action("Sync Submodules", git::SyncSubmodules.boxed_clone())
+            })
     })
 }
 
+fn submodule_status_label(status: SubmoduleStatus) -> &'static str {
+    match status {
+        SubmoduleStatus::Uninitialized => "not initialized",
+        SubmoduleStatus::UpToDate => "submodule",
+        SubmoduleStatus::Modified => "new commits",
+        SubmoduleStatus::Conflicted => "conflicted submodule",
+    }
+}
+
 const GIT_PANEL_KEY: &str = "GitPanel";
 
 const UPDATE_DEBOUNCE: Duration = Duration::from_millis(50);
@@ -1251,6 +1288,220 @@ impl GitPanel {
         self.change_file_stage(false, entries, cx);
     }
 
+    pub fn stash_all(&mut self, _: &StashAll, window: &mut Window, cx: &mut Context<Self>) {
+        self.stash(StashScope::All, window, cx);
+    }
+
+    pub fn stash_staged(&mut self, _: &StashStaged, window: &mut Window, cx: &mut Context<Self>) {
+        self.stash(StashScope::Staged, window, cx);
+    }
+
+    fn stash_selected(&mut self, _: &git::StashFile, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(status_entry) = self
+            .get_selected_entry()
+            .and_then(|entry| entry.status_entry())
+        else {
+            return;
+        };
+        let scope = StashScope::Paths(vec![status_entry.repo_path.clone()]);
+        self.stash(scope, window, cx);
+    }
+
+    fn open_merge_editor(
+        &mut self,
+        _: &git::OpenMergeEditor,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(status_entry) = self
+            .get_selected_entry()
+            .and_then(|entry| entry.status_entry())
+            .filter(|entry| entry.status.is_conflicted())
+        else {
+            return;
+        };
+        let Some(active_repository) = self.active_repository.clone() else {
+            return;
+        };
+        let path = status_entry.repo_path.clone();
+        self.workspace
+            .update(cx, |workspace, cx| {
+                MergeEditor::open(active_repository, path, workspace, window, cx);
+            })
+            .ok();
+    }
+
+    pub fn init_submodules(
+        &mut self,
+        _: &git::InitSubmodules,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(active_repository) = self.active_repository.clone() else {
+            return;
+        };
+        let paths = self.selected_submodule_paths(cx);
+        let job = active_repository.read(cx).submodule_init(paths);
+        self.handle_submodule_job("submodule init", job, cx);
+    }
+
+    pub fn update_submodules(
+        &mut self,
+        _: &git::UpdateSubmodules,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(active_repository) = self.active_repository.clone() else {
+            return;
+        };
+        let paths = self.selected_submodule_paths(cx);
+        let job = active_repository.read(cx).submodule_update(paths);
+        self.handle_submodule_job("submodule update", job, cx);
+    }
+
+    pub fn sync_submodules(
+        &mut self,
+        _: &git::SyncSubmodules,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(active_repository) = self.active_repository.clone() else {
+            return;
+        };
+        let paths = self.selected_submodule_paths(cx);
+        let job = active_repository.read(cx).submodule_sync(paths);
+        self.handle_submodule_job("submodule sync", job, cx);
+    }
+
+    /// The selected entry's path if it's a submodule, so that submodule actions
+    /// apply to it alone. Otherwise they apply to every submodule.
+    fn selected_submodule_paths(&self, cx: &App) -> Vec<RepoPath> {
+        self.get_selected_entry()
+            .and_then(|entry| entry.status_entry())
+            .filter(|entry| self.submodule_status(&entry.repo_path, cx).is_some())
+            .map(|entry| vec![entry.repo_path.clone()])
+            .unwrap_or_default()
+    }
+
+    fn handle_submodule_job(
+        &self,
+        action: &'static str,
+        job: oneshot::Receiver<Result<()>>,
+        cx: &mut Context<Self>,
+    ) {
+        cx.spawn(async move |this, cx| {
+            let result = job.await?;
+            this.update(cx, |this, cx| {
+                if let Err(e) = result {
+                    this.show_error_toast(action, e, cx);
+                }
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn submodule_status(&self, repo_path: &RepoPath, cx: &App) -> Option<SubmoduleStatus> {
+        let repository = self.active_repository.as_ref()?.read(cx);
+        Some(repository.submodule_for_path(repo_path)?.status)
+    }
+
+    fn has_submodules(&self, cx: &App) -> bool {
+        self.active_repository
+            .as_ref()
+            .is_some_and(|repository| !repository.read(cx).submodules.is_empty())
+    }
+
+    pub fn continue_rebase(
+        &mut self,
+        _: &git::ContinueRebase,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(active_repository) = self.active_repository.clone() else {
+            return;
+        };
+        if self.has_unstaged_conflicts() {
+            let prompt = window.prompt(
+                PromptLevel::Warning,
+                "There are still conflicts. You must stage these before continuing the rebase",
+                None,
+                &["Ok"],
+                cx,
+            );
+            cx.spawn(async move |_, _| {
+                prompt.await.ok();
+            })
+            .detach();
+            return;
+        }
+        let continue_task = active_repository.read(cx).continue_rebase();
+        cx.spawn(async move |this, cx| {
+            let result = continue_task.await?;
+            this.update(cx, |this, cx| {
+                if let Err(e) = result {
+                    this.show_error_toast("rebase --continue", e, cx);
+                }
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    pub fn abort_rebase(
+        &mut self,
+        _: &git::AbortRebase,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(active_repository) = self.active_repository.clone() else {
+            return;
+        };
+        let prompt = window.prompt(
+            PromptLevel::Warning,
+            "Abort the rebase?",
+            Some("The branch will be returned to where it was before the rebase started."),
+            &["Abort Rebase", "Cancel"],
+            cx,
+        );
+        cx.spawn(async move |this, cx| {
+            if prompt.await? != 0 {
+                return Ok(());
+            }
+            let result = active_repository
+                .update(cx, |repository, _| repository.abort_rebase())?
+                .await?;
+            this.update(cx, |this, cx| {
+                if let Err(e) = result {
+                    this.show_error_toast("rebase --abort", e, cx);
+                }
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Stashes changes, using the commit message, if any, as the stash's message.
+    fn stash(&mut self, scope: StashScope, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(active_repository) = self.active_repository.clone() else {
+            return;
+        };
+        let message = self.commit_editor.read(cx).text(cx);
+        let message = (!message.trim().is_empty()).then(|| message.trim().to_string());
+        let clear_message = message.is_some();
+        let stash_task = active_repository.read(cx).stash_push(scope, message);
+        cx.spawn_in(window, async move |this, cx| {
+            let result = stash_task.await?;
+            this.update_in(cx, |this, window, cx| match result {
+                Ok(()) => {
+                    if clear_message {
+                        this.commit_editor
+                            .update(cx, |editor, cx| editor.clear(window, cx));
+                    }
+                }
+                Err(e) => this.show_error_toast("stash", e, cx),
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
     fn toggle_staged_for_entry(
         &mut self,
         entry: &GitListEntry,
@@ -2013,6 +2264,12 @@ impl GitPanel {
         !self.project.read(cx).is_via_collab()
     }
 
+    /// Stashing, worktrees and submodules have no RPCs, so they're only offered
+    /// when git runs on this machine.
+    fn runs_git_locally(&self, cx: &App) -> bool {
+        self.project.read(cx).is_local()
+    }
+
     fn get_current_remote(
         &mut self,
         window: &mut Window,
@@ -2622,15 +2879,25 @@ impl GitPanel {
         path + file_name
     }
 
-    fn render_overflow_menu(&self, id: impl Into<ElementId>) -> impl IntoElement {
+    fn render_overflow_menu(&self, id: impl Into<ElementId>, cx: &App) -> impl IntoElement {
         let focus_handle = self.focus_handle.clone();
+        let runs_git_locally = self.runs_git_locally(cx);
+        let has_submodules = self.has_submodules(cx);
         PopoverMenu::new(id.into())
             .trigger(
                 IconButton::new("overflow-menu-trigger", IconName::EllipsisVertical)
                     .icon_size(IconSize::Small)
                     .icon_color(Color::Muted),
             )
-            .menu(move |window, cx| Some(git_panel_context_menu(focus_handle.clone(), window, cx)))
+            .menu(move |window, cx| {
+                Some(git_panel_context_menu(
+                    focus_handle.clone(),
+                    runs_git_locally,
+                    has_submodules,
+                    window,
+                    cx,
+                ))
+            })
             .anchor(Corner::TopRight)
     }
 
@@ -2807,7 +3074,7 @@ impl GitPanel {
                         }),
                 )
                 .child(div().flex_grow()) // spacer
-                .child(self.render_overflow_menu("overflow_menu"))
+                .child(self.render_overflow_menu("overflow_menu", cx))
                 .child(div().w_2()) // another spacer
                 .child(
                     panel_filled_button(text)
@@ -2997,6 +3264,70 @@ impl GitPanel {
         Some(footer)
     }
 
+    fn render_rebase_status(&self, cx: &mut Context<Self>) -> Option<impl IntoElement> {
+        let active_repository = self.active_repository.as_ref()?;
+        let rebase = active_repository.read(cx).rebase.clone()?;
+        let onto = rebase.onto.get(..SHORT_SHA_LENGTH).unwrap_or(&rebase.onto);
+        let description = match &rebase.branch {
+            Some(branch) => format!("Rebasing {branch} onto {onto}"),
+            None => format!("Rebasing onto {onto}"),
+        };
+        let has_unstaged_conflicts = self.has_unstaged_conflicts();
+
+        Some(
+            h_flex()
+                .items_center()
+                .py_1p5()
+                .px(px(8.))
+                .gap_1p5()
+                .border_t_1()
+                .border_color(cx.theme().colors().border)
+                .child(
+                    Icon::new(IconName::GitBranch)
+                        .size(IconSize::Small)
+                        .color(Color::Warning),
+                )
+                .child(Label::new(description).size(LabelSize::Small).truncate())
+                .when(rebase.total > 0, |this| {
+                    this.child(
+                        Label::new(format!("{}/{}", rebase.step, rebase.total))
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                })
+                .child(div().flex_1())
+                .child(
+                    Button::new("abort-rebase", "Abort")
+                        .label_size(LabelSize::Small)
+                        .tooltip(Tooltip::for_action_title("Abort Rebase", &git::AbortRebase))
+                        .on_click(cx.listener(|this, _, window, cx| {
+                            this.abort_rebase(&git::AbortRebase, window, cx)
+                        })),
+                )
+                .child(
+                    Button::new("continue-rebase", "Continue")
+                        .label_size(LabelSize::Small)
+                        .style(ButtonStyle::Filled)
+                        .disabled(has_unstaged_conflicts)
+                        .tooltip(move |window, cx| {
+                            if has_unstaged_conflicts {
+                                Tooltip::simple("Stage the resolved conflicts to continue", cx)
+                            } else {
+                                Tooltip::for_action(
+                                    "Continue Rebase",
+                                    &git::ContinueRebase,
+                                    window,
+                                    cx,
+                                )
+                            }
+                        })
+                        .on_click(cx.listener(|this, _, window, cx| {
+                            this.continue_rebase(&git::ContinueRebase, window, cx)
+                        })),
+                ),
+        )
+    }
+
     fn render_previous_commit(&self, cx: &mut Context<Self>) -> Option<impl IntoElement> {
         let active_repository = self.active_repository.as_ref()?;
         let branch = active_repository.read(cx).branch.as_ref()?;
@@ -3485,12 +3816,25 @@ impl GitPanel {
         } else {
             "Restore File"
         };
+        let is_conflicted = entry.status.is_conflicted();
+        let runs_git_locally = self.runs_git_locally(cx);
+        let is_submodule = self.submodule_status(&entry.repo_path, cx).is_some();
         let context_menu = ContextMenu::build(window, cx, |context_menu, _, _| {
             context_menu
                 .context(self.focus_handle.clone())
                 .action(stage_title, ToggleStaged.boxed_clone())
                 .action(restore_title, git::RestoreFile::default().boxed_clone())
+                .when(runs_git_locally, |menu| {
+                    menu.action("Stash File", git::StashFile.boxed_clone())
+                })
                 .separator()
+                .when(is_conflicted, |menu| {
+                    menu.action("Open Merge Editor", git::OpenMergeEditor.boxed_clone())
+                })
+                .when(runs_git_locally && is_submodule, |menu| {
+                    menu.action("Update Submodule", git::UpdateSubmodules.boxed_clone())
+                        .action("Sync Submodule", git::SyncSubmodules.boxed_clone())
+                })
                 .action("Open Diff", Confirm.boxed_clone())
                 .action("Open File", SecondaryConfirm.boxed_clone())
         });
@@ -3504,7 +3848,13 @@ impl GitPanel {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        let context_menu = git_panel_context_menu(self.focus_handle.clone(), window, cx);
+        let context_menu = git_panel_context_menu(
+            self.focus_handle.clone(),
+            self.runs_git_locally(cx),
+            self.has_submodules(cx),
+            window,
+            cx,
+        );
         self.set_context_menu(context_menu, position, window, cx);
     }
 
@@ -3568,6 +3918,8 @@ impl GitPanel {
             Color::Default
         };
 
+        let submodule_status = self.submodule_status(&entry.repo_path, cx);
+
         let path_color = if status.is_deleted() {
             Color::Disabled
         } else {
@@ -3751,7 +4103,16 @@ impl GitPanel {
                     .child(
                         self.entry_label(display_name.clone(), label_color)
                             .when(status.is_deleted(), |this| this.strikethrough()),
-                    ),
+                    )
+                    .when_some(submodule_status, |this, submodule_status| {
+                        this.child(
+                            div().pl_1().child(
+                                Label::new(submodule_status_label(submodule_status))
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted),
+                            ),
+                        )
+                    }),
             )
             .into_any_element()
     }
@@ -3806,6 +4167,17 @@ impl Render for GitPanel {
                     .on_action(cx.listener(Self::restore_tracked_files))
                     .on_action(cx.listener(Self::revert_selected))
                     .on_action(cx.listener(Self::clean_all))
+                    .when(self.runs_git_locally(cx), |this| {
+                        this.on_action(cx.listener(Self::stash_all))
+                            .on_action(cx.listener(Self::stash_staged))
+                            .on_action(cx.listener(Self::stash_selected))
+                            .on_action(cx.listener(Self::init_submodules))
+                            .on_action(cx.listener(Self::update_submodules))
+                            .on_action(cx.listener(Self::sync_submodules))
+                    })
+                    .on_action(cx.listener(Self::open_merge_editor))
+                    .on_action(cx.listener(Self::continue_rebase))
+                    .on_action(cx.listener(Self::abort_rebase))
                     .on_action(cx.listener(Self::generate_commit_message_action))
             })
             .on_action(cx.listener(Self::select_first))
@@ -3844,6 +4216,7 @@ impl Render for GitPanel {
                             this.child(self.render_empty_state(cx).into_any_element())
                         }
                     })
+                    .children(self.render_rebase_status(cx))
                     .children(self.render_footer(window, cx))
                     .children(self.render_previous_commit(cx))
                     .into_any_element(),
@@ -4453,8 +4826,9 @@ impl ComponentPreview for PanelRepoFooter {
 
 #[cfg(test)]
 mod tests {
-    use git::status::StatusCode;
-    use gpui::TestAppContext;
+    use git::repository::RebaseState;
+    use git::status::{StatusCode, UnmergedStatus, UnmergedStatusCode};
+    use gpui::{TestAppContext, VisualTestContext};
     use project::{FakeFs, WorktreeSettings};
     use serde_json::json;
     use settings::SettingsStore;
@@ -4646,4 +5020,200 @@ mod tests {
             ],
         );
     }
+
+    #[gpui::test]
+    async fn test_stash(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/root",
+            json!({
+                "notes": {
+                    ".git": {},
+                    "draft.md": "draft",
+                    "ideas.md": "ideas",
+                },
+            }),
+        )
+        .await;
+
+        let dot_git = Path::new(path!("/root/notes/.git"));
+        fs.set_branch_name(dot_git, Some("main"));
+        fs.set_status_for_repo(
+            dot_git,
+            &[
+                (Path::new("draft.md"), StatusCode::Modified.worktree()),
+                (Path::new("ideas.md"), StatusCode::Modified.worktree()),
+            ],
+        );
+
+        let project = Project::test(fs.clone(), [path!("/root/notes").as_ref()], cx).await;
+        let (workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project.clone(), window, cx));
+        cx.executor().run_until_parked();
+
+        let app_state = workspace.update(cx, |workspace, _| workspace.app_state().clone());
+        let panel = cx.new_window_entity(|window, cx| {
+            GitPanel::new(workspace.clone(), project.clone(), app_state, window, cx)
+        });
+
+        let status_entry_paths = |panel: &Entity<GitPanel>, cx: &mut VisualTestContext| {
+            cx.run_until_parked();
+            cx.executor().advance_clock(2 * UPDATE_DEBOUNCE);
+            cx.run_until_parked();
+            panel.update(cx, |panel, _| {
+                panel
+                    .entries
+                    .iter()
+                    .filter_map(|entry| entry.status_entry())
+                    .map(|entry| entry.repo_path.to_string())
+                    .collect::<Vec<_>>()
+            })
+        };
+        assert_eq!(status_entry_paths(&panel, cx), ["draft.md", "ideas.md"]);
+
+        cx.update_window_entity(&panel, |panel, window, cx| {
+            panel.selected_entry = panel.entries.iter().position(|entry| {
+                entry
+                    .status_entry()
+                    .is_some_and(|entry| entry.repo_path.as_ref() == Path::new("ideas.md"))
+            });
+            panel.stash_selected(&git::StashFile, window, cx);
+        });
+        assert_eq!(status_entry_paths(&panel, cx), ["draft.md"]);
+
+        cx.update_window_entity(&panel, |panel, window, cx| {
+            panel
+                .commit_editor
+                .update(cx, |editor, cx| editor.set_text("Rewrite", window, cx));
+            panel.stash_all(&StashAll, window, cx);
+        });
+        assert!(status_entry_paths(&panel, cx).is_empty());
+        panel.update(cx, |panel, cx| {
+            assert_eq!(panel.commit_editor.read(cx).text(cx), "");
+        });
+
+        let repository = panel.update(cx, |panel, _| panel.active_repository.clone().unwrap());
+        let stash_entries = repository
+            .update(cx, |repository, _| repository.stash_entries())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            stash_entries
+                .iter()
+                .map(|entry| entry.message.as_ref())
+                .collect::<Vec<_>>(),
+            ["On main: Rewrite", "WIP on main"]
+        );
+
+        repository
+            .update(cx, |repository, _| {
+                repository.stash_pop(stash_entries[1].clone())
+            })
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(status_entry_paths(&panel, cx), ["ideas.md"]);
+        assert_eq!(
+            fs.load(Path::new(path!("/root/notes/ideas.md")))
+                .await
+                .unwrap(),
+            "ideas"
+        );
+
+        repository
+            .update(cx, |repository, _| {
+                repository.stash_drop(stash_entries[0].clone())
+            })
+            .await
+            .unwrap()
+            .unwrap();
+        let stash_entries = repository
+            .update(cx, |repository, _| repository.stash_entries())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(stash_entries.is_empty());
+        assert_eq!(status_entry_paths(&panel, cx), ["ideas.md"]);
+    }
+
+    #[gpui::test]
+    async fn test_rebase_conflicts(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/root",
+            json!({
+                "notes": {
+                    ".git": {},
+                    "draft.md": "draft",
+                },
+            }),
+        )
+        .await;
+
+        let dot_git = Path::new(path!("/root/notes/.git"));
+        fs.set_branch_name(dot_git, Some("main"));
+        fs.set_status_for_repo(
+            dot_git,
+            &[(Path::new("draft.md"), StatusCode::Modified.worktree())],
+        );
+
+        let project = Project::test(fs.clone(), [path!("/root/notes").as_ref()], cx).await;
+        let (workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project.clone(), window, cx));
+        cx.executor().run_until_parked();
+
+        let app_state = workspace.update(cx, |workspace, _| workspace.app_state().clone());
+        let panel = cx.new_window_entity(|window, cx| {
+            GitPanel::new(workspace.clone(), project.clone(), app_state, window, cx)
+        });
+        let rebase_status = |panel: &Entity<GitPanel>, cx: &mut VisualTestContext| {
+            cx.run_until_parked();
+            cx.executor().advance_clock(2 * UPDATE_DEBOUNCE);
+            cx.run_until_parked();
+            panel.update(cx, |panel, cx| {
+                let repository = panel.active_repository.as_ref().unwrap().read(cx);
+                (
+                    repository.rebase.is_some(),
+                    repository.merge_conflicts.iter().count(),
+                    panel.conflicted_count,
+                )
+            })
+        };
+        assert_eq!(rebase_status(&panel, cx), (false, 0, 0));
+
+        // A rebase stopping on a conflict reports it without any merge heads.
+        fs.with_git_state(dot_git, false, |state| {
+            state.rebase_state = Some(RebaseState {
+                branch: Some("main".into()),
+                onto: "abc123".into(),
+                stopped_sha: Some("def456".into()),
+                step: 1,
+                total: 2,
+            });
+        })
+        .unwrap();
+        fs.set_status_for_repo(
+            dot_git,
+            &[(
+                Path::new("draft.md"),
+                UnmergedStatus {
+                    first_head: UnmergedStatusCode::Updated,
+                    second_head: UnmergedStatusCode::Updated,
+                }
+                .into(),
+            )],
+        );
+        assert_eq!(rebase_status(&panel, cx), (true, 1, 1));
+
+        fs.with_git_state(dot_git, false, |state| state.rebase_state = None)
+            .unwrap();
+        fs.set_status_for_repo(
+            dot_git,
+            &[(Path::new("draft.md"), StatusCode::Modified.worktree())],
+        );
+        assert_eq!(rebase_status(&panel, cx), (false, 0, 0));
+    }
 }
@@ -20,13 +20,19 @@ pub mod branch_picker;
 mod commit_modal;
 pub mod commit_tooltip;
 mod commit_view;
+mod git_log;
 pub mod git_panel;
 mod git_panel_settings;
+mod log_graph;
+mod merge_editor;
 pub mod onboarding;
 pub mod picker_prompt;
 pub mod project_diff;
+mod rebase_editor;
 pub(crate) mod remote_output;
 pub mod repository_selector;
+pub mod stash_picker;
+pub mod worktree_picker;
 
 actions!(git, [ResetOnboarding]);
 
@@ -41,6 +47,16 @@ pub fn init(cx: &mut App) {
         git_panel::register(workspace);
         repository_selector::register(workspace);
         branch_picker::register(workspace);
+        git_log::register(workspace);
+        rebase_editor::register(workspace);
+        merge_editor::register(workspace);
+
+        // Stashing, worktrees and submodules have no RPCs yet, so they're only
+        // offered when git runs locally.
+        if workspace.project().read(cx).is_local() {
+            stash_picker::register(workspace);
+            worktree_picker::register(workspace);
+        }
 
         let project = workspace.project().read(cx);
         if project.is_read_only(cx) {
@@ -96,6 +112,66 @@ pub fn init(cx: &mut App) {
                 panel.unstage_all(action, window, cx);
             });
         });
+        if project.is_local() {
+            workspace.register_action(|workspace, action: &git::StashAll, window, cx| {
+                let Some(panel) = workspace.panel::<git_panel::GitPanel>(cx) else {
+                    return;
+                };
+                panel.update(cx, |panel, cx| {
+                    panel.stash_all(action, window, cx);
+                });
+            });
+            workspace.register_action(|workspace, action: &git::StashStaged, window, cx| {
+                let Some(panel) = workspace.panel::<git_panel::GitPanel>(cx) else {
+                    return;
+                };
+                panel.update(cx, |panel, cx| {
+                    panel.stash_staged(action, window, cx);
+                });
+            });
+        }
+        workspace.register_action(|workspace, action: &git::ContinueRebase, window, cx| {
+            let Some(panel) = workspace.panel::<git_panel::GitPanel>(cx) else {
+                return;
+            };
+            panel.update(cx, |panel, cx| {
+                panel.continue_rebase(action, window, cx);
+            });
+        });
+        workspace.register_action(|workspace, action: &git::AbortRebase, window, cx| {
+            let Some(panel) = workspace.panel::<git_panel::GitPanel>(cx) else {
+                return;
+            };
+            panel.update(cx, |panel, cx| {
+                panel.abort_rebase(action, window, cx);
+            });
+        });
+        if project.is_local() {
+            workspace.register_action(|workspace, action: &git::InitSubmodules, window, cx| {
+                let Some(panel) = workspace.panel::<git_panel::GitPanel>(cx) else {
+                    return;
+                };
+                panel.update(cx, |panel, cx| {
+                    panel.init_submodules(action, window, cx);
+                });
+            });
+            workspace.register_action(|workspace, action: &git::UpdateSubmodules, window, cx| {
+                let Some(panel) = workspace.panel::<git_panel::GitPanel>(cx) else {
+                    return;
+                };
+                panel.update(cx, |panel, cx| {
+                    panel.update_submodules(action, window, cx);
+                });
+            });
+            workspace.register_action(|workspace, action: &git::SyncSubmodules, window, cx| {
+                let Some(panel) = workspace.panel::<git_panel::GitPanel>(cx) else {
+                    return;
+                };
+                panel.update(cx, |panel, cx| {
+                    panel.sync_submodules(action, window, cx);
+                });
+            });
+        }
         CommandPaletteFilter::update_global(cx, |filter, _cx| {
             filter.hide_action_types(&[
                 zed_actions::OpenGitIntegrationOnboarding.type_id(),
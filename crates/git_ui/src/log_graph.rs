@@ -0,0 +1,190 @@
+use gpui::SharedString;
+
+/// Assigns the commits of a log to lanes, so that the log can be drawn as a
+/// graph of branches and merges.
+///
+/// Commits are pushed newest first, and a graph can be extended page by page
+/// as more of the log is loaded.
+#[derive(Debug, Default)]
+pub struct LogGraph {
+    /// The commit each lane is waiting for, or `None` for a free lane.
+    lanes: Vec<Option<SharedString>>,
+}
+
+/// How one commit's row of the graph is drawn.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GraphRow {
+    /// The lane the commit's node is drawn in.
+    pub lane: usize,
+    /// Lines in the top half of the row, from a lane at its top edge to a
+    /// lane at its middle.
+    pub incoming: Vec<(usize, usize)>,
+    /// Lines in the bottom half of the row, from a lane at its middle to a
+    /// lane at its bottom edge.
+    pub outgoing: Vec<(usize, usize)>,
+}
+
+impl GraphRow {
+    /// The number of lanes the row spans.
+    pub fn width(&self) -> usize {
+        self.incoming
+            .iter()
+            .chain(&self.outgoing)
+            .flat_map(|(from, to)| [*from, *to])
+            .chain([self.lane])
+            .max()
+            .map_or(0, |lane| lane + 1)
+    }
+}
+
+impl LogGraph {
+    pub fn push(&mut self, sha: &str, parents: &[SharedString]) -> GraphRow {
+        let lane = self
+            .lanes
+            .iter()
+            .position(|expected| expected.as_deref() == Some(sha))
+            .unwrap_or_else(|| self.free_lane());
+
+        // Every lane waiting for this commit ends at its node.
+        let mut incoming = Vec::new();
+        for (ix, expected) in self.lanes.iter_mut().enumerate() {
+            match expected {
+                Some(expected_sha) if expected_sha.as_ref() == sha => {
+                    incoming.push((ix, lane));
+                    *expected = None;
+                }
+                Some(_) => incoming.push((ix, ix)),
+                None => {}
+            }
+        }
+
+        let mut parents = parents.iter();
+        let mut new_lanes = Vec::new();
+        let mut merges = Vec::new();
+        if let Some(first_parent) = parents.next() {
+            self.lanes[lane] = Some(first_parent.clone());
+            new_lanes.push(lane);
+        }
+        for parent in parents {
+            match self
+                .lanes
+                .iter()
+                .position(|expected| expected.as_ref() == Some(parent))
+            {
+                Some(existing) => merges.push((lane, existing)),
+                None => {
+                    let parent_lane = self.free_lane();
+                    self.lanes[parent_lane] = Some(parent.clone());
+                    new_lanes.push(parent_lane);
+                }
+            }
+        }
+
+        let mut outgoing = merges;
+        for (ix, expected) in self.lanes.iter().enumerate() {
+            if expected.is_some() {
+                if new_lanes.contains(&ix) {
+                    outgoing.push((lane, ix));
+                } else {
+                    outgoing.push((ix, ix));
+                }
+            }
+        }
+        outgoing.sort_unstable();
+
+        while self.lanes.last().is_some_and(Option::is_none) {
+            self.lanes.pop();
+        }
+
+        GraphRow {
+            lane,
+            incoming,
+            outgoing,
+        }
+    }
+
+    fn free_lane(&mut self) -> usize {
+        match self.lanes.iter().position(Option::is_none) {
+            Some(lane) => lane,
+            None => {
+                self.lanes.push(None);
+                self.lanes.len() - 1
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn push(graph: &mut LogGraph, sha: &str, parents: &[&str]) -> GraphRow {
+        let parents = parents
+            .iter()
+            .map(|parent| SharedString::from(parent.to_string()))
+            .collect::<Vec<_>>();
+        graph.push(sha, &parents)
+    }
+
+    #[test]
+    fn test_linear_history() {
+        let mut graph = LogGraph::default();
+        assert_eq!(
+            push(&mut graph, "c", &["b"]),
+            GraphRow {
+                lane: 0,
+                incoming: vec![],
+                outgoing: vec![(0, 0)],
+            }
+        );
+        assert_eq!(
+            push(&mut graph, "b", &["a"]),
+            GraphRow {
+                lane: 0,
+                incoming: vec![(0, 0)],
+                outgoing: vec![(0, 0)],
+            }
+        );
+        assert_eq!(
+            push(&mut graph, "a", &[]),
+            GraphRow {
+                lane: 0,
+                incoming: vec![(0, 0)],
+                outgoing: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn test_branch_and_merge() {
+        // merge ─┬─ feature ─┐
+        //        └───────────┴─ base
+        let mut graph = LogGraph::default();
+        let merge = push(&mut graph, "merge", &["base", "feature"]);
+        assert_eq!(merge.lane, 0);
+        assert_eq!(merge.outgoing, [(0, 0), (0, 1)]);
+        assert_eq!(merge.width(), 2);
+
+        let feature = push(&mut graph, "feature", &["base"]);
+        assert_eq!(feature.lane, 1);
+        assert_eq!(feature.incoming, [(0, 0), (1, 1)]);
+        assert_eq!(feature.outgoing, [(0, 0), (1, 1)]);
+
+        let base = push(&mut graph, "base", &[]);
+        assert_eq!(base.lane, 0);
+        assert_eq!(base.incoming, [(0, 0), (1, 0)]);
+        assert!(base.outgoing.is_empty());
+        assert!(graph.lanes.is_empty());
+    }
+
+    #[test]
+    fn test_merge_into_existing_lane() {
+        // Two branch tips whose histories meet: the second merges the first's
+        // parent, which already has a lane.
+        let mut graph = LogGraph::default();
+        push(&mut graph, "tip", &["base"]);
+        let merge = push(&mut graph, "merge", &["other", "base"]);
+        assert_eq!(merge.lane, 1);
+        assert_eq!(merge.outgoing, [(0, 0), (1, 0), (1, 1)]);
+    }
+}
@@ -0,0 +1,688 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use buffer_diff::BufferDiff;
+use collections::HashSet;
+use editor::display_map::{
+    BlockContext, BlockPlacement, BlockProperties, BlockStyle, CustomBlockId,
+};
+use editor::scroll::Autoscroll;
+use editor::{Editor, MultiBuffer, ToOffset as _};
+use git::conflict::{ConflictRegion, conflict_regions};
+use git::repository::{ConflictVersions, RepoPath};
+use gpui::{
+    App, Entity, EventEmitter, FocusHandle, Focusable, PromptLevel, Subscription, Task, WeakEntity,
+    actions,
+};
+use language::{Buffer, BufferEvent, Capability};
+use project::Project;
+use project::git_store::Repository;
+use ui::{Tooltip, prelude::*};
+use workspace::Workspace;
+use workspace::item::{Item, ItemEvent, ItemHandle as _};
+use workspace::notifications::DetachAndPromptErr;
+
+actions!(
+    merge_editor,
+    [
+        AcceptOurs,
+        AcceptTheirs,
+        AcceptBoth,
+        NextConflict,
+        PreviousConflict,
+        MarkResolved
+    ]
+);
+
+pub fn register(workspace: &mut Workspace) {
+    workspace.register_action(|workspace, _: &git::OpenMergeEditor, window, cx| {
+        let Some(editor) = workspace.active_item_as::<Editor>(cx) else {
+            return;
+        };
+        let Some(buffer) = editor.read(cx).buffer().read(cx).as_singleton() else {
+            return;
+        };
+        let Some((repository, path)) = workspace
+            .project()
+            .read(cx)
+            .git_store()
+            .read(cx)
+            .repository_and_path_for_buffer_id(buffer.read(cx).remote_id(), cx)
+        else {
+            return;
+        };
+        if repository.read(cx).has_conflict(&path) {
+            MergeEditor::open(repository, path, workspace, window, cx);
+        }
+    });
+}
+
+/// Which side of a conflict to keep in the result.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Side {
+    Ours,
+    Theirs,
+    /// Our lines followed by theirs.
+    Both,
+}
+
+/// Returns the text that replaces `region` when it's resolved to `side`.
+fn resolved_text(text: &str, region: &ConflictRegion, side: Side) -> String {
+    let ours = &text[region.ours.clone()];
+    let theirs = &text[region.theirs.clone()];
+    match side {
+        Side::Ours => ours.to_string(),
+        Side::Theirs => theirs.to_string(),
+        Side::Both => {
+            let mut resolved = ours.to_string();
+            if !ours.is_empty() && !ours.ends_with('\n') {
+                resolved.push('\n');
+            }
+            resolved.push_str(theirs);
+            resolved
+        }
+    }
+}
+
+/// The conflict after `offset`, wrapping around to the first one.
+fn next_conflict(regions: &[ConflictRegion], offset: usize) -> Option<&ConflictRegion> {
+    regions
+        .iter()
+        .find(|region| region.range.start > offset)
+        .or_else(|| regions.first())
+}
+
+/// The conflict before the one at `offset`, wrapping around to the last one.
+fn previous_conflict(regions: &[ConflictRegion], offset: usize) -> Option<&ConflictRegion> {
+    regions
+        .iter()
+        .rev()
+        .find(|region| region.range.end <= offset)
+        .or_else(|| regions.last())
+}
+
+enum OursHighlight {}
+enum BaseHighlight {}
+enum TheirsHighlight {}
+
+/// A tab for resolving a file's merge conflicts: the ours, base and theirs
+/// versions are shown side by side above the file itself, where each
+/// conflict can be resolved by keeping either side or both.
+///
+/// The result pane edits the project's buffer for the file, so it keeps its
+/// syntax highlighting and language server features.
+pub struct MergeEditor {
+    repository: Entity<Repository>,
+    project: Entity<Project>,
+    path: RepoPath,
+    result_buffer: Entity<Buffer>,
+    result_editor: Entity<Editor>,
+    ours_editor: Entity<Editor>,
+    base_editor: Entity<Editor>,
+    theirs_editor: Entity<Editor>,
+    conflict_count: usize,
+    conflict_blocks: HashSet<CustomBlockId>,
+    _subscription: Subscription,
+}
+
+impl MergeEditor {
+    /// Opens the merge editor for a conflicted file, or activates it if it's
+    /// already open in the active pane.
+    pub fn open(
+        repository: Entity<Repository>,
+        path: RepoPath,
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let existing = workspace
+            .active_pane()
+            .read(cx)
+            .items_of_type::<MergeEditor>()
+            .find(|editor| {
+                let editor = editor.read(cx);
+                editor.repository == repository && editor.path == path
+            });
+        if let Some(existing) = existing {
+            workspace.activate_item(&existing, true, true, window, cx);
+            return;
+        }
+
+        let Some(project_path) = repository.read(cx).repo_path_to_project_path(&path, cx) else {
+            return;
+        };
+        let project = workspace.project().clone();
+        let open_buffer = project.update(cx, |project, cx| project.open_buffer(project_path, cx));
+        let versions = repository.read(cx).load_conflict_versions(path.clone());
+        cx.spawn_in(window, async move |workspace, cx| {
+            let result_buffer = open_buffer.await?;
+            let versions = versions.await??;
+            workspace.update_in(cx, |workspace, window, cx| {
+                let merge_editor = cx.new(|cx| {
+                    MergeEditor::new(
+                        repository,
+                        project,
+                        path,
+                        result_buffer,
+                        versions,
+                        window,
+                        cx,
+                    )
+                });
+                workspace.add_item_to_active_pane(Box::new(merge_editor), None, true, window, cx);
+            })
+        })
+        .detach_and_prompt_err("Failed to open merge editor", window, cx, |_, _, _| None);
+    }
+
+    fn new(
+        repository: Entity<Repository>,
+        project: Entity<Project>,
+        path: RepoPath,
+        result_buffer: Entity<Buffer>,
+        versions: ConflictVersions,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let language = result_buffer.read(cx).language().cloned();
+        let language_registry = project.read(cx).languages().clone();
+        let side_buffer = |text: Option<String>, cx: &mut Context<Self>| {
+            cx.new(|cx| {
+                let mut buffer = Buffer::local(text.unwrap_or_default(), cx);
+                buffer.set_language_registry(language_registry.clone());
+                buffer.set_language(language.clone(), cx);
+                buffer.set_capability(Capability::ReadOnly, cx);
+                buffer
+            })
+        };
+        let base_buffer = side_buffer(versions.base, cx);
+        let ours_buffer = side_buffer(versions.ours, cx);
+        let theirs_buffer = side_buffer(versions.theirs, cx);
+
+        // Each side shows its changes since the common ancestor.
+        let mut side_editor = |buffer: Entity<Buffer>, diff_base: Option<&Entity<Buffer>>| {
+            let multibuffer = cx.new(|cx| MultiBuffer::singleton(buffer.clone(), cx));
+            if let Some(diff_base) = diff_base {
+                let diff = cx.new(|cx| {
+                    let snapshot = buffer.read(cx).text_snapshot();
+                    let mut diff = BufferDiff::new(&snapshot, cx);
+                    let _ = diff.set_base_text_buffer(diff_base.clone(), snapshot, cx);
+                    diff
+                });
+                multibuffer.update(cx, |multibuffer, cx| multibuffer.add_diff(diff, cx));
+            }
+            cx.new(|cx| {
+                let mut editor = Editor::for_multibuffer(multibuffer, None, window, cx);
+                editor.set_read_only(true);
+                editor.set_expand_all_diff_hunks(cx);
+                editor
+            })
+        };
+        let ours_editor = side_editor(ours_buffer, Some(&base_buffer));
+        let theirs_editor = side_editor(theirs_buffer, Some(&base_buffer));
+        let base_editor = side_editor(base_buffer, None);
+
+        let result_editor = cx.new(|cx| {
+            let multibuffer = cx.new(|cx| MultiBuffer::singleton(result_buffer.clone(), cx));
+            Editor::for_multibuffer(multibuffer, Some(project.clone()), window, cx)
+        });
+        let subscription = cx.subscribe(&result_buffer, |this, _, event, cx| {
+            if let BufferEvent::Edited = event {
+                this.refresh_conflicts(cx);
+            }
+        });
+
+        let mut this = Self {
+            repository,
+            project,
+            path,
+            result_buffer,
+            result_editor,
+            ours_editor,
+            base_editor,
+            theirs_editor,
+            conflict_count: 0,
+            conflict_blocks: HashSet::default(),
+            _subscription: subscription,
+        };
+        this.refresh_conflicts(cx);
+        if let Some(first) = this.conflicts(cx).first() {
+            let offset = first.range.start;
+            this.select_offset(offset, window, cx);
+        }
+        this
+    }
+
+    fn conflicts(&self, cx: &App) -> Vec<ConflictRegion> {
+        conflict_regions(&self.result_buffer.read(cx).text())
+    }
+
+    /// Highlights the sides of each conflict in the result, with buttons to
+    /// resolve it above.
+    fn refresh_conflicts(&mut self, cx: &mut Context<Self>) {
+        let conflicts = self.conflicts(cx);
+        self.conflict_count = conflicts.len();
+        let merge_editor = cx.weak_entity();
+        let old_blocks = std::mem::take(&mut self.conflict_blocks);
+        self.conflict_blocks = self.result_editor.update(cx, |editor, cx| {
+            editor.remove_blocks(old_blocks, None, cx);
+
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let anchor_range = |range: &std::ops::Range<usize>| {
+                snapshot.anchor_after(range.start)..snapshot.anchor_before(range.end)
+            };
+            let ours = conflicts
+                .iter()
+                .map(|conflict| anchor_range(&conflict.ours))
+                .collect::<Vec<_>>();
+            let base = conflicts
+                .iter()
+                .filter_map(|conflict| conflict.base.as_ref().map(anchor_range))
+                .collect::<Vec<_>>();
+            let theirs = conflicts
+                .iter()
+                .map(|conflict| anchor_range(&conflict.theirs))
+                .collect::<Vec<_>>();
+            editor.highlight_background::<OursHighlight>(
+                &ours,
+                |colors| colors.version_control_added.opacity(0.2),
+                cx,
+            );
+            editor.highlight_background::<BaseHighlight>(
+                &base,
+                |colors| colors.version_control_conflict.opacity(0.2),
+                cx,
+            );
+            editor.highlight_background::<TheirsHighlight>(
+                &theirs,
+                |colors| colors.version_control_modified.opacity(0.2),
+                cx,
+            );
+
+            let blocks = conflicts
+                .iter()
+                .enumerate()
+                .map(|(ix, conflict)| {
+                    let anchor = snapshot.anchor_after(conflict.range.start);
+                    let merge_editor = merge_editor.clone();
+                    BlockProperties {
+                        placement: BlockPlacement::Above(anchor),
+                        height: 1,
+                        style: BlockStyle::Sticky,
+                        render: Arc::new(move |cx: &mut BlockContext| {
+                            render_conflict_buttons(ix, anchor, merge_editor.clone(), cx)
+                        }),
+                        priority: 0,
+                    }
+                })
+                .collect::<Vec<_>>();
+            editor.insert_blocks(blocks, None, cx).into_iter().collect()
+        });
+        cx.notify();
+    }
+
+    fn cursor_offset(&self, cx: &mut App) -> usize {
+        self.result_editor.update(cx, |editor, cx| {
+            editor.selections.newest::<usize>(cx).head()
+        })
+    }
+
+    fn select_offset(&mut self, offset: usize, window: &mut Window, cx: &mut Context<Self>) {
+        self.result_editor.update(cx, |editor, cx| {
+            editor.change_selections(Some(Autoscroll::center()), window, cx, |selections| {
+                selections.select_ranges([offset..offset])
+            });
+        });
+    }
+
+    fn resolve_at(&mut self, offset: usize, side: Side, cx: &mut Context<Self>) {
+        let text = self.result_buffer.read(cx).text();
+        let Some(region) = conflict_regions(&text)
+            .into_iter()
+            .find(|region| region.range.contains(&offset))
+        else {
+            return;
+        };
+        let resolved = resolved_text(&text, &region, side);
+        self.result_buffer.update(cx, |buffer, cx| {
+            buffer.edit([(region.range, resolved)], None, cx);
+        });
+    }
+
+    fn accept(&mut self, side: Side, cx: &mut Context<Self>) {
+        let offset = self.cursor_offset(cx);
+        self.resolve_at(offset, side, cx);
+    }
+
+    fn accept_ours(&mut self, _: &AcceptOurs, _: &mut Window, cx: &mut Context<Self>) {
+        self.accept(Side::Ours, cx);
+    }
+
+    fn accept_theirs(&mut self, _: &AcceptTheirs, _: &mut Window, cx: &mut Context<Self>) {
+        self.accept(Side::Theirs, cx);
+    }
+
+    fn accept_both(&mut self, _: &AcceptBoth, _: &mut Window, cx: &mut Context<Self>) {
+        self.accept(Side::Both, cx);
+    }
+
+    fn next_conflict(&mut self, _: &NextConflict, window: &mut Window, cx: &mut Context<Self>) {
+        let offset = self.cursor_offset(cx);
+        let conflicts = self.conflicts(cx);
+        if let Some(conflict) = next_conflict(&conflicts, offset) {
+            self.select_offset(conflict.range.start, window, cx);
+        }
+    }
+
+    fn previous_conflict(
+        &mut self,
+        _: &PreviousConflict,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let offset = self.cursor_offset(cx);
+        let conflicts = self.conflicts(cx);
+        if let Some(conflict) = previous_conflict(&conflicts, offset) {
+            self.select_offset(conflict.range.start, window, cx);
+        }
+    }
+
+    /// Saves the result and stages it, which marks the conflict resolved.
+    fn mark_resolved(&mut self, _: &MarkResolved, window: &mut Window, cx: &mut Context<Self>) {
+        let prompt = (self.conflict_count > 0).then(|| {
+            window.prompt(
+                PromptLevel::Warning,
+                "This file still has conflict markers",
+                Some("Marking it resolved will stage it with the markers in it."),
+                &["Mark Resolved", "Cancel"],
+                cx,
+            )
+        });
+        cx.spawn_in(window, async move |this, cx| {
+            if let Some(prompt) = prompt {
+                if prompt.await? != 0 {
+                    return Ok(());
+                }
+            }
+            this.update(cx, |this, cx| {
+                let buffer = this.result_buffer.clone();
+                this.project
+                    .update(cx, |project, cx| project.save_buffer(buffer, cx))
+            })?
+            .await?;
+            this.update(cx, |this, cx| {
+                let path = this.path.clone();
+                this.repository.update(cx, |repository, cx| {
+                    repository.stage_entries(vec![path], cx)
+                })
+            })?
+            .await?;
+            this.update(cx, |_, cx| cx.emit(ItemEvent::CloseItem))
+        })
+        .detach_and_prompt_err("Failed to mark file resolved", window, cx, |_, _, _| None);
+    }
+
+    fn render_header(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let status = match self.conflict_count {
+            0 => "No conflicts left".to_string(),
+            1 => "1 conflict".to_string(),
+            count => format!("{count} conflicts"),
+        };
+        let focus_handle = self.result_editor.focus_handle(cx);
+        h_flex()
+            .p_2()
+            .gap_2()
+            .border_b_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(Label::new(self.path.to_string()))
+            .child(Label::new(status).color(Color::Muted))
+            .child(div().flex_1())
+            .child(
+                IconButton::new("previous-conflict", IconName::ArrowUp)
+                    .disabled(self.conflict_count == 0)
+                    .tooltip({
+                        let focus_handle = focus_handle.clone();
+                        move |window, cx| {
+                            Tooltip::for_action_in(
+                                "Previous Conflict",
+                                &PreviousConflict,
+                                &focus_handle,
+                                window,
+                                cx,
+                            )
+                        }
+                    })
+                    .on_click(cx.listener(|this, _, window, cx| {
+                        this.previous_conflict(&PreviousConflict, window, cx)
+                    })),
+            )
+            .child(
+                IconButton::new("next-conflict", IconName::ArrowDown)
+                    .disabled(self.conflict_count == 0)
+                    .tooltip({
+                        let focus_handle = focus_handle.clone();
+                        move |window, cx| {
+                            Tooltip::for_action_in(
+                                "Next Conflict",
+                                &NextConflict,
+                                &focus_handle,
+                                window,
+                                cx,
+                            )
+                        }
+                    })
+                    .on_click(cx.listener(|this, _, window, cx| {
+                        this.next_conflict(&NextConflict, window, cx)
+                    })),
+            )
+            .child(
+                Button::new("mark-resolved", "Mark Resolved")
+                    .style(ButtonStyle::Filled)
+                    .tooltip(move |window, cx| {
+                        Tooltip::for_action_in(
+                            "Save and Stage File",
+                            &MarkResolved,
+                            &focus_handle,
+                            window,
+                            cx,
+                        )
+                    })
+                    .on_click(cx.listener(|this, _, window, cx| {
+                        this.mark_resolved(&MarkResolved, window, cx)
+                    })),
+            )
+    }
+
+    fn render_pane(
+        &self,
+        title: &'static str,
+        editor: &Entity<Editor>,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        v_flex()
+            .flex_1()
+            .min_w_0()
+            .min_h_0()
+            .border_r_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(
+                div()
+                    .px_2()
+                    .py_1()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .child(Label::new(title).size(LabelSize::Small).color(Color::Muted)),
+            )
+            .child(div().flex_1().min_h_0().child(editor.clone()))
+    }
+}
+
+fn render_conflict_buttons(
+    ix: usize,
+    anchor: editor::Anchor,
+    merge_editor: WeakEntity<MergeEditor>,
+    cx: &mut BlockContext,
+) -> AnyElement {
+    let button = |id: &'static str, label: &'static str, side: Side| {
+        let merge_editor = merge_editor.clone();
+        Button::new((id, ix), label)
+            .label_size(LabelSize::Small)
+            .on_click(move |_, _, cx| {
+                merge_editor
+                    .update(cx, |merge_editor, cx| {
+                        let snapshot = merge_editor
+                            .result_editor
+                            .read(cx)
+                            .buffer()
+                            .read(cx)
+                            .snapshot(cx);
+                        let offset = anchor.to_offset(&snapshot);
+                        merge_editor.resolve_at(offset, side, cx);
+                    })
+                    .ok();
+            })
+    };
+    h_flex()
+        .pl(cx.anchor_x)
+        .gap_1()
+        .child(button("accept-ours", "Accept Ours", Side::Ours))
+        .child(button("accept-theirs", "Accept Theirs", Side::Theirs))
+        .child(button("accept-both", "Accept Both", Side::Both))
+        .into_any_element()
+}
+
+impl EventEmitter<ItemEvent> for MergeEditor {}
+
+impl Focusable for MergeEditor {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.result_editor.focus_handle(cx)
+    }
+}
+
+impl Item for MergeEditor {
+    type Event = ItemEvent;
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        f(*event)
+    }
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::GitBranch))
+    }
+
+    fn tab_content_text(&self, _window: &Window, _cx: &App) -> Option<SharedString> {
+        let file_name = self
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| self.path.to_string());
+        Some(format!("Merge {file_name}").into())
+    }
+
+    fn tab_tooltip_text(&self, _: &App) -> Option<SharedString> {
+        Some(format!("Resolve conflicts in {}", self.path).into())
+    }
+
+    fn is_dirty(&self, cx: &App) -> bool {
+        self.result_buffer.read(cx).is_dirty()
+    }
+
+    fn has_conflict(&self, cx: &App) -> bool {
+        self.result_buffer.read(cx).has_conflict()
+    }
+
+    fn can_save(&self, _: &App) -> bool {
+        true
+    }
+
+    fn save(
+        &mut self,
+        format: bool,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        self.result_editor.save(format, project, window, cx)
+    }
+}
+
+impl Render for MergeEditor {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("MergeEditor")
+            .on_action(cx.listener(Self::accept_ours))
+            .on_action(cx.listener(Self::accept_theirs))
+            .on_action(cx.listener(Self::accept_both))
+            .on_action(cx.listener(Self::next_conflict))
+            .on_action(cx.listener(Self::previous_conflict))
+            .on_action(cx.listener(Self::mark_resolved))
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(self.render_header(cx))
+            .child(
+                h_flex()
+                    .flex_1()
+                    .min_h_0()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border)
+                    .child(self.render_pane("Ours", &self.ours_editor, cx))
+                    .child(self.render_pane("Base", &self.base_editor, cx))
+                    .child(self.render_pane("Theirs", &self.theirs_editor, cx)),
+            )
+            .child(h_flex().flex_1().min_h_0().child(self.render_pane(
+                "Result",
+                &self.result_editor,
+                cx,
+            )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_resolve_and_navigate_conflicts() {
+        let text = concat!(
+            "use std::fmt;\n",
+            "<<<<<<< HEAD\n",
+            "use std::io;\n",
+            "=======\n",
+            "use std::fs;\n",
+            ">>>>>>> feature\n",
+            "fn main() {}\n",
+            "<<<<<<< HEAD\n",
+            "||||||| base\n",
+            "// TODO\n",
+            "=======\n",
+            "// Done\n",
+            ">>>>>>> feature\n",
+        );
+        let regions = conflict_regions(text);
+        assert_eq!(regions.len(), 2);
+        assert_eq!(
+            resolved_text(text, &regions[0], Side::Ours),
+            "use std::io;\n"
+        );
+        assert_eq!(
+            resolved_text(text, &regions[0], Side::Theirs),
+            "use std::fs;\n"
+        );
+        assert_eq!(
+            resolved_text(text, &regions[0], Side::Both),
+            "use std::io;\nuse std::fs;\n"
+        );
+        assert_eq!(resolved_text(text, &regions[1], Side::Both), "// Done\n");
+
+        let start = |ix: usize| regions[ix].range.start;
+        assert_eq!(next_conflict(&regions, 0), Some(&regions[0]));
+        assert_eq!(next_conflict(&regions, start(0)), Some(&regions[1]));
+        assert_eq!(next_conflict(&regions, start(1)), Some(&regions[0]));
+        assert_eq!(previous_conflict(&regions, start(1)), Some(&regions[0]));
+        assert_eq!(previous_conflict(&regions, start(1) + 1), Some(&regions[0]));
+        assert_eq!(previous_conflict(&regions, start(0)), Some(&regions[1]));
+        assert_eq!(previous_conflict(&regions, text.len()), Some(&regions[1]));
+        assert_eq!(next_conflict(&[], 0), None);
+    }
+}
@@ -0,0 +1,633 @@
+use editor::{Editor, EditorEvent};
+use git::SHORT_SHA_LENGTH;
+use git::repository::{LogEntry, RebaseAction, RebaseTodoEntry};
+use gpui::{
+    Action, App, Corner, Entity, EventEmitter, FocusHandle, Focusable, KeyContext, ScrollHandle,
+    Subscription, Task, WeakEntity, actions,
+};
+use menu::{SelectNext, SelectPrevious};
+use project::git_store::Repository;
+use ui::{ContextMenu, PopoverMenu, Tooltip, prelude::*};
+use workspace::Workspace;
+use workspace::item::{Item, ItemEvent};
+
+use crate::git_log::GitLog;
+
+actions!(
+    rebase_editor,
+    [
+        PickCommit,
+        RewordCommit,
+        EditCommit,
+        SquashCommit,
+        FixupCommit,
+        DropCommit,
+        MoveCommitUp,
+        MoveCommitDown,
+        StartRebase
+    ]
+);
+
+pub fn register(workspace: &mut Workspace) {
+    workspace.register_action(|workspace, _: &git::InteractiveRebase, window, cx| {
+        let Some(repository) = workspace.project().read(cx).active_repository(cx) else {
+            return;
+        };
+        let upstream = repository
+            .read(cx)
+            .branch
+            .as_ref()
+            .and_then(|branch| branch.upstream.as_ref())
+            .map(|upstream| upstream.ref_name.to_string());
+        match upstream {
+            Some(base) => RebaseEditor::open(repository, base, workspace, window, cx),
+            // Without an upstream to rebase onto, the base is chosen from the history.
+            None => GitLog::open(repository, None, workspace, window, cx),
+        }
+    });
+}
+
+fn action_label(action: RebaseAction) -> &'static str {
+    match action {
+        RebaseAction::Pick => "Pick",
+        RebaseAction::Reword => "Reword",
+        RebaseAction::Edit => "Edit",
+        RebaseAction::Squash => "Squash",
+        RebaseAction::Fixup => "Fixup",
+        RebaseAction::Drop => "Drop",
+    }
+}
+
+fn action_for(action: RebaseAction) -> Box<dyn Action> {
+    match action {
+        RebaseAction::Pick => PickCommit.boxed_clone(),
+        RebaseAction::Reword => RewordCommit.boxed_clone(),
+        RebaseAction::Edit => EditCommit.boxed_clone(),
+        RebaseAction::Squash => SquashCommit.boxed_clone(),
+        RebaseAction::Fixup => FixupCommit.boxed_clone(),
+        RebaseAction::Drop => DropCommit.boxed_clone(),
+    }
+}
+
+struct TodoEntry {
+    commit: LogEntry,
+    action: RebaseAction,
+    /// The commit's full message, edited when it's reworded.
+    message: Option<String>,
+}
+
+#[derive(Clone)]
+struct DraggedTodoEntry {
+    ix: usize,
+    subject: SharedString,
+}
+
+impl Render for DraggedTodoEntry {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        div()
+            .px_2()
+            .py_1()
+            .rounded_sm()
+            .bg(cx.theme().colors().elevated_surface_background)
+            .border_1()
+            .border_color(cx.theme().colors().border)
+            .child(Label::new(self.subject.clone()).size(LabelSize::Small))
+    }
+}
+
+/// A tab for planning an interactive rebase: the commits since its base are
+/// listed in the order they'll be replayed, and can be reordered, reworded,
+/// squashed or dropped before the rebase starts.
+pub struct RebaseEditor {
+    repository: Entity<Repository>,
+    /// The commit or ref the commits are rebased onto.
+    base: String,
+    entries: Vec<TodoEntry>,
+    selected: usize,
+    message_editor: Entity<Editor>,
+    loaded: bool,
+    rebasing: bool,
+    error: Option<SharedString>,
+    focus_handle: FocusHandle,
+    scroll_handle: ScrollHandle,
+    _load_commits: Task<()>,
+    _subscription: Subscription,
+}
+
+impl RebaseEditor {
+    /// Opens the editor for a rebase onto `base`, or activates it if it's
+    /// already open in the active pane.
+    pub fn open(
+        repository: Entity<Repository>,
+        base: String,
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let existing = workspace
+            .active_pane()
+            .read(cx)
+            .items_of_type::<RebaseEditor>()
+            .find(|editor| {
+                let editor = editor.read(cx);
+                editor.repository == repository && editor.base == base
+            });
+        if let Some(existing) = existing {
+            workspace.activate_item(&existing, true, true, window, cx);
+            return;
+        }
+
+        let editor = cx.new(|cx| RebaseEditor::new(repository, base, window, cx));
+        workspace.add_item_to_active_pane(Box::new(editor), None, true, window, cx);
+    }
+
+    fn new(
+        repository: Entity<Repository>,
+        base: String,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let message_editor = cx.new(|cx| {
+            let mut editor = Editor::auto_height(8, window, cx);
+            editor.set_placeholder_text("Commit message", cx);
+            editor
+        });
+        let subscription = cx.subscribe(&message_editor, |this, editor, event, cx| {
+            if let EditorEvent::BufferEdited = event {
+                let text = editor.read(cx).text(cx);
+                // The message is only edited once it has loaded.
+                if let Some(entry) = this.entries.get_mut(this.selected) {
+                    if entry.action == RebaseAction::Reword && entry.message.is_some() {
+                        entry.message = Some(text);
+                    }
+                }
+            }
+        });
+
+        let commits = repository.read(cx).rebase_commits(base.clone());
+        let load_commits = cx.spawn(async move |this, cx| {
+            let commits = commits.await;
+            this.update(cx, |this, cx| {
+                this.loaded = true;
+                match commits {
+                    Ok(Ok(commits)) => {
+                        this.entries = commits
+                            .into_iter()
+                            .map(|commit| TodoEntry {
+                                commit,
+                                action: RebaseAction::Pick,
+                                message: None,
+                            })
+                            .collect();
+                    }
+                    Ok(Err(error)) => this.error = Some(error.to_string().into()),
+                    Err(_) => {}
+                }
+                cx.notify();
+            })
+            .ok();
+        });
+
+        Self {
+            repository,
+            base,
+            entries: Vec::new(),
+            selected: 0,
+            message_editor,
+            loaded: false,
+            rebasing: false,
+            error: None,
+            focus_handle: cx.focus_handle(),
+            scroll_handle: ScrollHandle::new(),
+            _load_commits: load_commits,
+            _subscription: subscription,
+        }
+    }
+
+    fn select(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(entry) = self.entries.get(ix) else {
+            return;
+        };
+        self.selected = ix;
+        if entry.action == RebaseAction::Reword {
+            let message = entry.message.clone().unwrap_or_default();
+            self.message_editor
+                .update(cx, |editor, cx| editor.set_text(message, window, cx));
+        }
+        self.scroll_handle.scroll_to_item(ix);
+        cx.notify();
+    }
+
+    fn select_next(&mut self, _: &SelectNext, window: &mut Window, cx: &mut Context<Self>) {
+        if self.selected + 1 < self.entries.len() {
+            self.select(self.selected + 1, window, cx);
+        }
+    }
+
+    fn select_previous(&mut self, _: &SelectPrevious, window: &mut Window, cx: &mut Context<Self>) {
+        if self.selected > 0 {
+            self.select(self.selected - 1, window, cx);
+        }
+    }
+
+    fn set_action(
+        &mut self,
+        ix: usize,
+        action: RebaseAction,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(entry) = self.entries.get_mut(ix) else {
+            return;
+        };
+        entry.action = action;
+        if action == RebaseAction::Reword {
+            if entry.message.is_none() {
+                self.load_message(ix, window, cx);
+            }
+            self.select(ix, window, cx);
+            window.focus(&self.message_editor.focus_handle(cx));
+        }
+        cx.notify();
+    }
+
+    /// Loads a commit's full message, to be edited when it's reworded.
+    fn load_message(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let sha = self.entries[ix].commit.sha.clone();
+        let details = self.repository.read(cx).show(sha.to_string());
+        cx.spawn_in(window, async move |this, cx| {
+            let details = details.await??;
+            this.update_in(cx, |this, window, cx| {
+                let Some(ix) = this
+                    .entries
+                    .iter()
+                    .position(|entry| entry.commit.sha == sha)
+                else {
+                    return;
+                };
+                if this.entries[ix].message.is_some() {
+                    return;
+                }
+                let message = details.message.trim_end().to_string();
+                this.entries[ix].message = Some(message.clone());
+                if this.selected == ix {
+                    this.message_editor
+                        .update(cx, |editor, cx| editor.set_text(message, window, cx));
+                }
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn pick(&mut self, _: &PickCommit, window: &mut Window, cx: &mut Context<Self>) {
+        self.set_action(self.selected, RebaseAction::Pick, window, cx);
+    }
+
+    fn reword(&mut self, _: &RewordCommit, window: &mut Window, cx: &mut Context<Self>) {
+        self.set_action(self.selected, RebaseAction::Reword, window, cx);
+    }
+
+    fn edit(&mut self, _: &EditCommit, window: &mut Window, cx: &mut Context<Self>) {
+        self.set_action(self.selected, RebaseAction::Edit, window, cx);
+    }
+
+    fn squash(&mut self, _: &SquashCommit, window: &mut Window, cx: &mut Context<Self>) {
+        self.set_action(self.selected, RebaseAction::Squash, window, cx);
+    }
+
+    fn fixup(&mut self, _: &FixupCommit, window: &mut Window, cx: &mut Context<Self>) {
+        self.set_action(self.selected, RebaseAction::Fixup, window, cx);
+    }
+
+    fn drop_commit(&mut self, _: &DropCommit, window: &mut Window, cx: &mut Context<Self>) {
+        self.set_action(self.selected, RebaseAction::Drop, window, cx);
+    }
+
+    fn move_entry(&mut self, from: usize, to: usize, window: &mut Window, cx: &mut Context<Self>) {
+        if from >= self.entries.len() || to >= self.entries.len() || from == to {
+            return;
+        }
+        let entry = self.entries.remove(from);
+        self.entries.insert(to, entry);
+        self.select(to, window, cx);
+    }
+
+    fn move_up(&mut self, _: &MoveCommitUp, window: &mut Window, cx: &mut Context<Self>) {
+        if self.selected > 0 {
+            self.move_entry(self.selected, self.selected - 1, window, cx);
+        }
+    }
+
+    fn move_down(&mut self, _: &MoveCommitDown, window: &mut Window, cx: &mut Context<Self>) {
+        self.move_entry(self.selected, self.selected + 1, window, cx);
+    }
+
+    fn can_start(&self, cx: &App) -> bool {
+        !self.rebasing && !self.entries.is_empty() && self.repository.read(cx).rebase.is_none()
+    }
+
+    fn start_rebase(&mut self, _: &StartRebase, _: &mut Window, cx: &mut Context<Self>) {
+        if !self.can_start(cx) {
+            return;
+        }
+        let todo = self
+            .entries
+            .iter()
+            .map(|entry| RebaseTodoEntry {
+                action: entry.action,
+                sha: entry.commit.sha.clone(),
+                message: entry
+                    .message
+                    .clone()
+                    .filter(|_| entry.action == RebaseAction::Reword),
+            })
+            .collect();
+        let rebase = self
+            .repository
+            .read(cx)
+            .rebase_interactive(self.base.clone(), todo);
+        self.rebasing = true;
+        self.error = None;
+        cx.spawn(async move |this, cx| {
+            let result = rebase.await;
+            this.update(cx, |this, cx| {
+                this.rebasing = false;
+                match result {
+                    // Any conflicts are now listed in the git panel, where the
+                    // rebase can be continued or aborted.
+                    Ok(Ok(())) => cx.emit(ItemEvent::CloseItem),
+                    Ok(Err(error)) => this.error = Some(error.to_string().into()),
+                    Err(_) => {}
+                }
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+        cx.notify();
+    }
+
+    fn dispatch_context(&self, window: &Window) -> KeyContext {
+        let mut dispatch_context = KeyContext::new_with_defaults();
+        dispatch_context.add("RebaseTodo");
+        if self.focus_handle.is_focused(window) {
+            dispatch_context.add("menu");
+        }
+        dispatch_context
+    }
+
+    /// Describes the base, e.g. `origin/main` or a short SHA.
+    fn base_label(&self) -> String {
+        if self.base.len() == 40 && self.base.chars().all(|c| c.is_ascii_hexdigit()) {
+            return self.base[..SHORT_SHA_LENGTH].to_string();
+        }
+        self.base
+            .strip_prefix("refs/remotes/")
+            .or_else(|| self.base.strip_prefix("refs/heads/"))
+            .unwrap_or(&self.base)
+            .to_string()
+    }
+
+    fn render_header(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let can_start = self.can_start(cx);
+        let commit_count = match self.entries.len() {
+            1 => "1 commit".to_string(),
+            count => format!("{count} commits"),
+        };
+        h_flex()
+            .p_2()
+            .gap_2()
+            .border_b_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(Label::new(format!("Rebase onto {}", self.base_label())))
+            .child(Label::new(commit_count).color(Color::Muted))
+            .child(div().flex_1())
+            .child(
+                Button::new("cancel-rebase", "Cancel")
+                    .on_click(cx.listener(|_, _, _, cx| cx.emit(ItemEvent::CloseItem))),
+            )
+            .child(
+                Button::new("start-rebase", "Start Rebase")
+                    .style(ButtonStyle::Filled)
+                    .disabled(!can_start)
+                    .tooltip(Tooltip::for_action_title("Start Rebase", &StartRebase))
+                    .on_click(cx.listener(|this, _, window, cx| {
+                        this.start_rebase(&StartRebase, window, cx)
+                    })),
+            )
+    }
+
+    fn render_action_menu(
+        &self,
+        ix: usize,
+        current: RebaseAction,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let this = cx.weak_entity();
+        PopoverMenu::new(("rebase-action", ix))
+            .trigger(
+                Button::new(("rebase-action-trigger", ix), current.as_str())
+                    .label_size(LabelSize::Small)
+                    .icon(IconName::ChevronDown)
+                    .icon_size(IconSize::XSmall)
+                    .icon_position(IconPosition::End)
+                    .icon_color(Color::Muted),
+            )
+            .menu(move |window, cx| {
+                let this = this.clone();
+                Some(ContextMenu::build(window, cx, move |mut menu, _, _| {
+                    for action in RebaseAction::ALL {
+                        let this: WeakEntity<RebaseEditor> = this.clone();
+                        menu = menu.toggleable_entry(
+                            action_label(action),
+                            action == current,
+                            IconPosition::Start,
+                            Some(action_for(action)),
+                            move |window, cx| {
+                                this.update(cx, |this, cx| this.set_action(ix, action, window, cx))
+                                    .ok();
+                            },
+                        );
+                    }
+                    menu
+                }))
+            })
+            .anchor(Corner::TopLeft)
+    }
+
+    fn render_entry(&self, ix: usize, cx: &mut Context<Self>) -> impl IntoElement {
+        let entry = &self.entries[ix];
+        let colors = cx.theme().colors();
+        let subject = match (&entry.message, entry.action) {
+            (Some(message), RebaseAction::Reword) => {
+                SharedString::from(message.lines().next().unwrap_or_default().to_string())
+            }
+            _ => entry.commit.subject.clone(),
+        };
+        let dropped = entry.action == RebaseAction::Drop;
+
+        h_flex()
+            .id(("rebase-entry", ix))
+            .w_full()
+            .px_2()
+            .py_0p5()
+            .gap_2()
+            .when(self.selected == ix, |this| this.bg(colors.element_selected))
+            .hover(|style| style.bg(colors.element_hover))
+            .on_click(cx.listener(move |this, _, window, cx| {
+                this.select(ix, window, cx);
+                window.focus(&this.focus_handle);
+            }))
+            .on_drag(
+                DraggedTodoEntry {
+                    ix,
+                    subject: subject.clone(),
+                },
+                |entry, _, _, cx| cx.new(|_| entry.clone()),
+            )
+            .drag_over::<DraggedTodoEntry>(|style, _, _, cx| {
+                style.bg(cx.theme().colors().drop_target_background)
+            })
+            .on_drop(
+                cx.listener(move |this, dragged: &DraggedTodoEntry, window, cx| {
+                    this.move_entry(dragged.ix, ix, window, cx);
+                }),
+            )
+            .child(
+                div()
+                    .w(rems(6.))
+                    .child(self.render_action_menu(ix, entry.action, cx)),
+            )
+            .child(
+                Label::new(
+                    entry
+                        .commit
+                        .sha
+                        .get(..SHORT_SHA_LENGTH)
+                        .unwrap_or(&entry.commit.sha)
+                        .to_string(),
+                )
+                .size(LabelSize::Small)
+                .color(Color::Muted)
+                .buffer_font(cx),
+            )
+            .child(
+                Label::new(subject)
+                    .truncate()
+                    .when(dropped, |label| label.strikethrough().color(Color::Muted)),
+            )
+    }
+
+    fn render_message_editor(&self, cx: &mut Context<Self>) -> Option<impl IntoElement> {
+        let entry = self.entries.get(self.selected)?;
+        if entry.action != RebaseAction::Reword {
+            return None;
+        }
+        let colors = cx.theme().colors();
+        Some(
+            v_flex()
+                .p_2()
+                .gap_1()
+                .border_t_1()
+                .border_color(colors.border_variant)
+                .child(
+                    Label::new("New commit message")
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                )
+                .child(
+                    div()
+                        .p_1()
+                        .rounded_sm()
+                        .border_1()
+                        .border_color(colors.border)
+                        .child(self.message_editor.clone()),
+                ),
+        )
+    }
+}
+
+impl EventEmitter<ItemEvent> for RebaseEditor {}
+
+impl Focusable for RebaseEditor {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for RebaseEditor {
+    type Event = ItemEvent;
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        f(*event)
+    }
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::GitBranch))
+    }
+
+    fn tab_content_text(&self, _window: &Window, _cx: &App) -> Option<SharedString> {
+        Some("Interactive Rebase".into())
+    }
+}
+
+impl Render for RebaseEditor {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let list = if self.entries.is_empty() {
+            let message = if !self.loaded {
+                "Loading commits…"
+            } else if self.error.is_none() {
+                "No commits to rebase"
+            } else {
+                ""
+            };
+            h_flex()
+                .size_full()
+                .justify_center()
+                .child(Label::new(message).color(Color::Muted))
+                .into_any_element()
+        } else {
+            v_flex()
+                .id("rebase-todo")
+                .key_context(self.dispatch_context(window))
+                .track_focus(&self.focus_handle)
+                .on_action(cx.listener(Self::select_next))
+                .on_action(cx.listener(Self::select_previous))
+                .on_action(cx.listener(Self::pick))
+                .on_action(cx.listener(Self::reword))
+                .on_action(cx.listener(Self::edit))
+                .on_action(cx.listener(Self::squash))
+                .on_action(cx.listener(Self::fixup))
+                .on_action(cx.listener(Self::drop_commit))
+                .on_action(cx.listener(Self::move_up))
+                .on_action(cx.listener(Self::move_down))
+                .size_full()
+                .overflow_y_scroll()
+                .track_scroll(&self.scroll_handle)
+                .children((0..self.entries.len()).map(|ix| self.render_entry(ix, cx)))
+                .into_any_element()
+        };
+
+        v_flex()
+            .key_context("RebaseEditor")
+            .on_action(cx.listener(Self::start_rebase))
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(self.render_header(cx))
+            .child(
+                div().px_2().pt_1().child(
+                    Label::new("Commits are replayed from top to bottom. Drag them to reorder.")
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                ),
+            )
+            .children(self.error.clone().map(|error| {
+                div()
+                    .px_2()
+                    .pt_1()
+                    .child(Label::new(error).size(LabelSize::Small).color(Color::Error))
+            }))
+            .child(div().flex_1().min_h_0().child(list))
+            .children(self.render_message_editor(cx))
+    }
+}
@@ -0,0 +1,381 @@
+use anyhow::Context as _;
+use fuzzy::StringMatchCandidate;
+
+use git::repository::{CommitSummary, StashEntry};
+use gpui::{
+    App, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, InteractiveElement,
+    IntoElement, ParentElement, PromptLevel, Render, SharedString, Styled, Subscription, Task,
+    WeakEntity, Window, rems,
+};
+use picker::{Picker, PickerDelegate};
+use project::git_store::Repository;
+use std::sync::Arc;
+use time::OffsetDateTime;
+use time_format::format_local_timestamp;
+use ui::{HighlightedLabel, ListItem, ListItemSpacing, prelude::*};
+use util::ResultExt;
+use workspace::notifications::DetachAndPromptErr;
+use workspace::{ModalView, Workspace};
+
+use crate::commit_view::CommitView;
+
+pub fn register(workspace: &mut Workspace) {
+    workspace.register_action(|workspace, _: &git::StashPop, window, cx| {
+        open(StashAction::Pop, workspace, window, cx)
+    });
+    workspace.register_action(|workspace, _: &git::StashApply, window, cx| {
+        open(StashAction::Apply, workspace, window, cx)
+    });
+    workspace.register_action(|workspace, _: &git::StashDrop, window, cx| {
+        open(StashAction::Drop, workspace, window, cx)
+    });
+    workspace.register_action(|workspace, _: &git::ViewStash, window, cx| {
+        open(StashAction::View, workspace, window, cx)
+    });
+}
+
+fn open(
+    action: StashAction,
+    workspace: &mut Workspace,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let repository = workspace.project().read(cx).active_repository(cx).clone();
+    let workspace_handle = cx.weak_entity();
+    workspace.toggle_modal(window, cx, |window, cx| {
+        StashList::new(repository, workspace_handle, action, window, cx)
+    })
+}
+
+/// What confirming an entry in the [`StashList`] does with it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StashAction {
+    Pop,
+    Apply,
+    Drop,
+    View,
+}
+
+pub struct StashList {
+    pub picker: Entity<Picker<StashListDelegate>>,
+    _subscription: Subscription,
+}
+
+impl StashList {
+    fn new(
+        repository: Option<Entity<Repository>>,
+        workspace: WeakEntity<Workspace>,
+        action: StashAction,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let stash_entries_request = repository
+            .clone()
+            .map(|repository| repository.read(cx).stash_entries());
+
+        cx.spawn_in(window, async move |this, cx| {
+            let stash_entries = stash_entries_request
+                .context("No active repository")?
+                .await??;
+
+            this.update_in(cx, |this, window, cx| {
+                this.picker.update(cx, |picker, cx| {
+                    picker.delegate.all_entries = Some(stash_entries);
+                    picker.refresh(window, cx);
+                })
+            })?;
+
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+
+        let delegate = StashListDelegate::new(repository, workspace, action);
+        let picker = cx.new(|cx| Picker::uniform_list(delegate, window, cx));
+
+        let _subscription = cx.subscribe(&picker, |_, _, _, cx| {
+            cx.emit(DismissEvent);
+        });
+
+        Self {
+            picker,
+            _subscription,
+        }
+    }
+}
+
+impl ModalView for StashList {}
+impl EventEmitter<DismissEvent> for StashList {}
+
+impl Focusable for StashList {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for StashList {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .w(rems(34.))
+            .child(self.picker.clone())
+            .on_mouse_down_out({
+                cx.listener(move |this, _, window, cx| {
+                    this.picker.update(cx, |this, cx| {
+                        this.cancel(&Default::default(), window, cx);
+                    })
+                })
+            })
+    }
+}
+
+#[derive(Debug, Clone)]
+struct StashMatch {
+    entry: StashEntry,
+    positions: Vec<usize>,
+}
+
+pub struct StashListDelegate {
+    matches: Vec<StashMatch>,
+    all_entries: Option<Vec<StashEntry>>,
+    repo: Option<Entity<Repository>>,
+    workspace: WeakEntity<Workspace>,
+    action: StashAction,
+    selected_index: usize,
+}
+
+impl StashListDelegate {
+    fn new(
+        repo: Option<Entity<Repository>>,
+        workspace: WeakEntity<Workspace>,
+        action: StashAction,
+    ) -> Self {
+        Self {
+            matches: vec![],
+            all_entries: None,
+            repo,
+            workspace,
+            action,
+            selected_index: 0,
+        }
+    }
+
+    fn drop_entry(
+        &self,
+        repo: Entity<Repository>,
+        entry: StashEntry,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) {
+        let prompt = window.prompt(
+            PromptLevel::Warning,
+            &format!("Drop stash@{{{}}}?", entry.index),
+            Some(entry.message.as_ref()),
+            &["Drop", "Cancel"],
+            cx,
+        );
+        cx.spawn(async move |_, cx| {
+            if prompt.await? != 0 {
+                return Ok(());
+            }
+            cx.update(|cx| repo.read(cx).stash_drop(entry))?.await??;
+            Ok(())
+        })
+        .detach_and_prompt_err("Failed to drop stash", window, cx, |e, _, _| {
+            Some(e.to_string())
+        });
+    }
+}
+
+impl PickerDelegate for StashListDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        match self.action {
+            StashAction::Pop => "Select a stash to pop...",
+            StashAction::Apply => "Select a stash to apply...",
+            StashAction::Drop => "Select a stash to drop...",
+            StashAction::View => "Select a stash to view...",
+        }
+        .into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let Some(all_entries) = self.all_entries.clone() else {
+            return Task::ready(());
+        };
+
+        cx.spawn_in(window, async move |picker, cx| {
+            let matches: Vec<StashMatch> = if query.is_empty() {
+                all_entries
+                    .into_iter()
+                    .map(|entry| StashMatch {
+                        entry,
+                        positions: Vec::new(),
+                    })
+                    .collect()
+            } else {
+                let candidates = all_entries
+                    .iter()
+                    .enumerate()
+                    .map(|(ix, entry)| StringMatchCandidate::new(ix, &entry.message))
+                    .collect::<Vec<StringMatchCandidate>>();
+                fuzzy::match_strings(
+                    &candidates,
+                    &query,
+                    true,
+                    10000,
+                    &Default::default(),
+                    cx.background_executor().clone(),
+                )
+                .await
+                .into_iter()
+                .map(|candidate| StashMatch {
+                    entry: all_entries[candidate.candidate_id].clone(),
+                    positions: candidate.positions,
+                })
+                .collect()
+            };
+            picker
+                .update(cx, |picker, _| {
+                    let delegate = &mut picker.delegate;
+                    delegate.matches = matches;
+                    if delegate.matches.is_empty() {
+                        delegate.selected_index = 0;
+                    } else {
+                        delegate.selected_index =
+                            core::cmp::min(delegate.selected_index, delegate.matches.len() - 1);
+                    }
+                })
+                .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _secondary: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(entry) = self
+            .matches
+            .get(self.selected_index())
+            .map(|stash_match| stash_match.entry.clone())
+        else {
+            return;
+        };
+        let Some(repo) = self.repo.clone() else {
+            return;
+        };
+
+        match self.action {
+            StashAction::Pop | StashAction::Apply => {
+                let (task, error_title) = if self.action == StashAction::Pop {
+                    (
+                        repo.read(cx).stash_pop(entry.clone()),
+                        "Failed to pop stash",
+                    )
+                } else {
+                    (
+                        repo.read(cx).stash_apply(entry.clone()),
+                        "Failed to apply stash",
+                    )
+                };
+                cx.spawn(async move |_, _| task.await?)
+                    .detach_and_prompt_err(error_title, window, cx, |e, _, _| Some(e.to_string()));
+            }
+            StashAction::Drop => self.drop_entry(repo, entry, window, cx),
+            StashAction::View => CommitView::open(
+                CommitSummary {
+                    sha: entry.sha,
+                    subject: entry.message,
+                    commit_timestamp: entry.commit_timestamp,
+                    has_parent: true,
+                },
+                repo.downgrade(),
+                self.workspace.clone(),
+                window,
+                cx,
+            ),
+        }
+        cx.emit(DismissEvent);
+    }
+
+    fn dismissed(&mut self, _: &mut Window, cx: &mut Context<Picker<Self>>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _window: &mut Window,
+        _cx: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let stash_match = &self.matches[ix];
+        let entry = &stash_match.entry;
+
+        let stash_time = OffsetDateTime::from_unix_timestamp(entry.commit_timestamp)
+            .unwrap_or_else(|_| OffsetDateTime::now_utc());
+        let formatted_time = format_local_timestamp(
+            stash_time,
+            OffsetDateTime::now_utc(),
+            time_format::TimestampFormat::Relative,
+        );
+
+        Some(
+            ListItem::new(SharedString::from(format!("stash-{ix}")))
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .child(
+                    h_flex()
+                        .w_full()
+                        .overflow_x_hidden()
+                        .gap_2()
+                        .justify_between()
+                        .child(
+                            h_flex()
+                                .flex_shrink()
+                                .overflow_x_hidden()
+                                .gap_2()
+                                .child(
+                                    Label::new(format!("stash@{{{}}}", entry.index))
+                                        .color(Color::Muted),
+                                )
+                                .child(
+                                    HighlightedLabel::new(
+                                        entry.message.clone(),
+                                        stash_match.positions.clone(),
+                                    )
+                                    .truncate(),
+                                ),
+                        )
+                        .child(
+                            Label::new(formatted_time)
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        ),
+                ),
+        )
+    }
+
+    fn no_matches_text(&self, _window: &mut Window, _cx: &mut App) -> Option<SharedString> {
+        Some("No stashes".into())
+    }
+}
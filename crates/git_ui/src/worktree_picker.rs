@@ -0,0 +1,618 @@
+use anyhow::Context as _;
+use fuzzy::StringMatchCandidate;
+
+use git::repository::GitWorktree;
+use gpui::{
+    App, AsyncApp, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable,
+    InteractiveElement, IntoElement, ParentElement, PromptLevel, Render, SharedString, Styled,
+    Subscription, Task, Window, rems,
+};
+use picker::{Picker, PickerDelegate};
+use project::git_store::Repository;
+use std::{path::PathBuf, sync::Arc};
+use ui::{HighlightedLabel, ListItem, ListItemSpacing, prelude::*};
+use util::ResultExt;
+use workspace::notifications::DetachAndPromptErr;
+use workspace::{AppState, ModalView, OpenOptions, Workspace};
+
+pub fn register(workspace: &mut Workspace) {
+    workspace.register_action(add_worktree);
+    workspace.register_action(|workspace, _: &git::OpenWorktree, window, cx| {
+        open(WorktreeAction::Open, workspace, window, cx)
+    });
+    workspace.register_action(|workspace, _: &git::RemoveWorktree, window, cx| {
+        open(WorktreeAction::Remove, workspace, window, cx)
+    });
+}
+
+fn open(
+    action: WorktreeAction,
+    workspace: &mut Workspace,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let repository = workspace.project().read(cx).active_repository(cx).clone();
+    let app_state = workspace.app_state().clone();
+    workspace.toggle_modal(window, cx, |window, cx| {
+        WorktreeList::new(repository, app_state, action, window, cx)
+    })
+}
+
+/// Prompts for a directory to check out a new linked worktree in, on a new
+/// branch named after it, and opens it in a new window.
+fn add_worktree(
+    workspace: &mut Workspace,
+    _: &git::AddWorktree,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let Some(repository) = workspace.project().read(cx).active_repository(cx) else {
+        return;
+    };
+    let app_state = workspace.app_state().clone();
+    let work_directory = repository.read(cx).work_directory_abs_path.clone();
+    let directory = work_directory
+        .parent()
+        .map(|parent| parent.to_path_buf())
+        .unwrap_or_else(|| work_directory.to_path_buf());
+    let path = cx.prompt_for_new_path(&directory);
+    cx.spawn_in(window, async move |_, cx| {
+        let Some(path) = path.await?? else {
+            return Ok(());
+        };
+        cx.update(|_, cx| repository.read(cx).create_worktree(path.clone(), None))?
+            .await??;
+        open_in_new_window(path, app_state, cx).await
+    })
+    .detach_and_prompt_err("Failed to add worktree", window, cx, |e, _, _| {
+        Some(e.to_string())
+    });
+}
+
+async fn open_in_new_window(
+    path: PathBuf,
+    app_state: Arc<AppState>,
+    cx: &mut AsyncApp,
+) -> anyhow::Result<()> {
+    cx.update(|cx| {
+        workspace::open_paths(
+            &[path],
+            app_state,
+            OpenOptions {
+                open_new_workspace: Some(true),
+                ..Default::default()
+            },
+            cx,
+        )
+    })?
+    .await?;
+    Ok(())
+}
+
+/// What confirming an entry in the [`WorktreeList`] does with it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WorktreeAction {
+    Open,
+    Remove,
+}
+
+pub struct WorktreeList {
+    pub picker: Entity<Picker<WorktreeListDelegate>>,
+    _subscription: Subscription,
+}
+
+impl WorktreeList {
+    fn new(
+        repository: Option<Entity<Repository>>,
+        app_state: Arc<AppState>,
+        action: WorktreeAction,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let worktrees_request = repository
+            .clone()
+            .map(|repository| repository.read(cx).worktrees());
+
+        cx.spawn_in(window, async move |this, cx| {
+            let mut worktrees = worktrees_request.context("No active repository")?.await??;
+            // The main worktree can't be removed.
+            if action == WorktreeAction::Remove {
+                worktrees.retain(|worktree| !worktree.is_main);
+            }
+
+            this.update_in(cx, |this, window, cx| {
+                this.picker.update(cx, |picker, cx| {
+                    picker.delegate.all_worktrees = Some(worktrees);
+                    picker.refresh(window, cx);
+                })
+            })?;
+
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+
+        let delegate = WorktreeListDelegate::new(repository, app_state, action);
+        let picker = cx.new(|cx| Picker::uniform_list(delegate, window, cx));
+
+        let _subscription = cx.subscribe(&picker, |_, _, _, cx| {
+            cx.emit(DismissEvent);
+        });
+
+        Self {
+            picker,
+            _subscription,
+        }
+    }
+}
+
+impl ModalView for WorktreeList {}
+impl EventEmitter<DismissEvent> for WorktreeList {}
+
+impl Focusable for WorktreeList {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for WorktreeList {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .w(rems(34.))
+            .child(self.picker.clone())
+            .on_mouse_down_out({
+                cx.listener(move |this, _, window, cx| {
+                    this.picker.update(cx, |this, cx| {
+                        this.cancel(&Default::default(), window, cx);
+                    })
+                })
+            })
+    }
+}
+
+#[derive(Debug, Clone)]
+struct WorktreeMatch {
+    worktree: GitWorktree,
+    positions: Vec<usize>,
+}
+
+pub struct WorktreeListDelegate {
+    matches: Vec<WorktreeMatch>,
+    all_worktrees: Option<Vec<GitWorktree>>,
+    repo: Option<Entity<Repository>>,
+    app_state: Arc<AppState>,
+    action: WorktreeAction,
+    selected_index: usize,
+}
+
+impl WorktreeListDelegate {
+    fn new(
+        repo: Option<Entity<Repository>>,
+        app_state: Arc<AppState>,
+        action: WorktreeAction,
+    ) -> Self {
+        Self {
+            matches: vec![],
+            all_worktrees: None,
+            repo,
+            app_state,
+            action,
+            selected_index: 0,
+        }
+    }
+
+    fn remove_worktree(
+        &self,
+        repo: Entity<Repository>,
+        worktree: GitWorktree,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) {
+        let path = worktree.path.to_string_lossy().to_string();
+        let prompt = window.prompt(
+            PromptLevel::Warning,
+            &format!("Remove the worktree at {path}?"),
+            Some("Uncommitted changes in it will be lost."),
+            &["Remove", "Cancel"],
+            cx,
+        );
+        cx.spawn(async move |_, cx| {
+            if prompt.await? != 0 {
+                return Ok(());
+            }
+            cx.update(|cx| repo.read(cx).remove_worktree(worktree.path, true))?
+                .await??;
+            Ok(())
+        })
+        .detach_and_prompt_err("Failed to remove worktree", window, cx, |e, _, _| {
+            Some(e.to_string())
+        });
+    }
+}
+
+impl PickerDelegate for WorktreeListDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        match self.action {
+            WorktreeAction::Open => "Select a worktree to open...",
+            WorktreeAction::Remove => "Select a worktree to remove...",
+        }
+        .into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let Some(all_worktrees) = self.all_worktrees.clone() else {
+            return Task::ready(());
+        };
+
+        cx.spawn_in(window, async move |picker, cx| {
+            let matches: Vec<WorktreeMatch> = if query.is_empty() {
+                all_worktrees
+                    .into_iter()
+                    .map(|worktree| WorktreeMatch {
+                        worktree,
+                        positions: Vec::new(),
+                    })
+                    .collect()
+            } else {
+                let candidates = all_worktrees
+                    .iter()
+                    .enumerate()
+                    .map(|(ix, worktree)| {
+                        StringMatchCandidate::new(ix, &worktree.path.to_string_lossy())
+                    })
+                    .collect::<Vec<StringMatchCandidate>>();
+                fuzzy::match_strings(
+                    &candidates,
+                    &query,
+                    true,
+                    10000,
+                    &Default::default(),
+                    cx.background_executor().clone(),
+                )
+                .await
+                .into_iter()
+                .map(|candidate| WorktreeMatch {
+                    worktree: all_worktrees[candidate.candidate_id].clone(),
+                    positions: candidate.positions,
+                })
+                .collect()
+            };
+            picker
+                .update(cx, |picker, _| {
+                    let delegate = &mut picker.delegate;
+                    delegate.matches = matches;
+                    if delegate.matches.is_empty() {
+                        delegate.selected_index = 0;
+                    } else {
+                        delegate.selected_index =
+                            core::cmp::min(delegate.selected_index, delegate.matches.len() - 1);
+                    }
+                })
+                .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _secondary: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(worktree) = self
+            .matches
+            .get(self.selected_index())
+            .map(|worktree_match| worktree_match.worktree.clone())
+        else {
+            return;
+        };
+
+        match self.action {
+            WorktreeAction::Open => {
+                let app_state = self.app_state.clone();
+                cx.spawn(async move |_, cx| open_in_new_window(worktree.path, app_state, cx).await)
+                    .detach_and_prompt_err("Failed to open worktree", window, cx, |e, _, _| {
+                        Some(e.to_string())
+                    });
+            }
+            WorktreeAction::Remove => {
+                let Some(repo) = self.repo.clone() else {
+                    return;
+                };
+                self.remove_worktree(repo, worktree, window, cx);
+            }
+        }
+        cx.emit(DismissEvent);
+    }
+
+    fn dismissed(&mut self, _: &mut Window, cx: &mut Context<Picker<Self>>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _window: &mut Window,
+        _cx: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let worktree_match = &self.matches[ix];
+        let worktree = &worktree_match.worktree;
+
+        let description: SharedString = match (&worktree.branch, &worktree.sha) {
+            (Some(branch), _) => branch.clone(),
+            (None, Some(sha)) => format!("detached at {}", &sha[..git::SHORT_SHA_LENGTH]).into(),
+            (None, None) => "no commits".into(),
+        };
+
+        Some(
+            ListItem::new(SharedString::from(format!("worktree-{ix}")))
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .child(
+                    h_flex()
+                        .w_full()
+                        .overflow_x_hidden()
+                        .gap_2()
+                        .justify_between()
+                        .child(
+                            HighlightedLabel::new(
+                                worktree.path.to_string_lossy().to_string(),
+                                worktree_match.positions.clone(),
+                            )
+                            .truncate(),
+                        )
+                        .child(
+                            h_flex()
+                                .flex_none()
+                                .gap_1()
+                                .when(worktree.is_main, |this| {
+                                    this.child(
+                                        Label::new("main")
+                                            .size(LabelSize::Small)
+                                            .color(Color::Accent),
+                                    )
+                                })
+                                .when(worktree.is_locked, |this| {
+                                    this.child(
+                                        Icon::new(IconName::FileLock)
+                                            .size(IconSize::Small)
+                                            .color(Color::Muted),
+                                    )
+                                })
+                                .when(worktree.is_prunable, |this| {
+                                    this.child(
+                                        Label::new("missing")
+                                            .size(LabelSize::Small)
+                                            .color(Color::Warning),
+                                    )
+                                })
+                                .child(
+                                    Label::new(description)
+                                        .size(LabelSize::Small)
+                                        .color(Color::Muted),
+                                ),
+                        ),
+                ),
+        )
+    }
+
+    fn no_matches_text(&self, _window: &mut Window, _cx: &mut App) -> Option<SharedString> {
+        Some("No worktrees".into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assistant_settings::AssistantSettings;
+    use git::repository::{RepoPath, Submodule, SubmoduleStatus};
+    use gpui::{TestAppContext, VisualTestContext};
+    use project::{FakeFs, Fs as _, Project, WorktreeSettings};
+    use serde_json::json;
+    use settings::{Settings as _, SettingsStore};
+    use std::path::Path;
+    use theme::LoadThemes;
+    use util::path;
+
+    use super::*;
+    use crate::git_panel::GitPanel;
+
+    fn init_test(cx: &mut TestAppContext) {
+        if std::env::var("RUST_LOG").is_ok() {
+            env_logger::try_init().ok();
+        }
+
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            AssistantSettings::register(cx);
+            WorktreeSettings::register(cx);
+            workspace::init_settings(cx);
+            theme::init(LoadThemes::JustBase, cx);
+            language::init(cx);
+            editor::init(cx);
+            Project::init_settings(cx);
+            crate::init(cx);
+        });
+    }
+
+    fn listed_worktrees(workspace: &Entity<Workspace>, cx: &mut VisualTestContext) -> Vec<PathBuf> {
+        cx.run_until_parked();
+        workspace.update(cx, |workspace, cx| {
+            let list = workspace.active_modal::<WorktreeList>(cx).unwrap();
+            list.read(cx)
+                .picker
+                .read(cx)
+                .delegate
+                .matches
+                .iter()
+                .map(|worktree_match| worktree_match.worktree.path.clone())
+                .collect()
+        })
+    }
+
+    #[gpui::test]
+    async fn test_worktrees_and_submodules(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/root",
+            json!({
+                "notes": {
+                    ".git": {},
+                    "draft.md": "draft",
+                    "vendor": {
+                        "theme": {},
+                        "fonts": {},
+                    },
+                },
+            }),
+        )
+        .await;
+
+        let dot_git = Path::new(path!("/root/notes/.git"));
+        fs.set_branch_name(dot_git, Some("main"));
+        fs.with_git_state(dot_git, true, |state| {
+            state.submodules = ["vendor/fonts", "vendor/theme"]
+                .into_iter()
+                .map(|path| Submodule {
+                    path: RepoPath::from(path),
+                    sha: "0123456789abcdef0123456789abcdef01234567".into(),
+                    status: SubmoduleStatus::Uninitialized,
+                })
+                .collect();
+        })
+        .unwrap();
+
+        let project = Project::test(fs.clone(), [path!("/root/notes").as_ref()], cx).await;
+        let (workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project.clone(), window, cx));
+        cx.run_until_parked();
+
+        // Adding a worktree checks out a new branch named after its directory,
+        // next to the repository by default.
+        cx.dispatch_action(git::AddWorktree);
+        cx.run_until_parked();
+        cx.simulate_new_path_selection(|directory| {
+            assert_eq!(directory, Path::new(path!("/root")));
+            Some(PathBuf::from(path!("/root/notes-feature")))
+        });
+        cx.run_until_parked();
+        assert!(fs.is_dir(Path::new(path!("/root/notes-feature"))).await);
+
+        let repository = project.update(cx, |project, cx| project.active_repository(cx).unwrap());
+        let worktrees = repository
+            .update(cx, |repository, _| repository.worktrees())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            worktrees
+                .iter()
+                .map(|worktree| (
+                    worktree.path.clone(),
+                    worktree.branch.clone(),
+                    worktree.is_main
+                ))
+                .collect::<Vec<_>>(),
+            [
+                (
+                    PathBuf::from(path!("/root/notes")),
+                    Some("main".into()),
+                    true
+                ),
+                (
+                    PathBuf::from(path!("/root/notes-feature")),
+                    Some("notes-feature".into()),
+                    false
+                ),
+            ]
+        );
+
+        // Opening lists every worktree, while removing leaves out the main one.
+        cx.dispatch_action(git::OpenWorktree);
+        assert_eq!(
+            listed_worktrees(&workspace, cx),
+            [
+                PathBuf::from(path!("/root/notes")),
+                PathBuf::from(path!("/root/notes-feature")),
+            ]
+        );
+        cx.dispatch_action(menu::Cancel);
+        cx.run_until_parked();
+
+        cx.dispatch_action(git::RemoveWorktree);
+        assert_eq!(
+            listed_worktrees(&workspace, cx),
+            [PathBuf::from(path!("/root/notes-feature"))]
+        );
+        cx.dispatch_action(menu::Confirm);
+        cx.run_until_parked();
+        cx.simulate_prompt_answer("Remove");
+        cx.run_until_parked();
+        assert!(!fs.is_dir(Path::new(path!("/root/notes-feature"))).await);
+        let worktrees = repository
+            .update(cx, |repository, _| repository.worktrees())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            worktrees
+                .iter()
+                .map(|worktree| worktree.path.clone())
+                .collect::<Vec<_>>(),
+            [PathBuf::from(path!("/root/notes"))]
+        );
+
+        // Updating submodules without a submodule selected updates all of them.
+        let app_state = workspace.update(cx, |workspace, _| workspace.app_state().clone());
+        let panel = cx.new_window_entity(|window, cx| {
+            GitPanel::new(workspace.clone(), project.clone(), app_state, window, cx)
+        });
+        let submodule_statuses = |cx: &mut VisualTestContext| {
+            cx.run_until_parked();
+            repository.update(cx, |repository, _| {
+                repository
+                    .submodules
+                    .iter()
+                    .map(|submodule| (submodule.path.to_string(), submodule.status))
+                    .collect::<Vec<_>>()
+            })
+        };
+        assert_eq!(
+            submodule_statuses(cx),
+            [
+                ("vendor/fonts".to_string(), SubmoduleStatus::Uninitialized),
+                ("vendor/theme".to_string(), SubmoduleStatus::Uninitialized),
+            ]
+        );
+        cx.update_window_entity(&panel, |panel, window, cx| {
+            panel.update_submodules(&git::UpdateSubmodules, window, cx);
+        });
+        assert_eq!(
+            submodule_statuses(cx),
+            [
+                ("vendor/fonts".to_string(), SubmoduleStatus::UpToDate),
+                ("vendor/theme".to_string(), SubmoduleStatus::UpToDate),
+            ]
+        );
+    }
+}
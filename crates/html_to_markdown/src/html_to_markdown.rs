@@ -3,6 +3,7 @@
 mod html_element;
 pub mod markdown;
 mod markdown_writer;
+pub mod readability;
 pub mod structure;
 
 use std::io::Read;
@@ -275,3 +275,126 @@ impl HandleTag for CodeHandler {
         HandlerOutcome::NoOp
     }
 }
+
+/// Wraps a link destination in angle brackets when it contains characters
+/// that would otherwise end it early.
+fn link_destination(url: &str) -> String {
+    if url.contains([' ', '(', ')']) {
+        format!("<{url}>")
+    } else {
+        url.to_string()
+    }
+}
+
+pub struct LinkHandler {
+    resolve_url: Box<dyn Fn(&str) -> Option<String>>,
+    /// For every open `<a>`, where its text starts and the URL it links to.
+    open_links: Vec<Option<(usize, String)>>,
+}
+
+impl LinkHandler {
+    /// Creates a handler that links to the URLs returned by `resolve_url`,
+    /// leaving links it returns `None` for as plain text.
+    pub fn new(resolve_url: impl Fn(&str) -> Option<String> + 'static) -> Self {
+        Self {
+            resolve_url: Box::new(resolve_url),
+            open_links: Vec::new(),
+        }
+    }
+}
+
+impl HandleTag for LinkHandler {
+    fn should_handle(&self, tag: &str) -> bool {
+        tag == "a"
+    }
+
+    fn handle_tag_start(
+        &mut self,
+        tag: &HtmlElement,
+        writer: &mut MarkdownWriter,
+    ) -> StartTagOutcome {
+        let url = tag
+            .attr("href")
+            .and_then(|href| (self.resolve_url)(href.trim()));
+        match url {
+            Some(url) => {
+                writer.push_str("[");
+                self.open_links.push(Some((writer.markdown.len(), url)));
+            }
+            None => self.open_links.push(None),
+        }
+
+        StartTagOutcome::Continue
+    }
+
+    fn handle_tag_end(&mut self, _tag: &HtmlElement, writer: &mut MarkdownWriter) {
+        let Some(Some((start, url))) = self.open_links.pop() else {
+            return;
+        };
+        if writer.markdown[start..].trim().is_empty() {
+            writer.markdown.truncate(start - 1);
+        } else {
+            writer.push_str(&format!("]({})", link_destination(&url)));
+        }
+    }
+}
+
+pub struct ImageHandler {
+    resolve_url: Box<dyn Fn(&str) -> Option<String>>,
+    images: Vec<String>,
+}
+
+impl ImageHandler {
+    /// Creates a handler that embeds images from the URLs returned by
+    /// `resolve_url`, dropping images it returns `None` for.
+    pub fn new(resolve_url: impl Fn(&str) -> Option<String> + 'static) -> Self {
+        Self {
+            resolve_url: Box::new(resolve_url),
+            images: Vec::new(),
+        }
+    }
+
+    /// Returns the URLs of the images written so far, without duplicates.
+    pub fn images(&self) -> &[String] {
+        &self.images
+    }
+}
+
+impl HandleTag for ImageHandler {
+    fn should_handle(&self, tag: &str) -> bool {
+        tag == "img"
+    }
+
+    fn handle_tag_start(
+        &mut self,
+        tag: &HtmlElement,
+        writer: &mut MarkdownWriter,
+    ) -> StartTagOutcome {
+        // Lazily loaded images often keep their real source in `data-src`.
+        let url = tag
+            .attr("data-src")
+            .or_else(|| tag.attr("src"))
+            .and_then(|src| (self.resolve_url)(src.trim()));
+        let Some(url) = url else {
+            return StartTagOutcome::Skip;
+        };
+
+        let alt = tag
+            .attr("alt")
+            .unwrap_or_default()
+            .replace(['[', ']', '\n'], " ");
+        let is_block = !writer.is_inside("p") && !writer.is_inside("a");
+        if is_block {
+            writer.push_blank_line();
+        }
+        writer.push_str(&format!("![{}]({})", alt.trim(), link_destination(&url)));
+        if is_block {
+            writer.push_blank_line();
+        }
+        if !self.images.contains(&url) {
+            self.images.push(url);
+        }
+
+        StartTagOutcome::Skip
+    }
+}
@@ -75,6 +75,18 @@ impl MarkdownWriter {
         Ok(Self::prettify_markdown(self.markdown))
     }
 
+    /// Converts the given sibling nodes, in order, into a single Markdown document.
+    pub(crate) fn run_nodes(
+        mut self,
+        nodes: &[Handle],
+        handlers: &mut [TagHandler],
+    ) -> Result<String> {
+        for node in nodes {
+            self.visit_node(node, handlers)?;
+        }
+        Ok(Self::prettify_markdown(self.markdown))
+    }
+
     fn prettify_markdown(markdown: String) -> String {
         let markdown = empty_line_regex().replace_all(&markdown, "");
         let markdown = more_than_three_newlines_regex().replace_all(&markdown, "\n\n");
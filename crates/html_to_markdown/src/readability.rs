@@ -0,0 +1,426 @@
+//! Finds the main content of a web page, leaving out navigation, sidebars,
+//! comments and other page chrome.
+//!
+//! This follows the approach of Mozilla's Readability: paragraphs award points
+//! to their parent and grandparent elements, scores are adjusted by class
+//! names and link density, and the best scoring element (together with
+//! siblings that look like part of the same article) is kept.
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::rc::Rc;
+use std::sync::LazyLock;
+
+use anyhow::Result;
+use markup5ever_rcdom::{Handle, NodeData, RcDom};
+use regex::Regex;
+
+use crate::{MarkdownWriter, TagHandler, parse_html};
+
+/// Elements that never contain article content.
+const REMOVED_TAGS: &[&str] = &[
+    "script", "style", "noscript", "nav", "aside", "form", "iframe", "svg", "button", "template",
+    "select", "input", "textarea",
+];
+
+/// Elements whose text counts towards the score of their ancestors.
+const SCORED_TAGS: &[&str] = &["p", "pre", "td", "blockquote"];
+
+static UNLIKELY_CANDIDATE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)-ad-|banner|breadcrumb|combx|comment|community|cookie|disqus|extra|footer|gdpr|header|legends|menu|newsletter|pager|pagination|popup|related|remark|replies|rss|share|shoutbox|sidebar|skyscraper|social|sponsor|subscribe|supplemental").unwrap()
+});
+static MAYBE_CANDIDATE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)and|article|body|column|content|main|shadow").unwrap());
+static POSITIVE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)article|body|content|entry|hentry|h-entry|main|page|post|text|blog|story")
+        .unwrap()
+});
+static NEGATIVE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)-ad-|hidden|banner|combx|comment|com-|contact|foot|footnote|gdpr|masthead|media|meta|outbrain|promo|related|scroll|share|shoutbox|sidebar|skyscraper|sponsor|shopping|tags|tool|widget").unwrap()
+});
+
+/// The main content of a web page.
+pub struct Article {
+    /// The page's title, from its Open Graph metadata or `<title>`.
+    pub title: Option<String>,
+    content: Vec<Handle>,
+    /// Dropping the document clears the children of every node in it, so it
+    /// has to outlive `content`.
+    _dom: RcDom,
+}
+
+impl Article {
+    /// Converts the article's content to Markdown.
+    pub fn to_markdown(&self, handlers: &mut [TagHandler]) -> Result<String> {
+        let markdown = MarkdownWriter::new().run_nodes(&self.content, handlers)?;
+        Ok(tidy_markdown(&markdown))
+    }
+}
+
+/// Extracts the main content from the provided HTML.
+pub fn extract_article(html: impl Read) -> Result<Article> {
+    let dom = parse_html(html)?;
+    let title = page_title(&dom.document);
+    let body = find_element(&dom.document, "body").unwrap_or_else(|| dom.document.clone());
+    remove_unlikely_candidates(&body, false);
+
+    let content = match top_candidate(&body) {
+        Some(candidate) => with_related_siblings(&candidate),
+        None => vec![body],
+    };
+
+    Ok(Article {
+        title,
+        content,
+        _dom: dom,
+    })
+}
+
+/// Strips the indentation that pages leave between block elements and
+/// collapses runs of blank lines, leaving code blocks untouched.
+fn tidy_markdown(markdown: &str) -> String {
+    let mut tidied = String::with_capacity(markdown.len());
+    let mut in_code_block = false;
+    let mut previous_blank = false;
+    for line in markdown.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+        } else if in_code_block {
+            tidied.push_str(line);
+            tidied.push('\n');
+            continue;
+        }
+        let line = line.trim_end();
+        let blank = line.is_empty();
+        if !(blank && previous_blank) {
+            tidied.push_str(line);
+            tidied.push('\n');
+        }
+        previous_blank = blank;
+    }
+    tidied.trim().to_string()
+}
+
+fn tag_name(node: &Handle) -> Option<&str> {
+    match &node.data {
+        NodeData::Element { name, .. } => Some(&*name.local),
+        _ => None,
+    }
+}
+
+fn attr(node: &Handle, attr_name: &str) -> Option<String> {
+    match &node.data {
+        NodeData::Element { attrs, .. } => attrs
+            .borrow()
+            .iter()
+            .find(|attr| &*attr.name.local == attr_name)
+            .map(|attr| attr.value.to_string()),
+        _ => None,
+    }
+}
+
+fn parent(node: &Handle) -> Option<Handle> {
+    let weak = node.parent.take()?;
+    let parent = weak.upgrade();
+    node.parent.set(Some(weak));
+    parent
+}
+
+fn find_element(node: &Handle, tag: &str) -> Option<Handle> {
+    if tag_name(node) == Some(tag) {
+        return Some(node.clone());
+    }
+    node.children
+        .borrow()
+        .iter()
+        .find_map(|child| find_element(child, tag))
+}
+
+fn text_content(node: &Handle) -> String {
+    let mut text = String::new();
+    collect_text(node, &mut text);
+    text
+}
+
+fn collect_text(node: &Handle, text: &mut String) {
+    if let NodeData::Text { contents } = &node.data {
+        text.push_str(&contents.borrow());
+    }
+    for child in node.children.borrow().iter() {
+        collect_text(child, text);
+    }
+}
+
+/// The share of an element's text that is inside links.
+fn link_density(node: &Handle) -> f32 {
+    let text_length = text_content(node).trim().chars().count();
+    if text_length == 0 {
+        return 0.;
+    }
+    let mut link_length = 0;
+    let mut links = Vec::new();
+    collect_elements(node, "a", &mut links);
+    for link in links {
+        link_length += text_content(&link).trim().chars().count();
+    }
+    link_length as f32 / text_length as f32
+}
+
+fn collect_elements(node: &Handle, tag: &str, elements: &mut Vec<Handle>) {
+    for child in node.children.borrow().iter() {
+        if tag_name(child) == Some(tag) {
+            elements.push(child.clone());
+        }
+        collect_elements(child, tag, elements);
+    }
+}
+
+fn page_title(document: &Handle) -> Option<String> {
+    let mut metas = Vec::new();
+    collect_elements(document, "meta", &mut metas);
+    let og_title = metas.iter().find_map(|meta| {
+        let property = attr(meta, "property").or_else(|| attr(meta, "name"))?;
+        if property == "og:title" || property == "twitter:title" {
+            attr(meta, "content")
+        } else {
+            None
+        }
+    });
+    let title = og_title
+        .or_else(|| find_element(document, "title").map(|title| text_content(&title)))
+        .or_else(|| find_element(document, "h1").map(|heading| text_content(&heading)))?;
+    let title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    // Drop a trailing site name, like "Article | Site", as long as that
+    // leaves a title that still reads like one.
+    for separator in [" | ", " - ", " — ", " – ", " :: "] {
+        if let Some((head, _)) = title.rsplit_once(separator) {
+            if head.split_whitespace().count() >= 3 {
+                return Some(head.to_string());
+            }
+        }
+    }
+    (!title.is_empty()).then_some(title)
+}
+
+fn class_and_id(node: &Handle) -> String {
+    let mut value = attr(node, "class").unwrap_or_default();
+    if let Some(id) = attr(node, "id") {
+        value.push(' ');
+        value.push_str(&id);
+    }
+    value
+}
+
+fn remove_unlikely_candidates(node: &Handle, inside_article: bool) {
+    let inside_article = inside_article || matches!(tag_name(node), Some("article" | "main"));
+    node.children.borrow_mut().retain(|child| {
+        let Some(tag) = tag_name(child) else {
+            return !matches!(child.data, NodeData::Comment { .. });
+        };
+        if REMOVED_TAGS.contains(&tag) {
+            return false;
+        }
+        if !inside_article && matches!(tag, "header" | "footer") {
+            return false;
+        }
+        if attr(child, "hidden").is_some()
+            || attr(child, "aria-hidden").as_deref() == Some("true")
+            || attr(child, "role").is_some_and(|role| {
+                matches!(
+                    role.as_str(),
+                    "navigation" | "complementary" | "dialog" | "banner"
+                )
+            })
+        {
+            return false;
+        }
+        let class_and_id = class_and_id(child);
+        !(UNLIKELY_CANDIDATE.is_match(&class_and_id)
+            && !MAYBE_CANDIDATE.is_match(&class_and_id)
+            && !matches!(tag, "body" | "article" | "main" | "a"))
+    });
+    for child in node.children.borrow().iter() {
+        remove_unlikely_candidates(child, inside_article);
+    }
+}
+
+fn class_weight(node: &Handle) -> f32 {
+    let mut weight = 0.;
+    for value in [attr(node, "class"), attr(node, "id")]
+        .into_iter()
+        .flatten()
+    {
+        if NEGATIVE.is_match(&value) {
+            weight -= 25.;
+        }
+        if POSITIVE.is_match(&value) {
+            weight += 25.;
+        }
+    }
+    weight
+}
+
+fn initial_score(node: &Handle) -> f32 {
+    let tag_score = match tag_name(node) {
+        Some("article") => 10.,
+        Some("div") | Some("main") => 5.,
+        Some("pre") | Some("td") | Some("blockquote") => 3.,
+        Some("address") | Some("ol") | Some("ul") | Some("dl") | Some("dd") | Some("dt")
+        | Some("li") | Some("form") => -3.,
+        Some("h1") | Some("h2") | Some("h3") | Some("h4") | Some("h5") | Some("h6")
+        | Some("th") => -5.,
+        _ => 0.,
+    };
+    tag_score + class_weight(node)
+}
+
+fn paragraph_score(text: &str) -> f32 {
+    let commas = text.matches([',', '，']).count();
+    1. + commas as f32 + (text.chars().count() as f32 / 100.).min(3.)
+}
+
+fn node_key(node: &Handle) -> *const markup5ever_rcdom::Node {
+    Rc::as_ptr(node)
+}
+
+/// Scores every element that contains paragraphs and returns the best one.
+fn top_candidate(body: &Handle) -> Option<Handle> {
+    let mut candidates: HashMap<*const markup5ever_rcdom::Node, (Handle, f32)> = HashMap::new();
+    let mut scored = Vec::new();
+    for tag in SCORED_TAGS {
+        collect_elements(body, tag, &mut scored);
+    }
+
+    for element in scored {
+        let text = text_content(&element);
+        let text = text.trim();
+        if text.chars().count() < 25 {
+            continue;
+        }
+        let score = paragraph_score(text);
+        let mut ancestor = parent(&element);
+        for divider in [1., 2., 3.] {
+            let Some(node) = ancestor else {
+                break;
+            };
+            if tag_name(&node).is_none() {
+                break;
+            }
+            let entry = candidates
+                .entry(node_key(&node))
+                .or_insert_with(|| (node.clone(), initial_score(&node)));
+            entry.1 += score / divider;
+            ancestor = parent(&node);
+        }
+    }
+
+    candidates
+        .into_values()
+        .map(|(node, score)| {
+            let score = score * (1. - link_density(&node));
+            (node, score)
+        })
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(node, _)| node)
+}
+
+/// Returns the candidate along with siblings that likely belong to the same
+/// article, such as paragraphs split across several containers.
+fn with_related_siblings(candidate: &Handle) -> Vec<Handle> {
+    let Some(parent) = parent(candidate) else {
+        return vec![candidate.clone()];
+    };
+    let candidate_class = attr(candidate, "class");
+    let children = parent.children.borrow();
+    children
+        .iter()
+        .filter(|sibling| {
+            if Rc::ptr_eq(sibling, candidate) {
+                return true;
+            }
+            let Some(tag) = tag_name(sibling) else {
+                return false;
+            };
+            if candidate_class.is_some() && attr(sibling, "class") == candidate_class {
+                return true;
+            }
+            if tag == "p" {
+                let text = text_content(sibling);
+                let length = text.trim().chars().count();
+                let density = link_density(sibling);
+                return (length > 80 && density < 0.25)
+                    || (length > 0 && density == 0. && text.contains(". "));
+            }
+            false
+        })
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    use crate::markdown;
+
+    use super::*;
+
+    fn article_handlers() -> Vec<TagHandler> {
+        vec![
+            Rc::new(RefCell::new(markdown::ParagraphHandler)),
+            Rc::new(RefCell::new(markdown::HeadingHandler)),
+            Rc::new(RefCell::new(markdown::StyledTextHandler)),
+            Rc::new(RefCell::new(markdown::LinkHandler::new(|href| {
+                Some(format!("https://example.com{href}"))
+            }))),
+        ]
+    }
+
+    #[test]
+    fn test_extract_article_skips_page_chrome() {
+        let html = indoc! {r#"
+            <html>
+              <head>
+                <meta property="og:title" content="Gardening in Small Spaces">
+                <title>Gardening in Small Spaces - The Plant Journal</title>
+              </head>
+              <body>
+                <header><a href="/">The Plant Journal</a></header>
+                <div id="sidebar"><p>Popular posts, tags, archives, and everything else you might want.</p></div>
+                <div class="content">
+                  <p>Balconies and windowsills can grow more food than you'd expect, as long as they get sun.</p>
+                  <p>Start with herbs, like basil and mint, then move on to <a href="/tomatoes">tomatoes</a>.</p>
+                </div>
+                <div class="comment-list"><p>I tried this last summer and it worked great, thank you!</p></div>
+              </body>
+            </html>
+        "#};
+        let article = extract_article(html.as_bytes()).unwrap();
+
+        assert_eq!(article.title.as_deref(), Some("Gardening in Small Spaces"));
+        assert_eq!(
+            article.to_markdown(&mut article_handlers()).unwrap(),
+            indoc! {"
+                Balconies and windowsills can grow more food than you'd expect, as long as they get sun.
+
+                Start with herbs, like basil and mint, then move on to [tomatoes](https://example.com/tomatoes).
+            "}
+            .trim()
+        );
+    }
+
+    #[test]
+    fn test_page_title_drops_site_name() {
+        let html = "<html><head><title>Five Ways to Water Plants | The Plant Journal</title></head></html>";
+        let article = extract_article(html.as_bytes()).unwrap();
+        assert_eq!(article.title.as_deref(), Some("Five Ways to Water Plants"));
+
+        let html = "<html><head><title>Docs | Example</title></head></html>";
+        let article = extract_article(html.as_bytes()).unwrap();
+        assert_eq!(article.title.as_deref(), Some("Docs | Example"));
+    }
+}
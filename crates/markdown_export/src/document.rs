@@ -0,0 +1,554 @@
+use gpui::{FontWeight, Rgba};
+use markdown_preview::markdown_elements::{
+    HeadingLevel, Link, MarkdownHighlight, MarkdownParagraph, MarkdownParagraphChunk,
+    ParsedMarkdown, ParsedMarkdownCodeBlock, ParsedMarkdownElement, ParsedMarkdownListItemType,
+    ParsedMarkdownTable, ParsedMarkdownTableAlignment, ParsedMarkdownText,
+};
+use std::{collections::BTreeSet, path::PathBuf};
+use theme::SyntaxTheme;
+use vault::{Frontmatter, heading_anchor};
+
+/// A format-independent description of an exported document.
+///
+/// It is built from the same [`ParsedMarkdown`] tree the markdown preview
+/// renders, so the PDF and DOCX writers only have to decide how each block
+/// looks on paper, not how markdown is interpreted.
+#[derive(Debug)]
+pub struct Document {
+    pub title: String,
+    pub frontmatter: Frontmatter,
+    pub blocks: Vec<Block>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Block {
+    Heading {
+        level: u8,
+        spans: Vec<Span>,
+        anchor: String,
+    },
+    Paragraph(Vec<Span>),
+    ListItem {
+        depth: u16,
+        marker: ListMarker,
+        content: Vec<Block>,
+    },
+    CodeBlock {
+        language: Option<String>,
+        lines: Vec<Vec<Span>>,
+    },
+    Table(Table),
+    BlockQuote(Vec<Block>),
+    Image {
+        path: PathBuf,
+        alt: String,
+    },
+    Rule,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ListMarker {
+    Bullet,
+    Ordered(u64),
+    Task { checked: bool },
+}
+
+impl ListMarker {
+    pub fn text(&self) -> String {
+        match self {
+            ListMarker::Bullet => "•".into(),
+            ListMarker::Ordered(number) => format!("{number}."),
+            ListMarker::Task { checked: true } => "☑".into(),
+            ListMarker::Task { checked: false } => "☐".into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alignment {
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Table {
+    pub alignments: Vec<Alignment>,
+    pub header: Vec<Vec<Span>>,
+    pub rows: Vec<Vec<Vec<Span>>>,
+}
+
+impl Table {
+    pub fn column_count(&self) -> usize {
+        self.rows
+            .iter()
+            .map(Vec::len)
+            .chain([self.header.len(), self.alignments.len()])
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// A run of text with uniform styling.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Span {
+    pub text: String,
+    pub bold: bool,
+    pub italic: bool,
+    pub strikethrough: bool,
+    pub underline: bool,
+    pub code: bool,
+    pub link: Option<String>,
+    pub color: Option<Rgba>,
+}
+
+impl Span {
+    pub fn plain(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            ..Default::default()
+        }
+    }
+
+    fn same_style(&self, other: &Span) -> bool {
+        self.bold == other.bold
+            && self.italic == other.italic
+            && self.strikethrough == other.strikethrough
+            && self.underline == other.underline
+            && self.code == other.code
+            && self.link == other.link
+            && self.color == other.color
+    }
+}
+
+/// An entry of the generated table of contents.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TocEntry {
+    pub level: u8,
+    pub text: String,
+    pub anchor: String,
+}
+
+impl Document {
+    pub fn new(
+        title: String,
+        frontmatter: Frontmatter,
+        parsed: &ParsedMarkdown,
+        syntax_theme: &SyntaxTheme,
+    ) -> Self {
+        let mut builder = DocumentBuilder {
+            syntax_theme,
+            anchors: BTreeSet::new(),
+        };
+        let blocks = builder.blocks(&parsed.children);
+        Self {
+            title,
+            frontmatter,
+            blocks,
+        }
+    }
+
+    /// The headings that make up the table of contents, down to `max_level`.
+    pub fn toc_entries(&self, max_level: u8) -> Vec<TocEntry> {
+        self.blocks
+            .iter()
+            .filter_map(|block| match block {
+                Block::Heading {
+                    level,
+                    spans,
+                    anchor,
+                } if *level <= max_level => Some(TocEntry {
+                    level: *level,
+                    text: plain_text(spans),
+                    anchor: anchor.clone(),
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+pub fn plain_text(spans: &[Span]) -> String {
+    spans.iter().map(|span| span.text.as_str()).collect()
+}
+
+struct DocumentBuilder<'a> {
+    syntax_theme: &'a SyntaxTheme,
+    anchors: BTreeSet<String>,
+}
+
+impl DocumentBuilder<'_> {
+    fn blocks(&mut self, elements: &[ParsedMarkdownElement]) -> Vec<Block> {
+        let mut blocks = Vec::new();
+        for element in elements {
+            self.push_element(element, &mut blocks);
+        }
+        blocks
+    }
+
+    fn push_element(&mut self, element: &ParsedMarkdownElement, blocks: &mut Vec<Block>) {
+        match element {
+            ParsedMarkdownElement::Heading(heading) => {
+                let level = match heading.level {
+                    HeadingLevel::H1 => 1,
+                    HeadingLevel::H2 => 2,
+                    HeadingLevel::H3 => 3,
+                    HeadingLevel::H4 => 4,
+                    HeadingLevel::H5 => 5,
+                    HeadingLevel::H6 => 6,
+                };
+                let (spans, images) = self.paragraph(&heading.contents);
+                let anchor = self.unique_anchor(&plain_text(&spans));
+                blocks.push(Block::Heading {
+                    level,
+                    spans,
+                    anchor,
+                });
+                blocks.extend(images);
+            }
+            ParsedMarkdownElement::Paragraph(paragraph) => {
+                self.push_paragraph(paragraph, blocks);
+            }
+            ParsedMarkdownElement::ListItem(item) => {
+                let marker = match &item.item_type {
+                    ParsedMarkdownListItemType::Ordered(number) => ListMarker::Ordered(*number),
+                    ParsedMarkdownListItemType::Task(checked, _) => {
+                        ListMarker::Task { checked: *checked }
+                    }
+                    ParsedMarkdownListItemType::Unordered => ListMarker::Bullet,
+                };
+                let content = self.blocks(&item.content);
+                blocks.push(Block::ListItem {
+                    depth: item.depth,
+                    marker,
+                    content,
+                });
+            }
+            ParsedMarkdownElement::Table(table) => blocks.push(Block::Table(self.table(table))),
+            ParsedMarkdownElement::BlockQuote(quote) => {
+                blocks.push(Block::BlockQuote(self.blocks(&quote.children)));
+            }
+            ParsedMarkdownElement::CodeBlock(code_block) => {
+                blocks.push(self.code_block(code_block));
+            }
+            ParsedMarkdownElement::HorizontalRule(_) => blocks.push(Block::Rule),
+        }
+    }
+
+    /// Pushes a paragraph, splitting it around images since both formats lay
+    /// images out as their own blocks.
+    fn push_paragraph(&mut self, paragraph: &MarkdownParagraph, blocks: &mut Vec<Block>) {
+        let mut spans = Vec::new();
+        for chunk in paragraph {
+            match chunk {
+                MarkdownParagraphChunk::Text(text) => self.push_text(text, &mut spans),
+                MarkdownParagraphChunk::Image(image) => match &image.link {
+                    Link::Path { path, .. } => {
+                        if !spans.is_empty() {
+                            blocks.push(Block::Paragraph(std::mem::take(&mut spans)));
+                        }
+                        blocks.push(Block::Image {
+                            path: path.clone(),
+                            alt: image
+                                .alt_text
+                                .as_ref()
+                                .map(|alt| alt.to_string())
+                                .unwrap_or_default(),
+                        });
+                    }
+                    // Remote images aren't downloaded during export; keep them
+                    // reachable as a link instead.
+                    Link::Web { url } => spans.push(Span {
+                        text: image
+                            .alt_text
+                            .as_ref()
+                            .map(|alt| alt.to_string())
+                            .unwrap_or_else(|| url.clone()),
+                        underline: true,
+                        link: Some(url.clone()),
+                        ..Default::default()
+                    }),
+                },
+            }
+        }
+        if !spans.is_empty() {
+            blocks.push(Block::Paragraph(spans));
+        }
+    }
+
+    /// Converts inline content into spans, returning images separately.
+    fn paragraph(&mut self, paragraph: &MarkdownParagraph) -> (Vec<Span>, Vec<Block>) {
+        let mut blocks = Vec::new();
+        self.push_paragraph(paragraph, &mut blocks);
+        let mut spans = Vec::new();
+        let mut images = Vec::new();
+        for block in blocks {
+            match block {
+                Block::Paragraph(paragraph_spans) => spans.extend(paragraph_spans),
+                image => images.push(image),
+            }
+        }
+        (spans, images)
+    }
+
+    fn push_text(&self, text: &ParsedMarkdownText, spans: &mut Vec<Span>) {
+        let mut boundaries = BTreeSet::from([0, text.contents.len()]);
+        for (range, _) in &text.highlights {
+            boundaries.extend([range.start, range.end]);
+        }
+        for range in &text.region_ranges {
+            boundaries.extend([range.start, range.end]);
+        }
+
+        let boundaries = boundaries
+            .into_iter()
+            .filter(|ix| *ix <= text.contents.len() && text.contents.is_char_boundary(*ix))
+            .collect::<Vec<_>>();
+        for window in boundaries.windows(2) {
+            let (start, end) = (window[0], window[1]);
+            if start == end {
+                continue;
+            }
+            let mut span = Span::plain(&text.contents[start..end]);
+            for (range, highlight) in &text.highlights {
+                if range.start > start || range.end < end {
+                    continue;
+                }
+                match highlight {
+                    MarkdownHighlight::Style(style) => {
+                        span.italic |= style.italic;
+                        span.underline |= style.underline;
+                        span.strikethrough |= style.strikethrough;
+                        span.bold |= style.weight >= FontWeight::SEMIBOLD;
+                    }
+                    MarkdownHighlight::Code(id) => {
+                        span.color = id
+                            .style(self.syntax_theme)
+                            .and_then(|style| style.color)
+                            .map(Rgba::from);
+                    }
+                }
+            }
+            for (range, region) in text.region_ranges.iter().zip(&text.regions) {
+                if range.start > start || range.end < end {
+                    continue;
+                }
+                span.code |= region.code;
+                if let Some(link) = &region.link {
+                    span.link = Some(match link {
+                        Link::Web { url } => url.clone(),
+                        Link::Path { path, .. } => path.to_string_lossy().into_owned(),
+                    });
+                }
+            }
+            push_span(spans, span);
+        }
+    }
+
+    fn table(&mut self, table: &ParsedMarkdownTable) -> Table {
+        let alignments = table
+            .column_alignments
+            .iter()
+            .map(|alignment| match alignment {
+                ParsedMarkdownTableAlignment::None | ParsedMarkdownTableAlignment::Left => {
+                    Alignment::Left
+                }
+                ParsedMarkdownTableAlignment::Center => Alignment::Center,
+                ParsedMarkdownTableAlignment::Right => Alignment::Right,
+            })
+            .collect();
+        let header = table
+            .header
+            .children
+            .iter()
+            .map(|cell| self.paragraph(cell).0)
+            .collect();
+        let rows = table
+            .body
+            .iter()
+            .map(|row| {
+                row.children
+                    .iter()
+                    .map(|cell| self.paragraph(cell).0)
+                    .collect()
+            })
+            .collect();
+        Table {
+            alignments,
+            header,
+            rows,
+        }
+    }
+
+    fn code_block(&self, code_block: &ParsedMarkdownCodeBlock) -> Block {
+        let contents = code_block.contents.as_ref();
+        let mut colors: Vec<(std::ops::Range<usize>, Rgba)> = code_block
+            .highlights
+            .iter()
+            .flatten()
+            .filter_map(|(range, id)| {
+                let color = id.style(self.syntax_theme)?.color?;
+                Some((range.clone(), Rgba::from(color)))
+            })
+            .collect();
+        colors.sort_by_key(|(range, _)| range.start);
+
+        let mut lines = Vec::new();
+        let mut line_start = 0;
+        for line in contents.split('\n') {
+            let line_end = line_start + line.len();
+            let mut spans = Vec::new();
+            let mut offset = line_start;
+            for (range, color) in &colors {
+                let start = range.start.max(offset);
+                let end = range.end.min(line_end);
+                if start >= end {
+                    continue;
+                }
+                if offset < start {
+                    push_span(&mut spans, code_span(&contents[offset..start], None));
+                }
+                push_span(&mut spans, code_span(&contents[start..end], Some(*color)));
+                offset = end;
+            }
+            if offset < line_end {
+                push_span(&mut spans, code_span(&contents[offset..line_end], None));
+            }
+            lines.push(spans);
+            line_start = line_end + 1;
+        }
+
+        Block::CodeBlock {
+            language: code_block.language.clone(),
+            lines,
+        }
+    }
+
+    fn unique_anchor(&mut self, text: &str) -> String {
+        let base = match heading_anchor(text) {
+            anchor if anchor.is_empty() => "section".to_string(),
+            anchor => anchor,
+        };
+        let mut anchor = base.clone();
+        let mut counter = 1;
+        while !self.anchors.insert(anchor.clone()) {
+            anchor = format!("{base}-{counter}");
+            counter += 1;
+        }
+        anchor
+    }
+}
+
+fn code_span(text: &str, color: Option<Rgba>) -> Span {
+    Span {
+        text: text.to_string(),
+        code: true,
+        color,
+        ..Default::default()
+    }
+}
+
+fn push_span(spans: &mut Vec<Span>, span: Span) {
+    if let Some(last) = spans.last_mut() {
+        if last.same_style(&span) {
+            last.text.push_str(&span.text);
+            return;
+        }
+    }
+    spans.push(span);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use markdown_preview::markdown_parser::parse_markdown;
+    use pretty_assertions::assert_eq;
+
+    async fn document(markdown: &str) -> Document {
+        let parsed = parse_markdown(markdown, None, None).await;
+        Document::new(
+            "Test".into(),
+            Frontmatter::default(),
+            &parsed,
+            &SyntaxTheme::default(),
+        )
+    }
+
+    #[gpui::test]
+    async fn test_inline_styles() {
+        let document = document("Some **bold** and *italic* `code`").await;
+        assert_eq!(
+            document.blocks,
+            vec![Block::Paragraph(vec![
+                Span::plain("Some "),
+                Span {
+                    text: "bold".into(),
+                    bold: true,
+                    ..Default::default()
+                },
+                Span::plain(" and "),
+                Span {
+                    text: "italic".into(),
+                    italic: true,
+                    ..Default::default()
+                },
+                Span::plain(" "),
+                Span {
+                    text: "code".into(),
+                    code: true,
+                    ..Default::default()
+                },
+            ])]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_headings_and_lists() {
+        let document = document("# Intro\n## Intro\n- [x] done\n1. first\n").await;
+        assert_eq!(
+            document.toc_entries(3),
+            vec![
+                TocEntry {
+                    level: 1,
+                    text: "Intro".into(),
+                    anchor: "intro".into(),
+                },
+                TocEntry {
+                    level: 2,
+                    text: "Intro".into(),
+                    anchor: "intro-1".into(),
+                },
+            ]
+        );
+        assert_eq!(
+            document.blocks[2..],
+            [
+                Block::ListItem {
+                    depth: 1,
+                    marker: ListMarker::Task { checked: true },
+                    content: vec![Block::Paragraph(vec![Span::plain("done")])],
+                },
+                Block::ListItem {
+                    depth: 1,
+                    marker: ListMarker::Ordered(1),
+                    content: vec![Block::Paragraph(vec![Span::plain("first")])],
+                },
+            ]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_code_block_lines() {
+        let document = document("```\nfn main() {\n}\n```").await;
+        assert_eq!(
+            document.blocks,
+            vec![Block::CodeBlock {
+                language: None,
+                lines: vec![
+                    vec![code_span("fn main() {", None)],
+                    vec![code_span("}", None)],
+                ],
+            }]
+        );
+    }
+}
@@ -0,0 +1,910 @@
+use anyhow::Result;
+use async_zip::{ZipEntryBuilder, base::write::ZipFileWriter};
+use collections::HashMap;
+use futures::io::Cursor;
+use gpui::Rgba;
+use std::{
+    fmt::Write as _,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    ExportAssets, ExportOptions,
+    document::{Alignment, Block, Document, ListMarker, Span, Table, TocEntry},
+    template::{PageTemplate, TemplatePart},
+};
+
+const MAIN_NAMESPACES: &str = r#"xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships" xmlns:wp="http://schemas.openxmlformats.org/drawingml/2006/wordprocessingDrawing" xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main" xmlns:pic="http://schemas.openxmlformats.org/drawingml/2006/picture""#;
+
+const BODY_HALF_POINTS: u32 = 22;
+const CODE_HALF_POINTS: u32 = 19;
+const LIST_INDENT_TWIPS: u32 = 360;
+const QUOTE_INDENT_TWIPS: u32 = 280;
+const EMU_PER_POINT: f32 = 12700.;
+
+/// Heading sizes relative to the body text, matching the markdown preview.
+const HEADING_SCALES: [f32; 6] = [2.0, 1.5, 1.25, 1.0, 0.875, 0.85];
+
+/// Serializes `document` as an Office Open XML word processing document.
+pub async fn write_docx(
+    document: &Document,
+    options: &ExportOptions,
+    assets: &ExportAssets,
+) -> Result<Vec<u8>> {
+    let mut builder = DocxBuilder::new(options, assets);
+    builder.assign_bookmarks(&document.blocks);
+    if options.table_of_contents {
+        builder.table_of_contents(&document.toc_entries(options.toc_depth));
+    }
+    for block in &document.blocks {
+        builder.block(block, &BlockContext::default());
+    }
+
+    let mut parts = vec![
+        (
+            "word/document.xml".to_string(),
+            builder.document_xml().into_bytes(),
+        ),
+        ("word/styles.xml".to_string(), styles_xml().into_bytes()),
+        (
+            "word/settings.xml".to_string(),
+            settings_xml(options).into_bytes(),
+        ),
+        (
+            "docProps/core.xml".to_string(),
+            core_xml(document).into_bytes(),
+        ),
+    ];
+    if let Some(header) = &options.header {
+        let template = PageTemplate::parse(header, &options.variables);
+        parts.push((
+            "word/header1.xml".to_string(),
+            page_template_xml("hdr", &template, options).into_bytes(),
+        ));
+    }
+    if let Some(footer) = &options.footer {
+        let template = PageTemplate::parse(footer, &options.variables);
+        parts.push((
+            "word/footer1.xml".to_string(),
+            page_template_xml("ftr", &template, options).into_bytes(),
+        ));
+    }
+    parts.push((
+        "word/_rels/document.xml.rels".to_string(),
+        builder.relationships_xml().into_bytes(),
+    ));
+    parts.push((
+        "_rels/.rels".to_string(),
+        ROOT_RELATIONSHIPS.as_bytes().to_vec(),
+    ));
+    parts.push((
+        "[Content_Types].xml".to_string(),
+        content_types_xml(options, &builder.media).into_bytes(),
+    ));
+    for media in builder.media {
+        parts.push((format!("word/media/{}", media.file_name), media.data));
+    }
+
+    let mut output = Cursor::new(Vec::new());
+    let mut writer = ZipFileWriter::new(&mut output);
+    for (name, data) in parts {
+        let entry = ZipEntryBuilder::new(name.into(), async_zip::Compression::Deflate);
+        writer.write_entry_whole(entry, &data).await?;
+    }
+    writer.close().await?;
+    Ok(output.into_inner())
+}
+
+struct Media {
+    file_name: String,
+    extension: &'static str,
+    data: Vec<u8>,
+    /// Size in points.
+    width: f32,
+    height: f32,
+}
+
+struct Relationship {
+    id: String,
+    kind: &'static str,
+    target: String,
+    external: bool,
+}
+
+/// Paragraph properties inherited from enclosing lists and quotes.
+#[derive(Clone, Default)]
+struct BlockContext {
+    indent: u32,
+    quoted: bool,
+}
+
+struct DocxBuilder<'a> {
+    options: &'a ExportOptions,
+    assets: &'a ExportAssets,
+    body: String,
+    relationships: Vec<Relationship>,
+    media: Vec<Media>,
+    media_indices: HashMap<PathBuf, Option<usize>>,
+    /// Maps heading anchors to bookmark names, which Word limits to 40
+    /// characters.
+    bookmarks: HashMap<String, String>,
+    next_bookmark_id: usize,
+}
+
+impl<'a> DocxBuilder<'a> {
+    fn new(options: &'a ExportOptions, assets: &'a ExportAssets) -> Self {
+        let mut relationships = vec![
+            Relationship {
+                id: "rIdStyles".into(),
+                kind: "styles",
+                target: "styles.xml".into(),
+                external: false,
+            },
+            Relationship {
+                id: "rIdSettings".into(),
+                kind: "settings",
+                target: "settings.xml".into(),
+                external: false,
+            },
+        ];
+        if options.header.is_some() {
+            relationships.push(Relationship {
+                id: "rIdHeader".into(),
+                kind: "header",
+                target: "header1.xml".into(),
+                external: false,
+            });
+        }
+        if options.footer.is_some() {
+            relationships.push(Relationship {
+                id: "rIdFooter".into(),
+                kind: "footer",
+                target: "footer1.xml".into(),
+                external: false,
+            });
+        }
+        Self {
+            options,
+            assets,
+            body: String::new(),
+            relationships,
+            media: Vec::new(),
+            media_indices: HashMap::default(),
+            bookmarks: HashMap::default(),
+            next_bookmark_id: 0,
+        }
+    }
+
+    fn assign_bookmarks(&mut self, blocks: &[Block]) {
+        for block in blocks {
+            match block {
+                Block::Heading { anchor, .. } => {
+                    let name = format!("_Heading{}", self.bookmarks.len() + 1);
+                    self.bookmarks.insert(anchor.clone(), name);
+                }
+                Block::ListItem { content, .. } => self.assign_bookmarks(content),
+                Block::BlockQuote(children) => self.assign_bookmarks(children),
+                _ => {}
+            }
+        }
+    }
+
+    fn content_width_twips(&self) -> u32 {
+        let margins = &self.options.margins;
+        twips(self.options.page_width - margins.left - margins.right)
+    }
+
+    fn block(&mut self, block: &Block, cx: &BlockContext) {
+        match block {
+            Block::Heading {
+                level,
+                spans,
+                anchor,
+            } => {
+                let mut properties = format!(r#"<w:pStyle w:val="Heading{level}"/>"#);
+                paragraph_layout(&mut properties, cx, None);
+                self.body.push_str("<w:p><w:pPr>");
+                self.body.push_str(&properties);
+                self.body.push_str("</w:pPr>");
+                let bookmark = self.bookmarks.get(anchor).cloned();
+                let bookmark_id = self.next_bookmark_id;
+                if let Some(name) = &bookmark {
+                    self.next_bookmark_id += 1;
+                    let _ = write!(
+                        self.body,
+                        r#"<w:bookmarkStart w:id="{bookmark_id}" w:name="{name}"/>"#
+                    );
+                }
+                self.runs(spans, false);
+                if bookmark.is_some() {
+                    let _ = write!(self.body, r#"<w:bookmarkEnd w:id="{bookmark_id}"/>"#);
+                }
+                self.body.push_str("</w:p>");
+            }
+            Block::Paragraph(spans) => self.paragraph(spans, cx, None),
+            Block::ListItem {
+                depth,
+                marker,
+                content,
+            } => self.list_item(*depth, marker, content, cx),
+            Block::CodeBlock { lines, .. } => self.code_block(lines, cx),
+            Block::Table(table) => self.table(table, cx),
+            Block::BlockQuote(children) => {
+                let cx = BlockContext {
+                    indent: cx.indent + QUOTE_INDENT_TWIPS,
+                    quoted: true,
+                };
+                for child in children {
+                    self.block(child, &cx);
+                }
+            }
+            Block::Image { path, alt } => self.image(path, alt, cx),
+            Block::Rule => {
+                self.body.push_str(
+                    r#"<w:p><w:pPr><w:pBdr><w:bottom w:val="single" w:sz="6" w:space="1" w:color="CCCCCC"/></w:pBdr></w:pPr></w:p>"#,
+                );
+            }
+        }
+    }
+
+    fn paragraph(&mut self, spans: &[Span], cx: &BlockContext, hanging_marker: Option<&str>) {
+        let mut properties = String::new();
+        paragraph_layout(
+            &mut properties,
+            cx,
+            hanging_marker.map(|_| LIST_INDENT_TWIPS),
+        );
+        let _ = write!(self.body, "<w:p><w:pPr>{properties}</w:pPr>");
+        if let Some(marker) = hanging_marker {
+            self.runs(&[Span::plain(marker)], false);
+            self.body.push_str("<w:r><w:tab/></w:r>");
+        }
+        self.runs(spans, false);
+        self.body.push_str("</w:p>");
+    }
+
+    fn list_item(&mut self, depth: u16, marker: &ListMarker, content: &[Block], cx: &BlockContext) {
+        let marker = marker.text();
+        let item_cx = BlockContext {
+            indent: cx.indent + LIST_INDENT_TWIPS * depth as u32,
+            quoted: cx.quoted,
+        };
+        let mut marker = Some(marker.as_str());
+        for block in content {
+            match block {
+                Block::Paragraph(spans) => {
+                    self.paragraph(spans, &item_cx, marker.take());
+                }
+                Block::ListItem { .. } => {
+                    // Nested items carry their own depth.
+                    if let Some(marker) = marker.take() {
+                        self.paragraph(&[], &item_cx, Some(marker));
+                    }
+                    self.block(block, cx);
+                }
+                block => {
+                    if let Some(marker) = marker.take() {
+                        self.paragraph(&[], &item_cx, Some(marker));
+                    }
+                    self.block(block, &item_cx);
+                }
+            }
+        }
+        if let Some(marker) = marker {
+            self.paragraph(&[], &item_cx, Some(marker));
+        }
+    }
+
+    fn code_block(&mut self, lines: &[Vec<Span>], cx: &BlockContext) {
+        for (ix, spans) in lines.iter().enumerate() {
+            let mut properties = String::from(r#"<w:pStyle w:val="Code"/>"#);
+            paragraph_layout(&mut properties, cx, None);
+            if ix + 1 < lines.len() {
+                properties.push_str(r#"<w:spacing w:after="0"/>"#);
+            }
+            let _ = write!(self.body, "<w:p><w:pPr>{properties}</w:pPr>");
+            self.runs(spans, true);
+            self.body.push_str("</w:p>");
+        }
+    }
+
+    fn table(&mut self, table: &Table, cx: &BlockContext) {
+        let column_count = table.column_count();
+        if column_count == 0 {
+            return;
+        }
+        let width = self.content_width_twips().saturating_sub(cx.indent);
+        let column_width = width / column_count as u32;
+        let _ = write!(
+            self.body,
+            r#"<w:tbl><w:tblPr><w:tblW w:w="{width}" w:type="dxa"/><w:tblInd w:w="{}" w:type="dxa"/><w:tblBorders>"#,
+            cx.indent
+        );
+        for side in ["top", "left", "bottom", "right", "insideH", "insideV"] {
+            let _ = write!(
+                self.body,
+                r#"<w:{side} w:val="single" w:sz="4" w:space="0" w:color="CCCCCC"/>"#
+            );
+        }
+        self.body.push_str(
+            r#"</w:tblBorders><w:tblCellMar><w:left w:w="80" w:type="dxa"/><w:right w:w="80" w:type="dxa"/></w:tblCellMar></w:tblPr><w:tblGrid>"#,
+        );
+        for _ in 0..column_count {
+            let _ = write!(self.body, r#"<w:gridCol w:w="{column_width}"/>"#);
+        }
+        self.body.push_str("</w:tblGrid>");
+
+        let rows =
+            std::iter::once((&table.header, true)).chain(table.rows.iter().map(|row| (row, false)));
+        for (row, is_header) in rows {
+            self.body.push_str("<w:tr>");
+            if is_header {
+                self.body.push_str("<w:trPr><w:tblHeader/></w:trPr>");
+            }
+            for column in 0..column_count {
+                let _ = write!(
+                    self.body,
+                    r#"<w:tc><w:tcPr><w:tcW w:w="{column_width}" w:type="dxa"/>"#
+                );
+                if is_header {
+                    self.body
+                        .push_str(r#"<w:shd w:val="clear" w:color="auto" w:fill="F2F2F4"/>"#);
+                }
+                self.body.push_str("</w:tcPr><w:p><w:pPr>");
+                let alignment = match table.alignments.get(column) {
+                    Some(Alignment::Center) => "center",
+                    Some(Alignment::Right) => "right",
+                    _ => "left",
+                };
+                let _ = write!(
+                    self.body,
+                    r#"<w:spacing w:before="40" w:after="40"/><w:jc w:val="{alignment}"/></w:pPr>"#
+                );
+                let spans = row.get(column).cloned().unwrap_or_default();
+                let spans = if is_header {
+                    spans
+                        .into_iter()
+                        .map(|span| Span { bold: true, ..span })
+                        .collect()
+                } else {
+                    spans
+                };
+                self.runs(&spans, false);
+                self.body.push_str("</w:p></w:tc>");
+            }
+            self.body.push_str("</w:tr>");
+        }
+        self.body.push_str("</w:tbl>");
+        // Word merges adjacent tables, so always follow one with a paragraph.
+        self.body.push_str("<w:p/>");
+    }
+
+    fn image(&mut self, path: &Path, alt: &str, cx: &BlockContext) {
+        let Some(index) = self.load_image(path) else {
+            let text = if alt.is_empty() {
+                path.file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default()
+            } else {
+                alt.to_string()
+            };
+            let span = Span {
+                text: format!("[{text}]"),
+                italic: true,
+                ..Default::default()
+            };
+            self.paragraph(&[span], cx, None);
+            return;
+        };
+
+        let media = &self.media[index];
+        let max_width = (self.content_width_twips().saturating_sub(cx.indent)) as f32 / 20.;
+        let scale = (max_width / media.width).min(1.);
+        let width = (media.width * scale * EMU_PER_POINT) as u64;
+        let height = (media.height * scale * EMU_PER_POINT) as u64;
+        let relationship_id = format!("rIdImage{}", index + 1);
+        let id = index + 1;
+        let name = xml_escape(&media.file_name);
+        let description = xml_escape(alt);
+
+        let mut properties = String::new();
+        paragraph_layout(&mut properties, cx, None);
+        let _ = write!(
+            self.body,
+            concat!(
+                r#"<w:p><w:pPr>{properties}</w:pPr><w:r><w:drawing><wp:inline distT="0" distB="0" distL="0" distR="0">"#,
+                r#"<wp:extent cx="{width}" cy="{height}"/><wp:docPr id="{id}" name="{name}" descr="{description}"/>"#,
+                r#"<a:graphic><a:graphicData uri="http://schemas.openxmlformats.org/drawingml/2006/picture">"#,
+                r#"<pic:pic><pic:nvPicPr><pic:cNvPr id="{id}" name="{name}"/><pic:cNvPicPr/></pic:nvPicPr>"#,
+                r#"<pic:blipFill><a:blip r:embed="{relationship_id}"/><a:stretch><a:fillRect/></a:stretch></pic:blipFill>"#,
+                r#"<pic:spPr><a:xfrm><a:off x="0" y="0"/><a:ext cx="{width}" cy="{height}"/></a:xfrm><a:prstGeom prst="rect"><a:avLst/></a:prstGeom></pic:spPr>"#,
+                r#"</pic:pic></a:graphicData></a:graphic></wp:inline></w:drawing></w:r></w:p>"#,
+            ),
+            properties = properties,
+            width = width,
+            height = height,
+            id = id,
+            name = name,
+            description = description,
+            relationship_id = relationship_id,
+        );
+    }
+
+    fn load_image(&mut self, path: &Path) -> Option<usize> {
+        if let Some(index) = self.media_indices.get(path) {
+            return *index;
+        }
+        let index = self
+            .assets
+            .images
+            .get(path)
+            .and_then(|bytes| match prepare_image(bytes) {
+                Ok((extension, data, width, height)) => {
+                    let index = self.media.len();
+                    self.media.push(Media {
+                        file_name: format!("image{}.{extension}", index + 1),
+                        extension,
+                        data,
+                        width,
+                        height,
+                    });
+                    self.relationships.push(Relationship {
+                        id: format!("rIdImage{}", index + 1),
+                        kind: "image",
+                        target: format!("media/image{}.{extension}", index + 1),
+                        external: false,
+                    });
+                    Some(index)
+                }
+                Err(error) => {
+                    log::warn!("failed to decode image {path:?} for export: {error:#}");
+                    None
+                }
+            });
+        self.media_indices.insert(path.to_path_buf(), index);
+        index
+    }
+
+    fn table_of_contents(&mut self, entries: &[TocEntry]) {
+        if entries.is_empty() {
+            return;
+        }
+        self.body
+            .push_str(r#"<w:p><w:pPr><w:pStyle w:val="TOCHeading"/></w:pPr><w:r><w:t>Contents</w:t></w:r></w:p>"#);
+        let right_tab = self.content_width_twips();
+        for entry in entries {
+            let Some(bookmark) = self.bookmarks.get(&entry.anchor).cloned() else {
+                continue;
+            };
+            let indent = (entry.level.saturating_sub(1)) as u32 * 240;
+            let bold = if entry.level == 1 { "<w:b/>" } else { "" };
+            // Word fills in the PAGEREF fields when it updates the document's
+            // fields, which `settings.xml` asks it to do when the file opens.
+            let _ = write!(
+                self.body,
+                concat!(
+                    r#"<w:p><w:pPr><w:tabs><w:tab w:val="right" w:leader="dot" w:pos="{right_tab}"/></w:tabs>"#,
+                    r#"<w:spacing w:after="60"/><w:ind w:left="{indent}"/></w:pPr>"#,
+                    r#"<w:hyperlink w:anchor="{bookmark}" w:history="1">"#,
+                    r#"<w:r><w:rPr>{bold}</w:rPr><w:t xml:space="preserve">{text}</w:t></w:r><w:r><w:tab/></w:r>"#,
+                    r#"<w:r><w:fldChar w:fldCharType="begin" w:dirty="true"/></w:r>"#,
+                    r#"<w:r><w:instrText xml:space="preserve"> PAGEREF {bookmark} \h </w:instrText></w:r>"#,
+                    r#"<w:r><w:fldChar w:fldCharType="separate"/></w:r><w:r><w:t>#</w:t></w:r>"#,
+                    r#"<w:r><w:fldChar w:fldCharType="end"/></w:r></w:hyperlink></w:p>"#,
+                ),
+                right_tab = right_tab,
+                indent = indent,
+                bookmark = bookmark,
+                bold = bold,
+                text = xml_escape(&entry.text),
+            );
+        }
+        self.body
+            .push_str(r#"<w:p><w:r><w:br w:type="page"/></w:r></w:p>"#);
+    }
+
+    fn runs(&mut self, spans: &[Span], code_block: bool) {
+        for span in spans {
+            if span.text.is_empty() {
+                continue;
+            }
+            let mut properties = String::new();
+            if span.bold {
+                properties.push_str("<w:b/>");
+            }
+            if span.italic {
+                properties.push_str("<w:i/>");
+            }
+            if span.strikethrough {
+                properties.push_str("<w:strike/>");
+            }
+            if span.link.is_some() {
+                properties.push_str(r#"<w:rStyle w:val="Hyperlink"/>"#);
+            } else if let Some(color) = span.color {
+                let _ = write!(properties, r#"<w:color w:val="{}"/>"#, hex_color(color));
+            }
+            if span.underline || span.link.is_some() {
+                properties.push_str(r#"<w:u w:val="single"/>"#);
+            }
+            if span.code && !code_block {
+                properties.push_str(r#"<w:rStyle w:val="InlineCode"/>"#);
+            }
+
+            let run = run_xml(&properties, &span.text);
+            match &span.link {
+                Some(link) if link.starts_with('#') => {
+                    let anchor = link.trim_start_matches('#');
+                    match self.bookmarks.get(anchor) {
+                        Some(bookmark) => {
+                            let _ = write!(
+                                self.body,
+                                r#"<w:hyperlink w:anchor="{bookmark}" w:history="1">{run}</w:hyperlink>"#
+                            );
+                        }
+                        None => self.body.push_str(&run),
+                    }
+                }
+                Some(link) => {
+                    let id = format!("rIdLink{}", self.relationships.len() + 1);
+                    self.relationships.push(Relationship {
+                        id: id.clone(),
+                        kind: "hyperlink",
+                        target: link.clone(),
+                        external: true,
+                    });
+                    let _ = write!(
+                        self.body,
+                        r#"<w:hyperlink r:id="{id}" w:history="1">{run}</w:hyperlink>"#
+                    );
+                }
+                None => self.body.push_str(&run),
+            }
+        }
+    }
+
+    fn document_xml(&self) -> String {
+        let margins = &self.options.margins;
+        let mut references = String::new();
+        if self.options.header.is_some() {
+            references.push_str(r#"<w:headerReference w:type="default" r:id="rIdHeader"/>"#);
+        }
+        if self.options.footer.is_some() {
+            references.push_str(r#"<w:footerReference w:type="default" r:id="rIdFooter"/>"#);
+        }
+        format!(
+            concat!(
+                r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+                "\n<w:document {namespaces}><w:body>{body}",
+                r#"<w:sectPr>{references}<w:pgSz w:w="{width}" w:h="{height}"/>"#,
+                r#"<w:pgMar w:top="{top}" w:right="{right}" w:bottom="{bottom}" w:left="{left}" w:header="{header}" w:footer="{footer}" w:gutter="0"/>"#,
+                "</w:sectPr></w:body></w:document>",
+            ),
+            namespaces = MAIN_NAMESPACES,
+            body = self.body,
+            references = references,
+            width = twips(self.options.page_width),
+            height = twips(self.options.page_height),
+            top = twips(margins.top),
+            right = twips(margins.right),
+            bottom = twips(margins.bottom),
+            left = twips(margins.left),
+            header = twips(margins.top / 2.),
+            footer = twips(margins.bottom / 2.),
+        )
+    }
+
+    fn relationships_xml(&self) -> String {
+        let mut xml = String::from(concat!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+            "\n",
+            r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
+        ));
+        for relationship in &self.relationships {
+            let _ = write!(
+                xml,
+                r#"<Relationship Id="{}" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/{}" Target="{}"{}/>"#,
+                relationship.id,
+                relationship.kind,
+                xml_escape(&relationship.target),
+                if relationship.external {
+                    r#" TargetMode="External""#
+                } else {
+                    ""
+                }
+            );
+        }
+        xml.push_str("</Relationships>");
+        xml
+    }
+}
+
+/// Appends indentation and quote borders for a paragraph.
+fn paragraph_layout(properties: &mut String, cx: &BlockContext, hanging: Option<u32>) {
+    if cx.quoted {
+        properties.push_str(
+            r#"<w:pBdr><w:left w:val="single" w:sz="18" w:space="8" w:color="CCCCCC"/></w:pBdr>"#,
+        );
+    }
+    match hanging {
+        Some(hanging) => {
+            let _ = write!(
+                properties,
+                r#"<w:tabs><w:tab w:val="left" w:pos="{}"/></w:tabs><w:ind w:left="{}" w:hanging="{hanging}"/>"#,
+                cx.indent, cx.indent,
+            );
+        }
+        None if cx.indent > 0 => {
+            let _ = write!(properties, r#"<w:ind w:left="{}"/>"#, cx.indent);
+        }
+        None => {}
+    }
+}
+
+fn run_xml(properties: &str, text: &str) -> String {
+    let mut xml = String::from("<w:r>");
+    if !properties.is_empty() {
+        let _ = write!(xml, "<w:rPr>{properties}</w:rPr>");
+    }
+    for (ix, line) in text.split('\n').enumerate() {
+        if ix > 0 {
+            xml.push_str("<w:br/>");
+        }
+        for (ix, part) in line.split('\t').enumerate() {
+            if ix > 0 {
+                xml.push_str("<w:tab/>");
+            }
+            if !part.is_empty() {
+                let _ = write!(
+                    xml,
+                    r#"<w:t xml:space="preserve">{}</w:t>"#,
+                    xml_escape(part)
+                );
+            }
+        }
+    }
+    xml.push_str("</w:r>");
+    xml
+}
+
+/// Returns the image's file extension, bytes and size in points, converting
+/// formats Word can't display to PNG.
+fn prepare_image(bytes: &[u8]) -> Result<(&'static str, Vec<u8>, f32, f32)> {
+    let format = image::guess_format(bytes)?;
+    let image = image::load_from_memory_with_format(bytes, format)?;
+    // Treat pixels as CSS pixels (96 per inch) so images appear at the size
+    // the preview shows them.
+    let (width, height) = (image.width() as f32 * 0.75, image.height() as f32 * 0.75);
+    let (extension, data) = match format {
+        image::ImageFormat::Png => ("png", bytes.to_vec()),
+        image::ImageFormat::Jpeg => ("jpeg", bytes.to_vec()),
+        image::ImageFormat::Gif => ("gif", bytes.to_vec()),
+        _ => {
+            let mut data = std::io::Cursor::new(Vec::new());
+            image.write_to(&mut data, image::ImageFormat::Png)?;
+            ("png", data.into_inner())
+        }
+    };
+    Ok((extension, data, width, height))
+}
+
+fn page_template_xml(element: &str, template: &PageTemplate, options: &ExportOptions) -> String {
+    let width = twips(options.page_width - options.margins.left - options.margins.right);
+    let mut xml = format!(
+        concat!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+            "\n<w:{element} {namespaces}><w:p><w:pPr>",
+            r#"<w:tabs><w:tab w:val="center" w:pos="{center}"/><w:tab w:val="right" w:pos="{right}"/></w:tabs>"#,
+            r#"<w:rPr><w:color w:val="737373"/><w:sz w:val="17"/></w:rPr></w:pPr>"#,
+        ),
+        element = element,
+        namespaces = MAIN_NAMESPACES,
+        center = width / 2,
+        right = width,
+    );
+    let columns = [&template.left, &template.center, &template.right];
+    for (ix, parts) in columns.into_iter().enumerate() {
+        if ix > 0 {
+            xml.push_str("<w:r><w:tab/></w:r>");
+        }
+        for part in parts {
+            let properties = r#"<w:color w:val="737373"/><w:sz w:val="17"/>"#;
+            match part {
+                TemplatePart::Text(text) => xml.push_str(&run_xml(properties, text)),
+                TemplatePart::PageNumber | TemplatePart::PageCount => {
+                    let field = if *part == TemplatePart::PageNumber {
+                        "PAGE"
+                    } else {
+                        "NUMPAGES"
+                    };
+                    let _ = write!(
+                        xml,
+                        r#"<w:fldSimple w:instr=" {field} "><w:r><w:rPr>{properties}</w:rPr><w:t>1</w:t></w:r></w:fldSimple>"#
+                    );
+                }
+            }
+        }
+    }
+    let _ = write!(xml, "</w:p></w:{element}>");
+    xml
+}
+
+fn styles_xml() -> String {
+    let mut xml = format!(
+        concat!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+            "\n<w:styles {namespaces}>",
+            r#"<w:docDefaults><w:rPrDefault><w:rPr><w:rFonts w:ascii="Helvetica" w:hAnsi="Helvetica" w:eastAsia="Helvetica" w:cs="Helvetica"/>"#,
+            r#"<w:color w:val="1A1A1A"/><w:sz w:val="{body}"/><w:szCs w:val="{body}"/></w:rPr></w:rPrDefault>"#,
+            r#"<w:pPrDefault><w:pPr><w:spacing w:after="160" w:line="336" w:lineRule="auto"/></w:pPr></w:pPrDefault></w:docDefaults>"#,
+            r#"<w:style w:type="paragraph" w:default="1" w:styleId="Normal"><w:name w:val="Normal"/><w:qFormat/></w:style>"#,
+            r#"<w:style w:type="paragraph" w:styleId="Code"><w:name w:val="Code"/><w:basedOn w:val="Normal"/><w:pPr>"#,
+            r#"<w:shd w:val="clear" w:color="auto" w:fill="F2F2F5"/><w:spacing w:line="240" w:lineRule="auto"/></w:pPr>"#,
+            r#"<w:rPr><w:rFonts w:ascii="Courier New" w:hAnsi="Courier New" w:cs="Courier New"/><w:sz w:val="{code}"/><w:szCs w:val="{code}"/></w:rPr></w:style>"#,
+            r#"<w:style w:type="character" w:styleId="InlineCode"><w:name w:val="Inline Code"/>"#,
+            r#"<w:rPr><w:rFonts w:ascii="Courier New" w:hAnsi="Courier New" w:cs="Courier New"/><w:sz w:val="{code}"/>"#,
+            r#"<w:shd w:val="clear" w:color="auto" w:fill="F2F2F5"/></w:rPr></w:style>"#,
+            r#"<w:style w:type="character" w:styleId="Hyperlink"><w:name w:val="Hyperlink"/><w:rPr><w:color w:val="0D59BF"/></w:rPr></w:style>"#,
+            r#"<w:style w:type="paragraph" w:styleId="TOCHeading"><w:name w:val="TOC Heading"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/>"#,
+            r#"<w:pPr><w:spacing w:before="240" w:after="240"/></w:pPr><w:rPr><w:b/><w:sz w:val="{toc}"/><w:szCs w:val="{toc}"/></w:rPr></w:style>"#,
+        ),
+        namespaces = MAIN_NAMESPACES,
+        body = BODY_HALF_POINTS,
+        code = CODE_HALF_POINTS,
+        toc = (BODY_HALF_POINTS as f32 * HEADING_SCALES[0]).round() as u32,
+    );
+    for (ix, scale) in HEADING_SCALES.iter().enumerate() {
+        let level = ix + 1;
+        let size = (BODY_HALF_POINTS as f32 * scale).round() as u32;
+        let color = if level == 6 { "737373" } else { "1A1A1A" };
+        let _ = write!(
+            xml,
+            concat!(
+                r#"<w:style w:type="paragraph" w:styleId="Heading{level}"><w:name w:val="heading {level}"/>"#,
+                r#"<w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:qFormat/>"#,
+                r#"<w:pPr><w:keepNext/><w:spacing w:before="{before}" w:after="120"/><w:outlineLvl w:val="{outline}"/></w:pPr>"#,
+                r#"<w:rPr><w:b/><w:color w:val="{color}"/><w:sz w:val="{size}"/><w:szCs w:val="{size}"/></w:rPr></w:style>"#,
+            ),
+            level = level,
+            before = size * 6,
+            outline = ix,
+            color = color,
+            size = size,
+        );
+    }
+    xml.push_str("</w:styles>");
+    xml
+}
+
+fn settings_xml(options: &ExportOptions) -> String {
+    // Ask Word to refresh fields on open so the table of contents gets its
+    // page numbers.
+    let update_fields = if options.table_of_contents {
+        r#"<w:updateFields w:val="true"/>"#
+    } else {
+        ""
+    };
+    format!(
+        concat!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+            "\n<w:settings {namespaces}>{update_fields}",
+            r#"<w:defaultTabStop w:val="720"/><w:compat><w:compatSetting w:name="compatibilityMode" w:uri="http://schemas.microsoft.com/office/word" w:val="15"/></w:compat>"#,
+            "</w:settings>",
+        ),
+        namespaces = MAIN_NAMESPACES,
+        update_fields = update_fields,
+    )
+}
+
+fn core_xml(document: &Document) -> String {
+    let author = document
+        .frontmatter
+        .get("author")
+        .map(|author| format!("<dc:creator>{}</dc:creator>", xml_escape(&author.display())))
+        .unwrap_or_default();
+    format!(
+        concat!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+            "\n",
+            r#"<cp:coreProperties xmlns:cp="http://schemas.openxmlformats.org/package/2006/metadata/core-properties" xmlns:dc="http://purl.org/dc/elements/1.1/">"#,
+            "<dc:title>{title}</dc:title>{author}</cp:coreProperties>",
+        ),
+        title = xml_escape(&document.title),
+        author = author,
+    )
+}
+
+fn content_types_xml(options: &ExportOptions, media: &[Media]) -> String {
+    let mut xml = String::from(concat!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+        "\n",
+        r#"<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">"#,
+        r#"<Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>"#,
+        r#"<Default Extension="xml" ContentType="application/xml"/>"#,
+        r#"<Override PartName="/word/document.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml"/>"#,
+        r#"<Override PartName="/word/styles.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.styles+xml"/>"#,
+        r#"<Override PartName="/word/settings.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.settings+xml"/>"#,
+        r#"<Override PartName="/docProps/core.xml" ContentType="application/vnd.openxmlformats-package.core-properties+xml"/>"#,
+    ));
+    if options.header.is_some() {
+        xml.push_str(r#"<Override PartName="/word/header1.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.header+xml"/>"#);
+    }
+    if options.footer.is_some() {
+        xml.push_str(r#"<Override PartName="/word/footer1.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.footer+xml"/>"#);
+    }
+    let mut extensions = media
+        .iter()
+        .map(|media| media.extension)
+        .collect::<Vec<_>>();
+    extensions.sort_unstable();
+    extensions.dedup();
+    for extension in extensions {
+        let _ = write!(
+            xml,
+            r#"<Default Extension="{extension}" ContentType="image/{extension}"/>"#
+        );
+    }
+    xml.push_str("</Types>");
+    xml
+}
+
+const ROOT_RELATIONSHIPS: &str = concat!(
+    r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+    "\n",
+    r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
+    r#"<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="word/document.xml"/>"#,
+    r#"<Relationship Id="rId2" Type="http://schemas.openxmlformats.org/package/2006/relationships/metadata/core-properties" Target="docProps/core.xml"/>"#,
+    "</Relationships>",
+);
+
+fn twips(points: f32) -> u32 {
+    (points * 20.).round().max(0.) as u32
+}
+
+fn hex_color(color: Rgba) -> String {
+    let channel = |value: f32| (value.clamp(0., 1.) * 255.).round() as u8;
+    format!(
+        "{:02X}{:02X}{:02X}",
+        channel(color.r),
+        channel(color.g),
+        channel(color.b)
+    )
+}
+
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Control characters aren't allowed in XML 1.0.
+            c if (c as u32) < 0x20 && !matches!(c, '\t' | '\n' | '\r') => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_xml() {
+        assert_eq!(
+            run_xml("<w:b/>", "a < b\nc"),
+            r#"<w:r><w:rPr><w:b/></w:rPr><w:t xml:space="preserve">a &lt; b</w:t><w:br/><w:t xml:space="preserve">c</w:t></w:r>"#
+        );
+        assert_eq!(
+            hex_color(Rgba {
+                r: 1.,
+                g: 0.5,
+                b: 0.,
+                a: 1.
+            }),
+            "FF8000"
+        );
+    }
+}
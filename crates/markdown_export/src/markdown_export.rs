@@ -0,0 +1,493 @@
+//! Exports markdown notes to PDF and DOCX.
+//!
+//! Notes go through the same preprocessing a vault reader would expect:
+//! frontmatter is turned into document metadata, `![[embeds]]` are inlined,
+//! and `$math$` is rendered to unicode text, before the markdown preview's
+//! parser produces the tree that the format writers lay out.
+
+mod document;
+mod docx;
+mod math;
+mod pdf;
+mod template;
+
+use anyhow::{Context as _, Result};
+use collections::HashMap;
+use editor::Editor;
+use fs::Fs;
+use gpui::{App, AppContext as _, Context, Entity, Window, actions};
+use markdown_preview::{
+    markdown_parser::parse_markdown, markdown_preview_view::MarkdownPreviewView,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use theme::{ActiveTheme as _, Appearance, SyntaxTheme, ThemeRegistry};
+use vault::{Frontmatter, NoteResolver, body_offset, code_ranges, expand_embeds};
+use workspace::{Workspace, notifications::NotifyTaskExt as _};
+
+pub use document::{Block, Document};
+pub use docx::write_docx;
+pub use math::tex_to_unicode;
+pub use pdf::write_pdf;
+
+actions!(markdown_export, [ExportToPdf, ExportToDocx]);
+
+/// The theme used to color code blocks when the active theme is dark, since
+/// exported documents are always printed on white.
+const LIGHT_SYNTAX_THEME: &str = "One Light";
+
+/// Settings for exporting markdown to PDF and DOCX.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct MarkdownExportSettings {
+    /// The size of exported pages.
+    ///
+    /// Default: a4
+    pub page_size: Option<PageSize>,
+    /// The page margins, in millimeters.
+    ///
+    /// Default: 20
+    pub margin: Option<f32>,
+    /// The text printed at the top of every page. Columns are separated by
+    /// `|`, and `{title}`, `{date}`, `{file}`, `{page}`, `{pages}` and any
+    /// frontmatter property can be used as placeholders.
+    ///
+    /// Default: ""
+    pub header: Option<String>,
+    /// The text printed at the bottom of every page, using the same
+    /// placeholders as the header.
+    ///
+    /// Default: "{page} / {pages}"
+    pub footer: Option<String>,
+    /// Whether to start the document with a table of contents.
+    ///
+    /// Default: false
+    pub table_of_contents: Option<bool>,
+    /// The deepest heading level listed in the table of contents.
+    ///
+    /// Default: 3
+    pub table_of_contents_depth: Option<u8>,
+}
+
+impl Default for MarkdownExportSettings {
+    fn default() -> Self {
+        Self {
+            page_size: Some(PageSize::default()),
+            margin: Some(20.),
+            header: Some(String::new()),
+            footer: Some("{page} / {pages}".into()),
+            table_of_contents: Some(false),
+            table_of_contents_depth: Some(3),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PageSize {
+    #[default]
+    A4,
+    A5,
+    Letter,
+    Legal,
+}
+
+impl PageSize {
+    /// The page's width and height in points.
+    pub fn dimensions(self) -> (f32, f32) {
+        match self {
+            PageSize::A4 => (595.28, 841.89),
+            PageSize::A5 => (419.53, 595.28),
+            PageSize::Letter => (612., 792.),
+            PageSize::Legal => (612., 1008.),
+        }
+    }
+}
+
+impl settings::Settings for MarkdownExportSettings {
+    const KEY: Option<&'static str> = Some("markdown_export");
+
+    type FileContent = Self;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _: &mut App) -> Result<Self> {
+        sources.json_merge()
+    }
+}
+
+/// Page margins in points.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Margins {
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+    pub left: f32,
+}
+
+/// Everything about an export that doesn't come from the note itself.
+#[derive(Clone, Debug)]
+pub struct ExportOptions {
+    /// Page width in points.
+    pub page_width: f32,
+    /// Page height in points.
+    pub page_height: f32,
+    pub margins: Margins,
+    pub header: Option<String>,
+    pub footer: Option<String>,
+    /// Values for the placeholders in the header and footer.
+    pub variables: HashMap<String, String>,
+    pub table_of_contents: bool,
+    pub toc_depth: u8,
+}
+
+impl ExportOptions {
+    pub fn from_settings(settings: &MarkdownExportSettings) -> Self {
+        let (page_width, page_height) = settings.page_size.unwrap_or_default().dimensions();
+        let margin = settings.margin.unwrap_or(20.).max(0.) * 72. / 25.4;
+        let template = |template: &Option<String>| {
+            template
+                .as_ref()
+                .filter(|template| !template.trim().is_empty())
+                .cloned()
+        };
+        Self {
+            page_width,
+            page_height,
+            margins: Margins {
+                top: margin,
+                right: margin,
+                bottom: margin,
+                left: margin,
+            },
+            header: template(&settings.header),
+            footer: template(&settings.footer),
+            variables: HashMap::default(),
+            table_of_contents: settings.table_of_contents.unwrap_or(false),
+            toc_depth: settings.table_of_contents_depth.unwrap_or(3).clamp(1, 6),
+        }
+    }
+}
+
+/// Files referenced by the document, loaded ahead of time so the writers
+/// don't need file system access.
+#[derive(Clone, Debug, Default)]
+pub struct ExportAssets {
+    /// Image contents, keyed by the absolute paths used in [`Block::Image`].
+    pub images: HashMap<PathBuf, Vec<u8>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    Pdf,
+    Docx,
+}
+
+impl ExportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Pdf => "pdf",
+            ExportFormat::Docx => "docx",
+        }
+    }
+}
+
+pub fn init(cx: &mut App) {
+    MarkdownExportSettings::register(cx);
+
+    cx.observe_new(|workspace: &mut Workspace, _window, _cx| {
+        workspace.register_action(|workspace, _: &ExportToPdf, window, cx| {
+            export_active_note(workspace, ExportFormat::Pdf, window, cx);
+        });
+        workspace.register_action(|workspace, _: &ExportToDocx, window, cx| {
+            export_active_note(workspace, ExportFormat::Docx, window, cx);
+        });
+    })
+    .detach();
+}
+
+/// Exports the markdown file in the active editor, asking where to save it.
+pub fn export_active_note(
+    workspace: &mut Workspace,
+    format: ExportFormat,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let Some(editor) = MarkdownPreviewView::resolve_active_item_as_markdown_editor(workspace, cx)
+    else {
+        return;
+    };
+    let Some(note) = NoteSource::for_editor(&editor, workspace, cx) else {
+        return;
+    };
+
+    let fs = workspace.app_state().fs.clone();
+    let language_registry = workspace.app_state().languages.clone();
+    let syntax_theme = export_syntax_theme(cx);
+    let settings = MarkdownExportSettings::get_global(cx).clone();
+    let directory = note
+        .abs_path
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+    let save_path = cx.prompt_for_new_path(&directory);
+
+    cx.spawn_in(window, async move |_, cx| {
+        let Some(save_path) = save_path.await?? else {
+            return anyhow::Ok(());
+        };
+        let save_path = if save_path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case(format.extension()))
+        {
+            save_path
+        } else {
+            save_path.with_extension(format.extension())
+        };
+
+        let (document, options, assets) = prepare_export(
+            note,
+            &settings,
+            fs.as_ref(),
+            language_registry,
+            &syntax_theme,
+        )
+        .await?;
+        let bytes = match format {
+            ExportFormat::Pdf => {
+                cx.background_spawn(async move { write_pdf(&document, &options, &assets) })
+                    .await?
+            }
+            ExportFormat::Docx => {
+                cx.background_spawn(async move { write_docx(&document, &options, &assets).await })
+                    .await?
+            }
+        };
+        fs.create_file_with(
+            &save_path,
+            Box::pin(futures::io::Cursor::new(bytes)).as_mut(),
+        )
+        .await
+        .with_context(|| format!("writing {save_path:?}"))?;
+        Ok(())
+    })
+    .detach_and_notify_err(window, cx);
+}
+
+/// A snapshot of the note being exported and the vault it lives in.
+pub struct NoteSource {
+    pub text: String,
+    pub abs_path: PathBuf,
+    /// The note's path relative to `root`.
+    pub path: PathBuf,
+    /// The folder that wiki links are resolved in.
+    pub root: PathBuf,
+    pub resolver: NoteResolver,
+}
+
+impl NoteSource {
+    fn for_editor(editor: &Entity<Editor>, workspace: &Workspace, cx: &App) -> Option<Self> {
+        let buffer = editor.read(cx).buffer().read(cx).as_singleton()?;
+        let buffer = buffer.read(cx);
+        let file = buffer.file()?.as_local()?;
+        let abs_path = file.abs_path(cx);
+        let worktree = workspace
+            .project()
+            .read(cx)
+            .worktree_for_id(file.worktree_id(cx), cx);
+
+        let (root, path, resolver) = match worktree {
+            Some(worktree) => {
+                let snapshot = worktree.read(cx).snapshot();
+                let resolver =
+                    NoteResolver::new(snapshot.files(false, 0).map(|entry| entry.path.clone()));
+                (
+                    snapshot.abs_path().to_path_buf(),
+                    file.path().to_path_buf(),
+                    resolver,
+                )
+            }
+            None => (
+                abs_path.parent()?.to_path_buf(),
+                PathBuf::from(abs_path.file_name()?),
+                NoteResolver::default(),
+            ),
+        };
+
+        Some(Self {
+            text: buffer.text(),
+            abs_path,
+            path,
+            root,
+            resolver,
+        })
+    }
+}
+
+/// Prefers the active theme's syntax colors, falling back to a light theme
+/// when the active one is dark.
+fn export_syntax_theme(cx: &App) -> Arc<SyntaxTheme> {
+    let theme = cx.theme();
+    if theme.appearance() == Appearance::Light {
+        return theme.syntax().clone();
+    }
+    ThemeRegistry::global(cx)
+        .get(LIGHT_SYNTAX_THEME)
+        .map(|theme| theme.syntax().clone())
+        .unwrap_or_else(|_| Arc::new(SyntaxTheme::default()))
+}
+
+/// Turns a note into a [`Document`] and loads the images it references.
+pub async fn prepare_export(
+    note: NoteSource,
+    settings: &MarkdownExportSettings,
+    fs: &dyn Fs,
+    language_registry: Arc<language::LanguageRegistry>,
+    syntax_theme: &SyntaxTheme,
+) -> Result<(Document, ExportOptions, ExportAssets)> {
+    let frontmatter = Frontmatter::parse(&note.text).unwrap_or_default();
+    let body = &note.text[body_offset(&note.text)..];
+    let file_stem = note
+        .abs_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let title = frontmatter
+        .get_text("title")
+        .map(ToString::to_string)
+        .unwrap_or_else(|| file_stem.clone());
+
+    let body = expand_embeds(body, &note.path, &note.root, &note.resolver, fs).await;
+    let body = math::replace_math(&body, &code_ranges(&body));
+    let parsed = parse_markdown(
+        &body,
+        note.abs_path.parent().map(Path::to_path_buf),
+        Some(language_registry),
+    )
+    .await;
+
+    let mut options = ExportOptions::from_settings(settings);
+    for (key, value) in frontmatter.properties() {
+        options.variables.insert(key.to_string(), value.display());
+    }
+    options.variables.insert("title".into(), title.clone());
+    options.variables.insert("file".into(), file_stem);
+    options.variables.insert(
+        "date".into(),
+        chrono::Local::now().format("%Y-%m-%d").to_string(),
+    );
+
+    let document = Document::new(title, frontmatter, &parsed, syntax_theme);
+
+    let mut assets = ExportAssets::default();
+    let mut image_paths = Vec::new();
+    collect_image_paths(&document.blocks, &mut image_paths);
+    for path in image_paths {
+        if assets.images.contains_key(&path) {
+            continue;
+        }
+        match fs.load_bytes(&path).await {
+            Ok(bytes) => {
+                assets.images.insert(path, bytes);
+            }
+            Err(error) => log::warn!("failed to load image {path:?} for export: {error}"),
+        }
+    }
+
+    Ok((document, options, assets))
+}
+
+fn collect_image_paths(blocks: &[Block], paths: &mut Vec<PathBuf>) {
+    for block in blocks {
+        match block {
+            Block::Image { path, .. } => paths.push(path.clone()),
+            Block::ListItem { content, .. } => collect_image_paths(content, paths),
+            Block::BlockQuote(children) => collect_image_paths(children, paths),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use language::LanguageRegistry;
+    use serde_json::json;
+    use util::path;
+
+    #[gpui::test]
+    async fn test_export_note(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/vault"),
+            json!({
+                "Roadmap.md": "---\ntitle: Product Roadmap\nauthor: Ada\n---\n# Goals\n\n![[Summary#Intro]]\n\nEnergy is $E = mc^2$.\n",
+                "Summary.md": "# Intro\nShipped **everything**.\n# Other\nHidden.\n",
+            }),
+        )
+        .await;
+
+        let text = fs.load(path!("/vault/Roadmap.md").as_ref()).await.unwrap();
+        let note = NoteSource {
+            text,
+            abs_path: PathBuf::from(path!("/vault/Roadmap.md")),
+            path: PathBuf::from("Roadmap.md"),
+            root: PathBuf::from(path!("/vault")),
+            resolver: NoteResolver::new([
+                Arc::from(Path::new("Roadmap.md")),
+                Arc::from(Path::new("Summary.md")),
+            ]),
+        };
+        let language_registry = Arc::new(LanguageRegistry::test(cx.executor()));
+        let settings = MarkdownExportSettings {
+            header: Some("{title} | {author}".into()),
+            table_of_contents: Some(true),
+            ..Default::default()
+        };
+        let (document, options, assets) = prepare_export(
+            note,
+            &settings,
+            fs.as_ref(),
+            language_registry,
+            &SyntaxTheme::default(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(document.title, "Product Roadmap");
+        assert_eq!(
+            options.variables.get("author").map(String::as_str),
+            Some("Ada")
+        );
+        let text = document
+            .blocks
+            .iter()
+            .filter_map(|block| match block {
+                Block::Heading { spans, .. } | Block::Paragraph(spans) => {
+                    Some(document::plain_text(spans))
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            text,
+            [
+                "Goals",
+                "Intro",
+                "Shipped everything.",
+                "Energy is E = mc²."
+            ]
+        );
+
+        let pdf = write_pdf(&document, &options, &assets).unwrap();
+        assert!(pdf.starts_with(b"%PDF-1.4"));
+        assert!(pdf.ends_with(b"%%EOF\n"));
+
+        let docx = write_docx(&document, &options, &assets).await.unwrap();
+        assert!(docx.starts_with(b"PK"));
+    }
+}
@@ -0,0 +1,480 @@
+//! Renders TeX math (`$x^2$`, `$$\sum_i x_i$$`) as plain Unicode text.
+//!
+//! Neither export format can typeset TeX, but most math in notes is short and
+//! reads fine once commands are replaced by their symbols and scripts are
+//! raised or lowered with Unicode super- and subscript characters.
+
+use std::ops::Range;
+
+/// Replaces every math span in `markdown` with its Unicode rendering, wrapped
+/// in emphasis so it still stands out from the surrounding prose.
+pub fn replace_math(markdown: &str, code_ranges: &[Range<usize>]) -> String {
+    let mut result = String::with_capacity(markdown.len());
+    let mut last_end = 0;
+    for span in math_spans(markdown, code_ranges) {
+        result.push_str(&markdown[last_end..span.range.start]);
+        let rendered = escape_markdown(&tex_to_unicode(&markdown[span.content.clone()]));
+        if span.display {
+            result.push_str(&format!("\n\n*{}*\n\n", rendered.trim()));
+        } else if !rendered.trim().is_empty() {
+            result.push_str(&format!("*{}*", rendered.trim()));
+        }
+        last_end = span.range.end;
+    }
+    result.push_str(&markdown[last_end..]);
+    result
+}
+
+#[derive(Debug, PartialEq)]
+struct MathSpan {
+    range: Range<usize>,
+    content: Range<usize>,
+    display: bool,
+}
+
+fn math_spans(text: &str, code_ranges: &[Range<usize>]) -> Vec<MathSpan> {
+    let bytes = text.as_bytes();
+    let mut spans = Vec::new();
+    let mut ix = 0;
+    while ix < bytes.len() {
+        if let Some(code) = code_ranges.iter().find(|range| range.contains(&ix)) {
+            ix = code.end;
+            continue;
+        }
+        if bytes[ix] == b'\\' {
+            ix += 2;
+            continue;
+        }
+        if bytes[ix] != b'$' {
+            ix += 1;
+            continue;
+        }
+
+        if bytes.get(ix + 1) == Some(&b'$') {
+            if let Some(len) = text[ix + 2..].find("$$") {
+                let end = ix + 2 + len + 2;
+                spans.push(MathSpan {
+                    range: ix..end,
+                    content: ix + 2..ix + 2 + len,
+                    display: true,
+                });
+                ix = end;
+                continue;
+            }
+            ix += 2;
+            continue;
+        }
+
+        // Inline math must hug its delimiters, which keeps prices like
+        // "$5 and $10" from being treated as math.
+        let opens = bytes
+            .get(ix + 1)
+            .is_some_and(|next| !next.is_ascii_whitespace());
+        let close = opens.then(|| find_inline_close(text, ix + 1)).flatten();
+        match close {
+            Some(close) => {
+                spans.push(MathSpan {
+                    range: ix..close + 1,
+                    content: ix + 1..close,
+                    display: false,
+                });
+                ix = close + 1;
+            }
+            None => ix += 1,
+        }
+    }
+    spans
+}
+
+fn find_inline_close(text: &str, start: usize) -> Option<usize> {
+    let bytes = text.as_bytes();
+    let mut ix = start;
+    while ix < bytes.len() {
+        match bytes[ix] {
+            b'\n' if bytes.get(ix + 1) == Some(&b'\n') => return None,
+            b'\\' => ix += 1,
+            b'$' => {
+                // A dollar sign after whitespace looks like the start of
+                // another span (or another price), so this one isn't math.
+                if bytes[ix - 1].is_ascii_whitespace() {
+                    return None;
+                }
+                let followed_by_digit = bytes.get(ix + 1).is_some_and(u8::is_ascii_digit);
+                if !followed_by_digit {
+                    return Some(ix);
+                }
+            }
+            _ => {}
+        }
+        ix += 1;
+    }
+    None
+}
+
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(
+            c,
+            '*' | '_' | '`' | '[' | ']' | '<' | '>' | '#' | '\\' | '|'
+        ) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Converts a TeX math expression into Unicode text.
+pub fn tex_to_unicode(tex: &str) -> String {
+    let mut parser = TexParser {
+        chars: tex.chars().collect(),
+        ix: 0,
+    };
+    let rendered = parser.parse_until(None);
+    rendered.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+struct TexParser {
+    chars: Vec<char>,
+    ix: usize,
+}
+
+impl TexParser {
+    fn parse_until(&mut self, terminator: Option<char>) -> String {
+        let mut output = String::new();
+        while let Some(c) = self.chars.get(self.ix).copied() {
+            if Some(c) == terminator {
+                self.ix += 1;
+                break;
+            }
+            self.ix += 1;
+            match c {
+                '\\' => output.push_str(&self.parse_command()),
+                '{' => output.push_str(&self.parse_until(Some('}'))),
+                '^' => {
+                    let argument = self.parse_argument();
+                    output.push_str(&script(&argument, superscript, '^'));
+                }
+                '_' => {
+                    let argument = self.parse_argument();
+                    output.push_str(&script(&argument, subscript, '_'));
+                }
+                '&' => output.push(' '),
+                '~' => output.push(' '),
+                '\'' => output.push('′'),
+                c => output.push(c),
+            }
+        }
+        output
+    }
+
+    fn parse_argument(&mut self) -> String {
+        self.skip_whitespace();
+        match self.chars.get(self.ix).copied() {
+            Some('{') => {
+                self.ix += 1;
+                self.parse_until(Some('}'))
+            }
+            Some('\\') => {
+                self.ix += 1;
+                self.parse_command()
+            }
+            Some(c) => {
+                self.ix += 1;
+                c.to_string()
+            }
+            None => String::new(),
+        }
+    }
+
+    fn parse_command(&mut self) -> String {
+        let start = self.ix;
+        while self
+            .chars
+            .get(self.ix)
+            .is_some_and(|c| c.is_ascii_alphabetic())
+        {
+            self.ix += 1;
+        }
+        if self.ix == start {
+            // A single-character command like `\,`, `\{` or `\\`.
+            let Some(c) = self.chars.get(self.ix).copied() else {
+                return String::new();
+            };
+            self.ix += 1;
+            return match c {
+                ',' | ';' | ':' | '!' | ' ' => " ".into(),
+                '\\' => "\n".into(),
+                c => c.to_string(),
+            };
+        }
+
+        let name: String = self.chars[start..self.ix].iter().collect();
+        match name.as_str() {
+            "frac" | "dfrac" | "tfrac" => {
+                let numerator = self.parse_argument();
+                let denominator = self.parse_argument();
+                format!("{}/{}", group(&numerator), group(&denominator))
+            }
+            "sqrt" => {
+                self.skip_whitespace();
+                let root = if self.chars.get(self.ix) == Some(&'[') {
+                    self.ix += 1;
+                    let root = self.parse_until(Some(']'));
+                    match root.as_str() {
+                        "3" => "∛".to_string(),
+                        "4" => "∜".to_string(),
+                        root => format!("{}√", script(root, superscript, '^')),
+                    }
+                } else {
+                    "√".to_string()
+                };
+                let radicand = self.parse_argument();
+                format!("{root}{}", group(&radicand))
+            }
+            "text" | "textrm" | "textit" | "textbf" | "mathrm" | "mathit" | "mathbf" | "mathsf"
+            | "mathtt" | "operatorname" | "boldsymbol" | "mbox" => self.parse_argument(),
+            "mathbb" => self.parse_argument().chars().map(double_struck).collect(),
+            "mathcal" => self.parse_argument(),
+            "overline" | "bar" => combine(&self.parse_argument(), '\u{0305}'),
+            "hat" => combine(&self.parse_argument(), '\u{0302}'),
+            "vec" => combine(&self.parse_argument(), '\u{20D7}'),
+            "dot" => combine(&self.parse_argument(), '\u{0307}'),
+            "tilde" => combine(&self.parse_argument(), '\u{0303}'),
+            "left" | "right" | "big" | "Big" | "bigg" | "Bigg" | "displaystyle" | "limits" => {
+                String::new()
+            }
+            "begin" | "end" => {
+                self.parse_argument();
+                String::new()
+            }
+            // Unknown commands, including operators like `\sin`, read best as
+            // their name.
+            name => symbol(name)
+                .map(|symbol| symbol.to_string())
+                .unwrap_or_else(|| name.to_string()),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.get(self.ix).is_some_and(|c| c.is_whitespace()) {
+            self.ix += 1;
+        }
+    }
+}
+
+fn group(text: &str) -> String {
+    if text.chars().count() <= 1 || text.chars().all(|c| c.is_alphanumeric()) {
+        text.to_string()
+    } else {
+        format!("({text})")
+    }
+}
+
+fn combine(text: &str, mark: char) -> String {
+    let mut result = String::new();
+    for c in text.chars() {
+        result.push(c);
+        result.push(mark);
+    }
+    result
+}
+
+fn script(text: &str, map: fn(char) -> Option<char>, fallback: char) -> String {
+    let mapped: Option<String> = text.chars().map(map).collect();
+    mapped.unwrap_or_else(|| format!("{fallback}({text})"))
+}
+
+fn superscript(c: char) -> Option<char> {
+    Some(match c {
+        '0' => '⁰',
+        '1' => '¹',
+        '2' => '²',
+        '3' => '³',
+        '4' => '⁴',
+        '5' => '⁵',
+        '6' => '⁶',
+        '7' => '⁷',
+        '8' => '⁸',
+        '9' => '⁹',
+        '+' => '⁺',
+        '-' | '−' => '⁻',
+        '=' => '⁼',
+        '(' => '⁽',
+        ')' => '⁾',
+        'n' => 'ⁿ',
+        'i' => 'ⁱ',
+        'T' => 'ᵀ',
+        '′' => '′',
+        '∗' | '*' => '*',
+        _ => return None,
+    })
+}
+
+fn subscript(c: char) -> Option<char> {
+    Some(match c {
+        '0' => '₀',
+        '1' => '₁',
+        '2' => '₂',
+        '3' => '₃',
+        '4' => '₄',
+        '5' => '₅',
+        '6' => '₆',
+        '7' => '₇',
+        '8' => '₈',
+        '9' => '₉',
+        '+' => '₊',
+        '-' | '−' => '₋',
+        '=' => '₌',
+        '(' => '₍',
+        ')' => '₎',
+        'a' => 'ₐ',
+        'e' => 'ₑ',
+        'i' => 'ᵢ',
+        'j' => 'ⱼ',
+        'k' => 'ₖ',
+        'n' => 'ₙ',
+        'o' => 'ₒ',
+        'x' => 'ₓ',
+        _ => return None,
+    })
+}
+
+fn double_struck(c: char) -> char {
+    match c {
+        'C' => 'ℂ',
+        'N' => 'ℕ',
+        'P' => 'ℙ',
+        'Q' => 'ℚ',
+        'R' => 'ℝ',
+        'Z' => 'ℤ',
+        c => c,
+    }
+}
+
+fn symbol(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "alpha" => "α",
+        "beta" => "β",
+        "gamma" => "γ",
+        "delta" => "δ",
+        "epsilon" | "varepsilon" => "ε",
+        "zeta" => "ζ",
+        "eta" => "η",
+        "theta" | "vartheta" => "θ",
+        "iota" => "ι",
+        "kappa" => "κ",
+        "lambda" => "λ",
+        "mu" => "μ",
+        "nu" => "ν",
+        "xi" => "ξ",
+        "pi" => "π",
+        "rho" | "varrho" => "ρ",
+        "sigma" => "σ",
+        "tau" => "τ",
+        "upsilon" => "υ",
+        "phi" | "varphi" => "φ",
+        "chi" => "χ",
+        "psi" => "ψ",
+        "omega" => "ω",
+        "Gamma" => "Γ",
+        "Delta" => "Δ",
+        "Theta" => "Θ",
+        "Lambda" => "Λ",
+        "Xi" => "Ξ",
+        "Pi" => "Π",
+        "Sigma" => "Σ",
+        "Upsilon" => "Υ",
+        "Phi" => "Φ",
+        "Psi" => "Ψ",
+        "Omega" => "Ω",
+        "times" => "×",
+        "cdot" => "·",
+        "div" => "÷",
+        "pm" => "±",
+        "mp" => "∓",
+        "leq" | "le" => "≤",
+        "geq" | "ge" => "≥",
+        "neq" | "ne" => "≠",
+        "approx" => "≈",
+        "equiv" => "≡",
+        "sim" => "∼",
+        "propto" => "∝",
+        "infty" => "∞",
+        "sum" => "∑",
+        "prod" => "∏",
+        "int" => "∫",
+        "oint" => "∮",
+        "partial" => "∂",
+        "nabla" => "∇",
+        "in" => "∈",
+        "notin" => "∉",
+        "subset" => "⊂",
+        "subseteq" => "⊆",
+        "supset" => "⊃",
+        "supseteq" => "⊇",
+        "cup" => "∪",
+        "cap" => "∩",
+        "emptyset" | "varnothing" => "∅",
+        "forall" => "∀",
+        "exists" => "∃",
+        "neg" | "lnot" => "¬",
+        "land" | "wedge" => "∧",
+        "lor" | "vee" => "∨",
+        "to" | "rightarrow" => "→",
+        "leftarrow" | "gets" => "←",
+        "leftrightarrow" => "↔",
+        "Rightarrow" | "implies" => "⇒",
+        "Leftarrow" => "⇐",
+        "Leftrightarrow" | "iff" => "⇔",
+        "mapsto" => "↦",
+        "ldots" | "dots" => "…",
+        "cdots" => "⋯",
+        "circ" => "∘",
+        "degree" => "°",
+        "angle" => "∠",
+        "perp" => "⊥",
+        "parallel" => "∥",
+        "langle" => "⟨",
+        "rangle" => "⟩",
+        "lfloor" => "⌊",
+        "rfloor" => "⌋",
+        "lceil" => "⌈",
+        "rceil" => "⌉",
+        "quad" | "qquad" => "  ",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_tex_to_unicode() {
+        assert_eq!(tex_to_unicode(r"x^2 + y_i"), "x² + yᵢ");
+        assert_eq!(tex_to_unicode(r"\frac{a+b}{2}"), "(a+b)/2");
+        assert_eq!(tex_to_unicode(r"\sum_{i=1}^{n} x_i"), "∑ᵢ₌₁ⁿ xᵢ");
+        assert_eq!(tex_to_unicode(r"\alpha \leq \sqrt{\beta}"), "α ≤ √β");
+        assert_eq!(tex_to_unicode(r"e^{i\pi} = -1"), "e^(iπ) = -1");
+        assert_eq!(tex_to_unicode(r"\mathbb{R}^n \to \text{loss}"), "ℝⁿ → loss");
+        assert_eq!(tex_to_unicode(r"\sin x"), "sin x");
+    }
+
+    #[test]
+    fn test_replace_math() {
+        let text = "Cost is $5 and $10, but $E = mc^2$ holds.\n\n$$\\int_0^1 f$$\n";
+        assert_eq!(
+            replace_math(text, &[]),
+            "Cost is $5 and $10, but *E = mc²* holds.\n\n\n\n*∫₀¹ f*\n\n\n"
+        );
+    }
+
+    #[test]
+    fn test_math_in_code_is_kept() {
+        let text = "`$x$` and $y$";
+        assert_eq!(replace_math(text, &[0..5]), "`$x$` and *y*");
+    }
+}
@@ -0,0 +1,1527 @@
+mod fonts;
+
+use anyhow::{Context as _, Result};
+use collections::HashMap;
+use flate2::{Compression, write::ZlibEncoder};
+use gpui::Rgba;
+use std::{
+    fmt::Write as _,
+    io::Write as _,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    ExportAssets, ExportOptions,
+    document::{Alignment, Block, Document, ListMarker, Span, Table, TocEntry, plain_text},
+    template::{PageTemplate, TemplatePart, render_parts},
+};
+use fonts::PdfFont;
+
+const BODY_SIZE: f32 = 11.;
+const CODE_SIZE: f32 = 9.5;
+const HEADER_SIZE: f32 = 8.5;
+const LINE_HEIGHT: f32 = 1.4;
+const BLOCK_SPACING: f32 = 8.;
+const LIST_INDENT: f32 = 18.;
+const QUOTE_INDENT: f32 = 14.;
+const CODE_PADDING: f32 = 6.;
+const CELL_PADDING: f32 = 4.;
+/// Images are downscaled to this many pixels on their longest side.
+const MAX_IMAGE_PIXELS: u32 = 1600;
+
+/// Heading sizes relative to the body text, matching the markdown preview.
+const HEADING_SCALES: [f32; 6] = [2.0, 1.5, 1.25, 1.0, 0.875, 0.85];
+
+const TEXT_COLOR: Rgba = Rgba {
+    r: 0.1,
+    g: 0.1,
+    b: 0.1,
+    a: 1.,
+};
+const MUTED_COLOR: Rgba = Rgba {
+    r: 0.45,
+    g: 0.45,
+    b: 0.45,
+    a: 1.,
+};
+const LINK_COLOR: Rgba = Rgba {
+    r: 0.05,
+    g: 0.35,
+    b: 0.75,
+    a: 1.,
+};
+const BORDER_COLOR: Rgba = Rgba {
+    r: 0.8,
+    g: 0.8,
+    b: 0.8,
+    a: 1.,
+};
+const CODE_BACKGROUND: Rgba = Rgba {
+    r: 0.95,
+    g: 0.95,
+    b: 0.96,
+    a: 1.,
+};
+
+/// Lays out `document` on pages and serializes it as a PDF file.
+pub fn write_pdf(
+    document: &Document,
+    options: &ExportOptions,
+    assets: &ExportAssets,
+) -> Result<Vec<u8>> {
+    let toc_entries = if options.table_of_contents {
+        document.toc_entries(options.toc_depth)
+    } else {
+        Vec::new()
+    };
+
+    let mut layout = Layout::new(options, assets);
+
+    // The table of contents needs the body's page numbers, and the body's page
+    // numbers depend on how long the table of contents is. Its length doesn't
+    // depend on the numbers themselves, so lay it out once to count its pages.
+    let mut toc_page_count = 0;
+    if !toc_entries.is_empty() {
+        layout.table_of_contents(&toc_entries, &HashMap::default(), 0);
+        toc_page_count = layout.pages.len();
+        layout.reset();
+    }
+
+    layout.new_page();
+    for block in &document.blocks {
+        layout.block(block);
+    }
+    let body_pages = std::mem::take(&mut layout.pages);
+    let anchors = std::mem::take(&mut layout.anchors);
+    let outline = std::mem::take(&mut layout.outline);
+    let images = std::mem::take(&mut layout.images);
+
+    let mut pages = Vec::new();
+    if !toc_entries.is_empty() {
+        layout.reset();
+        layout.images = images;
+        layout.table_of_contents(&toc_entries, &anchors, toc_page_count);
+        pages.append(&mut layout.pages);
+        debug_assert_eq!(pages.len(), toc_page_count);
+    } else {
+        layout.images = images;
+    }
+    pages.extend(body_pages);
+
+    let anchors = anchors
+        .into_iter()
+        .map(|(anchor, destination)| {
+            let destination = Destination {
+                page: destination.page + toc_page_count,
+                ..destination
+            };
+            (anchor, destination)
+        })
+        .collect::<HashMap<_, _>>();
+
+    let header = options
+        .header
+        .as_deref()
+        .map(|template| PageTemplate::parse(template, &options.variables));
+    let footer = options
+        .footer
+        .as_deref()
+        .map(|template| PageTemplate::parse(template, &options.variables));
+    let page_count = pages.len();
+    for (ix, page) in pages.iter_mut().enumerate() {
+        if let Some(header) = &header {
+            let y = options.margins.top / 2.;
+            page.draw_page_template(header, ix + 1, page_count, y, options);
+        }
+        if let Some(footer) = &footer {
+            let y = options.page_height - options.margins.bottom / 2. + HEADER_SIZE / 2.;
+            page.draw_page_template(footer, ix + 1, page_count, y, options);
+        }
+    }
+
+    serialize(
+        document,
+        options,
+        &pages,
+        &layout.images,
+        &anchors,
+        &outline,
+    )
+}
+
+#[derive(Clone, Debug)]
+struct Destination {
+    page: usize,
+    /// Distance from the top of the page.
+    y: f32,
+}
+
+#[derive(Clone, Debug)]
+struct OutlineEntry {
+    level: u8,
+    title: String,
+    anchor: String,
+}
+
+enum LinkTarget {
+    Uri(String),
+    Anchor(String),
+}
+
+struct PageLink {
+    /// `[left, top, right, bottom]`, measured from the top left of the page.
+    rect: [f32; 4],
+    target: LinkTarget,
+}
+
+#[derive(Default)]
+struct Page {
+    content: String,
+    links: Vec<PageLink>,
+    images: Vec<usize>,
+}
+
+struct PdfImage {
+    width: u32,
+    height: u32,
+    data: Vec<u8>,
+}
+
+/// A measured run of text on a single line.
+#[derive(Clone, Debug)]
+struct Fragment {
+    text: String,
+    style: TextStyle,
+    width: f32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct TextStyle {
+    font: PdfFont,
+    size: f32,
+    color: Rgba,
+    underline: bool,
+    strikethrough: bool,
+    code: bool,
+    link: Option<String>,
+}
+
+impl TextStyle {
+    fn for_span(span: &Span, size: f32, bold: bool, color: Rgba) -> Self {
+        let size = if span.code && size >= BODY_SIZE {
+            size * CODE_SIZE / BODY_SIZE
+        } else {
+            size
+        };
+        Self {
+            font: PdfFont::for_style(span.bold || bold, span.italic, span.code),
+            size,
+            color: if span.link.is_some() {
+                LINK_COLOR
+            } else {
+                span.color.unwrap_or(color)
+            },
+            underline: span.underline || span.link.is_some(),
+            strikethrough: span.strikethrough,
+            code: span.code,
+            link: span.link.clone(),
+        }
+    }
+}
+
+#[derive(Default, Debug)]
+struct Line {
+    fragments: Vec<Fragment>,
+    width: f32,
+}
+
+fn measure(text: &str, font: PdfFont, size: f32) -> f32 {
+    let mut encoded = Vec::new();
+    for c in text.chars() {
+        font.encode(c, &mut encoded);
+    }
+    encoded
+        .iter()
+        .map(|(font, byte)| font.width(*byte))
+        .sum::<f32>()
+        * size
+        / 1000.
+}
+
+/// Breaks styled text into lines no wider than `width`.
+fn wrap(runs: &[(String, TextStyle)], width: f32) -> Vec<Line> {
+    let mut lines = vec![Line::default()];
+    let mut pending_space: Option<Fragment> = None;
+
+    for (text, style) in runs {
+        for token in tokenize(text) {
+            if token == "\n" {
+                lines.push(Line::default());
+                pending_space = None;
+                continue;
+            }
+            if token.chars().all(char::is_whitespace) {
+                let token_width = measure(token, style.font, style.size);
+                pending_space = Some(Fragment {
+                    text: token.to_string(),
+                    style: style.clone(),
+                    width: token_width,
+                });
+                continue;
+            }
+
+            let mut word = token.to_string();
+            loop {
+                let word_width = measure(&word, style.font, style.size);
+                let line = lines.last_mut().unwrap();
+                let space_width = pending_space.as_ref().map_or(0., |space| space.width);
+                if line.fragments.is_empty() || line.width + space_width + word_width <= width {
+                    if !line.fragments.is_empty() {
+                        if let Some(space) = pending_space.take() {
+                            line.width += space.width;
+                            push_fragment(line, space);
+                        }
+                    }
+                    pending_space = None;
+
+                    if word_width <= width || !line.fragments.is_empty() {
+                        line.width += word_width;
+                        push_fragment(
+                            line,
+                            Fragment {
+                                text: word,
+                                style: style.clone(),
+                                width: word_width,
+                            },
+                        );
+                        break;
+                    }
+
+                    // The word is wider than a whole line, so break it at the
+                    // last character that fits.
+                    let mut split = 0;
+                    let mut split_width = 0.;
+                    for (ix, c) in word.char_indices() {
+                        let char_width =
+                            measure(&word[ix..ix + c.len_utf8()], style.font, style.size);
+                        if split > 0 && split_width + char_width > width {
+                            break;
+                        }
+                        split = ix + c.len_utf8();
+                        split_width += char_width;
+                    }
+                    let rest = word.split_off(split);
+                    line.width += split_width;
+                    push_fragment(
+                        line,
+                        Fragment {
+                            text: word,
+                            style: style.clone(),
+                            width: split_width,
+                        },
+                    );
+                    lines.push(Line::default());
+                    if rest.is_empty() {
+                        break;
+                    }
+                    word = rest;
+                } else {
+                    pending_space = None;
+                    lines.push(Line::default());
+                }
+            }
+        }
+    }
+    lines
+}
+
+fn push_fragment(line: &mut Line, fragment: Fragment) {
+    if let Some(last) = line.fragments.last_mut() {
+        if last.style == fragment.style {
+            last.text.push_str(&fragment.text);
+            last.width += fragment.width;
+            return;
+        }
+    }
+    line.fragments.push(fragment);
+}
+
+/// Splits text into words, runs of spaces and newlines.
+fn tokenize(text: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut previous: Option<u8> = None;
+    for (ix, c) in text.char_indices() {
+        let class = match c {
+            '\n' => 0,
+            c if c.is_whitespace() => 1,
+            _ => 2,
+        };
+        if previous.is_some_and(|previous| previous != class || class == 0) {
+            tokens.push(&text[start..ix]);
+            start = ix;
+        }
+        previous = Some(class);
+    }
+    if start < text.len() {
+        tokens.push(&text[start..]);
+    }
+    tokens
+}
+
+struct Layout<'a> {
+    options: &'a ExportOptions,
+    assets: &'a ExportAssets,
+    pages: Vec<Page>,
+    /// Distance from the top of the current page to where the next block starts.
+    y: f32,
+    /// Horizontal offset from the left margin, used by lists and quotes.
+    indent: f32,
+    anchors: HashMap<String, Destination>,
+    outline: Vec<OutlineEntry>,
+    images: Vec<PdfImage>,
+    image_indices: HashMap<PathBuf, Option<usize>>,
+}
+
+impl<'a> Layout<'a> {
+    fn new(options: &'a ExportOptions, assets: &'a ExportAssets) -> Self {
+        Self {
+            options,
+            assets,
+            pages: Vec::new(),
+            y: options.margins.top,
+            indent: 0.,
+            anchors: HashMap::default(),
+            outline: Vec::new(),
+            images: Vec::new(),
+            image_indices: HashMap::default(),
+        }
+    }
+
+    fn reset(&mut self) {
+        self.pages.clear();
+        self.y = self.options.margins.top;
+        self.indent = 0.;
+    }
+
+    fn left(&self) -> f32 {
+        self.options.margins.left + self.indent
+    }
+
+    fn content_width(&self) -> f32 {
+        self.options.page_width - self.options.margins.right - self.left()
+    }
+
+    fn bottom(&self) -> f32 {
+        self.options.page_height - self.options.margins.bottom
+    }
+
+    fn page(&mut self) -> &mut Page {
+        if self.pages.is_empty() {
+            self.pages.push(Page::default());
+        }
+        self.pages.last_mut().unwrap()
+    }
+
+    fn new_page(&mut self) {
+        self.pages.push(Page::default());
+        self.y = self.options.margins.top;
+    }
+
+    fn at_page_top(&self) -> bool {
+        self.y <= self.options.margins.top + 0.5
+    }
+
+    /// Starts a new page unless `height` still fits on the current one.
+    fn ensure_space(&mut self, height: f32) {
+        if self.pages.is_empty() {
+            self.new_page();
+        } else if self.y + height > self.bottom() && !self.at_page_top() {
+            self.new_page();
+        }
+    }
+
+    fn space(&mut self, amount: f32) {
+        if !self.at_page_top() {
+            self.y += amount;
+        }
+    }
+
+    fn block(&mut self, block: &Block) {
+        match block {
+            Block::Heading {
+                level,
+                spans,
+                anchor,
+            } => self.heading(*level, spans, anchor),
+            Block::Paragraph(spans) => {
+                self.paragraph(spans, BODY_SIZE, false, TEXT_COLOR);
+                self.y += BLOCK_SPACING;
+            }
+            Block::ListItem {
+                depth,
+                marker,
+                content,
+            } => self.list_item(*depth, marker, content),
+            Block::CodeBlock { lines, .. } => {
+                self.code_block(lines);
+                self.y += BLOCK_SPACING;
+            }
+            Block::Table(table) => {
+                self.table(table);
+                self.y += BLOCK_SPACING;
+            }
+            Block::BlockQuote(children) => {
+                self.block_quote(children);
+                self.y += BLOCK_SPACING;
+            }
+            Block::Image { path, alt } => {
+                self.image(path, alt);
+                self.y += BLOCK_SPACING;
+            }
+            Block::Rule => {
+                self.ensure_space(BLOCK_SPACING * 2.);
+                self.y += BLOCK_SPACING;
+                let (left, right, y) = (self.left(), self.left() + self.content_width(), self.y);
+                self.stroke_line(left, y, right, y, 1., BORDER_COLOR);
+                self.y += BLOCK_SPACING;
+            }
+        }
+    }
+
+    fn heading(&mut self, level: u8, spans: &[Span], anchor: &str) {
+        let scale = HEADING_SCALES[(level.clamp(1, 6) - 1) as usize];
+        let size = BODY_SIZE * scale;
+        self.space(size * 0.6);
+        // Keep the heading together with at least one line of the section.
+        self.ensure_space(size * LINE_HEIGHT + BODY_SIZE * LINE_HEIGHT * 2.);
+        self.anchors.insert(
+            anchor.to_string(),
+            Destination {
+                page: self.pages.len() - 1,
+                y: self.y,
+            },
+        );
+        self.outline.push(OutlineEntry {
+            level,
+            title: plain_text(spans),
+            anchor: anchor.to_string(),
+        });
+        let color = if level == 6 { MUTED_COLOR } else { TEXT_COLOR };
+        self.paragraph(spans, size, true, color);
+        self.y += BLOCK_SPACING / 2.;
+    }
+
+    fn paragraph(&mut self, spans: &[Span], size: f32, bold: bool, color: Rgba) {
+        let runs = spans
+            .iter()
+            .map(|span| {
+                (
+                    span.text.clone(),
+                    TextStyle::for_span(span, size, bold, color),
+                )
+            })
+            .collect::<Vec<_>>();
+        let lines = wrap(&runs, self.content_width());
+        self.draw_lines(
+            &lines,
+            size,
+            Alignment::Left,
+            self.left(),
+            self.content_width(),
+        );
+    }
+
+    fn draw_lines(&mut self, lines: &[Line], size: f32, alignment: Alignment, x: f32, width: f32) {
+        let line_height = size * LINE_HEIGHT;
+        for line in lines {
+            self.ensure_space(line_height);
+            let offset = match alignment {
+                Alignment::Left => 0.,
+                Alignment::Center => (width - line.width).max(0.) / 2.,
+                Alignment::Right => (width - line.width).max(0.),
+            };
+            let baseline = self.y + (line_height - size) / 2. + size * 0.8;
+            self.draw_line(line, x + offset, baseline);
+            self.y += line_height;
+        }
+    }
+
+    fn draw_line(&mut self, line: &Line, x: f32, baseline: f32) {
+        let mut x = x;
+        for fragment in &line.fragments {
+            let style = &fragment.style;
+            if style.code && !fragment.text.trim().is_empty() {
+                self.fill_rect(
+                    x - 1.,
+                    baseline - style.size * 0.85,
+                    fragment.width + 2.,
+                    style.size * 1.15,
+                    CODE_BACKGROUND,
+                );
+            }
+            self.draw_text(&fragment.text, style, x, baseline);
+            if style.underline {
+                let y = baseline + style.size * 0.12;
+                self.stroke_line(x, y, x + fragment.width, y, 0.5, style.color);
+            }
+            if style.strikethrough {
+                let y = baseline - style.size * 0.3;
+                self.stroke_line(x, y, x + fragment.width, y, 0.5, style.color);
+            }
+            if let Some(link) = &style.link {
+                let target = if link.starts_with('#') {
+                    LinkTarget::Anchor(link.trim_start_matches('#').to_string())
+                } else {
+                    LinkTarget::Uri(link.clone())
+                };
+                self.page().links.push(PageLink {
+                    rect: [
+                        x,
+                        baseline - style.size,
+                        x + fragment.width,
+                        baseline + style.size * 0.3,
+                    ],
+                    target,
+                });
+            }
+            x += fragment.width;
+        }
+    }
+
+    fn draw_text(&mut self, text: &str, style: &TextStyle, x: f32, baseline: f32) {
+        let mut encoded = Vec::new();
+        for c in text.chars() {
+            style.font.encode(c, &mut encoded);
+        }
+        let page_height = self.options.page_height;
+        let content = &mut self.page().content;
+        let _ = writeln!(content, "{} rg", color_operands(style.color));
+        let mut x = x;
+        let mut ix = 0;
+        while ix < encoded.len() {
+            let font = encoded[ix].0;
+            let run_end = encoded[ix..]
+                .iter()
+                .position(|(run_font, _)| *run_font != font)
+                .map_or(encoded.len(), |len| ix + len);
+            let bytes = encoded[ix..run_end]
+                .iter()
+                .map(|(_, byte)| *byte)
+                .collect::<Vec<_>>();
+            let _ = writeln!(
+                content,
+                "BT /{} {} Tf {} {} Td {} Tj ET",
+                font.resource_name(),
+                number(style.size),
+                number(x),
+                number(page_height - baseline),
+                pdf_string(&bytes),
+            );
+            x += bytes.iter().map(|byte| font.width(*byte)).sum::<f32>() * style.size / 1000.;
+            ix = run_end;
+        }
+    }
+
+    fn fill_rect(&mut self, x: f32, top: f32, width: f32, height: f32, color: Rgba) {
+        let page_height = self.options.page_height;
+        let _ = writeln!(
+            self.page().content,
+            "{} rg {} {} {} {} re f",
+            color_operands(color),
+            number(x),
+            number(page_height - top - height),
+            number(width),
+            number(height),
+        );
+    }
+
+    fn stroke_rect(&mut self, x: f32, top: f32, width: f32, height: f32, color: Rgba) {
+        let page_height = self.options.page_height;
+        let _ = writeln!(
+            self.page().content,
+            "{} RG 0.5 w {} {} {} {} re S",
+            color_operands(color),
+            number(x),
+            number(page_height - top - height),
+            number(width),
+            number(height),
+        );
+    }
+
+    fn stroke_line(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, width: f32, color: Rgba) {
+        let page_height = self.options.page_height;
+        let _ = writeln!(
+            self.page().content,
+            "{} RG {} w {} {} m {} {} l S",
+            color_operands(color),
+            number(width),
+            number(x1),
+            number(page_height - y1),
+            number(x2),
+            number(page_height - y2),
+        );
+    }
+
+    fn list_item(&mut self, depth: u16, marker: &ListMarker, content: &[Block]) {
+        let indent = LIST_INDENT * depth.saturating_sub(1) as f32;
+        let marker_text = marker.text();
+        let marker_style = TextStyle::for_span(&Span::plain(""), BODY_SIZE, false, TEXT_COLOR);
+        let marker_width =
+            measure(&marker_text, marker_style.font, BODY_SIZE).max(LIST_INDENT - 6.);
+
+        let line_height = BODY_SIZE * LINE_HEIGHT;
+        self.ensure_space(line_height);
+        let baseline = self.y + (line_height - BODY_SIZE) / 2. + BODY_SIZE * 0.8;
+        let x = self.left() + indent;
+        self.draw_text(&marker_text, &marker_style, x, baseline);
+
+        let previous_indent = self.indent;
+        self.indent += indent + marker_width + 6.;
+        for (ix, block) in content.iter().enumerate() {
+            match block {
+                Block::Paragraph(spans) => {
+                    self.paragraph(spans, BODY_SIZE, false, TEXT_COLOR);
+                    if ix + 1 < content.len() {
+                        self.y += BLOCK_SPACING / 2.;
+                    }
+                }
+                block => self.block(block),
+            }
+        }
+        if content.is_empty() {
+            self.y += line_height;
+        }
+        self.indent = previous_indent;
+        self.y += BLOCK_SPACING / 4.;
+    }
+
+    fn code_block(&mut self, lines: &[Vec<Span>]) {
+        let line_height = CODE_SIZE * LINE_HEIGHT;
+        let x = self.left();
+        let width = self.content_width();
+        self.ensure_space(line_height + CODE_PADDING * 2.);
+        self.fill_rect(x, self.y, width, CODE_PADDING, CODE_BACKGROUND);
+        self.y += CODE_PADDING;
+
+        for spans in lines {
+            let runs = spans
+                .iter()
+                .map(|span| {
+                    let mut style = TextStyle::for_span(span, CODE_SIZE, false, TEXT_COLOR);
+                    style.code = false;
+                    (span.text.replace('\t', "    "), style)
+                })
+                .collect::<Vec<_>>();
+            let wrapped = if runs.is_empty() {
+                vec![Line::default()]
+            } else {
+                wrap(&runs, width - CODE_PADDING * 2.)
+            };
+            for line in wrapped {
+                if self.y + line_height > self.bottom() {
+                    self.new_page();
+                }
+                self.fill_rect(x, self.y, width, line_height, CODE_BACKGROUND);
+                let baseline = self.y + (line_height - CODE_SIZE) / 2. + CODE_SIZE * 0.8;
+                self.draw_line(&line, x + CODE_PADDING, baseline);
+                self.y += line_height;
+            }
+        }
+
+        if self.y + CODE_PADDING > self.bottom() {
+            self.new_page();
+        }
+        self.fill_rect(x, self.y, width, CODE_PADDING, CODE_BACKGROUND);
+        self.y += CODE_PADDING;
+    }
+
+    fn table(&mut self, table: &Table) {
+        let column_count = table.column_count();
+        if column_count == 0 {
+            return;
+        }
+        let width = self.content_width();
+        let style_for =
+            |span: &Span, bold: bool| TextStyle::for_span(span, BODY_SIZE, bold, TEXT_COLOR);
+
+        // Size columns by their natural (unwrapped) widths, like the preview
+        // sizes them by text length, but never narrower than their longest word.
+        let mut natural = vec![0f32; column_count];
+        let mut minimum = vec![20f32; column_count];
+        for (row_ix, row) in std::iter::once(&table.header)
+            .chain(&table.rows)
+            .enumerate()
+        {
+            for (column, cell) in row.iter().enumerate() {
+                let bold = row_ix == 0;
+                let cell_width: f32 = cell
+                    .iter()
+                    .map(|span| {
+                        let style = style_for(span, bold);
+                        measure(&span.text, style.font, style.size)
+                    })
+                    .sum();
+                natural[column] = natural[column].max(cell_width + CELL_PADDING * 2.);
+                for span in cell {
+                    let style = style_for(span, bold);
+                    for word in span.text.split_whitespace() {
+                        let word_width = measure(word, style.font, style.size);
+                        minimum[column] = minimum[column].max(word_width + CELL_PADDING * 2.);
+                    }
+                }
+            }
+        }
+        let natural_total: f32 = natural.iter().sum();
+        let widths = if natural_total <= width {
+            natural
+        } else {
+            let minimum_total: f32 = minimum
+                .iter()
+                .map(|w| w.min(width / column_count as f32))
+                .sum();
+            let flexible = (width - minimum_total).max(0.);
+            let excess_total: f32 = natural
+                .iter()
+                .zip(&minimum)
+                .map(|(natural, minimum)| {
+                    (natural - minimum.min(width / column_count as f32)).max(0.)
+                })
+                .sum();
+            natural
+                .iter()
+                .zip(&minimum)
+                .map(|(natural, minimum)| {
+                    let minimum = minimum.min(width / column_count as f32);
+                    let excess = (natural - minimum).max(0.);
+                    minimum
+                        + if excess_total > 0. {
+                            flexible * excess / excess_total
+                        } else {
+                            0.
+                        }
+                })
+                .collect()
+        };
+
+        let layout_row = |row: &Vec<Vec<Span>>, bold: bool| -> Vec<Vec<Line>> {
+            (0..column_count)
+                .map(|column| {
+                    let runs = row
+                        .get(column)
+                        .into_iter()
+                        .flatten()
+                        .map(|span| (span.text.clone(), style_for(span, bold)))
+                        .collect::<Vec<_>>();
+                    wrap(&runs, widths[column] - CELL_PADDING * 2.)
+                })
+                .collect()
+        };
+        let row_height = |cells: &[Vec<Line>]| {
+            cells.iter().map(Vec::len).max().unwrap_or(1) as f32 * BODY_SIZE * LINE_HEIGHT
+                + CELL_PADDING * 2.
+        };
+
+        let header = layout_row(&table.header, true);
+        let header_height = row_height(&header);
+        self.ensure_space(header_height * 2.);
+        self.table_row(&header, &widths, &table.alignments, header_height, true);
+        for row in &table.rows {
+            let cells = layout_row(row, false);
+            let height = row_height(&cells);
+            if self.y + height > self.bottom() && !self.at_page_top() {
+                self.new_page();
+                self.table_row(&header, &widths, &table.alignments, header_height, true);
+            }
+            self.table_row(&cells, &widths, &table.alignments, height, false);
+        }
+    }
+
+    fn table_row(
+        &mut self,
+        cells: &[Vec<Line>],
+        widths: &[f32],
+        alignments: &[Alignment],
+        height: f32,
+        is_header: bool,
+    ) {
+        let mut x = self.left();
+        let top = self.y;
+        for (column, lines) in cells.iter().enumerate() {
+            let width = widths[column];
+            if is_header {
+                self.fill_rect(x, top, width, height, CODE_BACKGROUND);
+            }
+            self.stroke_rect(x, top, width, height, BORDER_COLOR);
+            let alignment = alignments.get(column).copied().unwrap_or(Alignment::Left);
+            let line_height = BODY_SIZE * LINE_HEIGHT;
+            for (line_ix, line) in lines.iter().enumerate() {
+                let inner = width - CELL_PADDING * 2.;
+                let offset = match alignment {
+                    Alignment::Left => 0.,
+                    Alignment::Center => (inner - line.width).max(0.) / 2.,
+                    Alignment::Right => (inner - line.width).max(0.),
+                };
+                let line_top = top + CELL_PADDING + line_ix as f32 * line_height;
+                let baseline = line_top + (line_height - BODY_SIZE) / 2. + BODY_SIZE * 0.8;
+                self.draw_line(line, x + CELL_PADDING + offset, baseline);
+            }
+            x += width;
+        }
+        self.y += height;
+    }
+
+    fn block_quote(&mut self, children: &[Block]) {
+        self.ensure_space(BODY_SIZE * LINE_HEIGHT);
+        let start_page = self.pages.len() - 1;
+        let start_y = self.y;
+        let bar_x = self.left() + 2.;
+
+        self.indent += QUOTE_INDENT;
+        for (ix, child) in children.iter().enumerate() {
+            self.block(child);
+            if ix + 1 == children.len() {
+                // Don't extend the bar over the spacing after the last child.
+                self.y -= BLOCK_SPACING;
+            }
+        }
+        self.indent -= QUOTE_INDENT;
+
+        // Draw the bar on every page the quote spans.
+        let end_page = self.pages.len() - 1;
+        let end_y = self.y;
+        let top = self.options.margins.top;
+        let bottom = self.bottom();
+        let color = BORDER_COLOR;
+        for page_ix in start_page..=end_page {
+            let from = if page_ix == start_page { start_y } else { top };
+            let to = if page_ix == end_page { end_y } else { bottom };
+            let page_height = self.options.page_height;
+            let _ = writeln!(
+                self.pages[page_ix].content,
+                "{} RG 3 w {} {} m {} {} l S",
+                color_operands(color),
+                number(bar_x),
+                number(page_height - from),
+                number(bar_x),
+                number(page_height - to),
+            );
+        }
+    }
+
+    fn image(&mut self, path: &Path, alt: &str) {
+        let Some(index) = self.load_image(path) else {
+            let text = if alt.is_empty() {
+                path.file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default()
+            } else {
+                alt.to_string()
+            };
+            let span = Span {
+                text: format!("[{text}]"),
+                italic: true,
+                ..Default::default()
+            };
+            self.paragraph(&[span], BODY_SIZE, false, MUTED_COLOR);
+            return;
+        };
+
+        let image = &self.images[index];
+        let max_width = self.content_width();
+        let max_height = (self.bottom() - self.options.margins.top) * 0.8;
+        // Treat pixels as CSS pixels (96 per inch) so images appear at the
+        // size the preview shows them.
+        let natural_width = image.width as f32 * 0.75;
+        let natural_height = image.height as f32 * 0.75;
+        let scale = (max_width / natural_width)
+            .min(max_height / natural_height)
+            .min(1.);
+        let (width, height) = (natural_width * scale, natural_height * scale);
+
+        self.ensure_space(height);
+        let x = self.left();
+        let page_height = self.options.page_height;
+        let y = self.y;
+        let page = self.page();
+        if !page.images.contains(&index) {
+            page.images.push(index);
+        }
+        let _ = writeln!(
+            page.content,
+            "q {} 0 0 {} {} {} cm /Im{} Do Q",
+            number(width),
+            number(height),
+            number(x),
+            number(page_height - y - height),
+            index,
+        );
+        self.y += height;
+    }
+
+    fn load_image(&mut self, path: &Path) -> Option<usize> {
+        if let Some(index) = self.image_indices.get(path) {
+            return *index;
+        }
+        let index = self
+            .assets
+            .images
+            .get(path)
+            .and_then(|bytes| match decode_image(bytes) {
+                Ok(image) => {
+                    self.images.push(image);
+                    Some(self.images.len() - 1)
+                }
+                Err(error) => {
+                    log::warn!("failed to decode image {path:?} for export: {error:#}");
+                    None
+                }
+            });
+        self.image_indices.insert(path.to_path_buf(), index);
+        index
+    }
+
+    fn table_of_contents(
+        &mut self,
+        entries: &[TocEntry],
+        anchors: &HashMap<String, Destination>,
+        page_offset: usize,
+    ) {
+        self.new_page();
+        let title = Span {
+            text: "Contents".into(),
+            ..Default::default()
+        };
+        self.paragraph(&[title], BODY_SIZE * HEADING_SCALES[0], true, TEXT_COLOR);
+        self.y += BLOCK_SPACING;
+
+        let number_width = measure("0000", PdfFont::Helvetica, BODY_SIZE);
+        let line_height = BODY_SIZE * LINE_HEIGHT;
+        for entry in entries {
+            let indent = (entry.level.saturating_sub(1)) as f32 * 12.;
+            let x = self.left() + indent;
+            let width = self.content_width() - indent - number_width - 8.;
+            let bold = entry.level == 1;
+            let style = TextStyle::for_span(&Span::plain(""), BODY_SIZE, bold, TEXT_COLOR);
+            let lines = wrap(&[(entry.text.clone(), style.clone())], width);
+            let page_number = anchors
+                .get(&entry.anchor)
+                .map(|destination| (destination.page + page_offset + 1).to_string())
+                .unwrap_or_default();
+
+            self.ensure_space(line_height * lines.len() as f32);
+            let top = self.y;
+            for (ix, line) in lines.iter().enumerate() {
+                let baseline = self.y + (line_height - BODY_SIZE) / 2. + BODY_SIZE * 0.8;
+                self.draw_line(line, x, baseline);
+                if ix + 1 == lines.len() {
+                    let right = self.options.page_width - self.options.margins.right;
+                    let page_number_width = measure(&page_number, PdfFont::Helvetica, BODY_SIZE);
+                    let dots_start = x + line.width + 4.;
+                    let dots_end = right - page_number_width - 4.;
+                    let leader_width = measure(".\u{A0}", PdfFont::Helvetica, BODY_SIZE);
+                    if dots_end > dots_start {
+                        let count = ((dots_end - dots_start) / leader_width) as usize;
+                        let dots = vec!["."; count].join("\u{A0}");
+                        let dots_style = TextStyle {
+                            font: PdfFont::Helvetica,
+                            color: MUTED_COLOR,
+                            ..style.clone()
+                        };
+                        let dots_x = dots_end - measure(&dots, PdfFont::Helvetica, BODY_SIZE);
+                        self.draw_text(&dots, &dots_style, dots_x, baseline);
+                    }
+                    let number_style = TextStyle {
+                        font: PdfFont::Helvetica,
+                        ..style.clone()
+                    };
+                    self.draw_text(
+                        &page_number,
+                        &number_style,
+                        right - page_number_width,
+                        baseline,
+                    );
+                }
+                self.y += line_height;
+            }
+            let right = self.options.page_width - self.options.margins.right;
+            let bottom = self.y;
+            self.page().links.push(PageLink {
+                rect: [x, top, right, bottom],
+                target: LinkTarget::Anchor(entry.anchor.clone()),
+            });
+        }
+    }
+}
+
+impl Page {
+    fn draw_page_template(
+        &mut self,
+        template: &PageTemplate,
+        page: usize,
+        page_count: usize,
+        baseline: f32,
+        options: &ExportOptions,
+    ) {
+        let style = TextStyle {
+            font: PdfFont::Helvetica,
+            size: HEADER_SIZE,
+            color: MUTED_COLOR,
+            underline: false,
+            strikethrough: false,
+            code: false,
+            link: None,
+        };
+        let left = options.margins.left;
+        let right = options.page_width - options.margins.right;
+        let columns: [(&[TemplatePart], Alignment); 3] = [
+            (&template.left, Alignment::Left),
+            (&template.center, Alignment::Center),
+            (&template.right, Alignment::Right),
+        ];
+        for (parts, alignment) in columns {
+            let text = render_parts(parts, page, page_count);
+            if text.is_empty() {
+                continue;
+            }
+            let width = measure(&text, style.font, style.size);
+            let x = match alignment {
+                Alignment::Left => left,
+                Alignment::Center => (left + right - width) / 2.,
+                Alignment::Right => right - width,
+            };
+            let mut encoded = Vec::new();
+            for c in text.chars() {
+                style.font.encode(c, &mut encoded);
+            }
+            let _ = writeln!(self.content, "{} rg", color_operands(style.color));
+            let mut x = x;
+            for (font, byte) in encoded {
+                let _ = writeln!(
+                    self.content,
+                    "BT /{} {} Tf {} {} Td {} Tj ET",
+                    font.resource_name(),
+                    number(style.size),
+                    number(x),
+                    number(options.page_height - baseline),
+                    pdf_string(&[byte]),
+                );
+                x += font.width(byte) * style.size / 1000.;
+            }
+        }
+    }
+}
+
+fn decode_image(bytes: &[u8]) -> Result<PdfImage> {
+    let image = image::load_from_memory(bytes).context("unsupported image format")?;
+    let image = if image.width().max(image.height()) > MAX_IMAGE_PIXELS {
+        image.thumbnail(MAX_IMAGE_PIXELS, MAX_IMAGE_PIXELS)
+    } else {
+        image
+    };
+    // PDF images can't carry alpha without a soft mask, so composite
+    // transparent images onto the white page instead.
+    let rgba = image.to_rgba8();
+    let mut rgb = Vec::with_capacity(rgba.width() as usize * rgba.height() as usize * 3);
+    for pixel in rgba.pixels() {
+        let [r, g, b, a] = pixel.0;
+        let alpha = a as f32 / 255.;
+        for channel in [r, g, b] {
+            rgb.push((channel as f32 * alpha + 255. * (1. - alpha)).round() as u8);
+        }
+    }
+    Ok(PdfImage {
+        width: rgba.width(),
+        height: rgba.height(),
+        data: compress(&rgb)?,
+    })
+}
+
+fn compress(data: &[u8]) -> Result<Vec<u8>> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data)?;
+    Ok(encoder.finish()?)
+}
+
+fn number(value: f32) -> String {
+    let formatted = format!("{:.2}", value);
+    let formatted = formatted.trim_end_matches('0').trim_end_matches('.');
+    if formatted == "-0" {
+        "0".to_string()
+    } else {
+        formatted.to_string()
+    }
+}
+
+fn color_operands(color: Rgba) -> String {
+    format!(
+        "{} {} {}",
+        number(color.r),
+        number(color.g),
+        number(color.b)
+    )
+}
+
+/// Encodes bytes as a PDF literal string, escaping everything outside of
+/// printable ASCII so content streams stay 7-bit clean.
+fn pdf_string(bytes: &[u8]) -> String {
+    let mut string = String::with_capacity(bytes.len() + 2);
+    string.push('(');
+    for byte in bytes {
+        match byte {
+            b'(' | b')' | b'\\' => {
+                string.push('\\');
+                string.push(*byte as char);
+            }
+            32..=126 => string.push(*byte as char),
+            _ => {
+                let _ = write!(string, "\\{:03o}", byte);
+            }
+        }
+    }
+    string.push(')');
+    string
+}
+
+/// Encodes text as a UTF-16 PDF text string, used for metadata and bookmarks.
+fn pdf_text_string(text: &str) -> String {
+    let mut string = String::from("<FEFF");
+    for unit in text.encode_utf16() {
+        let _ = write!(string, "{:04X}", unit);
+    }
+    string.push('>');
+    string
+}
+
+/// Writes numbered PDF objects and keeps track of their offsets for the
+/// cross-reference table.
+struct PdfWriter {
+    output: Vec<u8>,
+    offsets: Vec<usize>,
+}
+
+impl PdfWriter {
+    fn new() -> Self {
+        let mut output = Vec::new();
+        output.extend_from_slice(b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n");
+        Self {
+            output,
+            offsets: Vec::new(),
+        }
+    }
+
+    fn reserve(&mut self) -> usize {
+        self.offsets.push(0);
+        self.offsets.len()
+    }
+
+    fn object(&mut self, id: usize, body: &str) {
+        self.offsets[id - 1] = self.output.len();
+        self.output
+            .extend_from_slice(format!("{id} 0 obj\n{body}\nendobj\n").as_bytes());
+    }
+
+    fn stream(&mut self, id: usize, dictionary: &str, data: &[u8]) {
+        self.offsets[id - 1] = self.output.len();
+        self.output.extend_from_slice(
+            format!(
+                "{id} 0 obj\n<< {dictionary} /Length {} >>\nstream\n",
+                data.len()
+            )
+            .as_bytes(),
+        );
+        self.output.extend_from_slice(data);
+        self.output.extend_from_slice(b"\nendstream\nendobj\n");
+    }
+
+    fn finish(mut self, root: usize, info: usize) -> Vec<u8> {
+        let xref_offset = self.output.len();
+        let mut xref = format!("xref\n0 {}\n0000000000 65535 f \n", self.offsets.len() + 1);
+        for offset in &self.offsets {
+            let _ = writeln!(xref, "{:010} 00000 n ", offset);
+        }
+        let _ = write!(
+            xref,
+            "trailer\n<< /Size {} /Root {root} 0 R /Info {info} 0 R >>\nstartxref\n{xref_offset}\n%%EOF\n",
+            self.offsets.len() + 1
+        );
+        self.output.extend_from_slice(xref.as_bytes());
+        self.output
+    }
+}
+
+fn serialize(
+    document: &Document,
+    options: &ExportOptions,
+    pages: &[Page],
+    images: &[PdfImage],
+    anchors: &HashMap<String, Destination>,
+    outline: &[OutlineEntry],
+) -> Result<Vec<u8>> {
+    let mut writer = PdfWriter::new();
+    let catalog_id = writer.reserve();
+    let pages_id = writer.reserve();
+    let info_id = writer.reserve();
+    let font_ids = PdfFont::ALL.map(|font| (font, writer.reserve()));
+    let image_ids = images.iter().map(|_| writer.reserve()).collect::<Vec<_>>();
+    let page_ids = pages
+        .iter()
+        .map(|_| (writer.reserve(), writer.reserve()))
+        .collect::<Vec<_>>();
+
+    let destination = |anchor: &str| -> Option<String> {
+        let destination = anchors.get(anchor)?;
+        let (page_id, _) = page_ids.get(destination.page)?;
+        Some(format!(
+            "[{page_id} 0 R /XYZ null {} null]",
+            number(options.page_height - destination.y)
+        ))
+    };
+
+    for (font, id) in &font_ids {
+        let encoding = if font.uses_win_ansi() {
+            " /Encoding /WinAnsiEncoding"
+        } else {
+            ""
+        };
+        writer.object(
+            *id,
+            &format!(
+                "<< /Type /Font /Subtype /Type1 /BaseFont /{}{} >>",
+                font.base_font(),
+                encoding
+            ),
+        );
+    }
+
+    for (image, id) in images.iter().zip(&image_ids) {
+        writer.stream(
+            *id,
+            &format!(
+                "/Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /DeviceRGB /BitsPerComponent 8 /Filter /FlateDecode",
+                image.width, image.height
+            ),
+            &image.data,
+        );
+    }
+
+    let font_resources = font_ids
+        .iter()
+        .map(|(font, id)| format!("/{} {id} 0 R", font.resource_name()))
+        .collect::<Vec<_>>()
+        .join(" ");
+    for (page, (page_id, content_id)) in pages.iter().zip(&page_ids) {
+        let content = compress(page.content.as_bytes())?;
+        writer.stream(*content_id, "/Filter /FlateDecode", &content);
+
+        let image_resources = page
+            .images
+            .iter()
+            .map(|index| format!("/Im{index} {} 0 R", image_ids[*index]))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let annotations = page
+            .links
+            .iter()
+            .filter_map(|link| {
+                let [left, top, right, bottom] = link.rect;
+                let action = match &link.target {
+                    LinkTarget::Uri(uri) => {
+                        format!("/A << /S /URI /URI {} >>", pdf_string(uri.as_bytes()))
+                    }
+                    LinkTarget::Anchor(anchor) => format!("/Dest {}", destination(anchor)?),
+                };
+                Some(format!(
+                    "<< /Type /Annot /Subtype /Link /Rect [{} {} {} {}] /Border [0 0 0] {action} >>",
+                    number(left),
+                    number(options.page_height - bottom),
+                    number(right),
+                    number(options.page_height - top),
+                ))
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        writer.object(
+            *page_id,
+            &format!(
+                "<< /Type /Page /Parent {pages_id} 0 R /MediaBox [0 0 {} {}] /Resources << /Font << {font_resources} >> /XObject << {image_resources} >> >> /Contents {content_id} 0 R /Annots [{annotations}] >>",
+                number(options.page_width),
+                number(options.page_height),
+            ),
+        );
+    }
+
+    let kids = page_ids
+        .iter()
+        .map(|(id, _)| format!("{id} 0 R"))
+        .collect::<Vec<_>>()
+        .join(" ");
+    writer.object(
+        pages_id,
+        &format!(
+            "<< /Type /Pages /Kids [{kids}] /Count {} >>",
+            page_ids.len()
+        ),
+    );
+
+    let outlines_id = write_outline(&mut writer, outline, &destination);
+    let page_mode = if outlines_id.is_some() {
+        " /PageMode /UseOutlines"
+    } else {
+        ""
+    };
+    let outlines = outlines_id
+        .map(|id| format!(" /Outlines {id} 0 R"))
+        .unwrap_or_default();
+    writer.object(
+        catalog_id,
+        &format!("<< /Type /Catalog /Pages {pages_id} 0 R{outlines}{page_mode} >>"),
+    );
+
+    let mut info = format!(
+        "<< /Title {} /Producer {}",
+        pdf_text_string(&document.title),
+        pdf_text_string("Zed")
+    );
+    if let Some(author) = document.frontmatter.get("author") {
+        let _ = write!(info, " /Author {}", pdf_text_string(&author.display()));
+    }
+    info.push_str(" >>");
+    writer.object(info_id, &info);
+
+    Ok(writer.finish(catalog_id, info_id))
+}
+
+/// Writes the bookmarks tree, nesting entries by heading level.
+fn write_outline(
+    writer: &mut PdfWriter,
+    entries: &[OutlineEntry],
+    destination: &dyn Fn(&str) -> Option<String>,
+) -> Option<usize> {
+    if entries.is_empty() {
+        return None;
+    }
+
+    let root_id = writer.reserve();
+    let ids = entries.iter().map(|_| writer.reserve()).collect::<Vec<_>>();
+
+    // Find each entry's parent: the closest preceding entry with a lower level.
+    let mut parents: Vec<Option<usize>> = vec![None; entries.len()];
+    let mut stack: Vec<usize> = Vec::new();
+    for (ix, entry) in entries.iter().enumerate() {
+        while stack
+            .last()
+            .is_some_and(|&parent| entries[parent].level >= entry.level)
+        {
+            stack.pop();
+        }
+        parents[ix] = stack.last().copied();
+        stack.push(ix);
+    }
+    let children = |parent: Option<usize>| {
+        (0..entries.len())
+            .filter(|ix| parents[*ix] == parent)
+            .collect::<Vec<_>>()
+    };
+
+    for (ix, entry) in entries.iter().enumerate() {
+        let siblings = children(parents[ix]);
+        let position = siblings.iter().position(|sibling| *sibling == ix).unwrap();
+        let mut dictionary = format!(
+            "<< /Title {} /Parent {} 0 R",
+            pdf_text_string(&entry.title),
+            parents[ix].map_or(root_id, |parent| ids[parent])
+        );
+        if position > 0 {
+            let _ = write!(dictionary, " /Prev {} 0 R", ids[siblings[position - 1]]);
+        }
+        if let Some(next) = siblings.get(position + 1) {
+            let _ = write!(dictionary, " /Next {} 0 R", ids[*next]);
+        }
+        let own_children = children(Some(ix));
+        if let (Some(first), Some(last)) = (own_children.first(), own_children.last()) {
+            let _ = write!(
+                dictionary,
+                " /First {} 0 R /Last {} 0 R /Count {}",
+                ids[*first],
+                ids[*last],
+                own_children.len()
+            );
+        }
+        if let Some(destination) = destination(&entry.anchor) {
+            let _ = write!(dictionary, " /Dest {destination}");
+        }
+        dictionary.push_str(" >>");
+        writer.object(ids[ix], &dictionary);
+    }
+
+    let top_level = children(None);
+    writer.object(
+        root_id,
+        &format!(
+            "<< /Type /Outlines /First {} 0 R /Last {} 0 R /Count {} >>",
+            ids[top_level[0]],
+            ids[*top_level.last().unwrap()],
+            top_level.len()
+        ),
+    );
+    Some(root_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::Span;
+
+    fn style() -> TextStyle {
+        TextStyle::for_span(&Span::plain(""), 10., false, TEXT_COLOR)
+    }
+
+    #[test]
+    fn test_wrap() {
+        let lines = wrap(
+            &[("aaa bbb ccc".to_string(), style())],
+            measure("aaa bbb", PdfFont::Helvetica, 10.),
+        );
+        let texts = lines
+            .iter()
+            .map(|line| {
+                line.fragments
+                    .iter()
+                    .map(|f| f.text.as_str())
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(texts, vec!["aaa bbb", "ccc"]);
+
+        let lines = wrap(
+            &[("abcdefgh".to_string(), style())],
+            measure("abc", PdfFont::Helvetica, 10.),
+        );
+        let texts = lines
+            .iter()
+            .map(|line| {
+                line.fragments
+                    .iter()
+                    .map(|f| f.text.as_str())
+                    .collect::<String>()
+            })
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>();
+        assert_eq!(texts, vec!["abc", "def", "gh"]);
+    }
+
+    #[test]
+    fn test_pdf_string() {
+        assert_eq!(pdf_string(b"a(b)\\"), "(a\\(b\\)\\\\)");
+        assert_eq!(pdf_string(&[0xE9]), "(\\351)");
+        assert_eq!(pdf_text_string("Hé"), "<FEFF004800E9>");
+    }
+
+    #[test]
+    fn test_number() {
+        assert_eq!(number(12.0), "12");
+        assert_eq!(number(12.505), "12.51");
+        assert_eq!(number(-0.001), "0");
+    }
+}
@@ -0,0 +1,403 @@
+//! Metrics and encodings for the standard PDF fonts.
+//!
+//! Every PDF reader ships Helvetica, Courier and Symbol, so using them keeps
+//! exported files small and avoids embedding font programs. Text is encoded
+//! with WinAnsiEncoding, with Greek letters and math operators routed to the
+//! Symbol font.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum PdfFont {
+    Helvetica,
+    HelveticaBold,
+    HelveticaOblique,
+    HelveticaBoldOblique,
+    Courier,
+    CourierBold,
+    CourierOblique,
+    Symbol,
+}
+
+impl PdfFont {
+    pub const ALL: [PdfFont; 8] = [
+        PdfFont::Helvetica,
+        PdfFont::HelveticaBold,
+        PdfFont::HelveticaOblique,
+        PdfFont::HelveticaBoldOblique,
+        PdfFont::Courier,
+        PdfFont::CourierBold,
+        PdfFont::CourierOblique,
+        PdfFont::Symbol,
+    ];
+
+    pub fn for_style(bold: bool, italic: bool, monospace: bool) -> Self {
+        match (monospace, bold, italic) {
+            (true, true, _) => PdfFont::CourierBold,
+            (true, false, true) => PdfFont::CourierOblique,
+            (true, false, false) => PdfFont::Courier,
+            (false, true, true) => PdfFont::HelveticaBoldOblique,
+            (false, true, false) => PdfFont::HelveticaBold,
+            (false, false, true) => PdfFont::HelveticaOblique,
+            (false, false, false) => PdfFont::Helvetica,
+        }
+    }
+
+    /// The name the font is registered under in each page's resources.
+    pub fn resource_name(self) -> &'static str {
+        match self {
+            PdfFont::Helvetica => "F1",
+            PdfFont::HelveticaBold => "F2",
+            PdfFont::HelveticaOblique => "F3",
+            PdfFont::HelveticaBoldOblique => "F4",
+            PdfFont::Courier => "F5",
+            PdfFont::CourierBold => "F6",
+            PdfFont::CourierOblique => "F7",
+            PdfFont::Symbol => "F8",
+        }
+    }
+
+    pub fn base_font(self) -> &'static str {
+        match self {
+            PdfFont::Helvetica => "Helvetica",
+            PdfFont::HelveticaBold => "Helvetica-Bold",
+            PdfFont::HelveticaOblique => "Helvetica-Oblique",
+            PdfFont::HelveticaBoldOblique => "Helvetica-BoldOblique",
+            PdfFont::Courier => "Courier",
+            PdfFont::CourierBold => "Courier-Bold",
+            PdfFont::CourierOblique => "Courier-Oblique",
+            PdfFont::Symbol => "Symbol",
+        }
+    }
+
+    pub fn uses_win_ansi(self) -> bool {
+        self != PdfFont::Symbol
+    }
+
+    fn is_monospace(self) -> bool {
+        matches!(
+            self,
+            PdfFont::Courier | PdfFont::CourierBold | PdfFont::CourierOblique
+        )
+    }
+
+    fn is_bold(self) -> bool {
+        matches!(self, PdfFont::HelveticaBold | PdfFont::HelveticaBoldOblique)
+    }
+
+    /// The advance width of an encoded byte, in thousandths of the font size.
+    pub fn width(self, byte: u8) -> f32 {
+        if self.is_monospace() {
+            return 600.;
+        }
+        if self == PdfFont::Symbol {
+            return symbol_width(byte);
+        }
+        let table = if self.is_bold() {
+            &HELVETICA_BOLD_WIDTHS
+        } else {
+            &HELVETICA_WIDTHS
+        };
+        match byte {
+            32..=126 => table[(byte - 32) as usize] as f32,
+            _ => {
+                let width = latin_width(byte);
+                if self.is_bold() { width * 1.05 } else { width }
+            }
+        }
+    }
+
+    /// Encodes a character for this font, falling back to the Symbol font for
+    /// Greek and mathematical characters. Characters neither font can show are
+    /// replaced by a close ASCII approximation.
+    pub fn encode(self, c: char, output: &mut Vec<(PdfFont, u8)>) {
+        if let Some(byte) = win_ansi_byte(c) {
+            output.push((self, byte));
+        } else if let Some(byte) = symbol_byte(c) {
+            output.push((PdfFont::Symbol, byte));
+        } else {
+            for fallback in ascii_fallback(c).bytes() {
+                output.push((self, fallback));
+            }
+        }
+    }
+}
+
+/// Helvetica widths for the printable ASCII range (32..=126).
+const HELVETICA_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, 1015, 667, 667, 722, 722, 667,
+    611, 778, 722, 278, 500, 667, 556, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 278, 278, 278, 469, 556, 333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500,
+    222, 833, 556, 556, 556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+/// Helvetica-Bold widths for the printable ASCII range (32..=126).
+const HELVETICA_BOLD_WIDTHS: [u16; 95] = [
+    278, 333, 474, 556, 556, 889, 722, 238, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 333, 333, 584, 584, 584, 611, 975, 722, 722, 722, 722, 667,
+    611, 778, 722, 278, 556, 722, 611, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 333, 278, 333, 584, 556, 333, 556, 611, 556, 611, 556, 333, 611, 611, 278, 278, 556,
+    278, 889, 611, 611, 611, 611, 389, 556, 333, 611, 556, 778, 556, 556, 500, 389, 280, 389, 584,
+];
+
+/// Helvetica widths for the upper half of WinAnsiEncoding.
+fn latin_width(byte: u8) -> f32 {
+    (match byte {
+        0x80 | 0x83 | 0x86 | 0x87 | 0x96 => 556,
+        0x82 | 0x91 | 0x92 => 222,
+        0x84 | 0x88 | 0x8B | 0x93 | 0x94 | 0x98 | 0x9B => 333,
+        0x85 | 0x89 | 0x8C | 0x97 | 0x99 => 1000,
+        0x8A | 0x9F => 667,
+        0x8E => 611,
+        0x95 => 350,
+        0x9A | 0x9E => 500,
+        0x9C => 944,
+        0xA0 | 0xB7 => 278,
+        0xA1 | 0xA8 | 0xAD | 0xAF | 0xB2..=0xB4 | 0xB8 | 0xB9 => 333,
+        0xA6 => 260,
+        0xA9 | 0xAE => 737,
+        0xAA => 370,
+        0xAC | 0xB1 | 0xD7 | 0xF7 => 584,
+        0xB0 => 400,
+        0xB6 => 537,
+        0xBA => 365,
+        0xBC..=0xBE => 834,
+        0xBF | 0xDF => 611,
+        0xC0..=0xC5 | 0xC8..=0xCB | 0xDD | 0xDE => 667,
+        0xC6 => 1000,
+        0xC7 | 0xD0 | 0xD1 | 0xD9..=0xDC => 722,
+        0xCC..=0xCF | 0xEC..=0xEF => 278,
+        0xD2..=0xD6 | 0xD8 => 778,
+        0xE6 => 889,
+        0xE7 | 0xFD | 0xFF => 500,
+        0xF8 => 611,
+        _ => 556,
+    }) as f32
+}
+
+fn symbol_width(byte: u8) -> f32 {
+    (match byte {
+        0x61 | 0x66 => 631,
+        0x62 | 0x6D | 0x70 | 0x71 | 0x75 | 0x77 | 0xB3 | 0xA3 | 0xB9 | 0xBB | 0xBA => 549,
+        0x63 | 0x68 => 549,
+        0x64 | 0x6C | 0x6E | 0x78 | 0x7A => 494,
+        0x65 => 439,
+        0x67 | 0x74 => 411,
+        0x69 => 329,
+        0x6B | 0x6F | 0x73 => 549,
+        0x72 => 549,
+        0x79 => 686,
+        0xE5 | 0xD5 => 713,
+        0xF2 => 274,
+        0xAE | 0xAC | 0xAB | 0xDE | 0xDB => 987,
+        0xA5 => 713,
+        0xBC => 1000,
+        _ => 600,
+    }) as f32
+}
+
+fn win_ansi_byte(c: char) -> Option<u8> {
+    let code = c as u32;
+    if (32..=126).contains(&code) || (0xA0..=0xFF).contains(&code) {
+        return Some(code as u8);
+    }
+    Some(match c {
+        '€' => 0x80,
+        '‚' => 0x82,
+        'ƒ' => 0x83,
+        '„' => 0x84,
+        '…' => 0x85,
+        '†' => 0x86,
+        '‡' => 0x87,
+        'ˆ' => 0x88,
+        '‰' => 0x89,
+        'Š' => 0x8A,
+        '‹' => 0x8B,
+        'Œ' => 0x8C,
+        'Ž' => 0x8E,
+        '‘' => 0x91,
+        '’' => 0x92,
+        '“' => 0x93,
+        '”' => 0x94,
+        '•' => 0x95,
+        '–' => 0x96,
+        '—' => 0x97,
+        '˜' => 0x98,
+        '™' => 0x99,
+        'š' => 0x9A,
+        '›' => 0x9B,
+        'œ' => 0x9C,
+        'ž' => 0x9E,
+        'Ÿ' => 0x9F,
+        '\t' => b' ',
+        _ => return None,
+    })
+}
+
+fn symbol_byte(c: char) -> Option<u8> {
+    Some(match c {
+        'α' => 0x61,
+        'β' => 0x62,
+        'χ' => 0x63,
+        'δ' => 0x64,
+        'ε' => 0x65,
+        'φ' => 0x66,
+        'γ' => 0x67,
+        'η' => 0x68,
+        'ι' => 0x69,
+        'κ' => 0x6B,
+        'λ' => 0x6C,
+        'μ' => 0x6D,
+        'ν' => 0x6E,
+        'ο' => 0x6F,
+        'π' => 0x70,
+        'θ' => 0x71,
+        'ρ' => 0x72,
+        'σ' => 0x73,
+        'τ' => 0x74,
+        'υ' => 0x75,
+        'ω' => 0x77,
+        'ξ' => 0x78,
+        'ψ' => 0x79,
+        'ζ' => 0x7A,
+        'Δ' => 0x44,
+        'Φ' => 0x46,
+        'Γ' => 0x47,
+        'Λ' => 0x4C,
+        'Π' => 0x50,
+        'Θ' => 0x51,
+        'Σ' => 0x53,
+        'Ω' => 0x57,
+        'Ξ' => 0x58,
+        'Ψ' => 0x59,
+        '∀' => 0x22,
+        '∃' => 0x24,
+        '∋' => 0x27,
+        '∗' => 0x2A,
+        '≅' => 0x40,
+        '∴' => 0x5C,
+        '⊥' => 0x5E,
+        '∼' => 0x7E,
+        '′' => 0xA2,
+        '≤' => 0xA3,
+        '∞' => 0xA5,
+        '↔' => 0xAB,
+        '←' => 0xAC,
+        '↑' => 0xAD,
+        '→' => 0xAE,
+        '↓' => 0xAF,
+        '″' => 0xB2,
+        '≥' => 0xB3,
+        '∝' => 0xB5,
+        '∂' => 0xB6,
+        '≠' => 0xB9,
+        '≡' => 0xBA,
+        '≈' => 0xBB,
+        'ℵ' => 0xC0,
+        '⊗' => 0xC4,
+        '⊕' => 0xC5,
+        '∅' => 0xC6,
+        '∩' => 0xC7,
+        '∪' => 0xC8,
+        '⊃' => 0xC9,
+        '⊇' => 0xCA,
+        '⊂' => 0xCC,
+        '⊆' => 0xCD,
+        '∈' => 0xCE,
+        '∉' => 0xCF,
+        '∠' => 0xD0,
+        '∇' => 0xD1,
+        '∏' => 0xD5,
+        '√' => 0xD6,
+        '⋅' => 0xD7,
+        '∧' => 0xD9,
+        '∨' => 0xDA,
+        '⇔' => 0xDB,
+        '⇐' => 0xDC,
+        '⇒' => 0xDE,
+        '◊' => 0xE0,
+        '⟨' => 0xE1,
+        '∑' => 0xE5,
+        '⌈' => 0xE9,
+        '⌊' => 0xEB,
+        '⟩' => 0xF1,
+        '∫' => 0xF2,
+        '⌉' => 0xF9,
+        '⌋' => 0xFB,
+        _ => return None,
+    })
+}
+
+fn ascii_fallback(c: char) -> String {
+    let fallback = match c {
+        '☐' => "[ ]",
+        '☑' | '☒' => "[x]",
+        '⁰' => "^0",
+        '⁴' => "^4",
+        '⁵' => "^5",
+        '⁶' => "^6",
+        '⁷' => "^7",
+        '⁸' => "^8",
+        '⁹' => "^9",
+        'ⁿ' => "^n",
+        'ⁱ' => "^i",
+        '⁺' => "^+",
+        '⁻' => "^-",
+        '₀' => "_0",
+        '₁' => "_1",
+        '₂' => "_2",
+        '₃' => "_3",
+        '₄' => "_4",
+        '₅' => "_5",
+        '₆' => "_6",
+        '₇' => "_7",
+        '₈' => "_8",
+        '₉' => "_9",
+        'ᵢ' => "_i",
+        'ⱼ' => "_j",
+        'ₙ' => "_n",
+        '↦' => "|->",
+        '∓' => "-/+",
+        '∘' => "o",
+        '⋯' => "...",
+        '\u{00A0}' | '\u{2000}'..='\u{200B}' => " ",
+        c if c.is_control() => "",
+        // Combining accents from math rendering can't be positioned without
+        // font metrics, so they are dropped.
+        '\u{0300}'..='\u{036F}' | '\u{20D0}'..='\u{20FF}' => "",
+        _ => "?",
+    };
+    fallback.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode() {
+        let mut encoded = Vec::new();
+        for c in "é α≤β☑".chars() {
+            PdfFont::Helvetica.encode(c, &mut encoded);
+        }
+        assert_eq!(
+            encoded,
+            vec![
+                (PdfFont::Helvetica, 0xE9),
+                (PdfFont::Helvetica, b' '),
+                (PdfFont::Symbol, 0x61),
+                (PdfFont::Symbol, 0xA3),
+                (PdfFont::Symbol, 0x62),
+                (PdfFont::Helvetica, b'['),
+                (PdfFont::Helvetica, b'x'),
+                (PdfFont::Helvetica, b']'),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_widths() {
+        assert_eq!(PdfFont::Helvetica.width(b'W'), 944.);
+        assert_eq!(PdfFont::HelveticaBold.width(b'a'), 556.);
+        assert_eq!(PdfFont::Courier.width(b'i'), 600.);
+    }
+}
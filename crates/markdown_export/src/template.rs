@@ -0,0 +1,145 @@
+use collections::HashMap;
+
+/// A piece of an expanded header or footer template.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TemplatePart {
+    Text(String),
+    PageNumber,
+    PageCount,
+}
+
+/// A header or footer split into its left, center and right columns.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PageTemplate {
+    pub left: Vec<TemplatePart>,
+    pub center: Vec<TemplatePart>,
+    pub right: Vec<TemplatePart>,
+}
+
+impl PageTemplate {
+    /// Parses a template such as `{title} | | {page} / {pages}`.
+    ///
+    /// Columns are separated by `|`; a template without separators is centered.
+    /// `{page}` and `{pages}` are left as parts so each output format can fill
+    /// them in, while every other `{name}` is replaced with the matching
+    /// variable (`title`, `date`, `file` or any frontmatter property). Unknown
+    /// variables expand to nothing.
+    pub fn parse(template: &str, variables: &HashMap<String, String>) -> Self {
+        let columns = template.split('|').collect::<Vec<_>>();
+        let parse = |column: Option<&&str>| {
+            column
+                .map(|column| parse_parts(column.trim(), variables))
+                .unwrap_or_default()
+        };
+        match columns.len() {
+            1 => Self {
+                center: parse(columns.first()),
+                ..Default::default()
+            },
+            2 => Self {
+                left: parse(columns.first()),
+                right: parse(columns.get(1)),
+                ..Default::default()
+            },
+            _ => Self {
+                left: parse(columns.first()),
+                center: parse(columns.get(1)),
+                right: parse(columns.get(2)),
+            },
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.left.is_empty() && self.center.is_empty() && self.right.is_empty()
+    }
+}
+
+/// Renders template parts to text for a known page number and count.
+pub fn render_parts(parts: &[TemplatePart], page: usize, page_count: usize) -> String {
+    parts
+        .iter()
+        .map(|part| match part {
+            TemplatePart::Text(text) => text.clone(),
+            TemplatePart::PageNumber => page.to_string(),
+            TemplatePart::PageCount => page_count.to_string(),
+        })
+        .collect()
+}
+
+fn parse_parts(template: &str, variables: &HashMap<String, String>) -> Vec<TemplatePart> {
+    let mut parts = Vec::new();
+    let mut text = String::new();
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        text.push_str(&rest[..open]);
+        let Some(close) = rest[open..].find('}').map(|ix| ix + open) else {
+            text.push_str(&rest[open..]);
+            rest = "";
+            break;
+        };
+        let name = rest[open + 1..close].trim();
+        match name {
+            "page" | "pages" => {
+                if !text.is_empty() {
+                    parts.push(TemplatePart::Text(std::mem::take(&mut text)));
+                }
+                parts.push(if name == "page" {
+                    TemplatePart::PageNumber
+                } else {
+                    TemplatePart::PageCount
+                });
+            }
+            name => {
+                if let Some(value) = variables
+                    .iter()
+                    .find(|(key, _)| key.eq_ignore_ascii_case(name))
+                    .map(|(_, value)| value)
+                {
+                    text.push_str(value);
+                }
+            }
+        }
+        rest = &rest[close + 1..];
+    }
+    text.push_str(rest);
+    if !text.is_empty() {
+        parts.push(TemplatePart::Text(text));
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_parse_template() {
+        let variables = HashMap::from_iter([
+            ("title".to_string(), "Roadmap".to_string()),
+            ("Author".to_string(), "Ada".to_string()),
+        ]);
+
+        let template = PageTemplate::parse(
+            "{title} by {author}{missing} | | {page} / {pages}",
+            &variables,
+        );
+        assert_eq!(
+            template,
+            PageTemplate {
+                left: vec![TemplatePart::Text("Roadmap by Ada".into())],
+                center: vec![],
+                right: vec![
+                    TemplatePart::PageNumber,
+                    TemplatePart::Text(" / ".into()),
+                    TemplatePart::PageCount,
+                ],
+            }
+        );
+        assert_eq!(render_parts(&template.right, 2, 7), "2 / 7");
+
+        let centered = PageTemplate::parse("Page {page}", &variables);
+        assert_eq!(render_parts(&centered.center, 1, 1), "Page 1");
+        assert!(centered.left.is_empty() && centered.right.is_empty());
+    }
+}
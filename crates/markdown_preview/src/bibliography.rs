@@ -0,0 +1,169 @@
+use std::{ops::Range, path::PathBuf, sync::Arc};
+
+use citations::{Library, bibliography, citations, format_citation};
+use language::LanguageRegistry;
+
+use crate::markdown_elements::*;
+use crate::markdown_parser::parse_markdown;
+
+/// Parses a note like [`parse_markdown`], formatting its citations with
+/// `library` and adding a bibliography of the cited references at the end.
+pub async fn parse_markdown_with_citations(
+    markdown_input: &str,
+    file_location_directory: Option<PathBuf>,
+    language_registry: Option<Arc<LanguageRegistry>>,
+    library: &Library,
+) -> ParsedMarkdown {
+    let Some(bibliography) = bibliography(markdown_input, library) else {
+        return parse_markdown(markdown_input, file_location_directory, language_registry).await;
+    };
+    // The bibliography goes after the note so the note's source ranges stay
+    // valid, and its own blocks point past the end of the note.
+    let markdown_input = format!("{markdown_input}\n\n{bibliography}");
+    let mut parsed =
+        parse_markdown(&markdown_input, file_location_directory, language_registry).await;
+    format_citations(&mut parsed, library);
+    parsed
+}
+
+/// Replaces citations like `[@smith2020]` in the parsed text with formatted
+/// ones like "(Smith 2020)".
+pub fn format_citations(parsed: &mut ParsedMarkdown, library: &Library) {
+    for element in &mut parsed.children {
+        format_element(element, library);
+    }
+}
+
+fn format_element(element: &mut ParsedMarkdownElement, library: &Library) {
+    match element {
+        ParsedMarkdownElement::Heading(heading) => format_paragraph(&mut heading.contents, library),
+        ParsedMarkdownElement::ListItem(list_item) => {
+            for element in &mut list_item.content {
+                format_element(element, library);
+            }
+        }
+        ParsedMarkdownElement::Table(table) => {
+            for row in std::iter::once(&mut table.header).chain(&mut table.body) {
+                for cell in &mut row.children {
+                    format_paragraph(cell, library);
+                }
+            }
+        }
+        ParsedMarkdownElement::BlockQuote(block_quote) => {
+            for element in &mut block_quote.children {
+                format_element(element, library);
+            }
+        }
+        ParsedMarkdownElement::Paragraph(paragraph) => format_paragraph(paragraph, library),
+        ParsedMarkdownElement::CodeBlock(_) | ParsedMarkdownElement::HorizontalRule(_) => {}
+    }
+}
+
+fn format_paragraph(paragraph: &mut MarkdownParagraph, library: &Library) {
+    for chunk in paragraph {
+        if let MarkdownParagraphChunk::Text(text) = chunk {
+            format_text(text, library);
+        }
+    }
+}
+
+fn format_text(text: &mut ParsedMarkdownText, library: &Library) {
+    let code_ranges = text
+        .region_ranges
+        .iter()
+        .zip(&text.regions)
+        .filter(|(_, region)| region.code)
+        .map(|(range, _)| range.clone())
+        .collect::<Vec<_>>();
+    let replacements = citations(&text.contents)
+        .into_iter()
+        .filter(|citation| {
+            !code_ranges
+                .iter()
+                .any(|code| code.start < citation.range.end && citation.range.start < code.end)
+        })
+        .filter_map(|citation| Some((citation.range.clone(), format_citation(&citation, library)?)))
+        .collect::<Vec<_>>();
+    if replacements.is_empty() {
+        return;
+    }
+
+    let mut contents = String::new();
+    let mut last_end = 0;
+    for (range, formatted) in &replacements {
+        contents.push_str(&text.contents[last_end..range.start]);
+        contents.push_str(formatted);
+        last_end = range.end;
+    }
+    contents.push_str(&text.contents[last_end..]);
+    text.contents = contents;
+
+    // Offsets inside a citation move to the end of its formatted text.
+    let map_offset = |offset: usize| {
+        let mut new_offset = offset;
+        for (range, formatted) in &replacements {
+            if offset >= range.end {
+                new_offset = new_offset - range.len() + formatted.len();
+            } else if offset > range.start {
+                new_offset = new_offset - (offset - range.start) + formatted.len();
+            }
+        }
+        new_offset
+    };
+    let map_range = |range: &mut Range<usize>| {
+        *range = map_offset(range.start)..map_offset(range.end);
+    };
+    for (range, _) in &mut text.highlights {
+        map_range(range);
+    }
+    for range in &mut text.region_ranges {
+        map_range(range);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::FontWeight;
+    use pretty_assertions::assert_eq;
+
+    #[gpui::test]
+    async fn test_citations_are_formatted() {
+        let library =
+            Library::from_bibtex("@book{jo, author = {Smith, Jo}, title = {Notes}, year = 2020}");
+        let text = "As **shown** [@jo, p. 3], not `[@jo]` or [@other].";
+        let parsed = parse_markdown_with_citations(text, None, None, &library).await;
+
+        let [
+            ParsedMarkdownElement::Paragraph(paragraph),
+            ParsedMarkdownElement::Heading(heading),
+            ParsedMarkdownElement::Paragraph(entry),
+        ] = parsed.children.as_slice()
+        else {
+            panic!("unexpected blocks: {:?}", parsed.children);
+        };
+        let MarkdownParagraphChunk::Text(paragraph) = &paragraph[0] else {
+            panic!("expected text");
+        };
+        assert_eq!(
+            paragraph.contents,
+            "As shown (Smith 2020, p. 3), not [@jo] or [@other]."
+        );
+        assert_eq!(
+            paragraph.highlights,
+            [(
+                3..8,
+                MarkdownHighlight::Style(MarkdownHighlightStyle {
+                    weight: FontWeight::BOLD,
+                    ..Default::default()
+                })
+            )]
+        );
+        assert_eq!(paragraph.region_ranges, [33..38]);
+        assert!(heading.source_range.start >= text.len());
+        let MarkdownParagraphChunk::Text(entry) = &entry[0] else {
+            panic!("expected text");
+        };
+        assert_eq!(entry.contents, "Smith, J. (2020). Notes.");
+    }
+}
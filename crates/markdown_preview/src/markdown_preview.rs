@@ -1,12 +1,15 @@
 use gpui::{App, actions};
 use workspace::Workspace;
 
+pub mod bibliography;
 pub mod markdown_elements;
 pub mod markdown_parser;
 pub mod markdown_preview_view;
 pub mod markdown_renderer;
+pub mod presentation_view;
+pub mod slides;
 
-actions!(markdown, [OpenPreview, OpenPreviewToTheSide]);
+actions!(markdown, [OpenPreview, OpenPreviewToTheSide, StartPresentation]);
 
 pub fn init(cx: &mut App) {
     cx.observe_new(|workspace: &mut Workspace, window, cx| {
@@ -14,6 +17,7 @@ pub fn init(cx: &mut App) {
             return;
         };
         markdown_preview_view::MarkdownPreviewView::register(workspace, window, cx);
+        presentation_view::PresentationView::register(workspace, window, cx);
     })
     .detach();
 }
@@ -19,6 +19,7 @@ use crate::OpenPreviewToTheSide;
 use crate::markdown_elements::ParsedMarkdownElement;
 use crate::{
     OpenPreview,
+    bibliography::parse_markdown_with_citations,
     markdown_elements::ParsedMarkdown,
     markdown_parser::parse_markdown,
     markdown_renderer::{RenderContext, render_markdown_block},
@@ -50,6 +51,7 @@ pub enum MarkdownPreviewMode {
 struct EditorState {
     editor: Entity<Editor>,
     _subscription: Subscription,
+    _library_subscription: Option<Subscription>,
 }
 
 impl MarkdownPreviewView {
@@ -339,9 +341,22 @@ impl MarkdownPreviewView {
             .tab_description(0, cx)
             .map(|tab_description| format!("Preview {}", tab_description));
 
+        // Citations are formatted again when the library loads or changes.
+        let library_subscription = editor
+            .read(cx)
+            .project
+            .clone()
+            .and_then(|project| citations::library_for_project(&project, cx))
+            .map(|library| {
+                cx.observe_in(&library, window, |this, _, window, cx| {
+                    this.parse_markdown_from_active_editor(false, window, cx);
+                })
+            });
+
         self.active_editor = Some(EditorState {
             editor,
             _subscription: subscription,
+            _library_subscription: library_subscription,
         });
 
         self.parse_markdown_from_active_editor(false, window, cx);
@@ -378,15 +393,32 @@ impl MarkdownPreviewView {
                 cx.background_executor().timer(REPARSE_DEBOUNCE).await;
             }
 
-            let (contents, file_location) = view.update(cx, |_, cx| {
+            let (contents, file_location, library) = view.update(cx, |_, cx| {
+                let library = editor
+                    .read(cx)
+                    .project
+                    .clone()
+                    .and_then(|project| citations::library_for_project(&project, cx))
+                    .and_then(|library| library.read(cx).library());
                 let editor = editor.read(cx);
                 let contents = editor.buffer().read(cx).snapshot(cx).text();
                 let file_location = MarkdownPreviewView::get_folder_for_active_editor(editor, cx);
-                (contents, file_location)
+                (contents, file_location, library)
             })?;
 
             let parsing_task = cx.background_spawn(async move {
-                parse_markdown(&contents, file_location, Some(language_registry)).await
+                match library {
+                    Some(library) => {
+                        parse_markdown_with_citations(
+                            &contents,
+                            file_location,
+                            Some(language_registry),
+                            &library,
+                        )
+                        .await
+                    }
+                    None => parse_markdown(&contents, file_location, Some(language_registry)).await,
+                }
             });
             let contents = parsing_task.await;
             view.update(cx, move |view, cx| {
@@ -408,6 +440,10 @@ impl MarkdownPreviewView {
     ) {
         if let Some(state) = &self.active_editor {
             state.editor.update(cx, |editor, cx| {
+                // Blocks the preview adds, like the bibliography, start past
+                // the end of the note.
+                let len = editor.buffer().read(cx).len(cx);
+                let selection = selection.start.min(len)..selection.end.min(len);
                 editor.change_selections(Some(Autoscroll::center()), window, cx, |selections| {
                     selections.select_ranges(vec![selection])
                 });
@@ -417,7 +453,7 @@ impl MarkdownPreviewView {
     }
 
     /// The absolute path of the file that is currently being previewed.
-    fn get_folder_for_active_editor(editor: &Editor, cx: &App) -> Option<PathBuf> {
+    pub(crate) fn get_folder_for_active_editor(editor: &Editor, cx: &App) -> Option<PathBuf> {
         if let Some(file) = editor.file_at(0, cx) {
             if let Some(file) = file.as_local() {
                 file.abs_path(cx).parent().map(|p| p.to_path_buf())
@@ -0,0 +1,705 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use editor::{Editor, EditorEvent};
+use gpui::{
+    Bounds, Entity, EventEmitter, FocusHandle, Focusable, Subscription, Task, TextStyleRefinement,
+    TitlebarOptions, WeakEntity, WindowBounds, WindowHandle, WindowOptions, actions, size,
+};
+use language::LanguageRegistry;
+use ui::prelude::*;
+use util::ResultExt as _;
+use workspace::item::Item;
+use workspace::{SaveIntent, ToggleZoom, Workspace};
+
+use crate::StartPresentation;
+use crate::markdown_elements::ParsedMarkdown;
+use crate::markdown_parser::parse_markdown;
+use crate::markdown_preview_view::MarkdownPreviewView;
+use crate::markdown_renderer::{RenderContext, render_markdown_block};
+use crate::slides::{slide_at_offset, split_slides};
+
+actions!(
+    markdown,
+    [
+        NextSlide,
+        PreviousSlide,
+        FirstSlide,
+        LastSlide,
+        ToggleFragments,
+        ToggleSpeakerNotes,
+        ExitPresentation
+    ]
+);
+
+const REPARSE_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Presents the slides of a markdown file, which are separated by `---`
+/// lines, in a zoomed pane of a fullscreen window.
+pub struct PresentationView {
+    editor: Entity<Editor>,
+    workspace: WeakEntity<Workspace>,
+    language_registry: Arc<LanguageRegistry>,
+    slides: Vec<ParsedSlide>,
+    current_slide: usize,
+    /// Whether the blocks of each slide are revealed one at a time.
+    step_through_fragments: bool,
+    /// How many blocks of the current slide are shown while stepping through
+    /// fragments.
+    visible_blocks: usize,
+    speaker_notes: Option<WindowHandle<SpeakerNotesView>>,
+    /// Whether the window was made fullscreen for the presentation, and so
+    /// should leave fullscreen when it ends.
+    entered_fullscreen: bool,
+    focus_handle: FocusHandle,
+    parsing_task: Option<Task<Result<()>>>,
+    _subscriptions: Vec<Subscription>,
+}
+
+struct ParsedSlide {
+    content: ParsedMarkdown,
+    notes: Option<ParsedMarkdown>,
+}
+
+impl ParsedSlide {
+    fn block_count(&self) -> usize {
+        self.content.children.len()
+    }
+}
+
+impl PresentationView {
+    pub fn register(workspace: &mut Workspace, _window: &mut Window, _cx: &mut Context<Workspace>) {
+        workspace.register_action(|workspace, _: &StartPresentation, window, cx| {
+            let Some(editor) =
+                MarkdownPreviewView::resolve_active_item_as_markdown_editor(workspace, cx)
+            else {
+                return;
+            };
+            let language_registry = workspace.project().read(cx).languages().clone();
+            let workspace_handle = workspace.weak_handle();
+            let view = cx.new(|cx| {
+                PresentationView::new(editor, workspace_handle, language_registry, window, cx)
+            });
+            workspace.add_item_to_active_pane(Box::new(view), None, true, window, cx);
+            workspace.active_pane().update(cx, |pane, cx| {
+                if !pane.is_zoomed() {
+                    pane.toggle_zoom(&ToggleZoom, window, cx);
+                }
+            });
+            view.update(cx, |view, _| view.enter_fullscreen(window));
+        });
+    }
+
+    pub fn new(
+        editor: Entity<Editor>,
+        workspace: WeakEntity<Workspace>,
+        language_registry: Arc<LanguageRegistry>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        // Start from the slide that's being edited.
+        let current_slide = editor.update(cx, |editor, cx| {
+            let text = editor.buffer().read(cx).snapshot(cx).text();
+            let cursor = editor.selections.newest::<usize>(cx).head();
+            slide_at_offset(&split_slides(&text), cursor)
+        });
+        let subscriptions = vec![
+            cx.subscribe_in(
+                &editor,
+                window,
+                |this, _, event: &EditorEvent, window, cx| {
+                    if let EditorEvent::Edited { .. } | EditorEvent::DirtyChanged = event {
+                        this.parse_slides(true, window, cx);
+                    }
+                },
+            ),
+            cx.on_release_in(window, |this, window, cx| {
+                this.close_speaker_notes(cx);
+                this.leave_fullscreen(window);
+            }),
+        ];
+
+        let mut this = Self {
+            editor,
+            workspace,
+            language_registry,
+            slides: Vec::new(),
+            current_slide,
+            step_through_fragments: false,
+            visible_blocks: 0,
+            speaker_notes: None,
+            entered_fullscreen: false,
+            focus_handle: cx.focus_handle(),
+            parsing_task: None,
+            _subscriptions: subscriptions,
+        };
+        this.parse_slides(false, window, cx);
+        this
+    }
+
+    fn parse_slides(
+        &mut self,
+        wait_for_debounce: bool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let editor = self.editor.clone();
+        let language_registry = self.language_registry.clone();
+        self.parsing_task = Some(cx.spawn_in(window, async move |this, cx| {
+            if wait_for_debounce {
+                cx.background_executor().timer(REPARSE_DEBOUNCE).await;
+            }
+
+            let (text, file_location) = this.update(cx, |_, cx| {
+                let editor = editor.read(cx);
+                let text = editor.buffer().read(cx).snapshot(cx).text();
+                let file_location = MarkdownPreviewView::get_folder_for_active_editor(editor, cx);
+                (text, file_location)
+            })?;
+
+            let slides = cx
+                .background_spawn(async move {
+                    let mut slides = Vec::new();
+                    for slide in split_slides(&text) {
+                        let content = parse_markdown(
+                            &text[slide.content],
+                            file_location.clone(),
+                            Some(language_registry.clone()),
+                        )
+                        .await;
+                        let notes = match slide.notes {
+                            Some(notes) => Some(
+                                parse_markdown(
+                                    &text[notes],
+                                    file_location.clone(),
+                                    Some(language_registry.clone()),
+                                )
+                                .await,
+                            ),
+                            None => None,
+                        };
+                        slides.push(ParsedSlide { content, notes });
+                    }
+                    slides
+                })
+                .await;
+
+            this.update(cx, |this, cx| {
+                let is_first_parse = this.slides.is_empty();
+                this.slides = slides;
+                this.current_slide = this.current_slide.min(this.slides.len().saturating_sub(1));
+                if is_first_parse || !this.step_through_fragments {
+                    this.visible_blocks = this.current_block_count();
+                } else {
+                    this.visible_blocks = this.visible_blocks.min(this.current_block_count());
+                }
+                cx.notify();
+            })
+        }));
+    }
+
+    fn current_block_count(&self) -> usize {
+        self.slides
+            .get(self.current_slide)
+            .map_or(0, |slide| slide.block_count())
+    }
+
+    fn show_slide(&mut self, ix: usize, reveal_all: bool, cx: &mut Context<Self>) {
+        self.current_slide = ix;
+        self.visible_blocks = if reveal_all || !self.step_through_fragments {
+            self.current_block_count()
+        } else {
+            1
+        };
+        cx.notify();
+    }
+
+    fn go_forward(&mut self, cx: &mut Context<Self>) {
+        if self.step_through_fragments && self.visible_blocks < self.current_block_count() {
+            self.visible_blocks += 1;
+            cx.notify();
+        } else if self.current_slide + 1 < self.slides.len() {
+            self.show_slide(self.current_slide + 1, false, cx);
+        }
+    }
+
+    fn go_back(&mut self, cx: &mut Context<Self>) {
+        if self.step_through_fragments && self.visible_blocks > 1 {
+            self.visible_blocks -= 1;
+            cx.notify();
+        } else if self.current_slide > 0 {
+            self.show_slide(self.current_slide - 1, true, cx);
+        }
+    }
+
+    fn next_slide(&mut self, _: &NextSlide, _: &mut Window, cx: &mut Context<Self>) {
+        self.go_forward(cx);
+    }
+
+    fn previous_slide(&mut self, _: &PreviousSlide, _: &mut Window, cx: &mut Context<Self>) {
+        self.go_back(cx);
+    }
+
+    fn first_slide(&mut self, _: &FirstSlide, _: &mut Window, cx: &mut Context<Self>) {
+        self.show_slide(0, false, cx);
+    }
+
+    fn last_slide(&mut self, _: &LastSlide, _: &mut Window, cx: &mut Context<Self>) {
+        self.show_slide(self.slides.len().saturating_sub(1), false, cx);
+    }
+
+    fn toggle_fragments(&mut self, _: &ToggleFragments, _: &mut Window, cx: &mut Context<Self>) {
+        self.step_through_fragments = !self.step_through_fragments;
+        self.visible_blocks = self.current_block_count();
+        cx.notify();
+    }
+
+    fn toggle_speaker_notes(
+        &mut self,
+        _: &ToggleSpeakerNotes,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(speaker_notes) = self.speaker_notes.take() {
+            // The window may have been closed already.
+            if speaker_notes
+                .update(cx, |_, window, _| window.remove_window())
+                .is_ok()
+            {
+                return;
+            }
+        }
+
+        let presentation = cx.entity().downgrade();
+        let options = WindowOptions {
+            titlebar: Some(TitlebarOptions {
+                title: Some("Speaker Notes".into()),
+                ..Default::default()
+            }),
+            window_bounds: Some(WindowBounds::Windowed(Bounds::centered(
+                None,
+                size(px(640.), px(480.)),
+                cx,
+            ))),
+            ..Default::default()
+        };
+        self.speaker_notes = cx
+            .open_window(options, |window, cx| {
+                cx.new(|cx| SpeakerNotesView::new(presentation, window, cx))
+            })
+            .log_err();
+    }
+
+    fn close_speaker_notes(&mut self, cx: &mut App) {
+        if let Some(speaker_notes) = self.speaker_notes.take() {
+            speaker_notes
+                .update(cx, |_, window, _| window.remove_window())
+                .ok();
+        }
+    }
+
+    /// Presents in a fullscreen window, unless the window already was one.
+    fn enter_fullscreen(&mut self, window: &mut Window) {
+        if !window.is_fullscreen() {
+            window.toggle_fullscreen();
+            self.entered_fullscreen = true;
+        }
+    }
+
+    fn leave_fullscreen(&mut self, window: &mut Window) {
+        if std::mem::take(&mut self.entered_fullscreen) && window.is_fullscreen() {
+            window.toggle_fullscreen();
+        }
+    }
+
+    fn exit(&mut self, _: &ExitPresentation, window: &mut Window, cx: &mut Context<Self>) {
+        self.leave_fullscreen(window);
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        let presentation = cx.entity();
+        workspace.update(cx, |workspace, cx| {
+            let Some(pane) = workspace.pane_for(&presentation) else {
+                return;
+            };
+            pane.update(cx, |pane, cx| {
+                if pane.is_zoomed() {
+                    pane.toggle_zoom(&ToggleZoom, window, cx);
+                }
+                pane.close_item_by_id(presentation.entity_id(), SaveIntent::Skip, window, cx)
+                    .detach_and_log_err(cx);
+            });
+        });
+    }
+
+    fn render_slide(&self, window: &mut Window, cx: &mut App) -> AnyElement {
+        let Some(slide) = self.slides.get(self.current_slide) else {
+            return Label::new("Separate the slides of the presentation with `---` lines")
+                .color(Color::Muted)
+                .into_any_element();
+        };
+        let text_style = TextStyleRefinement {
+            font_size: Some(rems(1.5).into()),
+            ..Default::default()
+        };
+        let mut render_cx = window.with_text_style(Some(text_style), |window| {
+            RenderContext::new(Some(self.workspace.clone()), window, cx)
+        });
+        let visible_blocks = if self.step_through_fragments {
+            self.visible_blocks
+        } else {
+            slide.block_count()
+        };
+        v_flex()
+            .w_full()
+            .max_w(rems(64.))
+            .gap_4()
+            .children(
+                slide
+                    .content
+                    .children
+                    .iter()
+                    .take(visible_blocks)
+                    .map(|block| render_markdown_block(block, &mut render_cx)),
+            )
+            .into_any_element()
+    }
+}
+
+impl Focusable for PresentationView {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<()> for PresentationView {}
+
+impl Item for PresentationView {
+    type Event = ();
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::Screen))
+    }
+
+    fn tab_content_text(&self, _window: &Window, cx: &App) -> Option<SharedString> {
+        Some(match self.editor.read(cx).tab_description(0, cx) {
+            Some(description) => format!("Present {description}").into(),
+            None => "Presentation".into(),
+        })
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("Markdown Presentation Started")
+    }
+}
+
+impl Render for PresentationView {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let slide = self.render_slide(window, cx);
+        let mut position = format!(
+            "{} / {}",
+            (self.current_slide + 1).min(self.slides.len()),
+            self.slides.len()
+        );
+        if self.step_through_fragments && self.current_block_count() > 1 {
+            position.push_str(&format!(
+                " · step {} / {}",
+                self.visible_blocks,
+                self.current_block_count()
+            ));
+        }
+
+        v_flex()
+            .id("markdown-presentation")
+            .key_context("MarkdownPresentation")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::next_slide))
+            .on_action(cx.listener(Self::previous_slide))
+            .on_action(cx.listener(Self::first_slide))
+            .on_action(cx.listener(Self::last_slide))
+            .on_action(cx.listener(Self::toggle_fragments))
+            .on_action(cx.listener(Self::toggle_speaker_notes))
+            .on_action(cx.listener(Self::exit))
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(
+                h_flex()
+                    .id("slide")
+                    .flex_1()
+                    .w_full()
+                    .justify_center()
+                    .px_16()
+                    .py_12()
+                    .overflow_y_scroll()
+                    .child(slide),
+            )
+            .child(
+                h_flex().w_full().px_4().pb_2().justify_end().child(
+                    Label::new(position)
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                ),
+            )
+    }
+}
+
+/// A window showing the speaker notes of the current slide, along with the
+/// start of the next one.
+pub struct SpeakerNotesView {
+    presentation: WeakEntity<PresentationView>,
+    focus_handle: FocusHandle,
+    _observe_presentation: Option<Subscription>,
+}
+
+impl SpeakerNotesView {
+    fn new(
+        presentation: WeakEntity<PresentationView>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let observe_presentation = presentation
+            .upgrade()
+            .map(|presentation| cx.observe(&presentation, |_, _, cx| cx.notify()));
+        let focus_handle = cx.focus_handle();
+        window.focus(&focus_handle);
+        Self {
+            presentation,
+            focus_handle,
+            _observe_presentation: observe_presentation,
+        }
+    }
+
+    /// Lets the presenter move through the slides from this window too.
+    fn update_presentation(
+        &mut self,
+        cx: &mut Context<Self>,
+        update: impl FnOnce(&mut PresentationView, &mut Context<PresentationView>),
+    ) {
+        self.presentation.update(cx, update).ok();
+    }
+}
+
+impl Render for SpeakerNotesView {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let mut render_cx = RenderContext::new(None, window, cx);
+        let container = v_flex()
+            .id("speaker-notes")
+            .key_context("MarkdownPresentation")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(|this, _: &NextSlide, _, cx| {
+                this.update_presentation(cx, |presentation, cx| presentation.go_forward(cx))
+            }))
+            .on_action(cx.listener(|this, _: &PreviousSlide, _, cx| {
+                this.update_presentation(cx, |presentation, cx| presentation.go_back(cx))
+            }))
+            .size_full()
+            .gap_4()
+            .p_4()
+            .overflow_y_scroll()
+            .bg(cx.theme().colors().editor_background);
+
+        let Some(presentation) = self.presentation.upgrade() else {
+            return container.child(Label::new("The presentation has ended").color(Color::Muted));
+        };
+        let presentation = presentation.read(cx);
+        let slide_count = presentation.slides.len();
+        let current = presentation.current_slide;
+
+        let notes = presentation
+            .slides
+            .get(current)
+            .and_then(|slide| slide.notes.as_ref())
+            .map(|notes| {
+                v_flex()
+                    .gap_3()
+                    .children(
+                        notes
+                            .children
+                            .iter()
+                            .map(|block| render_markdown_block(block, &mut render_cx)),
+                    )
+                    .into_any_element()
+            })
+            .unwrap_or_else(|| {
+                Label::new("No notes for this slide")
+                    .color(Color::Muted)
+                    .into_any_element()
+            });
+        let next_slide = presentation
+            .slides
+            .get(current + 1)
+            .and_then(|slide| slide.content.children.first())
+            .map(|block| render_markdown_block(block, &mut render_cx));
+
+        container
+            .child(
+                Label::new(format!("Slide {} of {slide_count}", current + 1))
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+            .child(notes)
+            .when_some(next_slide, |this, next_slide| {
+                this.child(
+                    v_flex()
+                        .gap_2()
+                        .pt_4()
+                        .border_t_1()
+                        .border_color(cx.theme().colors().border_variant)
+                        .child(
+                            Label::new("Up next")
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        )
+                        .child(next_slide),
+                )
+            })
+    }
+}
+
+impl Focusable for SpeakerNotesView {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{TestAppContext, VisualTestContext};
+    use language::{Language, LanguageConfig, LanguageMatcher};
+    use project::{FakeFs, Project};
+    use serde_json::json;
+    use util::path;
+    use workspace::AppState;
+
+    #[gpui::test]
+    async fn test_slide_navigation(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/dir"),
+            json!({
+                "deck.md": "# One\n\nFirst\n\nSecond\n\n---\n\n# Two\n\nThird\n",
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs, [path!("/dir").as_ref()], cx).await;
+        project.read_with(cx, |project, _| project.languages().add(markdown_lang()));
+        let (workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project.clone(), window, cx));
+        let worktree_id = project.read_with(cx, |project, cx| {
+            project.worktrees(cx).next().unwrap().read(cx).id()
+        });
+        workspace
+            .update_in(cx, |workspace, window, cx| {
+                workspace.open_path((worktree_id, "deck.md"), None, true, window, cx)
+            })
+            .await
+            .unwrap();
+        cx.run_until_parked();
+
+        cx.dispatch_action(StartPresentation);
+        cx.run_until_parked();
+        let presentation = workspace.update(cx, |workspace, cx| {
+            workspace.active_item_as::<PresentationView>(cx).unwrap()
+        });
+        assert!(cx.update(|window, _| window.is_fullscreen()));
+
+        // Without fragments, whole slides are shown.
+        assert_eq!(position(&presentation, cx), (0, 3));
+        next_slide(&presentation, cx);
+        assert_eq!(position(&presentation, cx), (1, 2));
+        next_slide(&presentation, cx);
+        assert_eq!(position(&presentation, cx), (1, 2));
+        previous_slide(&presentation, cx);
+        assert_eq!(position(&presentation, cx), (0, 3));
+
+        // With fragments, going forward reveals one block at a time, and going
+        // back to a previous slide shows all of it.
+        presentation.update_in(cx, |presentation, window, cx| {
+            presentation.toggle_fragments(&ToggleFragments, window, cx)
+        });
+        assert_eq!(position(&presentation, cx), (0, 3));
+        next_slide(&presentation, cx);
+        assert_eq!(position(&presentation, cx), (1, 1));
+        next_slide(&presentation, cx);
+        assert_eq!(position(&presentation, cx), (1, 2));
+        next_slide(&presentation, cx);
+        assert_eq!(position(&presentation, cx), (1, 2));
+        previous_slide(&presentation, cx);
+        assert_eq!(position(&presentation, cx), (1, 1));
+        previous_slide(&presentation, cx);
+        assert_eq!(position(&presentation, cx), (0, 3));
+        previous_slide(&presentation, cx);
+        assert_eq!(position(&presentation, cx), (0, 2));
+
+        let window_count = |cx: &mut VisualTestContext| cx.update(|_, cx| cx.windows().len());
+        presentation.update_in(cx, |presentation, window, cx| {
+            presentation.toggle_speaker_notes(&ToggleSpeakerNotes, window, cx)
+        });
+        assert_eq!(window_count(cx), 2);
+        presentation.update_in(cx, |presentation, window, cx| {
+            presentation.toggle_speaker_notes(&ToggleSpeakerNotes, window, cx)
+        });
+        cx.run_until_parked();
+        assert_eq!(window_count(cx), 1);
+
+        presentation.update_in(cx, |presentation, window, cx| {
+            presentation.exit(&ExitPresentation, window, cx)
+        });
+        cx.run_until_parked();
+        assert!(!cx.update(|window, _| window.is_fullscreen()));
+        workspace.update(cx, |workspace, cx| {
+            assert!(workspace.active_item_as::<PresentationView>(cx).is_none());
+        });
+    }
+
+    fn position(
+        presentation: &Entity<PresentationView>,
+        cx: &mut VisualTestContext,
+    ) -> (usize, usize) {
+        presentation.read_with(cx, |presentation, _| {
+            (presentation.current_slide, presentation.visible_blocks)
+        })
+    }
+
+    fn next_slide(presentation: &Entity<PresentationView>, cx: &mut VisualTestContext) {
+        presentation.update_in(cx, |presentation, window, cx| {
+            presentation.next_slide(&NextSlide, window, cx)
+        });
+    }
+
+    fn previous_slide(presentation: &Entity<PresentationView>, cx: &mut VisualTestContext) {
+        presentation.update_in(cx, |presentation, window, cx| {
+            presentation.previous_slide(&PreviousSlide, window, cx)
+        });
+    }
+
+    fn markdown_lang() -> Arc<Language> {
+        Arc::new(Language::new(
+            LanguageConfig {
+                name: "Markdown".into(),
+                matcher: LanguageMatcher {
+                    path_suffixes: vec!["md".to_string()],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            None,
+        ))
+    }
+
+    fn init_test(cx: &mut TestAppContext) -> Arc<AppState> {
+        cx.update(|cx| {
+            let state = AppState::test(cx);
+            language::init(cx);
+            editor::init(cx);
+            crate::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+            state
+        })
+    }
+}
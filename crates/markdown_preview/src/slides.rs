@@ -0,0 +1,123 @@
+use std::ops::Range;
+
+/// The prefix of the line that starts a slide's speaker notes.
+const NOTES_PREFIX: &str = "Note:";
+
+/// A slide of a markdown deck, as byte ranges into the deck's text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Slide {
+    /// The whole slide, without the `---` lines around it.
+    pub range: Range<usize>,
+    /// The part of the slide that's presented.
+    pub content: Range<usize>,
+    /// The speaker notes after a `Note:` line, without the prefix.
+    pub notes: Option<Range<usize>>,
+}
+
+/// Splits a deck into slides at `---` lines that follow a blank line, which
+/// keeps `---` heading underlines and frontmatter out of the way.
+pub fn split_slides(text: &str) -> Vec<Slide> {
+    let mut slides = Vec::new();
+    let mut offset = frontmatter_len(text);
+    let mut slide_start = offset;
+    let mut notes_start = None;
+    let mut in_code_block = false;
+    let mut previous_line_blank = true;
+
+    let mut push_slide = |start: usize, end: usize, notes_start: Option<(usize, usize)>| {
+        let slide = match notes_start {
+            Some((line_start, notes_start)) => Slide {
+                range: start..end,
+                content: start..line_start,
+                notes: Some(notes_start..end),
+            },
+            None => Slide {
+                range: start..end,
+                content: start..end,
+                notes: None,
+            },
+        };
+        if !text[slide.range.clone()].trim().is_empty() {
+            slides.push(slide);
+        }
+    };
+
+    for line in text[offset..].split_inclusive('\n') {
+        let line_start = offset;
+        offset += line.len();
+        let content = line.trim_end();
+
+        if content.starts_with("```") || content.starts_with("~~~") {
+            in_code_block = !in_code_block;
+        } else if !in_code_block {
+            if content == "---" && previous_line_blank {
+                push_slide(slide_start, line_start, notes_start.take());
+                slide_start = offset;
+            } else if notes_start.is_none() && content.starts_with(NOTES_PREFIX) {
+                notes_start = Some((line_start, line_start + NOTES_PREFIX.len()));
+            }
+        }
+        previous_line_blank = content.is_empty();
+    }
+    push_slide(slide_start, text.len(), notes_start);
+    slides
+}
+
+/// Returns the index of the slide that `offset` is in, or the closest one
+/// before it.
+pub fn slide_at_offset(slides: &[Slide], offset: usize) -> usize {
+    slides
+        .iter()
+        .rposition(|slide| slide.range.start <= offset)
+        .unwrap_or(0)
+}
+
+/// The length of the `---` fenced frontmatter at the start of `text`.
+fn frontmatter_len(text: &str) -> usize {
+    let Some(rest) = text.strip_prefix("---\n") else {
+        return 0;
+    };
+    let mut offset = 4;
+    for line in rest.split_inclusive('\n') {
+        offset += line.len();
+        if line.trim_end() == "---" {
+            return offset;
+        }
+    }
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_split_slides() {
+        let text = "---\ntitle: Weekly\n---\n# Weekly\n\n---\n\nStatus\n---\n\n```\n\n---\n```\nNote: mention the\nbuild times\n\n---\n\n";
+        let slides = split_slides(text);
+        let contents = slides
+            .iter()
+            .map(|slide| {
+                (
+                    &text[slide.content.clone()],
+                    slide.notes.clone().map(|notes| &text[notes]),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            contents,
+            [
+                ("# Weekly\n\n", None),
+                (
+                    "\nStatus\n---\n\n```\n\n---\n```\n",
+                    Some(" mention the\nbuild times\n\n")
+                ),
+            ]
+        );
+
+        assert_eq!(slide_at_offset(&slides, 0), 0);
+        assert_eq!(slide_at_offset(&slides, text.find("Status").unwrap()), 1);
+        assert_eq!(slide_at_offset(&slides, text.len()), 1);
+    }
+}
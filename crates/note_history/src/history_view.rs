@@ -0,0 +1,386 @@
+use std::path::PathBuf;
+
+use anyhow::{Result, anyhow};
+use buffer_diff::BufferDiff;
+use chrono::{Local, TimeZone as _};
+use editor::Editor;
+use git::repository::RepoPath;
+use gpui::{
+    AnyElement, App, ClipboardItem, Entity, EventEmitter, FocusHandle, Focusable, Subscription,
+    Task,
+};
+use language::{Buffer, BufferEvent, LineEnding, Rope};
+use multi_buffer::MultiBuffer;
+use project::git_store::Repository;
+use ui::{KeyBinding, ListItem, ListItemSpacing, prelude::*};
+use util::ResultExt as _;
+use workspace::item::Item;
+
+use crate::{
+    CopyHunk, NOTE_HISTORY_DB, NoteVersion, RestoreVersion, VersionSource, merge_versions,
+};
+
+const VERSION_LIST_WIDTH: f32 = 280.;
+
+/// A tab listing the prior versions of a note, from git and from snapshots
+/// taken on save, and showing how the selected one differs from the note.
+///
+/// The diff is against the note's open buffer, so it follows unsaved edits,
+/// and restoring a version is an edit of the buffer that can be undone.
+pub struct NoteHistoryView {
+    buffer: Entity<Buffer>,
+    abs_path: PathBuf,
+    repository: Option<(Entity<Repository>, RepoPath)>,
+    /// The versions, or `None` while they're loading.
+    versions: Option<Vec<NoteVersion>>,
+    selected: Option<usize>,
+    /// The text of the selected version.
+    base_text: Option<String>,
+    diff: Entity<BufferDiff>,
+    editor: Entity<Editor>,
+    focus_handle: FocusHandle,
+    _load_versions: Task<()>,
+    _load_version: Task<()>,
+    _update_diff: Task<()>,
+    _buffer_subscription: Subscription,
+}
+
+impl NoteHistoryView {
+    pub fn new(
+        buffer: Entity<Buffer>,
+        abs_path: PathBuf,
+        repository: Option<(Entity<Repository>, RepoPath)>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let diff = cx.new(|cx| BufferDiff::new(&buffer.read(cx).text_snapshot(), cx));
+        let multibuffer = cx.new(|cx| {
+            let mut multibuffer = MultiBuffer::singleton(buffer.clone(), cx);
+            multibuffer.add_diff(diff.clone(), cx);
+            multibuffer
+        });
+        let editor = cx.new(|cx| {
+            let mut editor = Editor::for_multibuffer(multibuffer, None, window, cx);
+            editor.set_read_only(true);
+            editor.set_expand_all_diff_hunks(cx);
+            editor
+        });
+
+        let git_history = repository
+            .as_ref()
+            .map(|(repository, path)| repository.read(cx).file_history(path.clone()));
+        let snapshots = cx.background_spawn({
+            let abs_path = abs_path.clone();
+            async move { NOTE_HISTORY_DB.snapshots(abs_path) }
+        });
+        let load_versions = cx.spawn(async move |this, cx| {
+            let commits = match git_history {
+                Some(git_history) => git_history
+                    .await
+                    .map_err(|_| anyhow!("loading the note's git history was canceled"))
+                    .and_then(|history| history)
+                    .log_err()
+                    .unwrap_or_default(),
+                None => Vec::new(),
+            };
+            let snapshots = snapshots.await.log_err().unwrap_or_default();
+            this.update(cx, |this, cx| {
+                this.versions = Some(merge_versions(commits, snapshots));
+                cx.notify();
+            })
+            .ok();
+        });
+
+        let buffer_subscription = cx.subscribe(&buffer, |this, _, event, cx| {
+            if let BufferEvent::Edited = event {
+                this.update_diff(cx);
+            }
+        });
+
+        Self {
+            buffer,
+            abs_path,
+            repository,
+            versions: None,
+            selected: None,
+            base_text: None,
+            diff,
+            editor,
+            focus_handle: cx.focus_handle(),
+            _load_versions: load_versions,
+            _load_version: Task::ready(()),
+            _update_diff: Task::ready(()),
+            _buffer_subscription: buffer_subscription,
+        }
+    }
+
+    fn select_version(&mut self, ix: usize, cx: &mut Context<Self>) {
+        let Some(version) = self.versions.as_ref().and_then(|versions| versions.get(ix)) else {
+            return;
+        };
+        self.selected = Some(ix);
+        let text: Task<Result<Option<String>>> = match &version.source {
+            VersionSource::Commit { sha, path, .. } => {
+                let Some((repository, _)) = &self.repository else {
+                    return;
+                };
+                let text = repository
+                    .read(cx)
+                    .load_text_at_commit(sha.to_string(), path.clone());
+                cx.background_spawn(async move { text.await? })
+            }
+            VersionSource::Snapshot { id } => {
+                let id = *id;
+                cx.background_spawn(async move { NOTE_HISTORY_DB.snapshot_content(id) })
+            }
+        };
+        self._load_version = cx.spawn(async move |this, cx| {
+            // A version without the note, from before it was created or after
+            // it was deleted, is empty.
+            let Some(mut text) = text.await.log_err().map(Option::unwrap_or_default) else {
+                return;
+            };
+            LineEnding::normalize(&mut text);
+            this.update(cx, |this, cx| {
+                this.base_text = Some(text);
+                this.update_diff(cx);
+            })
+            .ok();
+        });
+        cx.notify();
+    }
+
+    fn update_diff(&mut self, cx: &mut Context<Self>) {
+        let Some(base_text) = &self.base_text else {
+            return;
+        };
+        let buffer = self.buffer.read(cx);
+        let language_registry = buffer.language_registry();
+        let buffer_snapshot = buffer.text_snapshot();
+        let base_buffer = Buffer::build_snapshot(
+            Rope::from(base_text.as_str()),
+            buffer.language().cloned(),
+            language_registry.clone(),
+            cx,
+        );
+        let diff = self.diff.clone();
+        self._update_diff = cx.spawn(async move |_, cx| {
+            let base_buffer = base_buffer.await;
+            let Ok(updated) = diff.update(cx, |diff, cx| {
+                diff.set_base_text(base_buffer, language_registry, buffer_snapshot, cx)
+            }) else {
+                return;
+            };
+            updated.await.ok();
+        });
+    }
+
+    fn restore_version(&mut self, _: &RestoreVersion, _: &mut Window, cx: &mut Context<Self>) {
+        let Some(base_text) = self.base_text.clone() else {
+            return;
+        };
+        let diff = self.buffer.read(cx).diff(base_text, cx);
+        let buffer = self.buffer.clone();
+        cx.spawn(async move |_, cx| {
+            let diff = diff.await;
+            buffer.update(cx, |buffer, cx| buffer.apply_diff(diff, cx))?;
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Copies the selected version's side of the changes under the cursor.
+    fn copy_hunk(&mut self, _: &CopyHunk, _: &mut Window, cx: &mut Context<Self>) {
+        let Some(base_text) = &self.base_text else {
+            return;
+        };
+        let cursor = self
+            .editor
+            .read(cx)
+            .selections
+            .newest_anchor()
+            .head()
+            .text_anchor;
+        let buffer_snapshot = self.buffer.read(cx).text_snapshot();
+        let text = self
+            .diff
+            .read(cx)
+            .hunks_intersecting_range(cursor..cursor, &buffer_snapshot, cx)
+            .filter_map(|hunk| base_text.get(hunk.diff_base_byte_range))
+            .collect::<String>();
+        if !text.is_empty() {
+            cx.write_to_clipboard(ClipboardItem::new_string(text));
+        }
+    }
+
+    fn render_versions(&self, cx: &mut Context<Self>) -> AnyElement {
+        let Some(versions) = &self.versions else {
+            return Label::new("Loading history…")
+                .color(Color::Muted)
+                .into_any_element();
+        };
+        if versions.is_empty() {
+            return Label::new(
+                "No history yet. Versions appear when the note is committed or saved.",
+            )
+            .color(Color::Muted)
+            .into_any_element();
+        }
+        v_flex()
+            .children(versions.iter().enumerate().map(|(ix, version)| {
+                let (icon, detail) = match &version.source {
+                    VersionSource::Commit {
+                        sha, author_name, ..
+                    } => (
+                        IconName::GitBranch,
+                        format!("{} · {author_name}", &sha[..sha.len().min(7)]),
+                    ),
+                    VersionSource::Snapshot { .. } => {
+                        (IconName::HistoryRerun, "Local snapshot".to_string())
+                    }
+                };
+                ListItem::new(ix)
+                    .inset(true)
+                    .spacing(ListItemSpacing::Sparse)
+                    .toggle_state(self.selected == Some(ix))
+                    .start_slot(Icon::new(icon).color(Color::Muted))
+                    .on_click(cx.listener(move |this, _, _, cx| this.select_version(ix, cx)))
+                    .child(
+                        v_flex()
+                            .child(Label::new(version.title()).truncate())
+                            .child(
+                                Label::new(format!(
+                                    "{} · {detail}",
+                                    format_timestamp(version.timestamp)
+                                ))
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                            ),
+                    )
+            }))
+            .into_any_element()
+    }
+}
+
+fn format_timestamp(timestamp: i64) -> String {
+    Local
+        .timestamp_opt(timestamp, 0)
+        .single()
+        .map(|time| time.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default()
+}
+
+impl EventEmitter<()> for NoteHistoryView {}
+
+impl Focusable for NoteHistoryView {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for NoteHistoryView {
+    type Event = ();
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::HistoryRerun))
+    }
+
+    fn tab_content_text(&self, _window: &Window, _cx: &App) -> Option<SharedString> {
+        let name = self
+            .abs_path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned());
+        Some(match name {
+            Some(name) => format!("History: {name}").into(),
+            None => "History".into(),
+        })
+    }
+}
+
+impl Render for NoteHistoryView {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let selected = self
+            .selected
+            .zip(self.versions.as_ref())
+            .and_then(|(ix, versions)| versions.get(ix));
+        let comparison = match selected {
+            None => h_flex()
+                .size_full()
+                .justify_center()
+                .child(
+                    Label::new("Select a version to compare it with the note").color(Color::Muted),
+                )
+                .into_any_element(),
+            Some(version) => v_flex()
+                .size_full()
+                .child(
+                    h_flex()
+                        .p_2()
+                        .gap_2()
+                        .justify_between()
+                        .border_b_1()
+                        .border_color(cx.theme().colors().border_variant)
+                        .child(
+                            Label::new(format!(
+                                "Changes since {} ({})",
+                                format_timestamp(version.timestamp),
+                                version.title()
+                            ))
+                            .truncate(),
+                        )
+                        .child(
+                            h_flex()
+                                .gap_1()
+                                .child(
+                                    Button::new("copy-hunk", "Copy Hunk")
+                                        .key_binding(KeyBinding::for_action_in(
+                                            &CopyHunk,
+                                            &self.focus_handle,
+                                            window,
+                                            cx,
+                                        ))
+                                        .on_click(cx.listener(|this, _, window, cx| {
+                                            this.copy_hunk(&CopyHunk, window, cx)
+                                        })),
+                                )
+                                .child(
+                                    Button::new("restore-version", "Restore Version")
+                                        .style(ButtonStyle::Filled)
+                                        .key_binding(KeyBinding::for_action_in(
+                                            &RestoreVersion,
+                                            &self.focus_handle,
+                                            window,
+                                            cx,
+                                        ))
+                                        .on_click(cx.listener(|this, _, window, cx| {
+                                            this.restore_version(&RestoreVersion, window, cx)
+                                        })),
+                                ),
+                        ),
+                )
+                .child(div().flex_1().child(self.editor.clone()))
+                .into_any_element(),
+        };
+
+        h_flex()
+            .key_context("NoteHistory")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::restore_version))
+            .on_action(cx.listener(Self::copy_hunk))
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(
+                v_flex()
+                    .id("note-versions")
+                    .flex_none()
+                    .w(px(VERSION_LIST_WIDTH))
+                    .h_full()
+                    .p_1()
+                    .overflow_y_scroll()
+                    .border_r_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .child(self.render_versions(cx)),
+            )
+            .child(comparison)
+    }
+}
@@ -0,0 +1,141 @@
+//! A timeline of a note's prior versions, from the commits that changed it and
+//! from snapshots taken in the local database whenever it's saved, so notes
+//! outside of git repositories have a history too.
+
+mod history_view;
+mod persistence;
+mod versions;
+
+use anyhow::Result;
+use chrono::Utc;
+use editor::Editor;
+use gpui::{App, AppContext as _, Context, Window, actions};
+use language::{Buffer, BufferEvent};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+use vault::is_note;
+use workspace::Workspace;
+
+pub use history_view::NoteHistoryView;
+pub use persistence::{NOTE_HISTORY_DB, NoteHistoryDb, Retention};
+pub use versions::{NoteVersion, VersionSource, merge_versions};
+
+actions!(note_history, [OpenNoteHistory, RestoreVersion, CopyHunk]);
+
+/// Notes larger than this aren't snapshotted.
+const MAX_SNAPSHOT_LEN: usize = 1024 * 1024;
+
+/// Settings for the history of notes.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct NoteHistorySettings {
+    /// Whether to snapshot notes in the local database whenever they're
+    /// saved.
+    ///
+    /// Default: true
+    pub snapshots: Option<bool>,
+    /// The number of snapshots to keep for each note.
+    ///
+    /// Default: 50
+    pub max_snapshots: Option<usize>,
+    /// The number of days to keep snapshots for.
+    ///
+    /// Default: 30
+    pub max_age_days: Option<u32>,
+}
+
+impl NoteHistorySettings {
+    pub fn snapshots(&self) -> bool {
+        self.snapshots.unwrap_or(true)
+    }
+
+    pub fn retention(&self) -> Retention {
+        Retention {
+            max_snapshots: self.max_snapshots.unwrap_or(50),
+            max_age_days: self.max_age_days.unwrap_or(30),
+        }
+    }
+}
+
+impl settings::Settings for NoteHistorySettings {
+    const KEY: Option<&'static str> = Some("note_history");
+
+    type FileContent = Self;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _: &mut App) -> Result<Self> {
+        sources.json_merge()
+    }
+}
+
+pub fn init(cx: &mut App) {
+    NoteHistorySettings::register(cx);
+
+    cx.observe_new(|_: &mut Buffer, _window, cx| {
+        cx.subscribe_self(|buffer, event: &BufferEvent, cx| {
+            if let BufferEvent::Saved = event {
+                record_snapshot(buffer, cx);
+            }
+        })
+        .detach();
+    })
+    .detach();
+
+    cx.observe_new(|workspace: &mut Workspace, _window, _cx| {
+        workspace.register_action(open_note_history);
+    })
+    .detach();
+}
+
+fn record_snapshot(buffer: &Buffer, cx: &mut Context<Buffer>) {
+    let settings = NoteHistorySettings::get_global(cx);
+    if !settings.snapshots() || buffer.len() > MAX_SNAPSHOT_LEN {
+        return;
+    }
+    let Some(file) = buffer.file().and_then(|file| file.as_local()) else {
+        return;
+    };
+    let abs_path = file.abs_path(cx);
+    if !is_note(&abs_path) {
+        return;
+    }
+    let retention = settings.retention();
+    let text = buffer.text();
+    let saved_at = Utc::now().timestamp();
+    cx.background_spawn(async move {
+        NOTE_HISTORY_DB
+            .record_snapshot(abs_path, saved_at, text, retention)
+            .await
+    })
+    .detach_and_log_err(cx);
+}
+
+/// Opens the history of the active editor's file.
+fn open_note_history(
+    workspace: &mut Workspace,
+    _: &OpenNoteHistory,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let Some(editor) = workspace.active_item_as::<Editor>(cx) else {
+        return;
+    };
+    let Some(buffer) = editor.read(cx).buffer().read(cx).as_singleton() else {
+        return;
+    };
+    let Some(abs_path) = buffer
+        .read(cx)
+        .file()
+        .and_then(|file| file.as_local())
+        .map(|file| file.abs_path(cx))
+    else {
+        return;
+    };
+    let repository = workspace
+        .project()
+        .read(cx)
+        .git_store()
+        .read(cx)
+        .repository_and_path_for_buffer_id(buffer.read(cx).remote_id(), cx);
+    let history = cx.new(|cx| NoteHistoryView::new(buffer, abs_path, repository, window, cx));
+    workspace.add_item_to_active_pane(Box::new(history), None, true, window, cx);
+}
@@ -0,0 +1,156 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use db::{define_connection, query, sqlez_macros::sql};
+
+/// How many snapshots of each note to keep.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Retention {
+    pub max_snapshots: usize,
+    pub max_age_days: u32,
+}
+
+define_connection!(pub static ref NOTE_HISTORY_DB: NoteHistoryDb<()> =
+    &[sql!(
+        CREATE TABLE IF NOT EXISTS note_snapshots(
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            path TEXT NOT NULL,
+            saved_at INTEGER NOT NULL,
+            content TEXT NOT NULL
+        ) STRICT;
+
+        CREATE INDEX IF NOT EXISTS note_snapshots_by_path ON note_snapshots(path, saved_at);
+    )];
+);
+
+impl NoteHistoryDb {
+    /// Saves a snapshot of a note unless it's unchanged since the last one,
+    /// then drops the note's snapshots that are past the retention limits.
+    /// `saved_at` is a unix timestamp.
+    pub async fn record_snapshot(
+        &self,
+        path: PathBuf,
+        saved_at: i64,
+        content: String,
+        retention: Retention,
+    ) -> Result<()> {
+        if self.latest_snapshot_content(path.clone())?.as_ref() != Some(&content) {
+            self.insert_snapshot(path.clone(), saved_at, content)
+                .await?;
+        }
+        let cutoff = saved_at - i64::from(retention.max_age_days) * 24 * 60 * 60;
+        self.prune_snapshots(path, retention.max_snapshots as i64, cutoff)
+            .await
+    }
+
+    // The id and unix timestamp of each snapshot of a note, newest first.
+    query! {
+        pub fn snapshots(path: PathBuf) -> Result<Vec<(i64, i64)>> {
+            SELECT id, saved_at FROM note_snapshots
+            WHERE path = (?)
+            ORDER BY saved_at DESC, id DESC
+        }
+    }
+
+    query! {
+        pub fn snapshot_content(id: i64) -> Result<Option<String>> {
+            SELECT content FROM note_snapshots WHERE id = (?)
+        }
+    }
+
+    query! {
+        fn latest_snapshot_content(path: PathBuf) -> Result<Option<String>> {
+            SELECT content FROM note_snapshots
+            WHERE path = (?)
+            ORDER BY saved_at DESC, id DESC
+            LIMIT 1
+        }
+    }
+
+    query! {
+        async fn insert_snapshot(path: PathBuf, saved_at: i64, content: String) -> Result<()> {
+            INSERT INTO note_snapshots (path, saved_at, content) VALUES (?, ?, ?)
+        }
+    }
+
+    query! {
+        async fn prune_snapshots(path: PathBuf, keep: i64, cutoff: i64) -> Result<()> {
+            DELETE FROM note_snapshots
+            WHERE path = ?1 AND (
+                saved_at < ?3 OR id NOT IN (
+                    SELECT id FROM note_snapshots
+                    WHERE path = ?1
+                    ORDER BY saved_at DESC, id DESC
+                    LIMIT ?2
+                )
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[gpui::test]
+    async fn test_snapshot_retention() {
+        let db = NoteHistoryDb(db::open_test_db("test_snapshot_retention").await);
+        let path = PathBuf::from("/vault/Ideas.md");
+        let retention = Retention {
+            max_snapshots: 2,
+            max_age_days: 1,
+        };
+        let day = 24 * 60 * 60;
+
+        db.record_snapshot(path.clone(), 100, "one".into(), retention)
+            .await
+            .unwrap();
+        // Saving without changes doesn't add a snapshot.
+        db.record_snapshot(path.clone(), 200, "one".into(), retention)
+            .await
+            .unwrap();
+        db.record_snapshot(path.clone(), 300, "two".into(), retention)
+            .await
+            .unwrap();
+        let snapshots = db.snapshots(path.clone()).unwrap();
+        assert_eq!(
+            snapshots
+                .iter()
+                .map(|(_, saved_at)| *saved_at)
+                .collect::<Vec<_>>(),
+            [300, 100]
+        );
+        assert_eq!(
+            db.snapshot_content(snapshots[0].0).unwrap().as_deref(),
+            Some("two")
+        );
+
+        // Only the newest snapshots are kept.
+        db.record_snapshot(path.clone(), 400, "three".into(), retention)
+            .await
+            .unwrap();
+        assert_eq!(
+            db.snapshots(path.clone())
+                .unwrap()
+                .iter()
+                .map(|(_, saved_at)| *saved_at)
+                .collect::<Vec<_>>(),
+            [400, 300]
+        );
+
+        // And old ones are dropped.
+        db.record_snapshot(path.clone(), 350 + day, "four".into(), retention)
+            .await
+            .unwrap();
+        assert_eq!(
+            db.snapshots(path.clone())
+                .unwrap()
+                .iter()
+                .map(|(_, saved_at)| *saved_at)
+                .collect::<Vec<_>>(),
+            [350 + day, 400]
+        );
+        assert_eq!(db.snapshots(PathBuf::from("/vault/Other.md")).unwrap(), []);
+    }
+}
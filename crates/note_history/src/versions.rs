@@ -0,0 +1,95 @@
+use git::repository::{FileHistoryEntry, RepoPath};
+use gpui::SharedString;
+
+/// A prior version of a note.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NoteVersion {
+    /// This is a unix timestamp
+    pub timestamp: i64,
+    pub source: VersionSource,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VersionSource {
+    /// The note as of a commit that changed it.
+    Commit {
+        sha: SharedString,
+        subject: SharedString,
+        author_name: SharedString,
+        /// The note's path in the commit's repository at the time.
+        path: RepoPath,
+    },
+    /// A snapshot taken when the note was saved.
+    Snapshot { id: i64 },
+}
+
+impl NoteVersion {
+    pub fn title(&self) -> SharedString {
+        match &self.source {
+            VersionSource::Commit { subject, .. } => subject.clone(),
+            VersionSource::Snapshot { .. } => "Saved".into(),
+        }
+    }
+}
+
+/// Interleaves a note's commits and snapshots, newest first. Snapshots taken
+/// when saving a version that was then committed are kept, since they show
+/// when the note was actually written.
+pub fn merge_versions(
+    commits: Vec<FileHistoryEntry>,
+    snapshots: Vec<(i64, i64)>,
+) -> Vec<NoteVersion> {
+    let mut versions = commits
+        .into_iter()
+        .map(|entry| NoteVersion {
+            timestamp: entry.commit_timestamp,
+            source: VersionSource::Commit {
+                sha: entry.sha,
+                subject: entry.subject,
+                author_name: entry.author_name,
+                path: entry.path,
+            },
+        })
+        .chain(snapshots.into_iter().map(|(id, saved_at)| NoteVersion {
+            timestamp: saved_at,
+            source: VersionSource::Snapshot { id },
+        }))
+        .collect::<Vec<_>>();
+    // The sort is stable, so commits made in the same second stay in git's order.
+    versions.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+    versions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_merge_versions() {
+        let commit = |sha: &str, timestamp| FileHistoryEntry {
+            sha: sha.to_string().into(),
+            subject: format!("Commit {sha}").into(),
+            commit_timestamp: timestamp,
+            author_name: "Zed".into(),
+            path: RepoPath::from("Ideas.md"),
+        };
+        let versions = merge_versions(
+            vec![commit("b", 300), commit("a", 100)],
+            vec![(7, 400), (6, 300), (5, 200)],
+        );
+        assert_eq!(
+            versions
+                .iter()
+                .map(|version| (version.timestamp, version.title()))
+                .collect::<Vec<_>>(),
+            [
+                (400, "Saved".into()),
+                (300, "Commit b".into()),
+                (300, "Saved".into()),
+                (200, "Saved".into()),
+                (100, "Commit a".into()),
+            ]
+        );
+    }
+}
@@ -1,101 +1,199 @@
-use gpui::{div, AnyElement, Bounds, Element, ElementId, IntoElement, Render, View, ViewContext, WindowContext};
-use petgraph::graph::{Graph, NodeIndex};
-use std::{collections::HashMap, path::PathBuf};
+use std::f32::consts::TAU;
 
+use gpui::{
+    AnyElement, App, Entity, EventEmitter, FocusHandle, Focusable, PathBuilder, Point,
+    Subscription, WeakEntity, canvas, point, relative,
+};
+use ui::prelude::*;
+use util::ResultExt as _;
+use workspace::{Workspace, item::Item};
+
+use crate::{GraphNode, VaultGraph, open_note};
+
+/// The diameter of the dot drawn for each node.
+const NODE_SIZE: f32 = 8.;
+
+/// A tab showing the notes of the vault and the links between them.
 pub struct GraphView {
-    graph: Graph<String, ()>,
-    node_positions: HashMap<NodeIndex, (f64, f64)>,
-    bounds: Bounds<f64>,
-    zoom_level: f64,
+    vault_graph: Entity<VaultGraph>,
+    workspace: WeakEntity<Workspace>,
+    layout: GraphLayout,
+    focus_handle: FocusHandle,
+    _observe_graph: Subscription,
+}
+
+struct GraphLayout {
+    nodes: Vec<GraphNode>,
+    edges: Vec<(usize, usize)>,
+    /// The center of each node, relative to the size of the view.
+    positions: Vec<Point<f32>>,
+}
+
+impl GraphLayout {
+    /// Places the nodes on a circle, in the order of their paths so notes in the
+    /// same folder end up next to each other.
+    fn new(vault_graph: &VaultGraph) -> Self {
+        let (nodes, edges) = vault_graph.graph().nodes_and_edges();
+        let positions = (0..nodes.len())
+            .map(|ix| {
+                let angle = TAU * ix as f32 / nodes.len() as f32;
+                point(0.5 + 0.4 * angle.cos(), 0.5 + 0.4 * angle.sin())
+            })
+            .collect();
+        Self {
+            nodes,
+            edges,
+            positions,
+        }
+    }
 }
 
 impl GraphView {
-    pub fn new(graph: Graph<String, ()>) -> Self {
+    pub fn new(
+        vault_graph: Entity<VaultGraph>,
+        workspace: WeakEntity<Workspace>,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let observe_graph = cx.observe(&vault_graph, |this, vault_graph, cx| {
+            this.layout = GraphLayout::new(vault_graph.read(cx));
+            cx.notify();
+        });
         Self {
-            graph,
-            node_positions: HashMap::new(),
-            bounds: Bounds {
-                origin: gpui::Point::new(0.0, 0.0),
-                size: gpui::Size::new(800.0, 600.0),
-            },
-            zoom_level: 1.0,
+            layout: GraphLayout::new(vault_graph.read(cx)),
+            vault_graph,
+            workspace,
+            focus_handle: cx.focus_handle(),
+            _observe_graph: observe_graph,
         }
     }
 
-    fn calculate_layout(&mut self) {
-        // Implement force-directed graph layout
-        // This is a simplified version - we'll need to implement proper force-directed
-        // layout algorithms like Fruchterman-Reingold for better visualization
-        let node_count = self.graph.node_count();
-        if node_count == 0 {
+    pub fn vault_graph(&self) -> &Entity<VaultGraph> {
+        &self.vault_graph
+    }
+
+    fn open_node(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(node) = self.layout.nodes.get(ix).cloned() else {
             return;
-        }
+        };
+        let Some(worktree_id) = self.vault_graph.read(cx).worktree_id() else {
+            return;
+        };
+        self.workspace
+            .update(cx, |workspace, cx| {
+                open_note(workspace, worktree_id, &node, None, window, cx).detach_and_log_err(cx);
+            })
+            .log_err();
+    }
 
-        // Place nodes in a circle initially
-        let radius = self.bounds.size.width.min(self.bounds.size.height) * 0.4;
-        let center_x = self.bounds.size.width / 2.0;
-        let center_y = self.bounds.size.height / 2.0;
+    fn render_node(&self, ix: usize, cx: &mut Context<Self>) -> AnyElement {
+        let position = self.layout.positions[ix];
+        let (label, color) = match &self.layout.nodes[ix] {
+            GraphNode::Note(path) => (
+                path.file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+                cx.theme().colors().icon_accent,
+            ),
+            GraphNode::Unresolved(target) => (target.clone(), cx.theme().colors().icon_muted),
+        };
+        h_flex()
+            .id(("graph-node", ix))
+            .absolute()
+            .left(relative(position.x))
+            .top(relative(position.y))
+            .ml(px(-NODE_SIZE / 2.))
+            .mt(px(-NODE_SIZE))
+            .gap_1()
+            .cursor_pointer()
+            .child(div().size(px(NODE_SIZE)).rounded_full().bg(color))
+            .child(
+                Label::new(label)
+                    .size(LabelSize::Small)
+                    .color(match &self.layout.nodes[ix] {
+                        GraphNode::Note(_) => Color::Default,
+                        GraphNode::Unresolved(_) => Color::Muted,
+                    }),
+            )
+            .on_click(cx.listener(move |this, _, window, cx| this.open_node(ix, window, cx)))
+            .into_any_element()
+    }
+}
 
-        for (i, node_idx) in self.graph.node_indices().enumerate() {
-            let angle = 2.0 * std::f64::consts::PI * (i as f64) / (node_count as f64);
-            let x = center_x + radius * angle.cos();
-            let y = center_y + radius * angle.sin();
-            self.node_positions.insert(node_idx, (x, y));
-        }
+impl EventEmitter<()> for GraphView {}
+
+impl Focusable for GraphView {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
     }
 }
 
-impl Render for GraphView {
-    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
-        if self.node_positions.is_empty() {
-            self.calculate_layout();
-        }
+impl Item for GraphView {
+    type Event = ();
 
-        let mut elements = Vec::new();
-
-        // Render edges
-        for edge in self.graph.edge_indices() {
-            if let Some((source, target)) = self.graph.edge_endpoints(edge) {
-                if let (Some(&(x1, y1)), Some(&(x2, y2))) = (
-                    self.node_positions.get(&source),
-                    self.node_positions.get(&target),
-                ) {
-                    elements.push(
-                        div()
-                            .absolute()
-                            .left(x1)
-                            .top(y1)
-                            .width(x2 - x1)
-                            .height(y2 - y1)
-                            .border_color(gpui::rgb(0x666666))
-                            .border_width(1.0)
-                            .into_any_element(),
-                    );
-                }
-            }
-        }
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::Link))
+    }
 
-        // Render nodes
-        for (node_idx, (x, y)) in &self.node_positions {
-            if let Some(label) = self.graph.node_weight(*node_idx) {
-                elements.push(
-                    div()
-                        .absolute()
-                        .left(*x - 25.0)
-                        .top(*y - 25.0)
-                        .width(50.0)
-                        .height(50.0)
-                        .background(gpui::rgb(0x2a2a2a))
-                        .border_radius(25.0)
-                        .child(label)
-                        .into_any_element(),
-                );
-            }
-        }
+    fn tab_content_text(&self, _window: &Window, _cx: &App) -> Option<SharedString> {
+        Some("Graph".into())
+    }
+}
+
+impl Render for GraphView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let edge_color = cx.theme().colors().border;
+        let edges = self
+            .layout
+            .edges
+            .iter()
+            .map(|&(source, target)| (self.layout.positions[source], self.layout.positions[target]))
+            .collect::<Vec<_>>();
 
         div()
+            .id("graph-view")
+            .key_context("GraphView")
+            .track_focus(&self.focus_handle)
+            .relative()
             .size_full()
-            .background(gpui::rgb(0x1a1a1a))
-            .children(elements)
+            .overflow_hidden()
+            .bg(cx.theme().colors().editor_background)
+            .child(
+                canvas(
+                    |_, _, _| {},
+                    move |bounds, _, window, _| {
+                        let to_pixels = |position: Point<f32>| {
+                            bounds.origin
+                                + point(
+                                    bounds.size.width * position.x,
+                                    bounds.size.height * position.y,
+                                )
+                        };
+                        for (source, target) in edges {
+                            let mut builder = PathBuilder::stroke(px(1.));
+                            builder.move_to(to_pixels(source));
+                            builder.line_to(to_pixels(target));
+                            if let Ok(path) = builder.build() {
+                                window.paint_path(path, edge_color);
+                            }
+                        }
+                    },
+                )
+                .absolute()
+                .size_full(),
+            )
+            .children((0..self.layout.nodes.len()).map(|ix| self.render_node(ix, cx)))
+            .when(self.layout.nodes.is_empty(), |this| {
+                let message = if self.vault_graph.read(cx).is_loading() {
+                    "Loading notes…"
+                } else {
+                    "No notes to show"
+                };
+                this.child(
+                    h_flex()
+                        .size_full()
+                        .justify_center()
+                        .child(Label::new(message).color(Color::Muted)),
+                )
+            })
     }
-}
\ No newline at end of file
+}
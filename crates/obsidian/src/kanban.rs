@@ -0,0 +1,391 @@
+use std::ops::Range;
+
+use vault::{inline_tags, wiki_links};
+
+/// A kanban board read from a markdown file, where each `## Column` heading
+/// starts a column and the `- [ ] card` items below it are its cards.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Board {
+    pub columns: Vec<Column>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Column {
+    pub title: String,
+    /// The range of the heading line, including its newline.
+    pub heading_range: Range<usize>,
+    pub cards: Vec<Card>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Card {
+    /// The lines of the card, including indented lines below the item and the
+    /// trailing newline.
+    pub range: Range<usize>,
+    pub checked: bool,
+    /// The text of the item without its metadata.
+    pub title: String,
+    pub metadata: Vec<CardMetadata>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CardMetadata {
+    Tag(String),
+    /// A `YYYY-MM-DD` date, optionally written as `@2024-05-01`,
+    /// `@{2024-05-01}` or `📅 2024-05-01`.
+    Date(String),
+    /// The display text of a wiki link.
+    Link(String),
+}
+
+impl Board {
+    pub fn parse(text: &str) -> Self {
+        let mut columns: Vec<Column> = Vec::new();
+        let mut offset = 0;
+        for line in text.split_inclusive('\n') {
+            let line_range = offset..offset + line.len();
+            offset += line.len();
+            let content = line.trim_end_matches(['\n', '\r']);
+
+            if let Some(title) = content.strip_prefix("## ") {
+                columns.push(Column {
+                    title: title.trim().to_string(),
+                    heading_range: line_range,
+                    cards: Vec::new(),
+                });
+                continue;
+            }
+            if content.starts_with('#') && content.trim_start_matches('#').starts_with(' ') {
+                // Other headings after the columns end the board, e.g. an
+                // archive.
+                if !columns.is_empty() {
+                    break;
+                }
+                continue;
+            }
+            let Some(column) = columns.last_mut() else {
+                continue;
+            };
+            if let Some((checked, item)) = parse_task(content) {
+                let (title, metadata) = parse_metadata(item);
+                column.cards.push(Card {
+                    range: line_range,
+                    checked,
+                    title,
+                    metadata,
+                });
+            } else if let Some(card) = column.cards.last_mut() {
+                let continues_card = card.range.end == line_range.start
+                    && content.starts_with([' ', '\t'])
+                    && !content.trim().is_empty();
+                if continues_card {
+                    card.range.end = line_range.end;
+                }
+            }
+        }
+        Self { columns }
+    }
+
+    /// Returns the edits that move a card to `index` among the cards of
+    /// `to_column`, where `index` counts the cards before the move. Returns
+    /// `None` when the card wouldn't move.
+    pub fn move_card(
+        &self,
+        text: &str,
+        (from_column, from_card): (usize, usize),
+        to_column: usize,
+        index: usize,
+    ) -> Option<Vec<(Range<usize>, String)>> {
+        let card = self.columns.get(from_column)?.cards.get(from_card)?;
+        let column = self.columns.get(to_column)?;
+        let insertion = match column.cards.get(index) {
+            Some(next_card) => next_card.range.start,
+            None => column
+                .cards
+                .last()
+                .map_or(column.heading_range.end, |card| card.range.end),
+        };
+        if card.range.start <= insertion && insertion <= card.range.end {
+            return None;
+        }
+
+        let mut card_text = text[card.range.clone()].to_string();
+        if !card_text.ends_with('\n') {
+            card_text.push('\n');
+        }
+        // The card is last in the file and the line before it keeps a newline
+        // that now ends the file.
+        let removal = if !text[card.range.clone()].ends_with('\n') && card.range.start > 0 {
+            card.range.start - 1..card.range.end
+        } else {
+            card.range.clone()
+        };
+        if !text[..insertion].ends_with('\n') && insertion > 0 {
+            card_text.insert(0, '\n');
+            card_text.pop();
+        }
+        let mut edits = vec![(removal, String::new()), (insertion..insertion, card_text)];
+        edits.sort_by_key(|(range, _)| range.start);
+        Some(edits)
+    }
+}
+
+/// Parses `- [ ] item`, returning whether it's checked and the item's text.
+fn parse_task(line: &str) -> Option<(bool, &str)> {
+    let rest = line
+        .strip_prefix("- [")
+        .or_else(|| line.strip_prefix("* ["))?;
+    let mut chars = rest.chars();
+    let mark = chars.next()?;
+    let rest = chars.as_str().strip_prefix(']')?;
+    let checked = match mark {
+        ' ' => false,
+        'x' | 'X' => true,
+        _ => return None,
+    };
+    Some((checked, rest.strip_prefix(' ').unwrap_or(rest)))
+}
+
+/// Splits the text of a card into its title and its metadata.
+fn parse_metadata(item: &str) -> (String, Vec<CardMetadata>) {
+    let mut spans = Vec::<(Range<usize>, CardMetadata, Option<String>)>::new();
+    for link in wiki_links(item) {
+        let display_text = link.display_text();
+        spans.push((
+            link.range,
+            CardMetadata::Link(display_text.clone()),
+            Some(display_text),
+        ));
+    }
+    for (range, tag) in inline_tags(item) {
+        if !spans.iter().any(|(span, _, _)| span.contains(&range.start)) {
+            spans.push((range, CardMetadata::Tag(tag), None));
+        }
+    }
+    for (range, date) in dates(item) {
+        if !spans.iter().any(|(span, _, _)| span.contains(&range.start)) {
+            spans.push((range, CardMetadata::Date(date), None));
+        }
+    }
+    spans.sort_by_key(|(range, _, _)| range.start);
+
+    let mut title = String::new();
+    let mut offset = 0;
+    let mut metadata = Vec::new();
+    for (range, data, replacement) in spans {
+        title.push_str(&item[offset..range.start]);
+        if let Some(replacement) = replacement {
+            title.push_str(&replacement);
+        }
+        offset = range.end;
+        metadata.push(data);
+    }
+    title.push_str(&item[offset..]);
+    let title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+    (title, metadata)
+}
+
+/// Returns the `YYYY-MM-DD` dates in `text`, along with their ranges including
+/// any `@`, `@{…}` or `📅` around them.
+fn dates(text: &str) -> Vec<(Range<usize>, String)> {
+    let bytes = text.as_bytes();
+    let is_date = |start: usize| {
+        bytes.get(start..start + 10).is_some_and(|date| {
+            date.iter().enumerate().all(|(ix, byte)| match ix {
+                4 | 7 => *byte == b'-',
+                _ => byte.is_ascii_digit(),
+            })
+        }) && bytes
+            .get(start + 10)
+            .is_none_or(|byte| !byte.is_ascii_alphanumeric())
+            && (start == 0 || !bytes[start - 1].is_ascii_alphanumeric())
+    };
+
+    let mut dates = Vec::new();
+    let mut start = 0;
+    while start < bytes.len() {
+        if !is_date(start) {
+            start += 1;
+            continue;
+        }
+        let date = text[start..start + 10].to_string();
+        let mut range = start..start + 10;
+        if text[..start].ends_with("@{") && text[range.end..].starts_with('}') {
+            range = start - 2..range.end + 1;
+        } else if let Some(prefix) = ["@", "📅 ", "📅"]
+            .into_iter()
+            .find(|prefix| text[..start].ends_with(prefix))
+        {
+            range.start -= prefix.len();
+        }
+        start = range.end;
+        dates.push((range, date));
+    }
+    dates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    fn apply(text: &str, mut edits: Vec<(Range<usize>, String)>) -> String {
+        let mut text = text.to_string();
+        edits.sort_by_key(|(range, _)| std::cmp::Reverse(range.start));
+        for (range, new_text) in edits {
+            text.replace_range(range, &new_text);
+        }
+        text
+    }
+
+    fn titles(board: &Board) -> Vec<(&str, Vec<&str>)> {
+        board
+            .columns
+            .iter()
+            .map(|column| {
+                (
+                    column.title.as_str(),
+                    column
+                        .cards
+                        .iter()
+                        .map(|card| card.title.as_str())
+                        .collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_board() {
+        let text = indoc! {"
+            ---
+            kanban-plugin: basic
+            ---
+
+            - [ ] not on the board
+
+            ## Todo
+
+            - [ ] Write the parser #backend @{2024-05-01}
+              with notes below it
+            - [ ] Review [[Design Doc|the design]] 📅 2024-05-03
+
+            ## Done
+            - [x] Set up the repo
+
+            # Archive
+            ## Old
+            - [ ] hidden
+        "};
+        let board = Board::parse(text);
+        assert_eq!(
+            titles(&board),
+            [
+                ("Todo", vec!["Write the parser", "Review the design"]),
+                ("Done", vec!["Set up the repo"]),
+            ]
+        );
+
+        let todo = &board.columns[0].cards;
+        assert_eq!(
+            &text[todo[0].range.clone()],
+            "- [ ] Write the parser #backend @{2024-05-01}\n  with notes below it\n"
+        );
+        assert_eq!(
+            todo[0].metadata,
+            [
+                CardMetadata::Tag("backend".into()),
+                CardMetadata::Date("2024-05-01".into()),
+            ]
+        );
+        assert_eq!(
+            todo[1].metadata,
+            [
+                CardMetadata::Link("the design".into()),
+                CardMetadata::Date("2024-05-03".into()),
+            ]
+        );
+        assert!(board.columns[1].cards[0].checked);
+    }
+
+    #[test]
+    fn test_move_card() {
+        let text = indoc! {"
+            ## Todo
+            - [ ] a
+              details
+            - [ ] b
+
+            ## Doing
+
+            ## Done
+            - [x] c"};
+        let board = Board::parse(text);
+
+        let moved = apply(text, board.move_card(text, (0, 0), 1, 0).unwrap());
+        assert_eq!(
+            moved,
+            indoc! {"
+                ## Todo
+                - [ ] b
+
+                ## Doing
+                - [ ] a
+                  details
+
+                ## Done
+                - [x] c"}
+        );
+        assert_eq!(
+            titles(&Board::parse(&moved)),
+            [
+                ("Todo", vec!["b"]),
+                ("Doing", vec!["a"]),
+                ("Done", vec!["c"])
+            ]
+        );
+
+        // Reordering within a column.
+        let moved = apply(text, board.move_card(text, (0, 1), 0, 0).unwrap());
+        assert_eq!(
+            titles(&Board::parse(&moved)),
+            [
+                ("Todo", vec!["b", "a"]),
+                ("Doing", vec![]),
+                ("Done", vec!["c"])
+            ]
+        );
+        assert_eq!(board.move_card(text, (0, 0), 0, 1), None);
+
+        // The last card of the file has no trailing newline.
+        let moved = apply(text, board.move_card(text, (2, 0), 0, 2).unwrap());
+        assert_eq!(
+            moved,
+            indoc! {"
+                ## Todo
+                - [ ] a
+                  details
+                - [ ] b
+                - [x] c
+
+                ## Doing
+
+                ## Done"}
+        );
+        let moved = apply(text, board.move_card(text, (0, 1), 2, 1).unwrap());
+        assert_eq!(
+            moved,
+            indoc! {"
+                ## Todo
+                - [ ] a
+                  details
+
+                ## Doing
+
+                ## Done
+                - [x] c
+                - [ ] b"}
+        );
+    }
+}
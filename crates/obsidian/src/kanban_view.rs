@@ -0,0 +1,370 @@
+use editor::{
+    Editor,
+    actions::{Redo, Undo},
+};
+use gpui::{AnyElement, App, Entity, EventEmitter, FocusHandle, Focusable, Subscription};
+use language::Buffer;
+use ui::{Checkbox, prelude::*};
+use workspace::{Workspace, item::Item};
+
+use crate::kanban::{Board, Card, CardMetadata};
+
+const COLUMN_WIDTH: f32 = 280.;
+
+/// A tab showing a markdown file as a kanban board.
+///
+/// The board is read from the file's buffer and every change to it is an edit
+/// of the buffer, so it stays in sync with editors and collaborators and can
+/// be undone like any other edit.
+pub struct KanbanView {
+    buffer: Entity<Buffer>,
+    board: Board,
+    focus_handle: FocusHandle,
+    _observe_buffer: Subscription,
+}
+
+/// A card being dragged to another place on the board.
+#[derive(Clone)]
+struct DraggedCard {
+    column: usize,
+    card: usize,
+    title: SharedString,
+}
+
+impl KanbanView {
+    pub fn new(buffer: Entity<Buffer>, cx: &mut Context<Self>) -> Self {
+        let observe_buffer = cx.observe(&buffer, |this, buffer, cx| {
+            this.board = Board::parse(&buffer.read(cx).text());
+            cx.notify();
+        });
+        Self {
+            board: Board::parse(&buffer.read(cx).text()),
+            buffer,
+            focus_handle: cx.focus_handle(),
+            _observe_buffer: observe_buffer,
+        }
+    }
+
+    pub fn buffer(&self) -> &Entity<Buffer> {
+        &self.buffer
+    }
+
+    fn move_card(
+        &mut self,
+        dragged: &DraggedCard,
+        to_column: usize,
+        index: usize,
+        cx: &mut Context<Self>,
+    ) {
+        self.buffer.update(cx, |buffer, cx| {
+            let text = buffer.text();
+            let board = Board::parse(&text);
+            // The buffer may have changed since the drag started.
+            let still_there = board
+                .columns
+                .get(dragged.column)
+                .and_then(|column| column.cards.get(dragged.card))
+                .is_some_and(|card| card.title == dragged.title.as_ref());
+            if !still_there {
+                return;
+            }
+            if let Some(edits) =
+                board.move_card(&text, (dragged.column, dragged.card), to_column, index)
+            {
+                buffer.start_transaction();
+                buffer.edit(edits, None, cx);
+                buffer.end_transaction(cx);
+            }
+        });
+    }
+
+    fn toggle_card(&mut self, column: usize, card: usize, cx: &mut Context<Self>) {
+        let Some(card) = self
+            .board
+            .columns
+            .get(column)
+            .and_then(|column| column.cards.get(card))
+        else {
+            return;
+        };
+        // The mark is the character after `- [`.
+        let mark = card.range.start + 3;
+        let new_mark = if card.checked { " " } else { "x" };
+        self.buffer.update(cx, |buffer, cx| {
+            buffer.edit([(mark..mark + 1, new_mark)], None, cx);
+        });
+    }
+
+    fn undo(&mut self, _: &Undo, _: &mut Window, cx: &mut Context<Self>) {
+        self.buffer.update(cx, |buffer, cx| buffer.undo(cx));
+    }
+
+    fn redo(&mut self, _: &Redo, _: &mut Window, cx: &mut Context<Self>) {
+        self.buffer.update(cx, |buffer, cx| buffer.redo(cx));
+    }
+
+    fn render_column(&self, ix: usize, cx: &mut Context<Self>) -> AnyElement {
+        let column = &self.board.columns[ix];
+        let card_count = column.cards.len();
+        v_flex()
+            .id(("kanban-column", ix))
+            .flex_none()
+            .w(px(COLUMN_WIDTH))
+            .max_h_full()
+            .gap_2()
+            .p_2()
+            .rounded_md()
+            .border_1()
+            .border_color(cx.theme().colors().border_variant)
+            .bg(cx.theme().colors().panel_background)
+            .drag_over::<DraggedCard>(|style, _, _, cx| {
+                style.bg(cx.theme().colors().drop_target_background)
+            })
+            .on_drop(cx.listener(move |this, dragged: &DraggedCard, _, cx| {
+                this.move_card(dragged, ix, card_count, cx)
+            }))
+            .child(
+                h_flex()
+                    .gap_2()
+                    .px_1()
+                    .child(Label::new(column.title.clone()).weight(FontWeight::SEMIBOLD))
+                    .child(
+                        Label::new(card_count.to_string())
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    ),
+            )
+            .child(
+                v_flex().id("cards").gap_2().overflow_y_scroll().children(
+                    column
+                        .cards
+                        .iter()
+                        .enumerate()
+                        .map(|(card_ix, card)| self.render_card(ix, card_ix, card, cx)),
+                ),
+            )
+            .into_any_element()
+    }
+
+    fn render_card(
+        &self,
+        column: usize,
+        ix: usize,
+        card: &Card,
+        cx: &mut Context<Self>,
+    ) -> AnyElement {
+        let title = SharedString::from(card.title.clone());
+        let dragged = DraggedCard {
+            column,
+            card: ix,
+            title: title.clone(),
+        };
+        v_flex()
+            .id(("kanban-card", ix))
+            .gap_1()
+            .p_2()
+            .rounded_sm()
+            .border_1()
+            .border_color(cx.theme().colors().border)
+            .bg(cx.theme().colors().elevated_surface_background)
+            .cursor_grab()
+            .on_drag(dragged, |dragged, _, _, cx| cx.new(|_| dragged.clone()))
+            .drag_over::<DraggedCard>(|style, _, _, cx| {
+                style.border_color(cx.theme().colors().border_focused)
+            })
+            .on_drop(cx.listener(move |this, dragged: &DraggedCard, _, cx| {
+                this.move_card(dragged, column, ix, cx)
+            }))
+            .child(
+                h_flex()
+                    .gap_2()
+                    .items_start()
+                    .child(
+                        Checkbox::new(("kanban-card-done", ix), card.checked.into()).on_click(
+                            cx.listener(move |this, _, _, cx| this.toggle_card(column, ix, cx)),
+                        ),
+                    )
+                    .child(Label::new(title).color(if card.checked {
+                        Color::Muted
+                    } else {
+                        Color::Default
+                    })),
+            )
+            .when(!card.metadata.is_empty(), |this| {
+                this.child(
+                    h_flex().flex_wrap().gap_1().children(
+                        card.metadata
+                            .iter()
+                            .map(|metadata| render_chip(metadata, cx)),
+                    ),
+                )
+            })
+            .into_any_element()
+    }
+}
+
+fn render_chip(metadata: &CardMetadata, cx: &App) -> AnyElement {
+    let (icon, text, color) = match metadata {
+        CardMetadata::Tag(tag) => (IconName::Hash, tag, Color::Accent),
+        CardMetadata::Date(date) => (IconName::CountdownTimer, date, Color::Default),
+        CardMetadata::Link(link) => (IconName::Link, link, Color::Accent),
+    };
+    h_flex()
+        .gap_0p5()
+        .px_1()
+        .rounded_sm()
+        .bg(cx.theme().colors().element_background)
+        .child(Icon::new(icon).size(IconSize::XSmall).color(Color::Muted))
+        .child(
+            Label::new(text.clone())
+                .size(LabelSize::XSmall)
+                .color(color),
+        )
+        .into_any_element()
+}
+
+impl Render for DraggedCard {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        div()
+            .w(px(COLUMN_WIDTH - 16.))
+            .p_2()
+            .rounded_sm()
+            .border_1()
+            .border_color(cx.theme().colors().border_focused)
+            .bg(cx.theme().colors().elevated_surface_background)
+            .shadow_md()
+            .child(Label::new(self.title.clone()))
+    }
+}
+
+impl EventEmitter<()> for KanbanView {}
+
+impl Focusable for KanbanView {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for KanbanView {
+    type Event = ();
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::ListTree))
+    }
+
+    fn tab_content_text(&self, _window: &Window, cx: &App) -> Option<SharedString> {
+        let name = self
+            .buffer
+            .read(cx)
+            .file()
+            .map(|file| file.file_name(cx).to_string_lossy().into_owned());
+        Some(match name {
+            Some(name) => format!("Board: {name}").into(),
+            None => "Board".into(),
+        })
+    }
+}
+
+impl Render for KanbanView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        h_flex()
+            .id("kanban-view")
+            .key_context("KanbanBoard")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::undo))
+            .on_action(cx.listener(Self::redo))
+            .size_full()
+            .items_start()
+            .gap_3()
+            .p_3()
+            .overflow_x_scroll()
+            .bg(cx.theme().colors().editor_background)
+            .children((0..self.board.columns.len()).map(|ix| self.render_column(ix, cx)))
+            .when(self.board.columns.is_empty(), |this| {
+                this.child(
+                    h_flex().size_full().justify_center().child(
+                        Label::new("Add a `## Column` heading with `- [ ] cards` to start a board")
+                            .color(Color::Muted),
+                    ),
+                )
+            })
+    }
+}
+
+/// Opens the buffer of the active editor as a kanban board.
+pub(crate) fn open_board(
+    workspace: &mut Workspace,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    // Channel notes act as editors, so they can be opened as boards too.
+    let Some(editor) = workspace
+        .active_item(cx)
+        .and_then(|item| item.act_as::<Editor>(cx))
+    else {
+        return;
+    };
+    let Some(buffer) = editor.read(cx).buffer().read(cx).as_singleton() else {
+        return;
+    };
+    let existing = workspace
+        .items_of_type::<KanbanView>(cx)
+        .find(|view| view.read(cx).buffer() == &buffer);
+    if let Some(existing) = existing {
+        workspace.activate_item(&existing, true, true, window, cx);
+        return;
+    }
+    let view = cx.new(|cx| KanbanView::new(buffer, cx));
+    workspace.add_item_to_active_pane(Box::new(view), None, true, window, cx);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[gpui::test]
+    fn test_moving_cards_edits_the_buffer(cx: &mut App) {
+        let text = indoc! {"
+            ## Todo
+            - [ ] a
+            - [ ] b
+
+            ## Done
+        "};
+        let buffer = cx.new(|cx| Buffer::local(text, cx));
+        let view = cx.new(|cx| KanbanView::new(buffer.clone(), cx));
+
+        let dragged = DraggedCard {
+            column: 0,
+            card: 1,
+            title: "b".into(),
+        };
+        view.update(cx, |view, cx| view.move_card(&dragged, 1, 0, cx));
+        assert_eq!(
+            buffer.read(cx).text(),
+            indoc! {"
+                ## Todo
+                - [ ] a
+
+                ## Done
+                - [ ] b
+            "}
+        );
+        assert_eq!(view.read(cx).board.columns[1].cards[0].title, "b");
+
+        // A card that has moved since the drag started stays put.
+        view.update(cx, |view, cx| view.move_card(&dragged, 0, 0, cx));
+        assert_eq!(view.read(cx).board.columns[0].cards.len(), 1);
+
+        view.update(cx, |view, cx| view.toggle_card(1, 0, cx));
+        assert!(view.read(cx).board.columns[1].cards[0].checked);
+
+        buffer.update(cx, |buffer, cx| {
+            buffer.undo(cx);
+            buffer.undo(cx);
+        });
+        assert_eq!(buffer.read(cx).text(), text);
+    }
+}
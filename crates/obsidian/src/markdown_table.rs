@@ -0,0 +1,551 @@
+use std::cmp::Ordering;
+use std::ops::Range;
+
+use unicode_width::UnicodeWidthStr;
+
+/// The narrowest a column gets, so its delimiter fits `:-:`.
+const MIN_COLUMN_WIDTH: usize = 3;
+
+/// The alignment of a table column, set by the colons in its delimiter cell.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Alignment {
+    #[default]
+    None,
+    Left,
+    Center,
+    Right,
+}
+
+/// A GitHub-flavored markdown table.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Table {
+    /// The header row followed by the body rows, each with a cell for every
+    /// column.
+    pub rows: Vec<Vec<String>>,
+    pub alignments: Vec<Alignment>,
+    /// The whitespace before every line, for tables in list items.
+    indent: String,
+}
+
+/// A table as text, along with where the text of each cell is in it.
+pub struct FormattedTable {
+    pub text: String,
+    cells: Vec<Vec<Range<usize>>>,
+}
+
+/// A cell of a table, and an offset in its text.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CellPosition {
+    /// The row of the cell, where the header is row 0 and the delimiter row
+    /// counts as part of the header.
+    pub row: usize,
+    pub column: usize,
+    /// The offset from the start of the cell's text. It's past the end of the
+    /// text when it's in whitespace after it.
+    pub offset: usize,
+}
+
+struct CellSpan {
+    /// Everything between the pipes around the cell.
+    span: Range<usize>,
+    /// The cell's text, without surrounding whitespace.
+    text: Range<usize>,
+}
+
+impl Table {
+    /// Parses the lines of a table: the header row, the delimiter row and any
+    /// body rows. Rows with a missing cell are padded with empty cells.
+    pub fn parse(text: &str) -> Option<Self> {
+        let lines = text.lines().collect::<Vec<_>>();
+        let [header, delimiter, body @ ..] = lines.as_slice() else {
+            return None;
+        };
+        let mut alignments = split_row(delimiter)
+            .into_iter()
+            .map(|cell| parse_alignment(&delimiter[cell.text]))
+            .collect::<Option<Vec<_>>>()?;
+        let mut rows = std::iter::once(header)
+            .chain(body)
+            .map(|line| {
+                split_row(line)
+                    .into_iter()
+                    .map(|cell| line[cell.text].to_string())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let column_count = rows
+            .iter()
+            .map(Vec::len)
+            .chain([alignments.len()])
+            .max()
+            .unwrap_or(0);
+        alignments.resize(column_count, Alignment::None);
+        for row in &mut rows {
+            row.resize(column_count, String::new());
+        }
+        Some(Self {
+            rows,
+            alignments,
+            indent: header[..header.len() - header.trim_start().len()].to_string(),
+        })
+    }
+
+    /// Builds a table from comma- or tab-separated values, using the first
+    /// line as the header. Values can be quoted like in CSV files.
+    pub fn from_delimited(text: &str) -> Option<Self> {
+        let separator = if text.contains('\t') { '\t' } else { ',' };
+        if !text.contains(separator) {
+            return None;
+        }
+        let mut rows = parse_delimited(text, separator);
+        rows.retain(|row| row.iter().any(|cell| !cell.trim().is_empty()));
+        let column_count = rows.iter().map(Vec::len).max()?;
+        for row in &mut rows {
+            row.resize(column_count, String::new());
+            for cell in row.iter_mut() {
+                *cell = cell
+                    .trim()
+                    .replace('|', "\\|")
+                    .replace("\r\n", "<br>")
+                    .replace('\n', "<br>");
+            }
+        }
+        Some(Self {
+            rows,
+            alignments: vec![Alignment::None; column_count],
+            indent: String::new(),
+        })
+    }
+
+    pub fn column_count(&self) -> usize {
+        self.alignments.len()
+    }
+
+    /// Inserts an empty body row before `row`, or after the header when `row`
+    /// is the header.
+    pub fn insert_row(&mut self, row: usize) {
+        let row = row.clamp(1, self.rows.len());
+        self.rows
+            .insert(row, vec![String::new(); self.column_count()]);
+    }
+
+    /// Removes a body row. The header can't be removed.
+    pub fn remove_row(&mut self, row: usize) -> bool {
+        if row == 0 || row >= self.rows.len() {
+            return false;
+        }
+        self.rows.remove(row);
+        true
+    }
+
+    /// Swaps two body rows.
+    pub fn swap_rows(&mut self, a: usize, b: usize) -> bool {
+        let body = 1..self.rows.len();
+        if a == b || !body.contains(&a) || !body.contains(&b) {
+            return false;
+        }
+        self.rows.swap(a, b);
+        true
+    }
+
+    /// Inserts an empty column before `column`.
+    pub fn insert_column(&mut self, column: usize) {
+        let column = column.min(self.column_count());
+        self.alignments.insert(column, Alignment::None);
+        for row in &mut self.rows {
+            row.insert(column, String::new());
+        }
+    }
+
+    /// Removes a column, unless it's the only one.
+    pub fn remove_column(&mut self, column: usize) -> bool {
+        if self.column_count() <= 1 || column >= self.column_count() {
+            return false;
+        }
+        self.alignments.remove(column);
+        for row in &mut self.rows {
+            row.remove(column);
+        }
+        true
+    }
+
+    pub fn swap_columns(&mut self, a: usize, b: usize) -> bool {
+        if a == b || a >= self.column_count() || b >= self.column_count() {
+            return false;
+        }
+        self.alignments.swap(a, b);
+        for row in &mut self.rows {
+            row.swap(a, b);
+        }
+        true
+    }
+
+    /// Sorts the body rows by the text of their cells in `column`, comparing
+    /// numbers by value and other text ignoring case.
+    pub fn sort_by_column(&mut self, column: usize, descending: bool) {
+        if column >= self.column_count() || self.rows.len() < 2 {
+            return;
+        }
+        self.rows[1..].sort_by(|a, b| {
+            let ordering = compare_cells(&a[column], &b[column]);
+            if descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+    }
+
+    /// Formats the table with its pipes lined up, padding each cell according
+    /// to its column's alignment.
+    pub fn format(&self) -> FormattedTable {
+        let widths = (0..self.column_count())
+            .map(|column| {
+                self.rows
+                    .iter()
+                    .map(|row| row[column].width())
+                    .max()
+                    .unwrap_or(0)
+                    .max(MIN_COLUMN_WIDTH)
+            })
+            .collect::<Vec<_>>();
+
+        let mut text = String::new();
+        let mut cells = Vec::with_capacity(self.rows.len());
+        for (row_ix, row) in self.rows.iter().enumerate() {
+            if row_ix > 0 {
+                text.push('\n');
+            }
+            if row_ix == 1 {
+                self.push_delimiter_row(&widths, &mut text);
+                text.push('\n');
+            }
+            text.push_str(&self.indent);
+            text.push('|');
+            let mut row_cells = Vec::with_capacity(row.len());
+            for ((cell, width), alignment) in row.iter().zip(&widths).zip(&self.alignments) {
+                let padding = width - cell.width();
+                let left_padding = match alignment {
+                    Alignment::None | Alignment::Left => 0,
+                    Alignment::Center => padding / 2,
+                    Alignment::Right => padding,
+                };
+                text.push(' ');
+                text.extend(std::iter::repeat_n(' ', left_padding));
+                row_cells.push(text.len()..text.len() + cell.len());
+                text.push_str(cell);
+                text.extend(std::iter::repeat_n(' ', padding - left_padding));
+                text.push_str(" |");
+            }
+            cells.push(row_cells);
+        }
+        if self.rows.len() == 1 {
+            text.push('\n');
+            self.push_delimiter_row(&widths, &mut text);
+        }
+        FormattedTable { text, cells }
+    }
+
+    fn push_delimiter_row(&self, widths: &[usize], text: &mut String) {
+        text.push_str(&self.indent);
+        text.push('|');
+        for (width, alignment) in widths.iter().zip(&self.alignments) {
+            let (left, right) = match alignment {
+                Alignment::None => ("", ""),
+                Alignment::Left => (":", ""),
+                Alignment::Center => (":", ":"),
+                Alignment::Right => ("", ":"),
+            };
+            text.push(' ');
+            text.push_str(left);
+            text.extend(std::iter::repeat_n('-', width - left.len() - right.len()));
+            text.push_str(right);
+            text.push_str(" |");
+        }
+    }
+}
+
+impl FormattedTable {
+    /// The range of a cell's text, clamped to the last row and column.
+    pub fn cell_range(&self, row: usize, column: usize) -> Range<usize> {
+        let row = &self.cells[row.min(self.cells.len() - 1)];
+        row[column.min(row.len() - 1)].clone()
+    }
+}
+
+/// Returns the cell of the table `text` that `offset` is in.
+pub fn cell_at(text: &str, offset: usize) -> Option<CellPosition> {
+    let mut line_start = 0;
+    for (line_ix, line) in text.split('\n').enumerate() {
+        let line_end = line_start + line.len();
+        if offset <= line_end {
+            let cells = split_row(line);
+            let column = cells
+                .iter()
+                .position(|cell| offset - line_start <= cell.span.end)
+                .unwrap_or(cells.len().saturating_sub(1));
+            let cell = cells.get(column)?;
+            let offset = if line_ix == 1 {
+                0
+            } else {
+                (offset - line_start).clamp(cell.text.start, cell.span.end.max(cell.text.start))
+                    - cell.text.start
+            };
+            return Some(CellPosition {
+                row: line_ix.saturating_sub(1),
+                column,
+                offset,
+            });
+        }
+        line_start = line_end + 1;
+    }
+    None
+}
+
+/// Splits a table row into cells at the pipes that aren't escaped, ignoring
+/// the pipes at either end.
+fn split_row(line: &str) -> Vec<CellSpan> {
+    let mut pipes = Vec::new();
+    let mut escaped = false;
+    for (ix, c) in line.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == '|' {
+            pipes.push(ix);
+        }
+    }
+
+    let content_start = line.len() - line.trim_start().len();
+    let content_end = line.trim_end().len();
+    let mut inner_pipes = pipes.as_slice();
+    let mut start = 0;
+    if let [first, rest @ ..] = inner_pipes {
+        if *first == content_start {
+            start = first + 1;
+            inner_pipes = rest;
+        }
+    }
+    let mut end = line.len();
+    if let [rest @ .., last] = inner_pipes {
+        if last + 1 == content_end {
+            end = *last;
+            inner_pipes = rest;
+        }
+    }
+
+    let mut cells = Vec::with_capacity(inner_pipes.len() + 1);
+    let mut cell_start = start;
+    for &cell_end in inner_pipes.iter().chain([&end.max(start)]) {
+        let span = cell_start..cell_end;
+        let text = &line[span.clone()];
+        let text_start = span.start + (text.len() - text.trim_start().len());
+        let text_end = (span.start + text.trim_end().len()).max(text_start);
+        cells.push(CellSpan {
+            span,
+            text: text_start..text_end,
+        });
+        cell_start = cell_end + 1;
+    }
+    cells
+}
+
+fn parse_alignment(cell: &str) -> Option<Alignment> {
+    let dashes = cell.trim_matches(':');
+    if dashes.is_empty() || dashes.chars().any(|c| c != '-') {
+        return None;
+    }
+    Some(match (cell.starts_with(':'), cell.ends_with(':')) {
+        (false, false) => Alignment::None,
+        (true, false) => Alignment::Left,
+        (true, true) => Alignment::Center,
+        (false, true) => Alignment::Right,
+    })
+}
+
+fn parse_delimited(text: &str, separator: char) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            if c != '"' {
+                field.push(c);
+            } else if chars.peek() == Some(&'"') {
+                field.push('"');
+                chars.next();
+            } else {
+                in_quotes = false;
+            }
+        } else if c == '"' && field.trim().is_empty() {
+            field.clear();
+            in_quotes = true;
+        } else if c == separator {
+            row.push(std::mem::take(&mut field));
+        } else if c == '\n' {
+            row.push(std::mem::take(&mut field));
+            rows.push(std::mem::take(&mut row));
+        } else if c != '\r' {
+            field.push(c);
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows
+}
+
+fn compare_cells(a: &str, b: &str) -> Ordering {
+    match (a.trim().parse::<f64>(), b.trim().parse::<f64>()) {
+        (Ok(a), Ok(b)) => a.total_cmp(&b),
+        _ => a.to_lowercase().cmp(&b.to_lowercase()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_parse_and_format() {
+        let table = Table::parse(indoc! {"
+            Name|Qty|  Note
+            :--|--:|:-:
+            | apple | 3 | red \\| green |
+            | 日本語 | 12
+        "})
+        .unwrap();
+        assert_eq!(
+            table.alignments,
+            [Alignment::Left, Alignment::Right, Alignment::Center]
+        );
+        assert_eq!(table.rows[1], ["apple", "3", "red \\| green"]);
+        assert_eq!(table.rows[2], ["日本語", "12", ""]);
+
+        let formatted = table.format();
+        assert_eq!(
+            formatted.text,
+            indoc! {"
+                | Name   | Qty |     Note     |
+                | :----- | --: | :----------: |
+                | apple  |   3 | red \\| green |
+                | 日本語 |  12 |              |"}
+        );
+        assert_eq!(&formatted.text[formatted.cell_range(2, 0)], "日本語");
+        assert_eq!(&formatted.text[formatted.cell_range(1, 2)], "red \\| green");
+        assert_eq!(&formatted.text[formatted.cell_range(9, 9)], "");
+        assert_eq!(Table::parse(&formatted.text).unwrap(), table);
+
+        // Indented tables keep their indentation, and a delimiter row is
+        // required.
+        let table = Table::parse("  | a |\n  | - |").unwrap();
+        assert_eq!(table.format().text, "  | a   |\n  | --- |");
+        assert_eq!(Table::parse("| a |\n| b |"), None);
+    }
+
+    #[test]
+    fn test_cell_at() {
+        let text = "| a  | bc |\n|----|----|\n| d  |    |";
+        let position = |offset| cell_at(text, offset).unwrap();
+        assert_eq!(
+            position(0),
+            CellPosition {
+                row: 0,
+                column: 0,
+                offset: 0
+            }
+        );
+        assert_eq!(position(3).offset, 1);
+        // Whitespace after the text is kept in the offset.
+        assert_eq!(position(4).offset, 2);
+        assert_eq!((position(8).column, position(8).offset), (1, 1));
+        assert_eq!((position(15).row, position(15).offset), (0, 0));
+        assert_eq!((position(26).row, position(26).column), (1, 0));
+        assert_eq!(
+            position(text.len()),
+            CellPosition {
+                row: 1,
+                column: 1,
+                offset: 0
+            }
+        );
+    }
+
+    #[test]
+    fn test_editing_rows_and_columns() {
+        let mut table = Table::parse("| a | b |\n|---|:-:|\n| 1 | 2 |").unwrap();
+        table.insert_row(0);
+        table.insert_column(1);
+        assert_eq!(
+            table.format().text,
+            indoc! {"
+                | a   |     |  b  |
+                | --- | --- | :-: |
+                |     |     |     |
+                | 1   |     |  2  |"}
+        );
+        assert!(table.swap_rows(1, 2));
+        assert!(!table.swap_rows(0, 1));
+        assert!(table.remove_row(2));
+        assert!(!table.remove_row(0));
+        assert!(table.remove_column(1));
+        assert!(table.swap_columns(0, 1));
+        assert_eq!(
+            table.format().text,
+            indoc! {"
+                |  b  | a   |
+                | :-: | --- |
+                |  2  | 1   |"}
+        );
+        assert!(table.remove_column(0));
+        assert!(!table.remove_column(0));
+    }
+
+    #[test]
+    fn test_sort_by_column() {
+        let mut table =
+            Table::parse("| n | name |\n|---|---|\n| 10 | b |\n| 9 | C |\n| 10.5 | a |").unwrap();
+        table.sort_by_column(0, false);
+        let column = |table: &Table, column: usize| {
+            table.rows[1..]
+                .iter()
+                .map(|row| row[column].clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(column(&table, 0), ["9", "10", "10.5"]);
+        table.sort_by_column(1, true);
+        assert_eq!(column(&table, 1), ["C", "b", "a"]);
+    }
+
+    #[test]
+    fn test_from_delimited() {
+        let table = Table::from_delimited(indoc! {r#"
+            name,quote
+            "Smith, J","said ""hi""
+            twice"
+
+            Doe,a|b,extra
+        "#})
+        .unwrap();
+        assert_eq!(
+            table.format().text,
+            indoc! {r#"
+                | name     | quote              |       |
+                | -------- | ------------------ | ----- |
+                | Smith, J | said "hi"<br>twice |       |
+                | Doe      | a\|b               | extra |"#}
+        );
+        assert_eq!(
+            Table::from_delimited("a\tb\n1\t2").unwrap().rows,
+            [["a", "b"], ["1", "2"]]
+        );
+        assert_eq!(Table::from_delimited("no separators"), None);
+    }
+}
@@ -0,0 +1,248 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use collections::{HashMap, HashSet};
+use vault::{NoteResolver, is_note, wiki_links};
+
+/// A node of the note graph.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum GraphNode {
+    /// A note, by its path relative to the vault root.
+    Note(Arc<Path>),
+    /// A link target that no note exists for yet.
+    Unresolved(String),
+}
+
+/// The notes of a vault and the `[[wiki links]]` between them.
+///
+/// Link targets are kept as written and resolved when the graph is queried, so
+/// adding or removing a note updates the links of every note that refers to it.
+#[derive(Default)]
+pub struct NoteGraph {
+    resolver: NoteResolver,
+    links: BTreeMap<Arc<Path>, Vec<String>>,
+}
+
+impl NoteGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn resolver(&self) -> &NoteResolver {
+        &self.resolver
+    }
+
+    /// Adds or replaces a note, parsing the links in its text.
+    pub fn insert_note(&mut self, path: Arc<Path>, text: &str) {
+        self.resolver.insert(path.clone());
+        let mut targets = Vec::new();
+        for link in wiki_links(text) {
+            let target = link.target.trim();
+            if !target.is_empty()
+                && !targets
+                    .iter()
+                    .any(|existing: &String| existing.to_lowercase() == target.to_lowercase())
+            {
+                targets.push(target.to_string());
+            }
+        }
+        self.links.insert(path, targets);
+    }
+
+    /// Adds a file that isn't a note, such as an image, so links to it resolve.
+    pub fn insert_attachment(&mut self, path: Arc<Path>) {
+        self.resolver.insert(path);
+    }
+
+    pub fn remove(&mut self, path: &Path) {
+        self.resolver.remove(path);
+        self.links.remove(path);
+    }
+
+    pub fn contains_note(&self, path: &Path) -> bool {
+        self.links.contains_key(path)
+    }
+
+    /// The nodes that the note at `path` links to, in the order they are first
+    /// linked. Links to attachments are skipped.
+    pub fn links_from(&self, path: &Path) -> Vec<GraphNode> {
+        let Some(targets) = self.links.get(path) else {
+            return Vec::new();
+        };
+        let mut nodes = Vec::new();
+        for target in targets {
+            if let Some(node) = self.resolve(target, path) {
+                if node != GraphNode::Note(path.into()) && !nodes.contains(&node) {
+                    nodes.push(node);
+                }
+            }
+        }
+        nodes
+    }
+
+    /// The notes that link to the note at `path`, sorted by path.
+    pub fn links_to(&self, path: &Path) -> Vec<Arc<Path>> {
+        let node = GraphNode::Note(path.into());
+        self.links
+            .keys()
+            .filter(|source| source.as_ref() != path && self.links_from(source).contains(&node))
+            .cloned()
+            .collect()
+    }
+
+    /// Returns every node, notes first and then unresolved targets, along with
+    /// the links between them as pairs of indices into the nodes.
+    pub fn nodes_and_edges(&self) -> (Vec<GraphNode>, Vec<(usize, usize)>) {
+        let mut links = Vec::new();
+        let mut unresolved = BTreeMap::new();
+        for source in self.links.keys() {
+            for target in self.links_from(source) {
+                if let GraphNode::Unresolved(name) = &target {
+                    unresolved
+                        .entry(name.to_lowercase())
+                        .or_insert_with(|| target.clone());
+                }
+                links.push((source.clone(), target));
+            }
+        }
+
+        let nodes = self
+            .links
+            .keys()
+            .map(|path| GraphNode::Note(path.clone()))
+            .chain(unresolved.into_values())
+            .collect::<Vec<_>>();
+        let indices = nodes
+            .iter()
+            .enumerate()
+            .map(|(ix, node)| (node_key(node), ix))
+            .collect::<HashMap<_, _>>();
+        let mut seen = HashSet::default();
+        let edges = links
+            .into_iter()
+            .filter_map(|(source, target)| {
+                let source = indices[&node_key(&GraphNode::Note(source))];
+                let target = indices[&node_key(&target)];
+                seen.insert((source, target)).then_some((source, target))
+            })
+            .collect();
+        (nodes, edges)
+    }
+
+    fn resolve(&self, target: &str, from: &Path) -> Option<GraphNode> {
+        match self.resolver.resolve(target, Some(from)) {
+            Some(path) if is_note(&path) => Some(GraphNode::Note(path)),
+            Some(_) => None,
+            // Targets with an extension other than `.md` are missing attachments.
+            None if Path::new(target).extension().is_none() || is_note(Path::new(target)) => {
+                Some(GraphNode::Unresolved(target.to_string()))
+            }
+            None => None,
+        }
+    }
+}
+
+/// Unresolved targets are matched case-insensitively, like note names.
+fn node_key(node: &GraphNode) -> GraphNode {
+    match node {
+        GraphNode::Note(path) => GraphNode::Note(path.clone()),
+        GraphNode::Unresolved(name) => GraphNode::Unresolved(name.to_lowercase()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    fn note(path: &str) -> GraphNode {
+        GraphNode::Note(Path::new(path).into())
+    }
+
+    #[test]
+    fn test_links_between_notes() {
+        let mut graph = NoteGraph::new();
+        graph.insert_note(
+            Path::new("Index.md").into(),
+            indoc! {"
+                See [[Projects/Roadmap|the roadmap]] and [[Ideas#Later]].
+                ![[diagram.png]] ![[Ideas]] [[Index]]
+                `[[Not a link]]`
+                [[Someday]] and [[someday]]
+            "},
+        );
+        graph.insert_note(Path::new("Ideas.md").into(), "Back to [[Index]].");
+        graph.insert_note(Path::new("Projects/Roadmap.md").into(), "");
+        graph.insert_attachment(Path::new("diagram.png").into());
+
+        assert_eq!(
+            graph.links_from(Path::new("Index.md")),
+            vec![
+                note("Projects/Roadmap.md"),
+                note("Ideas.md"),
+                GraphNode::Unresolved("Someday".into()),
+            ]
+        );
+        assert_eq!(
+            graph.links_to(Path::new("Index.md")),
+            vec![Arc::from(Path::new("Ideas.md"))]
+        );
+        assert_eq!(
+            graph.links_to(Path::new("Ideas.md")),
+            vec![Arc::from(Path::new("Index.md"))]
+        );
+
+        let (nodes, edges) = graph.nodes_and_edges();
+        assert_eq!(
+            nodes,
+            vec![
+                note("Ideas.md"),
+                note("Index.md"),
+                note("Projects/Roadmap.md"),
+                GraphNode::Unresolved("Someday".into()),
+            ]
+        );
+        assert_eq!(edges, vec![(0, 1), (1, 2), (1, 0), (1, 3)]);
+    }
+
+    #[test]
+    fn test_updating_notes() {
+        let mut graph = NoteGraph::new();
+        graph.insert_note(Path::new("a.md").into(), "[[b]] [[c]]");
+        graph.insert_note(Path::new("b.md").into(), "");
+        assert_eq!(
+            graph.links_from(Path::new("a.md")),
+            vec![note("b.md"), GraphNode::Unresolved("c".into())]
+        );
+
+        // Creating the missing note resolves the links to it.
+        graph.insert_note(Path::new("notes/c.md").into(), "[[a]]");
+        assert_eq!(
+            graph.links_from(Path::new("a.md")),
+            vec![note("b.md"), note("notes/c.md")]
+        );
+        assert_eq!(
+            graph.links_to(Path::new("a.md")),
+            vec![Arc::from(Path::new("notes/c.md"))]
+        );
+
+        // Editing a note replaces its links.
+        graph.insert_note(Path::new("a.md").into(), "[[c]]");
+        assert_eq!(
+            graph.links_from(Path::new("a.md")),
+            vec![note("notes/c.md")]
+        );
+        assert_eq!(graph.links_to(Path::new("b.md")), Vec::<Arc<Path>>::new());
+
+        // Deleting a note leaves the links to it unresolved.
+        graph.remove(Path::new("notes/c.md"));
+        assert!(!graph.contains_note(Path::new("notes/c.md")));
+        assert_eq!(
+            graph.links_from(Path::new("a.md")),
+            vec![GraphNode::Unresolved("c".into())]
+        );
+        assert_eq!(graph.links_to(Path::new("a.md")), Vec::<Arc<Path>>::new());
+    }
+}
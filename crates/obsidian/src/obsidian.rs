@@ -0,0 +1,553 @@
+//! Obsidian-style navigation for a vault of markdown notes: following
+//! `[[wiki links]]`, viewing the notes as a graph of their links, editing
+//! markdown tables and viewing task lists as kanban boards.
+
+mod graph_view;
+mod kanban;
+mod kanban_view;
+mod markdown_table;
+mod note_graph;
+mod table_editor;
+mod vault_graph;
+
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::Result;
+use command_palette_hooks::CommandPaletteFilter;
+use editor::{Editor, scroll::Autoscroll};
+use gpui::{App, AppContext as _, Context, Task, Window, actions};
+use project::WorktreeId;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources, SettingsStore};
+use vault::{LinkSubpath, NoteResolver, WikiLink, is_note, sanitize_file_name, wiki_links};
+use workspace::{SaveIntent, Workspace, item::ItemHandle as _};
+
+pub use graph_view::GraphView;
+pub use kanban_view::KanbanView;
+pub use note_graph::{GraphNode, NoteGraph};
+pub use vault_graph::VaultGraph;
+
+actions!(
+    obsidian,
+    [OpenNote, UpdateGraph, ToggleGraph, OpenKanbanBoard]
+);
+
+/// Settings for Obsidian-style note navigation.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ObsidianSettings {
+    /// Whether to enable following wiki links, the note graph, table editing
+    /// and kanban boards.
+    ///
+    /// Default: false
+    pub enabled: Option<bool>,
+}
+
+impl ObsidianSettings {
+    fn enabled(cx: &App) -> bool {
+        Self::get_global(cx).enabled.unwrap_or(false)
+    }
+}
+
+impl settings::Settings for ObsidianSettings {
+    const KEY: Option<&'static str> = Some("obsidian");
+
+    type FileContent = Self;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _: &mut App) -> Result<Self> {
+        sources.json_merge()
+    }
+}
+
+pub fn init(cx: &mut App) {
+    ObsidianSettings::register(cx);
+
+    cx.observe_global::<SettingsStore>(update_command_palette_filter)
+        .detach();
+
+    cx.observe_new(|workspace: &mut Workspace, _window, cx| {
+        // The command palette filter may not have existed when the settings
+        // were first loaded.
+        update_command_palette_filter(cx);
+        workspace
+            .register_action(open_link_under_cursor)
+            .register_action(update_graph)
+            .register_action(toggle_graph)
+            .register_action(open_kanban_board);
+    })
+    .detach();
+
+    table_editor::init(cx);
+}
+
+fn update_command_palette_filter(cx: &mut App) {
+    let enabled = ObsidianSettings::enabled(cx);
+    CommandPaletteFilter::update_global(cx, |filter, _| {
+        if enabled {
+            filter.show_namespace("obsidian");
+        } else {
+            filter.hide_namespace("obsidian");
+        }
+    });
+}
+
+/// Opens the note linked by the wiki link under the cursor, creating it when
+/// no note by that name exists yet.
+fn open_link_under_cursor(
+    workspace: &mut Workspace,
+    _: &OpenNote,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    if !ObsidianSettings::enabled(cx) {
+        cx.propagate();
+        return;
+    }
+    let Some(editor) = workspace.active_item_as::<Editor>(cx) else {
+        return;
+    };
+    let Some(project_path) = editor.project_path(cx) else {
+        return;
+    };
+    let Some((text, offset)) = editor.update(cx, |editor, cx| {
+        let offset = editor.selections.newest::<usize>(cx).head();
+        let buffer = editor.buffer().read(cx).as_singleton()?;
+        Some((buffer.read(cx).text(), offset))
+    }) else {
+        return;
+    };
+    let Some(link) = link_at(&text, offset) else {
+        return;
+    };
+    let Some(worktree) = workspace
+        .project()
+        .read(cx)
+        .worktree_for_id(project_path.worktree_id, cx)
+    else {
+        return;
+    };
+
+    let resolver = NoteResolver::new(
+        worktree
+            .read(cx)
+            .snapshot()
+            .files(false, 0)
+            .map(|entry| entry.path.clone()),
+    );
+    let node = match resolver.resolve(&link.target, Some(&project_path.path)) {
+        Some(path) => GraphNode::Note(path),
+        None => GraphNode::Unresolved(link.target),
+    };
+    open_note(
+        workspace,
+        project_path.worktree_id,
+        &node,
+        link.subpath,
+        window,
+        cx,
+    )
+    .detach_and_log_err(cx);
+}
+
+fn update_graph(
+    workspace: &mut Workspace,
+    _: &UpdateGraph,
+    _window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    if !ObsidianSettings::enabled(cx) {
+        cx.propagate();
+        return;
+    }
+    for graph_view in workspace.items_of_type::<GraphView>(cx).collect::<Vec<_>>() {
+        let vault_graph = graph_view.read(cx).vault_graph().clone();
+        vault_graph.update(cx, |vault_graph, cx| vault_graph.rebuild(cx));
+    }
+}
+
+/// Opens the graph view, or closes it when it's the active item.
+fn toggle_graph(
+    workspace: &mut Workspace,
+    _: &ToggleGraph,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    if !ObsidianSettings::enabled(cx) {
+        cx.propagate();
+        return;
+    }
+    if let Some(graph_view) = workspace.active_item_as::<GraphView>(cx) {
+        workspace
+            .active_pane()
+            .update(cx, |pane, cx| {
+                pane.close_item_by_id(graph_view.item_id(), SaveIntent::Skip, window, cx)
+            })
+            .detach_and_log_err(cx);
+        return;
+    }
+    if let Some(graph_view) = workspace.items_of_type::<GraphView>(cx).next() {
+        workspace.activate_item(&graph_view, true, true, window, cx);
+        return;
+    }
+
+    let vault_graph = cx.new(|cx| VaultGraph::new(workspace.project().clone(), cx));
+    let workspace_handle = cx.entity().downgrade();
+    let graph_view = cx.new(|cx| GraphView::new(vault_graph, workspace_handle, cx));
+    workspace.add_item_to_active_pane(Box::new(graph_view), None, true, window, cx);
+}
+
+fn open_kanban_board(
+    workspace: &mut Workspace,
+    _: &OpenKanbanBoard,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    if !ObsidianSettings::enabled(cx) {
+        cx.propagate();
+        return;
+    }
+    kanban_view::open_board(workspace, window, cx);
+}
+
+/// Opens a note, creating it first when it's unresolved, and moves the cursor
+/// to the linked heading or block.
+pub(crate) fn open_note(
+    workspace: &mut Workspace,
+    worktree_id: WorktreeId,
+    node: &GraphNode,
+    subpath: Option<LinkSubpath>,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) -> Task<Result<()>> {
+    let (path, create): (Arc<Path>, _) = match node {
+        GraphNode::Note(path) => (path.clone(), false),
+        GraphNode::Unresolved(target) => (new_note_path(target).into(), true),
+    };
+    let project = workspace.project().clone();
+    cx.spawn_in(window, async move |workspace, cx| {
+        if create {
+            project
+                .update(cx, |project, cx| {
+                    project.create_entry((worktree_id, path.clone()), false, cx)
+                })?
+                .await?;
+        }
+        let item = workspace
+            .update_in(cx, |workspace, window, cx| {
+                workspace.open_path((worktree_id, path), None, true, window, cx)
+            })?
+            .await?;
+
+        let (Some(subpath), Some(editor)) = (subpath, item.downcast::<Editor>()) else {
+            return Ok(());
+        };
+        editor.update_in(cx, |editor, window, cx| {
+            let text = editor.buffer().read(cx).snapshot(cx).text();
+            let range = match &subpath {
+                LinkSubpath::Heading(heading) => vault::heading_section(&text, heading),
+                LinkSubpath::Block(block_id) => vault::block_range(&text, block_id),
+            };
+            if let Some(range) = range {
+                editor.change_selections(Some(Autoscroll::center()), window, cx, |selections| {
+                    selections.select_ranges([range.start..range.start])
+                });
+            }
+        })
+    })
+}
+
+/// Returns the wiki link that `offset` is in or at either end of.
+fn link_at(text: &str, offset: usize) -> Option<WikiLink> {
+    wiki_links(text)
+        .into_iter()
+        .find(|link| link.range.start <= offset && offset <= link.range.end)
+}
+
+/// The path of the note that a link to the missing note `target` creates,
+/// relative to the vault root.
+fn new_note_path(target: &str) -> PathBuf {
+    let target = Path::new(target.trim());
+    let stem = if is_note(target) {
+        target.with_extension("")
+    } else {
+        target.to_path_buf()
+    };
+    let name = stem
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let folder = stem
+        .parent()
+        .into_iter()
+        .flat_map(Path::components)
+        .filter(|component| matches!(component, Component::Normal(_)))
+        .collect::<PathBuf>();
+    folder.join(format!("{}.md", sanitize_file_name(&name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::{FakeFs, Fs as _};
+    use gpui::{Entity, TestAppContext, VisualTestContext};
+    use pretty_assertions::assert_eq;
+    use project::Project;
+    use serde_json::json;
+    use util::path;
+    use workspace::AppState;
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            AppState::test(cx);
+            language::init(cx);
+            editor::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+            crate::init(cx);
+        });
+    }
+
+    pub(crate) fn set_enabled(enabled: bool, cx: &mut VisualTestContext) {
+        cx.update(|_, cx| {
+            SettingsStore::update_global(cx, |store, cx| {
+                store.update_user_settings::<ObsidianSettings>(cx, |settings| {
+                    settings.enabled = Some(enabled);
+                });
+            });
+        });
+    }
+
+    fn active_path(workspace: &Entity<Workspace>, cx: &mut VisualTestContext) -> Arc<Path> {
+        workspace.update(cx, |workspace, cx| {
+            workspace
+                .active_item(cx)
+                .and_then(|item| item.project_path(cx))
+                .unwrap()
+                .path
+        })
+    }
+
+    async fn open_editor_at(
+        workspace: &Entity<Workspace>,
+        path: &str,
+        offset: usize,
+        cx: &mut VisualTestContext,
+    ) -> Entity<Editor> {
+        let worktree_id = workspace.update(cx, |workspace, cx| {
+            workspace
+                .project()
+                .read(cx)
+                .worktrees(cx)
+                .next()
+                .unwrap()
+                .read(cx)
+                .id()
+        });
+        let editor = workspace
+            .update_in(cx, |workspace, window, cx| {
+                workspace.open_path((worktree_id, path), None, true, window, cx)
+            })
+            .await
+            .unwrap()
+            .downcast::<Editor>()
+            .unwrap();
+        editor.update_in(cx, |editor, window, cx| {
+            editor.change_selections(None, window, cx, |selections| {
+                selections.select_ranges([offset..offset])
+            })
+        });
+        editor
+    }
+
+    #[test]
+    fn test_link_at() {
+        let text = "See [[Ideas#Later|later]] and ![[Plan]].";
+        assert_eq!(link_at(text, 0), None);
+        assert_eq!(link_at(text, 4).unwrap().target, "Ideas");
+        assert_eq!(link_at(text, 12).unwrap().target, "Ideas");
+        assert_eq!(link_at(text, 25).unwrap().target, "Ideas");
+        assert_eq!(link_at(text, 27), None);
+        assert_eq!(link_at(text, 34).unwrap().target, "Plan");
+    }
+
+    #[test]
+    fn test_new_note_path() {
+        assert_eq!(new_note_path("Someday"), Path::new("Someday.md"));
+        assert_eq!(
+            new_note_path("Projects/Roadmap.md"),
+            Path::new("Projects/Roadmap.md")
+        );
+        assert_eq!(
+            new_note_path("../Outside: a plan?"),
+            Path::new("Outside a plan.md")
+        );
+    }
+
+    #[gpui::test]
+    async fn test_open_note(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/vault"),
+            json!({
+                "Index.md": "See [[Ideas#Later]] and [[Projects/Someday]].",
+                "Ideas.md": "# Ideas\nNow\n## Later\nSome day\n",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), [path!("/vault").as_ref()], cx).await;
+        let (workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project.clone(), window, cx));
+
+        open_editor_at(&workspace, "Index.md", 8, cx).await;
+
+        // Links aren't followed unless the feature is enabled.
+        cx.dispatch_action(OpenNote);
+        cx.run_until_parked();
+        assert_eq!(active_path(&workspace, cx).as_ref(), Path::new("Index.md"));
+
+        set_enabled(true, cx);
+        cx.dispatch_action(OpenNote);
+        cx.run_until_parked();
+        assert_eq!(active_path(&workspace, cx).as_ref(), Path::new("Ideas.md"));
+        let editor = workspace.update(cx, |workspace, cx| {
+            workspace.active_item_as::<Editor>(cx).unwrap()
+        });
+        let cursor = editor.update(cx, |editor, cx| {
+            editor.selections.newest::<usize>(cx).head()
+        });
+        assert_eq!(cursor, "# Ideas\nNow\n".len());
+
+        // Following a link to a missing note creates it.
+        open_editor_at(&workspace, "Index.md", 30, cx).await;
+        cx.dispatch_action(OpenNote);
+        cx.run_until_parked();
+        assert_eq!(
+            active_path(&workspace, cx).as_ref(),
+            Path::new("Projects/Someday.md")
+        );
+        assert_eq!(
+            fs.load(path!("/vault/Projects/Someday.md").as_ref())
+                .await
+                .unwrap(),
+            ""
+        );
+    }
+
+    #[gpui::test]
+    async fn test_graph_updates(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/vault"),
+            json!({
+                "Index.md": "[[Ideas]] [[Someday]] ![[chart.png]]",
+                "Ideas.md": "Back to [[Index]]",
+                "chart.png": "",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), [path!("/vault").as_ref()], cx).await;
+        let (workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project.clone(), window, cx));
+        set_enabled(true, cx);
+
+        cx.dispatch_action(ToggleGraph);
+        cx.run_until_parked();
+        let graph_view = workspace.update(cx, |workspace, cx| {
+            workspace.active_item_as::<GraphView>(cx).unwrap()
+        });
+        let vault_graph =
+            graph_view.read_with(cx, |graph_view, _| graph_view.vault_graph().clone());
+        let links_from = |path: &str, cx: &mut VisualTestContext| {
+            vault_graph.read_with(cx, |vault_graph, _| {
+                vault_graph.graph().links_from(Path::new(path))
+            })
+        };
+        assert_eq!(
+            links_from("Index.md", cx),
+            vec![
+                GraphNode::Note(Path::new("Ideas.md").into()),
+                GraphNode::Unresolved("Someday".into()),
+            ]
+        );
+
+        // Notes are updated as they change on disk.
+        fs.insert_file(path!("/vault/Someday.md"), "[[Ideas]]".into())
+            .await;
+        fs.insert_file(path!("/vault/Ideas.md"), "Nothing here".into())
+            .await;
+        cx.run_until_parked();
+        assert_eq!(
+            links_from("Index.md", cx),
+            vec![
+                GraphNode::Note(Path::new("Ideas.md").into()),
+                GraphNode::Note(Path::new("Someday.md").into()),
+            ]
+        );
+        assert_eq!(links_from("Ideas.md", cx), Vec::new());
+        vault_graph.read_with(cx, |vault_graph, _| {
+            assert_eq!(
+                vault_graph.graph().links_to(Path::new("Ideas.md")),
+                vec![
+                    Arc::from(Path::new("Index.md")),
+                    Arc::from(Path::new("Someday.md"))
+                ]
+            );
+        });
+
+        fs.remove_file(path!("/vault/Someday.md").as_ref(), Default::default())
+            .await
+            .unwrap();
+        cx.run_until_parked();
+        assert_eq!(
+            links_from("Index.md", cx),
+            vec![
+                GraphNode::Note(Path::new("Ideas.md").into()),
+                GraphNode::Unresolved("Someday".into()),
+            ]
+        );
+
+        // Clicking an unresolved node creates its note.
+        workspace.update_in(cx, |workspace, window, cx| {
+            let worktree_id = vault_graph.read(cx).worktree_id().unwrap();
+            open_note(
+                workspace,
+                worktree_id,
+                &GraphNode::Unresolved("Someday".into()),
+                None,
+                window,
+                cx,
+            )
+            .detach();
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            active_path(&workspace, cx).as_ref(),
+            Path::new("Someday.md")
+        );
+        assert_eq!(
+            links_from("Index.md", cx),
+            vec![
+                GraphNode::Note(Path::new("Ideas.md").into()),
+                GraphNode::Note(Path::new("Someday.md").into()),
+            ]
+        );
+
+        // Toggling activates the open graph, and closes it when it's active.
+        cx.dispatch_action(ToggleGraph);
+        cx.run_until_parked();
+        assert!(workspace.update(cx, |workspace, cx| {
+            workspace.active_item_as::<GraphView>(cx).is_some()
+        }));
+        cx.dispatch_action(ToggleGraph);
+        cx.run_until_parked();
+        assert_eq!(
+            workspace.update(cx, |workspace, cx| workspace
+                .items_of_type::<GraphView>(cx)
+                .count()),
+            0
+        );
+    }
+}
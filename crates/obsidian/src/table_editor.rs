@@ -0,0 +1,684 @@
+use std::ops::Range;
+
+use editor::{Editor, EditorEvent, EditorMode, scroll::Autoscroll};
+use gpui::{Action, App, Context, Window, actions};
+use language::{BufferSnapshot, Point, ToOffset as _, TransactionId};
+
+use crate::ObsidianSettings;
+use crate::markdown_table::{CellPosition, Table, cell_at};
+
+actions!(
+    obsidian,
+    [
+        NextTableCell,
+        PreviousTableCell,
+        InsertTableRowBelow,
+        InsertTableRowAbove,
+        DeleteTableRow,
+        MoveTableRowUp,
+        MoveTableRowDown,
+        InsertTableColumnLeft,
+        InsertTableColumnRight,
+        DeleteTableColumn,
+        MoveTableColumnLeft,
+        MoveTableColumnRight,
+        SortTableAscending,
+        SortTableDescending,
+        FormatTable,
+        ConvertToTable,
+    ]
+);
+
+pub(crate) fn init(cx: &mut App) {
+    cx.observe_new(|editor: &mut Editor, window, cx: &mut Context<Editor>| {
+        let Some(window) = window else {
+            return;
+        };
+        if editor.mode() != EditorMode::Full {
+            return;
+        }
+        register_table_actions(editor, cx);
+        cx.subscribe_in(
+            &cx.entity(),
+            window,
+            |editor, _, event: &EditorEvent, window, cx| {
+                if let EditorEvent::Edited { transaction_id } = event {
+                    realign_table(editor, *transaction_id, window, cx);
+                }
+            },
+        )
+        .detach();
+    })
+    .detach();
+}
+
+fn register_table_actions(editor: &mut Editor, cx: &mut Context<Editor>) {
+    on_table_action::<NextTableCell>(editor, true, cx, |table, cell| {
+        if cell.column + 1 < table.column_count() {
+            (cell.row, cell.column + 1)
+        } else {
+            // Tabbing out of the last cell adds a row, like in a spreadsheet.
+            if cell.row + 1 == table.rows.len() {
+                table.insert_row(cell.row + 1);
+            }
+            (cell.row + 1, 0)
+        }
+    });
+    on_table_action::<PreviousTableCell>(editor, true, cx, |table, cell| {
+        match (cell.row, cell.column) {
+            (0, 0) => (0, 0),
+            (row, 0) => (row - 1, table.column_count() - 1),
+            (row, column) => (row, column - 1),
+        }
+    });
+    on_table_action::<InsertTableRowBelow>(editor, true, cx, |table, cell| {
+        table.insert_row(cell.row + 1);
+        (cell.row + 1, 0)
+    });
+    on_table_action::<InsertTableRowAbove>(editor, true, cx, |table, cell| {
+        table.insert_row(cell.row);
+        (cell.row.max(1), 0)
+    });
+    on_table_action::<DeleteTableRow>(editor, false, cx, |table, cell| {
+        table.remove_row(cell.row);
+        (cell.row.min(table.rows.len() - 1), cell.column)
+    });
+    on_table_action::<MoveTableRowUp>(editor, false, cx, |table, cell| {
+        if cell.row > 1 && table.swap_rows(cell.row, cell.row - 1) {
+            (cell.row - 1, cell.column)
+        } else {
+            (cell.row, cell.column)
+        }
+    });
+    on_table_action::<MoveTableRowDown>(editor, false, cx, |table, cell| {
+        if table.swap_rows(cell.row, cell.row + 1) {
+            (cell.row + 1, cell.column)
+        } else {
+            (cell.row, cell.column)
+        }
+    });
+    on_table_action::<InsertTableColumnLeft>(editor, true, cx, |table, cell| {
+        table.insert_column(cell.column);
+        (cell.row, cell.column)
+    });
+    on_table_action::<InsertTableColumnRight>(editor, true, cx, |table, cell| {
+        table.insert_column(cell.column + 1);
+        (cell.row, cell.column + 1)
+    });
+    on_table_action::<DeleteTableColumn>(editor, false, cx, |table, cell| {
+        table.remove_column(cell.column);
+        (cell.row, cell.column.min(table.column_count() - 1))
+    });
+    on_table_action::<MoveTableColumnLeft>(editor, false, cx, |table, cell| {
+        if cell.column > 0 && table.swap_columns(cell.column, cell.column - 1) {
+            (cell.row, cell.column - 1)
+        } else {
+            (cell.row, cell.column)
+        }
+    });
+    on_table_action::<MoveTableColumnRight>(editor, false, cx, |table, cell| {
+        if table.swap_columns(cell.column, cell.column + 1) {
+            (cell.row, cell.column + 1)
+        } else {
+            (cell.row, cell.column)
+        }
+    });
+    on_table_action::<SortTableAscending>(editor, false, cx, |table, cell| {
+        table.sort_by_column(cell.column, false);
+        (cell.row, cell.column)
+    });
+    on_table_action::<SortTableDescending>(editor, false, cx, |table, cell| {
+        table.sort_by_column(cell.column, true);
+        (cell.row, cell.column)
+    });
+    on_table_action::<FormatTable>(editor, false, cx, |_, cell| (cell.row, cell.column));
+
+    let editor_handle = cx.entity().downgrade();
+    editor
+        .register_action(move |_: &ConvertToTable, window, cx| {
+            let handled = ObsidianSettings::enabled(cx)
+                && editor_handle
+                    .update(cx, |editor, cx| convert_to_table(editor, window, cx))
+                    .unwrap_or(false);
+            if !handled {
+                cx.propagate();
+            }
+        })
+        .detach();
+}
+
+/// Handles an action by editing the table around the cursor and moving the
+/// cursor to the cell that `edit` returns. Outside of tables, the action
+/// propagates, so keys like tab keep working as usual.
+///
+/// When `select_text` is set, the text of the cell is selected. Otherwise the
+/// cursor keeps its offset in the text of its cell.
+fn on_table_action<A: Action>(
+    editor: &mut Editor,
+    select_text: bool,
+    cx: &mut Context<Editor>,
+    edit: fn(&mut Table, CellPosition) -> (usize, usize),
+) {
+    let editor_handle = cx.entity().downgrade();
+    editor
+        .register_action(move |_: &A, window, cx| {
+            let handled = ObsidianSettings::enabled(cx)
+                && editor_handle
+                    .update(cx, |editor, cx| {
+                        edit_table_at_cursor(editor, select_text, edit, window, cx)
+                    })
+                    .unwrap_or(false);
+            if !handled {
+                cx.propagate();
+            }
+        })
+        .detach();
+}
+
+/// The table around the cursor, when there's a single cursor.
+struct TableAtCursor {
+    range: Range<usize>,
+    text: String,
+    table: Table,
+    cell: CellPosition,
+    /// The cursor's offset in the text of the table.
+    cursor: usize,
+}
+
+fn table_at_cursor(editor: &mut Editor, cx: &mut Context<Editor>) -> Option<TableAtCursor> {
+    if editor.selections.count() > 1 {
+        return None;
+    }
+    let buffer = editor.buffer().read(cx).as_singleton()?.read(cx).snapshot();
+    let cursor = editor.selections.newest::<usize>(cx).head();
+    let range = table_range(&buffer, cursor)?;
+    let text = buffer.text_for_range(range.clone()).collect::<String>();
+    let table = Table::parse(&text)?;
+    let cursor = cursor - range.start;
+    let cell = cell_at(&text, cursor)?;
+    Some(TableAtCursor {
+        range,
+        text,
+        table,
+        cell,
+        cursor,
+    })
+}
+
+/// Returns the lines of the `pipe_table` syntax node on the line of `offset`.
+fn table_range(buffer: &BufferSnapshot, offset: usize) -> Option<Range<usize>> {
+    let row = buffer.offset_to_point(offset).row;
+    let indent = buffer.indent_size_for_line(row).len;
+    if indent >= buffer.line_len(row) {
+        return None;
+    }
+    let line_start = Point::new(row, indent).to_offset(buffer);
+    for layer in buffer.syntax_layers() {
+        let mut node = layer
+            .node()
+            .descendant_for_byte_range(line_start, line_start + 1);
+        while let Some(current) = node {
+            if current.kind() == "pipe_table" {
+                let start_row = buffer.offset_to_point(current.start_byte()).row;
+                let end = buffer.offset_to_point(current.end_byte());
+                let end_row = if end.column == 0 && end.row > start_row {
+                    end.row - 1
+                } else {
+                    end.row
+                };
+                return Some(
+                    Point::new(start_row, 0).to_offset(buffer)
+                        ..Point::new(end_row, buffer.line_len(end_row)).to_offset(buffer),
+                );
+            }
+            node = current.parent();
+        }
+    }
+    None
+}
+
+fn edit_table_at_cursor(
+    editor: &mut Editor,
+    select_text: bool,
+    edit: fn(&mut Table, CellPosition) -> (usize, usize),
+    window: &mut Window,
+    cx: &mut Context<Editor>,
+) -> bool {
+    let Some(TableAtCursor {
+        range,
+        text,
+        mut table,
+        cell,
+        ..
+    }) = table_at_cursor(editor, cx)
+    else {
+        return false;
+    };
+    let (row, column) = edit(&mut table, cell);
+    let formatted = table.format();
+    let cell_range = formatted.cell_range(row, column);
+    let selection = if select_text {
+        cell_range
+    } else {
+        let cursor = cell_range.start + cell.offset.min(cell_range.len());
+        cursor..cursor
+    };
+    replace_text(
+        editor,
+        range.start,
+        &text,
+        &formatted.text,
+        selection,
+        window,
+        cx,
+    );
+    true
+}
+
+/// Lines up the columns of the table that was just edited, keeping the cursor
+/// in its cell. The realignment is undone along with the edit.
+fn realign_table(
+    editor: &mut Editor,
+    transaction_id: TransactionId,
+    window: &mut Window,
+    cx: &mut Context<Editor>,
+) {
+    if !ObsidianSettings::enabled(cx) {
+        return;
+    }
+    let Some(buffer) = editor.buffer().read(cx).as_singleton() else {
+        return;
+    };
+    // Undoing an edit moves it to the redo stack, and shouldn't be realigned.
+    let is_new_edit = buffer
+        .read(cx)
+        .peek_undo_stack()
+        .is_some_and(|entry| entry.transaction_id() == transaction_id);
+    if !is_new_edit {
+        return;
+    }
+    let Some(TableAtCursor {
+        range,
+        text,
+        mut table,
+        cell,
+        cursor,
+    }) = table_at_cursor(editor, cx)
+    else {
+        return;
+    };
+    // Leave the delimiter row alone while it's being edited.
+    if text[..cursor].matches('\n').count() == 1 {
+        return;
+    }
+
+    // Keep the whitespace that was just typed after the text of a cell.
+    let cell_text = &mut table.rows[cell.row][cell.column];
+    if cell.offset > cell_text.len() {
+        let padding = cell.offset - cell_text.len();
+        cell_text.extend(std::iter::repeat_n(' ', padding));
+    }
+    let formatted = table.format();
+    if formatted.text == text {
+        return;
+    }
+    let cell_range = formatted.cell_range(cell.row, cell.column);
+    let cursor = cell_range.start + cell.offset.min(cell_range.len());
+    let realignment = replace_text(
+        editor,
+        range.start,
+        &text,
+        &formatted.text,
+        cursor..cursor,
+        window,
+        cx,
+    );
+    if let Some(realignment) = realignment.filter(|id| *id != transaction_id) {
+        editor.buffer().update(cx, |buffer, cx| {
+            buffer.merge_transactions(realignment, transaction_id, cx)
+        });
+    }
+}
+
+/// Converts the comma- or tab-separated values in the selection to a table.
+fn convert_to_table(editor: &mut Editor, window: &mut Window, cx: &mut Context<Editor>) -> bool {
+    let selection = editor.selections.newest::<usize>(cx).range();
+    if selection.is_empty() {
+        return false;
+    }
+    let buffer = editor.buffer().read(cx).snapshot(cx);
+    let text = buffer.text_for_range(selection.clone()).collect::<String>();
+    let Some(table) = Table::from_delimited(&text) else {
+        return false;
+    };
+    let formatted = table.format();
+    let mut new_text = formatted.text;
+    if text.ends_with('\n') {
+        new_text.push('\n');
+    }
+    let cell_range = formatted.cell_range(0, 0);
+    replace_text(
+        editor,
+        selection.start,
+        &text,
+        &new_text,
+        cell_range,
+        window,
+        cx,
+    );
+    true
+}
+
+/// Replaces `old_text` at `start` with `new_text`, editing only the part that
+/// changed so anchors elsewhere in the table stay put, and selects
+/// `selection` in the new text.
+fn replace_text(
+    editor: &mut Editor,
+    start: usize,
+    old_text: &str,
+    new_text: &str,
+    selection: Range<usize>,
+    window: &mut Window,
+    cx: &mut Context<Editor>,
+) -> Option<TransactionId> {
+    let (changed_range, replacement) = changed_range(old_text, new_text);
+    let selection = start + selection.start..start + selection.end;
+    editor.transact(window, cx, |editor, window, cx| {
+        if !changed_range.is_empty() || !replacement.is_empty() {
+            let changed_range = start + changed_range.start..start + changed_range.end;
+            editor.edit([(changed_range, replacement)], cx);
+        }
+        editor.change_selections(Some(Autoscroll::fit()), window, cx, |selections| {
+            selections.select_ranges([selection])
+        });
+    })
+}
+
+/// Returns the range of `old` that differs from `new`, and the text of `new`
+/// to replace it with.
+fn changed_range<'a>(old: &str, new: &'a str) -> (Range<usize>, &'a str) {
+    let prefix = old
+        .char_indices()
+        .zip(new.chars())
+        .find(|((_, old_char), new_char)| old_char != new_char)
+        .map_or(old.len().min(new.len()), |((ix, _), _)| ix);
+    let suffix = old[prefix..]
+        .chars()
+        .rev()
+        .zip(new[prefix..].chars().rev())
+        .take_while(|(old_char, new_char)| old_char == new_char)
+        .map(|(c, _)| c.len_utf8())
+        .sum::<usize>();
+    (prefix..old.len() - suffix, &new[prefix..new.len() - suffix])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::set_enabled;
+    use editor::actions::{SelectAll, Undo};
+    use gpui::{AppContext as _, Entity, TestAppContext, VisualTestContext};
+    use indoc::indoc;
+    use language::{Buffer, Language};
+    use pretty_assertions::assert_eq;
+    use project::Project;
+    use std::sync::Arc;
+    use workspace::AppState;
+
+    fn init_test(cx: &mut TestAppContext) -> Arc<Language> {
+        cx.update(|cx| {
+            AppState::test(cx);
+            language::init(cx);
+            editor::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+            crate::init(cx);
+        });
+        languages::language("markdown", tree_sitter_md::LANGUAGE.into())
+    }
+
+    /// Opens an editor on `text`, with the cursor at `ˇ`.
+    fn open_editor(
+        text: &str,
+        markdown: Arc<Language>,
+        cx: &mut TestAppContext,
+    ) -> (Entity<Editor>, VisualTestContext) {
+        let cursor = text.find('ˇ').unwrap();
+        let text = text.replace('ˇ', "");
+        let buffer = cx.new(|cx| Buffer::local(text, cx).with_language(markdown, cx));
+        let window = cx.add_window(|window, cx| {
+            let mut editor = Editor::for_buffer(buffer, None, window, cx);
+            editor.change_selections(None, window, cx, |selections| {
+                selections.select_ranges([cursor..cursor])
+            });
+            editor
+        });
+        let editor = window.root(cx).unwrap();
+        let mut cx = VisualTestContext::from_window(*window, cx);
+        cx.run_until_parked();
+        (editor, cx)
+    }
+
+    /// The text of the editor, with `«»` around the selection or `ˇ` at the
+    /// cursor.
+    fn marked_text(editor: &Entity<Editor>, cx: &mut VisualTestContext) -> String {
+        editor.update(cx, |editor, cx| {
+            let mut text = editor.text(cx);
+            let selection = editor.selections.newest::<usize>(cx).range();
+            if selection.is_empty() {
+                text.insert(selection.start, 'ˇ');
+            } else {
+                text.insert(selection.end, '»');
+                text.insert(selection.start, '«');
+            }
+            text
+        })
+    }
+
+    fn dispatch(action: impl Action, cx: &mut VisualTestContext) {
+        cx.dispatch_action(action);
+        cx.run_until_parked();
+    }
+
+    #[gpui::test]
+    async fn test_navigating_cells(cx: &mut TestAppContext) {
+        let markdown = init_test(cx);
+        let (editor, mut cx) = open_editor(
+            indoc! {"
+                Intro
+
+                | Name | Qty |
+                |---|---|
+                | appleˇ | 3 |
+            "},
+            markdown,
+            cx,
+        );
+
+        // Actions outside of tables do nothing while the crate is disabled.
+        dispatch(NextTableCell, &mut cx);
+        assert_eq!(
+            marked_text(&editor, &mut cx),
+            indoc! {"
+                Intro
+
+                | Name | Qty |
+                |---|---|
+                | appleˇ | 3 |
+            "}
+        );
+
+        set_enabled(true, &mut cx);
+        dispatch(NextTableCell, &mut cx);
+        assert_eq!(
+            marked_text(&editor, &mut cx),
+            indoc! {"
+                Intro
+
+                | Name  | Qty |
+                | ----- | --- |
+                | apple | «3»   |
+            "}
+        );
+
+        // Tabbing out of the last cell adds a row.
+        dispatch(NextTableCell, &mut cx);
+        assert_eq!(
+            marked_text(&editor, &mut cx),
+            indoc! {"
+                Intro
+
+                | Name  | Qty |
+                | ----- | --- |
+                | apple | 3   |
+                | ˇ      |     |
+            "}
+        );
+
+        dispatch(PreviousTableCell, &mut cx);
+        dispatch(PreviousTableCell, &mut cx);
+        assert_eq!(
+            marked_text(&editor, &mut cx),
+            indoc! {"
+                Intro
+
+                | Name  | Qty |
+                | ----- | --- |
+                | «apple» | 3   |
+                |       |     |
+            "}
+        );
+
+        dispatch(InsertTableRowBelow, &mut cx);
+        cx.simulate_input("pear");
+        cx.run_until_parked();
+        assert_eq!(
+            marked_text(&editor, &mut cx),
+            indoc! {"
+                Intro
+
+                | Name  | Qty |
+                | ----- | --- |
+                | apple | 3   |
+                | pearˇ  |     |
+                |       |     |
+            "}
+        );
+    }
+
+    #[gpui::test]
+    async fn test_realigning_on_edit(cx: &mut TestAppContext) {
+        let markdown = init_test(cx);
+        let (editor, mut cx) = open_editor(
+            indoc! {"
+                | a   | b   |
+                | --- | --- |
+                | xˇ   | y   |
+            "},
+            markdown,
+            cx,
+        );
+        set_enabled(true, &mut cx);
+
+        cx.simulate_input("yz 日本");
+        cx.run_until_parked();
+        assert_eq!(
+            marked_text(&editor, &mut cx),
+            indoc! {"
+                | a        | b   |
+                | -------- | --- |
+                | xyz 日本ˇ | y   |
+            "}
+        );
+
+        // Whitespace typed after the text of a cell is kept.
+        cx.simulate_input(" ");
+        cx.run_until_parked();
+        assert_eq!(
+            marked_text(&editor, &mut cx),
+            indoc! {"
+                | a          | b   |
+                | ---------- | --- |
+                | xyz 日本 ˇ | y   |
+            "}
+        );
+
+        // Undoing an edit undoes its realignment too.
+        dispatch(Undo, &mut cx);
+        assert_eq!(
+            marked_text(&editor, &mut cx),
+            indoc! {"
+                | a   | b   |
+                | --- | --- |
+                | xˇ   | y   |
+            "}
+        );
+    }
+
+    #[gpui::test]
+    async fn test_editing_rows_and_columns(cx: &mut TestAppContext) {
+        let markdown = init_test(cx);
+        let (editor, mut cx) = open_editor(
+            indoc! {"
+                | n | name |
+                |---|:-:|
+                | 2 | bˇ |
+                | 10 | a |
+            "},
+            markdown,
+            cx,
+        );
+        set_enabled(true, &mut cx);
+
+        dispatch(MoveTableColumnLeft, &mut cx);
+        dispatch(SortTableAscending, &mut cx);
+        assert_eq!(
+            marked_text(&editor, &mut cx),
+            indoc! {"
+                | name | n   |
+                | :--: | --- |
+                |  aˇ   | 10  |
+                |  b   | 2   |
+            "}
+        );
+
+        dispatch(MoveTableRowDown, &mut cx);
+        dispatch(InsertTableColumnRight, &mut cx);
+        dispatch(DeleteTableRow, &mut cx);
+        assert_eq!(
+            marked_text(&editor, &mut cx),
+            indoc! {"
+                | name |     | n   |
+                | :--: | --- | --- |
+                |  b   | ˇ    | 2   |
+            "}
+        );
+    }
+
+    #[gpui::test]
+    async fn test_convert_to_table(cx: &mut TestAppContext) {
+        let markdown = init_test(cx);
+        let (editor, mut cx) = open_editor("ˇname,qty\napple,3\n", markdown, cx);
+        set_enabled(true, &mut cx);
+
+        editor.update_in(&mut cx, |editor, window, cx| {
+            editor.select_all(&SelectAll, window, cx)
+        });
+        dispatch(ConvertToTable, &mut cx);
+        assert_eq!(
+            marked_text(&editor, &mut cx),
+            indoc! {"
+                | «name»  | qty |
+                | ----- | --- |
+                | apple | 3   |
+            "}
+        );
+    }
+
+    #[test]
+    fn test_changed_range() {
+        assert_eq!(changed_range("| a | b |", "| a  | b |"), (4..4, " "));
+        assert_eq!(changed_range("abc", "abc"), (3..3, ""));
+        assert_eq!(changed_range("日本", "日x本"), (3..3, "x"));
+    }
+}
@@ -0,0 +1,168 @@
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::sync::Arc;
+
+use gpui::{App, Context, Entity, Subscription, Task};
+use project::{PathChange, Project, UpdatedEntriesSet, Worktree, WorktreeId};
+use vault::is_note;
+
+use crate::note_graph::NoteGraph;
+
+/// The note graph of a project's vault, which is its first visible worktree,
+/// kept up to date as notes change on disk.
+pub struct VaultGraph {
+    project: Entity<Project>,
+    worktree_id: Option<WorktreeId>,
+    graph: NoteGraph,
+    /// Notes that were added or changed since they were last read.
+    pending_notes: BTreeSet<Arc<Path>>,
+    load_task: Option<Task<()>>,
+    _subscription: Subscription,
+}
+
+impl VaultGraph {
+    pub fn new(project: Entity<Project>, cx: &mut Context<Self>) -> Self {
+        let subscription = cx.subscribe(
+            &project,
+            |this, _, event: &project::Event, cx| match event {
+                project::Event::WorktreeAdded(_) | project::Event::WorktreeRemoved(_) => {
+                    if this
+                        .vault_worktree(cx)
+                        .map(|worktree| worktree.read(cx).id())
+                        != this.worktree_id
+                    {
+                        this.rebuild(cx);
+                    }
+                }
+                project::Event::WorktreeUpdatedEntries(worktree_id, changes)
+                    if Some(*worktree_id) == this.worktree_id =>
+                {
+                    this.update_entries(changes, cx);
+                }
+                _ => {}
+            },
+        );
+        let mut this = Self {
+            project,
+            worktree_id: None,
+            graph: NoteGraph::new(),
+            pending_notes: BTreeSet::new(),
+            load_task: None,
+            _subscription: subscription,
+        };
+        this.rebuild(cx);
+        this
+    }
+
+    pub fn graph(&self) -> &NoteGraph {
+        &self.graph
+    }
+
+    pub fn worktree_id(&self) -> Option<WorktreeId> {
+        self.worktree_id
+    }
+
+    /// Whether notes are still being read.
+    pub fn is_loading(&self) -> bool {
+        self.load_task.is_some()
+    }
+
+    /// Discards the graph and reads every note in the vault again.
+    pub fn rebuild(&mut self, cx: &mut Context<Self>) {
+        self.graph = NoteGraph::new();
+        self.pending_notes.clear();
+        let worktree = self.vault_worktree(cx);
+        self.worktree_id = worktree.as_ref().map(|worktree| worktree.read(cx).id());
+        if let Some(worktree) = worktree {
+            let snapshot = worktree.read(cx).snapshot();
+            for entry in snapshot.files(false, 0) {
+                self.add_file(entry.path.clone());
+            }
+        }
+        self.load_pending_notes(cx);
+        cx.notify();
+    }
+
+    fn vault_worktree(&self, cx: &App) -> Option<Entity<Worktree>> {
+        self.project.read(cx).visible_worktrees(cx).next()
+    }
+
+    fn add_file(&mut self, path: Arc<Path>) {
+        if is_note(&path) {
+            self.pending_notes.insert(path);
+        } else {
+            self.graph.insert_attachment(path);
+        }
+    }
+
+    fn update_entries(&mut self, changes: &UpdatedEntriesSet, cx: &mut Context<Self>) {
+        let Some(worktree) = self.vault_worktree(cx) else {
+            return;
+        };
+        let worktree = worktree.read(cx);
+        for (path, _, change) in changes.iter() {
+            if let PathChange::Removed = change {
+                self.pending_notes.remove(path);
+                self.graph.remove(path);
+            } else if worktree
+                .entry_for_path(path)
+                .is_some_and(|entry| entry.is_file() && !entry.is_ignored)
+            {
+                self.add_file(path.clone());
+            }
+        }
+        self.load_pending_notes(cx);
+        cx.notify();
+    }
+
+    fn load_pending_notes(&mut self, cx: &mut Context<Self>) {
+        if self.load_task.is_some() || self.pending_notes.is_empty() {
+            return;
+        }
+        let fs = self.project.read(cx).fs().clone();
+        self.load_task = Some(cx.spawn(async move |this, cx| {
+            loop {
+                let Some((root, paths)) = this
+                    .update(cx, |this, cx| {
+                        let paths = std::mem::take(&mut this.pending_notes);
+                        let worktree = this.vault_worktree(cx).filter(|_| !paths.is_empty());
+                        let Some(worktree) = worktree else {
+                            this.load_task = None;
+                            cx.notify();
+                            return None;
+                        };
+                        Some((worktree.read(cx).abs_path(), paths))
+                    })
+                    .ok()
+                    .flatten()
+                else {
+                    break;
+                };
+
+                let mut notes = Vec::with_capacity(paths.len());
+                for path in paths {
+                    let text = fs.load(&root.join(&path)).await;
+                    notes.push((path, text));
+                }
+
+                this.update(cx, |this, cx| {
+                    let Some(worktree) = this.vault_worktree(cx) else {
+                        return;
+                    };
+                    let worktree = worktree.read(cx);
+                    for (path, text) in notes {
+                        // Skip notes that were deleted while they were read.
+                        match text {
+                            Ok(text) if worktree.entry_for_path(&path).is_some() => {
+                                this.graph.insert_note(path, &text)
+                            }
+                            _ => this.graph.remove(&path),
+                        }
+                    }
+                    cx.notify();
+                })
+                .ok();
+            }
+        }));
+    }
+}
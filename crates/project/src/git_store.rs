@@ -21,9 +21,10 @@ use git::{
     blame::Blame,
     parse_git_remote_url,
     repository::{
-        Branch, CommitDetails, CommitDiff, CommitFile, DiffType, GitRepository,
-        GitRepositoryCheckpoint, PushOptions, Remote, RemoteCommandOutput, RepoPath, ResetMode,
-        UpstreamTrackingStatus,
+        Branch, CommitDetails, CommitDiff, CommitFile, ConflictVersions, DiffType,
+        FileHistoryEntry, GitRepository, GitRepositoryCheckpoint, GitWorktree, LogEntry,
+        LogOptions, PushOptions, RebaseState, RebaseTodoEntry, Remote, RemoteCommandOutput,
+        RepoPath, ResetMode, StashEntry, StashScope, Submodule, UpstreamTrackingStatus,
     },
     status::{
         FileStatus, GitSummary, StatusCode, TrackedStatus, UnmergedStatus, UnmergedStatusCode,
@@ -221,6 +222,10 @@ pub struct RepositorySnapshot {
     pub branch: Option<Branch>,
     pub merge_conflicts: TreeSet<RepoPath>,
     pub merge_head_shas: Vec<SharedString>,
+    /// The rebase that has stopped partway through, if any.
+    pub rebase: Option<RebaseState>,
+    /// The repository's submodules. These aren't synced to remote projects.
+    pub submodules: Vec<Submodule>,
     pub scan_id: u64,
 }
 
@@ -2473,6 +2478,8 @@ impl RepositorySnapshot {
             branch: None,
             merge_conflicts: Default::default(),
             merge_head_shas: Default::default(),
+            rebase: None,
+            submodules: Vec::new(),
             scan_id: 0,
         }
     }
@@ -2587,6 +2594,12 @@ impl RepositorySnapshot {
             .map_or(false, |entry| entry.status.is_conflicted())
     }
 
+    pub fn submodule_for_path(&self, repo_path: &RepoPath) -> Option<&Submodule> {
+        self.submodules
+            .iter()
+            .find(|submodule| &submodule.path == repo_path)
+    }
+
     /// This is the name that will be displayed in the repository selector for this repository.
     pub fn display_name(&self) -> SharedString {
         self.work_directory_abs_path
@@ -2906,6 +2919,320 @@ impl Repository {
         })
     }
 
+    pub fn log(
+        &self,
+        options: LogOptions,
+        skip: usize,
+        limit: usize,
+    ) -> oneshot::Receiver<Result<Vec<LogEntry>>> {
+        self.send_job(move |git_repo, _cx| async move {
+            match git_repo {
+                RepositoryState::Local { backend, .. } => backend.log(options, skip, limit).await,
+                RepositoryState::Remote { .. } => {
+                    Err(anyhow!("the git log isn't available in remote projects"))
+                }
+            }
+        })
+    }
+
+    pub fn rebase_commits(&self, base: String) -> oneshot::Receiver<Result<Vec<LogEntry>>> {
+        self.send_job(move |git_repo, _cx| async move {
+            match git_repo {
+                RepositoryState::Local { backend, .. } => backend.rebase_commits(base).await,
+                RepositoryState::Remote { .. } => {
+                    Err(anyhow!("rebasing isn't available in remote projects"))
+                }
+            }
+        })
+    }
+
+    /// Starts an interactive rebase onto `base`.
+    ///
+    /// Stopping on conflicts isn't an error: the rebase is left in progress,
+    /// and the conflicts are reported like those of a merge.
+    pub fn rebase_interactive(
+        &self,
+        base: String,
+        todo: Vec<RebaseTodoEntry>,
+    ) -> oneshot::Receiver<Result<()>> {
+        self.send_job(move |git_repo, _cx| async move {
+            match git_repo {
+                RepositoryState::Local {
+                    backend,
+                    environment,
+                    ..
+                } => {
+                    if backend.rebase_state().is_some() {
+                        return Err(anyhow!("A rebase is already in progress"));
+                    }
+                    let result = backend.rebase_interactive(base, todo, environment).await;
+                    if result.is_err() && backend.rebase_state().is_some() {
+                        return Ok(());
+                    }
+                    result
+                }
+                RepositoryState::Remote { .. } => {
+                    Err(anyhow!("rebasing isn't available in remote projects"))
+                }
+            }
+        })
+    }
+
+    /// Continues the rebase in progress, once its conflicts have been resolved
+    /// and staged, or the commit it stopped at has been edited.
+    ///
+    /// Stopping on the next commit's conflicts isn't an error.
+    pub fn continue_rebase(&self) -> oneshot::Receiver<Result<()>> {
+        self.send_job(move |git_repo, _cx| async move {
+            match git_repo {
+                RepositoryState::Local {
+                    backend,
+                    environment,
+                    ..
+                } => {
+                    let stopped_sha = backend.rebase_state().and_then(|rebase| rebase.stopped_sha);
+                    let result = backend.continue_rebase(environment).await;
+                    if result.is_err()
+                        && backend
+                            .rebase_state()
+                            .is_some_and(|rebase| rebase.stopped_sha != stopped_sha)
+                    {
+                        return Ok(());
+                    }
+                    result
+                }
+                RepositoryState::Remote { .. } => {
+                    Err(anyhow!("rebasing isn't available in remote projects"))
+                }
+            }
+        })
+    }
+
+    pub fn abort_rebase(&self) -> oneshot::Receiver<Result<()>> {
+        self.send_job(move |git_repo, _cx| async move {
+            match git_repo {
+                RepositoryState::Local {
+                    backend,
+                    environment,
+                    ..
+                } => backend.abort_rebase(environment).await,
+                RepositoryState::Remote { .. } => {
+                    Err(anyhow!("rebasing isn't available in remote projects"))
+                }
+            }
+        })
+    }
+
+    /// Loads the base, ours and theirs versions of a file with merge conflicts.
+    pub fn load_conflict_versions(
+        &self,
+        path: RepoPath,
+    ) -> oneshot::Receiver<Result<ConflictVersions>> {
+        self.send_job(move |git_repo, _cx| async move {
+            match git_repo {
+                RepositoryState::Local { backend, .. } => {
+                    backend.load_conflict_versions(path).await
+                }
+                RepositoryState::Remote { .. } => Err(anyhow!(
+                    "the merge editor isn't available in remote projects"
+                )),
+            }
+        })
+    }
+
+    pub fn stash_push(
+        &self,
+        scope: StashScope,
+        message: Option<String>,
+    ) -> oneshot::Receiver<Result<()>> {
+        self.send_job(move |git_repo, _cx| async move {
+            match git_repo {
+                RepositoryState::Local {
+                    backend,
+                    environment,
+                    ..
+                } => backend.stash_push(scope, message, environment).await,
+                RepositoryState::Remote { .. } => {
+                    Err(anyhow!("stashing isn't available in remote projects"))
+                }
+            }
+        })
+    }
+
+    pub fn stash_entries(&self) -> oneshot::Receiver<Result<Vec<StashEntry>>> {
+        self.send_job(move |git_repo, _cx| async move {
+            match git_repo {
+                RepositoryState::Local { backend, .. } => backend.stash_entries().await,
+                RepositoryState::Remote { .. } => {
+                    Err(anyhow!("stashing isn't available in remote projects"))
+                }
+            }
+        })
+    }
+
+    pub fn stash_apply(&self, stash: StashEntry) -> oneshot::Receiver<Result<()>> {
+        self.send_job(move |git_repo, _cx| async move {
+            match git_repo {
+                RepositoryState::Local {
+                    backend,
+                    environment,
+                    ..
+                } => backend.stash_apply(stash, environment).await,
+                RepositoryState::Remote { .. } => {
+                    Err(anyhow!("stashing isn't available in remote projects"))
+                }
+            }
+        })
+    }
+
+    pub fn stash_pop(&self, stash: StashEntry) -> oneshot::Receiver<Result<()>> {
+        self.send_job(move |git_repo, _cx| async move {
+            match git_repo {
+                RepositoryState::Local {
+                    backend,
+                    environment,
+                    ..
+                } => backend.stash_pop(stash, environment).await,
+                RepositoryState::Remote { .. } => {
+                    Err(anyhow!("stashing isn't available in remote projects"))
+                }
+            }
+        })
+    }
+
+    pub fn stash_drop(&self, stash: StashEntry) -> oneshot::Receiver<Result<()>> {
+        self.send_job(move |git_repo, _cx| async move {
+            match git_repo {
+                RepositoryState::Local {
+                    backend,
+                    environment,
+                    ..
+                } => backend.stash_drop(stash, environment).await,
+                RepositoryState::Remote { .. } => {
+                    Err(anyhow!("stashing isn't available in remote projects"))
+                }
+            }
+        })
+    }
+
+    pub fn worktrees(&self) -> oneshot::Receiver<Result<Vec<GitWorktree>>> {
+        self.send_job(move |git_repo, _cx| async move {
+            match git_repo {
+                RepositoryState::Local { backend, .. } => backend.worktrees().await,
+                RepositoryState::Remote { .. } => {
+                    Err(anyhow!("worktrees aren't available in remote projects"))
+                }
+            }
+        })
+    }
+
+    pub fn create_worktree(
+        &self,
+        path: PathBuf,
+        branch: Option<String>,
+    ) -> oneshot::Receiver<Result<()>> {
+        self.send_job(move |git_repo, _cx| async move {
+            match git_repo {
+                RepositoryState::Local {
+                    backend,
+                    environment,
+                    ..
+                } => backend.create_worktree(path, branch, environment).await,
+                RepositoryState::Remote { .. } => {
+                    Err(anyhow!("worktrees aren't available in remote projects"))
+                }
+            }
+        })
+    }
+
+    pub fn remove_worktree(&self, path: PathBuf, force: bool) -> oneshot::Receiver<Result<()>> {
+        self.send_job(move |git_repo, _cx| async move {
+            match git_repo {
+                RepositoryState::Local {
+                    backend,
+                    environment,
+                    ..
+                } => backend.remove_worktree(path, force, environment).await,
+                RepositoryState::Remote { .. } => {
+                    Err(anyhow!("worktrees aren't available in remote projects"))
+                }
+            }
+        })
+    }
+
+    pub fn submodule_init(&self, paths: Vec<RepoPath>) -> oneshot::Receiver<Result<()>> {
+        self.send_job(move |git_repo, _cx| async move {
+            match git_repo {
+                RepositoryState::Local {
+                    backend,
+                    environment,
+                    ..
+                } => backend.submodule_init(paths, environment).await,
+                RepositoryState::Remote { .. } => {
+                    Err(anyhow!("submodules aren't available in remote projects"))
+                }
+            }
+        })
+    }
+
+    pub fn submodule_update(&self, paths: Vec<RepoPath>) -> oneshot::Receiver<Result<()>> {
+        self.send_job(move |git_repo, _cx| async move {
+            match git_repo {
+                RepositoryState::Local {
+                    backend,
+                    environment,
+                    ..
+                } => backend.submodule_update(paths, environment).await,
+                RepositoryState::Remote { .. } => {
+                    Err(anyhow!("submodules aren't available in remote projects"))
+                }
+            }
+        })
+    }
+
+    pub fn submodule_sync(&self, paths: Vec<RepoPath>) -> oneshot::Receiver<Result<()>> {
+        self.send_job(move |git_repo, _cx| async move {
+            match git_repo {
+                RepositoryState::Local {
+                    backend,
+                    environment,
+                    ..
+                } => backend.submodule_sync(paths, environment).await,
+                RepositoryState::Remote { .. } => {
+                    Err(anyhow!("submodules aren't available in remote projects"))
+                }
+            }
+        })
+    }
+
+    pub fn file_history(&self, path: RepoPath) -> oneshot::Receiver<Result<Vec<FileHistoryEntry>>> {
+        self.send_job(move |git_repo, _cx| async move {
+            match git_repo {
+                RepositoryState::Local { backend, .. } => backend.file_history(path).await,
+                RepositoryState::Remote { .. } => {
+                    Err(anyhow!("file history isn't available in remote projects"))
+                }
+            }
+        })
+    }
+
+    pub fn load_text_at_commit(
+        &self,
+        commit: String,
+        path: RepoPath,
+    ) -> oneshot::Receiver<Result<Option<String>>> {
+        self.send_job(move |git_repo, _cx| async move {
+            match git_repo {
+                RepositoryState::Local { backend, .. } => {
+                    backend.load_text_at_commit(commit, path).await
+                }
+                RepositoryState::Remote { .. } => {
+                    Err(anyhow!("file history isn't available in remote projects"))
+                }
+            }
+        })
+    }
+
     pub fn load_commit_diff(&self, commit: String) -> oneshot::Receiver<Result<CommitDiff>> {
         let id = self.id;
         self.send_job(move |git_repo, cx| async move {
@@ -4088,6 +4415,8 @@ async fn compute_snapshot(
         .into_iter()
         .map(SharedString::from)
         .collect();
+    let rebase = backend.rebase_state();
+    let submodules = backend.submodules().await.log_err().unwrap_or_default();
 
     let statuses_by_path = SumTree::from_iter(
         statuses
@@ -4100,11 +4429,15 @@ async fn compute_snapshot(
         &(),
     );
 
-    let merge_head_shas_changed = merge_head_shas != prev_snapshot.merge_head_shas;
+    // Each step of a rebase can stop on different conflicts, so they're
+    // tracked like merge heads.
+    let merge_head_shas_changed =
+        merge_head_shas != prev_snapshot.merge_head_shas || rebase != prev_snapshot.rebase;
 
     if merge_head_shas_changed
         || branch != prev_snapshot.branch
         || statuses_by_path != prev_snapshot.statuses_by_path
+        || submodules != prev_snapshot.submodules
     {
         events.push(RepositoryEvent::Updated { full_scan: true });
     }
@@ -4133,6 +4466,8 @@ async fn compute_snapshot(
         branch,
         merge_conflicts,
         merge_head_shas,
+        rebase,
+        submodules,
     };
 
     Ok((snapshot, events))
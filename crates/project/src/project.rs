@@ -83,7 +83,7 @@ use rpc::{
     AnyProtoClient, ErrorCode,
     proto::{FromProto, LanguageServerPromptResponse, SSH_PROJECT_ID, ToProto},
 };
-use search::{SearchInputKind, SearchQuery, SearchResult};
+use search::{NoteKey, SearchInputKind, SearchQuery, SearchResult};
 use search_history::SearchHistory;
 use settings::{InvalidSettingsError, Settings, SettingsLocation, SettingsStore};
 use smol::channel::Receiver;
@@ -107,6 +107,7 @@ use util::{
     ResultExt as _, maybe,
     paths::{SanitizedPath, compare_paths},
 };
+use vault::NoteIndex;
 use worktree::{CreatedEntry, Snapshot, Traversal};
 pub use worktree::{
     Entry, EntryKind, FS_WATCH_LATENCY, File, LocalWorktree, PathChange, ProjectEntryId,
@@ -189,6 +190,8 @@ pub struct Project {
     search_history: SearchHistory,
     search_included_history: SearchHistory,
     search_excluded_history: SearchHistory,
+    /// The structure of the notes that structured searches looked at.
+    note_index: Arc<NoteIndex<NoteKey>>,
     snippets: Entity<SnippetProvider>,
     environment: Entity<ProjectEnvironment>,
     settings_observer: Entity<SettingsObserver>,
@@ -962,6 +965,7 @@ impl Project {
 
                 search_included_history: Self::new_search_history(),
                 search_excluded_history: Self::new_search_history(),
+                note_index: Default::default(),
 
                 toolchain_store: Some(toolchain_store),
             }
@@ -1117,6 +1121,7 @@ impl Project {
 
                 search_included_history: Self::new_search_history(),
                 search_excluded_history: Self::new_search_history(),
+                note_index: Default::default(),
 
                 toolchain_store: Some(toolchain_store),
             };
@@ -1350,6 +1355,7 @@ impl Project {
                 search_history: Self::new_search_history(),
                 search_included_history: Self::new_search_history(),
                 search_excluded_history: Self::new_search_history(),
+                note_index: Default::default(),
                 environment,
                 remotely_created_models: Arc::new(Mutex::new(RemotelyCreatedModels::default())),
                 toolchain_store: None,
@@ -2823,6 +2829,7 @@ impl Project {
                 cx.emit(Event::WorktreeAdded(worktree.read(cx).id()));
             }
             WorktreeStoreEvent::WorktreeRemoved(_, id) => {
+                self.note_index.retain(|(worktree_id, _)| worktree_id != id);
                 cx.emit(Event::WorktreeRemoved(*id));
             }
             WorktreeStoreEvent::WorktreeReleased(_, id) => {
@@ -2831,6 +2838,15 @@ impl Project {
             WorktreeStoreEvent::WorktreeOrderChanged => cx.emit(Event::WorktreeOrderChanged),
             WorktreeStoreEvent::WorktreeUpdateSent(_) => {}
             WorktreeStoreEvent::WorktreeUpdatedEntries(worktree_id, changes) => {
+                // Renaming or removing a directory only reports the directory
+                // itself, so the notes within it are dropped too.
+                for (path, _, change) in changes.iter() {
+                    if *change == PathChange::Removed {
+                        self.note_index.retain(|(note_worktree_id, note_path)| {
+                            note_worktree_id != worktree_id || !note_path.starts_with(path)
+                        });
+                    }
+                }
                 self.client()
                     .telemetry()
                     .report_discovered_project_events(*worktree_id, changes);
@@ -3556,6 +3572,7 @@ impl Project {
 
     pub fn search(&mut self, query: SearchQuery, cx: &mut Context<Self>) -> Receiver<SearchResult> {
         let (result_tx, result_rx) = smol::channel::unbounded();
+        let query = query.with_note_index(self.note_index.clone());
 
         let matching_buffers_rx = if query.is_opened_only() {
             self.sort_search_candidates(&query, cx)
@@ -3580,10 +3597,13 @@ impl Project {
                 for buffer in matching_buffer_chunk {
                     let buffer = buffer.clone();
                     let query = query.clone();
-                    let snapshot = buffer.read_with(cx, |buffer, _| buffer.snapshot())?;
+                    let (snapshot, worktree_id) = buffer.read_with(cx, |buffer, cx| {
+                        let worktree_id = buffer.file().map(|file| file.worktree_id(cx));
+                        (buffer.snapshot(), worktree_id)
+                    })?;
                     chunk_results.push(cx.background_spawn(async move {
                         let ranges = query
-                            .search(&snapshot, None)
+                            .search_in_worktree(worktree_id, &snapshot, None)
                             .await
                             .iter()
                             .map(|range| {
@@ -4809,6 +4809,140 @@ async fn test_search(cx: &mut gpui::TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_search_with_query_syntax(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            "notes": {
+                "plan.md": "---\ntags: [project]\n---\n# Goals\n- [ ] write the parser\n",
+                "ideas.md": "Parser ideas #project",
+            },
+            "src": {
+                "parser.rs": "// TODO: parser\nfn parse() {}\n",
+            },
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+    let structured = |query: &str| {
+        SearchQuery::structured(
+            query,
+            false,
+            false,
+            false,
+            Default::default(),
+            Default::default(),
+            None,
+        )
+        .unwrap()
+    };
+
+    assert_eq!(
+        search(&project, structured("parser path:src"), cx)
+            .await
+            .unwrap(),
+        HashMap::from_iter([(separator!("dir/src/parser.rs").to_string(), vec![9..15])])
+    );
+    assert_eq!(
+        search(&project, structured("tag:project"), cx)
+            .await
+            .unwrap(),
+        HashMap::from_iter([
+            (separator!("dir/notes/plan.md").to_string(), vec![24..31]),
+            (separator!("dir/notes/ideas.md").to_string(), vec![13..21]),
+        ])
+    );
+    assert_eq!(
+        search(
+            &project,
+            structured("task-todo:parser OR (todo -path:notes)"),
+            cx
+        )
+        .await
+        .unwrap(),
+        HashMap::from_iter([
+            (separator!("dir/notes/plan.md").to_string(), vec![48..54]),
+            (separator!("dir/src/parser.rs").to_string(), vec![3..7]),
+        ])
+    );
+}
+
+#[gpui::test]
+async fn test_search_note_index(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/one"),
+        json!({
+            "README.md": "Read me #one",
+            "notes": {
+                "plan.md": "The plan #one",
+            },
+        }),
+    )
+    .await;
+    fs.insert_tree(path!("/two"), json!({ "README.md": "Read me #two" }))
+        .await;
+    let project = Project::test(
+        fs.clone(),
+        [path!("/one").as_ref(), path!("/two").as_ref()],
+        cx,
+    )
+    .await;
+    let structured = |query: &str| {
+        SearchQuery::structured(
+            query,
+            false,
+            false,
+            false,
+            Default::default(),
+            Default::default(),
+            None,
+        )
+        .unwrap()
+    };
+    let indexed_notes = |cx: &mut gpui::TestAppContext| {
+        project.read_with(cx, |project, _| project.note_index.len())
+    };
+
+    // Notes with the same path in different worktrees are kept apart.
+    assert_eq!(
+        search(&project, structured("tag:one"), cx).await.unwrap(),
+        HashMap::from_iter([
+            (separator!("one/README.md").to_string(), vec![8..12]),
+            (separator!("one/notes/plan.md").to_string(), vec![9..13]),
+        ])
+    );
+    assert_eq!(
+        search(&project, structured("tag:two"), cx).await.unwrap(),
+        HashMap::from_iter([(separator!("two/README.md").to_string(), vec![8..12])])
+    );
+    assert_eq!(indexed_notes(cx), 3);
+
+    // Renaming a directory drops the notes within it.
+    fs.rename(
+        path!("/one/notes").as_ref(),
+        path!("/one/archive").as_ref(),
+        Default::default(),
+    )
+    .await
+    .unwrap();
+    cx.executor().run_until_parked();
+    assert_eq!(indexed_notes(cx), 2);
+
+    let worktree_id = project.read_with(cx, |project, cx| {
+        project.worktrees(cx).nth(1).unwrap().read(cx).id()
+    });
+    project.update(cx, |project, cx| project.remove_worktree(worktree_id, cx));
+    cx.executor().run_until_parked();
+    assert_eq!(indexed_notes(cx), 1);
+}
+
 #[gpui::test]
 async fn test_search_with_inclusions(cx: &mut gpui::TestAppContext) {
     init_test(cx);
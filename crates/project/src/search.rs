@@ -14,6 +14,14 @@ use std::{
 };
 use text::Anchor;
 use util::paths::PathMatcher;
+use vault::NoteIndex;
+use worktree::WorktreeId;
+
+use crate::ProjectPath;
+
+mod query_syntax;
+
+pub use query_syntax::{NoteKey, StructuredQuery};
 
 #[derive(Debug)]
 pub enum SearchResult {
@@ -73,6 +81,16 @@ pub enum SearchQuery {
         include_ignored: bool,
         inner: SearchInputs,
     },
+
+    /// A query in the search operator syntax, see [`StructuredQuery`].
+    Structured {
+        query: StructuredQuery,
+        replacement: Option<String>,
+        whole_word: bool,
+        case_sensitive: bool,
+        include_ignored: bool,
+        inner: SearchInputs,
+    },
 }
 
 static WORD_MATCH_TEST: LazyLock<Regex> = LazyLock::new(|| {
@@ -159,8 +177,45 @@ impl SearchQuery {
         })
     }
 
+    pub fn structured(
+        query: impl ToString,
+        whole_word: bool,
+        case_sensitive: bool,
+        include_ignored: bool,
+        files_to_include: PathMatcher,
+        files_to_exclude: PathMatcher,
+        buffers: Option<Vec<Entity<Buffer>>>,
+    ) -> Result<Self> {
+        let query = query.to_string();
+        let structured_query = StructuredQuery::parse(&query, whole_word, case_sensitive)?;
+        let inner = SearchInputs {
+            query: query.into(),
+            files_to_exclude,
+            files_to_include,
+            buffers,
+        };
+        Ok(Self::Structured {
+            query: structured_query,
+            replacement: None,
+            whole_word,
+            case_sensitive,
+            include_ignored,
+            inner,
+        })
+    }
+
     pub fn from_proto(message: proto::SearchQuery) -> Result<Self> {
-        if message.regex {
+        if message.structured {
+            Self::structured(
+                message.query,
+                message.whole_word,
+                message.case_sensitive,
+                message.include_ignored,
+                deserialize_path_matches(&message.files_to_include)?,
+                deserialize_path_matches(&message.files_to_exclude)?,
+                None, // search opened only don't need search remote
+            )
+        } else if message.regex {
             Self::regex(
                 message.query,
                 message.whole_word,
@@ -192,6 +247,10 @@ impl SearchQuery {
             | Self::Regex {
                 ref mut replacement,
                 ..
+            }
+            | Self::Structured {
+                ref mut replacement,
+                ..
             } => {
                 *replacement = Some(new_replacement);
                 self
@@ -199,10 +258,19 @@ impl SearchQuery {
         }
     }
 
+    /// Makes a structured query share `note_index` with other searches.
+    pub fn with_note_index(mut self, note_index: Arc<NoteIndex<NoteKey>>) -> Self {
+        if let Self::Structured { ref mut query, .. } = self {
+            query.set_note_index(note_index);
+        }
+        self
+    }
+
     pub fn to_proto(&self) -> proto::SearchQuery {
         proto::SearchQuery {
             query: self.as_str().to_string(),
             regex: self.is_regex(),
+            structured: self.is_structured(),
             whole_word: self.whole_word(),
             case_sensitive: self.case_sensitive(),
             include_ignored: self.include_ignored(),
@@ -213,6 +281,7 @@ impl SearchQuery {
 
     pub(crate) fn detect(
         &self,
+        path: &ProjectPath,
         mut reader: BufReader<Box<dyn Read + Send + Sync>>,
     ) -> Result<bool> {
         if self.as_str().is_empty() {
@@ -248,20 +317,29 @@ impl SearchQuery {
                     Ok(false)
                 }
             }
+            Self::Structured { query, .. } => {
+                let mut text = String::new();
+                reader.read_to_string(&mut text)?;
+                Ok(query
+                    .matches(Some(path.worktree_id), &path.path, &text)
+                    .is_some())
+            }
         }
     }
     /// Returns the replacement text for this `SearchQuery`.
     pub fn replacement(&self) -> Option<&str> {
         match self {
-            SearchQuery::Text { replacement, .. } | SearchQuery::Regex { replacement, .. } => {
-                replacement.as_deref()
-            }
+            SearchQuery::Text { replacement, .. }
+            | SearchQuery::Regex { replacement, .. }
+            | SearchQuery::Structured { replacement, .. } => replacement.as_deref(),
         }
     }
     /// Replaces search hits if replacement is set. `text` is assumed to be a string that matches this `SearchQuery` exactly, without any leftovers on either side.
     pub fn replacement_for<'a>(&self, text: &'a str) -> Option<Cow<'a, str>> {
         match self {
-            SearchQuery::Text { replacement, .. } => replacement.clone().map(Cow::from),
+            SearchQuery::Text { replacement, .. } | SearchQuery::Structured { replacement, .. } => {
+                replacement.clone().map(Cow::from)
+            }
             SearchQuery::Regex {
                 regex, replacement, ..
             } => {
@@ -289,6 +367,17 @@ impl SearchQuery {
         &self,
         buffer: &BufferSnapshot,
         subrange: Option<Range<usize>>,
+    ) -> Vec<Range<usize>> {
+        self.search_in_worktree(None, buffer, subrange).await
+    }
+
+    /// Searches a buffer in the worktree with `worktree_id`, which lets
+    /// structured queries cache the structure of the notes they match.
+    pub async fn search_in_worktree(
+        &self,
+        worktree_id: Option<WorktreeId>,
+        buffer: &BufferSnapshot,
+        subrange: Option<Range<usize>>,
     ) -> Vec<Range<usize>> {
         const YIELD_INTERVAL: usize = 20000;
 
@@ -377,6 +466,19 @@ impl SearchQuery {
                     }
                 }
             }
+
+            Self::Structured { query, .. } => {
+                // Operators like `path:` and `section:` need the whole file, so
+                // searching a subrange matches it as if it were the whole file.
+                let path = buffer
+                    .file()
+                    .map(|file| file.path().clone())
+                    .unwrap_or_else(|| Path::new("").into());
+                let text = rope.to_string();
+                if let Some(ranges) = query.matches(worktree_id, &path, &text) {
+                    matches.extend(ranges);
+                }
+            }
         }
 
         matches
@@ -394,6 +496,7 @@ impl SearchQuery {
         match self {
             Self::Text { whole_word, .. } => *whole_word,
             Self::Regex { whole_word, .. } => *whole_word,
+            Self::Structured { whole_word, .. } => *whole_word,
         }
     }
 
@@ -401,6 +504,7 @@ impl SearchQuery {
         match self {
             Self::Text { case_sensitive, .. } => *case_sensitive,
             Self::Regex { case_sensitive, .. } => *case_sensitive,
+            Self::Structured { case_sensitive, .. } => *case_sensitive,
         }
     }
 
@@ -412,6 +516,9 @@ impl SearchQuery {
             Self::Regex {
                 include_ignored, ..
             } => *include_ignored,
+            Self::Structured {
+                include_ignored, ..
+            } => *include_ignored,
         }
     }
 
@@ -419,6 +526,10 @@ impl SearchQuery {
         matches!(self, Self::Regex { .. })
     }
 
+    pub fn is_structured(&self) -> bool {
+        matches!(self, Self::Structured { .. })
+    }
+
     pub fn files_to_include(&self) -> &PathMatcher {
         self.as_inner().files_to_include()
     }
@@ -456,7 +567,9 @@ impl SearchQuery {
     }
     pub fn as_inner(&self) -> &SearchInputs {
         match self {
-            Self::Regex { inner, .. } | Self::Text { inner, .. } => inner,
+            Self::Regex { inner, .. }
+            | Self::Text { inner, .. }
+            | Self::Structured { inner, .. } => inner,
         }
     }
 }
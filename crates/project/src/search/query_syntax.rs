@@ -0,0 +1,855 @@
+//! A query syntax for project search, modeled on the search operators of
+//! note-taking tools.
+//!
+//! Terms are separated by spaces and must all match. `OR` matches either side,
+//! a leading `-` negates a term and parentheses group terms. Besides plain
+//! words, `"quoted phrases"` and `/regular expressions/`, a query can contain:
+//!
+//! - `path:term` and `file:term`, matching the file's path or name.
+//! - `line:(a b)`, matching when a single line matches the group.
+//! - `section:(a b)`, matching when a single heading section matches the group.
+//! - `tag:#tag`, matching notes tagged with `tag` or one of its nested tags.
+//! - `task-todo:term`, matching unchecked tasks.
+//! - `[property]` and `[property:value]`, matching frontmatter properties.
+//!
+//! `path:`, `file:` and `line:` apply to every file. The other operators look at
+//! the structure of markdown notes, and never match other files.
+
+use std::ops::Range;
+use std::path::Path;
+use std::sync::Arc;
+
+use aho_corasick::{AhoCorasick, AhoCorasickBuilder};
+use anyhow::{Result, anyhow, bail};
+use fancy_regex::{Regex, RegexBuilder};
+use vault::{Frontmatter, NoteIndex, NoteMetadata, is_note, tag_matches};
+use worktree::WorktreeId;
+
+/// Identifies a note in the note index of [`StructuredQuery`].
+pub type NoteKey = (WorktreeId, Arc<Path>);
+
+/// A parsed query in the structured query syntax.
+#[derive(Clone, Debug)]
+pub struct StructuredQuery {
+    expr: Expr,
+    note_index: Arc<NoteIndex<NoteKey>>,
+}
+
+#[derive(Clone, Debug)]
+enum Expr {
+    Term(Term),
+    And(Vec<Expr>),
+    Or(Vec<Expr>),
+    Not(Box<Expr>),
+    Scoped(Scope, Box<Expr>),
+    Tag(String),
+    Property {
+        name: String,
+        value: Option<Box<Expr>>,
+    },
+}
+
+/// The part of a file that an operator's group is matched against.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Scope {
+    Path,
+    File,
+    Line,
+    Section,
+    TaskTodo,
+}
+
+impl Scope {
+    fn from_operator(operator: &str) -> Option<Self> {
+        match operator {
+            "path" => Some(Self::Path),
+            "file" => Some(Self::File),
+            "line" => Some(Self::Line),
+            "section" => Some(Self::Section),
+            "task-todo" => Some(Self::TaskTodo),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Term {
+    Text {
+        search: AhoCorasick,
+        whole_word: bool,
+    },
+    Regex(Regex),
+}
+
+impl StructuredQuery {
+    /// Parses `query`. The options apply to the text terms of the query;
+    /// regular expression terms are case insensitive unless `case_sensitive`
+    /// is set.
+    pub fn parse(query: &str, whole_word: bool, case_sensitive: bool) -> Result<Self> {
+        let mut parser = Parser {
+            input: query,
+            offset: 0,
+            whole_word,
+            case_sensitive,
+        };
+        let expr = parser.parse_or(None)?;
+        if let Some(c) = parser.peek() {
+            bail!("unexpected `{c}`");
+        }
+        Ok(Self {
+            expr,
+            note_index: Arc::default(),
+        })
+    }
+
+    /// Makes the query look up the structure of notes in `note_index`, so
+    /// notes aren't parsed again for every query.
+    pub fn set_note_index(&mut self, note_index: Arc<NoteIndex<NoteKey>>) {
+        self.note_index = note_index;
+    }
+
+    /// Returns the ranges in `text` that the query highlights if the file at
+    /// `path` matches it, or `None` if it doesn't.
+    ///
+    /// A file can match without any text to highlight, e.g. for `path:` or
+    /// `tag:` queries. The first line of the file is returned then, skipping
+    /// the frontmatter of notes, so the match can still be shown.
+    ///
+    /// The structure of notes in a worktree is cached in the query's note
+    /// index, keyed by `worktree_id` and `path`.
+    pub fn matches(
+        &self,
+        worktree_id: Option<WorktreeId>,
+        path: &Path,
+        text: &str,
+    ) -> Option<Vec<Range<usize>>> {
+        let metadata = |text| match worktree_id {
+            Some(worktree_id) => self.note_index.metadata((worktree_id, path.into()), text),
+            None => Arc::new(NoteMetadata::parse(text)),
+        };
+        let document = Document::new(path, text, metadata);
+        let mut ranges = document.eval(&self.expr, Haystack::file(text))?;
+        if ranges.is_empty() {
+            return Some(vec![document.first_line()]);
+        }
+        ranges.sort_by_key(|range| (range.start, range.end));
+        let mut merged: Vec<Range<usize>> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            match merged.last_mut() {
+                Some(last) if range.start < last.end => last.end = last.end.max(range.end),
+                _ => merged.push(range),
+            }
+        }
+        Some(merged)
+    }
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    offset: usize,
+    whole_word: bool,
+    case_sensitive: bool,
+}
+
+impl Parser<'_> {
+    fn rest(&self) -> &str {
+        &self.input[self.offset..]
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.rest().chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.offset += rest.len() - rest.trim_start().len();
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.offset += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn at_or(&mut self) -> bool {
+        self.peek();
+        self.rest().strip_prefix("OR").is_some_and(|after| {
+            after
+                .chars()
+                .next()
+                .is_none_or(|c| c.is_whitespace() || c == '(')
+        })
+    }
+
+    fn parse_or(&mut self, closing: Option<char>) -> Result<Expr> {
+        let mut alternatives = vec![self.parse_and(closing)?];
+        while self.at_or() {
+            self.offset += "OR".len();
+            alternatives.push(self.parse_and(closing)?);
+        }
+        if alternatives.iter().any(is_empty) && alternatives.len() > 1 {
+            bail!("expected a search term around `OR`");
+        }
+        Ok(if alternatives.len() == 1 {
+            alternatives.pop().unwrap()
+        } else {
+            Expr::Or(alternatives)
+        })
+    }
+
+    fn parse_and(&mut self, closing: Option<char>) -> Result<Expr> {
+        let mut operands = Vec::new();
+        loop {
+            match self.peek() {
+                None => break,
+                Some(c) if Some(c) == closing => break,
+                Some(c @ (')' | ']')) => bail!("unexpected `{c}`"),
+                Some(_) if self.at_or() => break,
+                Some(_) => operands.push(self.parse_unary()?),
+            }
+        }
+        Ok(if operands.len() == 1 {
+            operands.pop().unwrap()
+        } else {
+            Expr::And(operands)
+        })
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr> {
+        let negated = self
+            .rest()
+            .strip_prefix('-')
+            .is_some_and(|after| after.chars().next().is_some_and(|c| !c.is_whitespace()));
+        if negated {
+            self.offset += 1;
+            return Ok(Expr::Not(Box::new(self.parse_unary()?)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr> {
+        if self.eat('(') {
+            return self.parse_group();
+        }
+        if self.eat('[') {
+            return self.parse_property();
+        }
+
+        let operator_len = self
+            .rest()
+            .find(|c: char| !(c.is_ascii_alphabetic() || c == '-'))
+            .unwrap_or(self.rest().len());
+        if self.rest()[operator_len..].starts_with(':') {
+            let operator = self.rest()[..operator_len].to_ascii_lowercase();
+            if operator == "tag" {
+                self.offset += operator_len + 1;
+                let tag = self.parse_text()?;
+                let tag = tag.trim_start_matches('#');
+                if tag.is_empty() {
+                    bail!("`tag:` needs a tag");
+                }
+                return Ok(Expr::Tag(tag.to_string()));
+            }
+            if let Some(scope) = Scope::from_operator(&operator) {
+                self.offset += operator_len + 1;
+                let argument = match self.rest().chars().next() {
+                    None => Expr::And(Vec::new()),
+                    Some(c) if c.is_whitespace() || c == ')' || c == ']' => Expr::And(Vec::new()),
+                    Some('(') => {
+                        self.offset += 1;
+                        self.parse_group()?
+                    }
+                    Some(_) => self.parse_term()?,
+                };
+                return Ok(Expr::Scoped(scope, Box::new(argument)));
+            }
+        }
+
+        self.parse_term()
+    }
+
+    fn parse_group(&mut self) -> Result<Expr> {
+        let expr = self.parse_or(Some(')'))?;
+        if !self.eat(')') {
+            bail!("missing `)`");
+        }
+        Ok(expr)
+    }
+
+    fn parse_property(&mut self) -> Result<Expr> {
+        let name_len = self
+            .rest()
+            .find([':', ']'])
+            .ok_or_else(|| anyhow!("missing `]`"))?;
+        let name = self.rest()[..name_len].trim().to_string();
+        if name.is_empty() {
+            bail!("`[]` needs a property name");
+        }
+        self.offset += name_len;
+        let value = if self.eat(':') {
+            Some(Box::new(self.parse_or(Some(']'))?))
+        } else {
+            None
+        };
+        if !self.eat(']') {
+            bail!("missing `]`");
+        }
+        Ok(Expr::Property { name, value })
+    }
+
+    fn parse_term(&mut self) -> Result<Expr> {
+        if let Some(after_slash) = self.rest().strip_prefix('/') {
+            if let Some(len) = closing_slash(after_slash) {
+                let pattern = after_slash[..len].replace("\\/", "/");
+                self.offset += len + 2;
+                let regex = RegexBuilder::new(&pattern)
+                    .case_insensitive(!self.case_sensitive)
+                    .build()?;
+                return Ok(Expr::Term(Term::Regex(regex)));
+            }
+        }
+        let text = self.parse_text()?;
+        let search = AhoCorasickBuilder::new()
+            .ascii_case_insensitive(!self.case_sensitive)
+            .build([&text])?;
+        Ok(Expr::Term(Term::Text {
+            search,
+            whole_word: self.whole_word,
+        }))
+    }
+
+    /// Parses a `"quoted phrase"` or a word, which ends at whitespace or at a
+    /// closing bracket.
+    fn parse_text(&mut self) -> Result<String> {
+        if let Some(after_quote) = self.rest().strip_prefix('"') {
+            let len = after_quote
+                .find('"')
+                .ok_or_else(|| anyhow!("missing closing `\"`"))?;
+            let text = after_quote[..len].to_string();
+            self.offset += len + 2;
+            return Ok(text);
+        }
+        let len = self
+            .rest()
+            .find(|c: char| c.is_whitespace() || c == ')' || c == ']')
+            .unwrap_or(self.rest().len());
+        let text = self.rest()[..len].to_string();
+        self.offset += len;
+        Ok(text)
+    }
+}
+
+fn is_empty(expr: &Expr) -> bool {
+    matches!(expr, Expr::And(operands) if operands.is_empty())
+}
+
+/// Returns the offset of the first `/` in `text` that isn't escaped.
+fn closing_slash(text: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (ix, c) in text.char_indices() {
+        match c {
+            '\\' if !escaped => escaped = true,
+            '/' if !escaped => return Some(ix).filter(|&ix| ix > 0),
+            _ => escaped = false,
+        }
+    }
+    None
+}
+
+/// A file being matched, along with the structure of its text if it's a note.
+struct Document<'a> {
+    path: &'a Path,
+    text: &'a str,
+    note: Option<NoteStructure>,
+}
+
+struct NoteStructure {
+    metadata: Arc<NoteMetadata>,
+    sections: Vec<Section>,
+}
+
+struct Section {
+    range: Range<usize>,
+    /// The range of the heading line, or an empty range for the text before
+    /// the first heading.
+    heading: Range<usize>,
+}
+
+/// The text an expression is matched against.
+#[derive(Clone, Copy)]
+struct Haystack<'a> {
+    text: &'a str,
+    /// The offset of `text` in the file, or `None` when matching something
+    /// other than the file's text, such as its path or a property value.
+    offset: Option<usize>,
+}
+
+impl<'a> Haystack<'a> {
+    fn file(text: &'a str) -> Self {
+        Self {
+            text,
+            offset: Some(0),
+        }
+    }
+
+    fn other(text: &'a str) -> Self {
+        Self { text, offset: None }
+    }
+
+    /// The range of this haystack in the file's text.
+    fn range(&self) -> Option<Range<usize>> {
+        self.offset.map(|offset| offset..offset + self.text.len())
+    }
+}
+
+impl<'a> Document<'a> {
+    fn new(
+        path: &'a Path,
+        text: &'a str,
+        metadata: impl FnOnce(&str) -> Arc<NoteMetadata>,
+    ) -> Self {
+        let note = is_note(path).then(|| NoteStructure::new(metadata(text), text));
+        Self { path, text, note }
+    }
+
+    /// The range of the first non-blank line after the frontmatter, or of the
+    /// first line when there is none.
+    fn first_line(&self) -> Range<usize> {
+        let body_start = self
+            .note
+            .as_ref()
+            .map_or(0, |note| note.metadata.body_start);
+        let mut line_start = body_start;
+        for line in self.text[body_start..].split_inclusive('\n') {
+            let content = line.trim_end_matches(['\n', '\r']);
+            if !content.trim().is_empty() {
+                return line_start..line_start + content.len();
+            }
+            line_start += line.len();
+        }
+        let first_line = self.text.lines().next().unwrap_or_default();
+        0..first_line.len()
+    }
+
+    fn eval(&self, expr: &Expr, haystack: Haystack) -> Option<Vec<Range<usize>>> {
+        match expr {
+            Expr::Term(term) => {
+                let ranges = term.find(haystack.text);
+                let offset = haystack.offset.unwrap_or(0);
+                (!ranges.is_empty()).then(|| {
+                    ranges
+                        .into_iter()
+                        .map(|range| range.start + offset..range.end + offset)
+                        .collect()
+                })
+            }
+            Expr::And(operands) => {
+                let mut ranges = Vec::new();
+                for operand in operands {
+                    ranges.extend(self.eval(operand, haystack)?);
+                }
+                Some(ranges)
+            }
+            Expr::Or(alternatives) => {
+                let mut matched = false;
+                let mut ranges = Vec::new();
+                for alternative in alternatives {
+                    if let Some(alternative_ranges) = self.eval(alternative, haystack) {
+                        matched = true;
+                        ranges.extend(alternative_ranges);
+                    }
+                }
+                matched.then_some(ranges)
+            }
+            Expr::Not(expr) => match self.eval(expr, haystack) {
+                Some(_) => None,
+                None => Some(Vec::new()),
+            },
+            Expr::Scoped(scope, expr) => self.eval_scoped(*scope, expr, haystack),
+            Expr::Tag(tag) => {
+                let note = &self.note.as_ref()?.metadata;
+                if !note
+                    .tags
+                    .iter()
+                    .any(|candidate| tag_matches(candidate, tag))
+                {
+                    return None;
+                }
+                Some(
+                    note.inline_tags
+                        .iter()
+                        .filter(|(_, candidate)| tag_matches(candidate, tag))
+                        .map(|(range, _)| range.clone())
+                        .collect(),
+                )
+            }
+            Expr::Property { name, value } => {
+                let frontmatter = self.note.as_ref()?.metadata.frontmatter.as_ref()?;
+                let property = frontmatter.get(name)?;
+                if let Some(value) = value {
+                    property
+                        .to_list()
+                        .iter()
+                        .find(|item| self.eval(value, Haystack::other(item)).is_some())?;
+                }
+                Some(
+                    property_line(self.text, frontmatter, name)
+                        .into_iter()
+                        .collect(),
+                )
+            }
+        }
+    }
+
+    fn eval_scoped(
+        &self,
+        scope: Scope,
+        expr: &Expr,
+        haystack: Haystack,
+    ) -> Option<Vec<Range<usize>>> {
+        // Each part is matched separately, and highlighted as a whole when
+        // the group has nothing to highlight, as with negated terms.
+        let mut matched = false;
+        let mut ranges = Vec::new();
+        let mut eval_part = |part: Haystack, highlight: Range<usize>| {
+            if let Some(part_ranges) = self.eval(expr, part) {
+                matched = true;
+                if part_ranges.is_empty() {
+                    ranges.push(highlight);
+                } else {
+                    ranges.extend(part_ranges);
+                }
+            }
+        };
+
+        let parts = match scope {
+            Scope::Path => {
+                let path = self.path.to_string_lossy();
+                return self.eval(expr, Haystack::other(&path)).map(|_| Vec::new());
+            }
+            Scope::File => {
+                let name = self.path.file_name()?.to_string_lossy();
+                return self.eval(expr, Haystack::other(&name)).map(|_| Vec::new());
+            }
+            Scope::Line => {
+                let offset = haystack.offset.unwrap_or(0);
+                let mut line_start = 0;
+                for line in haystack.text.split_inclusive('\n') {
+                    let line_end = line_start + line.trim_end_matches(['\n', '\r']).len();
+                    let part = Haystack {
+                        text: &haystack.text[line_start..line_end],
+                        offset: haystack.offset.map(|offset| offset + line_start),
+                    };
+                    eval_part(part, line_start + offset..line_end + offset);
+                    line_start += line.len();
+                }
+                return matched.then_some(ranges);
+            }
+            Scope::Section => {
+                let sections = &self.note.as_ref()?.sections;
+                let parts = sections
+                    .iter()
+                    .map(|section| (section.range.clone(), section.heading.clone()));
+                narrow_parts(parts, haystack.range()?)
+            }
+            Scope::TaskTodo => {
+                let tasks = &self.note.as_ref()?.metadata.todo_tasks;
+                let parts = tasks.iter().map(|task| (task.clone(), task.clone()));
+                narrow_parts(parts, haystack.range()?)
+            }
+        };
+        for (part, highlight) in parts {
+            let text = &self.text[part.clone()];
+            eval_part(
+                Haystack {
+                    text,
+                    offset: Some(part.start),
+                },
+                highlight,
+            );
+        }
+        matched.then_some(ranges)
+    }
+}
+
+/// Narrows the `(part, highlight)` ranges of a file to the ones within
+/// `window`, so that operators nested in a `line:` or `section:` group only look
+/// at the text of that line or section.
+fn narrow_parts(
+    parts: impl Iterator<Item = (Range<usize>, Range<usize>)>,
+    window: Range<usize>,
+) -> Vec<(Range<usize>, Range<usize>)> {
+    parts
+        .filter_map(|(part, highlight)| {
+            let narrowed = part.start.max(window.start)..part.end.min(window.end);
+            if narrowed.start >= narrowed.end {
+                return None;
+            }
+            if narrowed == part || (window.start <= highlight.start && highlight.end <= window.end)
+            {
+                Some((narrowed, highlight))
+            } else {
+                Some((narrowed.clone(), narrowed))
+            }
+        })
+        .collect()
+}
+
+impl NoteStructure {
+    fn new(metadata: Arc<NoteMetadata>, text: &str) -> Self {
+        let headings = &metadata.headings;
+        let body_start = metadata.body_start;
+        let mut sections = Vec::with_capacity(headings.len() + 1);
+        let first_heading_start = headings.first().map_or(text.len(), |h| h.range.start);
+        if body_start < first_heading_start {
+            sections.push(Section {
+                range: body_start..first_heading_start,
+                heading: body_start..body_start,
+            });
+        }
+        for (ix, heading) in headings.iter().enumerate() {
+            let end = headings
+                .get(ix + 1)
+                .map_or(text.len(), |next| next.range.start);
+            sections.push(Section {
+                range: heading.range.start..end,
+                heading: heading.range.clone(),
+            });
+        }
+        Self { metadata, sections }
+    }
+}
+
+impl Term {
+    fn find(&self, haystack: &str) -> Vec<Range<usize>> {
+        match self {
+            Term::Text { search, whole_word } => search
+                .find_iter(haystack)
+                .map(|mat| mat.range())
+                .filter(|range| !whole_word || is_whole_word(haystack, range))
+                .collect(),
+            Term::Regex(regex) => regex
+                .find_iter(haystack)
+                .filter_map(|mat| mat.ok())
+                .filter(|mat| !mat.range().is_empty())
+                .map(|mat| mat.range())
+                .collect(),
+        }
+    }
+}
+
+fn is_whole_word(text: &str, range: &Range<usize>) -> bool {
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+    let starts_word = text[range.clone()].chars().next().is_some_and(is_word_char);
+    let ends_word = text[range.clone()]
+        .chars()
+        .next_back()
+        .is_some_and(is_word_char);
+    let before = text[..range.start].chars().next_back();
+    let after = text[range.end..].chars().next();
+    !(starts_word && before.is_some_and(is_word_char))
+        && !(ends_word && after.is_some_and(is_word_char))
+}
+
+/// Returns the range of the line that defines `name` in the frontmatter.
+fn property_line(text: &str, frontmatter: &Frontmatter, name: &str) -> Option<Range<usize>> {
+    let mut line_start = frontmatter.range.start;
+    for line in text[frontmatter.range.clone()].split_inclusive('\n') {
+        let content = line.trim_end_matches(['\n', '\r']);
+        let is_property = content
+            .split_once(':')
+            .is_some_and(|(key, _)| key.trim().trim_matches('"').eq_ignore_ascii_case(name));
+        if is_property {
+            return Some(line_start..line_start + content.len());
+        }
+        line_start += line.len();
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    const NOTE: &str = indoc! {"
+        ---
+        tags: [project]
+        status: in progress
+        aliases: [Plan, Roadmap]
+        ---
+        Intro about the plan.
+
+        # Goals
+        Ship the search #feature/search.
+        - [ ] write the parser
+        - [x] write the tests
+        1. [ ] review the parser
+
+        ## Risks
+        The parser may be slow.
+        ```
+        - [ ] not a task
+        ```
+    "};
+
+    fn matched_text(query: &str, path: &str, text: &str) -> Option<Vec<String>> {
+        let query = StructuredQuery::parse(query, false, false).unwrap();
+        query.matches(None, Path::new(path), text).map(|ranges| {
+            ranges
+                .into_iter()
+                .map(|range| text[range].to_string())
+                .collect()
+        })
+    }
+
+    #[test]
+    fn test_terms_and_boolean_operators() {
+        assert_eq!(
+            matched_text("parser slow", "plan.md", NOTE),
+            Some(vec![
+                "parser".into(),
+                "parser".into(),
+                "parser".into(),
+                "slow".into()
+            ])
+        );
+        assert_eq!(matched_text("parser missing", "plan.md", NOTE), None);
+        assert_eq!(
+            matched_text("missing OR slow", "plan.md", NOTE),
+            Some(vec!["slow".into()])
+        );
+        assert_eq!(
+            matched_text("\"the plan\" -missing", "plan.md", NOTE),
+            Some(vec!["the plan".into()])
+        );
+        assert_eq!(matched_text("intro -(slow OR fast)", "plan.md", NOTE), None);
+        assert_eq!(
+            matched_text("/wr[a-z]+e/", "plan.md", NOTE),
+            Some(vec!["write".into(), "write".into()])
+        );
+
+        let whole_word = StructuredQuery::parse("plan", true, false).unwrap();
+        assert_eq!(
+            whole_word.matches(None, Path::new("plan.md"), "plans and a plan"),
+            Some(vec![12..16])
+        );
+    }
+
+    #[test]
+    fn test_generic_operators() {
+        assert_eq!(
+            matched_text("path:notes/ ship", "notes/plan.md", NOTE),
+            Some(vec!["Ship".into()])
+        );
+        assert_eq!(
+            matched_text("path:archive ship", "notes/plan.md", NOTE),
+            None
+        );
+        assert_eq!(
+            matched_text("file:(plan -notes)", "notes/plan.md", NOTE),
+            Some(vec!["Intro about the plan.".into()])
+        );
+        assert_eq!(
+            matched_text("line:(parser write)", "notes/plan.md", NOTE),
+            Some(vec!["write".into(), "parser".into()])
+        );
+        assert_eq!(
+            matched_text("line:(parser slow intro)", "plan.md", NOTE),
+            None
+        );
+        assert_eq!(
+            matched_text("line:(fn -pub)", "main.rs", "pub fn a() {}\nfn b() {}\n"),
+            Some(vec!["fn".into()])
+        );
+    }
+
+    #[test]
+    fn test_markdown_operators() {
+        assert_eq!(
+            matched_text("section:(parser slow)", "plan.md", NOTE),
+            Some(vec!["parser".into(), "slow".into()])
+        );
+        assert_eq!(matched_text("section:(ship slow)", "plan.md", NOTE), None);
+        assert_eq!(
+            matched_text("tag:#feature", "plan.md", NOTE),
+            Some(vec!["#feature/search".into()])
+        );
+        assert_eq!(
+            matched_text("tag:project", "plan.md", NOTE),
+            Some(vec!["Intro about the plan.".into()])
+        );
+        assert_eq!(
+            matched_text("path:main", "main.rs", "\nfn main() {}\n"),
+            Some(vec!["fn main() {}".into()])
+        );
+        assert_eq!(matched_text("tag:feat", "plan.md", NOTE), None);
+        assert_eq!(
+            matched_text("task-todo:parser", "plan.md", NOTE),
+            Some(vec!["parser".into(), "parser".into()])
+        );
+        assert_eq!(matched_text("task-todo:tests", "plan.md", NOTE), None);
+        assert_eq!(
+            matched_text("task-todo:", "plan.md", NOTE),
+            Some(vec!["write the parser".into(), "review the parser".into()])
+        );
+        assert_eq!(
+            matched_text("[status:progress]", "plan.md", NOTE),
+            Some(vec!["status: in progress".into()])
+        );
+        assert_eq!(
+            matched_text("[aliases:(roadmap OR draft)] [status]", "plan.md", NOTE),
+            Some(vec![
+                "status: in progress".into(),
+                "aliases: [Plan, Roadmap]".into()
+            ])
+        );
+        assert_eq!(matched_text("[status:done]", "plan.md", NOTE), None);
+
+        // Nested operators only look at the text of the enclosing group.
+        assert_eq!(
+            matched_text("section:(task-todo:write parser)", "plan.md", NOTE),
+            Some(vec!["write".into(), "parser".into(), "parser".into()])
+        );
+        assert_eq!(
+            matched_text("section:(task-todo:write slow)", "plan.md", NOTE),
+            None
+        );
+        assert_eq!(
+            matched_text("line:(task-todo:parser -review)", "plan.md", NOTE),
+            Some(vec!["parser".into()])
+        );
+        assert_eq!(
+            matched_text("line:(section:slow)", "plan.md", NOTE),
+            Some(vec!["slow".into()])
+        );
+
+        // Markdown operators never match other files.
+        assert_eq!(matched_text("tag:project", "plan.txt", NOTE), None);
+        assert_eq!(matched_text("section:(parser)", "plan.txt", NOTE), None);
+    }
+
+    #[test]
+    fn test_invalid_queries() {
+        for query in [
+            "(parser",
+            "parser)",
+            "[status",
+            "[]",
+            "a OR",
+            "tag:",
+            "\"unclosed",
+            "/(/",
+        ] {
+            assert!(
+                StructuredQuery::parse(query, false, false).is_err(),
+                "{query:?} should be invalid"
+            );
+        }
+        assert!(StructuredQuery::parse("path:/src/ -OR-", false, false).is_ok());
+    }
+}
@@ -366,6 +366,7 @@ impl Project {
             settings.cursor_shape.unwrap_or_default(),
             settings.alternate_scroll,
             settings.max_scroll_history_lines,
+            settings.shell_integration,
             ssh_details.is_some(),
             window,
             completion_tx,
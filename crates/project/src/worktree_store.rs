@@ -911,7 +911,7 @@ impl WorktreeStore {
                 continue;
             }
 
-            if query.detect(file).unwrap_or(false) {
+            if query.detect(&entry.path, file).unwrap_or(false) {
                 entry.respond.send(entry.path).await?
             }
         }
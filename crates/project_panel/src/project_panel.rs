@@ -15,7 +15,7 @@ use editor::{
     scroll::{Autoscroll, ScrollbarAutoHide},
 };
 use file_icons::FileIcons;
-use git::status::GitSummary;
+use git::{repository::SubmoduleStatus, status::GitSummary};
 use gpui::{
     Action, AnyElement, App, ArcCow, AsyncWindowContext, Bounds, ClipboardItem, Context,
     DismissEvent, Div, DragMoveEvent, Entity, EventEmitter, ExternalPaths, FocusHandle, Focusable,
@@ -162,6 +162,7 @@ struct EntryDetails {
     is_private: bool,
     worktree_id: WorktreeId,
     canonical_path: Option<Arc<Path>>,
+    submodule: Option<SubmoduleStatus>,
 }
 
 #[derive(PartialEq, Clone, Default, Debug, Deserialize, JsonSchema)]
@@ -3102,6 +3103,24 @@ impl ProjectPanel {
         }
     }
 
+    fn submodules_by_abs_path(&self, cx: &App) -> HashMap<PathBuf, SubmoduleStatus> {
+        let mut submodules = HashMap::default();
+        if !ProjectPanelSettings::get_global(cx).git_status {
+            return submodules;
+        }
+        let git_store = self.project.read(cx).git_store().read(cx);
+        for repository in git_store.repositories().values() {
+            let repository = repository.read(cx);
+            for submodule in &repository.submodules {
+                submodules.insert(
+                    repository.work_directory_abs_path.join(&submodule.path.0),
+                    submodule.status,
+                );
+            }
+        }
+        submodules
+    }
+
     fn for_each_visible_entry(
         &self,
         range: Range<usize>,
@@ -3110,6 +3129,7 @@ impl ProjectPanel {
         mut callback: impl FnMut(ProjectEntryId, EntryDetails, &mut Window, &mut Context<ProjectPanel>),
     ) {
         let mut ix = 0;
+        let submodules = self.submodules_by_abs_path(cx);
         for (worktree_id, visible_worktree_entries, entries_paths) in &self.visible_entries {
             if ix >= range.end {
                 return;
@@ -3222,6 +3242,11 @@ impl ProjectPanel {
                         is_private: entry.is_private,
                         worktree_id: *worktree_id,
                         canonical_path: entry.canonical_path.clone(),
+                        submodule: entry
+                            .is_dir()
+                            .then(|| submodules.get(&snapshot.abs_path().join(&entry.path)))
+                            .flatten()
+                            .copied(),
                     };
 
                     if let Some(edit_state) = &self.edit_state {
@@ -3605,6 +3630,7 @@ impl ProjectPanel {
             .canonical_path
             .as_ref()
             .map(|f| f.to_string_lossy().to_string());
+        let submodule = details.submodule;
         let path = details.path.clone();
 
         let depth = details.depth;
@@ -3890,6 +3916,32 @@ impl ProjectPanel {
                                 .into_any_element(),
                         )
                     })
+                    .when_some(submodule, |this, submodule| {
+                        let (description, color) = match submodule {
+                            SubmoduleStatus::Uninitialized => {
+                                ("Submodule (not initialized)", Color::Disabled)
+                            }
+                            SubmoduleStatus::UpToDate => ("Submodule", filename_text_color),
+                            SubmoduleStatus::Modified => {
+                                ("Submodule (new commits)", Color::VersionControlModified)
+                            }
+                            SubmoduleStatus::Conflicted => {
+                                ("Submodule (conflicted)", Color::VersionControlConflict)
+                            }
+                        };
+                        this.end_slot::<AnyElement>(
+                            div()
+                                .id("submodule_icon")
+                                .pr_3()
+                                .tooltip(Tooltip::text(description))
+                                .child(
+                                    Icon::new(IconName::GitBranchSmall)
+                                        .size(IconSize::Indicator)
+                                        .color(color),
+                                )
+                                .into_any_element(),
+                        )
+                    })
                     .child(if let Some(icon) = &icon {
                         if let Some((_, decoration_color)) =
                             entry_diagnostic_aware_icon_decoration_and_color(diagnostic_severity)
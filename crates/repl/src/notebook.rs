@@ -1,4 +1,5 @@
 mod cell;
 mod notebook_ui;
+mod persistence;
 pub use cell::*;
 pub use notebook_ui::*;
@@ -1,20 +1,25 @@
 #![allow(unused, dead_code)]
+use std::future::Future;
 use std::sync::Arc;
 
+use anyhow::Result;
 use editor::{Editor, EditorMode, MultiBuffer};
 use futures::future::Shared;
-use gpui::{App, Entity, Hsla, Task, TextStyleRefinement, prelude::*};
+use gpui::{App, ClickEvent, Entity, EventEmitter, Hsla, Task, TextStyleRefinement, prelude::*};
 use language::{Buffer, Language, LanguageRegistry};
 use markdown_preview::{markdown_parser::parse_markdown, markdown_renderer::render_markdown_block};
 use nbformat::v4::{CellId, CellMetadata, CellType};
+use runtimelib::{ExecutionState, JupyterMessageContent, MimeBundle};
+use serde::Serialize;
 use settings::Settings as _;
 use theme::ThemeSettings;
 use ui::{IconButtonShape, prelude::*};
 use util::ResultExt;
+use uuid::Uuid;
 
 use crate::{
     notebook::{CODE_BLOCK_INSET, GUTTER_WIDTH},
-    outputs::{Output, plain::TerminalOutput, user_error::ErrorView},
+    outputs::{ExecutionStatus, Output, plain::TerminalOutput, user_error::ErrorView},
 };
 
 #[derive(Copy, Clone, PartialEq, PartialOrd)]
@@ -110,6 +115,103 @@ fn convert_outputs(
         .collect()
 }
 
+/// Splits cell source into lines the way notebooks store it, with each line
+/// keeping its newline.
+pub fn source_lines(source: &str) -> Vec<String> {
+    source
+        .split_inclusive('\n')
+        .map(ToString::to_string)
+        .collect()
+}
+
+/// Builds a notebook cell with no outputs, with a new id unless one is given.
+pub fn new_nbformat_cell(
+    cell_type: CellType,
+    id: Option<&CellId>,
+    metadata: Option<&CellMetadata>,
+    source: &str,
+) -> Result<nbformat::v4::Cell> {
+    let id = match id {
+        Some(id) => serde_json::to_value(id)?,
+        None => Uuid::new_v4().to_string().into(),
+    };
+    let metadata = match metadata {
+        Some(metadata) => serde_json::to_value(metadata)?,
+        None => serde_json::json!({}),
+    };
+    let mut cell = serde_json::json!({
+        "id": id,
+        "metadata": metadata,
+        "source": source_lines(source),
+    });
+    match cell_type {
+        CellType::Code => {
+            cell["cell_type"] = "code".into();
+            cell["outputs"] = serde_json::json!([]);
+            cell["execution_count"] = serde_json::Value::Null;
+        }
+        CellType::Markdown => cell["cell_type"] = "markdown".into(),
+        CellType::Raw => cell["cell_type"] = "raw".into(),
+    }
+    Ok(serde_json::from_value(cell)?)
+}
+
+/// Converts the content of a kernel message into the output stored in the
+/// notebook file, which has the same fields apart from the transient ones.
+fn nbformat_output(output_type: &str, content: &impl Serialize) -> Option<nbformat::v4::Output> {
+    let mut output = serde_json::to_value(content).log_err()?;
+    let fields = output.as_object_mut()?;
+    fields.remove("transient");
+    fields.insert("output_type".into(), output_type.into());
+    serde_json::from_value(output).log_err()
+}
+
+fn new_cell_editor(source: &str, window: &mut Window, cx: &mut App) -> Entity<Editor> {
+    let buffer = cx.new(|cx| Buffer::local(source, cx));
+    let multi_buffer = cx.new(|cx| MultiBuffer::singleton(buffer, cx));
+
+    cx.new(|cx| {
+        let mut editor = Editor::new(
+            EditorMode::AutoHeight { max_lines: 1024 },
+            multi_buffer,
+            None,
+            window,
+            cx,
+        );
+
+        let theme = ThemeSettings::get_global(cx);
+
+        let refinement = TextStyleRefinement {
+            font_family: Some(theme.buffer_font.family.clone()),
+            font_size: Some(theme.buffer_font_size(cx).into()),
+            color: Some(cx.theme().colors().editor_foreground),
+            background_color: Some(gpui::transparent_black()),
+            ..Default::default()
+        };
+
+        editor.set_show_gutter(false, cx);
+        editor.set_text_style_refinement(refinement);
+        editor
+    })
+}
+
+fn set_editor_language(
+    editor: &Entity<Editor>,
+    language: impl Future<Output = Option<Arc<Language>>> + 'static,
+    window: &mut Window,
+    cx: &mut App,
+) -> Task<()> {
+    let buffer = editor.read(cx).buffer().read(cx).as_singleton();
+    window.spawn(cx, async move |cx| {
+        let language = language.await;
+        if let Some(buffer) = buffer {
+            buffer
+                .update(cx, |buffer, cx| buffer.set_language(language, cx))
+                .ok();
+        }
+    })
+}
+
 impl Cell {
     pub fn load(
         cell: &nbformat::v4::Cell,
@@ -128,34 +230,31 @@ impl Cell {
                 let source = source.join("");
 
                 let entity = cx.new(|cx| {
-                    let markdown_parsing_task = {
-                        let languages = languages.clone();
-                        let source = source.clone();
-
-                        cx.spawn_in(window, async move |this, cx| {
-                            let parsed_markdown = cx
-                                .background_spawn(async move {
-                                    parse_markdown(&source, None, Some(languages)).await
-                                })
-                                .await;
-
-                            this.update(cx, |cell: &mut MarkdownCell, _| {
-                                cell.parsed_markdown = Some(parsed_markdown);
-                            })
-                            .log_err();
-                        })
-                    };
+                    let editor = new_cell_editor(&source, window, cx);
+                    let markdown_language = languages.language_for_name("Markdown");
+                    let language_task = set_editor_language(
+                        &editor,
+                        async move { markdown_language.await.ok() },
+                        window,
+                        cx,
+                    );
 
-                    MarkdownCell {
-                        markdown_parsing_task,
+                    let mut markdown_cell = MarkdownCell {
+                        markdown_parsing_task: Task::ready(()),
                         languages: languages.clone(),
                         id: id.clone(),
                         metadata: metadata.clone(),
-                        source: source.clone(),
+                        source,
+                        original: cell.clone(),
+                        editor,
+                        editing: false,
                         parsed_markdown: None,
                         selected: false,
                         cell_position: None,
-                    }
+                        language_task,
+                    };
+                    markdown_cell.parse_markdown(window, cx);
+                    markdown_cell
                 });
 
                 Cell::Markdown(entity)
@@ -168,53 +267,19 @@ impl Cell {
                 outputs,
             } => Cell::Code(cx.new(|cx| {
                 let text = source.join("");
-
-                let buffer = cx.new(|cx| Buffer::local(text.clone(), cx));
-                let multi_buffer = cx.new(|cx| MultiBuffer::singleton(buffer.clone(), cx));
-
-                let editor_view = cx.new(|cx| {
-                    let mut editor = Editor::new(
-                        EditorMode::AutoHeight { max_lines: 1024 },
-                        multi_buffer,
-                        None,
-                        window,
-                        cx,
-                    );
-
-                    let theme = ThemeSettings::get_global(cx);
-
-                    let refinement = TextStyleRefinement {
-                        font_family: Some(theme.buffer_font.family.clone()),
-                        font_size: Some(theme.buffer_font_size(cx).into()),
-                        color: Some(cx.theme().colors().editor_foreground),
-                        background_color: Some(gpui::transparent_black()),
-                        ..Default::default()
-                    };
-
-                    editor.set_text(text, window, cx);
-                    editor.set_show_gutter(false, cx);
-                    editor.set_text_style_refinement(refinement);
-
-                    // editor.set_read_only(true);
-                    editor
-                });
-
-                let buffer = buffer.clone();
-                let language_task = cx.spawn_in(window, async move |this, cx| {
-                    let language = notebook_language.await;
-
-                    buffer.update(cx, |buffer, cx| {
-                        buffer.set_language(language.clone(), cx);
-                    });
-                });
+                let editor = new_cell_editor(&text, window, cx);
+                let language_task = set_editor_language(&editor, notebook_language, window, cx);
 
                 CodeCell {
                     id: id.clone(),
                     metadata: metadata.clone(),
                     execution_count: *execution_count,
-                    source: source.join(""),
-                    editor: editor_view,
+                    source: text,
+                    editor,
                     outputs: convert_outputs(outputs, window, cx),
+                    nbformat_outputs: outputs.clone(),
+                    execution_status: ExecutionStatus::Unknown,
+                    clear_on_next_output: false,
                     selected: false,
                     language_task,
                     cell_position: None,
@@ -224,15 +289,57 @@ impl Cell {
                 id,
                 metadata,
                 source,
-            } => Cell::Raw(cx.new(|_| RawCell {
-                id: id.clone(),
-                metadata: metadata.clone(),
-                source: source.join(""),
-                selected: false,
-                cell_position: None,
+            } => Cell::Raw(cx.new(|cx| {
+                let source = source.join("");
+                RawCell {
+                    id: id.clone(),
+                    metadata: metadata.clone(),
+                    editor: new_cell_editor(&source, window, cx),
+                    source,
+                    selected: false,
+                    cell_position: None,
+                }
             })),
         }
     }
+
+    pub fn id(&self, cx: &App) -> CellId {
+        match self {
+            Cell::Code(cell) => cell.read(cx).id().clone(),
+            Cell::Markdown(cell) => cell.read(cx).id().clone(),
+            Cell::Raw(cell) => cell.read(cx).id().clone(),
+        }
+    }
+
+    pub fn metadata(&self, cx: &App) -> CellMetadata {
+        match self {
+            Cell::Code(cell) => cell.read(cx).metadata().clone(),
+            Cell::Markdown(cell) => cell.read(cx).metadata().clone(),
+            Cell::Raw(cell) => cell.read(cx).metadata().clone(),
+        }
+    }
+
+    pub fn editor(&self, cx: &App) -> Entity<Editor> {
+        match self {
+            Cell::Code(cell) => cell.read(cx).editor.clone(),
+            Cell::Markdown(cell) => cell.read(cx).editor.clone(),
+            Cell::Raw(cell) => cell.read(cx).editor.clone(),
+        }
+    }
+
+    /// The current source of the cell, including unsaved edits.
+    pub fn text(&self, cx: &App) -> String {
+        self.editor(cx).read(cx).text(cx)
+    }
+
+    /// The cell as it should be written to the notebook file.
+    pub fn to_nbformat(&self, cx: &App) -> nbformat::v4::Cell {
+        match self {
+            Cell::Code(cell) => cell.read(cx).to_nbformat(cx),
+            Cell::Markdown(cell) => cell.read(cx).to_nbformat(cx),
+            Cell::Raw(cell) => cell.read(cx).to_nbformat(cx),
+        }
+    }
 }
 
 pub trait RenderableCell: Render {
@@ -330,11 +437,67 @@ pub struct MarkdownCell {
     id: CellId,
     metadata: CellMetadata,
     source: String,
+    /// The cell as it was loaded, so the fields that can't be edited here,
+    /// like attachments, are saved as they were.
+    original: nbformat::v4::Cell,
+    editor: Entity<Editor>,
+    editing: bool,
     parsed_markdown: Option<markdown_preview::markdown_elements::ParsedMarkdown>,
     markdown_parsing_task: Task<()>,
     selected: bool,
     cell_position: Option<CellPosition>,
     languages: Arc<LanguageRegistry>,
+    language_task: Task<()>,
+}
+
+impl MarkdownCell {
+    pub fn is_editing(&self) -> bool {
+        self.editing
+    }
+
+    pub fn start_editing(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.editing = true;
+        window.focus(&self.editor.focus_handle(cx));
+        cx.notify();
+    }
+
+    /// Leaves editing, rendering the edited markdown.
+    pub fn stop_editing(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if !self.editing {
+            return;
+        }
+        self.editing = false;
+        self.source = self.editor.read(cx).text(cx);
+        self.parse_markdown(window, cx);
+        cx.notify();
+    }
+
+    fn parse_markdown(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let languages = self.languages.clone();
+        let source = self.source.clone();
+
+        self.markdown_parsing_task = cx.spawn_in(window, async move |this, cx| {
+            let parsed_markdown = cx
+                .background_spawn(
+                    async move { parse_markdown(&source, None, Some(languages)).await },
+                )
+                .await;
+
+            this.update(cx, |cell: &mut MarkdownCell, cx| {
+                cell.parsed_markdown = Some(parsed_markdown);
+                cx.notify();
+            })
+            .log_err();
+        });
+    }
+
+    fn to_nbformat(&self, cx: &App) -> nbformat::v4::Cell {
+        let mut cell = self.original.clone();
+        if let nbformat::v4::Cell::Markdown { source, .. } = &mut cell {
+            *source = source_lines(&self.editor.read(cx).text(cx));
+        }
+        cell
+    }
 }
 
 impl RenderableCell for MarkdownCell {
@@ -381,13 +544,52 @@ impl RenderableCell for MarkdownCell {
 
 impl Render for MarkdownCell {
     fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
-        let Some(parsed) = self.parsed_markdown.as_ref() else {
-            return div();
+        let content = if self.editing || self.source.trim().is_empty() {
+            div()
+                .py_1p5()
+                .w_full()
+                .child(
+                    div()
+                        .flex()
+                        .size_full()
+                        .flex_1()
+                        .py_3()
+                        .px_5()
+                        .rounded_lg()
+                        .border_1()
+                        .border_color(cx.theme().colors().border)
+                        .bg(cx.theme().colors().editor_background)
+                        .child(div().w_full().child(self.editor.clone())),
+                )
+                .into_any_element()
+        } else if let Some(parsed) = self.parsed_markdown.as_ref() {
+            let mut markdown_render_context =
+                markdown_preview::markdown_renderer::RenderContext::new(None, window, cx);
+
+            v_flex()
+                .id("rendered-markdown")
+                .size_full()
+                .flex_1()
+                .p_3()
+                .font_ui(cx)
+                .text_size(TextSize::Default.rems(cx))
+                .children(parsed.children.iter().map(|child| {
+                    div().relative().child(
+                        div()
+                            .relative()
+                            .child(render_markdown_block(child, &mut markdown_render_context)),
+                    )
+                }))
+                .on_click(cx.listener(|this, event: &ClickEvent, window, cx| {
+                    if event.up.click_count > 1 {
+                        this.start_editing(window, cx);
+                    }
+                }))
+                .into_any_element()
+        } else {
+            div().into_any_element()
         };
 
-        let mut markdown_render_context =
-            markdown_preview::markdown_renderer::RenderContext::new(None, window, cx);
-
         v_flex()
             .size_full()
             // TODO: Move base cell render into trait impl so we don't have to repeat this
@@ -401,26 +603,18 @@ impl Render for MarkdownCell {
                     .gap(DynamicSpacing::Base08.rems(cx))
                     .bg(self.selected_bg_color(window, cx))
                     .child(self.gutter(window, cx))
-                    .child(
-                        v_flex()
-                            .size_full()
-                            .flex_1()
-                            .p_3()
-                            .font_ui(cx)
-                            .text_size(TextSize::Default.rems(cx))
-                            //
-                            .children(parsed.children.iter().map(|child| {
-                                div().relative().child(div().relative().child(
-                                    render_markdown_block(child, &mut markdown_render_context),
-                                ))
-                            })),
-                    ),
+                    .child(content),
             )
             // TODO: Move base cell render into trait impl so we don't have to repeat this
             .children(self.cell_position_spacer(false, window, cx))
     }
 }
 
+/// Emitted by a code cell when its run control is clicked.
+pub enum CellEvent {
+    Run,
+}
+
 pub struct CodeCell {
     id: CellId,
     metadata: CellMetadata,
@@ -428,12 +622,25 @@ pub struct CodeCell {
     source: String,
     editor: Entity<editor::Editor>,
     outputs: Vec<Output>,
+    /// The outputs as they're saved to the notebook file, kept alongside the
+    /// rendered ones.
+    nbformat_outputs: Vec<nbformat::v4::Output>,
+    execution_status: ExecutionStatus,
+    /// Whether a `clear_output` with `wait` set is waiting for the next
+    /// output to arrive.
+    clear_on_next_output: bool,
     selected: bool,
     cell_position: Option<CellPosition>,
     language_task: Task<()>,
 }
 
+impl EventEmitter<CellEvent> for CodeCell {}
+
 impl CodeCell {
+    pub fn editor(&self) -> &Entity<Editor> {
+        &self.editor
+    }
+
     pub fn is_dirty(&self, cx: &App) -> bool {
         self.editor.read(cx).buffer().read(cx).is_dirty(cx)
     }
@@ -443,6 +650,189 @@ impl CodeCell {
 
     pub fn clear_outputs(&mut self) {
         self.outputs.clear();
+        self.nbformat_outputs.clear();
+        self.execution_count = None;
+        self.execution_status = ExecutionStatus::Unknown;
+    }
+
+    /// Clears the outputs of a previous run ahead of a new one.
+    pub fn start_execution(&mut self, status: ExecutionStatus, cx: &mut Context<Self>) {
+        self.clear_outputs();
+        self.execution_status = status;
+        cx.notify();
+    }
+
+    pub fn set_execution_status(&mut self, status: ExecutionStatus, cx: &mut Context<Self>) {
+        self.execution_status = status;
+        cx.notify();
+    }
+
+    pub fn execution_status(&self) -> &ExecutionStatus {
+        &self.execution_status
+    }
+
+    pub fn push_message(
+        &mut self,
+        message: &JupyterMessageContent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let (output, nbformat_output) = match message {
+            JupyterMessageContent::ExecuteInput(input) => {
+                self.execution_count = Some(input.execution_count.0 as i32);
+                cx.notify();
+                return;
+            }
+            JupyterMessageContent::ExecuteResult(result) => {
+                self.execution_count = Some(result.execution_count.0 as i32);
+                (
+                    Output::new(
+                        &result.data,
+                        result.transient.as_ref().and_then(|t| t.display_id.clone()),
+                        window,
+                        cx,
+                    ),
+                    nbformat_output("execute_result", result),
+                )
+            }
+            JupyterMessageContent::DisplayData(result) => (
+                Output::new(
+                    &result.data,
+                    result.transient.as_ref().and_then(|t| t.display_id.clone()),
+                    window,
+                    cx,
+                ),
+                nbformat_output("display_data", result),
+            ),
+            JupyterMessageContent::StreamContent(result) => {
+                self.take_pending_clear();
+
+                // Previous stream data combines together, as it does in the REPL
+                if let Some(Output::Stream { content }) = self.outputs.last() {
+                    content.update(cx, |content, cx| {
+                        content.append_text(&result.text, cx);
+                        cx.notify();
+                    });
+                } else {
+                    self.outputs.push(Output::Stream {
+                        content: cx.new(|cx| TerminalOutput::from(&result.text, window, cx)),
+                    });
+                }
+
+                if let Some(nbformat::v4::Output::Stream { name, text }) =
+                    nbformat_output("stream", result)
+                {
+                    match self.nbformat_outputs.last_mut() {
+                        Some(nbformat::v4::Output::Stream {
+                            name: last_name,
+                            text: last_text,
+                        }) if *last_name == name => last_text.0.push_str(&text.0),
+                        _ => self
+                            .nbformat_outputs
+                            .push(nbformat::v4::Output::Stream { name, text }),
+                    }
+                }
+                cx.notify();
+                return;
+            }
+            JupyterMessageContent::ErrorOutput(result) => {
+                let terminal =
+                    cx.new(|cx| TerminalOutput::from(&result.traceback.join("\n"), window, cx));
+                (
+                    Output::ErrorOutput(ErrorView {
+                        ename: result.ename.clone(),
+                        evalue: result.evalue.clone(),
+                        traceback: terminal,
+                    }),
+                    nbformat_output("error", result),
+                )
+            }
+            JupyterMessageContent::ExecuteReply(reply) => {
+                self.execution_count = Some(reply.execution_count.0 as i32);
+                cx.notify();
+                return;
+            }
+            JupyterMessageContent::ClearOutput(options) => {
+                if options.wait {
+                    self.clear_on_next_output = true;
+                } else {
+                    self.outputs.clear();
+                    self.nbformat_outputs.clear();
+                }
+                cx.notify();
+                return;
+            }
+            JupyterMessageContent::Status(status) => {
+                self.execution_status = match status.execution_state {
+                    ExecutionState::Busy => ExecutionStatus::Executing,
+                    ExecutionState::Idle => ExecutionStatus::Finished,
+                };
+                cx.notify();
+                return;
+            }
+            _ => return,
+        };
+
+        self.take_pending_clear();
+        self.outputs.push(output);
+        self.nbformat_outputs.extend(nbformat_output);
+        cx.notify();
+    }
+
+    pub fn update_display_data(
+        &mut self,
+        data: &MimeBundle,
+        display_id: &str,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let mut any = false;
+        for output in &mut self.outputs {
+            if output.display_id().as_deref() == Some(display_id) {
+                *output = Output::new(data, Some(display_id.to_owned()), window, cx);
+                any = true;
+            }
+        }
+        if any {
+            cx.notify();
+        }
+    }
+
+    fn take_pending_clear(&mut self) {
+        if std::mem::take(&mut self.clear_on_next_output) {
+            self.outputs.clear();
+            self.nbformat_outputs.clear();
+        }
+    }
+
+    fn to_nbformat(&self, cx: &App) -> nbformat::v4::Cell {
+        nbformat::v4::Cell::Code {
+            id: self.id.clone(),
+            metadata: self.metadata.clone(),
+            execution_count: self.execution_count,
+            source: source_lines(&self.editor.read(cx).text(cx)),
+            outputs: self.nbformat_outputs.clone(),
+        }
+    }
+
+    fn render_execution_status(&self) -> Option<AnyElement> {
+        let label = match &self.execution_status {
+            ExecutionStatus::ConnectingToKernel => Label::new("Connecting to kernel..."),
+            ExecutionStatus::Queued => Label::new("Queued..."),
+            ExecutionStatus::Executing => Label::new("Executing..."),
+            ExecutionStatus::Restarting => Label::new("Kernel restarting..."),
+            ExecutionStatus::ShuttingDown => Label::new("Kernel shutting down..."),
+            ExecutionStatus::Shutdown => Label::new("Kernel shutdown"),
+            ExecutionStatus::KernelErrored(error) => {
+                return Some(
+                    Label::new(format!("Kernel error: {}", error))
+                        .color(Color::Error)
+                        .into_any_element(),
+                );
+            }
+            ExecutionStatus::Unknown | ExecutionStatus::Finished => return None,
+        };
+        Some(label.color(Color::Muted).into_any_element())
     }
 
     fn output_control(&self) -> Option<CellControlType> {
@@ -519,8 +909,8 @@ impl RenderableCell for CodeCell {
             CellControl::new("rerun-cell", CellControlType::RerunCell)
         } else {
             CellControl::new("run-cell", CellControlType::RunCell)
-                .on_click(cx.listener(move |this, _, window, cx| this.run(window, cx)))
-        };
+        }
+        .on_click(cx.listener(move |this, _, window, cx| this.run(window, cx)));
 
         Some(cell_control)
     }
@@ -545,8 +935,8 @@ impl RenderableCell for CodeCell {
 }
 
 impl RunnableCell for CodeCell {
-    fn run(&mut self, window: &mut Window, cx: &mut Context<Self>) {
-        println!("Running code cell: {}", self.id);
+    fn run(&mut self, _window: &mut Window, cx: &mut Context<Self>) {
+        cx.emit(CellEvent::Run);
     }
 
     fn execution_count(&self) -> Option<i32> {
@@ -614,45 +1004,48 @@ impl Render for CodeCell {
                                 .border_1()
                                 // .border_color(cx.theme().colors().border)
                                 // .bg(cx.theme().colors().editor_background)
-                                .child(div().w_full().children(self.outputs.iter().map(
-                                    |output| {
-                                        let content = match output {
-                                            Output::Plain { content, .. } => {
-                                                Some(content.clone().into_any_element())
-                                            }
-                                            Output::Markdown { content, .. } => {
-                                                Some(content.clone().into_any_element())
-                                            }
-                                            Output::Stream { content, .. } => {
-                                                Some(content.clone().into_any_element())
-                                            }
-                                            Output::Image { content, .. } => {
-                                                Some(content.clone().into_any_element())
-                                            }
-                                            Output::Message(message) => Some(
-                                                div().child(message.clone()).into_any_element(),
-                                            ),
-                                            Output::Table { content, .. } => {
-                                                Some(content.clone().into_any_element())
-                                            }
-                                            Output::ErrorOutput(error_view) => {
-                                                error_view.render(window, cx)
-                                            }
-                                            Output::ClearOutputWaitMarker => None,
-                                        };
-
-                                        div()
-                                            // .w_full()
-                                            // .mt_3()
-                                            // .p_3()
-                                            // .rounded_sm()
-                                            // .bg(cx.theme().colors().editor_background)
-                                            // .border(px(1.))
-                                            // .border_color(cx.theme().colors().border)
-                                            // .shadow_sm()
-                                            .children(content)
-                                    },
-                                ))),
+                                .child(
+                                    div()
+                                        .w_full()
+                                        .children(self.render_execution_status())
+                                        .children(self.outputs.iter().map(|output| {
+                                            let content = match output {
+                                                Output::Plain { content, .. } => {
+                                                    Some(content.clone().into_any_element())
+                                                }
+                                                Output::Markdown { content, .. } => {
+                                                    Some(content.clone().into_any_element())
+                                                }
+                                                Output::Stream { content, .. } => {
+                                                    Some(content.clone().into_any_element())
+                                                }
+                                                Output::Image { content, .. } => {
+                                                    Some(content.clone().into_any_element())
+                                                }
+                                                Output::Message(message) => Some(
+                                                    div().child(message.clone()).into_any_element(),
+                                                ),
+                                                Output::Table { content, .. } => {
+                                                    Some(content.clone().into_any_element())
+                                                }
+                                                Output::ErrorOutput(error_view) => {
+                                                    error_view.render(window, cx)
+                                                }
+                                                Output::ClearOutputWaitMarker => None,
+                                            };
+
+                                            div()
+                                                // .w_full()
+                                                // .mt_3()
+                                                // .p_3()
+                                                // .rounded_sm()
+                                                // .bg(cx.theme().colors().editor_background)
+                                                // .border(px(1.))
+                                                // .border_color(cx.theme().colors().border)
+                                                // .shadow_sm()
+                                                .children(content)
+                                        })),
+                                ),
                         ),
                     ),
             )
@@ -665,10 +1058,21 @@ pub struct RawCell {
     id: CellId,
     metadata: CellMetadata,
     source: String,
+    editor: Entity<Editor>,
     selected: bool,
     cell_position: Option<CellPosition>,
 }
 
+impl RawCell {
+    fn to_nbformat(&self, cx: &App) -> nbformat::v4::Cell {
+        nbformat::v4::Cell::Raw {
+            id: self.id.clone(),
+            metadata: self.metadata.clone(),
+            source: source_lines(&self.editor.read(cx).text(cx)),
+        }
+    }
+}
+
 impl RenderableCell for RawCell {
     const CELL_TYPE: CellType = CellType::Raw;
 
@@ -728,9 +1132,7 @@ impl Render for RawCell {
                             .size_full()
                             .flex_1()
                             .p_3()
-                            .font_ui(cx)
-                            .text_size(TextSize::Default.rems(cx))
-                            .child(self.source.clone()),
+                            .child(div().w_full().child(self.editor.clone())),
                     ),
             )
             // TODO: Move base cell render into trait impl so we don't have to repeat this
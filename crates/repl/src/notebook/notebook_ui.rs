@@ -1,40 +1,68 @@
 #![allow(unused, dead_code)]
 use std::future::Future;
-use std::{path::PathBuf, sync::Arc};
+use std::{
+    env::temp_dir,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
 
-use anyhow::{Context as _, Result};
+use anyhow::{Context as _, Result, anyhow};
 use client::proto::ViewId;
 use collections::HashMap;
+use editor::EditorEvent;
 use feature_flags::{FeatureFlagAppExt as _, NotebookFeatureFlag};
 use futures::FutureExt;
 use futures::future::Shared;
 use gpui::{
     AnyElement, App, Entity, EventEmitter, FocusHandle, Focusable, ListScrollEvent, ListState,
-    Point, Task, actions, list, prelude::*,
+    Point, Subscription, Task, WeakEntity, actions, list, prelude::*,
+};
+use language::{Language, LanguageRegistry, LineEnding, Rope};
+use project::{Fs, Project, ProjectEntryId, ProjectPath};
+use runtimelib::{
+    ExecuteRequest, InterruptRequest, JupyterMessage, JupyterMessageContent, ShutdownRequest,
 };
-use language::{Language, LanguageRegistry};
-use project::{Project, ProjectEntryId, ProjectPath};
+use serde::Serialize as _;
 use ui::{Tooltip, prelude::*};
-use workspace::item::{ItemEvent, TabContentParams};
+use util::ResultExt as _;
+use workspace::item::{ItemEvent, SerializableItem, TabContentParams};
 use workspace::searchable::SearchableItemHandle;
-use workspace::{Item, ItemHandle, Pane, ProjectItem, ToolbarItemLocation};
+use workspace::{
+    Item, ItemHandle, ItemId, Pane, ProjectItem, ToolbarItemLocation, Workspace, WorkspaceId,
+};
 use workspace::{ToolbarItemEvent, ToolbarItemView};
 
-use super::{Cell, CellPosition, RenderableCell};
+use super::persistence::NOTEBOOK_DB;
+use super::{Cell, CellEvent, CellPosition, CodeCell, RenderableCell, new_nbformat_cell};
+use crate::kernels::{
+    Kernel, KernelSession, KernelSpecification, KernelStatus, NativeRunningKernel,
+    RemoteRunningKernel,
+};
+use crate::outputs::ExecutionStatus;
+use crate::repl_store::ReplStore;
 
-use nbformat::v4::CellId;
 use nbformat::v4::Metadata as NotebookMetadata;
+use nbformat::v4::{CellId, CellType};
 
 actions!(
     notebook,
     [
         OpenNotebook,
         RunAll,
+        RunCell,
         ClearOutputs,
         MoveCellUp,
         MoveCellDown,
         AddMarkdownBlock,
         AddCodeBlock,
+        DeleteCell,
+        EditCell,
+        ConvertToCode,
+        ConvertToMarkdown,
+        ConvertToRaw,
+        InterruptKernel,
+        RestartKernel,
     ]
 );
 
@@ -49,12 +77,14 @@ pub(crate) const CONTROL_SIZE: f32 = 20.0;
 pub fn init(cx: &mut App) {
     if cx.has_flag::<NotebookFeatureFlag>() || std::env::var("LOCAL_NOTEBOOK_DEV").is_ok() {
         workspace::register_project_item::<NotebookEditor>(cx);
+        workspace::register_serializable_item::<NotebookEditor>(cx);
     }
 
     cx.observe_flag::<NotebookFeatureFlag, _>({
         move |is_enabled, cx| {
             if is_enabled {
                 workspace::register_project_item::<NotebookEditor>(cx);
+                workspace::register_serializable_item::<NotebookEditor>(cx);
             } else {
                 // todo: there is no way to unregister a project item, so if the feature flag
                 // gets turned off they need to restart Zed.
@@ -67,9 +97,11 @@ pub fn init(cx: &mut App) {
 pub struct NotebookEditor {
     languages: Arc<LanguageRegistry>,
     project: Entity<Project>,
+    fs: Arc<dyn Fs>,
 
     focus_handle: FocusHandle,
     notebook_item: Entity<NotebookItem>,
+    notebook_language: Shared<Task<Option<Arc<Language>>>>,
 
     remote_id: Option<ViewId>,
     cell_list: ListState,
@@ -77,6 +109,17 @@ pub struct NotebookEditor {
     selected_cell_index: usize,
     cell_order: Vec<CellId>,
     cell_map: HashMap<CellId, Cell>,
+    cell_subscriptions: HashMap<CellId, Vec<Subscription>>,
+
+    kernel: Kernel,
+    kernel_specification: Option<KernelSpecification>,
+    /// The cell each execution request was sent for, by message id.
+    executions: HashMap<String, CellId>,
+
+    /// Bumped on every edit, so a save only marks the edits made before it
+    /// as saved.
+    version: usize,
+    saved_version: usize,
 }
 
 impl NotebookEditor {
@@ -89,38 +132,17 @@ impl NotebookEditor {
         let focus_handle = cx.focus_handle();
 
         let languages = project.read(cx).languages().clone();
-        let language_name = notebook_item.read(cx).language_name();
+        let fs = project.read(cx).fs().clone();
 
         let notebook_language = notebook_item.read(cx).notebook_language();
         let notebook_language = cx
             .spawn_in(window, async move |_, _| notebook_language.await)
             .shared();
 
-        let mut cell_order = vec![]; // Vec<CellId>
-        let mut cell_map = HashMap::default(); // HashMap<CellId, Cell>
-
-        for (index, cell) in notebook_item
-            .read(cx)
-            .notebook
-            .clone()
-            .cells
-            .iter()
-            .enumerate()
-        {
-            let cell_id = cell.id();
-            cell_order.push(cell_id.clone());
-            cell_map.insert(
-                cell_id.clone(),
-                Cell::load(cell, &languages, notebook_language.clone(), window, cx),
-            );
-        }
-
         let notebook_handle = cx.entity().downgrade();
-        let cell_count = cell_order.len();
 
-        let this = cx.entity();
         let cell_list = ListState::new(
-            cell_count,
+            0,
             gpui::ListAlignment::Top,
             px(1000.),
             move |ix, window, cx| {
@@ -143,17 +165,126 @@ impl NotebookEditor {
             },
         );
 
-        Self {
+        let mut this = Self {
             project,
-            languages: languages.clone(),
+            languages,
+            fs,
             focus_handle,
             notebook_item,
+            notebook_language,
             remote_id: None,
             cell_list,
             selected_cell_index: 0,
-            cell_order: cell_order.clone(),
-            cell_map: cell_map.clone(),
+            cell_order: Vec::new(),
+            cell_map: HashMap::default(),
+            cell_subscriptions: HashMap::default(),
+            kernel: Kernel::Shutdown,
+            kernel_specification: None,
+            executions: HashMap::default(),
+            version: 0,
+            saved_version: 0,
+        };
+        this.load_cells(window, cx);
+        this
+    }
+
+    /// Replaces the cells with the ones in the notebook item.
+    fn load_cells(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.cell_order.clear();
+        self.cell_map.clear();
+        self.cell_subscriptions.clear();
+        self.executions.clear();
+
+        for cell in self.notebook_item.read(cx).notebook.cells.clone() {
+            let cell = Cell::load(
+                &cell,
+                &self.languages,
+                self.notebook_language.clone(),
+                window,
+                cx,
+            );
+            let cell_id = self.track_cell(cell, window, cx);
+            self.cell_order.push(cell_id);
         }
+
+        self.cell_list.reset(self.cell_order.len());
+        self.selected_cell_index = self
+            .selected_cell_index
+            .min(self.cell_order.len().saturating_sub(1));
+        cx.notify();
+    }
+
+    fn track_cell(&mut self, cell: Cell, window: &mut Window, cx: &mut Context<Self>) -> CellId {
+        let cell_id = cell.id(cx);
+        let subscriptions = self.subscribe_to_cell(&cell, window, cx);
+        self.cell_subscriptions
+            .insert(cell_id.clone(), subscriptions);
+        self.cell_map.insert(cell_id.clone(), cell);
+        cell_id
+    }
+
+    fn subscribe_to_cell(
+        &self,
+        cell: &Cell,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Vec<Subscription> {
+        let cell_id = cell.id(cx);
+        let mut subscriptions = vec![cx.subscribe_in(&cell.editor(cx), window, {
+            let cell = cell.clone();
+            let cell_id = cell_id.clone();
+            move |this, _, event: &EditorEvent, window, cx| match event {
+                EditorEvent::Focused => {
+                    if let Some(index) = this.cell_index(&cell_id) {
+                        this.set_selected_index(index, false, window, cx);
+                        cx.notify();
+                    }
+                }
+                EditorEvent::Blurred => {
+                    if let Cell::Markdown(markdown_cell) = &cell {
+                        markdown_cell.update(cx, |cell, cx| cell.stop_editing(window, cx));
+                    }
+                }
+                EditorEvent::BufferEdited => this.mark_dirty(cx),
+                _ => {}
+            }
+        })];
+
+        if let Cell::Code(code_cell) = cell {
+            subscriptions.push(cx.subscribe_in(
+                code_cell,
+                window,
+                move |this, _, event: &CellEvent, window, cx| match event {
+                    CellEvent::Run => {
+                        if let Some(index) = this.cell_index(&cell_id) {
+                            this.run_cell(index, window, cx);
+                        }
+                    }
+                },
+            ));
+        }
+
+        subscriptions
+    }
+
+    fn cell_index(&self, cell_id: &CellId) -> Option<usize> {
+        self.cell_order.iter().position(|id| id == cell_id)
+    }
+
+    fn cell_at(&self, index: usize) -> Option<Cell> {
+        self.cell_order
+            .get(index)
+            .and_then(|cell_id| self.cell_map.get(cell_id))
+            .cloned()
+    }
+
+    fn mark_dirty(&mut self, cx: &mut Context<Self>) {
+        let was_dirty = self.version != self.saved_version;
+        self.version += 1;
+        if !was_dirty {
+            cx.emit(());
+        }
+        cx.notify();
     }
 
     fn has_outputs(&self, window: &mut Window, cx: &mut Context<Self>) -> bool {
@@ -169,15 +300,225 @@ impl NotebookEditor {
     fn clear_outputs(&mut self, window: &mut Window, cx: &mut Context<Self>) {
         for cell in self.cell_map.values() {
             if let Cell::Code(code_cell) = cell {
-                code_cell.update(cx, |cell, _cx| {
+                code_cell.update(cx, |cell, cx| {
                     cell.clear_outputs();
+                    cx.notify();
                 });
             }
         }
+        self.executions.clear();
+        self.mark_dirty(cx);
     }
 
     fn run_cells(&mut self, window: &mut Window, cx: &mut Context<Self>) {
-        println!("Cells would all run here, if that was implemented!");
+        for index in 0..self.cell_count() {
+            self.run_cell(index, window, cx);
+        }
+    }
+
+    /// Runs the selected cell and moves on to the next one, like Jupyter does.
+    fn run_selected_cell(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let index = self.selected_index();
+        self.run_cell(index, window, cx);
+        if index + 1 < self.cell_count() {
+            self.set_selected_index(index + 1, true, window, cx);
+            window.focus(&self.focus_handle);
+        }
+        cx.notify();
+    }
+
+    fn run_cell(&mut self, index: usize, window: &mut Window, cx: &mut Context<Self>) {
+        match self.cell_at(index) {
+            Some(Cell::Code(code_cell)) => {
+                let code = code_cell.read(cx).editor().read(cx).text(cx);
+                if !code.trim().is_empty() {
+                    self.execute(code_cell, code, window, cx);
+                }
+            }
+            Some(Cell::Markdown(markdown_cell)) => {
+                markdown_cell.update(cx, |cell, cx| cell.stop_editing(window, cx));
+            }
+            Some(Cell::Raw(_)) | None => {}
+        }
+    }
+
+    fn execute(
+        &mut self,
+        code_cell: Entity<CodeCell>,
+        code: String,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if matches!(self.kernel, Kernel::Shutdown | Kernel::ErroredLaunch(_)) {
+            self.start_kernel(window, cx);
+        }
+
+        let message: JupyterMessage = ExecuteRequest {
+            code,
+            ..ExecuteRequest::default()
+        }
+        .into();
+
+        let status = match &self.kernel {
+            Kernel::Restarting => ExecutionStatus::Restarting,
+            Kernel::RunningKernel(_) => ExecutionStatus::Queued,
+            Kernel::StartingKernel(_) => ExecutionStatus::ConnectingToKernel,
+            Kernel::ErroredLaunch(error) => ExecutionStatus::KernelErrored(error.clone()),
+            Kernel::ShuttingDown => ExecutionStatus::ShuttingDown,
+            Kernel::Shutdown => ExecutionStatus::Shutdown,
+        };
+
+        let cell_id = code_cell.read(cx).id().clone();
+        self.executions.retain(|_, id| *id != cell_id);
+        self.executions
+            .insert(message.header.msg_id.clone(), cell_id);
+        code_cell.update(cx, |cell, cx| cell.start_execution(status, cx));
+        self.mark_dirty(cx);
+
+        match &self.kernel {
+            Kernel::RunningKernel(_) => self.send(message),
+            Kernel::StartingKernel(task) => {
+                // Queue up the execution to run once the kernel has started
+                let task = task.clone();
+                cx.spawn(async move |this, cx| {
+                    task.await;
+                    this.update(cx, |this, _| this.send(message)).ok();
+                })
+                .detach();
+            }
+            _ => {}
+        }
+    }
+
+    fn send(&mut self, message: JupyterMessage) {
+        if let Kernel::RunningKernel(kernel) = &mut self.kernel {
+            kernel.request_tx().try_send(message).ok();
+        }
+    }
+
+    /// The kernel named in the notebook's metadata, falling back to the one
+    /// picked for the notebook's language.
+    fn find_kernel_specification(&self, cx: &App) -> Option<KernelSpecification> {
+        let notebook_item = self.notebook_item.read(cx);
+        let worktree_id = notebook_item.project_path.worktree_id;
+        let store = ReplStore::global(cx).read(cx);
+
+        let kernel_name = notebook_item
+            .notebook
+            .metadata
+            .kernelspec
+            .as_ref()
+            .map(|kernelspec| kernelspec.name.as_str());
+        let named_kernel = kernel_name.and_then(|kernel_name| {
+            store
+                .kernel_specifications_for_worktree(worktree_id)
+                .find(|spec| spec.name().as_ref() == kernel_name)
+                .cloned()
+        });
+
+        named_kernel.or_else(|| {
+            let language = self.cell_map.values().find_map(|cell| match cell {
+                Cell::Code(code_cell) => code_cell
+                    .read(cx)
+                    .editor()
+                    .read(cx)
+                    .buffer()
+                    .read(cx)
+                    .as_singleton()?
+                    .read(cx)
+                    .language()
+                    .cloned(),
+                _ => None,
+            });
+            store.active_kernelspec(worktree_id, language, cx)
+        })
+    }
+
+    fn start_kernel(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(kernel_specification) = self.find_kernel_specification(cx) else {
+            self.kernel = Kernel::ErroredLaunch(format!(
+                "No kernel found for this notebook. See {}",
+                crate::KERNEL_DOCS_URL
+            ));
+            cx.notify();
+            return;
+        };
+
+        let working_directory = self
+            .notebook_item
+            .read(cx)
+            .path
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_else(temp_dir);
+        let notebook = cx.entity();
+
+        let kernel = match kernel_specification.clone() {
+            KernelSpecification::Jupyter(kernel_specification)
+            | KernelSpecification::PythonEnv(kernel_specification) => NativeRunningKernel::new(
+                kernel_specification,
+                cx.entity_id(),
+                working_directory,
+                self.fs.clone(),
+                notebook,
+                window,
+                cx,
+            ),
+            KernelSpecification::Remote(remote_kernel_specification) => RemoteRunningKernel::new(
+                remote_kernel_specification,
+                working_directory,
+                notebook,
+                window,
+                cx,
+            ),
+        };
+
+        let pending_kernel = cx
+            .spawn(async move |this, cx| {
+                let kernel = kernel.await;
+                this.update(cx, |this, cx| match kernel {
+                    Ok(kernel) => {
+                        this.kernel = Kernel::RunningKernel(kernel);
+                        cx.notify();
+                    }
+                    Err(error) => this.kernel_errored(error.to_string(), cx),
+                })
+                .ok();
+            })
+            .shared();
+
+        self.kernel_specification = Some(kernel_specification);
+        self.kernel = Kernel::StartingKernel(pending_kernel);
+        cx.notify();
+    }
+
+    fn interrupt_kernel(&mut self, _: &InterruptKernel, _: &mut Window, _: &mut Context<Self>) {
+        self.send(InterruptRequest {}.into());
+    }
+
+    fn restart_kernel(&mut self, _: &RestartKernel, window: &mut Window, cx: &mut Context<Self>) {
+        match std::mem::replace(&mut self.kernel, Kernel::Restarting) {
+            Kernel::Restarting => {}
+            Kernel::RunningKernel(mut kernel) => {
+                let mut request_tx = kernel.request_tx();
+                let forced = kernel.force_shutdown(window, cx);
+
+                cx.spawn_in(window, async move |this, cx| {
+                    let message: JupyterMessage = ShutdownRequest { restart: true }.into();
+                    request_tx.try_send(message).ok();
+
+                    // Give the kernel a moment to shut down before forcing it
+                    cx.background_executor().timer(Duration::from_secs(1)).await;
+                    forced.await.log_err();
+
+                    this.update_in(cx, |this, window, cx| this.start_kernel(window, cx))
+                        .ok();
+                })
+                .detach();
+            }
+            _ => self.start_kernel(window, cx),
+        }
+        cx.notify();
     }
 
     fn open_notebook(&mut self, _: &OpenNotebook, _window: &mut Window, _cx: &mut Context<Self>) {
@@ -185,19 +526,194 @@ impl NotebookEditor {
     }
 
     fn move_cell_up(&mut self, window: &mut Window, cx: &mut Context<Self>) {
-        println!("Move cell up triggered");
+        let index = self.selected_index();
+        if index > 0 && index < self.cell_count() {
+            self.swap_cells(index - 1, window, cx);
+            self.set_selected_index(index - 1, true, window, cx);
+        }
     }
 
     fn move_cell_down(&mut self, window: &mut Window, cx: &mut Context<Self>) {
-        println!("Move cell down triggered");
+        let index = self.selected_index();
+        if index + 1 < self.cell_count() {
+            self.swap_cells(index, window, cx);
+            self.set_selected_index(index + 1, true, window, cx);
+        }
+    }
+
+    /// Swaps the cell at `index` with the one after it.
+    fn swap_cells(&mut self, index: usize, _window: &mut Window, cx: &mut Context<Self>) {
+        self.cell_order.swap(index, index + 1);
+        self.cell_list.splice(index..index + 2, 2);
+        self.mark_dirty(cx);
     }
 
     fn add_markdown_block(&mut self, window: &mut Window, cx: &mut Context<Self>) {
-        println!("Add markdown block triggered");
+        self.add_cell(CellType::Markdown, window, cx);
     }
 
     fn add_code_block(&mut self, window: &mut Window, cx: &mut Context<Self>) {
-        println!("Add code block triggered");
+        self.add_cell(CellType::Code, window, cx);
+    }
+
+    /// Adds an empty cell after the selected one and starts editing it.
+    fn add_cell(&mut self, cell_type: CellType, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(cell) = new_nbformat_cell(cell_type, None, None, "").log_err() else {
+            return;
+        };
+        let cell = Cell::load(
+            &cell,
+            &self.languages,
+            self.notebook_language.clone(),
+            window,
+            cx,
+        );
+
+        let index = if self.cell_order.is_empty() {
+            0
+        } else {
+            self.selected_index() + 1
+        };
+        let cell_id = self.track_cell(cell, window, cx);
+        self.cell_order.insert(index, cell_id);
+        self.cell_list.splice(index..index, 1);
+        self.set_selected_index(index, true, window, cx);
+        self.edit_cell(index, window, cx);
+        self.mark_dirty(cx);
+    }
+
+    fn delete_cell(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let index = self.selected_index();
+        if index >= self.cell_count() {
+            return;
+        }
+
+        let cell_id = self.cell_order.remove(index);
+        self.cell_map.remove(&cell_id);
+        self.cell_subscriptions.remove(&cell_id);
+        self.executions.retain(|_, id| *id != cell_id);
+        self.cell_list.splice(index..index + 1, 0);
+
+        if !self.cell_order.is_empty() {
+            self.set_selected_index(index.min(self.cell_count() - 1), true, window, cx);
+        }
+        window.focus(&self.focus_handle);
+        self.mark_dirty(cx);
+    }
+
+    /// Moves focus into the selected cell's editor.
+    fn edit_cell(&mut self, index: usize, window: &mut Window, cx: &mut Context<Self>) {
+        match self.cell_at(index) {
+            Some(Cell::Markdown(markdown_cell)) => {
+                markdown_cell.update(cx, |cell, cx| cell.start_editing(window, cx));
+            }
+            Some(cell) => window.focus(&cell.editor(cx).focus_handle(cx)),
+            None => {}
+        }
+    }
+
+    /// Changes the type of the selected cell, keeping its id, metadata and
+    /// source.
+    fn convert_cell(&mut self, cell_type: CellType, window: &mut Window, cx: &mut Context<Self>) {
+        let index = self.selected_index();
+        let Some(cell) = self.cell_at(index) else {
+            return;
+        };
+        let unchanged = matches!(
+            (&cell, &cell_type),
+            (Cell::Code(_), CellType::Code)
+                | (Cell::Markdown(_), CellType::Markdown)
+                | (Cell::Raw(_), CellType::Raw)
+        );
+        if unchanged {
+            return;
+        }
+
+        let cell_id = cell.id(cx);
+        let Some(converted) = new_nbformat_cell(
+            cell_type,
+            Some(&cell_id),
+            Some(&cell.metadata(cx)),
+            &cell.text(cx),
+        )
+        .log_err() else {
+            return;
+        };
+        let converted = Cell::load(
+            &converted,
+            &self.languages,
+            self.notebook_language.clone(),
+            window,
+            cx,
+        );
+
+        self.executions.retain(|_, id| *id != cell_id);
+        self.track_cell(converted, window, cx);
+        self.cell_list.splice(index..index + 1, 1);
+        window.focus(&self.focus_handle);
+        self.mark_dirty(cx);
+    }
+
+    /// The notebook as it should be written to disk, with the current cells.
+    fn to_notebook(&self, cx: &App) -> nbformat::v4::Notebook {
+        let mut notebook = self.notebook_item.read(cx).notebook.clone();
+        notebook.cells = self
+            .cell_order
+            .iter()
+            .filter_map(|cell_id| self.cell_map.get(cell_id))
+            .map(|cell| cell.to_nbformat(cx))
+            .collect();
+        notebook
+    }
+
+    fn write_notebook(
+        &mut self,
+        project: Entity<Project>,
+        project_path: ProjectPath,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let notebook = self.to_notebook(cx);
+        let content = match serialize_notebook(&notebook) {
+            Ok(content) => content,
+            Err(error) => return Task::ready(Err(error)),
+        };
+        let Some(worktree) = project
+            .read(cx)
+            .worktree_for_id(project_path.worktree_id, cx)
+        else {
+            return Task::ready(Err(anyhow!("no worktree for {:?}", project_path.path)));
+        };
+        let abs_path = project.read(cx).absolute_path(&project_path, cx);
+        let write = worktree.update(cx, |worktree, cx| {
+            worktree.write_file(
+                &project_path.path,
+                Rope::from(content.as_str()),
+                LineEnding::Unix,
+                cx,
+            )
+        });
+
+        let version = self.version;
+        let notebook_item = self.notebook_item.clone();
+        cx.spawn_in(window, async move |this, cx| {
+            let file = write.await?;
+            notebook_item.update(cx, |notebook_item, _| {
+                notebook_item.notebook = notebook;
+                if let Some(abs_path) = abs_path {
+                    notebook_item.path = abs_path;
+                }
+                notebook_item.project_path = project_path;
+                if let Some(entry_id) = file.entry_id {
+                    notebook_item.id = entry_id;
+                }
+            })?;
+            this.update(cx, |this, cx| {
+                this.saved_version = version;
+                cx.emit(());
+                cx.notify();
+            })
+        })
     }
 
     fn cell_count(&self) -> usize {
@@ -319,6 +835,15 @@ impl NotebookEditor {
         cx: &mut Context<Self>,
     ) -> impl IntoElement {
         let has_outputs = self.has_outputs(window, cx);
+        let kernel_description: SharedString = match &self.kernel_specification {
+            Some(kernel_specification) => format!(
+                "{}: {}",
+                kernel_specification.name(),
+                self.kernel.status().to_string()
+            )
+            .into(),
+            None => "No kernel started".into(),
+        };
 
         v_flex()
             .max_w(px(CONTROL_SIZE + 4.0))
@@ -397,6 +922,20 @@ impl NotebookEditor {
                                 .on_click(|_, window, cx| {
                                     window.dispatch_action(Box::new(MoveCellDown), cx);
                                 }),
+                            )
+                            .child(
+                                Self::render_notebook_control(
+                                    "delete-cell",
+                                    IconName::Trash,
+                                    window,
+                                    cx,
+                                )
+                                .tooltip(move |window, cx| {
+                                    Tooltip::for_action("Delete cell", &DeleteCell, window, cx)
+                                })
+                                .on_click(|_, window, cx| {
+                                    window.dispatch_action(Box::new(DeleteCell), cx);
+                                }),
                             ),
                     )
                     .child(
@@ -447,8 +986,21 @@ impl NotebookEditor {
                         cx,
                     ))
                     .child(
-                        Self::button_group(window, cx)
-                            .child(IconButton::new("repl", IconName::ReplNeutral)),
+                        Self::button_group(window, cx).child(
+                            IconButton::new("repl", IconName::ReplNeutral)
+                                .tooltip(move |window, cx| {
+                                    Tooltip::with_meta(
+                                        "Restart kernel",
+                                        Some(&RestartKernel),
+                                        kernel_description.clone(),
+                                        window,
+                                        cx,
+                                    )
+                                })
+                                .on_click(|_, window, cx| {
+                                    window.dispatch_action(Box::new(RestartKernel), cx);
+                                }),
+                        ),
                     ),
             )
     }
@@ -510,6 +1062,7 @@ impl Render for NotebookEditor {
                 cx.listener(|this, &ClearOutputs, window, cx| this.clear_outputs(window, cx)),
             )
             .on_action(cx.listener(|this, &RunAll, window, cx| this.run_cells(window, cx)))
+            .on_action(cx.listener(|this, &RunCell, window, cx| this.run_selected_cell(window, cx)))
             .on_action(cx.listener(|this, &MoveCellUp, window, cx| this.move_cell_up(window, cx)))
             .on_action(
                 cx.listener(|this, &MoveCellDown, window, cx| this.move_cell_down(window, cx)),
@@ -520,6 +1073,21 @@ impl Render for NotebookEditor {
             .on_action(
                 cx.listener(|this, &AddCodeBlock, window, cx| this.add_code_block(window, cx)),
             )
+            .on_action(cx.listener(|this, &DeleteCell, window, cx| this.delete_cell(window, cx)))
+            .on_action(cx.listener(|this, &EditCell, window, cx| {
+                this.edit_cell(this.selected_index(), window, cx)
+            }))
+            .on_action(cx.listener(|this, &ConvertToCode, window, cx| {
+                this.convert_cell(CellType::Code, window, cx)
+            }))
+            .on_action(cx.listener(|this, &ConvertToMarkdown, window, cx| {
+                this.convert_cell(CellType::Markdown, window, cx)
+            }))
+            .on_action(cx.listener(|this, &ConvertToRaw, window, cx| {
+                this.convert_cell(CellType::Raw, window, cx)
+            }))
+            .on_action(cx.listener(Self::interrupt_kernel))
+            .on_action(cx.listener(Self::restart_kernel))
             .on_action(cx.listener(Self::select_next))
             .on_action(cx.listener(Self::select_previous))
             .on_action(cx.listener(Self::select_first))
@@ -578,23 +1146,7 @@ impl project::ProjectItem for NotebookItem {
                     .ok_or_else(|| anyhow::anyhow!("Failed to find the absolute path"))?;
 
                 // todo: watch for changes to the file
-                let file_content = fs.load(&abs_path.as_path()).await?;
-                let notebook = nbformat::parse_notebook(&file_content);
-
-                let notebook = match notebook {
-                    Ok(nbformat::Notebook::V4(notebook)) => notebook,
-                    // 4.1 - 4.4 are converted to 4.5
-                    Ok(nbformat::Notebook::Legacy(legacy_notebook)) => {
-                        // TODO: Decide if we want to mutate the notebook by including Cell IDs
-                        // and any other conversions
-                        let notebook = nbformat::upgrade_legacy_notebook(legacy_notebook)?;
-                        notebook
-                    }
-                    // Bad notebooks and notebooks v4.0 and below are not supported
-                    Err(e) => {
-                        anyhow::bail!("Failed to parse notebook: {:?}", e);
-                    }
-                };
+                let notebook = load_notebook(fs.as_ref(), &abs_path).await?;
 
                 let id = project
                     .update(cx, |project, cx| project.entry_for_path(&path, cx))?
@@ -627,6 +1179,37 @@ impl project::ProjectItem for NotebookItem {
     }
 }
 
+async fn load_notebook(fs: &dyn Fs, abs_path: &Path) -> Result<nbformat::v4::Notebook> {
+    let file_content = fs.load(abs_path).await?;
+    let notebook = nbformat::parse_notebook(&file_content);
+
+    match notebook {
+        Ok(nbformat::Notebook::V4(notebook)) => Ok(notebook),
+        // 4.1 - 4.4 are converted to 4.5
+        Ok(nbformat::Notebook::Legacy(legacy_notebook)) => {
+            // TODO: Decide if we want to mutate the notebook by including Cell IDs
+            // and any other conversions
+            let notebook = nbformat::upgrade_legacy_notebook(legacy_notebook)?;
+            Ok(notebook)
+        }
+        // Bad notebooks and notebooks v4.0 and below are not supported
+        Err(e) => {
+            anyhow::bail!("Failed to parse notebook: {:?}", e);
+        }
+    }
+}
+
+/// Serializes a notebook the way Jupyter writes it, indented by one space.
+fn serialize_notebook(notebook: &nbformat::v4::Notebook) -> Result<String> {
+    let mut content = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(b" ");
+    let mut serializer = serde_json::Serializer::with_formatter(&mut content, formatter);
+    notebook.serialize(&mut serializer)?;
+    let mut content = String::from_utf8(content)?;
+    content.push('\n');
+    Ok(content)
+}
+
 impl NotebookItem {
     pub fn language_name(&self) -> Option<String> {
         self.notebook
@@ -771,54 +1354,206 @@ impl Item for NotebookEditor {
         // TODO
     }
 
-    // TODO
     fn can_save(&self, _cx: &App) -> bool {
-        false
+        true
     }
-    // TODO
+
     fn save(
         &mut self,
         _format: bool,
-        _project: Entity<Project>,
-        _window: &mut Window,
-        _cx: &mut Context<Self>,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
     ) -> Task<Result<()>> {
-        unimplemented!("save() must be implemented if can_save() returns true")
+        let project_path = self.notebook_item.read(cx).project_path.clone();
+        self.write_notebook(project, project_path, window, cx)
     }
 
-    // TODO
     fn save_as(
         &mut self,
-        _project: Entity<Project>,
-        _path: ProjectPath,
-        _window: &mut Window,
-        _cx: &mut Context<Self>,
+        project: Entity<Project>,
+        path: ProjectPath,
+        window: &mut Window,
+        cx: &mut Context<Self>,
     ) -> Task<Result<()>> {
-        unimplemented!("save_as() must be implemented if can_save() returns true")
+        self.write_notebook(project, path, window, cx)
     }
-    // TODO
+
     fn reload(
         &mut self,
-        _project: Entity<Project>,
-        _window: &mut Window,
-        _cx: &mut Context<Self>,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
     ) -> Task<Result<()>> {
-        unimplemented!("reload() must be implemented if can_save() returns true")
+        let fs = project.read(cx).fs().clone();
+        let abs_path = self.notebook_item.read(cx).path.clone();
+
+        cx.spawn_in(window, async move |this, cx| {
+            let notebook = load_notebook(fs.as_ref(), &abs_path).await?;
+            this.update_in(cx, |this, window, cx| {
+                this.notebook_item
+                    .update(cx, |notebook_item, _| notebook_item.notebook = notebook);
+                this.load_cells(window, cx);
+                this.saved_version = this.version;
+                cx.emit(());
+            })
+        })
     }
 
-    fn is_dirty(&self, cx: &App) -> bool {
-        self.cell_map.values().any(|cell| {
-            if let Cell::Code(code_cell) = cell {
-                code_cell.read(cx).is_dirty(cx)
-            } else {
-                false
-            }
+    fn is_dirty(&self, _cx: &App) -> bool {
+        self.version != self.saved_version
+    }
+
+    fn to_item_events(_event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        f(ItemEvent::UpdateTab)
+    }
+}
+
+impl SerializableItem for NotebookEditor {
+    fn serialized_item_kind() -> &'static str {
+        "NotebookEditor"
+    }
+
+    fn deserialize(
+        project: Entity<Project>,
+        _workspace: WeakEntity<Workspace>,
+        workspace_id: WorkspaceId,
+        item_id: ItemId,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Task<gpui::Result<Entity<Self>>> {
+        window.spawn(cx, async move |cx| {
+            let notebook_path = NOTEBOOK_DB
+                .get_notebook_path(item_id, workspace_id)?
+                .ok_or_else(|| anyhow!("No notebook path found"))?;
+
+            let (worktree, relative_path) = project
+                .update(cx, |project, cx| {
+                    project.find_or_create_worktree(notebook_path, false, cx)
+                })?
+                .await
+                .context("Path not found")?;
+            let worktree_id = worktree.update(cx, |worktree, _cx| worktree.id())?;
+
+            let project_path = ProjectPath {
+                worktree_id,
+                path: relative_path.into(),
+            };
+
+            let notebook_item = cx
+                .update(|_, cx| {
+                    <NotebookItem as project::ProjectItem>::try_open(&project, &project_path, cx)
+                })?
+                .context("Not a notebook")?
+                .await?;
+
+            cx.update(|window, cx| {
+                Ok(cx.new(|cx| NotebookEditor::new(project, notebook_item, window, cx)))
+            })?
         })
     }
+
+    fn cleanup(
+        workspace_id: WorkspaceId,
+        alive_items: Vec<ItemId>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Task<gpui::Result<()>> {
+        window.spawn(cx, async move |_| {
+            NOTEBOOK_DB
+                .delete_unloaded_items(workspace_id, alive_items)
+                .await
+        })
+    }
+
+    fn serialize(
+        &mut self,
+        workspace: &mut Workspace,
+        item_id: ItemId,
+        _closing: bool,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Option<Task<gpui::Result<()>>> {
+        let workspace_id = workspace.database_id()?;
+        let notebook_path = self.notebook_item.read(cx).path.clone();
+
+        Some(cx.background_spawn(async move {
+            NOTEBOOK_DB
+                .save_notebook_path(item_id, workspace_id, notebook_path)
+                .await
+        }))
+    }
+
+    fn should_serialize(&self, _event: &Self::Event) -> bool {
+        false
+    }
 }
 
-// TODO: Implement this to allow us to persist to the database, etc:
-// impl SerializableItem for NotebookEditor {}
+impl KernelSession for NotebookEditor {
+    fn route(&mut self, message: &JupyterMessage, window: &mut Window, cx: &mut Context<Self>) {
+        let parent_message_id = match message.parent_header.as_ref() {
+            Some(header) => &header.msg_id,
+            None => return,
+        };
+
+        match &message.content {
+            JupyterMessageContent::Status(status) => {
+                self.kernel.set_execution_state(&status.execution_state);
+                cx.notify();
+            }
+            JupyterMessageContent::KernelInfoReply(reply) => {
+                self.kernel.set_kernel_info(reply);
+                cx.notify();
+            }
+            JupyterMessageContent::UpdateDisplayData(update) => {
+                let Some(display_id) = update.transient.display_id.as_ref() else {
+                    return;
+                };
+                for cell in self.cell_map.values() {
+                    if let Cell::Code(code_cell) = cell {
+                        code_cell.update(cx, |cell, cx| {
+                            cell.update_display_data(&update.data, display_id, window, cx);
+                        });
+                    }
+                }
+                return;
+            }
+            _ => {}
+        }
+
+        let cell = self
+            .executions
+            .get(parent_message_id)
+            .and_then(|cell_id| self.cell_map.get(cell_id))
+            .cloned();
+        if let Some(Cell::Code(code_cell)) = cell {
+            code_cell.update(cx, |cell, cx| {
+                cell.push_message(&message.content, window, cx);
+            });
+            if !matches!(message.content, JupyterMessageContent::Status(_)) {
+                self.mark_dirty(cx);
+            }
+        }
+    }
+
+    fn kernel_errored(&mut self, error_message: String, cx: &mut Context<Self>) {
+        self.kernel = Kernel::ErroredLaunch(error_message.clone());
+
+        for cell_id in self.executions.values() {
+            if let Some(Cell::Code(code_cell)) = self.cell_map.get(cell_id) {
+                code_cell.update(cx, |cell, cx| {
+                    if !matches!(cell.execution_status(), ExecutionStatus::Finished) {
+                        cell.set_execution_status(
+                            ExecutionStatus::KernelErrored(error_message.clone()),
+                            cx,
+                        );
+                    }
+                });
+            }
+        }
+        cx.notify();
+    }
+}
 
 impl ProjectItem for NotebookEditor {
     type Item = NotebookItem;
@@ -836,3 +1571,348 @@ impl ProjectItem for NotebookEditor {
         Self::new(project, item, window, cx)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notebook::source_lines;
+    use gpui::{TestAppContext, VisualTestContext};
+    use project::FakeFs;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use util::path;
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            workspace::init_settings(cx);
+            editor::init(cx);
+            Project::init_settings(cx);
+        });
+    }
+
+    fn notebook_json(code: &str, output: &str) -> String {
+        json!({
+            "cells": [
+                {
+                    "cell_type": "markdown",
+                    "id": "intro",
+                    "metadata": {},
+                    "source": ["# Title"]
+                },
+                {
+                    "cell_type": "code",
+                    "execution_count": 1,
+                    "id": "greet",
+                    "metadata": {},
+                    "outputs": [
+                        { "name": "stdout", "output_type": "stream", "text": [output] }
+                    ],
+                    "source": [code]
+                }
+            ],
+            "metadata": {},
+            "nbformat": 4,
+            "nbformat_minor": 5
+        })
+        .to_string()
+    }
+
+    async fn open_notebook(
+        project: &Entity<Project>,
+        path: &str,
+        cx: &mut VisualTestContext,
+    ) -> Entity<NotebookEditor> {
+        let project_path = project
+            .read_with(cx, |project, cx| project.find_project_path(path, cx))
+            .unwrap();
+        let notebook_item = cx
+            .update(|_, cx| {
+                <NotebookItem as project::ProjectItem>::try_open(project, &project_path, cx)
+            })
+            .unwrap()
+            .await
+            .unwrap();
+        cx.new_window_entity(|window, cx| {
+            NotebookEditor::new(project.clone(), notebook_item, window, cx)
+        })
+    }
+
+    fn cell_texts(notebook: &Entity<NotebookEditor>, cx: &mut VisualTestContext) -> Vec<String> {
+        cx.run_until_parked();
+        notebook.read_with(cx, |notebook, cx| {
+            (0..notebook.cell_count())
+                .filter_map(|ix| notebook.cell_at(ix))
+                .map(|cell| cell.text(cx))
+                .collect()
+        })
+    }
+
+    fn set_cell_text(
+        notebook: &Entity<NotebookEditor>,
+        index: usize,
+        text: &str,
+        cx: &mut VisualTestContext,
+    ) {
+        let editor = notebook.read_with(cx, |notebook, cx| {
+            notebook.cell_at(index).unwrap().editor(cx)
+        });
+        editor.update_in(cx, |editor, window, cx| editor.set_text(text, window, cx));
+        cx.run_until_parked();
+    }
+
+    #[gpui::test]
+    async fn test_save_notebook(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/project"),
+            json!({ "notebook.ipynb": notebook_json("print('hello')", "hello\n") }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), [path!("/project").as_ref()], cx).await;
+        let (_, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project.clone(), window, cx));
+
+        let notebook = open_notebook(&project, "notebook.ipynb", cx).await;
+        assert_eq!(cell_texts(&notebook, cx), ["# Title", "print('hello')"]);
+
+        set_cell_text(&notebook, 1, "print('hello')\nprint('bye')", cx);
+        notebook.read_with(cx, |notebook, cx| assert!(notebook.is_dirty(cx)));
+
+        notebook
+            .update_in(cx, |notebook, window, cx| {
+                notebook.save(false, project.clone(), window, cx)
+            })
+            .await
+            .unwrap();
+        notebook.read_with(cx, |notebook, cx| assert!(!notebook.is_dirty(cx)));
+
+        // Edits are written along with the outputs and ids of the cells.
+        let saved = fs
+            .load(Path::new(path!("/project/notebook.ipynb")))
+            .await
+            .unwrap();
+        let saved: serde_json::Value = serde_json::from_str(&saved).unwrap();
+        assert_eq!(saved["cells"][0]["id"], "intro");
+        assert_eq!(saved["cells"][0]["source"], json!(["# Title"]));
+        assert_eq!(saved["cells"][1]["id"], "greet");
+        assert_eq!(
+            saved["cells"][1]["source"],
+            json!(["print('hello')\n", "print('bye')"])
+        );
+        assert_eq!(saved["cells"][1]["execution_count"], 1);
+        assert_eq!(saved["cells"][1]["outputs"][0]["text"], json!(["hello\n"]));
+    }
+
+    #[gpui::test]
+    async fn test_reload_notebook(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/project"),
+            json!({ "notebook.ipynb": notebook_json("print('hello')", "hello\n") }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), [path!("/project").as_ref()], cx).await;
+        let (_, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project.clone(), window, cx));
+
+        let notebook = open_notebook(&project, "notebook.ipynb", cx).await;
+        set_cell_text(&notebook, 1, "print('unsaved')", cx);
+
+        // Reloading replaces unsaved edits with the notebook on disk.
+        fs.insert_file(
+            path!("/project/notebook.ipynb"),
+            notebook_json("print('changed')", "changed\n").into_bytes(),
+        )
+        .await;
+        notebook
+            .update_in(cx, |notebook, window, cx| {
+                notebook.reload(project.clone(), window, cx)
+            })
+            .await
+            .unwrap();
+        assert_eq!(cell_texts(&notebook, cx), ["# Title", "print('changed')"]);
+        notebook.read_with(cx, |notebook, cx| {
+            assert!(!notebook.is_dirty(cx));
+            let reloaded = serialize_notebook(&notebook.to_notebook(cx)).unwrap();
+            let reloaded: serde_json::Value = serde_json::from_str(&reloaded).unwrap();
+            assert_eq!(
+                reloaded["cells"][1]["outputs"][0]["text"],
+                json!(["changed\n"])
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_serialize_notebook_editor(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/project"),
+            json!({ "notebook.ipynb": notebook_json("print('hello')", "hello\n") }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), [path!("/project").as_ref()], cx).await;
+        let (workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project.clone(), window, cx));
+        let app_state = workspace.read_with(cx, |workspace, _| workspace.app_state().clone());
+        let workspace_id = workspace::WORKSPACE_DB.next_id().await.unwrap();
+        let (workspace, cx) = cx.add_window_view(|window, cx| {
+            Workspace::new(Some(workspace_id), project.clone(), app_state, window, cx)
+        });
+
+        let notebook = open_notebook(&project, "notebook.ipynb", cx).await;
+        let item_id = 1234 as ItemId;
+        workspace
+            .update_in(cx, |workspace, window, cx| {
+                notebook.update(cx, |notebook, cx| {
+                    notebook.serialize(workspace, item_id, false, window, cx)
+                })
+            })
+            .unwrap()
+            .await
+            .unwrap();
+        assert_eq!(
+            NOTEBOOK_DB
+                .get_notebook_path(item_id, workspace_id)
+                .unwrap(),
+            Some(PathBuf::from(path!("/project/notebook.ipynb")))
+        );
+
+        let restored = workspace
+            .update_in(cx, |workspace, window, cx| {
+                NotebookEditor::deserialize(
+                    project.clone(),
+                    workspace.weak_handle(),
+                    workspace_id,
+                    item_id,
+                    window,
+                    cx,
+                )
+            })
+            .await
+            .unwrap();
+        assert_eq!(cell_texts(&restored, cx), ["# Title", "print('hello')"]);
+        restored.read_with(cx, |restored, cx| {
+            assert_eq!(
+                restored.notebook_item.read(cx).project_path,
+                notebook.read(cx).notebook_item.read(cx).project_path
+            );
+            assert!(!restored.is_dirty(cx));
+        });
+
+        // Items that are no longer open are removed from the database.
+        workspace
+            .update_in(cx, |_, window, cx| {
+                NotebookEditor::cleanup(workspace_id, Vec::new(), window, cx)
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            NOTEBOOK_DB
+                .get_notebook_path(item_id, workspace_id)
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_serialize_notebook_round_trip() {
+        let original = json!({
+            "cells": [
+                {
+                    "cell_type": "markdown",
+                    "id": "intro",
+                    "metadata": { "tags": ["header"] },
+                    "source": ["# Title\n", "Some text"]
+                },
+                {
+                    "cell_type": "code",
+                    "execution_count": 3,
+                    "id": "compute",
+                    "metadata": { "collapsed": false },
+                    "outputs": [
+                        { "name": "stdout", "output_type": "stream", "text": ["hello\n"] },
+                        {
+                            "data": { "text/plain": ["2"] },
+                            "execution_count": 3,
+                            "metadata": {},
+                            "output_type": "execute_result"
+                        }
+                    ],
+                    "source": ["print('hello')\n", "1 + 1"]
+                },
+                {
+                    "cell_type": "raw",
+                    "id": "raw",
+                    "metadata": {},
+                    "source": ["raw text"]
+                }
+            ],
+            "metadata": {
+                "kernelspec": {
+                    "display_name": "Python 3",
+                    "language": "python",
+                    "name": "python3"
+                },
+                "custom_extension": { "setting": 42 }
+            },
+            "nbformat": 4,
+            "nbformat_minor": 5
+        });
+
+        let Ok(nbformat::Notebook::V4(notebook)) = nbformat::parse_notebook(&original.to_string())
+        else {
+            panic!("failed to parse notebook");
+        };
+        let serialized = serialize_notebook(&notebook).unwrap();
+        assert!(serialized.starts_with("{\n \""));
+        assert!(serialized.ends_with("}\n"));
+
+        let saved: serde_json::Value = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(
+            saved["metadata"]["custom_extension"],
+            json!({ "setting": 42 })
+        );
+        assert_eq!(saved["metadata"]["kernelspec"]["name"], "python3");
+        assert_eq!(saved["cells"][0]["metadata"]["tags"], json!(["header"]));
+        assert_eq!(
+            saved["cells"][1]["outputs"][1]["data"]["text/plain"],
+            original["cells"][1]["outputs"][1]["data"]["text/plain"]
+        );
+        assert_eq!(saved["cells"][1]["execution_count"], 3);
+        assert_eq!(saved["cells"][2]["cell_type"], "raw");
+
+        let Ok(nbformat::Notebook::V4(reparsed)) = nbformat::parse_notebook(&serialized) else {
+            panic!("failed to parse the saved notebook");
+        };
+        assert_eq!(serialize_notebook(&reparsed).unwrap(), serialized);
+    }
+
+    #[test]
+    fn test_new_nbformat_cell() {
+        assert_eq!(
+            source_lines("a = 1\nb = 2\n\nprint(a)"),
+            ["a = 1\n", "b = 2\n", "\n", "print(a)"]
+        );
+
+        let cell = new_nbformat_cell(CellType::Code, None, None, "x = 1\nx").unwrap();
+        let nbformat::v4::Cell::Code {
+            source,
+            outputs,
+            execution_count,
+            ..
+        } = cell
+        else {
+            panic!("expected a code cell");
+        };
+        assert_eq!(source, ["x = 1\n", "x"]);
+        assert!(outputs.is_empty());
+        assert_eq!(execution_count, None);
+    }
+}
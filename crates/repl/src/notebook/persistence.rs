@@ -0,0 +1,68 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use db::{define_connection, query, sqlez::statement::Statement, sqlez_macros::sql};
+use workspace::{ItemId, WorkspaceDb, WorkspaceId};
+
+define_connection! {
+    pub static ref NOTEBOOK_DB: NotebookDb<WorkspaceDb> =
+        &[sql!(
+            CREATE TABLE notebook_editors (
+                workspace_id INTEGER,
+                item_id INTEGER UNIQUE,
+
+                notebook_path BLOB,
+
+                PRIMARY KEY(workspace_id, item_id),
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                ON DELETE CASCADE
+            ) STRICT;
+        )];
+}
+
+impl NotebookDb {
+    query! {
+        pub async fn save_notebook_path(
+            item_id: ItemId,
+            workspace_id: WorkspaceId,
+            notebook_path: PathBuf
+        ) -> Result<()> {
+            INSERT OR REPLACE INTO notebook_editors(item_id, workspace_id, notebook_path)
+            VALUES (?, ?, ?)
+        }
+    }
+
+    query! {
+        pub fn get_notebook_path(item_id: ItemId, workspace_id: WorkspaceId) -> Result<Option<PathBuf>> {
+            SELECT notebook_path
+            FROM notebook_editors
+            WHERE item_id = ? AND workspace_id = ?
+        }
+    }
+
+    pub async fn delete_unloaded_items(
+        &self,
+        workspace: WorkspaceId,
+        alive_items: Vec<ItemId>,
+    ) -> Result<()> {
+        let placeholders = alive_items
+            .iter()
+            .map(|_| "?")
+            .collect::<Vec<&str>>()
+            .join(", ");
+
+        let query = format!(
+            "DELETE FROM notebook_editors WHERE workspace_id = ? AND item_id NOT IN ({placeholders})"
+        );
+
+        self.write(move |conn| {
+            let mut statement = Statement::prepare(conn, query)?;
+            let mut next_index = statement.bind(&workspace, 1)?;
+            for id in alive_items {
+                next_index = statement.bind(&id, next_index)?;
+            }
+            statement.exec()
+        })
+        .await
+    }
+}
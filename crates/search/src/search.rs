@@ -27,6 +27,7 @@ actions!(
         ToggleCaseSensitive,
         ToggleIncludeIgnored,
         ToggleRegex,
+        ToggleQuerySyntax,
         ToggleReplace,
         ToggleSelection,
         SelectNextMatch,
@@ -49,6 +50,8 @@ bitflags! {
         const REGEX = 0b1000;
         /// If set, reverse direction when finding the active match
         const BACKWARDS = 0b10000;
+        /// If set, the query uses the search operator syntax, like `path:` and `tag:`
+        const QUERY_SYNTAX = 0b100000;
     }
 }
 
@@ -59,6 +62,7 @@ impl SearchOptions {
             SearchOptions::CASE_SENSITIVE => "Match Case Sensitively",
             SearchOptions::INCLUDE_IGNORED => "Also search files ignored by configuration",
             SearchOptions::REGEX => "Use Regular Expressions",
+            SearchOptions::QUERY_SYNTAX => "Use Query Syntax",
             _ => panic!("{:?} is not a named SearchOption", self),
         }
     }
@@ -69,6 +73,7 @@ impl SearchOptions {
             SearchOptions::CASE_SENSITIVE => ui::IconName::CaseSensitive,
             SearchOptions::INCLUDE_IGNORED => ui::IconName::Sliders,
             SearchOptions::REGEX => ui::IconName::Regex,
+            SearchOptions::QUERY_SYNTAX => ui::IconName::SearchCode,
             _ => panic!("{:?} is not a named SearchOption", self),
         }
     }
@@ -79,6 +84,7 @@ impl SearchOptions {
             SearchOptions::CASE_SENSITIVE => Box::new(ToggleCaseSensitive),
             SearchOptions::INCLUDE_IGNORED => Box::new(ToggleIncludeIgnored),
             SearchOptions::REGEX => Box::new(ToggleRegex),
+            SearchOptions::QUERY_SYNTAX => Box::new(ToggleQuerySyntax),
             _ => panic!("{:?} is not a named SearchOption", self),
         }
     }
@@ -93,6 +99,7 @@ impl SearchOptions {
         options.set(SearchOptions::CASE_SENSITIVE, query.case_sensitive());
         options.set(SearchOptions::INCLUDE_IGNORED, query.include_ignored());
         options.set(SearchOptions::REGEX, query.is_regex());
+        options.set(SearchOptions::QUERY_SYNTAX, query.is_structured());
         options
     }
 
@@ -0,0 +1,795 @@
+//! Shell integration for bash, zsh and fish.
+//!
+//! Zed starts these shells with scripts that report OSC 133 prompt and command
+//! marks (`A` at the start of the prompt, `B` at its end, `C` when a command
+//! starts and `D;<exit status>` when it finishes) and OSC 7 working
+//! directories. Alacritty drops these sequences, so a [`MarkParser`] on the pty
+//! read path replaces them before alacritty sees the bytes: each prompt is
+//! wrapped in an OSC 8 hyperlink whose URI has the form
+//! `zed-shell:prompt;status=<exit status>;cwd=<working directory>`, carrying
+//! the exit status of the previous command and the working directory. These
+//! hyperlinks never leave Zed. The grid keeps them with the prompt's cells
+//! through scrolling, resizing and clearing, which is where the command blocks
+//! are read back from.
+
+use std::{
+    io::{self, Read as _, Write as _},
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::{Arc, LazyLock},
+};
+
+use alacritty_terminal::{
+    event::{OnResize, WindowSize},
+    grid::{Dimensions, Grid},
+    index::{Column, Line},
+    term::cell::{Cell, Flags, Hyperlink},
+    tty::{ChildEvent, EventedPty, EventedReadWrite, Pty},
+};
+use anyhow::{Context as _, Result};
+use collections::HashMap;
+use polling::{Event, PollMode, Poller};
+use util::ResultExt;
+
+const MARK_SCHEME: &str = "zed-shell:";
+
+const ESC: u8 = 0x1b;
+const BEL: u8 = 0x07;
+/// Longer OSC 133 and OSC 7 sequences are passed on to alacritty untouched.
+const MAX_MARK_LEN: usize = 4096;
+
+const BASH_SCRIPT: &str = include_str!("shell_integration/zed.bash");
+const ZSH_SCRIPT: &str = include_str!("shell_integration/zed.zsh");
+const FISH_SCRIPT: &str = include_str!("shell_integration/zed.fish");
+
+/// A prompt mark left in the grid by the [`MarkParser`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShellMark {
+    /// The exit status of the command that ran before the prompt.
+    pub exit_status: Option<i32>,
+    /// The working directory the prompt was shown in.
+    pub working_directory: Option<PathBuf>,
+}
+
+impl ShellMark {
+    /// Parses the URI of a prompt's hyperlink, such as
+    /// `zed-shell:prompt;status=0;cwd=/home/me`.
+    pub fn parse(uri: &str) -> Option<Self> {
+        let fields = uri.strip_prefix(MARK_SCHEME)?.strip_prefix("prompt")?;
+        // The working directory comes last and is not escaped, so it may
+        // contain semicolons of its own.
+        let (fields, working_directory) = match fields.split_once(";cwd=") {
+            Some((fields, working_directory)) => (fields, Some(working_directory)),
+            None => (fields, None),
+        };
+        let exit_status = fields
+            .split(';')
+            .find_map(|field| field.strip_prefix("status="))
+            .and_then(|status| status.parse().ok());
+        Some(Self {
+            exit_status,
+            working_directory: working_directory
+                .filter(|directory| !directory.is_empty())
+                .map(PathBuf::from),
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum ParserState {
+    #[default]
+    Ground,
+    Escape,
+    Osc,
+    OscEscape,
+}
+
+/// Replaces the OSC 133 marks and OSC 7 working directories in the output of a
+/// shell with the prompt hyperlinks that the grid keeps, passing everything
+/// else through unchanged.
+///
+/// The output is read in chunks, so a sequence may be split across calls to
+/// [`MarkParser::advance`].
+#[derive(Debug, Default)]
+pub struct MarkParser {
+    state: ParserState,
+    osc: Vec<u8>,
+    prompt_id: usize,
+    exit_status: Option<i32>,
+    working_directory: Option<String>,
+}
+
+impl MarkParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses the next chunk of output, appending what alacritty should see
+    /// to `output`.
+    pub fn advance(&mut self, mut bytes: &[u8], output: &mut Vec<u8>) {
+        while let Some(&byte) = bytes.first() {
+            match self.state {
+                ParserState::Ground => match bytes.iter().position(|byte| *byte == ESC) {
+                    Some(ix) => {
+                        output.extend_from_slice(&bytes[..ix]);
+                        self.state = ParserState::Escape;
+                        bytes = &bytes[ix + 1..];
+                    }
+                    None => {
+                        output.extend_from_slice(bytes);
+                        return;
+                    }
+                },
+                ParserState::Escape => {
+                    if byte == b']' {
+                        self.osc.clear();
+                        self.state = ParserState::Osc;
+                    } else {
+                        output.push(ESC);
+                        if byte != ESC {
+                            output.push(byte);
+                            self.state = ParserState::Ground;
+                        }
+                    }
+                    bytes = &bytes[1..];
+                }
+                ParserState::Osc => {
+                    match byte {
+                        BEL => self.finish_osc(&[BEL], output),
+                        ESC => self.state = ParserState::OscEscape,
+                        _ => {
+                            self.osc.push(byte);
+                            if !is_mark_prefix(&self.osc) || self.osc.len() > MAX_MARK_LEN {
+                                self.pass_through_osc(output);
+                            }
+                        }
+                    }
+                    bytes = &bytes[1..];
+                }
+                ParserState::OscEscape => {
+                    if byte == b'\\' {
+                        self.finish_osc(&[ESC, b'\\'], output);
+                        bytes = &bytes[1..];
+                    } else {
+                        // The escape aborts the sequence and starts another one.
+                        self.pass_through_osc(output);
+                        self.state = ParserState::Escape;
+                    }
+                }
+            }
+        }
+    }
+
+    fn pass_through_osc(&mut self, output: &mut Vec<u8>) {
+        output.extend_from_slice(&[ESC, b']']);
+        output.append(&mut self.osc);
+        self.state = ParserState::Ground;
+    }
+
+    fn finish_osc(&mut self, terminator: &[u8], output: &mut Vec<u8>) {
+        if !self.handle_mark(output) {
+            self.pass_through_osc(output);
+            output.extend_from_slice(terminator);
+        }
+        self.osc.clear();
+        self.state = ParserState::Ground;
+    }
+
+    /// Handles the OSC sequence that was just read, returning whether it was
+    /// a shell integration mark.
+    fn handle_mark(&mut self, output: &mut Vec<u8>) -> bool {
+        let Ok(osc) = std::str::from_utf8(&self.osc) else {
+            return false;
+        };
+        if let Some(mark) = osc.strip_prefix("133;") {
+            let mut params = mark.split(';');
+            match params.next() {
+                Some("A") => {
+                    // A prompt that is drawn again, e.g. after a resize, gets
+                    // a new id but keeps the exit status of the last command.
+                    self.prompt_id += 1;
+                    write!(
+                        output,
+                        "\x1b]8;id=zed-{};{MARK_SCHEME}prompt;status={};cwd={}\x07",
+                        self.prompt_id,
+                        self.exit_status
+                            .map(|status| status.to_string())
+                            .unwrap_or_default(),
+                        self.working_directory.as_deref().unwrap_or_default(),
+                    )
+                    .ok();
+                }
+                Some("B") => output.extend_from_slice(b"\x1b]8;;\x07"),
+                Some("C") => self.exit_status = None,
+                Some("D") => {
+                    self.exit_status = params.next().and_then(|status| status.parse().ok());
+                }
+                _ => {}
+            }
+            true
+        } else if let Some(url) = osc.strip_prefix("7;") {
+            self.working_directory = working_directory_from_url(url);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Whether an OSC sequence that has been read so far may still turn out to be
+/// an OSC 133 mark or an OSC 7 working directory.
+fn is_mark_prefix(osc: &[u8]) -> bool {
+    ["133;", "7;"].iter().any(|prefix| {
+        let prefix = prefix.as_bytes();
+        osc.starts_with(prefix) || prefix.starts_with(osc)
+    })
+}
+
+/// Returns the path of a `file://<host>/<path>` URL, decoding its
+/// percent-encoded bytes.
+fn working_directory_from_url(url: &str) -> Option<String> {
+    let url = url.strip_prefix("file://")?;
+    let path = &url[url.find('/')?..];
+    let mut bytes = Vec::with_capacity(path.len());
+    let mut rest = path.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        let decoded = tail
+            .get(..2)
+            .filter(|hex| byte == b'%' && hex.iter().all(u8::is_ascii_hexdigit))
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match decoded {
+            Some(decoded) => {
+                bytes.push(decoded);
+                rest = &tail[2..];
+            }
+            None => {
+                bytes.push(byte);
+                rest = tail;
+            }
+        }
+    }
+    Some(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// The pty of a terminal, which passes the shell's output through a
+/// [`MarkParser`] when the shell was started with its integration script.
+pub(crate) struct ShellIntegrationPty {
+    pty: Pty,
+    parser: Option<MarkParser>,
+    input: Vec<u8>,
+    output: Vec<u8>,
+}
+
+impl ShellIntegrationPty {
+    pub(crate) fn new(pty: Pty, shell_integration: bool) -> Self {
+        Self {
+            pty,
+            parser: shell_integration.then(MarkParser::new),
+            input: Vec::new(),
+            output: Vec::new(),
+        }
+    }
+}
+
+impl io::Read for ShellIntegrationPty {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let Some(parser) = self.parser.as_mut() else {
+            return self.pty.reader().read(buf);
+        };
+        // Alacritty keeps reading until the pty would block, so output that
+        // doesn't fit in `buf` is returned by the next read.
+        while self.output.is_empty() {
+            self.input.resize(buf.len().min(0x1_0000), 0);
+            let len = self.pty.reader().read(&mut self.input)?;
+            if len == 0 {
+                return Ok(0);
+            }
+            parser.advance(&self.input[..len], &mut self.output);
+        }
+        let len = buf.len().min(self.output.len());
+        buf[..len].copy_from_slice(&self.output[..len]);
+        self.output.drain(..len);
+        Ok(len)
+    }
+}
+
+impl EventedReadWrite for ShellIntegrationPty {
+    type Reader = Self;
+    type Writer = <Pty as EventedReadWrite>::Writer;
+
+    unsafe fn register(
+        &mut self,
+        poll: &Arc<Poller>,
+        interest: Event,
+        mode: PollMode,
+    ) -> io::Result<()> {
+        unsafe { self.pty.register(poll, interest, mode) }
+    }
+
+    fn reregister(
+        &mut self,
+        poll: &Arc<Poller>,
+        interest: Event,
+        mode: PollMode,
+    ) -> io::Result<()> {
+        self.pty.reregister(poll, interest, mode)
+    }
+
+    fn deregister(&mut self, poll: &Arc<Poller>) -> io::Result<()> {
+        self.pty.deregister(poll)
+    }
+
+    fn reader(&mut self) -> &mut Self::Reader {
+        self
+    }
+
+    fn writer(&mut self) -> &mut Self::Writer {
+        self.pty.writer()
+    }
+}
+
+impl EventedPty for ShellIntegrationPty {
+    fn next_child_event(&mut self) -> Option<ChildEvent> {
+        self.pty.next_child_event()
+    }
+}
+
+impl OnResize for ShellIntegrationPty {
+    fn on_resize(&mut self, window_size: WindowSize) {
+        self.pty.on_resize(window_size);
+    }
+}
+
+/// Whether a hyperlink's URI is a shell integration mark rather than a link
+/// printed by a program.
+pub fn is_shell_mark(uri: &str) -> bool {
+    uri.starts_with(MARK_SCHEME)
+}
+
+/// A command entered at a prompt, along with the lines it printed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommandBlock {
+    /// The first line of the prompt.
+    pub prompt_line: Line,
+    /// The command entered at the prompt, without the prompt itself.
+    pub command: String,
+    /// The lines between the command and the next prompt.
+    pub output_lines: Range<Line>,
+    /// Whether the shell has shown another prompt since.
+    pub finished: bool,
+    /// The exit status the shell reported for the command, once finished.
+    pub exit_status: Option<i32>,
+    /// The working directory the command was entered in.
+    pub working_directory: Option<PathBuf>,
+}
+
+/// A command that finished running, with its output.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommandOutput {
+    pub command: String,
+    pub output: String,
+    pub exit_status: Option<i32>,
+    pub working_directory: Option<PathBuf>,
+}
+
+/// The exit status mark shown next to a command's prompt.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CommandMark {
+    pub prompt_line: Line,
+    pub finished: bool,
+    pub exit_status: Option<i32>,
+}
+
+struct Prompt {
+    start: i32,
+    end: i32,
+    hyperlink: Hyperlink,
+    mark: ShellMark,
+}
+
+/// Finds the prompts that start within the given lines, merging the lines of
+/// multi-line prompts.
+fn prompts(grid: &Grid<Cell>, lines: Range<i32>) -> Vec<Prompt> {
+    let mut prompts: Vec<Prompt> = Vec::new();
+    for line in lines {
+        // Shells print their prompt at the start of a line, so only the first
+        // column needs to be checked.
+        let Some(hyperlink) = grid[Line(line)][Column(0)].hyperlink() else {
+            continue;
+        };
+        let Some(mark) = ShellMark::parse(hyperlink.uri()) else {
+            continue;
+        };
+        match prompts.last_mut() {
+            Some(prompt) if prompt.end + 1 == line && prompt.hyperlink == hyperlink => {
+                prompt.end = line;
+            }
+            _ => prompts.push(Prompt {
+                start: line,
+                end: line,
+                hyperlink,
+                mark,
+            }),
+        }
+    }
+    prompts
+}
+
+/// Returns the lines on which prompts start, from top to bottom.
+pub fn prompt_lines(grid: &Grid<Cell>) -> Vec<Line> {
+    prompts(grid, grid.topmost_line().0..grid.bottommost_line().0 + 1)
+        .into_iter()
+        .map(|prompt| Line(prompt.start))
+        .collect()
+}
+
+/// Returns the commands entered at the prompts in the grid, from top to bottom.
+pub fn command_blocks(grid: &Grid<Cell>) -> Vec<CommandBlock> {
+    let end = grid.bottommost_line().0 + 1;
+    command_blocks_in(grid, grid.topmost_line().0..end, end)
+}
+
+/// Returns the marks for the commands whose prompt starts within the visible
+/// lines of the grid.
+pub fn visible_command_marks(grid: &Grid<Cell>) -> Vec<CommandMark> {
+    let top = -(grid.display_offset() as i32);
+    let bottom = top + grid.screen_lines() as i32;
+    // The exit status of a command is reported by the next prompt, which may
+    // be below the visible lines.
+    command_blocks_in(grid, top..grid.bottommost_line().0 + 1, bottom)
+        .into_iter()
+        .filter(|block| !block.command.is_empty())
+        .map(|block| CommandMark {
+            prompt_line: block.prompt_line,
+            finished: block.finished,
+            exit_status: block.exit_status,
+        })
+        .collect()
+}
+
+fn command_blocks_in(grid: &Grid<Cell>, lines: Range<i32>, prompt_end: i32) -> Vec<CommandBlock> {
+    let end = lines.end;
+    let prompts = prompts(grid, lines);
+    let last_column = Column(grid.columns() - 1);
+    let mut blocks = Vec::new();
+    for (ix, prompt) in prompts.iter().enumerate() {
+        if prompt.start >= prompt_end {
+            break;
+        }
+        let next_prompt = prompts.get(ix + 1);
+        let block_end = next_prompt.map_or(end, |next_prompt| next_prompt.start);
+
+        // The command follows the prompt on its last line, continuing onto
+        // the lines it wrapped to.
+        let row = &grid[Line(prompt.end)];
+        let command_column = (0..grid.columns())
+            .rev()
+            .find(|column| row[Column(*column)].hyperlink().as_ref() == Some(&prompt.hyperlink))
+            .map_or(0, |column| column + 1);
+        let mut command_end = prompt.end + 1;
+        while command_end < block_end
+            && grid[Line(command_end - 1)][last_column]
+                .flags
+                .contains(Flags::WRAPLINE)
+        {
+            command_end += 1;
+        }
+
+        blocks.push(CommandBlock {
+            prompt_line: Line(prompt.start),
+            command: text_in_lines(
+                grid,
+                Line(prompt.end)..Line(command_end),
+                Column(command_column),
+            )
+            .trim()
+            .to_string(),
+            output_lines: Line(command_end)..Line(block_end),
+            finished: next_prompt.is_some(),
+            exit_status: next_prompt.and_then(|next_prompt| next_prompt.mark.exit_status),
+            working_directory: prompt.mark.working_directory.clone(),
+        });
+    }
+    blocks
+}
+
+/// Returns the text on the given lines, starting at the given column on the
+/// first one, and joining lines that were wrapped.
+pub fn text_in_lines(grid: &Grid<Cell>, lines: Range<Line>, start_column: Column) -> String {
+    let last_column = Column(grid.columns() - 1);
+    let mut text = String::new();
+    for line in lines.start.0..lines.end.0 {
+        let row = &grid[Line(line)];
+        let first_column = if line == lines.start.0 {
+            start_column.0
+        } else {
+            0
+        };
+        let line_text = (first_column..grid.columns())
+            .map(|column| &row[Column(column)])
+            .filter(|cell| !cell.flags.contains(Flags::WIDE_CHAR_SPACER))
+            .map(|cell| cell.c)
+            .collect::<String>();
+        if row[last_column].flags.contains(Flags::WRAPLINE) {
+            text.push_str(&line_text);
+        } else {
+            text.push_str(line_text.trim_end());
+            text.push('\n');
+        }
+    }
+    text.trim_end().to_string()
+}
+
+/// Returns the last `count` commands that finished running, oldest first.
+pub fn recent_commands(grid: &Grid<Cell>, count: usize) -> Vec<CommandOutput> {
+    let blocks = command_blocks(grid);
+    let mut commands = blocks
+        .into_iter()
+        .rev()
+        .filter(|block| block.finished && !block.command.is_empty())
+        .take(count)
+        .map(|block| CommandOutput {
+            output: text_in_lines(grid, block.output_lines.clone(), Column(0)),
+            command: block.command,
+            exit_status: block.exit_status,
+            working_directory: block.working_directory,
+        })
+        .collect::<Vec<_>>();
+    commands.reverse();
+    commands
+}
+
+static SCRIPTS_INSTALLED: LazyLock<bool> = LazyLock::new(|| {
+    install_scripts(paths::shell_integration_dir())
+        .log_err()
+        .is_some()
+});
+
+fn install_scripts(dir: &Path) -> Result<()> {
+    for (path, script) in [
+        (bash_script_path(dir), BASH_SCRIPT),
+        (zsh_dotdir(dir).join(".zshenv"), ZSH_SCRIPT),
+        (
+            fish_data_dir(dir).join("fish/vendor_conf.d/zed.fish"),
+            FISH_SCRIPT,
+        ),
+    ] {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("creating directory {parent:?}"))?;
+        }
+        std::fs::write(&path, script).with_context(|| format!("writing {path:?}"))?;
+    }
+    Ok(())
+}
+
+fn bash_script_path(dir: &Path) -> PathBuf {
+    dir.join("zed.bash")
+}
+
+fn zsh_dotdir(dir: &Path) -> PathBuf {
+    dir.join("zsh")
+}
+
+fn fish_data_dir(dir: &Path) -> PathBuf {
+    dir.join("fish_data")
+}
+
+/// Sets up the environment for the given shell program to load its
+/// integration script, returning the arguments to start the shell with, or
+/// `None` if the shell isn't supported.
+///
+/// `login` starts the shell as a login shell, so that it still loads the
+/// login files it would have loaded without the integration.
+pub(crate) fn inject(
+    program: &str,
+    login: bool,
+    env: &mut HashMap<String, String>,
+) -> Option<Vec<String>> {
+    let name = Path::new(program)
+        .file_name()?
+        .to_str()?
+        .trim_start_matches('-');
+    if !matches!(name, "bash" | "zsh" | "fish") || !*SCRIPTS_INSTALLED {
+        return None;
+    }
+    let dir = paths::shell_integration_dir();
+    let inherited = |env: &HashMap<String, String>, key: &str| {
+        env.get(key).cloned().or_else(|| std::env::var(key).ok())
+    };
+
+    match name {
+        "bash" => {
+            // Login shells ignore `--init-file`, so the script loads the
+            // login files itself instead.
+            if login {
+                env.insert("ZED_SHELL_LOGIN".to_string(), "1".to_string());
+            }
+            Some(vec![
+                "--init-file".to_string(),
+                bash_script_path(dir).to_string_lossy().to_string(),
+            ])
+        }
+        "zsh" => {
+            if let Some(zdotdir) = inherited(env, "ZDOTDIR") {
+                env.insert("ZED_ORIGINAL_ZDOTDIR".to_string(), zdotdir);
+            }
+            env.insert(
+                "ZDOTDIR".to_string(),
+                zsh_dotdir(dir).to_string_lossy().to_string(),
+            );
+            // The script restores `ZDOTDIR` before zsh reads `.zprofile` and
+            // `.zlogin`, so a login shell still finds the user's files.
+            Some(if login {
+                vec!["-l".to_string()]
+            } else {
+                Vec::new()
+            })
+        }
+        _ => {
+            let data_dirs = match inherited(env, "XDG_DATA_DIRS") {
+                Some(data_dirs) => {
+                    env.insert("ZED_ORIGINAL_XDG_DATA_DIRS".to_string(), data_dirs.clone());
+                    data_dirs
+                }
+                None => "/usr/local/share:/usr/share".to_string(),
+            };
+            env.insert(
+                "XDG_DATA_DIRS".to_string(),
+                format!("{}:{data_dirs}", fish_data_dir(dir).to_string_lossy()),
+            );
+            Some(if login {
+                vec!["--login".to_string()]
+            } else {
+                Vec::new()
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(lines: &[&str]) -> Grid<Cell> {
+        let columns = 20;
+        let mut grid = Grid::<Cell>::new(lines.len(), columns, 0);
+        let mut hyperlink = None;
+        for (line, text) in lines.iter().enumerate() {
+            let mut column = 0;
+            let mut chars = text.chars();
+            while let Some(c) = chars.next() {
+                // `[n:uri]` starts a hyperlink with the id `n`, `[]` ends it,
+                // and a trailing `\` marks a wrapped line.
+                if c == '[' {
+                    let rest = chars.as_str();
+                    let end = rest.find(']').unwrap();
+                    hyperlink = rest[..end]
+                        .split_once(':')
+                        .map(|(id, uri)| Hyperlink::new(Some(id), uri));
+                    chars = rest[end + 1..].chars();
+                    continue;
+                }
+                if c == '\\' && chars.as_str().is_empty() {
+                    grid[Line(line as i32)][Column(columns - 1)]
+                        .flags
+                        .insert(Flags::WRAPLINE);
+                    break;
+                }
+                let cell = &mut grid[Line(line as i32)][Column(column)];
+                cell.c = c;
+                cell.set_hyperlink(hyperlink.clone());
+                column += 1;
+            }
+        }
+        grid
+    }
+
+    #[test]
+    fn test_parse_shell_mark() {
+        assert_eq!(
+            ShellMark::parse("zed-shell:prompt;status=1;cwd=/tmp/a;b"),
+            Some(ShellMark {
+                exit_status: Some(1),
+                working_directory: Some(PathBuf::from("/tmp/a;b")),
+            })
+        );
+        assert_eq!(
+            ShellMark::parse("zed-shell:prompt;status=;cwd="),
+            Some(ShellMark {
+                exit_status: None,
+                working_directory: None,
+            })
+        );
+        assert_eq!(ShellMark::parse("https://zed.dev"), None);
+        assert!(is_shell_mark("zed-shell:prompt"));
+        assert!(!is_shell_mark("file:///tmp"));
+    }
+
+    #[test]
+    fn test_mark_parser() {
+        let input = concat!(
+            "\x1b]7;file://host/tmp/a%20b;c\x07",
+            "\x1b]133;A\x07$ \x1b]133;B\x07ls\r\n",
+            "\x1b]133;C\x07one\r\n\x1b]133;D;2\x1b\\",
+            "\x1b]133;A\x07$ \x1b]133;B\x07",
+            "\x1b]0;title\x07\x1b[1mbold\x1b]13",
+        );
+        let expected = concat!(
+            "\x1b]8;id=zed-1;zed-shell:prompt;status=;cwd=/tmp/a b;c\x07$ \x1b]8;;\x07ls\r\n",
+            "one\r\n",
+            "\x1b]8;id=zed-2;zed-shell:prompt;status=2;cwd=/tmp/a b;c\x07$ \x1b]8;;\x07",
+            "\x1b]0;title\x07\x1b[1mbold",
+        );
+
+        // The output is the same however it is split into chunks.
+        for chunk_len in [1, 2, 7, input.len()] {
+            let mut parser = MarkParser::new();
+            let mut output = Vec::new();
+            for chunk in input.as_bytes().chunks(chunk_len) {
+                parser.advance(chunk, &mut output);
+            }
+            assert_eq!(String::from_utf8(output).unwrap(), expected);
+        }
+
+        let mut parser = MarkParser::new();
+        let mut output = Vec::new();
+        parser.advance(b"\x1b]7;\x07\x1b]1337;x\x07\x1b\x1b[m", &mut output);
+        assert_eq!(output, b"\x1b]1337;x\x07\x1b\x1b[m");
+    }
+
+    #[test]
+    fn test_command_blocks() {
+        let grid = grid(&[
+            "[1:zed-shell:prompt;status=0;cwd=/a]$ []ls",
+            "one",
+            "two",
+            "[2:zed-shell:prompt;status=0;cwd=/a]~/a[]",
+            "[2:zed-shell:prompt;status=0;cwd=/a]$ []echo aaaaaaaaaaaaa\\",
+            "aa",
+            "[3:zed-shell:prompt;status=2;cwd=/b]$ []",
+        ]);
+
+        assert_eq!(prompt_lines(&grid), vec![Line(0), Line(3), Line(6)]);
+        assert_eq!(
+            command_blocks(&grid),
+            vec![
+                CommandBlock {
+                    prompt_line: Line(0),
+                    command: "ls".to_string(),
+                    output_lines: Line(1)..Line(3),
+                    finished: true,
+                    exit_status: Some(0),
+                    working_directory: Some(PathBuf::from("/a")),
+                },
+                CommandBlock {
+                    prompt_line: Line(3),
+                    command: "echo aaaaaaaaaaaaaaa".to_string(),
+                    output_lines: Line(6)..Line(6),
+                    finished: true,
+                    exit_status: Some(2),
+                    working_directory: Some(PathBuf::from("/a")),
+                },
+                CommandBlock {
+                    prompt_line: Line(6),
+                    command: String::new(),
+                    output_lines: Line(7)..Line(7),
+                    finished: false,
+                    exit_status: None,
+                    working_directory: Some(PathBuf::from("/b")),
+                },
+            ]
+        );
+        assert_eq!(
+            recent_commands(&grid, 1),
+            vec![CommandOutput {
+                command: "echo aaaaaaaaaaaaaaa".to_string(),
+                output: String::new(),
+                exit_status: Some(2),
+                working_directory: Some(PathBuf::from("/a")),
+            }]
+        );
+        assert_eq!(recent_commands(&grid, 5)[0].output, "one\ntwo".to_string());
+    }
+}
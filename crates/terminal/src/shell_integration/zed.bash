@@ -0,0 +1,81 @@
+# Zed shell integration for bash.
+#
+# Started with `bash --init-file`, so this loads the user's startup files
+# first. It then reports OSC 133 prompt and command marks and OSC 7 working
+# directories, which Zed reads to find the commands in the terminal's
+# scrollback.
+
+if [ -n "$ZED_SHELL_LOGIN" ]; then
+    unset ZED_SHELL_LOGIN
+    [ -r /etc/profile ] && . /etc/profile
+    if [ -r ~/.bash_profile ]; then
+        . ~/.bash_profile
+    elif [ -r ~/.bash_login ]; then
+        . ~/.bash_login
+    elif [ -r ~/.profile ]; then
+        . ~/.profile
+    fi
+elif [ -r ~/.bashrc ]; then
+    . ~/.bashrc
+fi
+
+if [[ -z "$__zed_shell_integration" && "$TERM_PROGRAM" == "zed" ]]; then
+    __zed_shell_integration=1
+    __zed_at_prompt=0
+    __zed_in_command=0
+    __zed_exit_status=0
+
+    # Percent-encodes the working directory for OSC 7 into `__zed_url_path`.
+    __zed_encode_pwd() {
+        local LC_ALL=C char hex i
+        __zed_url_path=
+        for ((i = 0; i < ${#PWD}; i++)); do
+            char=${PWD:i:1}
+            case "$char" in
+            [-/._~A-Za-z0-9]) __zed_url_path+=$char ;;
+            *)
+                printf -v hex '%%%02X' "'$char"
+                __zed_url_path+=$hex
+                ;;
+            esac
+        done
+    }
+
+    # Runs first in PROMPT_COMMAND, to see the exit status of the command.
+    __zed_precmd() {
+        __zed_exit_status=$?
+        if [[ "$__zed_in_command" == 1 ]]; then
+            printf '\e]133;D;%s\a' "$__zed_exit_status"
+            __zed_in_command=0
+        fi
+        __zed_encode_pwd
+        printf '\e]7;file://%s%s\a' "$HOSTNAME" "$__zed_url_path"
+        return $__zed_exit_status
+    }
+
+    # Runs last in PROMPT_COMMAND, so that the prompt is marked after the
+    # user's hooks have set it, and so that the DEBUG trap only reports the
+    # commands the user enters at the prompt.
+    __zed_prompt_ready() {
+        if [[ "$PS1" != *'\[\e]133;A\a\]'* ]]; then
+            PS1='\[\e]133;A\a\]'"$PS1"'\[\e]133;B\a\]'
+        fi
+        __zed_at_prompt=1
+        return $__zed_exit_status
+    }
+
+    __zed_preexec() {
+        if [[ "$__zed_at_prompt" == 1 ]]; then
+            __zed_at_prompt=0
+            __zed_in_command=1
+            printf '\e]133;C\a'
+        fi
+    }
+
+    PROMPT_COMMAND="__zed_precmd${PROMPT_COMMAND:+$'\n'$PROMPT_COMMAND}"$'\n'"__zed_prompt_ready"
+    # Don't replace a DEBUG trap the user already has; without it, commands
+    # are still found by their prompts, but finish without an exit status.
+    if [[ -z "$(trap -p DEBUG)" ]]; then
+        trap '__zed_preexec' DEBUG
+    fi
+fi
@@ -0,0 +1,50 @@
+# Zed shell integration for fish.
+#
+# Loaded from a `vendor_conf.d` directory that Zed adds to `XDG_DATA_DIRS`.
+# It restores the user's `XDG_DATA_DIRS`, and then reports OSC 133 prompt and
+# command marks and OSC 7 working directories, which Zed reads to find the
+# commands in the terminal's scrollback.
+
+if set -q ZED_ORIGINAL_XDG_DATA_DIRS
+    set -gx XDG_DATA_DIRS $ZED_ORIGINAL_XDG_DATA_DIRS
+    set -e ZED_ORIGINAL_XDG_DATA_DIRS
+else
+    set -e XDG_DATA_DIRS
+end
+
+if status is-interactive; and not set -q __zed_shell_integration; and test "$TERM_PROGRAM" = zed
+    set -g __zed_shell_integration 1
+
+    function __zed_preexec --on-event fish_preexec
+        printf '\e]133;C\a'
+    end
+
+    function __zed_postexec --on-event fish_postexec
+        printf '\e]133;D;%s\a' $status
+    end
+
+    function __zed_set_status
+        return $argv[1]
+    end
+
+    # The user's prompt is defined after this file is loaded, so it is wrapped
+    # right before the first prompt is shown.
+    function __zed_wrap_prompt --on-event fish_prompt
+        functions -e __zed_wrap_prompt
+        if functions -q fish_prompt
+            functions -c fish_prompt __zed_original_fish_prompt
+        else
+            function __zed_original_fish_prompt
+            end
+        end
+
+        function fish_prompt
+            set -l exit_status $status
+            printf '\e]7;file://%s%s\a' $hostname (string escape --style=url -- $PWD)
+            printf '\e]133;A\a'
+            __zed_set_status $exit_status
+            __zed_original_fish_prompt
+            printf '\e]133;B\a'
+        end
+    end
+end
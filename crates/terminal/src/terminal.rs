@@ -3,6 +3,7 @@ pub mod mappings;
 pub use alacritty_terminal;
 
 mod pty_info;
+pub mod shell_integration;
 pub mod terminal_settings;
 
 use alacritty_terminal::{
@@ -42,6 +43,7 @@ use pty_info::PtyProcessInfo;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use settings::Settings;
+use shell_integration::{CommandBlock, CommandMark, CommandOutput, ShellIntegrationPty};
 use smol::channel::{Receiver, Sender};
 use task::{HideStrategy, Shell, TaskId};
 use terminal_settings::{AlternateScroll, CursorShape, TerminalSettings};
@@ -80,6 +82,10 @@ actions!(
         ScrollPageDown,
         ScrollToTop,
         ScrollToBottom,
+        ScrollToPreviousPrompt,
+        ScrollToNextPrompt,
+        CopyLastCommandOutput,
+        RerunLastCommand,
         ToggleViMode,
     ]
 );
@@ -138,6 +144,8 @@ enum InternalEvent {
     // FocusNextMatch,
     Scroll(AlacScroll),
     ScrollToAlacPoint(AlacPoint),
+    ScrollToPreviousPrompt,
+    ScrollToNextPrompt,
     SetSelection(Option<(Selection, AlacPoint)>),
     UpdateSelection(Point<Pixels>),
     // Adjusted mouse position, should open
@@ -349,6 +357,7 @@ impl TerminalBuilder {
         cursor_shape: CursorShape,
         alternate_scroll: AlternateScroll,
         max_scroll_history_lines: Option<usize>,
+        shell_integration: bool,
         is_ssh_terminal: bool,
         window: AnyWindowHandle,
         completion_tx: Sender<()>,
@@ -374,6 +383,10 @@ impl TerminalBuilder {
 
         let mut terminal_title_override = None;
 
+        // Tasks and remote shells run without the integration scripts, as do
+        // shells started with arguments of their own.
+        let shell_integration = shell_integration && task.is_none() && !is_ssh_terminal;
+
         let pty_options = {
             let alac_shell = match shell.clone() {
                 Shell::System => {
@@ -386,11 +399,25 @@ impl TerminalBuilder {
                     }
                     #[cfg(not(target_os = "windows"))]
                     {
-                        None
+                        // Alacritty starts the system shell as a login shell on
+                        // macOS, so keep doing that when starting it ourselves.
+                        let login = cfg!(target_os = "macos");
+                        std::env::var("SHELL")
+                            .ok()
+                            .filter(|_| shell_integration)
+                            .and_then(|program| {
+                                let args = shell_integration::inject(&program, login, &mut env)?;
+                                Some(alacritty_terminal::tty::Shell::new(program, args))
+                            })
                     }
                 }
                 Shell::Program(program) => {
-                    Some(alacritty_terminal::tty::Shell::new(program, Vec::new()))
+                    let args = if shell_integration && cfg!(not(target_os = "windows")) {
+                        shell_integration::inject(&program, false, &mut env).unwrap_or_default()
+                    } else {
+                        Vec::new()
+                    };
+                    Some(alacritty_terminal::tty::Shell::new(program, args))
                 }
                 Shell::WithArguments {
                     program,
@@ -471,7 +498,7 @@ impl TerminalBuilder {
         let event_loop = EventLoop::new(
             term.clone(),
             ZedListener(events_tx.clone()),
-            pty,
+            ShellIntegrationPty::new(pty, shell_integration),
             pty_options.drain_on_exit,
             false,
         )?;
@@ -603,6 +630,7 @@ pub struct TerminalContent {
     pub cursor_char: char,
     pub terminal_bounds: TerminalBounds,
     pub last_hovered_word: Option<HoveredWord>,
+    pub command_marks: Vec<CommandMark>,
 }
 
 #[derive(Clone)]
@@ -627,6 +655,7 @@ impl Default for TerminalContent {
             cursor_char: Default::default(),
             terminal_bounds: Default::default(),
             last_hovered_word: None,
+            command_marks: Vec::new(),
         }
     }
 }
@@ -908,6 +937,26 @@ impl Terminal {
                 term.scroll_to_point(*point);
                 self.refresh_hovered_word(window);
             }
+            InternalEvent::ScrollToPreviousPrompt | InternalEvent::ScrollToNextPrompt => {
+                let display_offset = term.grid().display_offset() as i32;
+                let top = Line(-display_offset);
+                let prompt_lines = if term.mode().contains(TermMode::ALT_SCREEN) {
+                    Vec::new()
+                } else {
+                    shell_integration::prompt_lines(term.grid())
+                };
+                let target = if matches!(event, InternalEvent::ScrollToPreviousPrompt) {
+                    prompt_lines.into_iter().rev().find(|line| *line < top)
+                } else {
+                    prompt_lines.into_iter().find(|line| *line > top)
+                };
+                if let Some(target) = target {
+                    // Show the prompt at the top, unless it's already on the last page.
+                    let target_offset = cmp::max(-target.0, 0);
+                    term.scroll_display(AlacScroll::Delta(target_offset - display_offset));
+                    self.refresh_hovered_word(window);
+                }
+            }
             InternalEvent::ToggleViMode => {
                 self.vi_mode_enabled = !self.vi_mode_enabled;
                 term.toggle_vi_mode();
@@ -925,7 +974,11 @@ impl Terminal {
                 )
                 .grid_clamp(term, Boundary::Grid);
 
-                let link = term.grid().index(point).hyperlink();
+                let link = term
+                    .grid()
+                    .index(point)
+                    .hyperlink()
+                    .filter(|link| !shell_integration::is_shell_mark(link.uri()));
                 let found_word = if link.is_some() {
                     let mut min_index = point;
                     loop {
@@ -1211,6 +1264,57 @@ impl Terminal {
             .push_back(InternalEvent::Scroll(AlacScroll::Bottom));
     }
 
+    pub fn scroll_to_previous_prompt(&mut self) {
+        self.events.push_back(InternalEvent::ScrollToPreviousPrompt);
+    }
+
+    pub fn scroll_to_next_prompt(&mut self) {
+        self.events.push_back(InternalEvent::ScrollToNextPrompt);
+    }
+
+    /// Returns the commands entered at the prompts marked by the shell
+    /// integration, from top to bottom.
+    pub fn command_blocks(&self) -> Vec<CommandBlock> {
+        let term = self.term.clone();
+        let terminal = term.lock_unfair();
+        if terminal.mode().contains(TermMode::ALT_SCREEN) {
+            return Vec::new();
+        }
+        shell_integration::command_blocks(terminal.grid())
+    }
+
+    /// Returns up to `count` of the last commands that finished running, with
+    /// their output, oldest first.
+    pub fn recent_commands(&self, count: usize) -> Vec<CommandOutput> {
+        let term = self.term.clone();
+        let terminal = term.lock_unfair();
+        if terminal.mode().contains(TermMode::ALT_SCREEN) {
+            return Vec::new();
+        }
+        shell_integration::recent_commands(terminal.grid(), count)
+    }
+
+    /// Runs the last finished command again, if the shell is waiting at an
+    /// empty prompt.
+    pub fn rerun_last_command(&mut self) -> bool {
+        let blocks = self.command_blocks();
+        let Some((prompt, finished_blocks)) = blocks.split_last() else {
+            return false;
+        };
+        if prompt.finished || !prompt.command.is_empty() {
+            return false;
+        }
+        let Some(block) = finished_blocks
+            .iter()
+            .rev()
+            .find(|block| !block.command.is_empty())
+        else {
+            return false;
+        };
+        self.input(format!("{}\r", block.command));
+        true
+    }
+
     ///Resize the terminal and the PTY.
     pub fn set_size(&mut self, new_bounds: TerminalBounds) {
         if self.last_content.terminal_bounds != new_bounds {
@@ -1420,6 +1524,11 @@ impl Terminal {
             cursor_char: term.grid()[content.cursor.point].c,
             terminal_bounds: last_content.terminal_bounds,
             last_hovered_word: last_content.last_hovered_word.clone(),
+            command_marks: if content.mode.contains(TermMode::ALT_SCREEN) {
+                Vec::new()
+            } else {
+                shell_integration::visible_command_marks(term.grid())
+            },
         }
     }
 
@@ -1675,7 +1784,10 @@ impl Terminal {
             if self.selection_phase == SelectionPhase::Ended {
                 let mouse_cell_index =
                     content_index_for_mouse(position, &self.last_content.terminal_bounds);
-                if let Some(link) = self.last_content.cells[mouse_cell_index].hyperlink() {
+                if let Some(link) = self.last_content.cells[mouse_cell_index]
+                    .hyperlink()
+                    .filter(|link| !shell_integration::is_shell_mark(link.uri()))
+                {
                     cx.open_url(link.uri());
                 } else if e.modifiers.secondary() {
                     self.events
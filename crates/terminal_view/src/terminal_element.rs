@@ -22,6 +22,7 @@ use terminal::{
             CursorShape as AlacCursorShape, NamedColor,
         },
     },
+    shell_integration,
     terminal_settings::TerminalSettings,
 };
 use theme::{ActiveTheme, Theme, ThemeSettings};
@@ -47,6 +48,9 @@ pub struct LayoutState {
     hyperlink_tooltip: Option<AnyElement>,
     gutter: Pixels,
     block_below_cursor_element: Option<AnyElement>,
+    /// The display lines of the prompts of commands, with the color of their
+    /// exit status mark.
+    command_marks: Vec<(i32, Hsla)>,
 }
 
 /// Helper struct for converting data between Alacritty's cursor points, and displayed cursor points.
@@ -353,7 +357,10 @@ impl TerminalElement {
         }
 
         let underline = (flags.intersects(Flags::ALL_UNDERLINES)
-            || indexed.cell.hyperlink().is_some())
+            || indexed
+                .cell
+                .hyperlink()
+                .is_some_and(|link| !shell_integration::is_shell_mark(link.uri())))
         .then(|| UnderlineStyle {
             color: Some(fg),
             thickness: Pixels::from(1.0),
@@ -728,11 +735,25 @@ impl Element for TerminalElement {
                     cursor_char,
                     selection,
                     cursor,
+                    command_marks,
                     ..
                 } = &self.terminal.read(cx).last_content;
                 let mode = *mode;
                 let display_offset = *display_offset;
 
+                let command_marks = command_marks
+                    .iter()
+                    .filter_map(|mark| {
+                        let color = match (mark.finished, mark.exit_status) {
+                            (false, _) => theme.status().info,
+                            (true, Some(0)) => theme.status().success,
+                            (true, Some(_)) => theme.status().error,
+                            (true, None) => return None,
+                        };
+                        Some((mark.prompt_line.0 + display_offset as i32, color))
+                    })
+                    .collect();
+
                 // searches, highlights to a single range representations
                 let mut relative_highlighted_ranges = Vec::new();
                 for search_match in search_matches {
@@ -852,6 +873,7 @@ impl Element for TerminalElement {
                     hyperlink_tooltip,
                     gutter,
                     block_below_cursor_element,
+                    command_marks,
                 }
             },
         )
@@ -938,6 +960,16 @@ impl Element for TerminalElement {
                         }
                     }
 
+                    let mark_width = layout.gutter / 4.;
+                    for (line, color) in &layout.command_marks {
+                        let mark_origin = point(
+                            bounds.origin.x + (layout.gutter - mark_width) / 2.,
+                            origin.y + *line as f32 * layout.dimensions.line_height,
+                        );
+                        let mark_size = size(mark_width, layout.dimensions.line_height);
+                        window.paint_quad(fill(Bounds::new(mark_origin, mark_size), *color));
+                    }
+
                     for cell in &layout.cells {
                         cell.paint(origin, &layout.dimensions, bounds, window, cx);
                     }
@@ -0,0 +1,172 @@
+use async_recursion::async_recursion;
+use collections::HashSet;
+use fs::Fs;
+use std::path::{Path, PathBuf};
+
+use crate::{
+    NoteResolver,
+    frontmatter::body_offset,
+    links::{LinkSubpath, WikiLink, wiki_links},
+    resolver::is_note,
+    sections::{block_range, heading_section},
+};
+
+/// How deep `![[embeds]]` inside embedded notes are followed.
+const MAX_EMBED_DEPTH: usize = 8;
+
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "bmp", "svg", "webp", "avif"];
+
+pub fn is_image(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            IMAGE_EXTENSIONS
+                .iter()
+                .any(|image| extension.eq_ignore_ascii_case(image))
+        })
+}
+
+/// Replaces the `![[embeds]]` in a note with the content they refer to, so the
+/// result can be handed to a plain markdown renderer.
+///
+/// Embedded notes are inlined (without their frontmatter, and narrowed to the
+/// linked heading or block), image embeds become regular markdown images with
+/// absolute paths, and other files become links. Unresolved embeds and cycles
+/// are left as they are.
+pub async fn expand_embeds(
+    text: &str,
+    note_path: &Path,
+    root: &Path,
+    resolver: &NoteResolver,
+    fs: &dyn Fs,
+) -> String {
+    let mut visited = HashSet::default();
+    visited.insert(note_path.to_path_buf());
+    expand_embeds_recursive(text, note_path, root, resolver, fs, &mut visited, 0).await
+}
+
+#[async_recursion]
+async fn expand_embeds_recursive(
+    text: &str,
+    note_path: &Path,
+    root: &Path,
+    resolver: &NoteResolver,
+    fs: &dyn Fs,
+    visited: &mut HashSet<PathBuf>,
+    depth: usize,
+) -> String {
+    let mut expanded = String::with_capacity(text.len());
+    let mut last_end = 0;
+    for link in wiki_links(text).into_iter().filter(|link| link.embed) {
+        expanded.push_str(&text[last_end..link.range.start]);
+        last_end = link.range.end;
+
+        let Some(path) = resolver.resolve(&link.target, Some(note_path)) else {
+            expanded.push_str(&text[link.range.clone()]);
+            continue;
+        };
+        let abs_path = root.join(&path);
+
+        if is_image(&path) {
+            let alt = link
+                .alias
+                .as_ref()
+                .filter(|alias| !alias.chars().all(|c| c.is_ascii_digit() || c == 'x'))
+                .cloned()
+                .unwrap_or_default();
+            expanded.push_str(&format!("![{}](<{}>)", alt, abs_path.display()));
+        } else if is_note(&path) {
+            if depth >= MAX_EMBED_DEPTH || !visited.insert(path.to_path_buf()) {
+                expanded.push_str(&text[link.range.clone()]);
+                continue;
+            }
+            match fs.load(&abs_path).await {
+                Ok(content) => {
+                    let content = embedded_content(&content, &link);
+                    let content = expand_embeds_recursive(
+                        &content,
+                        &path,
+                        root,
+                        resolver,
+                        fs,
+                        visited,
+                        depth + 1,
+                    )
+                    .await;
+                    expanded.push_str(content.trim_end());
+                }
+                Err(error) => {
+                    log::warn!("failed to load embedded note {abs_path:?}: {error}");
+                    expanded.push_str(&text[link.range.clone()]);
+                }
+            }
+            visited.remove(path.as_ref());
+        } else {
+            expanded.push_str(&format!(
+                "[{}](<{}>)",
+                link.display_text(),
+                abs_path.display()
+            ));
+        }
+    }
+    expanded.push_str(&text[last_end..]);
+    expanded
+}
+
+/// The part of an embedded note that `link` refers to.
+fn embedded_content(content: &str, link: &WikiLink) -> String {
+    let body = &content[body_offset(content)..];
+    let range = match &link.subpath {
+        Some(LinkSubpath::Heading(heading)) => heading_section(body, heading),
+        Some(LinkSubpath::Block(block_id)) => block_range(body, block_id),
+        None => None,
+    };
+    match range {
+        Some(range) => body[range].to_string(),
+        None => body.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use serde_json::json;
+    use std::sync::Arc;
+    use util::path;
+
+    #[gpui::test]
+    async fn test_expand_embeds(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/vault"),
+            json!({
+                "Main.md": "# Main\n![[Snippets#Install]]\n![[logo.png|Logo]]\n![[Main]]\n![[Missing]]\n",
+                "Snippets.md": "---\ntags: [code]\n---\n## Install\nRun it.\n![[Deep]]\n## Other\nSkipped.\n",
+                "Deep.md": "Deepest ^b1\n",
+                "assets": { "logo.png": "" },
+            }),
+        )
+        .await;
+
+        let resolver = NoteResolver::new(
+            ["Main.md", "Snippets.md", "Deep.md", "assets/logo.png"]
+                .into_iter()
+                .map(|path| Arc::from(Path::new(path))),
+        );
+        let root = Path::new(path!("/vault"));
+        let text = fs.load(&root.join("Main.md")).await.unwrap();
+        let expanded =
+            expand_embeds(&text, Path::new("Main.md"), root, &resolver, fs.as_ref()).await;
+
+        let logo = root.join("assets").join("logo.png");
+        assert_eq!(
+            expanded,
+            format!(
+                "# Main\n## Install\nRun it.\nDeepest ^b1\n![Logo](<{}>)\n![[Main]]\n![[Missing]]\n",
+                logo.display()
+            )
+        );
+    }
+}
@@ -0,0 +1,333 @@
+use std::{borrow::Cow, ops::Range};
+
+/// The YAML properties block at the top of a note.
+///
+/// Only the subset of YAML that note-taking tools actually write is supported:
+/// `key: value` scalars, inline `[a, b]` lists and block `- item` lists.
+/// Nested mappings are skipped rather than rejected, so a note with exotic
+/// frontmatter still yields every property we understand.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Frontmatter {
+    /// The byte range of the whole block, including both `---` delimiters and
+    /// the trailing newline.
+    pub range: Range<usize>,
+    properties: Vec<(String, PropertyValue)>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PropertyValue {
+    Text(String),
+    List(Vec<String>),
+}
+
+impl PropertyValue {
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            PropertyValue::Text(text) => Some(text),
+            PropertyValue::List(_) => None,
+        }
+    }
+
+    /// Returns the value as a list, splitting comma separated scalars the same
+    /// way `tags: a, b` is interpreted by most note-taking tools.
+    pub fn to_list(&self) -> Vec<String> {
+        match self {
+            PropertyValue::Text(text) => text
+                .split(',')
+                .map(|item| item.trim().to_string())
+                .filter(|item| !item.is_empty())
+                .collect(),
+            PropertyValue::List(items) => items.clone(),
+        }
+    }
+
+    /// A flat, human readable rendering of the value.
+    pub fn display(&self) -> String {
+        match self {
+            PropertyValue::Text(text) => text.clone(),
+            PropertyValue::List(items) => items.join(", "),
+        }
+    }
+}
+
+impl Frontmatter {
+    /// Parses the frontmatter at the start of `text`, if there is one.
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut lines = LineIter::new(text);
+        let (first, _) = lines.next()?;
+        if first.trim_end() != "---" {
+            return None;
+        }
+
+        let mut properties = Vec::new();
+        let mut current_list: Option<(String, Vec<String>)> = None;
+        for (line, line_range) in lines {
+            let trimmed = line.trim_end();
+            if trimmed == "---" || trimmed == "..." {
+                if let Some((key, items)) = current_list.take() {
+                    properties.push((key, PropertyValue::List(items)));
+                }
+                return Some(Self {
+                    range: 0..line_range.end,
+                    properties,
+                });
+            }
+
+            if trimmed.trim().is_empty() || trimmed.trim_start().starts_with('#') {
+                continue;
+            }
+
+            if line.starts_with(' ') || line.starts_with('\t') || line.starts_with('-') {
+                let item = trimmed.trim_start();
+                if let Some(item) = item.strip_prefix('-') {
+                    if let Some((_, items)) = current_list.as_mut() {
+                        items.push(unquote(item.trim()).to_string());
+                    }
+                }
+                continue;
+            }
+
+            if let Some((key, items)) = current_list.take() {
+                properties.push((key, PropertyValue::List(items)));
+            }
+
+            let Some((key, value)) = trimmed.split_once(':') else {
+                continue;
+            };
+            let key = unquote(key.trim()).to_string();
+            let value = value.trim();
+            if value.is_empty() {
+                current_list = Some((key, Vec::new()));
+            } else if let Some(inner) = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+                let items = inner
+                    .split(',')
+                    .map(|item| unquote(item.trim()).to_string())
+                    .filter(|item| !item.is_empty())
+                    .collect();
+                properties.push((key, PropertyValue::List(items)));
+            } else {
+                properties.push((key, PropertyValue::Text(unquote(value).to_string())));
+            }
+        }
+
+        None
+    }
+
+    /// Creates a frontmatter block from scratch, e.g. for a newly created note.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&PropertyValue> {
+        self.properties
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, value)| value)
+    }
+
+    pub fn get_text(&self, key: &str) -> Option<&str> {
+        self.get(key).and_then(PropertyValue::as_text)
+    }
+
+    pub fn properties(&self) -> impl Iterator<Item = (&str, &PropertyValue)> {
+        self.properties.iter().map(|(k, v)| (k.as_str(), v))
+    }
+
+    /// Sets a property, replacing an existing one with the same key while keeping
+    /// its position.
+    pub fn set(&mut self, key: impl Into<String>, value: PropertyValue) {
+        let key = key.into();
+        if let Some(existing) = self
+            .properties
+            .iter_mut()
+            .find(|(k, _)| k.eq_ignore_ascii_case(&key))
+        {
+            existing.1 = value;
+        } else {
+            self.properties.push((key, value));
+        }
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<PropertyValue> {
+        let ix = self
+            .properties
+            .iter()
+            .position(|(k, _)| k.eq_ignore_ascii_case(key))?;
+        Some(self.properties.remove(ix).1)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.properties.is_empty()
+    }
+
+    /// The note's tags, without the leading `#`.
+    pub fn tags(&self) -> Vec<String> {
+        ["tags", "tag"]
+            .iter()
+            .filter_map(|key| self.get(key))
+            .flat_map(|value| value.to_list())
+            .map(|tag| tag.trim_start_matches('#').to_string())
+            .collect()
+    }
+
+    pub fn aliases(&self) -> Vec<String> {
+        ["aliases", "alias"]
+            .iter()
+            .filter_map(|key| self.get(key))
+            .flat_map(|value| value.to_list())
+            .collect()
+    }
+
+    /// Serializes the properties back into a `---` delimited block, including
+    /// the trailing newline.
+    pub fn to_yaml(&self) -> String {
+        let mut yaml = String::from("---\n");
+        for (key, value) in &self.properties {
+            match value {
+                PropertyValue::Text(text) => {
+                    yaml.push_str(&format!("{key}: {}\n", quote_if_needed(text)));
+                }
+                PropertyValue::List(items) if items.is_empty() => {
+                    yaml.push_str(&format!("{key}: []\n"));
+                }
+                PropertyValue::List(items) => {
+                    yaml.push_str(&format!("{key}:\n"));
+                    for item in items {
+                        yaml.push_str(&format!("  - {}\n", quote_if_needed(item)));
+                    }
+                }
+            }
+        }
+        yaml.push_str("---\n");
+        yaml
+    }
+}
+
+/// Returns the offset at which the note body starts, i.e. just past the
+/// frontmatter if the note has any.
+pub fn body_offset(text: &str) -> usize {
+    Frontmatter::parse(text).map_or(0, |frontmatter| frontmatter.range.end)
+}
+
+fn unquote(value: &str) -> Cow<'_, str> {
+    if value.len() >= 2 && value.starts_with('"') && value.ends_with('"') {
+        let inner = &value[1..value.len() - 1];
+        if inner.contains('\\') {
+            return Cow::Owned(inner.replace("\\\"", "\"").replace("\\\\", "\\"));
+        }
+        return Cow::Borrowed(inner);
+    }
+    if value.len() >= 2 && value.starts_with('\'') && value.ends_with('\'') {
+        return Cow::Borrowed(&value[1..value.len() - 1]);
+    }
+    Cow::Borrowed(value)
+}
+
+fn quote_if_needed(value: &str) -> String {
+    let needs_quotes = value.is_empty()
+        || value.contains(": ")
+        || value.contains(" #")
+        || value.starts_with(|c: char| "[]{}#&*!|>'\"%@`-?,".contains(c))
+        || value.ends_with(':')
+        || value.trim() != value;
+    if needs_quotes {
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Iterates over lines along with the byte range of each line, including its
+/// line terminator.
+struct LineIter<'a> {
+    text: &'a str,
+    offset: usize,
+}
+
+impl<'a> LineIter<'a> {
+    fn new(text: &'a str) -> Self {
+        Self { text, offset: 0 }
+    }
+}
+
+impl<'a> Iterator for LineIter<'a> {
+    type Item = (&'a str, Range<usize>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.offset >= self.text.len() {
+            return None;
+        }
+        let rest = &self.text[self.offset..];
+        let (line, len) = match rest.find('\n') {
+            Some(ix) => (&rest[..ix], ix + 1),
+            None => (rest, rest.len()),
+        };
+        let range = self.offset..self.offset + len;
+        self.offset += len;
+        Some((line.strip_suffix('\r').unwrap_or(line), range))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_parse_frontmatter() {
+        let text = indoc! {r#"
+            ---
+            title: "Weekly sync: notes"
+            tags: [meeting, team]
+            aliases:
+              - sync
+              - 'standup'
+            draft: true
+            ---
+            # Body
+        "#};
+        let frontmatter = Frontmatter::parse(text).unwrap();
+        assert_eq!(&text[frontmatter.range.end..], "# Body\n");
+        assert_eq!(frontmatter.get_text("title"), Some("Weekly sync: notes"));
+        assert_eq!(frontmatter.get_text("draft"), Some("true"));
+        assert_eq!(frontmatter.tags(), vec!["meeting", "team"]);
+        assert_eq!(frontmatter.aliases(), vec!["sync", "standup"]);
+    }
+
+    #[test]
+    fn test_unterminated_frontmatter() {
+        assert_eq!(Frontmatter::parse("---\ntitle: x\n# Body\n"), None);
+        assert_eq!(Frontmatter::parse("# Title\n---\n"), None);
+        assert_eq!(body_offset("no frontmatter"), 0);
+    }
+
+    #[test]
+    fn test_comma_separated_tags() {
+        let frontmatter = Frontmatter::parse("---\ntags: #one, two\n---\n").unwrap();
+        assert_eq!(frontmatter.tags(), vec!["one", "two"]);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut frontmatter = Frontmatter::new();
+        frontmatter.set("title", PropertyValue::Text("Rust: a primer".into()));
+        frontmatter.set("source", PropertyValue::Text("https://example.com".into()));
+        frontmatter.set("tags", PropertyValue::List(vec!["clipping".into()]));
+        let yaml = frontmatter.to_yaml();
+        assert_eq!(
+            yaml,
+            indoc! {r#"
+                ---
+                title: "Rust: a primer"
+                source: https://example.com
+                tags:
+                  - clipping
+                ---
+            "#}
+        );
+        let parsed = Frontmatter::parse(&yaml).unwrap();
+        assert_eq!(parsed.get_text("title"), Some("Rust: a primer"));
+        assert_eq!(parsed.tags(), vec!["clipping"]);
+    }
+}
@@ -0,0 +1,195 @@
+use collections::{FxHasher, HashMap};
+use parking_lot::Mutex;
+use std::{
+    hash::{Hash, Hasher},
+    ops::Range,
+    sync::Arc,
+};
+
+use crate::{Frontmatter, Heading, body_offset, code_ranges, headings, inline_tags};
+
+/// The parsed structure of a note: its properties, tags, headings and tasks.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NoteMetadata {
+    pub frontmatter: Option<Frontmatter>,
+    /// The offset of the body, after the frontmatter.
+    pub body_start: usize,
+    /// The tags from both the frontmatter and the body.
+    pub tags: Vec<String>,
+    /// The `#tags` in the body along with their byte ranges.
+    pub inline_tags: Vec<(Range<usize>, String)>,
+    pub headings: Vec<Heading>,
+    /// The text ranges of unchecked `- [ ]` tasks, after their `[ ]`.
+    pub todo_tasks: Vec<Range<usize>>,
+}
+
+impl NoteMetadata {
+    pub fn parse(text: &str) -> Self {
+        let frontmatter = Frontmatter::parse(text);
+        let body_start = body_offset(text);
+        let inline_tags = inline_tags(&text[body_start..])
+            .into_iter()
+            .map(|(range, tag)| (range.start + body_start..range.end + body_start, tag))
+            .collect::<Vec<_>>();
+        let tags = frontmatter
+            .as_ref()
+            .map(|frontmatter| frontmatter.tags())
+            .unwrap_or_default()
+            .into_iter()
+            .chain(inline_tags.iter().map(|(_, tag)| tag.clone()))
+            .collect();
+        Self {
+            body_start,
+            tags,
+            inline_tags,
+            headings: headings(text),
+            todo_tasks: todo_tasks(text, body_start),
+            frontmatter,
+        }
+    }
+}
+
+/// Caches the metadata of notes, so that matching many queries against the
+/// same notes parses each of them once.
+///
+/// Notes are identified by a key, such as their path in a worktree. Entries
+/// also record a digest of the text they were parsed from, so a note whose
+/// text changed, e.g. in an unsaved buffer, is parsed again.
+#[derive(Debug)]
+pub struct NoteIndex<K> {
+    notes: Mutex<HashMap<K, (u64, Arc<NoteMetadata>)>>,
+}
+
+impl<K> Default for NoteIndex<K> {
+    fn default() -> Self {
+        Self {
+            notes: Mutex::new(HashMap::default()),
+        }
+    }
+}
+
+impl<K: Eq + Hash> NoteIndex<K> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the metadata of the note with the given `key` and `text`.
+    pub fn metadata(&self, key: K, text: &str) -> Arc<NoteMetadata> {
+        let digest = digest(text);
+        if let Some((cached_digest, metadata)) = self.notes.lock().get(&key) {
+            if *cached_digest == digest {
+                return metadata.clone();
+            }
+        }
+        let metadata = Arc::new(NoteMetadata::parse(text));
+        self.notes.lock().insert(key, (digest, metadata.clone()));
+        metadata
+    }
+
+    /// Drops the notes whose keys don't satisfy `keep`.
+    pub fn retain(&self, mut keep: impl FnMut(&K) -> bool) {
+        self.notes.lock().retain(|key, _| keep(key));
+    }
+
+    pub fn len(&self) -> usize {
+        self.notes.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.notes.lock().is_empty()
+    }
+}
+
+fn digest(text: &str) -> u64 {
+    let mut hasher = FxHasher::default();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Returns the text ranges of the unchecked `- [ ]` tasks in the note body,
+/// skipping code blocks.
+fn todo_tasks(text: &str, body_start: usize) -> Vec<Range<usize>> {
+    let code = code_ranges(text);
+    let mut tasks = Vec::new();
+    let mut line_start = body_start;
+    for line in text[body_start..].split_inclusive('\n') {
+        let content = line.trim_end_matches(['\n', '\r']);
+        let item = content.trim_start();
+        let marker_len = if item.starts_with(['-', '*', '+']) {
+            Some(1)
+        } else {
+            let digits = item.find(|c: char| !c.is_ascii_digit()).unwrap_or(0);
+            (digits > 0 && item[digits..].starts_with(['.', ')'])).then_some(digits + 1)
+        };
+        let task_text = marker_len
+            .and_then(|len| item[len..].strip_prefix(' '))
+            .and_then(|rest| rest.trim_start().strip_prefix("[ ]"));
+        if let Some(task_text) = task_text {
+            let start = line_start + content.len() - task_text.len();
+            if !code.iter().any(|range| range.contains(&start)) {
+                let task_text = task_text.trim_start();
+                let start = line_start + content.len() - task_text.len();
+                tasks.push(start..line_start + content.len());
+            }
+        }
+        line_start += line.len();
+    }
+    tasks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_note_metadata() {
+        let text = indoc! {"
+            ---
+            tags: [project]
+            ---
+            # Plan
+            Ship it #draft.
+            - [ ] write the parser
+            - [x] write the tests
+            ```
+            - [ ] not a task
+            ```
+        "};
+        let metadata = NoteMetadata::parse(text);
+        assert_eq!(metadata.tags, ["project", "draft"]);
+        assert_eq!(
+            metadata
+                .headings
+                .iter()
+                .map(|heading| heading.text.as_str())
+                .collect::<Vec<_>>(),
+            ["Plan"]
+        );
+        assert_eq!(
+            metadata
+                .todo_tasks
+                .iter()
+                .map(|range| &text[range.clone()])
+                .collect::<Vec<_>>(),
+            ["write the parser"]
+        );
+    }
+
+    #[test]
+    fn test_note_index_reparses_changed_notes() {
+        let index = NoteIndex::new();
+        let first = index.metadata("Plan.md", "#one");
+        assert!(Arc::ptr_eq(&first, &index.metadata("Plan.md", "#one")));
+
+        let changed = index.metadata("Plan.md", "#two");
+        assert_eq!(changed.tags, ["two"]);
+        assert!(!Arc::ptr_eq(&first, &changed));
+
+        index.metadata("Ideas.md", "#two");
+        index.retain(|key| *key != "Plan.md");
+        assert_eq!(index.len(), 1);
+        assert!(!Arc::ptr_eq(&changed, &index.metadata("Plan.md", "#two")));
+    }
+}
@@ -0,0 +1,234 @@
+use std::ops::Range;
+
+/// A `[[wiki link]]` or `![[embed]]` found in a note.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WikiLink {
+    /// The byte range of the link, including the brackets and the `!` of embeds.
+    pub range: Range<usize>,
+    pub embed: bool,
+    /// The linked note or file, e.g. `Projects/Roadmap` or `diagram.png`.
+    pub target: String,
+    pub subpath: Option<LinkSubpath>,
+    /// The text after `|`, which is the display text for links and the size
+    /// for image embeds.
+    pub alias: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LinkSubpath {
+    /// `[[note#Heading]]`
+    Heading(String),
+    /// `[[note#^block-id]]`
+    Block(String),
+}
+
+impl WikiLink {
+    fn parse(inner: &str, range: Range<usize>, embed: bool) -> Option<Self> {
+        let (reference, alias) = match inner.split_once('|') {
+            Some((reference, alias)) => (reference, Some(alias.trim().to_string())),
+            None => (inner, None),
+        };
+        let (target, subpath) = match reference.split_once('#') {
+            Some((target, subpath)) => {
+                let subpath = subpath.trim();
+                let subpath = if let Some(block) = subpath.strip_prefix('^') {
+                    LinkSubpath::Block(block.to_string())
+                } else {
+                    LinkSubpath::Heading(subpath.to_string())
+                };
+                (target, Some(subpath))
+            }
+            None => (reference, None),
+        };
+        let target = target.trim().to_string();
+        if target.is_empty() && subpath.is_none() {
+            return None;
+        }
+        Some(Self {
+            range,
+            embed,
+            target,
+            subpath,
+            alias,
+        })
+    }
+
+    /// The text a reader sees for this link.
+    pub fn display_text(&self) -> String {
+        if let Some(alias) = &self.alias {
+            return alias.clone();
+        }
+        match &self.subpath {
+            Some(LinkSubpath::Heading(heading)) if self.target.is_empty() => heading.clone(),
+            Some(LinkSubpath::Heading(heading)) => format!("{} > {}", self.target, heading),
+            _ => self.target.clone(),
+        }
+    }
+}
+
+/// Returns every wiki link and embed in `text`, skipping code blocks and code
+/// spans.
+pub fn wiki_links(text: &str) -> Vec<WikiLink> {
+    let code = code_ranges(text);
+    let mut links = Vec::new();
+    let mut offset = 0;
+    while let Some(start) = text[offset..].find("[[").map(|ix| ix + offset) {
+        if let Some(code_range) = code.iter().find(|range| range.contains(&start)) {
+            offset = code_range.end;
+            continue;
+        }
+        let Some(len) = text[start + 2..].find("]]") else {
+            break;
+        };
+        let inner = &text[start + 2..start + 2 + len];
+        if inner.contains('\n') || inner.contains("[[") {
+            offset = start + 2;
+            continue;
+        }
+        let embed = start > 0 && text.as_bytes()[start - 1] == b'!';
+        let link_start = if embed { start - 1 } else { start };
+        let end = start + 2 + len + 2;
+        if let Some(link) = WikiLink::parse(inner, link_start..end, embed) {
+            links.push(link);
+        }
+        offset = end;
+    }
+    links
+}
+
+/// Returns the `#tags` in the body of a note along with their byte ranges.
+/// Tags are returned without the leading `#`.
+pub fn inline_tags(text: &str) -> Vec<(Range<usize>, String)> {
+    let code = code_ranges(text);
+    let mut tags = Vec::new();
+    for (ix, _) in text.match_indices('#') {
+        if code.iter().any(|range| range.contains(&ix)) {
+            continue;
+        }
+        let preceded_by_boundary = text[..ix]
+            .chars()
+            .next_back()
+            .map_or(true, |c| c.is_whitespace() || c == '(' || c == ',');
+        if !preceded_by_boundary {
+            continue;
+        }
+        let tag_len = text[ix + 1..]
+            .char_indices()
+            .find(|(_, c)| !is_tag_char(*c))
+            .map_or(text.len() - ix - 1, |(len, _)| len);
+        let tag = &text[ix + 1..ix + 1 + tag_len];
+        // Headings (`# Title`) and purely numeric tags (`#1`) aren't tags.
+        if tag.is_empty() || tag.chars().all(|c| c.is_ascii_digit() || c == '/') {
+            continue;
+        }
+        tags.push((ix..ix + 1 + tag_len, tag.to_string()));
+    }
+    tags
+}
+
+fn is_tag_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '/')
+}
+
+/// Returns the byte ranges of fenced code blocks and inline code spans.
+pub fn code_ranges(text: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut fence: Option<(usize, &str)> = None;
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let line_start = offset;
+        offset += line.len();
+        let trimmed = line.trim_start();
+        if let Some((fence_start, marker)) = fence {
+            if trimmed.trim_end().starts_with(marker) {
+                ranges.push(fence_start..offset);
+                fence = None;
+            }
+            continue;
+        }
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            let marker = &trimmed[..3];
+            fence = Some((line_start, marker));
+            continue;
+        }
+
+        let mut search_from = 0;
+        while let Some(open) = line[search_from..].find('`').map(|ix| ix + search_from) {
+            let ticks = line[open..].chars().take_while(|c| *c == '`').count();
+            let delimiter = &line[open..open + ticks];
+            let Some(close) = line[open + ticks..]
+                .find(delimiter)
+                .map(|ix| ix + open + ticks)
+            else {
+                break;
+            };
+            ranges.push(line_start + open..line_start + close + ticks);
+            search_from = close + ticks;
+        }
+    }
+    if let Some((fence_start, _)) = fence {
+        ranges.push(fence_start..text.len());
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_wiki_links() {
+        let text = "See [[Roadmap]], ![[diagram.png|300]] and [[Specs/API#Auth|auth docs]].";
+        let links = wiki_links(text);
+        assert_eq!(
+            links,
+            vec![
+                WikiLink {
+                    range: 4..15,
+                    embed: false,
+                    target: "Roadmap".into(),
+                    subpath: None,
+                    alias: None,
+                },
+                WikiLink {
+                    range: 17..37,
+                    embed: true,
+                    target: "diagram.png".into(),
+                    subpath: None,
+                    alias: Some("300".into()),
+                },
+                WikiLink {
+                    range: 42..70,
+                    embed: false,
+                    target: "Specs/API".into(),
+                    subpath: Some(LinkSubpath::Heading("Auth".into())),
+                    alias: Some("auth docs".into()),
+                },
+            ]
+        );
+        assert_eq!(
+            &text[links[2].range.clone()],
+            "[[Specs/API#Auth|auth docs]]"
+        );
+    }
+
+    #[test]
+    fn test_links_in_code_are_ignored() {
+        let text = "`[[not a link]]`\n```\n[[also not]]\n```\n[[real#^abc123]]";
+        let links = wiki_links(text);
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].target, "real");
+        assert_eq!(links[0].subpath, Some(LinkSubpath::Block("abc123".into())));
+    }
+
+    #[test]
+    fn test_inline_tags() {
+        let text = "# Heading\nTagged #project/zed and #todo, not a#tag or #123.\n`#code`";
+        let tags = inline_tags(text)
+            .into_iter()
+            .map(|(_, tag)| tag)
+            .collect::<Vec<_>>();
+        assert_eq!(tags, vec!["project/zed", "todo"]);
+    }
+}
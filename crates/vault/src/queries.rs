@@ -0,0 +1,223 @@
+use fs::Fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::{Frontmatter, NoteResolver, frontmatter::body_offset, inline_tags, wiki_links};
+
+/// A line in one note that links to another.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Backlink {
+    /// The linking note, relative to the vault root.
+    pub source: Arc<Path>,
+    /// The zero-based row of the link.
+    pub row: u32,
+    /// The trimmed line containing the link.
+    pub line: String,
+}
+
+/// Returns the lines in `text`, the content of the note at `source`, that
+/// contain a link or embed resolving to `target`. Each line is reported once.
+pub fn links_to(
+    text: &str,
+    source: &Arc<Path>,
+    target: &Path,
+    resolver: &NoteResolver,
+) -> Vec<Backlink> {
+    let mut backlinks: Vec<Backlink> = Vec::new();
+    for link in wiki_links(text) {
+        if resolver
+            .resolve(&link.target, Some(source.as_ref()))
+            .is_none_or(|path| path.as_ref() != target)
+        {
+            continue;
+        }
+        let row = text[..link.range.start].matches('\n').count() as u32;
+        if backlinks.last().is_some_and(|backlink| backlink.row == row) {
+            continue;
+        }
+        let line_start = text[..link.range.start].rfind('\n').map_or(0, |ix| ix + 1);
+        let line_end = text[link.range.end..]
+            .find('\n')
+            .map_or(text.len(), |ix| link.range.end + ix);
+        backlinks.push(Backlink {
+            source: source.clone(),
+            row,
+            line: text[line_start..line_end].trim().to_string(),
+        });
+    }
+    backlinks
+}
+
+/// Returns every link to `target` from the other notes in the vault, ordered
+/// by the linking note's path.
+pub async fn backlinks(
+    target: &Path,
+    root: &Path,
+    resolver: &NoteResolver,
+    fs: &dyn Fs,
+) -> Vec<Backlink> {
+    let mut backlinks = Vec::new();
+    for source in resolver.notes() {
+        if source.as_ref() == target {
+            continue;
+        }
+        match fs.load(&root.join(source)).await {
+            Ok(text) => backlinks.extend(links_to(&text, source, target, resolver)),
+            Err(error) => log::warn!("failed to load note {source:?}: {error}"),
+        }
+    }
+    backlinks
+}
+
+/// The tags of a note, from both its frontmatter and `#tags` in its body,
+/// without duplicates and in the order they first appear.
+pub fn note_tags(text: &str) -> Vec<String> {
+    let mut tags = Frontmatter::parse(text)
+        .map(|frontmatter| frontmatter.tags())
+        .unwrap_or_default();
+    let body_start = body_offset(text);
+    tags.extend(
+        inline_tags(&text[body_start..])
+            .into_iter()
+            .map(|(_, tag)| tag),
+    );
+
+    let mut unique_tags: Vec<String> = Vec::with_capacity(tags.len());
+    for tag in tags {
+        if !unique_tags
+            .iter()
+            .any(|existing| existing.eq_ignore_ascii_case(&tag))
+        {
+            unique_tags.push(tag);
+        }
+    }
+    unique_tags
+}
+
+/// Whether `tag` is `query` or nested under it, ignoring case, so that
+/// `project` matches `project/alpha` but not `projects`.
+pub fn tag_matches(tag: &str, query: &str) -> bool {
+    let query = query.trim_start_matches('#').trim_end_matches('/');
+    if query.is_empty() {
+        return false;
+    }
+    match (tag.get(..query.len()), tag.get(query.len()..)) {
+        (Some(prefix), Some(rest)) => {
+            prefix.eq_ignore_ascii_case(query) && (rest.is_empty() || rest.starts_with('/'))
+        }
+        _ => false,
+    }
+}
+
+/// Returns the notes tagged with `tag` or one of its nested tags, along with
+/// each note's matching tags, ordered by path.
+pub async fn notes_with_tag(
+    tag: &str,
+    root: &Path,
+    resolver: &NoteResolver,
+    fs: &dyn Fs,
+) -> Vec<(Arc<Path>, Vec<String>)> {
+    let mut notes = Vec::new();
+    for path in resolver.notes() {
+        let text = match fs.load(&root.join(path)).await {
+            Ok(text) => text,
+            Err(error) => {
+                log::warn!("failed to load note {path:?}: {error}");
+                continue;
+            }
+        };
+        let matching_tags = note_tags(&text)
+            .into_iter()
+            .filter(|note_tag| tag_matches(note_tag, tag))
+            .collect::<Vec<_>>();
+        if !matching_tags.is_empty() {
+            notes.push((path.clone(), matching_tags));
+        }
+    }
+    notes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use util::path;
+
+    #[gpui::test]
+    async fn test_backlinks_and_tags(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/vault"),
+            json!({
+                "Garden.md": "---\ntags: [Projects/garden]\n---\nSee [[Seeds]] and [[Seeds#Tomatoes|tomatoes]].\n",
+                "Seeds.md": "# Seeds\nLinks back to [[Seeds]]. #projects\n",
+                "journal": {
+                    "2025-04-01.md": "Ordered ![[Seeds]] today.\n\nPlanted #garden #projectsx\n",
+                },
+            }),
+        )
+        .await;
+
+        let resolver = NoteResolver::new(
+            ["Garden.md", "Seeds.md", "journal/2025-04-01.md"]
+                .into_iter()
+                .map(|path| Arc::from(Path::new(path))),
+        );
+        let root = Path::new(path!("/vault"));
+
+        let backlinks = backlinks(Path::new("Seeds.md"), root, &resolver, fs.as_ref()).await;
+        assert_eq!(
+            backlinks
+                .iter()
+                .map(|backlink| (
+                    backlink.source.to_string_lossy().into_owned(),
+                    backlink.row,
+                    backlink.line.as_str()
+                ))
+                .collect::<Vec<_>>(),
+            [
+                (
+                    "Garden.md".to_string(),
+                    3,
+                    "See [[Seeds]] and [[Seeds#Tomatoes|tomatoes]]."
+                ),
+                (
+                    Path::new("journal")
+                        .join("2025-04-01.md")
+                        .to_string_lossy()
+                        .into_owned(),
+                    0,
+                    "Ordered ![[Seeds]] today."
+                ),
+            ]
+        );
+
+        let tagged = notes_with_tag("#projects", root, &resolver, fs.as_ref()).await;
+        assert_eq!(
+            tagged,
+            [
+                (
+                    Arc::<Path>::from(Path::new("Garden.md")),
+                    vec!["Projects/garden".to_string()]
+                ),
+                (
+                    Arc::from(Path::new("Seeds.md")),
+                    vec!["projects".to_string()]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tag_matches() {
+        assert!(tag_matches("project", "project"));
+        assert!(tag_matches("Project/Alpha", "project"));
+        assert!(tag_matches("project/alpha", "#project/"));
+        assert!(!tag_matches("projects", "project"));
+        assert!(!tag_matches("project", "project/alpha"));
+        assert!(!tag_matches("project", ""));
+    }
+}
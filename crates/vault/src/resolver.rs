@@ -0,0 +1,366 @@
+use crate::{LinkSubpath, wiki_links};
+use anyhow::{Context as _, Result};
+use collections::HashMap;
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// The file extension of notes. Links without an extension refer to notes.
+pub const NOTE_EXTENSION: &str = "md";
+
+/// A folder of notes, and the resolver for the links between them.
+#[derive(Clone, Debug)]
+pub struct Vault {
+    pub root: Arc<Path>,
+    pub resolver: NoteResolver,
+}
+
+impl Vault {
+    pub fn new(root: Arc<Path>, paths: impl IntoIterator<Item = Arc<Path>>) -> Self {
+        Self {
+            root,
+            resolver: NoteResolver::new(paths),
+        }
+    }
+
+    /// Resolves a note by the name a wiki link would use, such as `Note`,
+    /// `folder/Note`, `[[Note#Heading]]` or `![[Note#^block-id]]`.
+    pub fn resolve(&self, name: &str) -> Result<(Arc<Path>, Option<LinkSubpath>)> {
+        let name = name.trim();
+        let name = name.strip_prefix('!').unwrap_or(name);
+        let name = name.strip_prefix("[[").unwrap_or(name);
+        let name = name.strip_suffix("]]").unwrap_or(name);
+        let link = wiki_links(&format!("[[{name}]]"))
+            .pop()
+            .with_context(|| format!("{name:?} is not a valid note name"))?;
+        let path = self
+            .resolver
+            .resolve(&link.target, None)
+            .with_context(|| format!("no note named {:?} in the vault", link.target))?;
+        Ok((path, link.subpath))
+    }
+}
+
+/// Resolves wiki link targets to paths relative to the vault root.
+///
+/// Resolution follows the rules most note-taking tools use: a target that is a
+/// full path (with or without the `.md` extension) wins, otherwise the target
+/// is matched by file name, preferring files in the linking note's folder and
+/// then the shortest path.
+#[derive(Clone, Debug, Default)]
+pub struct NoteResolver {
+    by_name: HashMap<String, Vec<Arc<Path>>>,
+    by_path: HashMap<String, Arc<Path>>,
+}
+
+impl NoteResolver {
+    pub fn new(paths: impl IntoIterator<Item = Arc<Path>>) -> Self {
+        let mut resolver = Self::default();
+        for path in paths {
+            resolver.insert(path);
+        }
+        resolver
+    }
+
+    pub fn insert(&mut self, path: Arc<Path>) {
+        let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+            return;
+        };
+        let mut keys = vec![name.to_lowercase()];
+        if is_note(&path) {
+            if let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) {
+                keys.push(stem.to_lowercase());
+            }
+        }
+        for key in keys {
+            let paths = self.by_name.entry(key).or_default();
+            if !paths.contains(&path) {
+                paths.push(path.clone());
+                paths.sort_by_key(|path| path.components().count());
+            }
+        }
+        self.by_path.insert(normalize(&path), path.clone());
+        if is_note(&path) {
+            self.by_path
+                .insert(normalize(&path.with_extension("")), path.clone());
+        }
+    }
+
+    pub fn remove(&mut self, path: &Path) {
+        self.by_name.retain(|_, paths| {
+            paths.retain(|candidate| candidate.as_ref() != path);
+            !paths.is_empty()
+        });
+        self.by_path
+            .retain(|_, candidate| candidate.as_ref() != path);
+    }
+
+    /// Resolves `target` as linked from the note at `from`.
+    pub fn resolve(&self, target: &str, from: Option<&Path>) -> Option<Arc<Path>> {
+        let target = target.trim().trim_start_matches("./");
+        if target.is_empty() {
+            return from.and_then(|from| self.by_path.get(&normalize(from)).cloned());
+        }
+
+        if let Some(from_dir) = from.and_then(Path::parent) {
+            let relative = from_dir.join(target);
+            if let Some(path) = self.by_path.get(&normalize(&relative)) {
+                return Some(path.clone());
+            }
+        }
+        if let Some(path) = self.by_path.get(&normalize(Path::new(target))) {
+            return Some(path.clone());
+        }
+
+        let name = Path::new(target).file_name()?.to_str()?.to_lowercase();
+        let candidates = self.by_name.get(&name)?;
+        let from_dir = from.and_then(Path::parent);
+        candidates
+            .iter()
+            .find(|candidate| candidate.parent() == from_dir)
+            .or_else(|| {
+                candidates
+                    .iter()
+                    .find(|candidate| path_ends_with(candidate, target))
+            })
+            .or_else(|| candidates.first())
+            .cloned()
+    }
+
+    /// Returns all known notes, i.e. markdown files.
+    pub fn notes(&self) -> impl Iterator<Item = &Arc<Path>> {
+        let mut notes = self
+            .by_path
+            .values()
+            .filter(|path| is_note(path))
+            .collect::<Vec<_>>();
+        notes.sort();
+        notes.dedup();
+        notes.into_iter()
+    }
+
+    /// The shortest link target that unambiguously resolves to `path`.
+    pub fn link_target(&self, path: &Path) -> String {
+        let stem = if is_note(path) {
+            path.with_extension("")
+        } else {
+            path.to_path_buf()
+        };
+        let name = stem
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let unambiguous = self
+            .by_name
+            .get(&name.to_lowercase())
+            .map_or(true, |paths| paths.len() <= 1);
+        if unambiguous {
+            name
+        } else {
+            to_link_path(&stem)
+        }
+    }
+}
+
+pub fn is_note(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| extension.eq_ignore_ascii_case(NOTE_EXTENSION))
+}
+
+/// Whether `target` names `path` by a trailing part of its path, e.g. `2023/Plan`
+/// for `Archive/2023/Plan.md`.
+fn path_ends_with(path: &Path, target: &str) -> bool {
+    let target = normalize(Path::new(target));
+    let candidates = if is_note(path) {
+        [normalize(path), normalize(&path.with_extension(""))]
+    } else {
+        [normalize(path), String::new()]
+    };
+    candidates.iter().any(|candidate| {
+        !candidate.is_empty()
+            && (*candidate == target || candidate.ends_with(&format!("/{target}")))
+    })
+}
+
+fn normalize(path: &Path) -> String {
+    let mut components: Vec<String> = Vec::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::ParentDir => {
+                components.pop();
+            }
+            std::path::Component::Normal(part) => {
+                components.push(part.to_string_lossy().to_lowercase());
+            }
+            _ => {}
+        }
+    }
+    components.join("/")
+}
+
+fn to_link_path(path: &Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Turns a note title into a file name that is valid on every platform.
+pub fn sanitize_file_name(title: &str) -> String {
+    let sanitized: String = title
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' | '#' | '^' | '[' | ']' => ' ',
+            c if c.is_control() => ' ',
+            c => c,
+        })
+        .collect();
+    let sanitized = sanitized.split_whitespace().collect::<Vec<_>>().join(" ");
+    let sanitized = sanitized.trim_matches('.').trim();
+    if sanitized.is_empty() {
+        "Untitled".to_string()
+    } else {
+        sanitized.chars().take(120).collect()
+    }
+}
+
+/// Returns the path of a new note named `title` in `folder` that doesn't clash
+/// with `existing`, appending a number when needed.
+pub fn unique_note_path(folder: &Path, title: &str, existing: impl Fn(&Path) -> bool) -> PathBuf {
+    unique_file_path(folder, &sanitize_file_name(title), NOTE_EXTENSION, existing)
+}
+
+/// Returns the path of a new `stem.extension` file in `folder` that doesn't
+/// clash with `existing`, appending a number when needed.
+pub fn unique_file_path(
+    folder: &Path,
+    stem: &str,
+    extension: &str,
+    existing: impl Fn(&Path) -> bool,
+) -> PathBuf {
+    let file_name = |suffix: String| {
+        if extension.is_empty() {
+            format!("{stem}{suffix}")
+        } else {
+            format!("{stem}{suffix}.{extension}")
+        }
+    };
+    let mut path = folder.join(file_name(String::new()));
+    let mut counter = 1;
+    while existing(&path) {
+        path = folder.join(file_name(format!(" {counter}")));
+        counter += 1;
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn resolver(paths: &[&str]) -> NoteResolver {
+        NoteResolver::new(paths.iter().map(|path| Arc::from(Path::new(path))))
+    }
+
+    #[test]
+    fn test_resolve() {
+        let resolver = resolver(&[
+            "Inbox.md",
+            "Projects/Roadmap.md",
+            "Archive/2023/Roadmap.md",
+            "Archive/Notes.md",
+            "assets/diagram.png",
+        ]);
+
+        let resolve = |target: &str, from: Option<&str>| {
+            resolver
+                .resolve(target, from.map(Path::new))
+                .map(|path| path.to_string_lossy().into_owned())
+        };
+
+        assert_eq!(resolve("inbox", None).as_deref(), Some("Inbox.md"));
+        assert_eq!(
+            resolve("Roadmap", None).as_deref(),
+            Some("Projects/Roadmap.md")
+        );
+        assert_eq!(
+            resolve("Roadmap", Some("Archive/2023/Plan.md")).as_deref(),
+            Some("Archive/2023/Roadmap.md")
+        );
+        assert_eq!(
+            resolve("2023/Roadmap", None).as_deref(),
+            Some("Archive/2023/Roadmap.md")
+        );
+        assert_eq!(
+            resolve("Archive/Notes.md", None).as_deref(),
+            Some("Archive/Notes.md")
+        );
+        assert_eq!(
+            resolve("diagram.png", Some("Inbox.md")).as_deref(),
+            Some("assets/diagram.png")
+        );
+        assert_eq!(resolve("Missing", None), None);
+    }
+
+    #[test]
+    fn test_vault_resolve() {
+        let vault = Vault::new(
+            Path::new("/vault").into(),
+            ["Inbox.md", "Projects/Roadmap.md"]
+                .iter()
+                .map(|path| Arc::from(Path::new(path))),
+        );
+        let resolve = |name: &str| {
+            vault
+                .resolve(name)
+                .map(|(path, subpath)| (path.to_string_lossy().into_owned(), subpath))
+        };
+
+        assert_eq!(resolve(" inbox ").unwrap(), ("Inbox.md".into(), None));
+        assert_eq!(
+            resolve("Projects/Roadmap#Goals").unwrap(),
+            (
+                "Projects/Roadmap.md".into(),
+                Some(LinkSubpath::Heading("Goals".into()))
+            )
+        );
+        assert_eq!(
+            resolve("[[Roadmap#^q3]]").unwrap(),
+            (
+                "Projects/Roadmap.md".into(),
+                Some(LinkSubpath::Block("q3".into()))
+            )
+        );
+        assert_eq!(
+            resolve("![[Inbox|the inbox]]").unwrap(),
+            ("Inbox.md".into(), None)
+        );
+        assert_eq!(
+            resolve("Missing").unwrap_err().to_string(),
+            "no note named \"Missing\" in the vault"
+        );
+        assert!(resolve("").is_err());
+    }
+
+    #[test]
+    fn test_link_target() {
+        let resolver = resolver(&["Projects/Roadmap.md", "Archive/Roadmap.md", "Inbox.md"]);
+        assert_eq!(resolver.link_target(Path::new("Inbox.md")), "Inbox");
+        assert_eq!(
+            resolver.link_target(Path::new("Archive/Roadmap.md")),
+            "Archive/Roadmap"
+        );
+    }
+
+    #[test]
+    fn test_unique_note_path() {
+        let existing = [PathBuf::from("Clippings/A title.md")];
+        let path = unique_note_path(Path::new("Clippings"), "A title?", |path| {
+            existing.iter().any(|existing| existing == path)
+        });
+        assert_eq!(path, PathBuf::from("Clippings/A title 1.md"));
+    }
+}
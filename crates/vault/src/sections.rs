@@ -0,0 +1,172 @@
+use std::ops::Range;
+
+use crate::links::code_ranges;
+
+/// An ATX heading (`## Title`) in a note.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Heading {
+    pub level: usize,
+    pub text: String,
+    /// The byte range of the heading line, excluding its newline.
+    pub range: Range<usize>,
+    /// The byte range of the heading line and everything below it, up to the
+    /// next heading of the same or a higher level.
+    pub section_range: Range<usize>,
+}
+
+/// Returns the ATX headings in `text`, ignoring `#` lines inside code blocks.
+pub fn headings(text: &str) -> Vec<Heading> {
+    let code = code_ranges(text);
+    let mut headings: Vec<Heading> = Vec::new();
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let line_start = offset;
+        offset += line.len();
+        if code.iter().any(|range| range.contains(&line_start)) {
+            continue;
+        }
+        let content = line.trim_end_matches(['\n', '\r']);
+        let Some(level) = heading_level(content) else {
+            continue;
+        };
+        let title = content[level..]
+            .trim()
+            .trim_end_matches('#')
+            .trim_end()
+            .to_string();
+        headings.push(Heading {
+            level,
+            text: title,
+            range: line_start..line_start + content.len(),
+            section_range: line_start..text.len(),
+        });
+    }
+
+    for ix in 0..headings.len() {
+        let level = headings[ix].level;
+        if let Some(next) = headings[ix + 1..].iter().find(|next| next.level <= level) {
+            let end = next.range.start;
+            headings[ix].section_range.end = end;
+        }
+    }
+    headings
+}
+
+fn heading_level(line: &str) -> Option<usize> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    match line[level..].chars().next() {
+        None | Some(' ') | Some('\t') => Some(level),
+        _ => None,
+    }
+}
+
+/// Returns the range of the section under the heading named `heading`,
+/// comparing names the way wiki links do (case-insensitively, ignoring
+/// surrounding whitespace).
+pub fn heading_section(text: &str, heading: &str) -> Option<Range<usize>> {
+    let heading = heading.trim();
+    headings(text)
+        .into_iter()
+        .find(|candidate| candidate.text.eq_ignore_ascii_case(heading))
+        .map(|heading| heading.section_range)
+}
+
+/// Returns the range of the block tagged with `^block_id`, which is the
+/// paragraph or list item ending with that marker.
+pub fn block_range(text: &str, block_id: &str) -> Option<Range<usize>> {
+    let marker = format!("^{block_id}");
+    let mut offset = 0;
+    let mut block_start = 0;
+    for line in text.split_inclusive('\n') {
+        let line_start = offset;
+        offset += line.len();
+        let content = line.trim_end();
+        if content.is_empty() {
+            block_start = offset;
+            continue;
+        }
+        if content.ends_with(&marker) {
+            let is_list_item = content.trim_start().starts_with(['-', '*', '+']);
+            let start = if is_list_item {
+                line_start
+            } else {
+                block_start
+            };
+            return Some(start..line_start + content.len() - marker.len());
+        }
+    }
+    None
+}
+
+/// Builds a URL-style anchor from a heading, e.g. `Getting Started` becomes
+/// `getting-started`.
+pub fn heading_anchor(heading: &str) -> String {
+    let mut anchor = String::with_capacity(heading.len());
+    for c in heading.trim().chars() {
+        if c.is_alphanumeric() {
+            anchor.extend(c.to_lowercase());
+        } else if (c == ' ' || c == '-' || c == '_') && !anchor.ends_with('-') {
+            anchor.push('-');
+        }
+    }
+    anchor.trim_end_matches('-').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_heading_sections() {
+        let text = indoc! {"
+            # Project
+            intro
+            ## Goals
+            - ship
+            ```sh
+            # not a heading
+            ```
+            ### Stretch
+            - more
+            ## Risks ##
+            none
+        "};
+        let headings = headings(text);
+        assert_eq!(
+            headings
+                .iter()
+                .map(|heading| (heading.level, heading.text.as_str()))
+                .collect::<Vec<_>>(),
+            vec![(1, "Project"), (2, "Goals"), (3, "Stretch"), (2, "Risks")]
+        );
+
+        let goals = heading_section(text, "goals").unwrap();
+        assert_eq!(
+            &text[goals],
+            "## Goals\n- ship\n```sh\n# not a heading\n```\n### Stretch\n- more\n"
+        );
+        let risks = heading_section(text, "Risks").unwrap();
+        assert_eq!(&text[risks], "## Risks ##\nnone\n");
+        assert_eq!(heading_section(text, "Missing"), None);
+    }
+
+    #[test]
+    fn test_block_range() {
+        let text = "First paragraph\ncontinues here ^intro\n\n- item one\n- item two ^item\n";
+        let intro = block_range(text, "intro").unwrap();
+        assert_eq!(&text[intro], "First paragraph\ncontinues here ");
+        let item = block_range(text, "item").unwrap();
+        assert_eq!(&text[item], "- item two ");
+    }
+
+    #[test]
+    fn test_heading_anchor() {
+        assert_eq!(heading_anchor("Getting Started"), "getting-started");
+        assert_eq!(heading_anchor(" API: v2 -- notes "), "api-v2-notes");
+    }
+}
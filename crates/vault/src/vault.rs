@@ -0,0 +1,19 @@
+//! Building blocks for working with a folder of markdown notes: frontmatter
+//! properties, `[[wiki links]]`, `![[embeds]]`, tags and heading sections, and
+//! an index of the metadata parsed from them.
+
+mod embeds;
+mod frontmatter;
+mod index;
+mod links;
+mod queries;
+mod resolver;
+mod sections;
+
+pub use embeds::*;
+pub use frontmatter::*;
+pub use index::*;
+pub use links::*;
+pub use queries::*;
+pub use resolver::*;
+pub use sections::*;
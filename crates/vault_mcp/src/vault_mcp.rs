@@ -0,0 +1,531 @@
+//! Serves the notes in a project over the Model Context Protocol, so that other
+//! local agents can use the vault as a knowledge base.
+//!
+//! Notes are exposed as resources, whose subscribers are notified when the
+//! project's worktree sees them change, and searching, listing backlinks and
+//! creating notes are exposed as tools.
+
+use std::cell::{Cell, RefCell};
+use std::path::Path;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use anyhow::{Context as _, Result, anyhow};
+use assistant_tool::{ActionLog, ToolRegistry};
+use collections::HashSet;
+use context_server::server::{Server, ServerNotifier};
+use context_server::transport::Transport;
+use context_server::types;
+use gpui::{App, AppContext as _, Entity, Task};
+use language_model::LanguageModelToolSchemaFormat;
+use project::{PathChange, Project, UpdatedEntriesSet, WorktreeId};
+use release_channel::AppVersion;
+use serde_json::Value;
+use url::Url;
+use util::ResultExt as _;
+use vault::{NoteResolver, is_note};
+use worktree::Snapshot;
+
+/// The assistant tools that are exposed to MCP clients.
+const TOOLS: &[&str] = &["vault_search", "list_backlinks", "create_note"];
+
+const NOTE_MIME_TYPE: &str = "text/markdown";
+
+/// Serves the vault in the project's first visible worktree over `transport`,
+/// until the client disconnects.
+pub fn serve(project: Entity<Project>, transport: Arc<dyn Transport>, cx: &mut App) -> Task<()> {
+    let mut server = Server::new();
+    let initialized = Rc::new(Cell::new(false));
+    let subscribed_notes = Rc::new(RefCell::new(HashSet::<Arc<Path>>::default()));
+
+    server.handle_request::<types::InitializeParams, _, _>(
+        types::RequestType::Initialize.as_str(),
+        {
+            let initialized = initialized.clone();
+            move |params, cx| {
+                log::info!(
+                    "MCP client connected: {} {}",
+                    params.client_info.name,
+                    params.client_info.version
+                );
+                initialized.set(true);
+                Task::ready(Ok(types::InitializeResponse {
+                    protocol_version: types::ProtocolVersion(
+                        types::LATEST_PROTOCOL_VERSION.to_string(),
+                    ),
+                    capabilities: types::ServerCapabilities {
+                        experimental: None,
+                        logging: None,
+                        prompts: None,
+                        resources: Some(types::ResourcesCapabilities {
+                            subscribe: Some(true),
+                            list_changed: Some(true),
+                        }),
+                        tools: Some(types::ToolsCapabilities { list_changed: None }),
+                    },
+                    server_info: types::Implementation {
+                        name: "zed".into(),
+                        version: AppVersion::global(cx).to_string(),
+                    },
+                    meta: None,
+                }))
+            }
+        },
+    );
+
+    server.handle_request::<Value, _, _>(types::RequestType::Ping.as_str(), |_, _| {
+        Task::ready(Ok(serde_json::json!({})))
+    });
+
+    server.handle_request::<Value, _, _>(types::RequestType::ResourcesList.as_str(), {
+        let project = project.clone();
+        move |_, cx| Task::ready(list_notes(&project, cx))
+    });
+
+    server.handle_request::<types::ResourcesReadParams, _, _>(
+        types::RequestType::ResourcesRead.as_str(),
+        {
+            let project = project.clone();
+            move |params, cx| read_note(&project, params.uri, cx)
+        },
+    );
+
+    server.handle_request::<types::ResourcesSubscribeParams, _, _>(
+        types::RequestType::ResourcesSubscribe.as_str(),
+        {
+            let project = project.clone();
+            let subscribed_notes = subscribed_notes.clone();
+            move |params, cx| {
+                Task::ready(vault_snapshot(&project, cx).and_then(|snapshot| {
+                    let path = note_path(&snapshot, &params.uri)?;
+                    subscribed_notes.borrow_mut().insert(path);
+                    Ok(serde_json::json!({}))
+                }))
+            }
+        },
+    );
+
+    server.handle_request::<types::ResourcesUnsubscribeParams, _, _>(
+        types::RequestType::ResourcesUnsubscribe.as_str(),
+        {
+            let project = project.clone();
+            let subscribed_notes = subscribed_notes.clone();
+            move |params, cx| {
+                Task::ready(vault_snapshot(&project, cx).and_then(|snapshot| {
+                    let path = note_path(&snapshot, &params.uri)?;
+                    subscribed_notes.borrow_mut().remove(&path);
+                    Ok(serde_json::json!({}))
+                }))
+            }
+        },
+    );
+
+    server.handle_request::<Value, _, _>(types::RequestType::ListTools.as_str(), |_, cx| {
+        let registry = ToolRegistry::global(cx);
+        let tools = TOOLS
+            .iter()
+            .filter_map(|name| registry.tool(name))
+            .map(|tool| types::Tool {
+                name: tool.name(),
+                description: Some(tool.description()),
+                input_schema: tool.input_schema(LanguageModelToolSchemaFormat::JsonSchema),
+            })
+            .collect();
+        Task::ready(Ok(types::ListToolsResponse {
+            tools,
+            next_cursor: None,
+            meta: None,
+        }))
+    });
+
+    server.handle_request::<types::CallToolParams, _, _>(types::RequestType::CallTool.as_str(), {
+        let project = project.clone();
+        move |params, cx| call_tool(&project, params, cx)
+    });
+
+    let notifier = server.notifier();
+    let subscription = cx.subscribe(&project, move |project, event: &project::Event, cx| {
+        if let project::Event::WorktreeUpdatedEntries(worktree_id, changes) = event {
+            if initialized.get() {
+                notify_notes_changed(
+                    &notifier,
+                    &project,
+                    *worktree_id,
+                    changes,
+                    &subscribed_notes,
+                    cx,
+                );
+            }
+        }
+    });
+
+    let server = server.run(transport, cx);
+    cx.spawn(async move |_| {
+        server.await;
+        drop(subscription);
+    })
+}
+
+/// Tells the client about the notes that changed in the vault's worktree.
+fn notify_notes_changed(
+    notifier: &ServerNotifier,
+    project: &Entity<Project>,
+    worktree_id: WorktreeId,
+    changes: &UpdatedEntriesSet,
+    subscribed_notes: &RefCell<HashSet<Arc<Path>>>,
+    cx: &App,
+) {
+    let Some(snapshot) = vault_snapshot(project, cx).ok() else {
+        return;
+    };
+    if snapshot.id() != worktree_id {
+        return;
+    }
+    let mut list_changed = false;
+    for (path, _, change) in changes.iter() {
+        if !is_note(path) {
+            continue;
+        }
+        match change {
+            PathChange::Added | PathChange::Removed | PathChange::Loaded => list_changed = true,
+            PathChange::Updated | PathChange::AddedOrUpdated => {
+                // The worktree doesn't know whether these notes are new.
+                list_changed |= *change == PathChange::AddedOrUpdated;
+                if !subscribed_notes.borrow().contains(path) {
+                    continue;
+                }
+                if let Some(uri) = note_uri(snapshot.abs_path(), path).log_err() {
+                    notifier
+                        .notify(
+                            types::NotificationType::ResourcesUpdated.as_str(),
+                            types::ResourcesUpdatedParams { uri },
+                        )
+                        .log_err();
+                }
+            }
+        }
+    }
+    if list_changed {
+        notifier
+            .notify(
+                types::NotificationType::ResourcesListChanged.as_str(),
+                serde_json::json!({}),
+            )
+            .log_err();
+    }
+}
+
+fn list_notes(project: &Entity<Project>, cx: &App) -> Result<types::ResourcesListResponse> {
+    let snapshot = vault_snapshot(project, cx)?;
+    let resolver = NoteResolver::new(snapshot.files(false, 0).map(|entry| entry.path.clone()));
+    let resources = resolver
+        .notes()
+        .map(|path| {
+            Ok(types::Resource {
+                uri: note_uri(snapshot.abs_path(), path)?,
+                name: resolver.link_target(path),
+                description: None,
+                mime_type: Some(NOTE_MIME_TYPE.into()),
+            })
+        })
+        .collect::<Result<_>>()?;
+    Ok(types::ResourcesListResponse {
+        resources,
+        next_cursor: None,
+        meta: None,
+    })
+}
+
+fn read_note(
+    project: &Entity<Project>,
+    uri: Url,
+    cx: &mut App,
+) -> Task<Result<types::ResourcesReadResponse>> {
+    let abs_path = match vault_snapshot(project, cx)
+        .and_then(|snapshot| Ok(snapshot.abs_path().join(note_path(&snapshot, &uri)?)))
+    {
+        Ok(abs_path) => abs_path,
+        Err(error) => return Task::ready(Err(error)),
+    };
+    let fs = project.read(cx).fs().clone();
+    cx.background_spawn(async move {
+        let text = fs.load(&abs_path).await?;
+        Ok(types::ResourcesReadResponse {
+            contents: vec![types::ResourceContentsType::Text(
+                types::TextResourceContents {
+                    uri,
+                    mime_type: Some(NOTE_MIME_TYPE.into()),
+                    text,
+                },
+            )],
+            meta: None,
+        })
+    })
+}
+
+/// Runs one of the exposed assistant tools. Failures of the tool itself are
+/// reported to the client as an error result, rather than as a protocol error.
+fn call_tool(
+    project: &Entity<Project>,
+    params: types::CallToolParams,
+    cx: &mut App,
+) -> Task<Result<types::CallToolResponse>> {
+    let Some(tool) = TOOLS
+        .contains(&params.name.as_str())
+        .then(|| ToolRegistry::global(cx).tool(&params.name))
+        .flatten()
+    else {
+        return Task::ready(Err(anyhow!("unknown tool {:?}", params.name)));
+    };
+    let input = Value::Object(params.arguments.unwrap_or_default().into_iter().collect());
+    let action_log = cx.new(|_| ActionLog::new(project.clone()));
+    let task = tool.run(input, &[], project.clone(), action_log, cx);
+    cx.background_spawn(async move {
+        let (text, is_error) = match task.await {
+            Ok(output) => (output, false),
+            Err(error) => (format!("{error:#}"), true),
+        };
+        Ok(types::CallToolResponse {
+            content: vec![types::ToolResponseContent::Text { text }],
+            is_error: Some(is_error),
+            meta: None,
+        })
+    })
+}
+
+fn vault_snapshot(project: &Entity<Project>, cx: &App) -> Result<Snapshot> {
+    let worktree = project
+        .read(cx)
+        .visible_worktrees(cx)
+        .next()
+        .context("no folder is open in the project")?;
+    Ok(worktree.read(cx).snapshot())
+}
+
+fn note_uri(root: &Path, path: &Path) -> Result<Url> {
+    Url::from_file_path(root.join(path)).map_err(|_| anyhow!("invalid note path {path:?}"))
+}
+
+/// Returns the path of the note at `uri`, relative to the vault root.
+fn note_path(snapshot: &Snapshot, uri: &Url) -> Result<Arc<Path>> {
+    let abs_path = uri
+        .to_file_path()
+        .map_err(|_| anyhow!("{uri} is not a file URI"))?;
+    let path = abs_path
+        .strip_prefix(snapshot.abs_path())
+        .with_context(|| format!("{uri} is not in the vault"))?;
+    let entry = snapshot
+        .entry_for_path(path)
+        .filter(|entry| entry.is_file() && is_note(&entry.path))
+        .with_context(|| format!("{uri} is not a note in the vault"))?;
+    Ok(entry.path.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    use context_server::client::{Client, ContextServerId};
+    use context_server::protocol::ModelContextProtocol;
+    use context_server::transport::ChannelTransport;
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use http_client::FakeHttpClient;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use util::path;
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            language::init(cx);
+            Project::init_settings(cx);
+            assistant_tools::init(FakeHttpClient::with_404_response(), cx);
+        });
+    }
+
+    fn uri(path: &str) -> Url {
+        Url::from_file_path(path).unwrap()
+    }
+
+    #[gpui::test]
+    async fn test_vault_mcp_server(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/vault"),
+            json!({
+                "Garden.md": "# Garden\nPlant [[Tomatoes]] after the frost.\n",
+                "Tomatoes.md": "# Tomatoes\nWater deeply.\n",
+                "image.png": "",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), [path!("/vault").as_ref()], cx).await;
+
+        let (server_transport, client_transport) = ChannelTransport::pair();
+        let _server = cx.update(|cx| serve(project.clone(), Arc::new(server_transport), cx));
+        cx.run_until_parked();
+        let client = Client::from_transport(
+            ContextServerId("vault".into()),
+            "zed".into(),
+            Arc::new(client_transport),
+            cx.to_async(),
+        );
+        let protocol = ModelContextProtocol::new(client)
+            .initialize(types::Implementation {
+                name: "test".into(),
+                version: "1.0".into(),
+            })
+            .await
+            .unwrap();
+
+        let notifications = Arc::new(Mutex::new(Vec::new()));
+        for method in [
+            types::NotificationType::ResourcesUpdated.as_str(),
+            types::NotificationType::ResourcesListChanged.as_str(),
+        ] {
+            let notifications = notifications.clone();
+            protocol.on_notification(method, move |params, _| {
+                notifications.lock().unwrap().push((method, params));
+            });
+        }
+
+        let resources = protocol.list_resources().await.unwrap().resources;
+        assert_eq!(
+            resources
+                .iter()
+                .map(|resource| (resource.name.as_str(), resource.uri.clone()))
+                .collect::<Vec<_>>(),
+            [
+                ("Garden", uri(path!("/vault/Garden.md"))),
+                ("Tomatoes", uri(path!("/vault/Tomatoes.md"))),
+            ]
+        );
+
+        let contents = protocol
+            .read_resource(uri(path!("/vault/Tomatoes.md")))
+            .await
+            .unwrap()
+            .contents;
+        let [types::ResourceContentsType::Text(contents)] = contents.as_slice() else {
+            panic!("expected text contents, got {contents:?}");
+        };
+        assert_eq!(contents.text, "# Tomatoes\nWater deeply.\n");
+        assert!(
+            protocol
+                .read_resource(uri(path!("/vault/image.png")))
+                .await
+                .is_err()
+        );
+        assert!(
+            protocol
+                .read_resource(uri(path!("/elsewhere/Note.md")))
+                .await
+                .is_err()
+        );
+
+        // Only subscribed notes send updates.
+        protocol
+            .subscribe_resource(uri(path!("/vault/Tomatoes.md")))
+            .await
+            .unwrap();
+        for (path, text) in [
+            (
+                path!("/vault/Tomatoes.md"),
+                "# Tomatoes\nWater every morning.\n",
+            ),
+            (
+                path!("/vault/Garden.md"),
+                "# Garden\nPlant after the frost.\n",
+            ),
+        ] {
+            fs.save(path.as_ref(), &text.into(), Default::default())
+                .await
+                .unwrap();
+        }
+        cx.run_until_parked();
+        assert_eq!(
+            notifications.lock().unwrap().drain(..).collect::<Vec<_>>(),
+            [(
+                types::NotificationType::ResourcesUpdated.as_str(),
+                json!({ "uri": uri(path!("/vault/Tomatoes.md")) })
+            )]
+        );
+
+        let tools = protocol.list_tools().await.unwrap().tools;
+        assert_eq!(
+            tools
+                .iter()
+                .map(|tool| tool.name.as_str())
+                .collect::<Vec<_>>(),
+            TOOLS
+        );
+
+        let response = protocol
+            .run_tool(
+                "list_backlinks",
+                Some(
+                    [("name".to_string(), json!("Garden"))]
+                        .into_iter()
+                        .collect(),
+                ),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.is_error, Some(false));
+
+        let response = protocol
+            .run_tool(
+                "create_note",
+                Some(
+                    [
+                        ("title".to_string(), json!("Peppers")),
+                        ("body".to_string(), json!("Links to [[Garden]].")),
+                    ]
+                    .into_iter()
+                    .collect(),
+                ),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.is_error, Some(false), "{:?}", response.content);
+        assert_eq!(
+            fs.load(path!("/vault/Peppers.md").as_ref()).await.unwrap(),
+            "Links to [[Garden]].\n"
+        );
+        cx.run_until_parked();
+        assert_eq!(
+            notifications.lock().unwrap().drain(..).collect::<Vec<_>>(),
+            [(
+                types::NotificationType::ResourcesListChanged.as_str(),
+                json!({})
+            )]
+        );
+
+        let response = protocol
+            .run_tool(
+                "list_backlinks",
+                Some(
+                    [("name".to_string(), json!("Garden"))]
+                        .into_iter()
+                        .collect(),
+                ),
+            )
+            .await
+            .unwrap();
+        let [types::ToolResponseContent::Text { text }] = response.content.as_slice() else {
+            panic!("expected text content, got {:?}", response.content);
+        };
+        assert!(
+            text.contains("Peppers.md:1: Links to [[Garden]]."),
+            "{text}"
+        );
+
+        // Tools other than the vault ones aren't exposed.
+        assert!(protocol.run_tool("bash", None).await.is_err());
+    }
+}
@@ -0,0 +1,223 @@
+use std::ops::Range;
+
+use semantic_index::TextToEmbed;
+use vault::Frontmatter;
+
+/// Sections longer than this are split into several chunks at paragraph
+/// boundaries, so that each passage stays focused on one topic.
+const MAX_CHUNK_LEN: usize = 1500;
+
+/// A passage of a note, embedded on its own.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NoteChunk {
+    /// The headings the passage is nested under, outermost first.
+    pub headings: Vec<String>,
+    /// The byte range of the passage in the note.
+    pub range: Range<usize>,
+    /// The text that gets embedded: the note's title and frontmatter, the
+    /// heading trail and the passage itself.
+    pub text: String,
+    pub digest: [u8; 32],
+}
+
+/// Splits a note into one chunk per heading section, giving every chunk the
+/// note's title and frontmatter as context.
+pub fn chunk_note(title: &str, text: &str) -> Vec<NoteChunk> {
+    let frontmatter = Frontmatter::parse(text);
+    let body_start = frontmatter
+        .as_ref()
+        .map_or(0, |frontmatter| frontmatter.range.end);
+
+    let mut context = title.to_string();
+    if let Some(frontmatter) = &frontmatter {
+        for (key, value) in frontmatter.properties() {
+            let value = value.display();
+            if !value.is_empty() {
+                context.push_str(&format!("\n{key}: {value}"));
+            }
+        }
+    }
+
+    let mut sections = Vec::new();
+    let mut trail: Vec<(usize, String)> = Vec::new();
+    let mut section_start = body_start;
+    for heading in vault::headings(text) {
+        if heading.range.start < body_start {
+            continue;
+        }
+        sections.push((
+            trail
+                .iter()
+                .map(|(_, text)| text.clone())
+                .collect::<Vec<_>>(),
+            section_start..heading.range.start,
+        ));
+        while trail
+            .last()
+            .is_some_and(|(level, _)| *level >= heading.level)
+        {
+            trail.pop();
+        }
+        trail.push((heading.level, heading.text));
+        section_start = heading.range.start;
+    }
+    sections.push((
+        trail.into_iter().map(|(_, text)| text).collect(),
+        section_start..text.len(),
+    ));
+
+    let mut chunks = Vec::new();
+    for (headings, range) in sections {
+        for range in split_section(text, range) {
+            let passage = text[range.clone()].trim();
+            if passage.is_empty() {
+                continue;
+            }
+            let chunk_text = if headings.is_empty() {
+                format!("{context}\n\n{passage}")
+            } else {
+                format!("{context}\n{}\n\n{passage}", headings.join(" > "))
+            };
+            let digest = TextToEmbed::new(&chunk_text).digest;
+            chunks.push(NoteChunk {
+                headings: headings.clone(),
+                range,
+                text: chunk_text,
+                digest,
+            });
+        }
+    }
+
+    // A note with nothing but frontmatter is still worth finding.
+    if chunks.is_empty() && frontmatter.is_some() {
+        let digest = TextToEmbed::new(&context).digest;
+        chunks.push(NoteChunk {
+            headings: Vec::new(),
+            range: 0..body_start,
+            text: context,
+            digest,
+        });
+    }
+    chunks
+}
+
+/// Splits `range` at blank lines into pieces of at most `MAX_CHUNK_LEN`
+/// bytes, unless a single paragraph is longer than that.
+fn split_section(text: &str, range: Range<usize>) -> Vec<Range<usize>> {
+    if range.len() <= MAX_CHUNK_LEN {
+        return vec![range];
+    }
+
+    let mut pieces = Vec::new();
+    let mut piece_start = range.start;
+    let mut offset = range.start;
+    let mut last_paragraph_end = None;
+    for line in text[range.clone()].split_inclusive('\n') {
+        offset += line.len();
+        if !line.trim().is_empty() {
+            continue;
+        }
+        if offset - piece_start > MAX_CHUNK_LEN {
+            if let Some(paragraph_end) = last_paragraph_end.take() {
+                pieces.push(piece_start..paragraph_end);
+                piece_start = paragraph_end;
+            }
+        }
+        last_paragraph_end = Some(offset);
+    }
+    if range.end - piece_start > MAX_CHUNK_LEN {
+        if let Some(paragraph_end) = last_paragraph_end.filter(|end| *end > piece_start) {
+            pieces.push(piece_start..paragraph_end);
+            piece_start = paragraph_end;
+        }
+    }
+    pieces.push(piece_start..range.end);
+    pieces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_chunk_note_by_heading() {
+        let text = indoc! {"
+            ---
+            tags: [garden, balcony]
+            ---
+            Notes from the first season.
+
+            # Tomatoes
+            Cherry varieties did best.
+
+            ## Watering
+            Every morning in July.
+
+            # Herbs
+
+            Basil bolted early.
+        "};
+        let chunks = chunk_note("Balcony Garden", text);
+
+        assert_eq!(
+            chunks
+                .iter()
+                .map(|chunk| (chunk.headings.join(" > "), &text[chunk.range.clone()]))
+                .collect::<Vec<_>>(),
+            [
+                ("".to_string(), "Notes from the first season.\n\n"),
+                (
+                    "Tomatoes".to_string(),
+                    "# Tomatoes\nCherry varieties did best.\n\n"
+                ),
+                (
+                    "Tomatoes > Watering".to_string(),
+                    "## Watering\nEvery morning in July.\n\n"
+                ),
+                ("Herbs".to_string(), "# Herbs\n\nBasil bolted early.\n"),
+            ]
+        );
+        assert_eq!(
+            chunks[2].text,
+            indoc! {"
+                Balcony Garden
+                tags: garden, balcony
+                Tomatoes > Watering
+
+                ## Watering
+                Every morning in July."}
+        );
+    }
+
+    #[test]
+    fn test_chunk_long_section() {
+        let paragraph = "word ".repeat(100);
+        let text = format!("# Long\n{}", format!("{paragraph}\n\n").repeat(8));
+        let chunks = chunk_note("Long", &text);
+
+        assert!(chunks.len() > 1);
+        assert!(
+            chunks
+                .iter()
+                .all(|chunk| chunk.range.len() <= MAX_CHUNK_LEN)
+        );
+        assert!(chunks.iter().all(|chunk| chunk.headings == ["Long"]));
+        assert_eq!(chunks.first().unwrap().range.start, 0);
+        assert_eq!(chunks.last().unwrap().range.end, text.len());
+        assert!(
+            chunks
+                .windows(2)
+                .all(|pair| pair[0].range.end == pair[1].range.start)
+        );
+    }
+
+    #[test]
+    fn test_chunk_frontmatter_only_note() {
+        let text = "---\naliases: [Reading list]\n---\n";
+        let chunks = chunk_note("Books", text);
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].text, "Books\naliases: Reading list");
+    }
+}
@@ -0,0 +1,569 @@
+use std::cmp::Ordering;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{Context as _, Result};
+use collections::HashMap;
+use fs::{Fs, MTime};
+use futures::StreamExt as _;
+use futures::future::{FutureExt as _, Shared};
+use gpui::{
+    App, AppContext as _, AsyncApp, BackgroundExecutor, Context, SharedString, Task, WeakEntity,
+};
+use heed::types::SerdeBincode;
+use semantic_index::{Embedding, EmbeddingProvider, TextToEmbed};
+use util::ResultExt as _;
+
+use crate::note_chunking::chunk_note;
+
+/// The number of characters shown in a search result's preview.
+const PREVIEW_LEN: usize = 160;
+
+type EmbeddingDb = heed::Database<SerdeBincode<[u8; 32]>, SerdeBincode<Embedding>>;
+
+/// Persists embeddings by the digest of the text they were computed from, so
+/// restarting doesn't mean re-embedding the whole vault.
+#[derive(Clone)]
+pub struct EmbeddingCache {
+    connection: Shared<Task<Option<(heed::Env, EmbeddingDb)>>>,
+    executor: BackgroundExecutor,
+}
+
+impl EmbeddingCache {
+    pub fn open(db_path: PathBuf, cx: &App) -> Self {
+        let connection = cx.background_spawn(async move {
+            let open = || -> Result<_> {
+                std::fs::create_dir_all(&db_path)?;
+                let db_connection = unsafe {
+                    heed::EnvOpenOptions::new()
+                        .map_size(1024 * 1024 * 1024)
+                        .max_dbs(1)
+                        .open(&db_path)
+                }?;
+                let mut txn = db_connection.write_txn()?;
+                let db = db_connection.create_database(&mut txn, Some("embeddings"))?;
+                txn.commit()?;
+                Ok((db_connection, db))
+            };
+            open()
+                .context("opening vault search embedding cache")
+                .log_err()
+        });
+        Self {
+            connection: connection.shared(),
+            executor: cx.background_executor().clone(),
+        }
+    }
+
+    async fn get(&self, digests: Vec<[u8; 32]>) -> Vec<Option<Embedding>> {
+        let Some((db_connection, db)) = self.connection.clone().await else {
+            return vec![None; digests.len()];
+        };
+        self.executor
+            .spawn(async move {
+                let Some(txn) = db_connection.read_txn().log_err() else {
+                    return vec![None; digests.len()];
+                };
+                digests
+                    .iter()
+                    .map(|digest| db.get(&txn, digest).log_err().flatten())
+                    .collect()
+            })
+            .await
+    }
+
+    async fn put(&self, embeddings: Vec<([u8; 32], Embedding)>) {
+        let Some((db_connection, db)) = self.connection.clone().await else {
+            return;
+        };
+        self.executor
+            .spawn(async move {
+                let mut txn = db_connection.write_txn()?;
+                for (digest, embedding) in &embeddings {
+                    db.put(&mut txn, digest, embedding)?;
+                }
+                txn.commit()?;
+                anyhow::Ok(())
+            })
+            .await
+            .log_err();
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Status {
+    Idle,
+    Indexing { remaining_notes: usize },
+    Error(SharedString),
+}
+
+/// A passage of a note that matched a search.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SearchResult {
+    /// The note's path, relative to the vault root.
+    pub path: Arc<Path>,
+    /// The headings the passage is nested under, outermost first.
+    pub headings: Vec<String>,
+    /// The row the passage starts at, which is its heading's row when the
+    /// passage starts a section.
+    pub row: u32,
+    pub preview: String,
+    pub score: f32,
+}
+
+struct IndexedNote {
+    mtime: MTime,
+    chunks: Vec<IndexedChunk>,
+}
+
+struct IndexedChunk {
+    headings: Vec<String>,
+    row: u32,
+    preview: String,
+    embedding: Embedding,
+}
+
+/// An embedding index of the notes in a vault, chunked by heading section.
+pub struct VaultIndex {
+    root: Arc<Path>,
+    fs: Arc<dyn Fs>,
+    embedding_provider: Arc<dyn EmbeddingProvider>,
+    embedding_cache: Option<EmbeddingCache>,
+    notes: Arc<HashMap<Arc<Path>, Arc<IndexedNote>>>,
+    status: Status,
+    refresh_task: Option<Shared<Task<()>>>,
+    refresh_again: bool,
+}
+
+impl VaultIndex {
+    pub fn new(
+        root: Arc<Path>,
+        fs: Arc<dyn Fs>,
+        embedding_provider: Arc<dyn EmbeddingProvider>,
+        embedding_cache: Option<EmbeddingCache>,
+    ) -> Self {
+        Self {
+            root,
+            fs,
+            embedding_provider,
+            embedding_cache,
+            notes: Default::default(),
+            status: Status::Idle,
+            refresh_task: None,
+            refresh_again: false,
+        }
+    }
+
+    pub fn root(&self) -> &Arc<Path> {
+        &self.root
+    }
+
+    pub fn status(&self) -> &Status {
+        &self.status
+    }
+
+    /// Indexes notes that were added or changed since the last refresh and
+    /// drops the ones that were deleted.
+    ///
+    /// When a refresh is already in progress, another one is started after it
+    /// finishes, so changes made in the meantime aren't missed.
+    pub fn refresh(&mut self, cx: &mut Context<Self>) {
+        if self.refresh_task.is_some() {
+            self.refresh_again = true;
+            return;
+        }
+        let fs = self.fs.clone();
+        let root = self.root.clone();
+        let refresh_task = cx.spawn(async move |this, cx| {
+            let result = Self::refresh_notes(this.clone(), fs, root, cx).await;
+            this.update(cx, |this, cx| {
+                this.refresh_task = None;
+                this.status = match result {
+                    Ok(()) => Status::Idle,
+                    Err(error) => {
+                        log::error!("failed to index vault {:?}: {error:#}", this.root);
+                        Status::Error(format!("{error:#}").into())
+                    }
+                };
+                cx.notify();
+                if std::mem::take(&mut this.refresh_again) {
+                    this.refresh(cx);
+                }
+            })
+            .ok();
+        });
+        self.refresh_task = Some(refresh_task.shared());
+    }
+
+    /// Resolves once the refresh in progress, if any, has finished.
+    pub fn refresh_finished(&self) -> impl Future<Output = ()> + use<> {
+        let refresh_task = self.refresh_task.clone();
+        async move {
+            if let Some(refresh_task) = refresh_task {
+                refresh_task.await;
+            }
+        }
+    }
+
+    async fn refresh_notes(
+        this: WeakEntity<Self>,
+        fs: Arc<dyn Fs>,
+        root: Arc<Path>,
+        cx: &mut AsyncApp,
+    ) -> Result<()> {
+        let mut changed_notes = Vec::new();
+        let mut notes = HashMap::default();
+        let previous_notes = this.read_with(cx, |this, _| this.notes.clone())?;
+        for abs_path in note_paths(fs.as_ref(), &root).await? {
+            let Some(metadata) = fs.metadata(&abs_path).await? else {
+                continue;
+            };
+            let path: Arc<Path> = abs_path.strip_prefix(&root)?.into();
+            if let Some(note) = previous_notes.get(&path) {
+                notes.insert(path.clone(), note.clone());
+                if note.mtime == metadata.mtime {
+                    continue;
+                }
+            }
+            changed_notes.push((path, abs_path, metadata.mtime));
+        }
+
+        let (embedding_provider, embedding_cache) = this.update(cx, |this, cx| {
+            this.notes = Arc::new(notes);
+            this.status = Status::Indexing {
+                remaining_notes: changed_notes.len(),
+            };
+            cx.notify();
+            (
+                this.embedding_provider.clone(),
+                this.embedding_cache.clone(),
+            )
+        })?;
+
+        let mut remaining_notes = changed_notes.len();
+        for (path, abs_path, mtime) in changed_notes {
+            remaining_notes -= 1;
+            let Some(text) = fs.load(&abs_path).await.log_err() else {
+                continue;
+            };
+            let note = index_note(
+                &path,
+                &text,
+                mtime,
+                embedding_provider.as_ref(),
+                embedding_cache.as_ref(),
+            )
+            .await
+            .with_context(|| format!("indexing {path:?}"))?;
+
+            this.update(cx, |this, cx| {
+                Arc::make_mut(&mut this.notes).insert(path, Arc::new(note));
+                this.status = Status::Indexing { remaining_notes };
+                cx.notify();
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Returns the passages most similar in meaning to `query`, best first.
+    pub fn search(&self, query: String, limit: usize, cx: &App) -> Task<Result<Vec<SearchResult>>> {
+        let notes = self.notes.clone();
+        let embedding_provider = self.embedding_provider.clone();
+        cx.background_spawn(async move {
+            let query = [TextToEmbed::new(&query)];
+            let query_embedding = embedding_provider
+                .embed(&query)
+                .await?
+                .pop()
+                .context("no embedding returned for the search query")?;
+
+            let mut results = Vec::new();
+            for (path, note) in notes.iter() {
+                for chunk in &note.chunks {
+                    let (score, _) =
+                        query_embedding.similarity(std::slice::from_ref(&chunk.embedding));
+                    results.push(SearchResult {
+                        path: path.clone(),
+                        headings: chunk.headings.clone(),
+                        row: chunk.row,
+                        preview: chunk.preview.clone(),
+                        score,
+                    });
+                }
+            }
+            results.sort_by(|a, b| {
+                b.score
+                    .partial_cmp(&a.score)
+                    .unwrap_or(Ordering::Equal)
+                    .then_with(|| a.path.cmp(&b.path))
+                    .then_with(|| a.row.cmp(&b.row))
+            });
+            results.truncate(limit);
+            Ok(results)
+        })
+    }
+}
+
+async fn index_note(
+    path: &Path,
+    text: &str,
+    mtime: MTime,
+    embedding_provider: &dyn EmbeddingProvider,
+    embedding_cache: Option<&EmbeddingCache>,
+) -> Result<IndexedNote> {
+    let title = vault::Frontmatter::parse(text)
+        .and_then(|frontmatter| frontmatter.get_text("title").map(ToString::to_string))
+        .or_else(|| {
+            path.file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+        })
+        .unwrap_or_default();
+    let chunks = chunk_note(&title, text);
+
+    let mut embeddings = match embedding_cache {
+        Some(cache) => {
+            cache
+                .get(chunks.iter().map(|chunk| chunk.digest).collect())
+                .await
+        }
+        None => vec![None; chunks.len()],
+    };
+    let missing = embeddings
+        .iter()
+        .enumerate()
+        .filter_map(|(ix, embedding)| embedding.is_none().then_some(ix))
+        .collect::<Vec<_>>();
+    let mut new_embeddings = Vec::new();
+    for batch in missing.chunks(embedding_provider.batch_size().max(1)) {
+        let texts = batch
+            .iter()
+            .map(|ix| TextToEmbed {
+                text: &chunks[*ix].text,
+                digest: chunks[*ix].digest,
+            })
+            .collect::<Vec<_>>();
+        let batch_embeddings = embedding_provider.embed(&texts).await?;
+        anyhow::ensure!(
+            batch_embeddings.len() == batch.len(),
+            "expected {} embeddings, got {}",
+            batch.len(),
+            batch_embeddings.len()
+        );
+        for (ix, embedding) in batch.iter().zip(batch_embeddings) {
+            new_embeddings.push((chunks[*ix].digest, embedding.clone()));
+            embeddings[*ix] = Some(embedding);
+        }
+    }
+    if let Some(cache) = embedding_cache {
+        if !new_embeddings.is_empty() {
+            cache.put(new_embeddings).await;
+        }
+    }
+
+    let chunks = chunks
+        .into_iter()
+        .zip(embeddings)
+        .filter_map(|(chunk, embedding)| {
+            Some(IndexedChunk {
+                row: text[..chunk.range.start].matches('\n').count() as u32,
+                preview: preview(&text[chunk.range.clone()]),
+                headings: chunk.headings,
+                embedding: embedding?,
+            })
+        })
+        .collect();
+    Ok(IndexedNote { mtime, chunks })
+}
+
+/// Returns the start of a passage on a single line, leaving out its heading.
+fn preview(passage: &str) -> String {
+    let passage = passage.trim_start();
+    let body = if passage.starts_with('#') {
+        passage.split_once('\n').map_or("", |(_, body)| body)
+    } else {
+        passage
+    };
+    let mut preview = body.split_whitespace().collect::<Vec<_>>().join(" ");
+    if let Some((end, _)) = preview.char_indices().nth(PREVIEW_LEN) {
+        preview.truncate(end);
+        preview.push('…');
+    }
+    preview
+}
+
+/// Returns the paths of all notes in the vault, skipping hidden folders such
+/// as `.git` and `.obsidian`.
+async fn note_paths(fs: &dyn Fs, root: &Path) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    let mut folders = vec![root.to_path_buf()];
+    while let Some(folder) = folders.pop() {
+        let mut entries = fs.read_dir(&folder).await?;
+        while let Some(entry) = entries.next().await {
+            let path = entry?;
+            let is_hidden = path
+                .file_name()
+                .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+            if is_hidden {
+                continue;
+            }
+            if fs.is_dir(&path).await {
+                folders.push(path);
+            } else if vault::is_note(&path) {
+                paths.push(path);
+            }
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering::SeqCst};
+
+    use fs::FakeFs;
+    use futures::future::BoxFuture;
+    use gpui::{AppContext as _, Entity, TestAppContext};
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use util::path;
+
+    /// Embeds texts as normalized bags of words, so that passages sharing
+    /// words with the query score highest.
+    #[derive(Default)]
+    struct WordEmbeddingProvider {
+        embedded_texts: AtomicUsize,
+    }
+
+    impl EmbeddingProvider for WordEmbeddingProvider {
+        fn embed<'a>(
+            &'a self,
+            texts: &'a [TextToEmbed<'a>],
+        ) -> BoxFuture<'a, Result<Vec<Embedding>>> {
+            self.embedded_texts.fetch_add(texts.len(), SeqCst);
+            let embeddings = texts
+                .iter()
+                .map(|text| {
+                    let mut embedding = vec![0.01; 64];
+                    for word in text.text.split(|c: char| !c.is_alphanumeric()) {
+                        if word.len() > 3 {
+                            let bucket = word.to_lowercase().bytes().fold(0usize, |hash, byte| {
+                                hash.wrapping_mul(31).wrapping_add(byte as usize)
+                            });
+                            embedding[bucket % 64] += 1.;
+                        }
+                    }
+                    Embedding::new(embedding)
+                })
+                .collect();
+            futures::future::ready(Ok(embeddings)).boxed()
+        }
+
+        fn batch_size(&self) -> usize {
+            4
+        }
+    }
+
+    async fn search(
+        index: &Entity<VaultIndex>,
+        query: &str,
+        limit: usize,
+        cx: &mut TestAppContext,
+    ) -> Vec<String> {
+        index
+            .read_with(cx, |index, cx| index.search(query.into(), limit, cx))
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|result| {
+                format!(
+                    "{}:{} {} - {}",
+                    result.path.display(),
+                    result.row,
+                    result.headings.join(" > "),
+                    result.preview
+                )
+            })
+            .collect()
+    }
+
+    #[gpui::test]
+    async fn test_vault_index(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/vault"),
+            json!({
+                ".obsidian": {
+                    "workspace.md": "Tomatoes tomatoes tomatoes",
+                },
+                "Garden.md": "---\ntags: [outdoors]\n---\n# Tomatoes\nPlant tomatoes after the frost.\n\n## Watering\nWater deeply every morning.\n",
+                "Projects": {
+                    "Bookshelf.md": "# Plan\nSand the shelves, then varnish twice.\n",
+                },
+                "image.png": "",
+            }),
+        )
+        .await;
+
+        let provider = Arc::new(WordEmbeddingProvider::default());
+        let index = cx.new(|_| {
+            VaultIndex::new(
+                Path::new(path!("/vault")).into(),
+                fs.clone(),
+                provider.clone(),
+                None,
+            )
+        });
+        index.update(cx, |index, cx| index.refresh(cx));
+        cx.executor().run_until_parked();
+        index.read_with(cx, |index, _| assert_eq!(index.status(), &Status::Idle));
+        assert_eq!(provider.embedded_texts.load(SeqCst), 3);
+
+        assert_eq!(
+            search(&index, "how often to water", 1, cx).await,
+            ["Garden.md:6 Tomatoes > Watering - Water deeply every morning."]
+        );
+        assert_eq!(
+            search(&index, "varnish shelves", 1, cx).await,
+            [format!(
+                "{}:0 Plan - Sand the shelves, then varnish twice.",
+                Path::new("Projects/Bookshelf.md").display()
+            )]
+        );
+
+        // Only changed notes get embedded again, and deleted ones are dropped.
+        fs.save(
+            path!("/vault/Projects/Bookshelf.md").as_ref(),
+            &"# Plan\nSand the shelves, then paint them blue.\n".into(),
+            Default::default(),
+        )
+        .await
+        .unwrap();
+        fs.remove_file(path!("/vault/Garden.md").as_ref(), Default::default())
+            .await
+            .unwrap();
+        index.update(cx, |index, cx| index.refresh(cx));
+        cx.executor().run_until_parked();
+        assert_eq!(provider.embedded_texts.load(SeqCst), 4);
+        assert_eq!(
+            search(&index, "paint", 2, cx).await,
+            [format!(
+                "{}:0 Plan - Sand the shelves, then paint them blue.",
+                Path::new("Projects/Bookshelf.md").display()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_preview() {
+        assert_eq!(
+            preview("## Watering\nWater   deeply\nevery morning.\n"),
+            "Water deeply every morning."
+        );
+        assert_eq!(
+            preview(&"word ".repeat(50)).chars().count(),
+            PREVIEW_LEN + 1
+        );
+    }
+}
@@ -0,0 +1,173 @@
+//! Semantic search over the notes in a vault, using a local embedding model.
+
+mod note_chunking;
+mod vault_index;
+mod vault_search_modal;
+
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{Result, anyhow};
+use collections::HashMap;
+use fs::Fs;
+use gpui::{App, AppContext as _, Context, Entity, Global, Task, Window, actions};
+use schemars::JsonSchema;
+use semantic_index::{OllamaEmbeddingModel, OllamaEmbeddingProvider};
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+use workspace::Workspace;
+
+pub use note_chunking::{NoteChunk, chunk_note};
+pub use vault_index::{EmbeddingCache, SearchResult, Status, VaultIndex};
+pub use vault_search_modal::VaultSearchModal;
+
+actions!(vault_search, [Toggle]);
+
+/// The Ollama model used to embed notes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum EmbeddingModel {
+    #[default]
+    NomicEmbedText,
+    MxbaiEmbedLarge,
+}
+
+impl EmbeddingModel {
+    fn id(&self) -> &'static str {
+        match self {
+            EmbeddingModel::NomicEmbedText => "nomic-embed-text",
+            EmbeddingModel::MxbaiEmbedLarge => "mxbai-embed-large",
+        }
+    }
+}
+
+/// Settings for semantic search over a vault.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct VaultSearchSettings {
+    /// The Ollama model used to embed notes. Embeddings are computed by a
+    /// local Ollama server, so notes never leave the machine.
+    ///
+    /// Default: nomic-embed-text
+    pub model: Option<EmbeddingModel>,
+    /// The maximum number of passages to show.
+    ///
+    /// Default: 50
+    pub max_results: Option<usize>,
+}
+
+impl settings::Settings for VaultSearchSettings {
+    const KEY: Option<&'static str> = Some("vault_search");
+
+    type FileContent = Self;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _: &mut App) -> Result<Self> {
+        sources.json_merge()
+    }
+}
+
+/// The vault indices and embedding caches that have been created so far,
+/// which live for as long as the app so reopening the search is instant.
+#[derive(Default)]
+struct VaultSearch {
+    indices: HashMap<(Arc<Path>, EmbeddingModel), Entity<VaultIndex>>,
+    embedding_caches: HashMap<EmbeddingModel, EmbeddingCache>,
+}
+
+impl Global for VaultSearch {}
+
+pub fn init(cx: &mut App) {
+    VaultSearchSettings::register(cx);
+
+    cx.observe_new(|workspace: &mut Workspace, _window, _cx| {
+        workspace.register_action(toggle);
+    })
+    .detach();
+}
+
+fn toggle(workspace: &mut Workspace, _: &Toggle, window: &mut Window, cx: &mut Context<Workspace>) {
+    let Some(root) = workspace
+        .visible_worktrees(cx)
+        .next()
+        .map(|worktree| worktree.read(cx).abs_path())
+    else {
+        workspace.show_error(&anyhow!("Open a vault folder to search it"), cx);
+        return;
+    };
+    let fs = workspace.app_state().fs.clone();
+    let index = vault_index(root, fs, cx);
+    let max_results = VaultSearchSettings::get_global(cx)
+        .max_results
+        .unwrap_or(50);
+    let workspace_handle = cx.entity().downgrade();
+    workspace.toggle_modal(window, cx, |window, cx| {
+        VaultSearchModal::new(index, workspace_handle, max_results, window, cx)
+    });
+}
+
+/// Searches the vault at `root` by meaning, first indexing any notes that
+/// changed since the last search.
+pub fn search(
+    root: Arc<Path>,
+    fs: Arc<dyn Fs>,
+    query: String,
+    limit: usize,
+    cx: &mut App,
+) -> Task<Result<Vec<SearchResult>>> {
+    let index = vault_index(root, fs, cx);
+    let refresh_finished = index.update(cx, |index, cx| {
+        index.refresh(cx);
+        index.refresh_finished()
+    });
+    cx.spawn(async move |cx| {
+        refresh_finished.await;
+        let search = index.read_with(cx, |index, cx| match index.status() {
+            Status::Error(error) => Task::ready(Err(anyhow!("failed to index the vault: {error}"))),
+            _ => index.search(query, limit, cx),
+        })?;
+        search.await
+    })
+}
+
+/// Returns the index for the vault at `root`, creating it on first use.
+fn vault_index(root: Arc<Path>, fs: Arc<dyn Fs>, cx: &mut App) -> Entity<VaultIndex> {
+    let model = VaultSearchSettings::get_global(cx)
+        .model
+        .unwrap_or_default();
+    if let Some(index) = cx
+        .try_global::<VaultSearch>()
+        .and_then(|vault_search| vault_search.indices.get(&(root.clone(), model)))
+    {
+        return index.clone();
+    }
+
+    let embedding_provider = Arc::new(OllamaEmbeddingProvider::new(
+        cx.http_client(),
+        match model {
+            EmbeddingModel::NomicEmbedText => OllamaEmbeddingModel::NomicEmbedText,
+            EmbeddingModel::MxbaiEmbedLarge => OllamaEmbeddingModel::MxbaiEmbedLarge,
+        },
+    ));
+    let embedding_cache = match cx
+        .try_global::<VaultSearch>()
+        .and_then(|vault_search| vault_search.embedding_caches.get(&model))
+    {
+        Some(cache) => cache.clone(),
+        None => EmbeddingCache::open(
+            paths::embeddings_dir().join(format!("vault-search-{}.0.mdb", model.id())),
+            cx,
+        ),
+    };
+    let index = cx.new(|_| {
+        VaultIndex::new(
+            root.clone(),
+            fs,
+            embedding_provider,
+            Some(embedding_cache.clone()),
+        )
+    });
+
+    let vault_search = cx.default_global::<VaultSearch>();
+    vault_search.indices.insert((root, model), index.clone());
+    vault_search.embedding_caches.insert(model, embedding_cache);
+    index
+}
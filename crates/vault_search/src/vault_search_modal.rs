@@ -0,0 +1,281 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use editor::Editor;
+use gpui::{
+    App, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, Render, SharedString,
+    Subscription, Task, WeakEntity, Window, rems,
+};
+use picker::{Picker, PickerDelegate};
+use text::Point;
+use ui::{ListItem, ListItemSpacing, prelude::*};
+use util::ResultExt as _;
+use workspace::notifications::NotifyResultExt as _;
+use workspace::{ModalView, OpenOptions, OpenVisible, Workspace};
+
+use crate::vault_index::{SearchResult, Status, VaultIndex};
+
+/// How long to wait for typing to pause before embedding the query.
+const SEARCH_DEBOUNCE: Duration = Duration::from_millis(250);
+
+pub struct VaultSearchModal {
+    picker: Entity<Picker<VaultSearchDelegate>>,
+    _index_subscription: Subscription,
+}
+
+impl ModalView for VaultSearchModal {}
+
+impl EventEmitter<DismissEvent> for VaultSearchModal {}
+
+impl Focusable for VaultSearchModal {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for VaultSearchModal {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex().w(rems(40.)).child(self.picker.clone())
+    }
+}
+
+impl VaultSearchModal {
+    pub fn new(
+        index: Entity<VaultIndex>,
+        workspace: WeakEntity<Workspace>,
+        max_results: usize,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let delegate = VaultSearchDelegate {
+            modal: cx.entity().downgrade(),
+            workspace,
+            index: index.clone(),
+            max_results,
+            matches: Vec::new(),
+            selected_index: 0,
+            search_error: None,
+        };
+        let picker = cx.new(|cx| {
+            Picker::uniform_list(delegate, window, cx).max_height(Some(vh(0.75, window)))
+        });
+
+        // Search again once indexing finishes, since the results may have
+        // been computed from a partial index.
+        let mut was_indexing = false;
+        let index_subscription = cx.observe_in(&index, window, move |this, index, window, cx| {
+            let is_indexing = matches!(index.read(cx).status(), Status::Indexing { .. });
+            if was_indexing && !is_indexing {
+                this.picker
+                    .update(cx, |picker, cx| picker.refresh(window, cx));
+            }
+            was_indexing = is_indexing;
+            this.picker.update(cx, |_, cx| cx.notify());
+        });
+        index.update(cx, |index, cx| index.refresh(cx));
+
+        Self {
+            picker,
+            _index_subscription: index_subscription,
+        }
+    }
+}
+
+pub struct VaultSearchDelegate {
+    modal: WeakEntity<VaultSearchModal>,
+    workspace: WeakEntity<Workspace>,
+    index: Entity<VaultIndex>,
+    max_results: usize,
+    matches: Vec<SearchResult>,
+    selected_index: usize,
+    search_error: Option<SharedString>,
+}
+
+impl PickerDelegate for VaultSearchDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        "Search notes by meaning...".into()
+    }
+
+    fn no_matches_text(&self, _window: &mut Window, _cx: &mut App) -> Option<SharedString> {
+        match &self.search_error {
+            Some(error) => Some(error.clone()),
+            None => Some("No matching passages".into()),
+        }
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _cx: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        if query.trim().is_empty() {
+            self.matches.clear();
+            self.search_error = None;
+            return Task::ready(());
+        }
+
+        cx.spawn_in(window, async move |picker, cx| {
+            cx.background_executor().timer(SEARCH_DEBOUNCE).await;
+            let Some(search) = picker
+                .update(cx, |picker, cx| {
+                    let delegate = &picker.delegate;
+                    delegate
+                        .index
+                        .read(cx)
+                        .search(query, delegate.max_results, cx)
+                })
+                .log_err()
+            else {
+                return;
+            };
+            let results = search.await;
+            picker
+                .update(cx, |picker, cx| {
+                    let delegate = &mut picker.delegate;
+                    match results {
+                        Ok(matches) => {
+                            delegate.matches = matches;
+                            delegate.search_error = None;
+                        }
+                        Err(error) => {
+                            delegate.matches.clear();
+                            delegate.search_error =
+                                Some(format!("Search failed: {error:#}").into());
+                        }
+                    }
+                    delegate.selected_index = 0;
+                    cx.notify();
+                })
+                .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _secondary: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(result) = self.matches.get(self.selected_index) else {
+            return;
+        };
+        let abs_path = self.index.read(cx).root().join(&result.path);
+        let row = result.row;
+        let Some(open_task) = self
+            .workspace
+            .update(cx, |workspace, cx| {
+                workspace.open_abs_path(
+                    abs_path,
+                    OpenOptions {
+                        visible: Some(OpenVisible::None),
+                        ..Default::default()
+                    },
+                    window,
+                    cx,
+                )
+            })
+            .log_err()
+        else {
+            return;
+        };
+
+        let modal = self.modal.clone();
+        cx.spawn_in(window, async move |_, cx| {
+            let item = open_task.await.notify_async_err(cx)?;
+            if let Some(editor) = item.downcast::<Editor>() {
+                editor
+                    .downgrade()
+                    .update_in(cx, |editor, window, cx| {
+                        editor.go_to_singleton_buffer_point(Point::new(row, 0), window, cx);
+                    })
+                    .log_err();
+            }
+            modal.update(cx, |_, cx| cx.emit(DismissEvent)).ok()
+        })
+        .detach();
+    }
+
+    fn dismissed(&mut self, _window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        self.modal
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _window: &mut Window,
+        _cx: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let result = self.matches.get(ix)?;
+        let note_name = result
+            .path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let location = if result.headings.is_empty() {
+            note_name
+        } else {
+            format!("{note_name} › {}", result.headings.join(" › "))
+        };
+
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .child(
+                    v_flex().child(Label::new(location)).child(
+                        Label::new(result.preview.clone())
+                            .size(LabelSize::Small)
+                            .color(Color::Muted)
+                            .truncate(),
+                    ),
+                ),
+        )
+    }
+
+    fn render_footer(
+        &self,
+        _window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Option<AnyElement> {
+        let status = match self.index.read(cx).status() {
+            Status::Idle => return None,
+            Status::Indexing { remaining_notes } => {
+                format!("Indexing notes, {remaining_notes} left...")
+            }
+            Status::Error(error) => format!("Indexing failed: {error}"),
+        };
+        Some(
+            h_flex()
+                .w_full()
+                .px_3()
+                .py_1()
+                .border_t_1()
+                .border_color(cx.theme().colors().border_variant)
+                .child(
+                    Label::new(status)
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                )
+                .into_any_element(),
+        )
+    }
+}
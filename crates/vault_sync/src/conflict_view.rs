@@ -0,0 +1,232 @@
+use git::repository::RepoPath;
+use gpui::{
+    App, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, Render, Subscription,
+    WeakEntity, Window,
+};
+use ui::{ListItem, ListItemSpacing, prelude::*};
+use workspace::{ModalView, OpenOptions, Workspace};
+
+use crate::{Resolution, SyncStatus, VaultSync};
+
+/// A modal listing the notes a sync stopped on, with ways to resolve each
+/// one and then finish or abort the sync.
+pub struct VaultSyncConflicts {
+    vault_sync: Entity<VaultSync>,
+    workspace: WeakEntity<Workspace>,
+    focus_handle: FocusHandle,
+    /// The notes of the sync when the modal was opened, so resolved ones stay
+    /// listed.
+    conflicts: Vec<RepoPath>,
+    _subscription: Subscription,
+}
+
+impl VaultSyncConflicts {
+    pub fn new(
+        vault_sync: Entity<VaultSync>,
+        workspace: WeakEntity<Workspace>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let conflicts = match vault_sync.read(cx).status() {
+            SyncStatus::Conflicts(conflicts) => conflicts.clone(),
+            _ => Vec::new(),
+        };
+        let subscription = cx.observe(&vault_sync, |this, vault_sync, cx| {
+            if let SyncStatus::Idle = vault_sync.read(cx).status() {
+                cx.emit(DismissEvent);
+            } else if let SyncStatus::Conflicts(conflicts) = vault_sync.read(cx).status() {
+                // Continuing can stop on the next local commit's conflicts.
+                for conflict in conflicts {
+                    if !this.conflicts.contains(conflict) {
+                        this.conflicts.push(conflict.clone());
+                    }
+                }
+            }
+            cx.notify();
+        });
+        let focus_handle = cx.focus_handle();
+        window.focus(&focus_handle);
+        Self {
+            vault_sync,
+            workspace,
+            focus_handle,
+            conflicts,
+            _subscription: subscription,
+        }
+    }
+
+    fn is_unresolved(&self, path: &RepoPath, cx: &App) -> bool {
+        match self.vault_sync.read(cx).status() {
+            SyncStatus::Conflicts(conflicts) => conflicts.contains(path),
+            _ => false,
+        }
+    }
+
+    fn resolve(&mut self, path: RepoPath, resolution: Resolution, cx: &mut Context<Self>) {
+        self.vault_sync
+            .update(cx, |vault_sync, cx| {
+                vault_sync.resolve(path, resolution, cx)
+            })
+            .detach_and_log_err(cx);
+    }
+
+    fn open(&mut self, path: &RepoPath, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(abs_path) = self.vault_sync.read(cx).abs_path(path, cx) else {
+            return;
+        };
+        self.workspace
+            .update(cx, |workspace, cx| {
+                workspace
+                    .open_abs_path(abs_path, OpenOptions::default(), window, cx)
+                    .detach_and_log_err(cx);
+            })
+            .ok();
+        cx.emit(DismissEvent);
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, _: &mut Window, cx: &mut Context<Self>) {
+        self.vault_sync
+            .update(cx, |vault_sync, cx| vault_sync.continue_sync(cx));
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, _: &mut Window, cx: &mut Context<Self>) {
+        cx.emit(DismissEvent);
+    }
+}
+
+impl EventEmitter<DismissEvent> for VaultSyncConflicts {}
+
+impl ModalView for VaultSyncConflicts {}
+
+impl Focusable for VaultSyncConflicts {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for VaultSyncConflicts {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let status = self.vault_sync.read(cx).status().clone();
+        let syncing = status == SyncStatus::Syncing;
+        let body = if self.conflicts.is_empty() {
+            Label::new("There are no sync conflicts to resolve")
+                .color(Color::Muted)
+                .into_any_element()
+        } else {
+            v_flex()
+                .children(self.conflicts.iter().enumerate().map(|(ix, path)| {
+                    let unresolved = self.is_unresolved(path, cx);
+                    let resolve_button = |id: &'static str, label, resolution| {
+                        let path = path.clone();
+                        Button::new(id, label)
+                            .label_size(LabelSize::Small)
+                            .disabled(!unresolved || syncing)
+                            .on_click(cx.listener(move |this, _, _, cx| {
+                                this.resolve(path.clone(), resolution, cx)
+                            }))
+                    };
+                    ListItem::new(ix)
+                        .inset(true)
+                        .spacing(ListItemSpacing::Sparse)
+                        .child(
+                            v_flex()
+                                .child(Label::new(path.display().to_string()))
+                                .child(
+                                    Label::new(if unresolved {
+                                        "Edited on both sides"
+                                    } else {
+                                        "Resolved"
+                                    })
+                                    .size(LabelSize::Small)
+                                    .color(if unresolved {
+                                        Color::Warning
+                                    } else {
+                                        Color::Muted
+                                    }),
+                                ),
+                        )
+                        .end_slot(
+                            h_flex()
+                                .gap_1()
+                                .child(resolve_button(
+                                    "keep-local",
+                                    "Keep Local",
+                                    Resolution::KeepLocal,
+                                ))
+                                .child(resolve_button(
+                                    "keep-remote",
+                                    "Keep Remote",
+                                    Resolution::KeepRemote,
+                                ))
+                                .child(resolve_button(
+                                    "keep-both",
+                                    "Keep Both",
+                                    Resolution::KeepBoth,
+                                ))
+                                .child(
+                                    Button::new("open", "Open")
+                                        .label_size(LabelSize::Small)
+                                        .on_click({
+                                            let path = path.clone();
+                                            cx.listener(move |this, _, window, cx| {
+                                                this.open(&path, window, cx)
+                                            })
+                                        }),
+                                ),
+                        )
+                }))
+                .into_any_element()
+        };
+        let error = match &status {
+            SyncStatus::Failed(error) => Some(
+                Label::new(error.clone())
+                    .size(LabelSize::Small)
+                    .color(Color::Error),
+            ),
+            _ => None,
+        };
+        let in_progress = matches!(status, SyncStatus::Conflicts(_));
+
+        v_flex()
+            .key_context("VaultSyncConflicts")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::cancel))
+            .w(rems(40.))
+            .elevation_3(cx)
+            .p_3()
+            .gap_2()
+            .child(Headline::new("Sync Conflicts").size(HeadlineSize::Small))
+            .child(
+                Label::new(
+                    "These notes were edited here and on the remote. Resolve them, or open them \
+                     to merge by hand, then continue the sync.",
+                )
+                .size(LabelSize::Small)
+                .color(Color::Muted),
+            )
+            .child(body)
+            .children(error)
+            .child(
+                h_flex()
+                    .justify_end()
+                    .gap_1()
+                    .child(
+                        Button::new("abort-sync", "Abort Sync")
+                            .disabled(!in_progress)
+                            .on_click(cx.listener(|this, _, _, cx| {
+                                this.vault_sync
+                                    .update(cx, |vault_sync, cx| vault_sync.abort_sync(cx));
+                            })),
+                    )
+                    .child(
+                        Button::new("continue-sync", "Continue Sync")
+                            .style(ButtonStyle::Filled)
+                            .disabled(!in_progress)
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.confirm(&menu::Confirm, window, cx)
+                            })),
+                    ),
+            )
+    }
+}
@@ -0,0 +1,126 @@
+use std::ops::Range;
+
+/// A region of a note with conflict markers, left by pulling with rebase when
+/// the same lines were edited locally and on the remote.
+///
+/// While rebasing, the first side of a conflict is the remote version the
+/// local commits are being replayed onto, and the second side is the local
+/// version.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConflictRegion {
+    /// The whole region, including the markers.
+    pub range: Range<usize>,
+    pub remote: Range<usize>,
+    /// The common ancestor, when conflicts are written in the `diff3` style.
+    pub base: Option<Range<usize>>,
+    pub local: Range<usize>,
+}
+
+/// How to resolve every conflict in a note.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Resolution {
+    KeepLocal,
+    KeepRemote,
+    /// Keeps the local lines followed by the remote ones.
+    KeepBoth,
+}
+
+/// Returns the conflict regions in `text`, ignoring incomplete ones.
+pub fn conflict_regions(text: &str) -> Vec<ConflictRegion> {
+    git::conflict::conflict_regions(text)
+        .into_iter()
+        .map(|region| ConflictRegion {
+            range: region.range,
+            remote: region.ours,
+            base: region.base,
+            local: region.theirs,
+        })
+        .collect()
+}
+
+/// Whether `text` still has conflict markers in it.
+pub fn has_conflicts(text: &str) -> bool {
+    git::conflict::has_conflicts(text)
+}
+
+/// Replaces every conflict region in `text` with the side chosen by
+/// `resolution`.
+pub fn resolve_conflicts(text: &str, resolution: Resolution) -> String {
+    let mut resolved = String::with_capacity(text.len());
+    let mut last_end = 0;
+    for region in conflict_regions(text) {
+        resolved.push_str(&text[last_end..region.range.start]);
+        let local = &text[region.local.clone()];
+        let remote = &text[region.remote.clone()];
+        match resolution {
+            Resolution::KeepLocal => resolved.push_str(local),
+            Resolution::KeepRemote => resolved.push_str(remote),
+            Resolution::KeepBoth => {
+                resolved.push_str(local);
+                if !local.is_empty() && !local.ends_with('\n') {
+                    resolved.push('\n');
+                }
+                resolved.push_str(remote);
+            }
+        }
+        last_end = region.range.end;
+    }
+    resolved.push_str(&text[last_end..]);
+    resolved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_resolve_conflicts() {
+        let text = indoc! {"
+            # Groceries
+            <<<<<<< HEAD
+            - pears
+            =======
+            - apples
+            - plums
+            >>>>>>> 1a2b3c4 (Vault sync: 2025-04-01 09:00)
+            ## Done
+            <<<<<<< HEAD
+            - milk
+            ||||||| parent of 1a2b3c4
+            - bread
+            =======
+            >>>>>>> 1a2b3c4 (Vault sync: 2025-04-01 09:00)
+            ======= not a marker
+        "};
+        let regions = conflict_regions(text);
+        assert_eq!(regions.len(), 2);
+        assert_eq!(&text[regions[0].remote.clone()], "- pears\n");
+        assert_eq!(&text[regions[0].local.clone()], "- apples\n- plums\n");
+        assert_eq!(regions[0].base, None);
+        assert_eq!(
+            regions[1].base.clone().map(|base| &text[base]),
+            Some("- bread\n")
+        );
+        assert!(regions[1].local.is_empty());
+
+        assert_eq!(
+            resolve_conflicts(text, Resolution::KeepLocal),
+            "# Groceries\n- apples\n- plums\n## Done\n======= not a marker\n"
+        );
+        assert_eq!(
+            resolve_conflicts(text, Resolution::KeepRemote),
+            "# Groceries\n- pears\n## Done\n- milk\n======= not a marker\n"
+        );
+        assert_eq!(
+            resolve_conflicts(text, Resolution::KeepBoth),
+            "# Groceries\n- apples\n- plums\n- pears\n## Done\n- milk\n======= not a marker\n"
+        );
+        assert!(!has_conflicts(&resolve_conflicts(
+            text,
+            Resolution::KeepBoth
+        )));
+        assert!(!has_conflicts("<<<<<<< HEAD\nunfinished\n"));
+    }
+}
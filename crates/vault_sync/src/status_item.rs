@@ -0,0 +1,104 @@
+use gpui::{Context, Entity, IntoElement, Render, Subscription, WeakEntity, Window, div};
+use settings::Settings as _;
+use ui::{Tooltip, prelude::*};
+use workspace::{ItemHandle, StatusItemView, Workspace};
+
+use crate::{SyncStatus, VaultSync, VaultSyncConflicts, VaultSyncEvent, VaultSyncSettings};
+
+/// Shows when the vault was last synced, and syncs it when clicked.
+pub struct VaultSyncStatusItem {
+    vault_sync: Entity<VaultSync>,
+    workspace: WeakEntity<Workspace>,
+    _subscriptions: [Subscription; 2],
+}
+
+impl VaultSyncStatusItem {
+    pub fn new(workspace: &Workspace, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let vault_sync = VaultSync::for_project(workspace.project(), cx);
+        let subscriptions = [
+            cx.observe(&vault_sync, |_, _, cx| cx.notify()),
+            cx.subscribe_in(
+                &vault_sync,
+                window,
+                |this, _, event, window, cx| match event {
+                    VaultSyncEvent::Conflicts => this.show_conflicts(window, cx),
+                },
+            ),
+        ];
+        Self {
+            vault_sync,
+            workspace: workspace.weak_handle(),
+            _subscriptions: subscriptions,
+        }
+    }
+
+    fn show_conflicts(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let vault_sync = self.vault_sync.clone();
+        self.workspace
+            .update(cx, |workspace, cx| {
+                let workspace_handle = workspace.weak_handle();
+                workspace.toggle_modal(window, cx, |window, cx| {
+                    VaultSyncConflicts::new(vault_sync, workspace_handle, window, cx)
+                });
+            })
+            .ok();
+    }
+}
+
+impl Render for VaultSyncStatusItem {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let vault_sync = self.vault_sync.read(cx);
+        let last_synced = vault_sync.last_synced();
+        let (label, tooltip) = match vault_sync.status() {
+            SyncStatus::Idle => match last_synced {
+                Some(last_synced) => (
+                    format!("Synced {}", last_synced.format("%H:%M")),
+                    format!("Vault last synced {}", last_synced.format("%Y-%m-%d %H:%M")),
+                ),
+                None if VaultSyncSettings::get_global(cx).enabled() => {
+                    ("Not synced".to_string(), "Sync the vault now".to_string())
+                }
+                None => return div(),
+            },
+            SyncStatus::Syncing => ("Syncing…".to_string(), "Syncing the vault".to_string()),
+            SyncStatus::Conflicts(conflicts) => (
+                "Sync conflicts".to_string(),
+                format!(
+                    "{} notes were edited on both sides. Click to resolve them.",
+                    conflicts.len()
+                ),
+            ),
+            SyncStatus::Failed(error) => ("Sync failed".to_string(), error.to_string()),
+        };
+        let has_conflicts = matches!(vault_sync.status(), SyncStatus::Conflicts(_));
+
+        div().child(
+            Button::new("vault-sync-status", label)
+                .label_size(LabelSize::Small)
+                .color(if has_conflicts {
+                    Color::Warning
+                } else {
+                    Color::Default
+                })
+                .tooltip(Tooltip::text(tooltip))
+                .on_click(cx.listener(move |this, _, window, cx| {
+                    if has_conflicts {
+                        this.show_conflicts(window, cx);
+                    } else {
+                        this.vault_sync
+                            .update(cx, |vault_sync, cx| vault_sync.sync(cx));
+                    }
+                })),
+        )
+    }
+}
+
+impl StatusItemView for VaultSyncStatusItem {
+    fn set_active_pane_item(
+        &mut self,
+        _active_pane_item: Option<&dyn ItemHandle>,
+        _window: &mut Window,
+        _cx: &mut Context<Self>,
+    ) {
+    }
+}
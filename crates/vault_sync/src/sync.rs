@@ -0,0 +1,397 @@
+use std::sync::Arc;
+
+use anyhow::{Context as _, Result};
+use askpass::AskPassDelegate;
+use chrono::{DateTime, Local};
+use collections::HashMap;
+use git::repository::{GitRepository, RepoPath};
+use git::status::{FileStatus, GitStatus};
+use gpui::AsyncApp;
+
+/// What happened when syncing a vault.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SyncOutcome {
+    Synced {
+        /// The notes that were committed.
+        committed: Vec<RepoPath>,
+        /// Whether the branch was pulled and pushed. It isn't when it has no
+        /// upstream.
+        pushed: bool,
+    },
+    /// Pulling stopped because these notes were edited both locally and on
+    /// the remote. The rebase stays in progress until the conflicts are
+    /// resolved with [`continue_sync`] or given up on with [`abort_sync`].
+    Conflicts(Vec<RepoPath>),
+}
+
+/// Commits every change in the repository, then pulls with rebase and pushes
+/// the current branch.
+pub async fn sync_repository(
+    repo: Arc<dyn GitRepository>,
+    message_template: &str,
+    env: Arc<HashMap<String, String>>,
+    now: DateTime<Local>,
+    cx: AsyncApp,
+) -> Result<SyncOutcome> {
+    let status = repo.status(&[]).await?;
+    let conflicts = conflicted_paths(&status);
+    if !conflicts.is_empty() {
+        return Ok(SyncOutcome::Conflicts(conflicts));
+    }
+
+    let changed = status
+        .entries
+        .iter()
+        .filter(|(_, status)| *status != FileStatus::Ignored)
+        .map(|(path, _)| path.clone())
+        .collect::<Vec<_>>();
+    if !changed.is_empty() {
+        repo.stage_paths(changed.clone(), env.clone())
+            .await
+            .context("staging changed notes")?;
+        repo.commit(
+            commit_message(message_template, &changed, now).into(),
+            None,
+            env.clone(),
+        )
+        .await
+        .context("committing changed notes")?;
+    }
+
+    let Some(upstream) = upstream(repo.as_ref()).await? else {
+        return Ok(SyncOutcome::Synced {
+            committed: changed,
+            pushed: false,
+        });
+    };
+    let pulled = repo
+        .pull(
+            upstream.branch.clone(),
+            upstream.remote.clone(),
+            no_prompts(&mut cx.clone()),
+            rebase_env(&env),
+            cx.clone(),
+        )
+        .await;
+    if let Err(error) = pulled {
+        let conflicts = conflicted_paths(&repo.status(&[]).await?);
+        if conflicts.is_empty() {
+            return Err(error.context("pulling"));
+        }
+        return Ok(SyncOutcome::Conflicts(conflicts));
+    }
+    push(repo.as_ref(), &upstream, env, cx).await?;
+    Ok(SyncOutcome::Synced {
+        committed: changed,
+        pushed: true,
+    })
+}
+
+/// Finishes a sync that stopped on conflicts, once the conflicted notes have
+/// been resolved and staged.
+pub async fn continue_sync(
+    repo: Arc<dyn GitRepository>,
+    env: Arc<HashMap<String, String>>,
+    cx: AsyncApp,
+) -> Result<SyncOutcome> {
+    if let Err(error) = repo.continue_rebase(env.clone()).await {
+        // Replaying the next local commit can conflict too.
+        let conflicts = conflicted_paths(&repo.status(&[]).await?);
+        if conflicts.is_empty() {
+            return Err(error);
+        }
+        return Ok(SyncOutcome::Conflicts(conflicts));
+    }
+    let pushed = match upstream(repo.as_ref()).await? {
+        Some(upstream) => {
+            push(repo.as_ref(), &upstream, env, cx).await?;
+            true
+        }
+        None => false,
+    };
+    Ok(SyncOutcome::Synced {
+        committed: Vec::new(),
+        pushed,
+    })
+}
+
+/// Gives up on a sync that stopped on conflicts, leaving the local commits
+/// unpushed.
+pub async fn abort_sync(
+    repo: Arc<dyn GitRepository>,
+    env: Arc<HashMap<String, String>>,
+) -> Result<()> {
+    repo.abort_rebase(env).await
+}
+
+/// Fills in a commit message template. `{{date}}` is replaced with the time
+/// of the sync, `{{count}}` with the number of changed notes and `{{files}}`
+/// with a list of them.
+pub fn commit_message(template: &str, paths: &[RepoPath], now: DateTime<Local>) -> String {
+    let files = paths
+        .iter()
+        .map(|path| format!("- {}", path.display()))
+        .collect::<Vec<_>>()
+        .join("\n");
+    template
+        .replace("{{date}}", &now.format("%Y-%m-%d %H:%M").to_string())
+        .replace("{{count}}", &paths.len().to_string())
+        .replace("{{files}}", &files)
+        .trim()
+        .to_string()
+}
+
+fn conflicted_paths(status: &GitStatus) -> Vec<RepoPath> {
+    status
+        .entries
+        .iter()
+        .filter(|(_, status)| status.is_conflicted())
+        .map(|(path, _)| path.clone())
+        .collect()
+}
+
+struct SyncUpstream {
+    branch: String,
+    remote: String,
+}
+
+/// The remote and branch the current branch tracks, if any.
+async fn upstream(repo: &dyn GitRepository) -> Result<Option<SyncUpstream>> {
+    let branches = repo.branches().await?;
+    let Some(upstream) = branches
+        .iter()
+        .find(|branch| branch.is_head)
+        .and_then(|branch| branch.upstream.as_ref())
+    else {
+        return Ok(None);
+    };
+    let Some(remote) = upstream.remote_name() else {
+        return Ok(None);
+    };
+    let branch = upstream
+        .ref_name
+        .strip_prefix(&format!("refs/remotes/{remote}/"))
+        .unwrap_or(&upstream.ref_name);
+    Ok(Some(SyncUpstream {
+        branch: branch.to_string(),
+        remote: remote.to_string(),
+    }))
+}
+
+async fn push(
+    repo: &dyn GitRepository,
+    upstream: &SyncUpstream,
+    env: Arc<HashMap<String, String>>,
+    cx: AsyncApp,
+) -> Result<()> {
+    repo.push(
+        upstream.branch.clone(),
+        upstream.remote.clone(),
+        None,
+        no_prompts(&mut cx.clone()),
+        env,
+        cx,
+    )
+    .await
+    .context("pushing")?;
+    Ok(())
+}
+
+/// Syncs run in the background, so there's no one to ask for credentials.
+/// Remotes need to authenticate with an SSH key or a credential helper.
+fn no_prompts(cx: &mut AsyncApp) -> AskPassDelegate {
+    AskPassDelegate::new(cx, |prompt, _, _| {
+        log::warn!("vault sync can't answer git's prompt {prompt:?}");
+    })
+}
+
+/// `GitRepository::pull` merges by default, so ask it to rebase through
+/// git's environment configuration, keeping any configuration already there.
+fn rebase_env(env: &HashMap<String, String>) -> Arc<HashMap<String, String>> {
+    let mut env = env.clone();
+    let mut count = env
+        .get("GIT_CONFIG_COUNT")
+        .and_then(|count| count.parse::<usize>().ok())
+        .unwrap_or(0);
+    for (key, value) in [("pull.rebase", "true"), ("rebase.autoStash", "true")] {
+        env.insert(format!("GIT_CONFIG_KEY_{count}"), key.to_string());
+        env.insert(format!("GIT_CONFIG_VALUE_{count}"), value.to_string());
+        count += 1;
+    }
+    env.insert("GIT_CONFIG_COUNT".to_string(), count.to_string());
+    Arc::new(env)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Resolution, resolve_conflicts};
+    use chrono::TimeZone as _;
+    use git::repository::RealGitRepository;
+    use gpui::TestAppContext;
+    use pretty_assertions::assert_eq;
+    use std::path::Path;
+    use std::process::Command;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let output = Command::new("git")
+            .current_dir(dir)
+            .envs(author_env().iter())
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "git {args:?} failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    fn author_env() -> HashMap<String, String> {
+        HashMap::from_iter([
+            ("GIT_AUTHOR_NAME".to_string(), "Zed".to_string()),
+            ("GIT_AUTHOR_EMAIL".to_string(), "hi@zed.dev".to_string()),
+            ("GIT_COMMITTER_NAME".to_string(), "Zed".to_string()),
+            ("GIT_COMMITTER_EMAIL".to_string(), "hi@zed.dev".to_string()),
+        ])
+    }
+
+    #[test]
+    fn test_commit_message() {
+        let now = Local.with_ymd_and_hms(2025, 4, 1, 9, 30, 0).unwrap();
+        let paths = [
+            RepoPath::from("Daily/2025-04-01.md"),
+            RepoPath::from("Ideas.md"),
+        ];
+        assert_eq!(
+            commit_message("Vault sync: {{date}}\n\n{{files}}", &paths, now),
+            "Vault sync: 2025-04-01 09:30\n\n- Daily/2025-04-01.md\n- Ideas.md"
+        );
+        assert_eq!(commit_message("{{count}} notes", &paths, now), "2 notes");
+    }
+
+    #[gpui::test]
+    async fn test_sync_with_bare_remote(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+
+        let dir = tempfile::tempdir().unwrap();
+        let remote = dir.path().join("remote.git");
+        let laptop = dir.path().join("laptop");
+        let desktop = dir.path().join("desktop");
+        git(dir.path(), &["init", "--bare", "remote.git"]);
+        git(dir.path(), &["clone", "remote.git", "laptop"]);
+        std::fs::write(laptop.join("Groceries.md"), "# Groceries\n- milk\n").unwrap();
+        git(&laptop, &["add", "."]);
+        git(&laptop, &["commit", "-m", "Initial notes"]);
+        git(&laptop, &["push", "-u", "origin", "HEAD"]);
+        git(dir.path(), &["clone", remote.to_str().unwrap(), "desktop"]);
+
+        let open = |path: &Path| -> Arc<dyn GitRepository> {
+            Arc::new(RealGitRepository::new(&path.join(".git"), None, cx.executor()).unwrap())
+        };
+        let (laptop_repo, desktop_repo) = (open(&laptop), open(&desktop));
+        let env = Arc::new(author_env());
+        let now = Local.with_ymd_and_hms(2025, 4, 1, 9, 30, 0).unwrap();
+        let template = "Vault sync: {{date}}\n\n{{files}}";
+
+        // Changes on one side reach the other.
+        std::fs::write(laptop.join("Ideas.md"), "Sync notes with git\n").unwrap();
+        let outcome = sync_repository(
+            laptop_repo.clone(),
+            template,
+            env.clone(),
+            now,
+            cx.to_async(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            outcome,
+            SyncOutcome::Synced {
+                committed: vec![RepoPath::from("Ideas.md")],
+                pushed: true
+            }
+        );
+        let outcome = sync_repository(
+            desktop_repo.clone(),
+            template,
+            env.clone(),
+            now,
+            cx.to_async(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            outcome,
+            SyncOutcome::Synced {
+                committed: vec![],
+                pushed: true
+            }
+        );
+        assert_eq!(
+            std::fs::read_to_string(desktop.join("Ideas.md")).unwrap(),
+            "Sync notes with git\n"
+        );
+
+        // Editing the same lines on both sides stops on a conflict.
+        std::fs::write(laptop.join("Groceries.md"), "# Groceries\n- pears\n").unwrap();
+        sync_repository(
+            laptop_repo.clone(),
+            template,
+            env.clone(),
+            now,
+            cx.to_async(),
+        )
+        .await
+        .unwrap();
+        std::fs::write(desktop.join("Groceries.md"), "# Groceries\n- apples\n").unwrap();
+        let outcome = sync_repository(
+            desktop_repo.clone(),
+            template,
+            env.clone(),
+            now,
+            cx.to_async(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            outcome,
+            SyncOutcome::Conflicts(vec![RepoPath::from("Groceries.md")])
+        );
+
+        let conflicted = std::fs::read_to_string(desktop.join("Groceries.md")).unwrap();
+        std::fs::write(
+            desktop.join("Groceries.md"),
+            resolve_conflicts(&conflicted, Resolution::KeepBoth),
+        )
+        .unwrap();
+        desktop_repo
+            .stage_paths(vec![RepoPath::from("Groceries.md")], env.clone())
+            .await
+            .unwrap();
+        let outcome = continue_sync(desktop_repo.clone(), env.clone(), cx.to_async())
+            .await
+            .unwrap();
+        assert_eq!(
+            outcome,
+            SyncOutcome::Synced {
+                committed: vec![],
+                pushed: true
+            }
+        );
+
+        sync_repository(
+            laptop_repo.clone(),
+            template,
+            env.clone(),
+            now,
+            cx.to_async(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(laptop.join("Groceries.md")).unwrap(),
+            "# Groceries\n- apples\n- pears\n"
+        );
+    }
+}
@@ -0,0 +1,412 @@
+//! Opt-in syncing of a notes vault through git. Changes are committed when
+//! editing goes idle or on an interval, then pulled with rebase and pushed to
+//! the branch's upstream. Notes edited on both sides are resolved in a
+//! conflict view before the sync finishes.
+
+mod conflict_view;
+mod conflicts;
+mod status_item;
+mod sync;
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Local};
+use collections::HashMap;
+use futures::channel::oneshot;
+use git::repository::{GitRepository, RepoPath};
+use gpui::{
+    App, AppContext as _, AsyncApp, Context, Entity, EntityId, EventEmitter, Global, SharedString,
+    Subscription, Task, WeakEntity, actions,
+};
+use project::Project;
+use project::git_store::{Repository, RepositoryState};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+use workspace::Workspace;
+
+pub use conflict_view::VaultSyncConflicts;
+pub use conflicts::{
+    ConflictRegion, Resolution, conflict_regions, has_conflicts, resolve_conflicts,
+};
+pub use status_item::VaultSyncStatusItem;
+pub use sync::{SyncOutcome, abort_sync, commit_message, continue_sync, sync_repository};
+
+actions!(vault_sync, [SyncNow, ResolveConflicts]);
+
+const DEFAULT_COMMIT_MESSAGE: &str = "Vault sync: {{date}}\n\n{{files}}";
+
+/// How often to check whether it's time to sync.
+const TICK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Settings for syncing the vault through git.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct VaultSyncSettings {
+    /// Whether to commit, pull and push the vault automatically.
+    ///
+    /// Default: false
+    pub enabled: Option<bool>,
+    /// When to sync, either "idle" to sync once notes haven't been changed
+    /// for `idle_seconds`, or "interval" to sync every `interval_minutes`.
+    ///
+    /// Default: "idle"
+    pub trigger: Option<SyncTrigger>,
+    /// The number of minutes between syncs when syncing on an interval.
+    ///
+    /// Default: 10
+    pub interval_minutes: Option<u64>,
+    /// The number of seconds without changes before syncing when syncing on
+    /// idle.
+    ///
+    /// Default: 60
+    pub idle_seconds: Option<u64>,
+    /// The message of sync commits. `{{date}}` is replaced with the time of
+    /// the sync, `{{count}}` with the number of changed notes and `{{files}}`
+    /// with a list of them.
+    ///
+    /// Default: "Vault sync: {{date}}\n\n{{files}}"
+    pub commit_message: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncTrigger {
+    #[default]
+    Idle,
+    Interval,
+}
+
+impl VaultSyncSettings {
+    pub fn enabled(&self) -> bool {
+        self.enabled.unwrap_or(false)
+    }
+
+    pub fn trigger(&self) -> SyncTrigger {
+        self.trigger.unwrap_or_default()
+    }
+
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval_minutes.unwrap_or(10).max(1) * 60)
+    }
+
+    pub fn idle_duration(&self) -> Duration {
+        Duration::from_secs(self.idle_seconds.unwrap_or(60))
+    }
+
+    pub fn commit_message(&self) -> &str {
+        self.commit_message
+            .as_deref()
+            .unwrap_or(DEFAULT_COMMIT_MESSAGE)
+    }
+}
+
+impl settings::Settings for VaultSyncSettings {
+    const KEY: Option<&'static str> = Some("vault_sync");
+
+    type FileContent = Self;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _: &mut App) -> Result<Self> {
+        sources.json_merge()
+    }
+}
+
+pub fn init(cx: &mut App) {
+    VaultSyncSettings::register(cx);
+
+    cx.observe_new(|workspace: &mut Workspace, _window, _cx| {
+        workspace.register_action(|workspace, _: &SyncNow, _window, cx| {
+            let vault_sync = VaultSync::for_project(workspace.project(), cx);
+            vault_sync.update(cx, |vault_sync, cx| vault_sync.sync(cx));
+        });
+        workspace.register_action(|workspace, _: &ResolveConflicts, window, cx| {
+            let vault_sync = VaultSync::for_project(workspace.project(), cx);
+            let workspace_handle = workspace.weak_handle();
+            workspace.toggle_modal(window, cx, |window, cx| {
+                VaultSyncConflicts::new(vault_sync, workspace_handle, window, cx)
+            });
+        });
+    })
+    .detach();
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SyncStatus {
+    Idle,
+    Syncing,
+    /// The last sync stopped on these conflicted notes.
+    Conflicts(Vec<RepoPath>),
+    Failed(SharedString),
+}
+
+pub enum VaultSyncEvent {
+    /// A sync stopped because notes were edited on both sides.
+    Conflicts,
+}
+
+/// Keeps a project's vault in sync with its git remote.
+pub struct VaultSync {
+    project: Entity<Project>,
+    status: SyncStatus,
+    last_synced: Option<DateTime<Local>>,
+    last_sync_started: Option<Instant>,
+    /// When notes were last changed, if they've changed since the last sync.
+    last_change: Option<Instant>,
+    _sync: Task<()>,
+    _tick: Task<()>,
+    _subscription: Subscription,
+}
+
+#[derive(Default)]
+struct GlobalVaultSyncs(HashMap<EntityId, WeakEntity<VaultSync>>);
+
+impl Global for GlobalVaultSyncs {}
+
+impl EventEmitter<VaultSyncEvent> for VaultSync {}
+
+impl VaultSync {
+    /// Returns the sync state of the project, creating it the first time.
+    pub fn for_project(project: &Entity<Project>, cx: &mut App) -> Entity<Self> {
+        let project_id = project.entity_id();
+        if let Some(vault_sync) = cx
+            .try_global::<GlobalVaultSyncs>()
+            .and_then(|syncs| syncs.0.get(&project_id))
+            .and_then(|vault_sync| vault_sync.upgrade())
+        {
+            return vault_sync;
+        }
+        let vault_sync = cx.new(|cx| Self::new(project.clone(), cx));
+        let syncs = &mut cx.default_global::<GlobalVaultSyncs>().0;
+        syncs.retain(|_, vault_sync| vault_sync.upgrade().is_some());
+        syncs.insert(project_id, vault_sync.downgrade());
+        vault_sync
+    }
+
+    fn new(project: Entity<Project>, cx: &mut Context<Self>) -> Self {
+        let subscription = cx.subscribe(&project, |this, _, event, _| {
+            if let project::Event::WorktreeUpdatedEntries(..) = event {
+                // Changes made by pulling aren't ours to commit.
+                if this.status != SyncStatus::Syncing {
+                    this.last_change = Some(Instant::now());
+                }
+            }
+        });
+        let tick = cx.spawn(async move |this, cx| {
+            loop {
+                cx.background_executor().timer(TICK_INTERVAL).await;
+                if this.update(cx, |this, cx| this.tick(cx)).is_err() {
+                    break;
+                }
+            }
+        });
+        Self {
+            project,
+            status: SyncStatus::Idle,
+            last_synced: None,
+            last_sync_started: None,
+            last_change: None,
+            _sync: Task::ready(()),
+            _tick: tick,
+            _subscription: subscription,
+        }
+    }
+
+    pub fn status(&self) -> &SyncStatus {
+        &self.status
+    }
+
+    pub fn last_synced(&self) -> Option<DateTime<Local>> {
+        self.last_synced
+    }
+
+    fn tick(&mut self, cx: &mut Context<Self>) {
+        let settings = VaultSyncSettings::get_global(cx);
+        if !settings.enabled() || self.status == SyncStatus::Syncing {
+            return;
+        }
+        if let SyncStatus::Conflicts(_) = self.status {
+            return;
+        }
+        let due = match settings.trigger() {
+            SyncTrigger::Idle => self
+                .last_change
+                .is_some_and(|changed| changed.elapsed() >= settings.idle_duration()),
+            // Syncing on an interval also brings in changes from the remote,
+            // so it doesn't wait for local changes.
+            SyncTrigger::Interval => self
+                .last_sync_started
+                .is_none_or(|started| started.elapsed() >= settings.interval()),
+        };
+        if due {
+            self.sync(cx);
+        }
+    }
+
+    /// Commits the vault's changes, then pulls and pushes them.
+    pub fn sync(&mut self, cx: &mut Context<Self>) {
+        if self.status == SyncStatus::Syncing {
+            return;
+        }
+        let message_template = VaultSyncSettings::get_global(cx)
+            .commit_message()
+            .to_string();
+        self.run(cx, move |repository, environment, _, cx| async move {
+            sync_repository(repository, &message_template, environment, Local::now(), cx).await
+        });
+    }
+
+    /// Finishes a sync that stopped on conflicts. Conflicted notes that no
+    /// longer have conflict markers are staged first, so notes resolved by
+    /// hand in the editor count as resolved.
+    pub fn continue_sync(&mut self, cx: &mut Context<Self>) {
+        let SyncStatus::Conflicts(conflicts) = &self.status else {
+            return;
+        };
+        let conflicts = conflicts.clone();
+        let fs = self.project.read(cx).fs().clone();
+        self.run(cx, move |repository, environment, root, cx| async move {
+            let mut resolved = Vec::new();
+            for path in conflicts {
+                if !has_conflicts(&fs.load(&root.join(&path)).await?) {
+                    resolved.push(path);
+                }
+            }
+            repository
+                .stage_paths(resolved, environment.clone())
+                .await?;
+            sync::continue_sync(repository, environment, cx).await
+        });
+    }
+
+    /// Gives up on a sync that stopped on conflicts. The local commits stay
+    /// unpushed until the next sync.
+    pub fn abort_sync(&mut self, cx: &mut Context<Self>) {
+        let SyncStatus::Conflicts(_) = self.status else {
+            return;
+        };
+        self.run(cx, move |repository, environment, _, _| async move {
+            sync::abort_sync(repository, environment).await?;
+            Ok(SyncOutcome::Synced {
+                committed: Vec::new(),
+                pushed: false,
+            })
+        });
+    }
+
+    /// Resolves every conflict in a note the same way and stages it.
+    pub fn resolve(
+        &mut self,
+        path: RepoPath,
+        resolution: Resolution,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let Some(repository) = self.repository(cx) else {
+            return Task::ready(Err(anyhow!("the vault isn't a git repository")));
+        };
+        let fs = self.project.read(cx).fs().clone();
+        let abs_path = repository.read(cx).work_directory_abs_path.join(&path);
+        let job = repository.read(cx).send_job({
+            let path = path.clone();
+            move |state, _| async move {
+                let RepositoryState::Local {
+                    backend,
+                    environment,
+                } = state
+                else {
+                    return Err(anyhow!("vault sync only works with local vaults"));
+                };
+                let text = fs.load(&abs_path).await?;
+                fs.atomic_write(abs_path, resolve_conflicts(&text, resolution))
+                    .await?;
+                backend.stage_paths(vec![path], environment).await
+            }
+        });
+        cx.spawn(async move |this, cx| {
+            job.await??;
+            this.update(cx, |this, cx| {
+                if let SyncStatus::Conflicts(conflicts) = &mut this.status {
+                    conflicts.retain(|conflict| conflict != &path);
+                    cx.notify();
+                }
+            })
+        })
+    }
+
+    /// The absolute path of a note in the vault's repository.
+    pub fn abs_path(&self, path: &RepoPath, cx: &App) -> Option<PathBuf> {
+        let repository = self.repository(cx)?;
+        Some(repository.read(cx).work_directory_abs_path.join(path))
+    }
+
+    /// The repository the vault is in: the one containing the project's
+    /// first folder.
+    fn repository(&self, cx: &App) -> Option<Entity<Repository>> {
+        let project = self.project.read(cx);
+        let worktree = project.visible_worktrees(cx).next()?;
+        let root = worktree.read(cx).abs_path();
+        project
+            .git_store()
+            .read(cx)
+            .repositories()
+            .values()
+            .find(|repository| repository.read(cx).abs_path_to_repo_path(&root).is_some())
+            .cloned()
+    }
+
+    fn run<F, Fut>(&mut self, cx: &mut Context<Self>, job: F)
+    where
+        F: FnOnce(Arc<dyn GitRepository>, Arc<HashMap<String, String>>, Arc<Path>, AsyncApp) -> Fut
+            + 'static,
+        Fut: Future<Output = Result<SyncOutcome>> + 'static,
+    {
+        let Some(repository) = self.repository(cx) else {
+            self.status = SyncStatus::Failed("The vault isn't a git repository".into());
+            cx.notify();
+            return;
+        };
+        let root = repository.read(cx).work_directory_abs_path.clone();
+        let job: oneshot::Receiver<Result<SyncOutcome>> =
+            repository.read(cx).send_job(move |state, cx| async move {
+                let RepositoryState::Local {
+                    backend,
+                    environment,
+                } = state
+                else {
+                    return Err(anyhow!("vault sync only works with local vaults"));
+                };
+                job(backend, environment, root, cx).await
+            });
+
+        self.status = SyncStatus::Syncing;
+        self.last_sync_started = Some(Instant::now());
+        self.last_change = None;
+        cx.notify();
+        self._sync = cx.spawn(async move |this, cx| {
+            let outcome = match job.await {
+                Ok(outcome) => outcome,
+                Err(_) => Err(anyhow!("the sync was canceled")),
+            };
+            this.update(cx, |this, cx| this.finish(outcome, cx)).ok();
+        });
+    }
+
+    fn finish(&mut self, outcome: Result<SyncOutcome>, cx: &mut Context<Self>) {
+        match outcome {
+            Ok(SyncOutcome::Synced { .. }) => {
+                self.status = SyncStatus::Idle;
+                self.last_synced = Some(Local::now());
+            }
+            Ok(SyncOutcome::Conflicts(conflicts)) => {
+                self.status = SyncStatus::Conflicts(conflicts);
+                cx.emit(VaultSyncEvent::Conflicts);
+            }
+            Err(error) => {
+                log::error!("vault sync failed: {error:#}");
+                self.status = SyncStatus::Failed(format!("{error:#}").into());
+            }
+        }
+        cx.notify();
+    }
+}
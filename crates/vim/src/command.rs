@@ -4,10 +4,12 @@ use command_palette_hooks::CommandInterceptResult;
 use editor::{
     Bias, Editor, ToPoint,
     actions::{SortLinesCaseInsensitive, SortLinesCaseSensitive},
-    display_map::ToDisplayPoint,
     scroll::Autoscroll,
 };
-use gpui::{Action, App, AppContext as _, Context, Global, Window, actions, impl_internal_actions};
+use gpui::{
+    Action, App, AppContext as _, AsyncWindowContext, Context, Global, Keystroke, WeakEntity,
+    Window, actions, impl_internal_actions,
+};
 use itertools::Itertools;
 use language::Point;
 use multi_buffer::MultiBufferRow;
@@ -15,6 +17,7 @@ use regex::Regex;
 use schemars::JsonSchema;
 use search::{BufferSearchBar, SearchOptions};
 use serde::Deserialize;
+use settings::Settings;
 use std::{
     io::Write,
     iter::Peekable,
@@ -31,14 +34,15 @@ use workspace::{SaveIntent, notifications::NotifyResultExt};
 use zed_actions::RevealTarget;
 
 use crate::{
-    ToggleMarksView, ToggleRegistersView, Vim,
+    ToggleMarksView, ToggleRegistersView, Vim, VimSettings,
+    expression::{Scope, evaluate, variable_name},
     motion::{EndOfDocument, Motion, MotionKind, StartOfDocument},
     normal::{
         JoinLines,
         search::{FindCommand, ReplaceCommand, Replacement},
     },
     object::Object,
-    state::{Mark, Mode},
+    state::{Mark, Mode, VimGlobals},
     visual::VisualDeleteLine,
 };
 
@@ -65,7 +69,7 @@ pub struct WithCount {
     action: WrappedAction,
 }
 
-#[derive(Clone, Deserialize, JsonSchema, PartialEq)]
+#[derive(Clone, Debug, Deserialize, JsonSchema, PartialEq)]
 pub enum VimOption {
     Wrap(bool),
     Number(bool),
@@ -118,7 +122,7 @@ impl VimOption {
         .map(|(_, option)| option)
     }
 
-    fn from(option: &str) -> Option<Self> {
+    pub(crate) fn from(option: &str) -> Option<Self> {
         match option {
             "wrap" => Some(Self::Wrap(true)),
             "nowrap" => Some(Self::Wrap(false)),
@@ -137,6 +141,23 @@ impl VimOption {
         }
     }
 
+    pub(crate) fn apply(&self, editor: &mut Editor, cx: &mut Context<Editor>) {
+        match self {
+            VimOption::Wrap(true) => {
+                editor.set_soft_wrap_mode(language::language_settings::SoftWrap::EditorWidth, cx);
+            }
+            VimOption::Wrap(false) => {
+                editor.set_soft_wrap_mode(language::language_settings::SoftWrap::None, cx);
+            }
+            VimOption::Number(enabled) => {
+                editor.set_show_line_numbers(*enabled, cx);
+            }
+            VimOption::RelativeNumber(enabled) => {
+                editor.set_relative_line_number(Some(*enabled), cx);
+            }
+        }
+    }
+
     fn to_string(&self) -> &'static str {
         match self {
             VimOption::Wrap(true) => "wrap",
@@ -168,6 +189,8 @@ impl_internal_actions!(
         OnMatchingLines,
         ShellExec,
         VimSet,
+        VimNorm,
+        VimLet,
     ]
 );
 
@@ -194,21 +217,7 @@ pub fn register(editor: &mut Editor, cx: &mut Context<Vim>) {
     // Vim::action(editor, cx, |vim, action: &StartOfLine, window, cx| {
     Vim::action(editor, cx, |vim, action: &VimSet, window, cx| {
         for option in action.options.iter() {
-            vim.update_editor(window, cx, |_, editor, _, cx| match option {
-                VimOption::Wrap(true) => {
-                    editor
-                        .set_soft_wrap_mode(language::language_settings::SoftWrap::EditorWidth, cx);
-                }
-                VimOption::Wrap(false) => {
-                    editor.set_soft_wrap_mode(language::language_settings::SoftWrap::None, cx);
-                }
-                VimOption::Number(enabled) => {
-                    editor.set_show_line_numbers(*enabled, cx);
-                }
-                VimOption::RelativeNumber(enabled) => {
-                    editor.set_relative_line_number(Some(*enabled), cx);
-                }
-            });
+            vim.update_editor(window, cx, |_, editor, _, cx| option.apply(editor, cx));
         }
     });
     Vim::action(editor, cx, |vim, _: &VisualCommand, window, cx| {
@@ -353,6 +362,14 @@ pub fn register(editor: &mut Editor, cx: &mut Context<Vim>) {
 
     Vim::action(editor, cx, |vim, action: &ShellExec, window, cx| {
         action.run(vim, window, cx)
+    });
+
+    Vim::action(editor, cx, |vim, action: &VimNorm, window, cx| {
+        action.run(vim, window, cx)
+    });
+
+    Vim::action(editor, cx, |vim, action: &VimLet, window, cx| {
+        action.run(vim, window, cx)
     })
 }
 
@@ -973,15 +990,41 @@ pub fn command_interceptor(mut input: &str, cx: &App) -> Vec<CommandInterceptRes
             substitute.next();
             query.next();
         }
-        if let Some(replacement) = Replacement::parse(query) {
+        let previous = cx
+            .try_global::<VimGlobals>()
+            .and_then(|globals| globals.last_replacement.as_ref());
+        let gdefault = VimSettings::get_global(cx).gdefault;
+        // A bare `:s` repeats the last substitution, like `:&`.
+        let replacement = if query.peek().is_none() {
+            previous.map(|previous| Replacement::repeat(previous, "", false, gdefault))
+        } else {
+            Replacement::parse(query, previous, gdefault)
+        };
+        replacement.map(|replacement| {
             let range = range.clone().unwrap_or(CommandRange {
                 start: Position::CurrentLine { offset: 0 },
                 end: None,
             });
-            Some(ReplaceCommand { replacement, range }.boxed_clone())
-        } else {
-            None
-        }
+            ReplaceCommand { replacement, range }.boxed_clone()
+        })
+    } else if query.starts_with('&') || query.starts_with('~') {
+        let previous = cx
+            .try_global::<VimGlobals>()
+            .and_then(|globals| globals.last_replacement.as_ref());
+        let gdefault = VimSettings::get_global(cx).gdefault;
+        previous.map(|previous| {
+            let replacement =
+                Replacement::repeat(previous, &query[1..], query.starts_with('~'), gdefault);
+            let range = range.clone().unwrap_or(CommandRange {
+                start: Position::CurrentLine { offset: 0 },
+                end: None,
+            });
+            ReplaceCommand { replacement, range }.boxed_clone()
+        })
+    } else if query.starts_with("norm") {
+        VimNorm::parse(query, range.clone())
+    } else if query.starts_with("let ") || query.starts_with("unl") {
+        VimLet::parse(query)
     } else if query.starts_with('g') || query.starts_with('v') {
         let mut global = "global".chars().peekable();
         let mut query = query.chars().peekable();
@@ -1066,7 +1109,8 @@ fn generate_positions(string: &str, query: &str) -> Vec<usize> {
 pub(crate) struct OnMatchingLines {
     range: CommandRange,
     search: String,
-    action: WrappedAction,
+    /// The commands to run on each line, from splitting the command on `|`.
+    actions: Vec<WrappedAction>,
     invert: bool,
 }
 
@@ -1110,19 +1154,22 @@ impl OnMatchingLines {
         }
 
         let command: String = chars.collect();
-
-        let action = WrappedAction(
-            command_interceptor(&command, cx)
-                .first()?
-                .action
-                .boxed_clone(),
-        );
+        let actions = split_commands(&command)
+            .iter()
+            .map(|command| {
+                let action = command_interceptor(command, cx)
+                    .first()?
+                    .action
+                    .boxed_clone();
+                Some(WrappedAction(action))
+            })
+            .collect::<Option<Vec<_>>>()?;
 
         Some(Self {
             range,
             search,
             invert,
-            action,
+            actions,
         })
     }
 
@@ -1145,7 +1192,7 @@ impl OnMatchingLines {
             Some(Ok(result)) => result,
         };
 
-        let mut action = self.action.boxed_clone();
+        let mut actions = self.actions.clone();
         let mut last_pattern = self.search.clone();
 
         let mut regexes = match Regex::new(&self.search) {
@@ -1160,17 +1207,16 @@ impl OnMatchingLines {
                 return;
             }
         };
-        while let Some(inner) = action
-            .boxed_clone()
-            .as_any()
-            .downcast_ref::<OnMatchingLines>()
-        {
+        while let [action] = actions.as_slice() {
+            let Some(inner) = action.as_any().downcast_ref::<OnMatchingLines>() else {
+                break;
+            };
             let Some(regex) = Regex::new(&inner.search).ok() else {
                 break;
             };
             last_pattern = inner.search.clone();
-            action = inner.action.boxed_clone();
-            regexes.push((regex, !inner.invert))
+            regexes.push((regex, !inner.invert));
+            actions = inner.actions.clone();
         }
 
         if let Some(pane) = vim.pane(window, cx) {
@@ -1191,66 +1237,408 @@ impl OnMatchingLines {
             });
         };
 
-        vim.update_editor(window, cx, |_, editor, window, cx| {
-            let snapshot = editor.snapshot(window, cx);
-            let mut row = range.start.0;
-
-            let point_range = Point::new(range.start.0, 0)
-                ..snapshot
-                    .buffer_snapshot
-                    .clip_point(Point::new(range.end.0 + 1, 0), Bias::Left);
-            cx.spawn_in(window, async move |editor, cx| {
-                let new_selections = cx
-                    .background_spawn(async move {
-                        let mut line = String::new();
-                        let mut new_selections = Vec::new();
-                        let chunks = snapshot
-                            .buffer_snapshot
-                            .text_for_range(point_range)
-                            .chain(["\n"]);
-
-                        for chunk in chunks {
-                            for (newline_ix, text) in chunk.split('\n').enumerate() {
-                                if newline_ix > 0 {
-                                    if regexes.iter().all(|(regex, should_match)| {
-                                        regex.is_match(&line) == *should_match
-                                    }) {
-                                        new_selections
-                                            .push(Point::new(row, 0).to_display_point(&snapshot))
-                                    }
-                                    row += 1;
-                                    line.clear();
+        let Some(editor) = vim.editor() else {
+            return;
+        };
+        let snapshot = editor.update(cx, |editor, cx| editor.snapshot(window, cx));
+        let mut row = range.start.0;
+        let point_range = Point::new(range.start.0, 0)
+            ..snapshot
+                .buffer_snapshot
+                .clip_point(Point::new(range.end.0 + 1, 0), Bias::Left);
+        cx.spawn_in(window, async move |vim, cx| {
+            let lines = cx
+                .background_spawn(async move {
+                    let mut line = String::new();
+                    let mut lines = Vec::new();
+                    let chunks = snapshot
+                        .buffer_snapshot
+                        .text_for_range(point_range)
+                        .chain(["\n"]);
+
+                    for chunk in chunks {
+                        for (newline_ix, text) in chunk.split('\n').enumerate() {
+                            if newline_ix > 0 {
+                                if regexes.iter().all(|(regex, should_match)| {
+                                    regex.is_match(&line) == *should_match
+                                }) {
+                                    lines.push(
+                                        snapshot.buffer_snapshot.anchor_before(Point::new(row, 0)),
+                                    )
                                 }
-                                line.push_str(text)
+                                row += 1;
+                                line.clear();
                             }
+                            line.push_str(text)
                         }
+                    }
 
-                        new_selections
-                    })
-                    .await;
+                    lines
+                })
+                .await;
 
-                if new_selections.is_empty() {
-                    return;
+            if lines.is_empty() {
+                return anyhow::Ok(());
+            }
+
+            // Each command runs to completion before moving to the next line,
+            // and all of their edits are undone together.
+            editor.update_in(cx, |editor, window, cx| {
+                editor.start_transaction_at(Instant::now(), window, cx);
+            })?;
+            for line in lines {
+                editor.update_in(cx, |editor, window, cx| {
+                    let point = line.to_point(&editor.buffer().read(cx).snapshot(cx));
+                    editor.change_selections(None, window, cx, |s| {
+                        s.select_ranges([point..point]);
+                    });
+                })?;
+                for action in &actions {
+                    if let Some(norm) = action.as_any().downcast_ref::<VimNorm>() {
+                        norm.run_keystrokes(&vim, cx).await?;
+                    } else {
+                        let action = action.boxed_clone();
+                        cx.update(|window, cx| window.dispatch_action(action, cx))?;
+                    }
+                }
+            }
+            editor.update_in(cx, |editor, _, cx| {
+                editor.end_transaction_at(Instant::now(), cx);
+            })?;
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+}
+
+/// Splits a command run by `:g` on `|`, the way Vim separates commands on a
+/// line. `:normal` and a nested `:g` consume the rest of the line, and `\|`
+/// is a literal `|`.
+fn split_commands(command: &str) -> Vec<String> {
+    let mut commands = vec![String::new()];
+    let mut chars = command.chars().peekable();
+    while let Some(c) = chars.next() {
+        let current = commands.last_mut().unwrap();
+        if c == '\\' && chars.peek() == Some(&'|') {
+            current.push(chars.next().unwrap());
+        } else if c == '|' && !takes_rest_of_line(current) {
+            commands.push(String::new());
+        } else {
+            current.push(c);
+        }
+    }
+    commands
+        .into_iter()
+        .map(|command| command.trim_start().to_string())
+        .collect()
+}
+
+fn takes_rest_of_line(command: &str) -> bool {
+    let (_, command) = VimCommand::parse_range(command.trim_start());
+    let command = command.trim_start();
+    command.starts_with("norm")
+        || ["g", "global", "v", "vglobal"].iter().any(|name| {
+            command
+                .strip_prefix(name)
+                .and_then(|rest| rest.chars().next())
+                .is_some_and(|c| !c.is_alphanumeric() && !c.is_whitespace())
+        })
+}
+
+/// Runs `:norm[al][!] {keys}`: types `keys` in normal mode, once on each line
+/// of the range, or once at the cursor if there isn't one.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct VimNorm {
+    range: Option<CommandRange>,
+    keystrokes: Vec<Keystroke>,
+}
+
+impl VimNorm {
+    pub fn parse(query: &str, range: Option<CommandRange>) -> Option<Box<dyn Action>> {
+        let rest = query.strip_prefix("norm")?;
+        let rest = rest
+            .strip_prefix("al")
+            .or_else(|| rest.strip_prefix('a'))
+            .unwrap_or(rest);
+        // The bang skips mappings, and Zed doesn't have any to skip.
+        let rest = rest.strip_prefix('!').unwrap_or(rest);
+        let keys = rest.strip_prefix(' ')?;
+        let keystrokes = parse_keys(keys);
+        if keystrokes.is_empty() {
+            return None;
+        }
+        Some(VimNorm { range, keystrokes }.boxed_clone())
+    }
+
+    pub fn run(&self, vim: &mut Vim, window: &mut Window, cx: &mut Context<Vim>) {
+        let Some(editor) = vim.editor() else {
+            return;
+        };
+        let rows = match &self.range {
+            None => None,
+            Some(range) => {
+                let result = vim.update_editor(window, cx, |vim, editor, window, cx| {
+                    let range = range.buffer_range(vim, editor, window, cx)?;
+                    let snapshot = editor.buffer().read(cx).snapshot(cx);
+                    anyhow::Ok(
+                        (range.start.0..=range.end.0)
+                            .map(|row| snapshot.anchor_before(Point::new(row, 0)))
+                            .collect::<Vec<_>>(),
+                    )
+                });
+                match result {
+                    None => return,
+                    Some(e @ Err(_)) => {
+                        let Some(workspace) = vim.workspace(window) else {
+                            return;
+                        };
+                        workspace.update(cx, |workspace, cx| {
+                            e.notify_err(workspace, cx);
+                        });
+                        return;
+                    }
+                    Some(Ok(rows)) => Some(rows),
                 }
-                editor
-                    .update_in(cx, |editor, window, cx| {
-                        editor.start_transaction_at(Instant::now(), window, cx);
+            }
+        };
+
+        if vim.mode.is_visual() {
+            vim.switch_mode(Mode::Normal, false, window, cx);
+        }
+
+        let norm = self.clone();
+        cx.spawn_in(window, async move |vim, cx| {
+            editor.update_in(cx, |editor, window, cx| {
+                editor.start_transaction_at(Instant::now(), window, cx);
+            })?;
+            if let Some(rows) = rows {
+                for row in rows {
+                    editor.update_in(cx, |editor, window, cx| {
+                        let point = row.to_point(&editor.buffer().read(cx).snapshot(cx));
                         editor.change_selections(None, window, cx, |s| {
-                            s.replace_cursors_with(|_| new_selections);
+                            s.select_ranges([point..point]);
                         });
-                        window.dispatch_action(action, cx);
-                        cx.defer_in(window, move |editor, window, cx| {
-                            let newest = editor.selections.newest::<Point>(cx).clone();
-                            editor.change_selections(None, window, cx, |s| {
-                                s.select(vec![newest]);
-                            });
-                            editor.end_transaction_at(Instant::now(), cx);
-                        })
-                    })
-                    .ok();
-            })
-            .detach();
-        });
+                    })?;
+                    norm.run_keystrokes(&vim, cx).await?;
+                }
+            } else {
+                norm.run_keystrokes(&vim, cx).await?;
+            }
+            editor.update_in(cx, |editor, _, cx| {
+                editor.end_transaction_at(Instant::now(), cx);
+            })?;
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Types the keys once, and then abandons any command they left
+    /// unfinished, like Vim does.
+    async fn run_keystrokes(
+        &self,
+        vim: &WeakEntity<Vim>,
+        cx: &mut AsyncWindowContext,
+    ) -> Result<()> {
+        for keystroke in &self.keystrokes {
+            let keystroke = keystroke.clone();
+            cx.update(|window, cx| window.dispatch_keystroke(keystroke, cx))?;
+        }
+        let is_pending = vim.read_with(cx, |vim, _| {
+            !matches!(vim.mode, Mode::Normal | Mode::HelixNormal) || !vim.operator_stack.is_empty()
+        })?;
+        if is_pending {
+            let escape = Keystroke::parse("escape")?;
+            cx.update(|window, cx| window.dispatch_keystroke(escape, cx))?;
+        }
+        Ok(())
+    }
+}
+
+/// Converts the keys given to `:normal` or a mapping into keystrokes,
+/// including Vim's `<Esc>`-style notation for special keys.
+pub(crate) fn parse_keys(keys: &str) -> Vec<Keystroke> {
+    let mut keystrokes = Vec::new();
+    let mut rest = keys;
+    while let Some(c) = rest.chars().next() {
+        if c == '<' {
+            if let Some((name, after)) = rest[1..].split_once('>') {
+                if let Some(keystroke) = special_key(name) {
+                    keystrokes.push(keystroke);
+                    rest = after;
+                    continue;
+                }
+            }
+        }
+        let key = if c == ' ' {
+            "space".to_string()
+        } else {
+            c.to_string()
+        };
+        keystrokes.extend(Keystroke::parse(&key).ok());
+        rest = &rest[c.len_utf8()..];
+    }
+    keystrokes
+}
+
+fn special_key(name: &str) -> Option<Keystroke> {
+    let lowercase = name.to_lowercase();
+    let key = match lowercase.as_str() {
+        "esc" => "escape".to_string(),
+        "cr" | "enter" | "return" => "enter".to_string(),
+        "tab" => "tab".to_string(),
+        "bs" => "backspace".to_string(),
+        "del" => "delete".to_string(),
+        "space" => "space".to_string(),
+        "lt" => "<".to_string(),
+        "bar" => "|".to_string(),
+        "bslash" => "\\".to_string(),
+        "up" | "down" | "left" | "right" | "home" | "end" | "pageup" | "pagedown" => {
+            lowercase.clone()
+        }
+        _ if lowercase.starts_with('f') && lowercase[1..].parse::<u8>().is_ok() => {
+            lowercase.clone()
+        }
+        _ => {
+            let (modifier, key) = lowercase.split_once('-')?;
+            let modifier = match modifier {
+                "c" => "ctrl",
+                "s" => "shift",
+                "a" | "m" => "alt",
+                "d" => "cmd",
+                _ => return None,
+            };
+            let key = special_key(key)
+                .map(|keystroke| keystroke.key)
+                .or_else(|| (key.chars().count() == 1).then(|| key.to_string()))?;
+            format!("{modifier}-{key}")
+        }
+    };
+    Keystroke::parse(&key).ok()
+}
+
+/// Runs `:let {var} [op]= {expr}` or `:unl[et][!] {var} ...`, for variables
+/// that `\=` substitutions can read.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum VimLet {
+    Assign {
+        name: String,
+        /// The operator of a compound assignment like `+=`.
+        operator: Option<String>,
+        expression: String,
+    },
+    Unlet {
+        names: Vec<String>,
+        ignore_missing: bool,
+    },
+}
+
+impl VimLet {
+    pub fn parse(query: &str) -> Option<Box<dyn Action>> {
+        if let Some(rest) = query.strip_prefix("let ") {
+            let (target, expression) = rest.split_once('=')?;
+            let target = target.trim();
+            let (name, operator) = match target
+                .char_indices()
+                .find(|(_, c)| !(c.is_alphanumeric() || *c == '_' || *c == ':'))
+            {
+                Some((ix, _)) => (target[..ix].trim(), Some(target[ix..].trim().to_string())),
+                None => (target, None),
+            };
+            if name.is_empty()
+                || operator.as_ref().is_some_and(|operator| {
+                    !["+", "-", "*", "/", "%", ".", ".."].contains(&operator.as_str())
+                })
+            {
+                return None;
+            }
+            return Some(
+                VimLet::Assign {
+                    name: variable_name(name).to_string(),
+                    operator,
+                    expression: expression.trim().to_string(),
+                }
+                .boxed_clone(),
+            );
+        }
+
+        let rest = query.strip_prefix("unl")?;
+        let rest = rest
+            .strip_prefix("et")
+            .or_else(|| rest.strip_prefix('e'))
+            .unwrap_or(rest);
+        let (ignore_missing, rest) = match rest.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, rest),
+        };
+        let names = rest
+            .split_whitespace()
+            .map(|name| variable_name(name).to_string())
+            .collect::<Vec<_>>();
+        if names.is_empty() || !rest.starts_with(' ') {
+            return None;
+        }
+        Some(
+            VimLet::Unlet {
+                names,
+                ignore_missing,
+            }
+            .boxed_clone(),
+        )
+    }
+
+    pub fn run(&self, vim: &mut Vim, window: &mut Window, cx: &mut Context<Vim>) {
+        let result: Result<()> = match self {
+            VimLet::Assign {
+                name,
+                operator,
+                expression,
+            } => {
+                let lines = vim.update_editor(window, cx, |_, editor, _, cx| {
+                    let snapshot = editor.buffer().read(cx).snapshot(cx);
+                    let line = editor.selections.newest::<Point>(cx).head().row + 1;
+                    (line, snapshot.max_row().0 + 1)
+                });
+                let Some((line, last_line)) = lines else {
+                    return;
+                };
+                // `x += 1` evaluates as `x + (1)`.
+                let expression = match operator {
+                    Some(operator) => format!("{name} {operator} ({expression})"),
+                    None => expression.clone(),
+                };
+                let variables = &mut Vim::globals(cx).variables;
+                let scope = Scope {
+                    variables,
+                    line,
+                    last_line,
+                    submatches: &[],
+                };
+                evaluate(&expression, &scope).map(|value| {
+                    variables.insert(name.clone(), value);
+                })
+            }
+            VimLet::Unlet {
+                names,
+                ignore_missing,
+            } => {
+                let variables = &mut Vim::globals(cx).variables;
+                names.iter().try_for_each(|name| {
+                    if variables.remove(name).is_none() && !*ignore_missing {
+                        Err(anyhow!("No such variable: {name}"))
+                    } else {
+                        Ok(())
+                    }
+                })
+            }
+        };
+        if result.is_err() {
+            let Some(workspace) = vim.workspace(window) else {
+                return;
+            };
+            workspace.update(cx, |workspace, cx| {
+                result.notify_err(workspace, cx);
+            });
+        }
     }
 }
 
@@ -1910,4 +2298,110 @@ mod test {
             a
             ˇa"});
     }
+
+    #[gpui::test]
+    async fn test_command_substitute_flags(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇaa\naa\nbb", Mode::Normal);
+        cx.simulate_keystrokes(": % s / a / x enter");
+        cx.assert_state("xa\nˇxa\nbb", Mode::Normal);
+        cx.simulate_keystrokes(": % s / a / y / g enter");
+        cx.assert_state("xy\nˇxy\nbb", Mode::Normal);
+        cx.simulate_keystrokes(": % s / x / [ & ] enter");
+        cx.assert_state("[x]y\nˇ[x]y\nbb", Mode::Normal);
+        cx.simulate_keystrokes(": & & enter");
+        cx.assert_state("[x]y\nˇ[[x]]y\nbb", Mode::Normal);
+        cx.simulate_keystrokes(": s / y / ~ ! enter");
+        cx.assert_state("[x]y\nˇ[[x]][y]!\nbb", Mode::Normal);
+        cx.simulate_keystrokes(": % s / b / c / n enter");
+        cx.assert_state("[x]y\nˇ[[x]][y]!\nbb", Mode::Normal);
+
+        cx.set_state("ˇAb\nab", Mode::Normal);
+        cx.simulate_keystrokes(": % s / a / x / i enter");
+        cx.assert_state("xb\nˇxb", Mode::Normal);
+        cx.simulate_keystrokes("u");
+        cx.assert_state("ˇAb\nab", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_command_substitute_multiline(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇa,\nb,\nc,\nd", Mode::Normal);
+        cx.simulate_keystrokes(": % s / , \\ n / ; space enter");
+        cx.assert_state("ˇa; b; c; d", Mode::Normal);
+        cx.simulate_keystrokes("u");
+        cx.assert_state("ˇa,\nb,\nc,\nd", Mode::Normal);
+
+        // Matches start on a line of the range, but may end past it.
+        cx.simulate_keystrokes(": 2 s / \\ n c / - c enter");
+        cx.assert_state("a,\nˇb,-c,\nd", Mode::Normal);
+
+        // Other patterns still stop at the end of each line.
+        cx.set_state("ˇa  \n\nb ", Mode::Normal);
+        cx.simulate_keystrokes(": % s / \\ s + $ / / enter");
+        cx.assert_state("a\n\nˇb", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_command_substitute_confirm(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇa a\na\na", Mode::Normal);
+        cx.simulate_keystrokes(": % s / a / x / g c enter");
+        cx.assert_state("ˇa a\na\na", Mode::Normal);
+        cx.simulate_keystrokes("y n y");
+        cx.assert_state("x a\nx\nˇa", Mode::Normal);
+        cx.simulate_keystrokes("q");
+        cx.assert_state("x a\nx\nˇa", Mode::Normal);
+
+        // Confirmed patterns can span lines too.
+        cx.set_state("ˇa\nb\nc", Mode::Normal);
+        cx.simulate_keystrokes(": % s / \\ n / + / c enter");
+        cx.simulate_keystrokes("a");
+        assert_eq!(cx.buffer_text(), "a+b+c");
+        assert_eq!(cx.active_operator(), None);
+    }
+
+    #[gpui::test]
+    async fn test_command_substitute_expression(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇa\na\na", Mode::Normal);
+        cx.simulate_keystrokes(": % s / a / \\ = l i n e ( ' . ' ) * 1 0 enter");
+        cx.assert_state("10\n20\nˇ30", Mode::Normal);
+
+        cx.set_state("ˇx1\ny\nx2\nx3", Mode::Normal);
+        cx.simulate_keystrokes(": l e t space i space = space 1 0 enter");
+        cx.simulate_keystrokes(
+            ": g / x / s / / \\ = i / space | space l e t space i space + = space 1 enter",
+        );
+        cx.run_until_parked();
+        cx.assert_state("101\ny\n112\nˇ123", Mode::Normal);
+        cx.simulate_keystrokes("u");
+        cx.assert_state("ˇx1\ny\nx2\nx3", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_command_normal(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇa\nb\na", Mode::Normal);
+        cx.simulate_keystrokes(": % n o r m space A x enter");
+        cx.run_until_parked();
+        cx.assert_state("ax\nbx\naˇx", Mode::Normal);
+        cx.simulate_keystrokes("u");
+        cx.assert_state("ˇa\nb\na", Mode::Normal);
+
+        cx.simulate_keystrokes(": g / a / n o r m a l ! space d d enter");
+        cx.run_until_parked();
+        cx.assert_state("ˇb", Mode::Normal);
+        cx.simulate_keystrokes("u");
+        cx.assert_state("ˇa\nb\na", Mode::Normal);
+
+        cx.simulate_keystrokes(": v / a / n o r m space I < l t > < e s c > l x enter");
+        cx.run_until_parked();
+        cx.assert_state("a\nˇ<\na", Mode::Normal);
+    }
 }
@@ -0,0 +1,498 @@
+//! A small subset of Vim script expressions, enough for `:let` and for the
+//! `\=` form of `:substitute`.
+
+use anyhow::{Result, anyhow, bail};
+use collections::HashMap;
+use std::{fmt, iter::Peekable, str::Chars};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Number(i64),
+    String(String),
+}
+
+impl Value {
+    /// Converts the value to a number the way Vim does, using the leading
+    /// digits of a string and 0 if there aren't any.
+    pub fn to_number(&self) -> i64 {
+        match self {
+            Value::Number(number) => *number,
+            Value::String(string) => {
+                let string = string.trim_start();
+                let (sign, digits) = match string.strip_prefix('-') {
+                    Some(rest) => (-1, rest),
+                    None => (1, string),
+                };
+                let end = digits
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(digits.len());
+                sign * digits[..end].parse::<i64>().unwrap_or(0)
+            }
+        }
+    }
+
+    fn is_truthy(&self) -> bool {
+        self.to_number() != 0
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Number(number) => write!(f, "{number}"),
+            Value::String(string) => write!(f, "{string}"),
+        }
+    }
+}
+
+/// What an expression can refer to, other than literals.
+pub struct Scope<'a> {
+    pub variables: &'a HashMap<String, Value>,
+    /// The 1-based number of the line being evaluated on, for `line('.')`.
+    pub line: u32,
+    /// The 1-based number of the buffer's last line, for `line('$')`.
+    pub last_line: u32,
+    /// The text of the current match followed by its capture groups, for `submatch()`.
+    pub submatches: &'a [String],
+}
+
+/// Variables are global, so the `g:` prefix is optional.
+pub fn variable_name(name: &str) -> &str {
+    name.strip_prefix("g:").unwrap_or(name)
+}
+
+pub fn evaluate(source: &str, scope: &Scope) -> Result<Value> {
+    let tokens = tokenize(source)?;
+    let mut parser = Parser {
+        tokens: &tokens,
+        position: 0,
+        scope,
+    };
+    let value = parser.ternary()?;
+    if let Some(token) = parser.tokens.get(parser.position) {
+        bail!("trailing characters in expression: {token:?}");
+    }
+    Ok(value)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(i64),
+    String(String),
+    Identifier(String),
+    Operator(&'static str),
+}
+
+const OPERATORS: &[&str] = &[
+    "..", "==", "!=", ">=", "<=", "&&", "||", "+", "-", "*", "/", "%", ".", "(", ")", ",", "!",
+    "<", ">", "?", ":",
+];
+
+fn tokenize(source: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_digit() {
+            tokens.push(Token::Number(number(&mut chars)?));
+        } else if c == '\'' {
+            chars.next();
+            tokens.push(Token::String(single_quoted(&mut chars)?));
+        } else if c == '"' {
+            chars.next();
+            tokens.push(Token::String(double_quoted(&mut chars)?));
+        } else if c.is_alphabetic() || c == '_' {
+            tokens.push(Token::Identifier(identifier(&mut chars)));
+        } else {
+            let rest = chars.clone().collect::<String>();
+            let operator = OPERATORS
+                .iter()
+                .find(|operator| rest.starts_with(**operator))
+                .ok_or_else(|| anyhow!("unexpected character in expression: {c}"))?;
+            for _ in 0..operator.len() {
+                chars.next();
+            }
+            tokens.push(Token::Operator(operator));
+        }
+    }
+    Ok(tokens)
+}
+
+fn number(chars: &mut Peekable<Chars>) -> Result<i64> {
+    let mut digits = String::new();
+    while let Some(c) = chars.next_if(|c| c.is_ascii_alphanumeric()) {
+        digits.push(c);
+    }
+    let parsed = if let Some(hex) = digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        i64::from_str_radix(hex, 16)
+    } else {
+        digits.parse()
+    };
+    parsed.map_err(|_| anyhow!("invalid number: {digits}"))
+}
+
+fn single_quoted(chars: &mut Peekable<Chars>) -> Result<String> {
+    let mut string = String::new();
+    loop {
+        match chars.next() {
+            // Inside single quotes, a quote is escaped by doubling it.
+            Some('\'') if chars.next_if_eq(&'\'').is_some() => string.push('\''),
+            Some('\'') => return Ok(string),
+            Some(c) => string.push(c),
+            None => bail!("missing quote: '{string}"),
+        }
+    }
+}
+
+fn double_quoted(chars: &mut Peekable<Chars>) -> Result<String> {
+    let mut string = String::new();
+    loop {
+        match chars.next() {
+            Some('"') => return Ok(string),
+            Some('\\') => match chars.next() {
+                Some('n') => string.push('\n'),
+                Some('t') => string.push('\t'),
+                Some('r') => string.push('\r'),
+                Some(c) => string.push(c),
+                None => bail!("missing quote: \"{string}"),
+            },
+            Some(c) => string.push(c),
+            None => bail!("missing quote: \"{string}"),
+        }
+    }
+}
+
+fn identifier(chars: &mut Peekable<Chars>) -> String {
+    let mut identifier = String::new();
+    while let Some(c) = chars.next_if(|c| c.is_alphanumeric() || *c == '_') {
+        identifier.push(c);
+    }
+    // Scope prefixes, like `g:count`, are part of the name.
+    if identifier.len() == 1 && "gbwtlsav".contains(&identifier) {
+        let mut lookahead = chars.clone();
+        if lookahead.next() == Some(':')
+            && lookahead
+                .next()
+                .is_some_and(|c| c.is_alphabetic() || c == '_')
+        {
+            chars.next();
+            identifier.push(':');
+            while let Some(c) = chars.next_if(|c| c.is_alphanumeric() || *c == '_') {
+                identifier.push(c);
+            }
+        }
+    }
+    identifier
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    position: usize,
+    scope: &'a Scope<'a>,
+}
+
+impl Parser<'_> {
+    fn peek_operator(&self, operators: &[&str]) -> Option<&'static str> {
+        match self.tokens.get(self.position) {
+            Some(Token::Operator(operator)) if operators.contains(operator) => Some(operator),
+            _ => None,
+        }
+    }
+
+    fn expect(&mut self, operator: &str) -> Result<()> {
+        if self.peek_operator(&[operator]).is_none() {
+            bail!("expected {operator} in expression");
+        }
+        self.position += 1;
+        Ok(())
+    }
+
+    fn ternary(&mut self) -> Result<Value> {
+        let condition = self.or()?;
+        if self.peek_operator(&["?"]).is_none() {
+            return Ok(condition);
+        }
+        self.position += 1;
+        let if_true = self.ternary()?;
+        self.expect(":")?;
+        let if_false = self.ternary()?;
+        Ok(if condition.is_truthy() {
+            if_true
+        } else {
+            if_false
+        })
+    }
+
+    fn or(&mut self) -> Result<Value> {
+        let mut value = self.and()?;
+        while self.peek_operator(&["||"]).is_some() {
+            self.position += 1;
+            let rhs = self.and()?;
+            value = Value::Number((value.is_truthy() || rhs.is_truthy()) as i64);
+        }
+        Ok(value)
+    }
+
+    fn and(&mut self) -> Result<Value> {
+        let mut value = self.comparison()?;
+        while self.peek_operator(&["&&"]).is_some() {
+            self.position += 1;
+            let rhs = self.comparison()?;
+            value = Value::Number((value.is_truthy() && rhs.is_truthy()) as i64);
+        }
+        Ok(value)
+    }
+
+    fn comparison(&mut self) -> Result<Value> {
+        let lhs = self.sum()?;
+        let Some(operator) = self.peek_operator(&["==", "!=", "<", ">", "<=", ">="]) else {
+            return Ok(lhs);
+        };
+        self.position += 1;
+        let rhs = self.sum()?;
+        // Strings are only compared as strings with other strings.
+        let ordering = match (&lhs, &rhs) {
+            (Value::String(lhs), Value::String(rhs)) => lhs.cmp(rhs),
+            _ => lhs.to_number().cmp(&rhs.to_number()),
+        };
+        let result = match operator {
+            "==" => ordering.is_eq(),
+            "!=" => ordering.is_ne(),
+            "<" => ordering.is_lt(),
+            ">" => ordering.is_gt(),
+            "<=" => ordering.is_le(),
+            _ => ordering.is_ge(),
+        };
+        Ok(Value::Number(result as i64))
+    }
+
+    fn sum(&mut self) -> Result<Value> {
+        let mut value = self.product()?;
+        while let Some(operator) = self.peek_operator(&["+", "-", ".", ".."]) {
+            self.position += 1;
+            let rhs = self.product()?;
+            value = match operator {
+                "+" => Value::Number(value.to_number().wrapping_add(rhs.to_number())),
+                "-" => Value::Number(value.to_number().wrapping_sub(rhs.to_number())),
+                _ => Value::String(format!("{value}{rhs}")),
+            };
+        }
+        Ok(value)
+    }
+
+    fn product(&mut self) -> Result<Value> {
+        let mut value = self.unary()?;
+        while let Some(operator) = self.peek_operator(&["*", "/", "%"]) {
+            self.position += 1;
+            let lhs = value.to_number();
+            let rhs = self.unary()?.to_number();
+            if operator != "*" && rhs == 0 {
+                bail!("division by zero");
+            }
+            value = Value::Number(match operator {
+                "*" => lhs.wrapping_mul(rhs),
+                "/" => lhs.wrapping_div(rhs),
+                _ => lhs.wrapping_rem(rhs),
+            });
+        }
+        Ok(value)
+    }
+
+    fn unary(&mut self) -> Result<Value> {
+        match self.peek_operator(&["-", "+", "!"]) {
+            Some(operator) => {
+                self.position += 1;
+                let value = self.unary()?.to_number();
+                Ok(Value::Number(match operator {
+                    "-" => value.wrapping_neg(),
+                    "+" => value,
+                    _ => (value == 0) as i64,
+                }))
+            }
+            None => self.primary(),
+        }
+    }
+
+    fn primary(&mut self) -> Result<Value> {
+        let token = self
+            .tokens
+            .get(self.position)
+            .ok_or_else(|| anyhow!("unexpected end of expression"))?;
+        self.position += 1;
+        match token {
+            Token::Number(number) => Ok(Value::Number(*number)),
+            Token::String(string) => Ok(Value::String(string.clone())),
+            Token::Operator("(") => {
+                let value = self.ternary()?;
+                self.expect(")")?;
+                Ok(value)
+            }
+            Token::Identifier(name) if self.peek_operator(&["("]).is_some() => {
+                self.position += 1;
+                let mut arguments = Vec::new();
+                if self.peek_operator(&[")"]).is_none() {
+                    loop {
+                        arguments.push(self.ternary()?);
+                        if self.peek_operator(&[","]).is_none() {
+                            break;
+                        }
+                        self.position += 1;
+                    }
+                }
+                self.expect(")")?;
+                self.call(name, arguments)
+            }
+            Token::Identifier(name) => self
+                .scope
+                .variables
+                .get(variable_name(name))
+                .cloned()
+                .ok_or_else(|| anyhow!("undefined variable: {name}")),
+            Token::Operator(operator) => bail!("unexpected {operator} in expression"),
+        }
+    }
+
+    fn call(&self, name: &str, arguments: Vec<Value>) -> Result<Value> {
+        let argument = |ix: usize| {
+            arguments
+                .get(ix)
+                .ok_or_else(|| anyhow!("not enough arguments for function: {name}"))
+        };
+        Ok(match name {
+            "submatch" => {
+                let ix = argument(0)?.to_number();
+                let submatch = usize::try_from(ix)
+                    .ok()
+                    .and_then(|ix| self.scope.submatches.get(ix))
+                    .ok_or_else(|| anyhow!("invalid argument for submatch(): {ix}"))?;
+                Value::String(submatch.clone())
+            }
+            "line" => match argument(0)?.to_string().as_str() {
+                "." => Value::Number(self.scope.line as i64),
+                "$" => Value::Number(self.scope.last_line as i64),
+                other => bail!("unsupported argument for line(): {other}"),
+            },
+            "len" => Value::Number(argument(0)?.to_string().chars().count() as i64),
+            "toupper" => Value::String(argument(0)?.to_string().to_uppercase()),
+            "tolower" => Value::String(argument(0)?.to_string().to_lowercase()),
+            "trim" => Value::String(argument(0)?.to_string().trim().to_string()),
+            "repeat" => Value::String(
+                argument(0)?
+                    .to_string()
+                    .repeat(argument(1)?.to_number().max(0) as usize),
+            ),
+            "str2nr" => Value::Number(argument(0)?.to_number()),
+            "string" => Value::String(argument(0)?.to_string()),
+            "abs" => Value::Number(argument(0)?.to_number().wrapping_abs()),
+            "printf" => Value::String(printf(&argument(0)?.to_string(), &arguments[1..])?),
+            _ => bail!("unknown function: {name}"),
+        })
+    }
+}
+
+/// Supports the `%d`, `%s`, `%x`, `%X`, `%o` and `%c` conversions, with the
+/// `-` and `0` flags and a width.
+fn printf(format: &str, arguments: &[Value]) -> Result<String> {
+    let mut output = String::new();
+    let mut arguments = arguments.iter();
+    let mut chars = format.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            output.push(c);
+            continue;
+        }
+        if chars.next_if_eq(&'%').is_some() {
+            output.push('%');
+            continue;
+        }
+        let left_align = chars.next_if_eq(&'-').is_some();
+        let zero_pad = chars.next_if_eq(&'0').is_some();
+        let mut width = 0;
+        while let Some(digit) = chars.next_if(|c| c.is_ascii_digit()) {
+            width = width * 10 + digit.to_digit(10).unwrap() as usize;
+        }
+        let conversion = chars
+            .next()
+            .ok_or_else(|| anyhow!("incomplete format in printf(): {format}"))?;
+        let argument = arguments
+            .next()
+            .ok_or_else(|| anyhow!("not enough arguments for printf(): {format}"))?;
+        let text = match conversion {
+            'd' => argument.to_number().to_string(),
+            's' => argument.to_string(),
+            'x' => format!("{:x}", argument.to_number()),
+            'X' => format!("{:X}", argument.to_number()),
+            'o' => format!("{:o}", argument.to_number()),
+            'c' => char::from_u32(argument.to_number() as u32)
+                .map(String::from)
+                .unwrap_or_default(),
+            _ => bail!("unsupported format in printf(): %{conversion}"),
+        };
+        let padding = width.saturating_sub(text.chars().count());
+        if left_align {
+            output.push_str(&text);
+            output.extend(std::iter::repeat_n(' ', padding));
+        } else if zero_pad && conversion != 's' {
+            let (sign, digits) = match text.strip_prefix('-') {
+                Some(digits) => ("-", digits),
+                None => ("", text.as_str()),
+            };
+            output.push_str(sign);
+            output.extend(std::iter::repeat_n('0', padding));
+            output.push_str(digits);
+        } else {
+            output.extend(std::iter::repeat_n(' ', padding));
+            output.push_str(&text);
+        }
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_evaluate() {
+        let mut variables = HashMap::default();
+        variables.insert("i".to_string(), Value::Number(3));
+        variables.insert("name".to_string(), Value::String("zed".to_string()));
+        let submatches = ["foo-12".to_string(), "foo".to_string(), "12".to_string()];
+        let scope = Scope {
+            variables: &variables,
+            line: 7,
+            last_line: 20,
+            submatches: &submatches,
+        };
+        let evaluate = |source| evaluate(source, &scope).unwrap().to_string();
+
+        assert_eq!(evaluate("1 + 2 * 3"), "7");
+        assert_eq!(evaluate("(1 + 2) * 3 - -1"), "10");
+        assert_eq!(evaluate("17 % 5 . 'x'"), "2x");
+        assert_eq!(evaluate("line('.') - 1 . '. '"), "6. ");
+        assert_eq!(evaluate("line(\"$\")"), "20");
+        assert_eq!(evaluate("g:i * 2"), "6");
+        assert_eq!(evaluate("toupper(name) .. '!'"), "ZED!");
+        assert_eq!(evaluate("submatch(2) + 1"), "13");
+        assert_eq!(evaluate("submatch(1) == 'foo' ? 'yes' : 'no'"), "yes");
+        assert_eq!(evaluate("'it''s' . \"\\tx\""), "it's\tx");
+        assert_eq!(
+            evaluate("printf('%03d|%-4s|%x', i, 'ab', 255)"),
+            "003|ab  |ff"
+        );
+        assert_eq!(evaluate("'12abc' + 0x10"), "28");
+        assert_eq!(evaluate("!0 && 2 > 1"), "1");
+
+        assert!(evaluate_err("j", &scope).contains("undefined variable"));
+        assert!(evaluate_err("1 / 0", &scope).contains("division by zero"));
+        assert!(evaluate_err("(1", &scope).contains("expected )"));
+    }
+
+    fn evaluate_err(source: &str, scope: &Scope) -> String {
+        evaluate(source, scope).unwrap_err().to_string()
+    }
+}
@@ -1,16 +1,86 @@
-use editor::{DisplayPoint, Editor, movement, scroll::Autoscroll};
+use editor::{DisplayPoint, Editor, ToOffset, actions::SelectLine, movement, scroll::Autoscroll};
 use gpui::{Action, actions};
 use gpui::{Context, Window};
 use language::{CharClassifier, CharKind};
+use regex::RegexBuilder;
+use search::{BufferSearchBar, SearchOptions, buffer_search};
 
 use crate::motion::MotionKind;
+use crate::object::Object;
+use crate::state::{Operator, SearchState};
 use crate::{Vim, motion::Motion, state::Mode};
 
-actions!(vim, [HelixNormalAfter, HelixDelete]);
+actions!(
+    vim,
+    [
+        HelixNormalAfter,
+        HelixDelete,
+        HelixYank,
+        HelixCollapseSelection,
+        HelixFlipSelections,
+        HelixKeepPrimarySelection,
+        HelixRemovePrimarySelection,
+        HelixSelectLine,
+        HelixSelectRegex,
+        HelixSplitSelectionOnRegex,
+        HelixKeepSelectionsMatchingRegex,
+        HelixRemoveSelectionsMatchingRegex,
+        HelixSaveSelection
+    ]
+);
+
+/// What to do with the current selections once the regex prompted for by
+/// `s`, `S`, `K` or `alt-K` is submitted.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum RegexSelection {
+    /// Select every match inside the selections.
+    Select,
+    /// Split the selections on every match.
+    Split,
+    /// Keep only the selections that contain a match.
+    Keep,
+    /// Remove the selections that contain a match.
+    Remove,
+}
 
 pub fn register(editor: &mut Editor, cx: &mut Context<Vim>) {
     Vim::action(editor, cx, Vim::helix_normal_after);
     Vim::action(editor, cx, Vim::helix_delete);
+    Vim::action(editor, cx, Vim::helix_yank);
+    Vim::action(editor, cx, Vim::helix_collapse_selection);
+    Vim::action(editor, cx, Vim::helix_flip_selections);
+    Vim::action(editor, cx, Vim::helix_keep_primary_selection);
+    Vim::action(editor, cx, Vim::helix_remove_primary_selection);
+    Vim::action(editor, cx, Vim::helix_select_line);
+    Vim::action(editor, cx, |vim, _: &HelixSelectRegex, window, cx| {
+        vim.helix_prompt_for_regex(RegexSelection::Select, window, cx)
+    });
+    Vim::action(
+        editor,
+        cx,
+        |vim, _: &HelixSplitSelectionOnRegex, window, cx| {
+            vim.helix_prompt_for_regex(RegexSelection::Split, window, cx)
+        },
+    );
+    Vim::action(
+        editor,
+        cx,
+        |vim, _: &HelixKeepSelectionsMatchingRegex, window, cx| {
+            vim.helix_prompt_for_regex(RegexSelection::Keep, window, cx)
+        },
+    );
+    Vim::action(
+        editor,
+        cx,
+        |vim, _: &HelixRemoveSelectionsMatchingRegex, window, cx| {
+            vim.helix_prompt_for_regex(RegexSelection::Remove, window, cx)
+        },
+    );
+    Vim::action(editor, cx, |vim, _: &HelixSaveSelection, window, cx| {
+        vim.update_editor(window, cx, |_, editor, _, cx| {
+            editor.create_nav_history_entry(cx)
+        });
+    });
 }
 
 impl Vim {
@@ -259,6 +329,311 @@ impl Vim {
             editor.insert("", window, cx);
         });
     }
+
+    fn helix_yank(&mut self, _: &HelixYank, window: &mut Window, cx: &mut Context<Self>) {
+        self.update_editor(window, cx, |vim, editor, window, cx| {
+            let (map, selections) = editor.selections.all_adjusted_display(cx);
+            let ranges = selections
+                .into_iter()
+                .map(|selection| {
+                    let mut range = selection.range();
+                    // A cursor selects the character under it.
+                    if range.is_empty() {
+                        range.end = movement::right(&map, range.end);
+                    }
+                    range.start.to_point(&map)..range.end.to_point(&map)
+                })
+                .collect();
+            vim.copy_ranges(editor, MotionKind::Exclusive, true, ranges, window, cx);
+        });
+    }
+
+    fn helix_collapse_selection(
+        &mut self,
+        _: &HelixCollapseSelection,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.update_editor(window, cx, |_, editor, window, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), window, cx, |s| {
+                s.move_with(|map, selection| {
+                    let cursor = if selection.is_empty() || selection.reversed {
+                        selection.head()
+                    } else {
+                        movement::left(map, selection.head())
+                    };
+                    selection.collapse_to(cursor, selection.goal);
+                });
+            });
+        });
+    }
+
+    fn helix_flip_selections(
+        &mut self,
+        _: &HelixFlipSelections,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.update_editor(window, cx, |_, editor, window, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), window, cx, |s| {
+                s.move_with(|_, selection| {
+                    if !selection.is_empty() {
+                        selection.reversed = !selection.reversed;
+                    }
+                });
+            });
+        });
+    }
+
+    fn helix_keep_primary_selection(
+        &mut self,
+        _: &HelixKeepPrimarySelection,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.update_editor(window, cx, |_, editor, window, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), window, cx, |s| {
+                let primary = s.newest_anchor().clone();
+                s.select_anchors(vec![primary]);
+            });
+        });
+    }
+
+    fn helix_remove_primary_selection(
+        &mut self,
+        _: &HelixRemovePrimarySelection,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.update_editor(window, cx, |_, editor, window, cx| {
+            if editor.selections.count() == 1 {
+                return;
+            }
+            editor.change_selections(Some(Autoscroll::fit()), window, cx, |s| {
+                let primary_id = s.newest_anchor().id;
+                let rest = s
+                    .all_anchors()
+                    .iter()
+                    .filter(|selection| selection.id != primary_id)
+                    .cloned()
+                    .collect::<Vec<_>>();
+                s.select_anchors(rest);
+            });
+        });
+    }
+
+    /// Selects the current line, or extends a selection that already
+    /// covers whole lines by another `count` lines.
+    fn helix_select_line(
+        &mut self,
+        _: &HelixSelectLine,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let count = Vim::take_count(cx).unwrap_or(1);
+        self.update_editor(window, cx, |_, editor, window, cx| {
+            for _ in 0..count {
+                editor.select_line(&SelectLine, window, cx);
+            }
+        });
+    }
+
+    /// Selects a text object around each cursor, for `m i` and `m a`.
+    pub(crate) fn helix_object(
+        &mut self,
+        object: Object,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(Operator::Object { around }) = self.active_operator() else {
+            return self.normal_object(object, window, cx);
+        };
+        self.clear_operator(window, cx);
+        self.update_editor(window, cx, |_, editor, window, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), window, cx, |s| {
+                s.move_with(|map, selection| {
+                    let cursor = if selection.is_empty() || selection.reversed {
+                        selection.head()
+                    } else {
+                        movement::left(map, selection.head())
+                    };
+                    let mut object_selection = selection.clone();
+                    object_selection.collapse_to(cursor, selection.goal);
+                    if object.expand_selection(map, &mut object_selection, around)
+                        && !object_selection.is_empty()
+                    {
+                        object_selection.reversed = false;
+                        *selection = object_selection;
+                    }
+                });
+            });
+        });
+    }
+
+    /// Surrounds each selection with the pair for `text`, for `m s`.
+    pub(crate) fn helix_surround_add(
+        &mut self,
+        text: &str,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let (open, close) = match text {
+            "(" | ")" => ("(", ")"),
+            "[" | "]" => ("[", "]"),
+            "{" | "}" => ("{", "}"),
+            "<" | ">" => ("<", ">"),
+            text => (text, text),
+        };
+        self.update_editor(window, cx, |_, editor, window, cx| {
+            editor.transact(window, cx, |editor, window, cx| {
+                let (map, selections) = editor.selections.all_adjusted_display(cx);
+                let mut edits = Vec::new();
+                let mut new_selections = Vec::new();
+                for selection in selections {
+                    let mut range = selection.range();
+                    if range.is_empty() {
+                        range.end = movement::right(&map, range.end);
+                    }
+                    let start = range.start.to_offset(&map, editor::Bias::Left);
+                    let end = range.end.to_offset(&map, editor::Bias::Right);
+                    edits.push((start..start, open.to_string()));
+                    edits.push((end..end, close.to_string()));
+                    // Account for the text inserted by the preceding edits.
+                    let shift = new_selections.len() * (open.len() + close.len());
+                    new_selections.push(start + shift..end + shift + open.len() + close.len());
+                }
+                editor.edit(edits, cx);
+                editor.change_selections(Some(Autoscroll::fit()), window, cx, |s| {
+                    s.select_ranges(new_selections)
+                });
+            });
+        });
+    }
+
+    /// Opens the buffer search bar to prompt for the regex used by `s`,
+    /// `S`, `K` and `alt-K`.
+    fn helix_prompt_for_regex(
+        &mut self,
+        operation: RegexSelection,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(pane) = self.pane(window, cx) else {
+            return;
+        };
+        let prior_selections = self.editor_selections(window, cx);
+        pane.update(cx, |pane, cx| {
+            if let Some(search_bar) = pane.toolbar().read(cx).item_of_type::<BufferSearchBar>() {
+                search_bar.update(cx, |search_bar, cx| {
+                    if !search_bar.show(window, cx) {
+                        return;
+                    }
+                    search_bar.select_query(window, cx);
+                    cx.focus_self(window);
+                    search_bar.set_replacement(None, cx);
+                    search_bar.set_search_options(SearchOptions::REGEX, cx);
+                    self.search = SearchState {
+                        prior_selections,
+                        prior_mode: self.mode,
+                        regex_selection: Some(operation),
+                        ..Default::default()
+                    };
+                });
+            }
+        })
+    }
+
+    /// Applies the regex submitted in the search bar to the selections that
+    /// were active when it was deployed.
+    pub(crate) fn helix_regex_submit(
+        &mut self,
+        operation: RegexSelection,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(pane) = self.pane(window, cx) else {
+            return;
+        };
+        let prior_selections = std::mem::take(&mut self.search.prior_selections);
+        let query = pane.update(cx, |pane, cx| {
+            let search_bar = pane.toolbar().read(cx).item_of_type::<BufferSearchBar>()?;
+            search_bar.update(cx, |search_bar, cx| {
+                let query = search_bar.query(cx);
+                search_bar.dismiss(&buffer_search::Dismiss, window, cx);
+                Some(query)
+            })
+        });
+        let Some(query) = query.filter(|query| !query.is_empty()) else {
+            return;
+        };
+        let regex = match RegexBuilder::new(&query)
+            .case_insensitive(!query.chars().any(char::is_uppercase))
+            .build()
+        {
+            Ok(regex) => regex,
+            Err(error) => {
+                self.status_label = Some(format!("Invalid regex: {error}").into());
+                return;
+            }
+        };
+
+        let mut found = true;
+        self.update_editor(window, cx, |_, editor, window, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let mut new_selections = Vec::new();
+            for selection in &prior_selections {
+                let mut start = selection.start.to_offset(&snapshot);
+                let mut end = selection.end.to_offset(&snapshot);
+                if start > end {
+                    std::mem::swap(&mut start, &mut end);
+                }
+                // A cursor selects the character under it.
+                if start == end {
+                    end += snapshot.chars_at(end).next().map_or(0, char::len_utf8);
+                }
+                let text = snapshot.text_for_range(start..end).collect::<String>();
+                match operation {
+                    RegexSelection::Select => new_selections.extend(
+                        regex
+                            .find_iter(&text)
+                            .filter(|found| !found.is_empty())
+                            .map(|found| start + found.start()..start + found.end()),
+                    ),
+                    RegexSelection::Split => {
+                        let mut piece_start = start;
+                        for found in regex.find_iter(&text) {
+                            if start + found.start() > piece_start {
+                                new_selections.push(piece_start..start + found.start());
+                            }
+                            piece_start = start + found.end();
+                        }
+                        if piece_start < end {
+                            new_selections.push(piece_start..end);
+                        }
+                    }
+                    RegexSelection::Keep | RegexSelection::Remove => {
+                        if regex.is_match(&text) == (operation == RegexSelection::Keep) {
+                            new_selections.push(start..end);
+                        }
+                    }
+                }
+            }
+
+            if new_selections.is_empty() {
+                found = false;
+                editor.change_selections(Some(Autoscroll::fit()), window, cx, |s| {
+                    s.select_anchor_ranges(prior_selections.iter().cloned())
+                });
+            } else {
+                editor.change_selections(Some(Autoscroll::fit()), window, cx, |s| {
+                    s.select_ranges(new_selections)
+                });
+            }
+        });
+        if !found {
+            self.status_label = Some("No matches".into());
+        }
+    }
 }
 
 #[cfg(test)]
@@ -381,4 +756,170 @@ mod test {
             Mode::HelixNormal,
         );
     }
+
+    #[gpui::test]
+    async fn test_select_line(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+            The quˇick brown
+            fox jumps over
+            the lazy dog."},
+            Mode::HelixNormal,
+        );
+        cx.simulate_keystrokes("x");
+        cx.assert_state(
+            indoc! {"
+            «The quick brown
+            ˇ»fox jumps over
+            the lazy dog."},
+            Mode::HelixNormal,
+        );
+        cx.simulate_keystrokes("x");
+        cx.assert_state(
+            indoc! {"
+            «The quick brown
+            fox jumps over
+            ˇ»the lazy dog."},
+            Mode::HelixNormal,
+        );
+
+        cx.set_state(
+            indoc! {"
+            The quˇick brown
+            fox jumps over
+            the lazy dog."},
+            Mode::HelixNormal,
+        );
+        cx.simulate_keystrokes("2 x");
+        cx.assert_state(
+            indoc! {"
+            «The quick brown
+            fox jumps over
+            ˇ»the lazy dog."},
+            Mode::HelixNormal,
+        );
+    }
+
+    #[gpui::test]
+    async fn test_collapse_and_flip_selections(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("The «quickˇ» brown", Mode::HelixNormal);
+        cx.simulate_keystrokes("alt-;");
+        cx.assert_state("The «ˇquick» brown", Mode::HelixNormal);
+        cx.simulate_keystrokes(";");
+        cx.assert_state("The ˇquick brown", Mode::HelixNormal);
+
+        cx.set_state("The «quickˇ» brown", Mode::HelixNormal);
+        cx.simulate_keystrokes(";");
+        cx.assert_state("The quicˇk brown", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_primary_selection(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇone two ˇthree", Mode::HelixNormal);
+        cx.simulate_keystrokes(",");
+        cx.assert_state("one two ˇthree", Mode::HelixNormal);
+
+        cx.set_state("ˇone two ˇthree", Mode::HelixNormal);
+        cx.simulate_keystrokes("alt-,");
+        cx.assert_state("ˇone two three", Mode::HelixNormal);
+        cx.simulate_keystrokes("alt-,");
+        cx.assert_state("ˇone two three", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_yank(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("The «quickˇ» brown", Mode::HelixNormal);
+        cx.simulate_keystrokes("y");
+        cx.assert_state("The «quickˇ» brown", Mode::HelixNormal);
+        assert_eq!(
+            cx.read_from_clipboard()
+                .and_then(|item| item.text().as_deref().map(str::to_string)),
+            Some("quick".to_string())
+        );
+
+        cx.set_state("The ˇquick brown", Mode::HelixNormal);
+        cx.simulate_keystrokes("y");
+        assert_eq!(
+            cx.read_from_clipboard()
+                .and_then(|item| item.text().as_deref().map(str::to_string)),
+            Some("q".to_string())
+        );
+    }
+
+    #[gpui::test]
+    async fn test_match_mode_objects(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("The qu«iˇ»ck brown", Mode::HelixNormal);
+        cx.simulate_keystrokes("m i w");
+        cx.assert_state("The «quickˇ» brown", Mode::HelixNormal);
+
+        cx.set_state("call(foo, bˇar) baz", Mode::HelixNormal);
+        cx.simulate_keystrokes("m i (");
+        cx.assert_state("call(«foo, barˇ») baz", Mode::HelixNormal);
+
+        cx.set_state("call(foo, bˇar) baz", Mode::HelixNormal);
+        cx.simulate_keystrokes("m a (");
+        cx.assert_state("call«(foo, bar)ˇ» baz", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_match_mode_surround(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("The «quickˇ» brown", Mode::HelixNormal);
+        cx.simulate_keystrokes("m s (");
+        cx.assert_state("The «(quick)ˇ» brown", Mode::HelixNormal);
+
+        cx.set_state("«oneˇ» «twoˇ»", Mode::HelixNormal);
+        cx.simulate_keystrokes("m s [");
+        cx.assert_state("«[one]ˇ» «[two]ˇ»", Mode::HelixNormal);
+
+        cx.set_state("The {quˇick} brown", Mode::HelixNormal);
+        cx.simulate_keystrokes("m d {");
+        cx.assert_state("The ˇquick brown", Mode::HelixNormal);
+
+        cx.set_state("The {quˇick} brown", Mode::HelixNormal);
+        cx.simulate_keystrokes("m r { ]");
+        cx.assert_state("The ˇ[quick] brown", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_select_regex(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+            «The quick brown
+            fox jumps overˇ»
+            the lazy dog."},
+            Mode::HelixNormal,
+        );
+        cx.simulate_keystrokes("s o enter");
+        cx.assert_state(
+            indoc! {"
+            The quick br«oˇ»wn
+            f«oˇ»x jumps «oˇ»ver
+            the lazy dog."},
+            Mode::HelixNormal,
+        );
+
+        cx.set_state("«one, two, threeˇ»", Mode::HelixNormal);
+        cx.simulate_keystrokes("shift-s , space enter");
+        cx.assert_state("«oneˇ», «twoˇ», «threeˇ»", Mode::HelixNormal);
+
+        cx.simulate_keystrokes("shift-k t enter");
+        cx.assert_state("one, «twoˇ», «threeˇ»", Mode::HelixNormal);
+
+        cx.simulate_keystrokes("alt-shift-k h enter");
+        cx.assert_state("one, «twoˇ», three", Mode::HelixNormal);
+    }
 }
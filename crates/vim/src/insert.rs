@@ -1,7 +1,7 @@
-use crate::{Vim, state::Mode};
+use crate::{Vim, Vimrc, state::Mode};
 use editor::{Bias, Editor, scroll::Autoscroll};
 use gpui::{Action, Context, Window, actions};
-use language::SelectionGoal;
+use language::{Point, SelectionGoal};
 
 actions!(vim, [NormalBefore, TemporaryNormal]);
 
@@ -51,11 +51,72 @@ impl Vim {
         self.switch_mode(Mode::Normal, true, window, cx);
         self.temp_mode = true;
     }
+
+    /// Expands any vimrc abbreviation that ends right before a non-keyword
+    /// character typed in insert mode.
+    pub(crate) fn expand_abbreviations(
+        &mut self,
+        text: &str,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if !matches!(self.mode, Mode::Insert | Mode::Replace) {
+            return;
+        }
+        let mut chars = text.chars();
+        let (Some(typed), None) = (chars.next(), chars.next()) else {
+            return;
+        };
+        if is_keyword(typed) {
+            return;
+        }
+        let Some(vimrc) = cx
+            .try_global::<Vimrc>()
+            .filter(|vimrc| vimrc.has_abbreviations())
+            .cloned()
+        else {
+            return;
+        };
+
+        self.update_editor(window, cx, |_, editor, _, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let mut edits = Vec::new();
+            for selection in editor.selections.all::<Point>(cx) {
+                let head = selection.head();
+                let Some(column) = head.column.checked_sub(typed.len_utf8() as u32) else {
+                    continue;
+                };
+                let word_end = Point::new(head.row, column);
+                let line = snapshot
+                    .text_for_range(Point::new(head.row, 0)..word_end)
+                    .collect::<String>();
+                let word_start = line
+                    .char_indices()
+                    .rev()
+                    .find(|(_, c)| !is_keyword(*c))
+                    .map_or(0, |(ix, c)| ix + c.len_utf8());
+                if let Some(expansion) = vimrc.abbreviation(&line[word_start..]) {
+                    edits.push((
+                        Point::new(head.row, word_start as u32)..word_end,
+                        expansion.to_string(),
+                    ));
+                }
+            }
+            if !edits.is_empty() {
+                editor.edit(edits, cx);
+            }
+        });
+    }
+}
+
+fn is_keyword(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
 }
 
 #[cfg(test)]
 mod test {
     use crate::{
+        load_vimrc,
         state::Mode,
         test::{NeovimBackedTestContext, VimTestContext},
     };
@@ -136,4 +197,16 @@ mod test {
             .await;
         cx.shared_state().await.assert_eq("hehello\nˇllo\n");
     }
+
+    #[gpui::test]
+    async fn test_vimrc_abbreviations(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        cx.update(|_, cx| {
+            load_vimrc("iabbrev teh the", cx);
+        });
+
+        cx.set_state("ˇ", Mode::Normal);
+        cx.simulate_keystrokes("i t e h space t e h x . space t e h ,");
+        cx.assert_editor_state("the tehx. the,ˇ");
+    }
 }
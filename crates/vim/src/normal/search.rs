@@ -1,18 +1,29 @@
-use editor::Editor;
+use anyhow::{Result, anyhow};
+use editor::{Editor, EditorSettings, ToPoint, scroll::Autoscroll};
 use gpui::{Context, Window, actions, impl_actions, impl_internal_actions};
+use itertools::Itertools;
 use language::Point;
+use multi_buffer::MultiBufferRow;
+use regex::RegexBuilder;
 use schemars::JsonSchema;
 use search::{BufferSearchBar, SearchOptions, buffer_search};
 use serde_derive::Deserialize;
-use std::{iter::Peekable, str::Chars, time::Duration};
+use settings::Settings;
+use std::{
+    iter::{self, Peekable},
+    ops::Range,
+    str::Chars,
+    sync::Arc,
+};
 use util::serde::default_true;
 use workspace::{notifications::NotifyResultExt, searchable::Direction};
 
 use crate::{
     Vim,
     command::CommandRange,
+    expression::{Scope, evaluate},
     motion::Motion,
-    state::{Mode, SearchState},
+    state::{Mode, Operator, SearchState},
 };
 
 #[derive(Clone, Debug, Deserialize, JsonSchema, PartialEq)]
@@ -63,8 +74,14 @@ pub struct ReplaceCommand {
 pub(crate) struct Replacement {
     search: String,
     replacement: String,
+    /// A `\=` expression evaluated for each match in place of `replacement`.
+    expression: Option<String>,
     should_replace_all: bool,
-    is_case_sensitive: bool,
+    should_confirm: bool,
+    should_count_only: bool,
+    should_ignore_errors: bool,
+    /// `None` defers to the smartcase setting.
+    is_case_sensitive: Option<bool>,
 }
 
 actions!(vim, [SearchSubmit, MoveToNextMatch, MoveToPreviousMatch]);
@@ -170,6 +187,7 @@ impl Vim {
                         prior_selections,
                         prior_operator: self.operator_stack.last().cloned(),
                         prior_mode,
+                        regex_selection: None,
                     }
                 });
             }
@@ -183,6 +201,10 @@ impl Vim {
     }
 
     pub fn search_submit(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(operation) = self.search.regex_selection.take() {
+            self.helix_regex_submit(operation, window, cx);
+            return;
+        }
         self.store_visual_marks(window, cx);
         let Some(pane) = self.pane(window, cx) else {
             return;
@@ -422,107 +444,362 @@ impl Vim {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        let replacement = action.replacement.clone();
-        let Some(((pane, workspace), editor)) = self
-            .pane(window, cx)
-            .zip(self.workspace(window))
-            .zip(self.editor())
-        else {
+        let mut replacement = action.replacement.clone();
+        let Some((pane, workspace)) = self.pane(window, cx).zip(self.workspace(window)) else {
             return;
         };
-        if let Some(result) = self.update_editor(window, cx, |vim, editor, window, cx| {
-            let range = action.range.buffer_range(vim, editor, window, cx)?;
-            let snapshot = &editor.snapshot(window, cx).buffer_snapshot;
-            let end_point = Point::new(range.end.0, snapshot.line_len(range.end));
-            let range = snapshot.anchor_before(Point::new(range.start.0, 0))
-                ..snapshot.anchor_after(end_point);
-            editor.set_search_within_ranges(&[range], cx);
-            anyhow::Ok(())
-        }) {
-            workspace.update(cx, |workspace, cx| {
-                result.notify_err(workspace, cx);
-            })
-        }
-        let vim = cx.entity().clone();
-        pane.update(cx, |pane, cx| {
-            let Some(search_bar) = pane.toolbar().read(cx).item_of_type::<BufferSearchBar>() else {
+        let search_bar = pane
+            .read(cx)
+            .toolbar()
+            .read(cx)
+            .item_of_type::<BufferSearchBar>();
+        if replacement.search.is_empty() {
+            let Some(search_bar) = search_bar.as_ref() else {
                 return;
             };
-            let search = search_bar.update(cx, |search_bar, cx| {
-                if !search_bar.show(window, cx) {
-                    return None;
+            replacement.search = search_bar.read(cx).query(cx);
+        }
+        Vim::globals(cx).last_replacement = Some(replacement.clone());
+
+        let is_case_sensitive = replacement.is_case_sensitive.unwrap_or_else(|| {
+            !EditorSettings::get_global(cx).use_smartcase_search
+                || replacement.search.chars().any(|c| c.is_uppercase())
+        });
+        let mut options = SearchOptions::REGEX;
+        options.set(SearchOptions::CASE_SENSITIVE, is_case_sensitive);
+
+        let result = self
+            .update_editor(window, cx, |vim, editor, window, cx| {
+                vim.substitutions(
+                    &replacement,
+                    &action.range,
+                    editor,
+                    is_case_sensitive,
+                    window,
+                    cx,
+                )
+            })
+            .unwrap_or_else(|| Ok(Vec::new()));
+        match result {
+            Ok(substitutions) if substitutions.is_empty() => {
+                if !replacement.should_ignore_errors {
+                    let result = Err::<(), _>(anyhow!("Pattern not found: {}", replacement.search));
+                    workspace.update(cx, |workspace, cx| result.notify_err(workspace, cx));
                 }
+            }
+            // Like Vim, the `c` flag is ignored when only counting.
+            Ok(substitutions) if replacement.should_count_only => {
+                let matches = substitutions.len();
+                let lines = substitutions
+                    .iter()
+                    .map(|substitution| substitution.row)
+                    .dedup()
+                    .count();
+                let match_label = if matches == 1 { "match" } else { "matches" };
+                let line_label = if lines == 1 { "line" } else { "lines" };
+                self.status_label =
+                    Some(format!("{matches} {match_label} on {lines} {line_label}").into());
+                cx.notify();
+            }
+            Ok(substitutions) if replacement.should_confirm => {
+                self.start_confirmed_substitution(substitutions, window, cx);
+            }
+            Ok(substitutions) => {
+                self.update_editor(window, cx, |_, editor, window, cx| {
+                    apply_substitutions(substitutions, editor, window, cx)
+                });
+            }
+            result @ Err(_) => {
+                workspace.update(cx, |workspace, cx| result.notify_err(workspace, cx));
+            }
+        }
+
+        // Leave the pattern in the search bar so that `n` and `N` find it next.
+        if let Some(search_bar) = search_bar {
+            search_bar.update(cx, |search_bar, cx| {
+                if search_bar.query(cx) != replacement.search && search_bar.show(window, cx) {
+                    drop(search_bar.search(&replacement.search, Some(options), window, cx));
+                }
+            });
+        }
+    }
+
+    /// Finds the matches of a substitution in `range`, along with the text
+    /// that replaces each of them.
+    ///
+    /// Patterns that contain `\n` are matched against the rest of the buffer,
+    /// so that they can span lines, and may start on any line of the range.
+    /// Other patterns are matched one line at a time, so that `$` and classes
+    /// like `\s` stop at the end of the line as they do in Vim.
+    fn substitutions(
+        &mut self,
+        replacement: &Replacement,
+        range: &CommandRange,
+        editor: &mut Editor,
+        is_case_sensitive: bool,
+        window: &mut Window,
+        cx: &mut Context<Editor>,
+    ) -> Result<Vec<Substitution>> {
+        let range = range.buffer_range(self, editor, window, cx)?;
+        let spans_lines = matches_newline(&replacement.search);
+        let regex = RegexBuilder::new(&replacement.search)
+            .case_insensitive(!is_case_sensitive)
+            .multi_line(spans_lines)
+            .build()?;
+        let template = unescape_replacement(&replacement.replacement);
+        let variables = &Vim::globals(cx).variables.clone();
+        let snapshot = editor.buffer().read(cx).snapshot(cx);
+        let last_line = snapshot.max_row().0 + 1;
+        let end_row = range.end.0.min(snapshot.max_row().0);
+        if range.start.0 > end_row {
+            return Ok(Vec::new());
+        }
 
-                let mut options = SearchOptions::REGEX;
-                if replacement.is_case_sensitive {
-                    options.set(SearchOptions::CASE_SENSITIVE, true)
+        let haystacks = if spans_lines {
+            vec![Point::new(range.start.0, 0)..snapshot.max_point()]
+        } else {
+            (range.start.0..=end_row)
+                .map(|row| {
+                    Point::new(row, 0)..Point::new(row, snapshot.line_len(MultiBufferRow(row)))
+                })
+                .collect()
+        };
+
+        let mut substitutions = Vec::new();
+        let mut last_row = None;
+        for haystack in haystacks {
+            let offset = snapshot.point_to_offset(haystack.start);
+            let text = snapshot.text_for_range(haystack).collect::<String>();
+            for captures in regex.captures_iter(&text) {
+                let whole = captures.get(0).unwrap();
+                let range = offset + whole.start()..offset + whole.end();
+                let row = snapshot.offset_to_point(range.start).row;
+                if row > end_row {
+                    break;
                 }
-                let search = if replacement.search.is_empty() {
-                    search_bar.query(cx)
+                // Without the `g` flag, only the first match on each line is
+                // replaced.
+                if !replacement.should_replace_all && last_row == Some(row) {
+                    continue;
+                }
+                last_row = Some(row);
+
+                let new_text = if let Some(expression) = &replacement.expression {
+                    let submatches = captures
+                        .iter()
+                        .map(|submatch| submatch.map_or("", |m| m.as_str()).to_string())
+                        .collect::<Vec<_>>();
+                    let scope = Scope {
+                        variables,
+                        line: row + 1,
+                        last_line,
+                        submatches: &submatches,
+                    };
+                    evaluate(expression, &scope)?.to_string()
                 } else {
-                    replacement.search
+                    let mut new_text = String::new();
+                    captures.expand(&template, &mut new_text);
+                    new_text
                 };
-                if search_bar.should_use_smartcase_search(cx) {
-                    options.set(
-                        SearchOptions::CASE_SENSITIVE,
-                        search_bar.is_contains_uppercase(&search),
-                    );
-                }
-                search_bar.set_replacement(Some(&replacement.replacement), cx);
-                Some(search_bar.search(&search, Some(options), window, cx))
-            });
-            let Some(search) = search else { return };
-            let search_bar = search_bar.downgrade();
-            cx.spawn_in(window, async move |_, cx| {
-                search.await?;
-                search_bar.update_in(cx, |search_bar, window, cx| {
-                    if replacement.should_replace_all {
-                        search_bar.select_last_match(window, cx);
-                        search_bar.replace_all(&Default::default(), window, cx);
-                        cx.spawn(async move |_, cx| {
-                            cx.background_executor()
-                                .timer(Duration::from_millis(200))
-                                .await;
-                            editor
-                                .update(cx, |editor, cx| editor.clear_search_within_ranges(cx))
-                                .ok();
-                        })
-                        .detach();
-                        vim.update(cx, |vim, cx| {
-                            vim.move_cursor(
-                                Motion::StartOfLine {
-                                    display_lines: false,
-                                },
-                                None,
-                                window,
-                                cx,
-                            )
-                        });
-                    }
-                })?;
-                anyhow::Ok(())
+                substitutions.push(Substitution {
+                    range,
+                    row,
+                    new_text,
+                });
+            }
+        }
+        Ok(substitutions)
+    }
+
+    /// Starts an `s///c` substitution, which highlights each match in turn and
+    /// waits in [`Operator::ConfirmSubstitution`] for it to be confirmed.
+    fn start_confirmed_substitution(
+        &mut self,
+        substitutions: Vec<Substitution>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(editor) = self.editor() else {
+            return;
+        };
+        let snapshot = editor.read(cx).buffer().read(cx).snapshot(cx);
+        // The anchors keep each match in place as the ones before it are
+        // replaced. They're kept in reverse, so that the next one is last.
+        self.pending_substitutions = substitutions
+            .into_iter()
+            .rev()
+            .map(|substitution| {
+                let range = snapshot.anchor_after(substitution.range.start)
+                    ..snapshot.anchor_before(substitution.range.end);
+                (range, substitution.new_text)
             })
-            .detach_and_log_err(cx);
-        })
+            .collect();
+        self.push_operator(Operator::ConfirmSubstitution, window, cx);
+        self.highlight_pending_substitution(window, cx);
     }
+
+    fn highlight_pending_substitution(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some((range, _)) = self.pending_substitutions.last().cloned() else {
+            self.update_editor(window, cx, |_, editor, _, cx| {
+                editor.clear_background_highlights::<ConfirmSubstitutionHighlight>(cx);
+            });
+            self.clear_operator(window, cx);
+            return;
+        };
+        self.update_editor(window, cx, |_, editor, window, cx| {
+            editor.highlight_background::<ConfirmSubstitutionHighlight>(
+                &[range.clone()],
+                |theme| theme.search_match_background,
+                cx,
+            );
+            editor.change_selections(Some(Autoscroll::center()), window, cx, |s| {
+                s.select_ranges([range.start..range.start])
+            });
+        });
+    }
+
+    /// Handles a key typed in answer to the `s///c` prompt.
+    pub(crate) fn confirm_substitution(
+        &mut self,
+        text: Arc<str>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(current) = self.pending_substitutions.pop() else {
+            self.highlight_pending_substitution(window, cx);
+            return;
+        };
+        let edits = match text.as_ref() {
+            "y" => vec![current],
+            "n" => Vec::new(),
+            "a" => iter::once(current)
+                .chain(self.pending_substitutions.drain(..).rev())
+                .collect(),
+            "l" => {
+                self.pending_substitutions.clear();
+                vec![current]
+            }
+            _ => {
+                self.pending_substitutions.clear();
+                Vec::new()
+            }
+        };
+        if !edits.is_empty() {
+            self.update_editor(window, cx, |_, editor, window, cx| {
+                editor.transact(window, cx, |editor, _, cx| editor.edit(edits, cx));
+            });
+        }
+        self.highlight_pending_substitution(window, cx);
+    }
+
+    /// Abandons an `s///c` substitution when its operator is cleared.
+    pub(crate) fn clear_pending_substitutions(
+        &mut self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.pending_substitutions.is_empty() {
+            return;
+        }
+        self.pending_substitutions.clear();
+        self.update_editor(window, cx, |_, editor, _, cx| {
+            editor.clear_background_highlights::<ConfirmSubstitutionHighlight>(cx);
+        });
+    }
+}
+
+/// A match of a `:substitute` pattern and the text that replaces it.
+struct Substitution {
+    range: Range<usize>,
+    /// The row the match starts on.
+    row: u32,
+    new_text: String,
+}
+
+struct ConfirmSubstitutionHighlight;
+
+/// Replaces all the matches as a single transaction.
+fn apply_substitutions(
+    substitutions: Vec<Substitution>,
+    editor: &mut Editor,
+    window: &mut Window,
+    cx: &mut Context<Editor>,
+) {
+    let Some(last_row) = substitutions.last().map(|substitution| substitution.row) else {
+        return;
+    };
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let cursor = snapshot.anchor_before(Point::new(last_row, 0));
+    editor.transact(window, cx, |editor, window, cx| {
+        editor.edit(
+            substitutions
+                .into_iter()
+                .map(|substitution| (substitution.range, substitution.new_text)),
+            cx,
+        );
+        // Like Vim, leave the cursor on the last line that changed.
+        let snapshot = editor.buffer().read(cx).snapshot(cx);
+        let row = cursor.to_point(&snapshot).row;
+        let indent = snapshot.indent_size_for_line(MultiBufferRow(row)).len;
+        let point = Point::new(row, indent);
+        editor.change_selections(None, window, cx, |s| s.select_ranges([point..point]));
+    });
+}
+
+/// Returns whether a pattern contains `\n`, and so can match across lines.
+fn matches_newline(pattern: &str) -> bool {
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' && chars.next() == Some('n') {
+            return true;
+        }
+    }
+    false
+}
+
+/// Undoes the escaping the search bar applies to replacement templates.
+fn unescape_replacement(replacement: &str) -> String {
+    let mut result = String::with_capacity(replacement.len());
+    let mut chars = replacement.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some('\\') => result.push('\\'),
+            Some(c) => {
+                result.push('\\');
+                result.push(c);
+            }
+            None => result.push('\\'),
+        }
+    }
+    result
 }
 
 impl Replacement {
     // convert a vim query into something more usable by zed.
     // we don't attempt to fully convert between the two regex syntaxes,
     // but we do flip \( and \) to ( and ) (and vice-versa) in the pattern,
-    // and convert \0..\9 to $0..$9 in the replacement so that common idioms work.
-    pub(crate) fn parse(mut chars: Peekable<Chars>) -> Option<Replacement> {
+    // and convert Vim's replacement specials (\0..\9, &, ~, \r) to the
+    // search bar's template syntax so that common idioms work.
+    pub(crate) fn parse(
+        mut chars: Peekable<Chars>,
+        previous: Option<&Replacement>,
+        gdefault: bool,
+    ) -> Option<Replacement> {
         let delimiter = chars
             .next()
             .filter(|c| !c.is_alphanumeric() && *c != '"' && *c != '|' && *c != '\'')?;
 
         let mut search = String::new();
         let mut replacement = String::new();
+        let mut expression: Option<String> = None;
         let mut flags = String::new();
 
-        let mut buffer = &mut search;
-
         let mut escaped = false;
         // 0 - parsing search
         // 1 - parsing replacement
@@ -530,54 +807,128 @@ impl Replacement {
         let mut phase = 0;
 
         for c in chars {
-            if escaped {
+            if phase == 2 {
+                if c == delimiter {
+                    break;
+                }
+                flags.push(c);
+            } else if escaped {
                 escaped = false;
-                if phase == 1 && c.is_ascii_digit() {
-                    buffer.push('$')
-                // unescape escaped parens
-                } else if phase == 0 && c == '(' || c == ')' {
-                } else if c != delimiter {
-                    buffer.push('\\')
+                if let Some(expression) = expression.as_mut() {
+                    if c != delimiter {
+                        expression.push('\\');
+                    }
+                    expression.push(c);
+                } else if phase == 0 {
+                    // unescape escaped parens
+                    if c != '(' && c != ')' && c != delimiter {
+                        search.push('\\');
+                    }
+                    search.push(c);
+                } else {
+                    match c {
+                        '=' if replacement.is_empty() => expression = Some(String::new()),
+                        '0'..='9' => {
+                            replacement.push_str("${");
+                            replacement.push(c);
+                            replacement.push('}');
+                        }
+                        'r' | 'n' => replacement.push_str("\\n"),
+                        't' => replacement.push_str("\\t"),
+                        '\\' => replacement.push_str("\\\\"),
+                        '&' | '~' => replacement.push(c),
+                        c if c == delimiter => replacement.push(c),
+                        c => {
+                            replacement.push('\\');
+                            replacement.push(c);
+                        }
+                    }
                 }
-                buffer.push(c)
             } else if c == '\\' {
                 escaped = true;
             } else if c == delimiter {
-                if phase == 0 {
-                    buffer = &mut replacement;
-                    phase = 1;
-                } else if phase == 1 {
-                    buffer = &mut flags;
-                    phase = 2;
-                } else {
-                    break;
+                phase += 1;
+            } else if let Some(expression) = expression.as_mut() {
+                expression.push(c);
+            } else if phase == 0 {
+                // escape unescaped parens
+                if c == '(' || c == ')' {
+                    search.push('\\');
                 }
+                search.push(c);
             } else {
-                // escape unescaped parens
-                if phase == 0 && c == '(' || c == ')' {
-                    buffer.push('\\')
+                match c {
+                    '&' => replacement.push_str("${0}"),
+                    '~' => replacement.push_str(previous.map_or("", |p| &p.replacement)),
+                    '$' => replacement.push_str("$$"),
+                    c => replacement.push(c),
                 }
-                buffer.push(c)
             }
         }
 
         let mut replacement = Replacement {
             search,
             replacement,
-            should_replace_all: true,
-            is_case_sensitive: true,
+            expression,
+            should_replace_all: gdefault,
+            should_confirm: false,
+            should_count_only: false,
+            should_ignore_errors: false,
+            is_case_sensitive: None,
+        };
+        replacement.apply_flags(&flags, previous);
+        Some(replacement)
+    }
+
+    /// Builds the replacement for `:&` and `:~`, which repeat the previous
+    /// substitution. `:&&` keeps its flags, and `:~` searches for the last
+    /// search pattern instead of the last substitute pattern.
+    pub(crate) fn repeat(
+        previous: &Replacement,
+        flags: &str,
+        use_last_search: bool,
+        gdefault: bool,
+    ) -> Replacement {
+        let mut replacement = Replacement {
+            search: previous.search.clone(),
+            replacement: previous.replacement.clone(),
+            expression: previous.expression.clone(),
+            should_replace_all: gdefault,
+            should_confirm: false,
+            should_count_only: false,
+            should_ignore_errors: false,
+            is_case_sensitive: None,
         };
+        if use_last_search {
+            replacement.search.clear();
+        }
+        replacement.apply_flags(flags, Some(previous));
+        replacement
+    }
 
-        for c in flags.chars() {
+    fn apply_flags(&mut self, flags: &str, previous: Option<&Replacement>) {
+        let mut flags = flags.trim().chars().peekable();
+        // `&` must come first, and keeps the flags of the previous substitution.
+        if flags.next_if_eq(&'&').is_some() {
+            if let Some(previous) = previous {
+                self.should_replace_all = previous.should_replace_all;
+                self.should_confirm = previous.should_confirm;
+                self.should_count_only = previous.should_count_only;
+                self.should_ignore_errors = previous.should_ignore_errors;
+                self.is_case_sensitive = previous.is_case_sensitive;
+            }
+        }
+        for c in flags {
             match c {
-                'g' | 'I' => {}
-                'c' | 'n' => replacement.should_replace_all = false,
-                'i' => replacement.is_case_sensitive = false,
+                'g' => self.should_replace_all = !self.should_replace_all,
+                'c' => self.should_confirm = true,
+                'n' => self.should_count_only = true,
+                'e' => self.should_ignore_errors = true,
+                'i' => self.is_case_sensitive = Some(false),
+                'I' => self.is_case_sensitive = Some(true),
                 _ => {}
             }
         }
-
-        Some(replacement)
     }
 }
 
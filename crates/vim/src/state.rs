@@ -1,6 +1,9 @@
 use crate::command::command_interceptor;
+use crate::expression::Value;
+use crate::helix::RegexSelection;
 use crate::motion::MotionKind;
 use crate::normal::repeat::Replayer;
+use crate::normal::search::Replacement;
 use crate::surrounds::SurroundsType;
 use crate::{ToggleMarksView, ToggleRegistersView, UseSystemClipboard, Vim, VimAddon, VimSettings};
 use crate::{motion::Motion, object::Object};
@@ -130,6 +133,7 @@ pub enum Operator {
     ToggleComments,
     ReplaceWithRegister,
     Exchange,
+    ConfirmSubstitution,
 }
 
 #[derive(Default, Clone, Debug)]
@@ -222,6 +226,14 @@ pub struct VimGlobals {
     pub focused_vim: Option<WeakEntity<Vim>>,
 
     pub marks: HashMap<EntityId, Entity<MarksState>>,
+
+    /// The last `:substitute`, repeated by `:&` and `:~`.
+    pub last_replacement: Option<Replacement>,
+    /// Variables assigned with `:let`.
+    pub variables: HashMap<String, Value>,
+    /// Set while the keystrokes of a vimrc `noremap` mapping are dispatched,
+    /// so that they skip the vimrc's own mappings.
+    pub noremap: bool,
 }
 
 pub struct MarksState {
@@ -925,6 +937,8 @@ pub struct SearchState {
     pub prior_selections: Vec<Range<Anchor>>,
     pub prior_operator: Option<Operator>,
     pub prior_mode: Mode,
+    /// Set when the search bar is prompting for a Helix selection regex.
+    pub regex_selection: Option<RegexSelection>,
 }
 
 impl Operator {
@@ -956,6 +970,7 @@ impl Operator {
             Operator::Rewrap => "gq",
             Operator::ReplaceWithRegister => "gr",
             Operator::Exchange => "cx",
+            Operator::ConfirmSubstitution => "s///c",
             Operator::Outdent => "<",
             Operator::Uppercase => "gU",
             Operator::Lowercase => "gu",
@@ -979,13 +994,16 @@ impl Operator {
             } => format!("^V{prefix}"),
             Operator::AutoIndent => "=".to_string(),
             Operator::ShellCommand => "=".to_string(),
+            Operator::ConfirmSubstitution => "replace? (y/n/a/q/l)".to_string(),
             _ => self.id().to_string(),
         }
     }
 
     pub fn is_waiting(&self, mode: Mode) -> bool {
         match self {
-            Operator::AddSurrounds { target } => target.is_some() || mode.is_visual(),
+            Operator::AddSurrounds { target } => {
+                target.is_some() || mode.is_visual() || mode == Mode::HelixNormal
+            }
             Operator::FindForward { .. }
             | Operator::Mark
             | Operator::Jump { .. }
@@ -999,7 +1017,8 @@ impl Operator {
             | Operator::Digraph { .. }
             | Operator::Literal { .. }
             | Operator::ChangeSurrounds { target: Some(_) }
-            | Operator::DeleteSurrounds => true,
+            | Operator::DeleteSurrounds
+            | Operator::ConfirmSubstitution => true,
             Operator::Change
             | Operator::Delete
             | Operator::Yank
@@ -1056,7 +1075,8 @@ impl Operator {
             | Operator::Jump { .. }
             | Operator::Register
             | Operator::RecordRegister
-            | Operator::ReplayRegister => false,
+            | Operator::ReplayRegister
+            | Operator::ConfirmSubstitution => false,
         }
     }
 }
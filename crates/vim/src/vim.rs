@@ -6,6 +6,7 @@ mod test;
 mod change_list;
 mod command;
 mod digraph;
+mod expression;
 mod helix;
 mod indent;
 mod insert;
@@ -17,6 +18,7 @@ mod replace;
 mod rewrap;
 mod state;
 mod surrounds;
+mod vimrc;
 mod visual;
 
 use anyhow::Result;
@@ -45,6 +47,7 @@ use surrounds::SurroundsType;
 use theme::ThemeSettings;
 use ui::{IntoElement, SharedString, px};
 use vim_mode_setting::VimModeSetting;
+pub use vimrc::{Vimrc, VimrcDiagnostic, load_vimrc, vimrc_key_bindings, vimrc_path};
 use workspace::{self, Pane, Workspace};
 
 use crate::state::ReplayableAction;
@@ -327,6 +330,9 @@ pub(crate) struct Vim {
 
     selected_register: Option<char>,
     pub search: SearchState,
+    /// The matches of an `s///c` substitution that are still to be confirmed,
+    /// with the text that replaces each of them.
+    pub(crate) pending_substitutions: Vec<(Range<Anchor>, String)>,
 
     editor: WeakEntity<Editor>,
 
@@ -374,6 +380,7 @@ impl Vim {
             status_label: None,
             selected_register: None,
             search: SearchState::default(),
+            pending_substitutions: Vec::new(),
 
             last_command: None,
             running_command: None,
@@ -384,6 +391,11 @@ impl Vim {
                 cx.subscribe_in(&editor, window, |this, _, event, window, cx| {
                     this.handle_editor_event(event, window, cx)
                 }),
+                cx.observe_global_in::<Vimrc>(window, |this, window, cx| {
+                    this.update_editor(window, cx, |_, editor, _, cx| {
+                        Self::apply_vimrc_options(editor, cx)
+                    });
+                }),
             ],
         })
     }
@@ -435,6 +447,7 @@ impl Vim {
         editor.register_addon(VimAddon {
             entity: vim.clone(),
         });
+        Self::apply_vimrc_options(editor, cx);
 
         vim.update(cx, |_, cx| {
             Vim::action(editor, cx, |vim, _: &SwitchToNormalMode, window, cx| {
@@ -706,6 +719,7 @@ impl Vim {
             visual::register(editor, cx);
             change_list::register(editor, cx);
             digraph::register(editor, cx);
+            vimrc::register(editor, cx);
 
             cx.defer_in(window, |vim, window, cx| {
                 vim.focused(false, window, cx);
@@ -729,6 +743,15 @@ impl Vim {
         }
     }
 
+    fn apply_vimrc_options(editor: &mut Editor, cx: &mut Context<Editor>) {
+        let Some(vimrc) = cx.try_global::<Vimrc>() else {
+            return;
+        };
+        for option in vimrc.options.clone() {
+            option.apply(editor, cx);
+        }
+    }
+
     /// Register an action on the editor.
     pub fn action<A: Action>(
         editor: &mut Editor,
@@ -829,7 +852,10 @@ impl Vim {
             EditorEvent::InputHandled {
                 text,
                 utf16_range_to_replace: range_to_replace,
-            } => Vim::globals(cx).observe_insertion(text, range_to_replace.clone()),
+            } => {
+                Vim::globals(cx).observe_insertion(text, range_to_replace.clone());
+                self.expand_abbreviations(text, window, cx);
+            }
             EditorEvent::TransactionBegun { transaction_id } => {
                 self.transaction_begun(*transaction_id, window, cx)
             }
@@ -1022,7 +1048,8 @@ impl Vim {
                         | Operator::Jump { .. }
                         | Operator::Register
                         | Operator::RecordRegister
-                        | Operator::ReplayRegister => CursorShape::Block,
+                        | Operator::ReplayRegister
+                        | Operator::ConfirmSubstitution => CursorShape::Block,
 
                         // All other operators -> Underline cursor
                         _ => CursorShape::Underline,
@@ -1095,6 +1122,9 @@ impl Vim {
         {
             context.add("VimCount");
         }
+        if cx.global::<VimGlobals>().noremap {
+            context.add("VimNoremap");
+        }
 
         if let Some(active_operator) = active_operator {
             if active_operator.is_waiting(self.mode) {
@@ -1373,6 +1403,7 @@ impl Vim {
         Vim::take_count(cx);
         self.selected_register.take();
         self.operator_stack.clear();
+        self.clear_pending_substitutions(window, cx);
         self.sync_vim_settings(window, cx);
     }
 
@@ -1584,10 +1615,14 @@ impl Vim {
                     self.add_surrounds(text, SurroundsType::Selection, window, cx);
                     self.clear_operator(window, cx);
                 }
+                Mode::HelixNormal => {
+                    self.helix_surround_add(&text, window, cx);
+                    self.clear_operator(window, cx);
+                }
                 _ => self.clear_operator(window, cx),
             },
             Some(Operator::ChangeSurrounds { target }) => match self.mode {
-                Mode::Normal => {
+                Mode::Normal | Mode::HelixNormal => {
                     if let Some(target) = target {
                         self.change_surrounds(text, target, window, cx);
                         self.clear_operator(window, cx);
@@ -1596,7 +1631,7 @@ impl Vim {
                 _ => self.clear_operator(window, cx),
             },
             Some(Operator::DeleteSurrounds) => match self.mode {
-                Mode::Normal => {
+                Mode::Normal | Mode::HelixNormal => {
                     self.delete_surrounds(text, window, cx);
                     self.clear_operator(window, cx);
                 }
@@ -1631,6 +1666,7 @@ impl Vim {
                 }
             },
             Some(Operator::Jump { line }) => self.jump(text, line, true, window, cx),
+            Some(Operator::ConfirmSubstitution) => self.confirm_substitution(text, window, cx),
             _ => {
                 if self.mode == Mode::Replace {
                     self.multi_replace(text, window, cx)
@@ -1687,8 +1723,10 @@ struct VimSettings {
     pub use_system_clipboard: UseSystemClipboard,
     pub use_multiline_find: bool,
     pub use_smartcase_find: bool,
+    pub gdefault: bool,
     pub custom_digraphs: HashMap<String, Arc<str>>,
     pub highlight_on_yank_duration: u64,
+    pub vimrc_path: Option<String>,
 }
 
 #[derive(Clone, Default, Serialize, Deserialize, JsonSchema)]
@@ -1698,8 +1736,10 @@ struct VimSettingsContent {
     pub use_system_clipboard: Option<UseSystemClipboard>,
     pub use_multiline_find: Option<bool>,
     pub use_smartcase_find: Option<bool>,
+    pub gdefault: Option<bool>,
     pub custom_digraphs: Option<HashMap<String, Arc<str>>>,
     pub highlight_on_yank_duration: Option<u64>,
+    pub vimrc_path: Option<String>,
 }
 
 #[derive(Clone, Default, Serialize, Deserialize, JsonSchema)]
@@ -1754,6 +1794,7 @@ impl Settings for VimSettings {
             use_smartcase_find: settings
                 .use_smartcase_find
                 .ok_or_else(Self::missing_default)?,
+            gdefault: settings.gdefault.ok_or_else(Self::missing_default)?,
             custom_digraphs: settings.custom_digraphs.ok_or_else(Self::missing_default)?,
             highlight_on_yank_duration: settings
                 .highlight_on_yank_duration
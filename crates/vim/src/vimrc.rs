@@ -0,0 +1,496 @@
+use std::path::PathBuf;
+
+use collections::HashMap;
+use editor::Editor;
+use gpui::{App, Context, Global, KeyBinding, Keystroke, Window, impl_actions};
+use itertools::Itertools;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::json;
+use settings::{KeymapFile, KeymapFileLoadResult, Settings};
+use util::ResultExt;
+
+use crate::{Vim, VimSettings, command::VimOption, command::parse_keys};
+
+/// Sends the keystrokes of a `noremap` mapping, which are not looked up in the
+/// vimrc's mappings.
+#[derive(Clone, Deserialize, JsonSchema, PartialEq)]
+pub struct SendNoremapKeystrokes(pub String);
+
+impl_actions!(vim, [SendNoremapKeystrokes]);
+
+pub(crate) fn register(editor: &mut Editor, cx: &mut Context<Vim>) {
+    Vim::action(editor, cx, Vim::send_noremap_keystrokes);
+}
+
+/// The mappings, options and abbreviations loaded from the user's vimrc.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Vimrc {
+    mappings: Vec<Mapping>,
+    pub(crate) options: Vec<VimOption>,
+    abbreviations: HashMap<String, String>,
+}
+
+impl Global for Vimrc {}
+
+/// A line of the vimrc that could not be applied.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VimrcDiagnostic {
+    /// The one-based line number.
+    pub line: usize,
+    pub message: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Mapping {
+    context: &'static str,
+    keys: String,
+    keystrokes: String,
+    noremap: bool,
+}
+
+/// Returns the vimrc path from the `vim.vimrc_path` setting, if one is set.
+pub fn vimrc_path(cx: &App) -> Option<PathBuf> {
+    let path = VimSettings::get_global(cx).vimrc_path.as_ref()?;
+    match path.strip_prefix("~/") {
+        Some(relative) => Some(util::paths::home_dir().join(relative)),
+        None => Some(PathBuf::from(path)),
+    }
+}
+
+/// Parses the given vimrc and makes it the active one, returning the lines
+/// that could not be applied.
+pub fn load_vimrc(content: &str, cx: &mut App) -> Vec<VimrcDiagnostic> {
+    let (vimrc, diagnostics) = Vimrc::parse(content);
+    if cx.try_global::<Vimrc>() != Some(&vimrc) {
+        cx.set_global(vimrc);
+    }
+    diagnostics
+}
+
+/// Returns the key bindings for the mappings in the active vimrc.
+pub fn vimrc_key_bindings(cx: &App) -> Vec<KeyBinding> {
+    let Some(vimrc) = cx.try_global::<Vimrc>() else {
+        return Vec::new();
+    };
+    // Each mapping gets its own section so that later mappings take
+    // precedence, as they would in Vim. None of them apply while the keys of a
+    // `noremap` mapping are being sent.
+    let sections = vimrc
+        .mappings
+        .iter()
+        .map(|mapping| {
+            let action = if mapping.noremap {
+                "vim::SendNoremapKeystrokes"
+            } else {
+                "workspace::SendKeystrokes"
+            };
+            let mut bindings = serde_json::Map::new();
+            bindings.insert(mapping.keys.clone(), json!([action, mapping.keystrokes]));
+            json!({
+                "context": format!("{} && !VimNoremap", mapping.context),
+                "bindings": bindings,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    match KeymapFile::load(&serde_json::to_string(&sections).unwrap_or_default(), cx) {
+        KeymapFileLoadResult::Success { key_bindings } => key_bindings,
+        KeymapFileLoadResult::SomeFailedToLoad {
+            key_bindings,
+            error_message,
+        } => {
+            log::error!("failed to load some vimrc mappings: {}", error_message.0);
+            key_bindings
+        }
+        KeymapFileLoadResult::JsonParseFailure { error } => {
+            log::error!("failed to load vimrc mappings: {error}");
+            Vec::new()
+        }
+    }
+}
+
+impl Vim {
+    fn send_noremap_keystrokes(
+        &mut self,
+        action: &SendNoremapKeystrokes,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let keystrokes = action
+            .0
+            .split(' ')
+            .flat_map(|keystroke| Keystroke::parse(keystroke).log_err())
+            .collect::<Vec<_>>();
+        // Like `workspace::SendKeystrokes`, the keystrokes are dispatched one at
+        // a time after this action. The window is refreshed around each one,
+        // so that the key contexts it's matched against include `VimNoremap`.
+        window
+            .spawn(cx, async move |cx| {
+                for keystroke in keystrokes {
+                    cx.update(|window, cx| {
+                        let focused = window.focused(cx);
+                        Vim::globals(cx).noremap = true;
+                        window.refresh();
+                        window.dispatch_keystroke(keystroke, cx);
+                        Vim::globals(cx).noremap = false;
+                        window.refresh();
+                        if window.focused(cx) != focused {
+                            window.draw(cx);
+                        }
+                    })?;
+                }
+                anyhow::Ok(())
+            })
+            .detach_and_log_err(cx);
+    }
+}
+
+impl Vimrc {
+    pub(crate) fn parse(content: &str) -> (Self, Vec<VimrcDiagnostic>) {
+        let mut vimrc = Vimrc::default();
+        let mut diagnostics = Vec::new();
+        let mut leader = "\\".to_string();
+        let mut local_leader = "\\".to_string();
+
+        let mut lines: Vec<(usize, String)> = Vec::new();
+        for (ix, line) in content.lines().enumerate() {
+            let line = line.trim();
+            // Lines starting with a backslash continue the previous line.
+            if let Some(continuation) = line.strip_prefix('\\') {
+                if let Some((_, previous)) = lines.last_mut() {
+                    previous.push_str(continuation);
+                    continue;
+                }
+            }
+            lines.push((ix + 1, line.to_string()));
+        }
+
+        for (line, text) in lines {
+            let text = text.trim_start_matches(':').trim();
+            if text.is_empty() || text.starts_with('"') {
+                continue;
+            }
+            let (command, args) = match text.find(char::is_whitespace) {
+                Some(ix) => (&text[..ix], text[ix..].trim()),
+                None => (text, ""),
+            };
+            let mut error = |message: String| diagnostics.push(VimrcDiagnostic { line, message });
+
+            if let Some(context) = mapping_context(command) {
+                let recursive = noremap_command(command);
+                match parse_mapping(args, &leader, &local_leader) {
+                    // Vim doesn't remap the start of such a mapping, but here
+                    // it would send its own keys until the keystroke limit.
+                    Ok((keys, keystrokes))
+                        if recursive.is_some()
+                            && (keystrokes == keys
+                                || keystrokes.starts_with(&format!("{keys} "))) =>
+                    {
+                        error(format!(
+                            "mapping of `{keys}` starts with itself, use `{}` instead",
+                            recursive.unwrap_or_default()
+                        ))
+                    }
+                    Ok((keys, keystrokes)) => vimrc.mappings.push(Mapping {
+                        context,
+                        keys,
+                        keystrokes,
+                        noremap: recursive.is_none(),
+                    }),
+                    Err(message) => error(message),
+                }
+                continue;
+            }
+
+            match command {
+                "set" | "se" | "setlocal" | "setl" | "setglobal" | "setg" => {
+                    for option in args.split_whitespace() {
+                        match VimOption::from(option) {
+                            Some(option) => vimrc.options.push(option),
+                            None => error(format!("unsupported option `{option}`")),
+                        }
+                    }
+                }
+                "let" => {
+                    let Some((name, value)) = args.split_once('=') else {
+                        error(format!("invalid `let`: {args}"));
+                        continue;
+                    };
+                    let Some(value) = parse_string(value.trim()) else {
+                        error(format!("expected a string value: {}", value.trim()));
+                        continue;
+                    };
+                    match name.trim() {
+                        "mapleader" | "g:mapleader" => leader = value,
+                        "maplocalleader" | "g:maplocalleader" => local_leader = value,
+                        name => error(format!("unsupported variable `{name}`")),
+                    }
+                }
+                "iabbrev" | "iab" | "inoreabbrev" | "inorea" | "abbreviate" | "ab"
+                | "noreabbrev" | "norea" => {
+                    let mut words = args.splitn(2, char::is_whitespace);
+                    match (words.next(), words.next().map(str::trim)) {
+                        (Some(lhs), _) if lhs.starts_with('<') => {
+                            error(format!("unsupported abbreviation argument `{lhs}`"))
+                        }
+                        (Some(lhs), Some(rhs)) if !lhs.is_empty() && !rhs.is_empty() => {
+                            vimrc.abbreviations.insert(lhs.to_string(), rhs.to_string());
+                        }
+                        _ => error(format!(
+                            "expected an abbreviation and its expansion: {text}"
+                        )),
+                    }
+                }
+                _ => error(format!("unsupported command `{command}`")),
+            }
+        }
+
+        (vimrc, diagnostics)
+    }
+
+    pub(crate) fn has_abbreviations(&self) -> bool {
+        !self.abbreviations.is_empty()
+    }
+
+    /// Returns the expansion of an abbreviation typed in insert mode.
+    pub(crate) fn abbreviation(&self, word: &str) -> Option<&str> {
+        self.abbreviations.get(word).map(String::as_str)
+    }
+}
+
+fn mapping_context(command: &str) -> Option<&'static str> {
+    Some(match command {
+        "map" | "noremap" | "no" => "VimControl && !menu",
+        "nmap" | "nm" | "nnoremap" | "nn" | "nno" => "vim_mode == normal && !menu",
+        "vmap" | "vm" | "vnoremap" | "vn" | "xmap" | "xm" | "xnoremap" | "xn" | "xno" => {
+            "vim_mode == visual && !menu"
+        }
+        "omap" | "om" | "onoremap" | "ono" => "vim_mode == operator",
+        "imap" | "im" | "inoremap" | "ino" => "vim_mode == insert",
+        _ => return None,
+    })
+}
+
+/// Returns the non-recursive variant of a recursive mapping command, or `None`
+/// if the command is already non-recursive.
+fn noremap_command(command: &str) -> Option<&'static str> {
+    Some(match command {
+        "map" => "noremap",
+        "nmap" | "nm" => "nnoremap",
+        "vmap" | "vm" => "vnoremap",
+        "xmap" | "xm" => "xnoremap",
+        "omap" | "om" => "onoremap",
+        "imap" | "im" => "inoremap",
+        _ => return None,
+    })
+}
+
+/// Parses the arguments of a mapping command into Zed's keystroke notation
+/// for its left and right hand sides.
+fn parse_mapping(args: &str, leader: &str, local_leader: &str) -> Result<(String, String), String> {
+    let mut args = args;
+    while args.starts_with('<') {
+        let Some(end) = args.find('>') else {
+            break;
+        };
+        match args[..=end].to_lowercase().as_str() {
+            "<silent>" | "<nowait>" | "<unique>" | "<special>" => args = args[end + 1..].trim(),
+            "<buffer>" | "<expr>" | "<script>" => {
+                return Err(format!("unsupported mapping argument `{}`", &args[..=end]));
+            }
+            _ => break,
+        }
+    }
+    let (lhs, rhs) = args
+        .split_once(char::is_whitespace)
+        .map(|(lhs, rhs)| (lhs, rhs.trim()))
+        .filter(|(lhs, rhs)| !lhs.is_empty() && !rhs.is_empty())
+        .ok_or_else(|| format!("expected keys and their mapping: {args}"))?;
+
+    let translate = |keys: &str| {
+        let keys = replace_ignore_case(keys, "<leader>", leader);
+        let keys = replace_ignore_case(&keys, "<localleader>", local_leader);
+        parse_keys(&keys)
+            .iter()
+            .map(|keystroke| keystroke.unparse())
+            .join(" ")
+    };
+    let keys = translate(lhs);
+    let keystrokes = translate(rhs);
+    if keys.is_empty() || keystrokes.is_empty() {
+        return Err(format!("could not parse mapping: {args}"));
+    }
+    Ok((keys, keystrokes))
+}
+
+fn replace_ignore_case(text: &str, pattern: &str, replacement: &str) -> String {
+    let mut result = String::new();
+    let mut rest = text;
+    while let Some(ix) = rest.to_ascii_lowercase().find(pattern) {
+        result.push_str(&rest[..ix]);
+        result.push_str(replacement);
+        rest = &rest[ix + pattern.len()..];
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Parses a quoted vimscript string, such as `","` or `"\<Space>"`.
+fn parse_string(value: &str) -> Option<String> {
+    if let Some(literal) = value
+        .strip_prefix('\'')
+        .and_then(|value| value.strip_suffix('\''))
+    {
+        return Some(literal.replace("''", "'"));
+    }
+    let value = value.strip_prefix('"')?.strip_suffix('"')?;
+    let mut result = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        let rest = chars.as_str();
+        if rest.to_ascii_lowercase().starts_with("<space>") {
+            result.push(' ');
+            chars = rest["<space>".len()..].chars();
+        } else if let Some(escaped) = chars.next() {
+            result.push(match escaped {
+                't' => '\t',
+                c => c,
+            });
+        }
+    }
+    Some(result)
+}
+
+#[cfg(test)]
+mod test {
+    use indoc::indoc;
+
+    use super::*;
+    use crate::{load_vimrc, state::Mode, test::VimTestContext};
+
+    #[test]
+    fn test_parse_vimrc() {
+        let (vimrc, diagnostics) = Vimrc::parse(indoc! {r#"
+            " Comments are skipped
+            let mapleader = "\<Space>"
+            set number norelativenumber
+            :set nowrap
+            nnoremap <leader>w :w<CR>
+            nnoremap <silent> H ^
+            vnoremap < <gv
+            inoremap jk <Esc>
+            map <C-j> 5j
+            nnoremap n nzz
+            nmap N Nzz
+            iabbrev teh the
+            set tabstop=4
+            syntax on
+            nnoremap <buffer> x y
+        "#});
+
+        assert_eq!(
+            vimrc.mappings,
+            vec![
+                Mapping {
+                    context: "vim_mode == normal && !menu",
+                    keys: "space w".to_string(),
+                    keystrokes: ": w enter".to_string(),
+                    noremap: true,
+                },
+                Mapping {
+                    context: "vim_mode == normal && !menu",
+                    keys: "shift-h".to_string(),
+                    keystrokes: "^".to_string(),
+                    noremap: true,
+                },
+                Mapping {
+                    context: "vim_mode == visual && !menu",
+                    keys: "<".to_string(),
+                    keystrokes: "< g v".to_string(),
+                    noremap: true,
+                },
+                Mapping {
+                    context: "vim_mode == insert",
+                    keys: "j k".to_string(),
+                    keystrokes: "escape".to_string(),
+                    noremap: true,
+                },
+                Mapping {
+                    context: "VimControl && !menu",
+                    keys: "ctrl-j".to_string(),
+                    keystrokes: "5 j".to_string(),
+                    noremap: false,
+                },
+                Mapping {
+                    context: "vim_mode == normal && !menu",
+                    keys: "n".to_string(),
+                    keystrokes: "n z z".to_string(),
+                    noremap: true,
+                },
+            ]
+        );
+        assert_eq!(
+            vimrc.options,
+            vec![
+                VimOption::Number(true),
+                VimOption::RelativeNumber(false),
+                VimOption::Wrap(false),
+            ]
+        );
+        assert_eq!(vimrc.abbreviation("teh"), Some("the"));
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|diagnostic| diagnostic.line)
+                .collect::<Vec<_>>(),
+            vec![11, 13, 14, 15]
+        );
+        assert_eq!(
+            diagnostics[0].message,
+            "mapping of `shift-n` starts with itself, use `nnoremap` instead"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_noremap(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        cx.update(|_, cx| {
+            load_vimrc(
+                indoc! {"
+                    nnoremap x l
+                    nnoremap l x
+                    vnoremap < <gv
+                "},
+                cx,
+            );
+            cx.bind_keys(vimrc_key_bindings(cx));
+        });
+
+        // Each mapping sends the other's keys, which run Zed's bindings.
+        cx.set_state("ˇabc", Mode::Normal);
+        cx.simulate_keystrokes("x");
+        cx.assert_state("aˇbc", Mode::Normal);
+        cx.simulate_keystrokes("l");
+        cx.assert_state("aˇc", Mode::Normal);
+
+        // A mapping can start with its own keys.
+        cx.set_state("        ˇa\n        b\n", Mode::Normal);
+        cx.simulate_keystrokes("shift-v j <");
+        assert_eq!(cx.buffer_text(), "    a\n    b\n");
+        assert_eq!(cx.mode(), Mode::VisualLine);
+    }
+
+    #[test]
+    fn test_parse_string() {
+        assert_eq!(parse_string(r#"",""#), Some(",".to_string()));
+        assert_eq!(parse_string(r#""\<Space>""#), Some(" ".to_string()));
+        assert_eq!(parse_string(r#""\\""#), Some("\\".to_string()));
+        assert_eq!(parse_string("'it''s'"), Some("it's".to_string()));
+        assert_eq!(parse_string("space"), None);
+    }
+}
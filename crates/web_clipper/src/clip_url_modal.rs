@@ -0,0 +1,98 @@
+use editor::{Editor, EditorEvent};
+use gpui::{
+    DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, Render, Subscription, WeakEntity,
+    div, prelude::*,
+};
+use theme::ActiveTheme;
+use ui::prelude::*;
+use workspace::{ModalView, Workspace};
+
+use crate::clip_url_into_workspace;
+
+/// Prompts for the URL of a page to clip into the vault.
+pub struct ClipUrlModal {
+    url_editor: Entity<Editor>,
+    workspace: WeakEntity<Workspace>,
+    _subscription: Subscription,
+}
+
+impl ModalView for ClipUrlModal {}
+
+impl Focusable for ClipUrlModal {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.url_editor.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for ClipUrlModal {}
+
+impl ClipUrlModal {
+    pub fn new(
+        workspace: WeakEntity<Workspace>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let url_editor = cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            editor.set_placeholder_text("https://example.com/article", cx);
+            editor
+        });
+        let subscription = cx.subscribe_in(&url_editor, window, Self::on_url_editor_event);
+        Self {
+            url_editor,
+            workspace,
+            _subscription: subscription,
+        }
+    }
+
+    fn on_url_editor_event(
+        &mut self,
+        _: &Entity<Editor>,
+        event: &EditorEvent,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let EditorEvent::Blurred = event {
+            cx.emit(DismissEvent);
+        }
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, _: &mut Window, cx: &mut Context<Self>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        let url = self.url_editor.read(cx).text(cx);
+        if url.trim().is_empty() {
+            return;
+        }
+        self.workspace
+            .update(cx, |workspace, cx| {
+                clip_url_into_workspace(workspace, url, window, cx)
+            })
+            .ok();
+        cx.emit(DismissEvent);
+    }
+}
+
+impl Render for ClipUrlModal {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .w(rems(34.))
+            .elevation_2(cx)
+            .key_context("ClipUrlModal")
+            .on_action(cx.listener(Self::cancel))
+            .on_action(cx.listener(Self::confirm))
+            .child(
+                div()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .px_2()
+                    .py_1()
+                    .child(self.url_editor.clone()),
+            )
+            .child(h_flex().px_2().py_1().gap_1().child(
+                Label::new("Save the page's main content as a new note").color(Color::Muted),
+            ))
+    }
+}
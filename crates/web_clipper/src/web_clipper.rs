@@ -0,0 +1,499 @@
+//! Clips web pages into the vault as clean markdown notes.
+
+mod clip_url_modal;
+
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::Arc;
+
+use anyhow::{Context as _, Result, bail};
+use chrono::NaiveDateTime;
+use collections::HashSet;
+use fs::Fs;
+use futures::{AsyncReadExt, StreamExt};
+use gpui::{App, Context, Window, actions};
+use html_to_markdown::{TagHandler, markdown, readability};
+use http_client::{AsyncBody, HttpClient, Url};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+use vault::{Frontmatter, PropertyValue, unique_file_path, unique_note_path};
+use workspace::{OpenOptions, Workspace, notifications::NotifyTaskExt as _};
+
+pub use clip_url_modal::ClipUrlModal;
+
+actions!(web_clipper, [ClipUrl]);
+
+/// Settings for clipping web pages into notes.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WebClipperSettings {
+    /// The folder, relative to the vault root, that clipped notes are saved in.
+    ///
+    /// Default: "Clippings"
+    pub folder: Option<String>,
+    /// The folder, relative to the vault root, that images from clipped
+    /// pages are downloaded into.
+    ///
+    /// Default: "attachments"
+    pub attachments_folder: Option<String>,
+    /// Whether to download images instead of linking to them on the web.
+    ///
+    /// Default: true
+    pub download_images: Option<bool>,
+}
+
+impl Default for WebClipperSettings {
+    fn default() -> Self {
+        Self {
+            folder: Some("Clippings".into()),
+            attachments_folder: Some("attachments".into()),
+            download_images: Some(true),
+        }
+    }
+}
+
+impl settings::Settings for WebClipperSettings {
+    const KEY: Option<&'static str> = Some("web_clipper");
+
+    type FileContent = Self;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _: &mut App) -> Result<Self> {
+        sources.json_merge()
+    }
+}
+
+pub fn init(cx: &mut App) {
+    WebClipperSettings::register(cx);
+
+    cx.observe_new(|workspace: &mut Workspace, _window, _cx| {
+        workspace.register_action(|workspace, _: &ClipUrl, window, cx| {
+            let workspace_handle = cx.entity().downgrade();
+            workspace.toggle_modal(window, cx, |window, cx| {
+                ClipUrlModal::new(workspace_handle, window, cx)
+            });
+        });
+    })
+    .detach();
+}
+
+/// Clips `url` into the first folder open in `workspace` and opens the new note.
+pub fn clip_url_into_workspace(
+    workspace: &mut Workspace,
+    url: String,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let Some(vault_root) = workspace
+        .visible_worktrees(cx)
+        .next()
+        .map(|worktree| worktree.read(cx).abs_path().to_path_buf())
+    else {
+        workspace.show_error(&anyhow::anyhow!("Open a folder to clip web pages into"), cx);
+        return;
+    };
+    let fs = workspace.app_state().fs.clone();
+    let http_client = cx.http_client();
+    let settings = WebClipperSettings::get_global(cx).clone();
+    let clipped_at = chrono::Local::now().naive_local();
+
+    cx.spawn_in(window, async move |workspace, cx| {
+        let note_path = clip_url(
+            &url,
+            &vault_root,
+            &settings,
+            clipped_at,
+            http_client,
+            fs.as_ref(),
+        )
+        .await?;
+        workspace
+            .update_in(cx, |workspace, window, cx| {
+                workspace.open_abs_path(note_path, OpenOptions::default(), window, cx)
+            })?
+            .await?;
+        anyhow::Ok(())
+    })
+    .detach_and_notify_err(window, cx);
+}
+
+/// The parts of a web page that end up in a note.
+#[derive(Debug)]
+struct Clipping {
+    title: Option<String>,
+    markdown: String,
+    images: Vec<Url>,
+}
+
+/// Downloads `url`, extracts its main content as markdown and saves it as a
+/// new note in the vault at `vault_root`, returning the note's path.
+pub async fn clip_url(
+    url: &str,
+    vault_root: &Path,
+    settings: &WebClipperSettings,
+    clipped_at: NaiveDateTime,
+    http_client: Arc<dyn HttpClient>,
+    fs: &dyn Fs,
+) -> Result<PathBuf> {
+    let url = parse_url(url)?;
+    let (content_type, body) = fetch(http_client.as_ref(), &url).await?;
+
+    let mut clipping = if content_type.starts_with("text/plain") {
+        Clipping {
+            title: None,
+            markdown: String::from_utf8_lossy(&body).into_owned(),
+            images: Vec::new(),
+        }
+    } else if content_type.is_empty() || content_type.contains("html") {
+        html_to_clipping(&body, &url)?
+    } else {
+        bail!("can't clip {url}: unsupported content type {content_type:?}");
+    };
+
+    let title = clipping
+        .title
+        .take()
+        .unwrap_or_else(|| fallback_title(&url));
+    let note_folder = vault_root.join(settings.folder.as_deref().unwrap_or("Clippings"));
+    fs.create_dir(&note_folder).await?;
+    let existing_notes = file_names(fs, &note_folder).await?;
+    let note_path = unique_note_path(&note_folder, &title, |path| existing_notes.contains(path));
+
+    if settings.download_images.unwrap_or(true) && !clipping.images.is_empty() {
+        let attachments_folder = settings
+            .attachments_folder
+            .as_deref()
+            .unwrap_or("attachments");
+        let attachments_path = vault_root.join(attachments_folder);
+        // Links in the note are relative to the note's folder.
+        let link_prefix = match note_folder
+            .strip_prefix(vault_root)
+            .map(|folder| folder.components().count())
+        {
+            Ok(depth) => format!("{}{}", "../".repeat(depth), attachments_folder),
+            Err(_) => attachments_path.to_string_lossy().into_owned(),
+        };
+        download_images(
+            &mut clipping,
+            &attachments_path,
+            &link_prefix,
+            http_client.as_ref(),
+            fs,
+        )
+        .await?;
+    }
+
+    let mut frontmatter = Frontmatter::new();
+    frontmatter.set("title", PropertyValue::Text(title));
+    frontmatter.set("source", PropertyValue::Text(url.to_string()));
+    frontmatter.set(
+        "clipped",
+        PropertyValue::Text(clipped_at.format("%Y-%m-%dT%H:%M").to_string()),
+    );
+    let content = format!("{}{}\n", frontmatter.to_yaml(), clipping.markdown.trim());
+    fs.atomic_write(note_path.clone(), content).await?;
+    Ok(note_path)
+}
+
+fn parse_url(url: &str) -> Result<Url> {
+    let url = url.trim();
+    let url = if url.starts_with("https://") || url.starts_with("http://") {
+        url.to_string()
+    } else {
+        format!("https://{url}")
+    };
+    Url::parse(&url).with_context(|| format!("invalid URL {url:?}"))
+}
+
+/// Returns the response's content type and body.
+async fn fetch(http_client: &dyn HttpClient, url: &Url) -> Result<(String, Vec<u8>)> {
+    let mut response = http_client
+        .get(url.as_str(), AsyncBody::default(), true)
+        .await
+        .with_context(|| format!("fetching {url}"))?;
+    let mut body = Vec::new();
+    response
+        .body_mut()
+        .read_to_end(&mut body)
+        .await
+        .context("error reading response body")?;
+    if !response.status().is_success() {
+        bail!(
+            "fetching {url} failed with status {}",
+            response.status().as_u16()
+        );
+    }
+    let content_type = response
+        .headers()
+        .get("content-type")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    Ok((content_type, body))
+}
+
+fn html_to_clipping(html: &[u8], base_url: &Url) -> Result<Clipping> {
+    let article = readability::extract_article(html)?;
+
+    let resolve_url = {
+        let base_url = base_url.clone();
+        move |href: &str| {
+            let url = base_url.join(href).ok()?;
+            matches!(url.scheme(), "http" | "https" | "mailto").then(|| url.to_string())
+        }
+    };
+    let images = Rc::new(RefCell::new(markdown::ImageHandler::new({
+        let base_url = base_url.clone();
+        move |src: &str| {
+            let url = base_url.join(src).ok()?;
+            matches!(url.scheme(), "http" | "https").then(|| url.to_string())
+        }
+    })));
+    let mut handlers: Vec<TagHandler> = vec![
+        Rc::new(RefCell::new(markdown::WebpageChromeRemover)),
+        Rc::new(RefCell::new(markdown::ParagraphHandler)),
+        Rc::new(RefCell::new(markdown::HeadingHandler)),
+        Rc::new(RefCell::new(markdown::ListHandler)),
+        Rc::new(RefCell::new(markdown::TableHandler::new())),
+        Rc::new(RefCell::new(markdown::StyledTextHandler)),
+        Rc::new(RefCell::new(markdown::CodeHandler)),
+        Rc::new(RefCell::new(markdown::LinkHandler::new(resolve_url))),
+        images.clone(),
+    ];
+    let markdown = article.to_markdown(&mut handlers)?;
+    let images = images
+        .borrow()
+        .images()
+        .iter()
+        .filter_map(|image| Url::parse(image).ok())
+        .collect();
+
+    Ok(Clipping {
+        title: article.title,
+        markdown,
+        images,
+    })
+}
+
+/// Downloads the clipping's images into `attachments_path`, pointing the
+/// markdown at the local copies. Images that fail to download stay remote.
+async fn download_images(
+    clipping: &mut Clipping,
+    attachments_path: &Path,
+    link_prefix: &str,
+    http_client: &dyn HttpClient,
+    fs: &dyn Fs,
+) -> Result<()> {
+    fs.create_dir(attachments_path).await?;
+    let mut existing = file_names(fs, attachments_path).await?;
+
+    for image_url in &clipping.images {
+        let (content_type, bytes) = match fetch(http_client, image_url).await {
+            Ok(response) => response,
+            Err(error) => {
+                log::warn!("failed to download image {image_url}: {error:#}");
+                continue;
+            }
+        };
+
+        let (stem, extension) = image_file_name(image_url, &content_type);
+        let path = unique_file_path(attachments_path, &stem, &extension, |path| {
+            existing.contains(path)
+        });
+        fs.create_file_with(&path, Box::pin(futures::io::Cursor::new(bytes)).as_mut())
+            .await
+            .with_context(|| format!("saving image to {path:?}"))?;
+
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        existing.insert(path);
+        let local_link = format!("{link_prefix}/{file_name}");
+        let local_link = if local_link.contains(' ') {
+            format!("<{local_link}>")
+        } else {
+            local_link
+        };
+        let remote_link = image_url.as_str();
+        clipping.markdown = clipping
+            .markdown
+            .replace(&format!("]({remote_link})"), &format!("]({local_link})"))
+            .replace(&format!("](<{remote_link}>)"), &format!("]({local_link})"));
+    }
+    Ok(())
+}
+
+/// Picks a file name for a downloaded image from its URL, falling back to
+/// the response's content type for the extension.
+fn image_file_name(url: &Url, content_type: &str) -> (String, String) {
+    let last_segment = url
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .filter(|segment| !segment.is_empty())
+        .unwrap_or("image");
+    let last_segment = urlencoding::decode(last_segment)
+        .map(|segment| segment.into_owned())
+        .unwrap_or_else(|_| last_segment.to_string());
+    let (stem, extension) = match last_segment.rsplit_once('.') {
+        Some((stem, extension))
+            if !stem.is_empty() && extension.len() <= 5 && !extension.is_empty() =>
+        {
+            (stem.to_string(), extension.to_ascii_lowercase())
+        }
+        _ => {
+            let extension = match content_type.split(';').next().unwrap_or_default().trim() {
+                "image/png" => "png",
+                "image/jpeg" => "jpg",
+                "image/gif" => "gif",
+                "image/webp" => "webp",
+                "image/svg+xml" => "svg",
+                "image/avif" => "avif",
+                _ => "",
+            };
+            (last_segment.clone(), extension.to_string())
+        }
+    };
+    (vault::sanitize_file_name(&stem), extension)
+}
+
+fn fallback_title(url: &Url) -> String {
+    url.path_segments()
+        .and_then(|mut segments| segments.rfind(|segment| !segment.is_empty()))
+        .map(|segment| segment.replace(['-', '_'], " "))
+        .or_else(|| url.host_str().map(ToString::to_string))
+        .unwrap_or_else(|| url.to_string())
+}
+
+async fn file_names(fs: &dyn Fs, folder: &Path) -> Result<HashSet<PathBuf>> {
+    let mut names = HashSet::default();
+    let mut entries = fs.read_dir(folder).await?;
+    while let Some(entry) = entries.next().await {
+        names.insert(entry?);
+    }
+    Ok(names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use http_client::{FakeHttpClient, Response};
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use util::path;
+
+    const PAGE: &str = indoc! {r#"
+        <html>
+          <head>
+            <title>Why Plain Text Notes Last | Example Blog</title>
+            <script>trackVisitor();</script>
+          </head>
+          <body>
+            <nav><a href="/">Home</a> <a href="/about">About</a></nav>
+            <div class="sidebar"><p>Subscribe to our newsletter for weekly updates, tips, and more.</p></div>
+            <article class="post">
+              <h1>Why Plain Text Notes Last</h1>
+              <p>Plain text files outlive the apps that create them, which makes them a safe place for notes, drafts, and ideas.</p>
+              <img src="/images/files.png" alt="A stack of files">
+              <p>Read more about <a href="/posts/formats">open formats</a>, or <strong>start today</strong>.</p>
+            </article>
+            <div class="comments"><p>Great post, thanks for sharing this with everyone here!</p></div>
+            <footer><p>Copyright Example Blog, all rights reserved, since forever.</p></footer>
+          </body>
+        </html>
+    "#};
+
+    #[gpui::test]
+    async fn test_clip_url(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/vault"),
+            json!({
+                "Clippings": {
+                    "Why Plain Text Notes Last.md": "An earlier clipping",
+                },
+            }),
+        )
+        .await;
+
+        let http_client = FakeHttpClient::create(|request| async move {
+            let response = match request.uri().to_string().as_str() {
+                "https://blog.example.com/posts/plain-text" => Response::builder()
+                    .header("content-type", "text/html; charset=utf-8")
+                    .body(PAGE.into())?,
+                "https://blog.example.com/images/files.png" => Response::builder()
+                    .header("content-type", "image/png")
+                    .body(b"png bytes".to_vec().into())?,
+                _ => Response::builder().status(404).body("not found".into())?,
+            };
+            Ok(response)
+        });
+
+        let clipped_at =
+            NaiveDateTime::parse_from_str("2025-03-04T09:30", "%Y-%m-%dT%H:%M").unwrap();
+        let note_path = clip_url(
+            "blog.example.com/posts/plain-text",
+            path!("/vault").as_ref(),
+            &WebClipperSettings::default(),
+            clipped_at,
+            http_client,
+            fs.as_ref(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            note_path,
+            PathBuf::from(path!("/vault/Clippings/Why Plain Text Notes Last 1.md"))
+        );
+        assert_eq!(
+            fs.load(&note_path).await.unwrap(),
+            indoc! {"
+                ---
+                title: Why Plain Text Notes Last
+                source: https://blog.example.com/posts/plain-text
+                clipped: 2025-03-04T09:30
+                ---
+                # Why Plain Text Notes Last
+
+                Plain text files outlive the apps that create them, which makes them a safe place for notes, drafts, and ideas.
+
+                ![A stack of files](../attachments/files.png)
+
+                Read more about [open formats](https://blog.example.com/posts/formats), or **start today**.
+            "}
+        );
+        assert_eq!(
+            fs.load_bytes(path!("/vault/attachments/files.png").as_ref())
+                .await
+                .unwrap(),
+            b"png bytes"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_clip_url_error(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(path!("/vault"), json!({})).await;
+        let http_client = FakeHttpClient::with_404_response();
+
+        let result = clip_url(
+            "https://example.com/missing",
+            path!("/vault").as_ref(),
+            &WebClipperSettings::default(),
+            chrono::Local::now().naive_local(),
+            http_client,
+            fs.as_ref(),
+        )
+        .await;
+        assert!(result.is_err());
+        assert!(
+            fs.read_dir(path!("/vault/Clippings").as_ref())
+                .await
+                .is_err()
+        );
+    }
+}
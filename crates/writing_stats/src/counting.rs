@@ -0,0 +1,198 @@
+use vault::{Frontmatter, body_offset};
+
+/// The frontmatter property holding a note's word goal.
+const WORD_GOAL_PROPERTY: &str = "word_goal";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TextStats {
+    pub words: usize,
+    /// The number of characters, not counting whitespace.
+    pub characters: usize,
+}
+
+impl TextStats {
+    /// The minutes it takes to read the text, rounded up.
+    pub fn reading_minutes(&self, words_per_minute: u32) -> usize {
+        self.words.div_ceil(words_per_minute.max(1) as usize)
+    }
+}
+
+/// Counts the words and characters of a note's prose, skipping its
+/// frontmatter, fenced code blocks, and `<!-- -->` and `%% %%` comments.
+///
+/// Words are separated by whitespace, except in scripts written without
+/// spaces between words (Chinese and Japanese), where each character counts
+/// as a word.
+pub fn count_prose(text: &str) -> TextStats {
+    let mut stats = TextStats::default();
+    let mut fence: Option<(char, usize)> = None;
+    // The delimiter that closes the comment we're in.
+    let mut comment_end: Option<&str> = None;
+    for line in text[body_offset(text)..].split_inclusive('\n') {
+        if let Some((fence_char, fence_len)) = fence {
+            if is_closing_fence(line, fence_char, fence_len) {
+                fence = None;
+            }
+            continue;
+        }
+        if comment_end.is_none() {
+            if let Some(opening_fence) = opening_fence(line) {
+                fence = Some(opening_fence);
+                continue;
+            }
+        }
+
+        let mut rest = line;
+        loop {
+            if let Some(end) = comment_end {
+                let Some(ix) = rest.find(end) else {
+                    break;
+                };
+                rest = &rest[ix + end.len()..];
+                comment_end = None;
+            } else {
+                let comment_start = [("<!--", "-->"), ("%%", "%%")]
+                    .into_iter()
+                    .filter_map(|(start, end)| Some((rest.find(start)?, start, end)))
+                    .min_by_key(|(ix, _, _)| *ix);
+                let Some((ix, start, end)) = comment_start else {
+                    count_words(rest, &mut stats);
+                    break;
+                };
+                count_words(&rest[..ix], &mut stats);
+                rest = &rest[ix + start.len()..];
+                comment_end = Some(end);
+            }
+        }
+    }
+    stats
+}
+
+/// The word goal set in a note's frontmatter, e.g. `word_goal: 1500`.
+pub fn word_goal(text: &str) -> Option<usize> {
+    Frontmatter::parse(text)?
+        .get_text(WORD_GOAL_PROPERTY)?
+        .replace([',', '_'], "")
+        .parse()
+        .ok()
+        .filter(|goal| *goal > 0)
+}
+
+fn count_words(text: &str, stats: &mut TextStats) {
+    // Whether the current run of non-whitespace has a letter or digit in it,
+    // so markup like `-` or `##` isn't counted as a word.
+    let mut in_word = false;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if in_word {
+                stats.words += 1;
+                in_word = false;
+            }
+            continue;
+        }
+        stats.characters += 1;
+        if is_cjk(c) {
+            if in_word {
+                stats.words += 1;
+                in_word = false;
+            }
+            stats.words += 1;
+        } else if c.is_alphanumeric() {
+            in_word = true;
+        }
+    }
+    if in_word {
+        stats.words += 1;
+    }
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(
+        c,
+        '\u{3040}'..='\u{30ff}' // Hiragana and Katakana
+            | '\u{31f0}'..='\u{31ff}' // Katakana phonetic extensions
+            | '\u{3400}'..='\u{4dbf}' // CJK unified ideographs extension A
+            | '\u{4e00}'..='\u{9fff}' // CJK unified ideographs
+            | '\u{f900}'..='\u{faff}' // CJK compatibility ideographs
+            | '\u{ff66}'..='\u{ff9f}' // Halfwidth Katakana
+            | '\u{20000}'..='\u{2fa1f}' // CJK unified ideographs extensions B to F
+    )
+}
+
+fn opening_fence(line: &str) -> Option<(char, usize)> {
+    let trimmed = line.trim_start_matches(' ');
+    if line.len() - trimmed.len() > 3 {
+        return None;
+    }
+    let fence_char = trimmed.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let fence_len = trimmed.len() - trimmed.trim_start_matches(fence_char).len();
+    (fence_len >= 3).then_some((fence_char, fence_len))
+}
+
+fn is_closing_fence(line: &str, fence_char: char, fence_len: usize) -> bool {
+    let trimmed = line.trim_start_matches(' ');
+    let rest = trimmed.trim_start_matches(fence_char);
+    line.len() - trimmed.len() <= 3
+        && trimmed.len() - rest.len() >= fence_len
+        && rest.trim().is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_count_prose() {
+        let text = indoc! {"
+            ---
+            title: Draft
+            word_goal: 1,500
+            ---
+            # A short draft
+
+            - Some *emphasis* and a [[Link]].
+            <!-- a comment
+            over two lines --> Back to it.
+            %% hidden %% done
+
+            ```rust
+            fn main() {}
+            ```
+            ````
+            ```
+            still code
+            ````
+            The end.
+        "};
+        assert_eq!(
+            count_prose(text),
+            TextStats {
+                words: 14,
+                characters: 60,
+            }
+        );
+        assert_eq!(word_goal(text), Some(1500));
+        assert_eq!(word_goal("# No frontmatter"), None);
+    }
+
+    #[test]
+    fn test_count_cjk() {
+        assert_eq!(count_prose("我喜欢写作。").words, 5);
+        assert_eq!(count_prose("日本語のテキスト").words, 8);
+        assert_eq!(count_prose("Rust是一种语言").words, 6);
+        // Korean is written with spaces between words.
+        assert_eq!(count_prose("나는 글을 쓴다").words, 3);
+    }
+
+    #[test]
+    fn test_reading_minutes() {
+        let stats = TextStats {
+            words: 401,
+            characters: 0,
+        };
+        assert_eq!(stats.reading_minutes(200), 3);
+        assert_eq!(TextStats::default().reading_minutes(200), 0);
+    }
+}
@@ -0,0 +1,95 @@
+use anyhow::Result;
+use chrono::{Days, NaiveDate};
+use collections::HashMap;
+
+use crate::WritingStatsDb;
+
+/// How many days of writing the statistics look back over.
+pub const HISTORY_DAYS: u64 = 30;
+
+/// The words written on each of the last [`HISTORY_DAYS`] days.
+#[derive(Clone, Debug, Default)]
+pub struct WritingHistory {
+    pub today: NaiveDate,
+    daily_totals: HashMap<NaiveDate, i64>,
+}
+
+impl WritingHistory {
+    pub fn load(db: &WritingStatsDb, today: NaiveDate) -> Result<Self> {
+        let since = today - Days::new(HISTORY_DAYS - 1);
+        Ok(Self::new(today, db.daily_totals_since(since)?))
+    }
+
+    pub fn new(today: NaiveDate, daily_totals: Vec<(NaiveDate, i64)>) -> Self {
+        Self {
+            today,
+            daily_totals: daily_totals.into_iter().collect(),
+        }
+    }
+
+    pub fn words_on(&self, day: NaiveDate) -> i64 {
+        self.daily_totals.get(&day).copied().unwrap_or(0)
+    }
+
+    /// The words written over the last `days` days, including today.
+    pub fn words_in_last(&self, days: u64) -> i64 {
+        self.days(days).map(|day| self.words_on(day)).sum()
+    }
+
+    /// The last `days` days, oldest first.
+    pub fn days(&self, days: u64) -> impl Iterator<Item = NaiveDate> + use<> {
+        let today = self.today;
+        (0..days)
+            .rev()
+            .filter_map(move |ago| today.checked_sub_days(Days::new(ago)))
+    }
+
+    /// The number of days in a row with words written, up to today, or up to
+    /// yesterday while nothing's been written yet today.
+    pub fn streak(&self) -> usize {
+        let mut day = self.today;
+        if self.words_on(day) <= 0 {
+            let Some(yesterday) = day.pred_opt() else {
+                return 0;
+            };
+            day = yesterday;
+        }
+        let mut streak = 0;
+        while self.words_on(day) > 0 {
+            streak += 1;
+            let Some(previous) = day.pred_opt() else {
+                break;
+            };
+            day = previous;
+        }
+        streak
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_writing_history() {
+        let day = |day| NaiveDate::from_ymd_opt(2025, 3, day).unwrap();
+        let history = WritingHistory::new(
+            day(10),
+            vec![(day(2), 500), (day(7), 90), (day(8), -20), (day(9), 300)],
+        );
+        assert_eq!(history.words_in_last(3), 280);
+        assert_eq!(history.words_in_last(30), 870);
+        assert_eq!(
+            history.days(3).collect::<Vec<_>>(),
+            [day(8), day(9), day(10)]
+        );
+        // Nothing's been written today yet, and a day with more deleted than
+        // written breaks the streak.
+        assert_eq!(history.streak(), 1);
+
+        let history = WritingHistory::new(day(10), vec![(day(9), 300), (day(10), 5)]);
+        assert_eq!(history.streak(), 2);
+        assert_eq!(WritingHistory::new(day(10), Vec::new()).streak(), 0);
+    }
+}
@@ -0,0 +1,77 @@
+use anyhow::{Context as _, Result};
+use chrono::NaiveDate;
+use db::{define_connection, query, sqlez_macros::sql};
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+define_connection!(pub static ref WRITING_STATS_DB: WritingStatsDb<()> =
+    &[sql!(
+        CREATE TABLE IF NOT EXISTS daily_word_counts(
+            day TEXT PRIMARY KEY,
+            words INTEGER NOT NULL
+        ) STRICT;
+    )];
+);
+
+impl WritingStatsDb {
+    /// Adds to the number of words written on `day`, which is negative when
+    /// more words were deleted than written.
+    pub async fn add_words(&self, day: NaiveDate, words: i64) -> Result<()> {
+        self.add_to_daily_total(day.format(DATE_FORMAT).to_string(), words)
+            .await
+    }
+
+    /// The number of words written on each day since `day`, oldest first.
+    /// Days without writing are left out.
+    pub fn daily_totals_since(&self, day: NaiveDate) -> Result<Vec<(NaiveDate, i64)>> {
+        self.daily_totals_from(day.format(DATE_FORMAT).to_string())?
+            .into_iter()
+            .map(|(day, words)| {
+                let day = NaiveDate::parse_from_str(&day, DATE_FORMAT)
+                    .with_context(|| format!("invalid writing date {day:?}"))?;
+                Ok((day, words))
+            })
+            .collect()
+    }
+
+    query! {
+        async fn add_to_daily_total(day: String, words: i64) -> Result<()> {
+            INSERT INTO daily_word_counts (day, words) VALUES (?1, ?2)
+            ON CONFLICT DO UPDATE SET words = words + ?2
+        }
+    }
+
+    query! {
+        fn daily_totals_from(day: String) -> Result<Vec<(String, i64)>> {
+            SELECT day, words FROM daily_word_counts
+            WHERE day >= (?)
+            ORDER BY day
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[gpui::test]
+    async fn test_daily_totals() {
+        let db = WritingStatsDb(db::open_test_db("test_daily_totals").await);
+        let day = |day| NaiveDate::from_ymd_opt(2025, 3, day).unwrap();
+
+        db.add_words(day(1), 120).await.unwrap();
+        db.add_words(day(3), 300).await.unwrap();
+        db.add_words(day(3), -40).await.unwrap();
+        db.add_words(day(4), 15).await.unwrap();
+
+        assert_eq!(
+            db.daily_totals_since(day(1)).unwrap(),
+            [(day(1), 120), (day(3), 260), (day(4), 15)]
+        );
+        assert_eq!(
+            db.daily_totals_since(day(2)).unwrap(),
+            [(day(3), 260), (day(4), 15)]
+        );
+    }
+}
@@ -0,0 +1,158 @@
+use chrono::Local;
+use gpui::{
+    App, Context, DismissEvent, EventEmitter, FocusHandle, Focusable, Render, Task, Window,
+};
+use ui::{Tooltip, prelude::*};
+use util::ResultExt as _;
+use workspace::ModalView;
+
+use crate::{WRITING_STATS_DB, WritingHistory};
+
+/// How many days the chart shows.
+const CHART_DAYS: u64 = 14;
+const CHART_HEIGHT: f32 = 80.;
+
+/// A modal summarizing the words written over the last month.
+pub struct WritingStatistics {
+    focus_handle: FocusHandle,
+    /// The history, or `None` while it's loading.
+    history: Option<WritingHistory>,
+    _load: Task<()>,
+}
+
+impl WritingStatistics {
+    pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let today = Local::now().date_naive();
+        let load = cx.spawn(async move |this, cx| {
+            let history = cx
+                .background_spawn(async move { WritingHistory::load(&WRITING_STATS_DB, today) })
+                .await
+                .log_err()
+                .unwrap_or_else(|| WritingHistory::new(today, Vec::new()));
+            this.update(cx, |this, cx| {
+                this.history = Some(history);
+                cx.notify();
+            })
+            .ok();
+        });
+        let focus_handle = cx.focus_handle();
+        window.focus(&focus_handle);
+        Self {
+            focus_handle,
+            history: None,
+            _load: load,
+        }
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, _: &mut Window, cx: &mut Context<Self>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn render_history(&self, history: &WritingHistory, cx: &mut Context<Self>) -> AnyElement {
+        let streak = history.streak();
+        let summary = [
+            ("Today", history.words_in_last(1).to_string()),
+            ("Last 7 days", history.words_in_last(7).to_string()),
+            ("Last 30 days", history.words_in_last(30).to_string()),
+            (
+                "Streak",
+                if streak == 1 {
+                    "1 day".to_string()
+                } else {
+                    format!("{streak} days")
+                },
+            ),
+        ];
+
+        let chart_days = history.days(CHART_DAYS).collect::<Vec<_>>();
+        let most_words = chart_days
+            .iter()
+            .map(|day| history.words_on(*day))
+            .max()
+            .unwrap_or(0)
+            .max(1);
+        let chart =
+            h_flex()
+                .h(px(CHART_HEIGHT))
+                .items_end()
+                .gap_1()
+                .children(chart_days.iter().map(|day| {
+                    let words = history.words_on(*day).max(0);
+                    let is_today = *day == history.today;
+                    v_flex()
+                        .flex_1()
+                        .h_full()
+                        .justify_end()
+                        .id(SharedString::from(day.to_string()))
+                        .tooltip(Tooltip::text(format!(
+                            "{words} words on {}",
+                            day.format("%A, %B %-d")
+                        )))
+                        .child(
+                            div()
+                                .w_full()
+                                .min_h(px(1.))
+                                .h(relative(words as f32 / most_words as f32))
+                                .rounded_t_sm()
+                                .bg(if is_today {
+                                    cx.theme().colors().text_accent
+                                } else {
+                                    cx.theme().colors().text_muted
+                                }),
+                        )
+                }));
+
+        v_flex()
+            .gap_3()
+            .child(
+                h_flex()
+                    .gap_4()
+                    .children(summary.into_iter().map(|(label, value)| {
+                        v_flex()
+                            .child(Headline::new(value).size(HeadlineSize::Small))
+                            .child(Label::new(label).size(LabelSize::Small).color(Color::Muted))
+                    })),
+            )
+            .child(chart)
+            .child(
+                Label::new(format!(
+                    "Words written per day over the last {CHART_DAYS} days"
+                ))
+                .size(LabelSize::Small)
+                .color(Color::Muted),
+            )
+            .into_any_element()
+    }
+}
+
+impl EventEmitter<DismissEvent> for WritingStatistics {}
+
+impl ModalView for WritingStatistics {}
+
+impl Focusable for WritingStatistics {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for WritingStatistics {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let body = match &self.history {
+            Some(history) => self.render_history(history, cx),
+            None => Label::new("Loading statistics…")
+                .color(Color::Muted)
+                .into_any_element(),
+        };
+
+        v_flex()
+            .key_context("WritingStatistics")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::cancel))
+            .w(rems(32.))
+            .elevation_3(cx)
+            .p_3()
+            .gap_2()
+            .child(Headline::new("Writing Statistics").size(HeadlineSize::Small))
+            .child(body)
+    }
+}
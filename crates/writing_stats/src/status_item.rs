@@ -0,0 +1,198 @@
+use std::{ops::Range, time::Duration};
+
+use editor::{Editor, EditorMode};
+use gpui::{Context, Entity, IntoElement, Render, Subscription, Task, WeakEntity, Window, div};
+use settings::Settings as _;
+use ui::{Tooltip, prelude::*};
+use workspace::{ItemHandle, StatusItemView, Workspace};
+
+use crate::{
+    MAX_COUNTED_LEN, TextStats, WritingStatistics, WritingStatsSettings, count_prose, is_prose,
+    word_goal,
+};
+
+const UPDATE_DEBOUNCE: Duration = Duration::from_millis(250);
+const GOAL_BAR_WIDTH: f32 = 32.;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct NoteStats {
+    note: TextStats,
+    /// The stats of the selected text, if any is selected.
+    selection: Option<TextStats>,
+    word_goal: Option<usize>,
+}
+
+/// Shows the word count and reading time of the active prose file, and its
+/// progress towards the word goal set in its frontmatter.
+pub struct WritingStatsItem {
+    stats: Option<NoteStats>,
+    workspace: WeakEntity<Workspace>,
+    update_stats: Task<()>,
+    _observe_active_editor: Option<Subscription>,
+}
+
+impl WritingStatsItem {
+    pub fn new(workspace: &Workspace) -> Self {
+        Self {
+            stats: None,
+            workspace: workspace.weak_handle(),
+            update_stats: Task::ready(()),
+            _observe_active_editor: None,
+        }
+    }
+
+    fn update_stats(
+        &mut self,
+        editor: Entity<Editor>,
+        debounce: Option<Duration>,
+        cx: &mut Context<Self>,
+    ) {
+        let note = editor.update(cx, |editor, cx| {
+            if !matches!(editor.mode(), EditorMode::Full) {
+                return None;
+            }
+            let buffer = editor.buffer().read(cx).as_singleton()?;
+            let buffer = buffer.read(cx);
+            if !is_prose(buffer) || buffer.len() > MAX_COUNTED_LEN {
+                return None;
+            }
+            let snapshot = buffer.text_snapshot();
+            let selections = editor
+                .selections
+                .all::<usize>(cx)
+                .into_iter()
+                .filter(|selection| !selection.is_empty())
+                .map(|selection| selection.range())
+                .collect::<Vec<Range<usize>>>();
+            Some((snapshot, selections))
+        });
+        let Some((snapshot, selections)) = note else {
+            self.stats = None;
+            self.update_stats = Task::ready(());
+            cx.notify();
+            return;
+        };
+
+        self.update_stats = cx.spawn(async move |this, cx| {
+            if let Some(debounce) = debounce {
+                cx.background_executor().timer(debounce).await;
+            }
+            let stats = cx
+                .background_spawn(async move {
+                    let text = snapshot.text();
+                    let selection = (!selections.is_empty()).then(|| {
+                        let mut stats = TextStats::default();
+                        for range in selections {
+                            let selected = count_prose(&text[range]);
+                            stats.words += selected.words;
+                            stats.characters += selected.characters;
+                        }
+                        stats
+                    });
+                    NoteStats {
+                        note: count_prose(&text),
+                        selection,
+                        word_goal: word_goal(&text),
+                    }
+                })
+                .await;
+            this.update(cx, |this, cx| {
+                this.stats = Some(stats);
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+
+    fn open_statistics(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.workspace
+            .update(cx, |workspace, cx| {
+                workspace.toggle_modal(window, cx, WritingStatistics::new);
+            })
+            .ok();
+    }
+}
+
+impl Render for WritingStatsItem {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let settings = WritingStatsSettings::get_global(cx);
+        let Some(stats) = self.stats.filter(|_| settings.enabled()) else {
+            return div();
+        };
+        let words = stats.note.words;
+        let minutes = stats.note.reading_minutes(settings.words_per_minute());
+
+        let mut label = match stats.word_goal {
+            Some(goal) => format!("{words}/{goal} words"),
+            None if words == 1 => "1 word".to_string(),
+            None => format!("{words} words"),
+        };
+        if let Some(selection) = stats.selection {
+            label = format!("{} selected · {label}", selection.words);
+        }
+        label.push_str(&format!(" · {minutes} min"));
+
+        let mut tooltip = format!(
+            "{words} words, {} characters, {minutes} min read",
+            stats.note.characters
+        );
+        if let Some(selection) = stats.selection {
+            tooltip.push_str(&format!(
+                "\nSelection: {} words, {} characters",
+                selection.words, selection.characters
+            ));
+        }
+
+        div().child(
+            h_flex()
+                .gap_1()
+                .when_some(stats.word_goal, |this, goal| {
+                    let progress = (words as f32 / goal as f32).min(1.);
+                    this.child(
+                        div()
+                            .w(px(GOAL_BAR_WIDTH))
+                            .h(px(4.))
+                            .rounded_sm()
+                            .bg(cx.theme().colors().element_background)
+                            .child(div().h_full().w(relative(progress)).rounded_sm().bg(
+                                if words >= goal {
+                                    cx.theme().status().success
+                                } else {
+                                    cx.theme().colors().text_accent
+                                },
+                            )),
+                    )
+                })
+                .child(
+                    Button::new("writing-stats", label)
+                        .label_size(LabelSize::Small)
+                        .tooltip(Tooltip::text(tooltip))
+                        .on_click(
+                            cx.listener(|this, _, window, cx| this.open_statistics(window, cx)),
+                        ),
+                ),
+        )
+    }
+}
+
+impl StatusItemView for WritingStatsItem {
+    fn set_active_pane_item(
+        &mut self,
+        active_pane_item: Option<&dyn ItemHandle>,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(editor) = active_pane_item.and_then(|item| item.act_as::<Editor>(cx)) {
+            self._observe_active_editor = Some(cx.observe(&editor, |this, editor, cx| {
+                this.update_stats(editor, Some(UPDATE_DEBOUNCE), cx)
+            }));
+            self.update_stats(editor, None, cx);
+        } else {
+            self.stats = None;
+            self.update_stats = Task::ready(());
+            self._observe_active_editor = None;
+        }
+
+        cx.notify();
+    }
+}
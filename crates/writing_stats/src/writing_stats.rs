@@ -0,0 +1,148 @@
+//! Word counts, reading time and word goals for prose files, shown in the
+//! status bar, along with the number of words written each day.
+
+mod counting;
+mod history;
+mod persistence;
+mod statistics_view;
+mod status_item;
+
+use anyhow::Result;
+use chrono::Local;
+use gpui::{App, AppContext as _, Context, Window, actions};
+use language::{Buffer, BufferEvent};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+use workspace::Workspace;
+
+pub use counting::{TextStats, count_prose, word_goal};
+pub use history::{HISTORY_DAYS, WritingHistory};
+pub use persistence::{WRITING_STATS_DB, WritingStatsDb};
+pub use statistics_view::WritingStatistics;
+pub use status_item::WritingStatsItem;
+
+actions!(writing_stats, [OpenWritingStatistics]);
+
+/// Files larger than this aren't counted.
+const MAX_COUNTED_LEN: usize = 1024 * 1024;
+
+/// The extensions of files counted as prose before their language is known.
+const PROSE_EXTENSIONS: &[&str] = &["md", "markdown", "txt"];
+
+/// Settings for writing statistics.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct WritingStatsSettings {
+    /// Whether to show writing statistics in the status bar, and to keep
+    /// track of the words written each day.
+    ///
+    /// Default: true
+    pub enabled: Option<bool>,
+    /// The reading speed used to estimate reading time.
+    ///
+    /// Default: 200
+    pub words_per_minute: Option<u32>,
+}
+
+impl WritingStatsSettings {
+    pub fn enabled(&self) -> bool {
+        self.enabled.unwrap_or(true)
+    }
+
+    pub fn words_per_minute(&self) -> u32 {
+        self.words_per_minute.unwrap_or(200)
+    }
+}
+
+impl settings::Settings for WritingStatsSettings {
+    const KEY: Option<&'static str> = Some("writing_stats");
+
+    type FileContent = Self;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _: &mut App) -> Result<Self> {
+        sources.json_merge()
+    }
+}
+
+pub fn init(cx: &mut App) {
+    WritingStatsSettings::register(cx);
+
+    cx.observe_new(|buffer: &mut Buffer, _window, cx| {
+        // The word count as of the last save, which the words written by each
+        // save are counted from.
+        let mut saved_words = initial_word_count(buffer);
+        cx.subscribe_self(move |buffer, event: &BufferEvent, cx| match event {
+            BufferEvent::Saved => record_words_written(buffer, &mut saved_words, cx),
+            BufferEvent::Reloaded if is_prose(buffer) && buffer.len() <= MAX_COUNTED_LEN => {
+                saved_words = Some(count_prose(&buffer.text()).words);
+            }
+            _ => {}
+        })
+        .detach();
+    })
+    .detach();
+
+    cx.observe_new(|workspace: &mut Workspace, _window, _cx| {
+        workspace.register_action(open_writing_statistics);
+    })
+    .detach();
+}
+
+/// Whether a buffer holds prose, rather than code.
+pub fn is_prose(buffer: &Buffer) -> bool {
+    buffer
+        .language()
+        .is_some_and(|language| matches!(language.name().as_ref(), "Markdown" | "Plain Text"))
+}
+
+fn initial_word_count(buffer: &Buffer) -> Option<usize> {
+    if buffer.len() > MAX_COUNTED_LEN {
+        return None;
+    }
+    // Buffers are created before their language is detected, so go by the
+    // file's extension. Untitled buffers start out empty.
+    let is_prose_file = buffer.file().map_or(true, |file| {
+        file.path()
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| {
+                PROSE_EXTENSIONS
+                    .iter()
+                    .any(|prose_extension| extension.eq_ignore_ascii_case(prose_extension))
+            })
+    });
+    is_prose_file.then(|| count_prose(&buffer.text()).words)
+}
+
+fn record_words_written(
+    buffer: &Buffer,
+    saved_words: &mut Option<usize>,
+    cx: &mut Context<Buffer>,
+) {
+    if !WritingStatsSettings::get_global(cx).enabled()
+        || !is_prose(buffer)
+        || buffer.len() > MAX_COUNTED_LEN
+        || !buffer.file().is_some_and(|file| file.is_local())
+    {
+        return;
+    }
+    let words = count_prose(&buffer.text()).words;
+    let Some(previous_words) = saved_words.replace(words) else {
+        return;
+    };
+    let written = words as i64 - previous_words as i64;
+    if written != 0 {
+        let today = Local::now().date_naive();
+        cx.background_spawn(async move { WRITING_STATS_DB.add_words(today, written).await })
+            .detach_and_log_err(cx);
+    }
+}
+
+fn open_writing_statistics(
+    workspace: &mut Workspace,
+    _: &OpenWritingStatistics,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    workspace.toggle_modal(window, cx, WritingStatistics::new);
+}
@@ -54,8 +54,8 @@ use workspace::{AppState, SerializedWorkspaceLocation, WorkspaceSettings, Worksp
 use zed::{
     OpenListener, OpenRequest, app_menus, build_window_options, derive_paths_with_position,
     handle_cli_connection, handle_keymap_file_changes, handle_settings_changed,
-    handle_settings_file_changes, initialize_workspace, inline_completion_registry,
-    open_paths_with_positions,
+    handle_settings_file_changes, handle_vimrc_file_changes, initialize_workspace,
+    inline_completion_registry, open_paths_with_positions,
 };
 
 #[cfg(unix)]
@@ -523,6 +523,7 @@ fn main() {
         channel::init(&app_state.client.clone(), app_state.user_store.clone(), cx);
         search::init(cx);
         vim::init(cx);
+        handle_vimrc_file_changes(app_state.fs.clone(), cx);
         terminal_view::init(cx);
         journal::init(app_state.clone(), cx);
         language_selector::init(cx);
@@ -534,7 +535,15 @@ fn main() {
         collab_ui::init(&app_state, cx);
         git_ui::init(cx);
         feedback::init(cx);
+        markdown_export::init(cx);
         markdown_preview::init(cx);
+        citations::init(cx);
+        flashcards::init(cx);
+        vault_sync::init(cx);
+        note_history::init(cx);
+        vault_search::init(cx);
+        web_clipper::init(cx);
+        writing_stats::init(cx);
         welcome::init(cx);
         settings_ui::init(cx);
         extensions_ui::init(cx);
@@ -50,7 +50,7 @@ use search::project_search::ProjectSearchBar;
 use settings::{
     DEFAULT_KEYMAP_PATH, InvalidSettingsError, KeymapFile, KeymapFileLoadResult, Settings,
     SettingsStore, VIM_KEYMAP_PATH, initial_debug_tasks_content, initial_project_settings_content,
-    initial_tasks_content, update_settings_file,
+    initial_tasks_content, update_settings_file, watch_config_file,
 };
 use std::any::TypeId;
 use std::path::PathBuf;
@@ -109,6 +109,8 @@ pub fn init(cx: &mut App) {
 
     cx.on_action(|_: &RestoreBanner, cx| title_bar::restore_banner(cx));
 
+    obsidian::init(cx);
+
     if ReleaseChannel::global(cx) == ReleaseChannel::Dev {
         cx.on_action(test_panic);
     }
@@ -236,8 +238,11 @@ pub fn initialize_workspace(
             cx.new(|cx| toolchain_selector::ActiveToolchain::new(workspace, window, cx));
         let vim_mode_indicator = cx.new(|cx| vim::ModeIndicator::new(window, cx));
         let image_info = cx.new(|_cx| ImageInfo::new(workspace));
+        let vault_sync_status =
+            cx.new(|cx| vault_sync::VaultSyncStatusItem::new(workspace, window, cx));
         let cursor_position =
             cx.new(|_| go_to_line::cursor_position::CursorPosition::new(workspace));
+        let writing_stats = cx.new(|_| writing_stats::WritingStatsItem::new(workspace));
         workspace.status_bar().update(cx, |status_bar, cx| {
             status_bar.add_left_item(diagnostic_summary, window, cx);
             status_bar.add_left_item(activity_indicator, window, cx);
@@ -245,8 +250,10 @@ pub fn initialize_workspace(
             status_bar.add_right_item(active_buffer_language, window, cx);
             status_bar.add_right_item(active_toolchain_language, window, cx);
             status_bar.add_right_item(vim_mode_indicator, window, cx);
+            status_bar.add_right_item(writing_stats, window, cx);
             status_bar.add_right_item(cursor_position, window, cx);
             status_bar.add_right_item(image_info, window, cx);
+            status_bar.add_right_item(vault_sync_status, window, cx);
         });
 
         let handle = cx.entity().downgrade();
@@ -1245,6 +1252,11 @@ pub fn handle_keymap_file_changes(
     let (keyboard_layout_tx, mut keyboard_layout_rx) = mpsc::unbounded();
     let mut old_base_keymap = *BaseKeymap::get_global(cx);
     let mut old_vim_enabled = VimModeSetting::get_global(cx).0;
+    cx.observe_global::<vim::Vimrc>({
+        let base_keymap_tx = base_keymap_tx.clone();
+        move |_| base_keymap_tx.unbounded_send(()).unwrap()
+    })
+    .detach();
     cx.observe_global::<SettingsStore>(move |cx| {
         let new_base_keymap = *BaseKeymap::get_global(cx);
         let new_vim_enabled = VimModeSetting::get_global(cx).0;
@@ -1362,6 +1374,80 @@ fn show_keymap_file_load_error(
     )
 }
 
+pub fn handle_vimrc_file_changes(fs: Arc<dyn fs::Fs>, cx: &mut App) {
+    struct VimrcLoadErrorNotification;
+    let notification_id = NotificationId::unique::<VimrcLoadErrorNotification>();
+
+    fn vimrc_path(cx: &App) -> PathBuf {
+        vim::vimrc_path(cx).unwrap_or_else(|| paths::vimrc_file().clone())
+    }
+
+    let (path_tx, mut path_rx) = mpsc::unbounded();
+    let mut path = vimrc_path(cx);
+    let mut old_path = path.clone();
+    cx.observe_global::<SettingsStore>(move |cx| {
+        let new_path = vimrc_path(cx);
+        if new_path != old_path {
+            old_path = new_path.clone();
+            path_tx.unbounded_send(new_path).ok();
+        }
+    })
+    .detach();
+
+    cx.spawn(async move |cx| {
+        loop {
+            let mut vimrc_file_rx =
+                watch_config_file(cx.background_executor(), fs.clone(), path.clone());
+            loop {
+                select_biased! {
+                    new_path = path_rx.next() => {
+                        let Some(new_path) = new_path else {
+                            return;
+                        };
+                        path = new_path;
+                        break;
+                    },
+                    content = vimrc_file_rx.next() => {
+                        let Some(content) = content else {
+                            continue;
+                        };
+                        cx.update(|cx| {
+                            let diagnostics = vim::load_vimrc(&content, cx);
+                            if diagnostics.is_empty() {
+                                dismiss_app_notification(&notification_id, cx);
+                            } else {
+                                show_vimrc_load_error(notification_id.clone(), &diagnostics, cx);
+                            }
+                        })
+                        .ok();
+                    },
+                };
+            }
+        }
+    })
+    .detach();
+}
+
+fn show_vimrc_load_error(
+    notification_id: NotificationId,
+    diagnostics: &[vim::VimrcDiagnostic],
+    cx: &mut App,
+) {
+    const MAX_DIAGNOSTICS: usize = 10;
+
+    let mut message = String::from("Some lines of the vimrc were not loaded:\n");
+    for diagnostic in diagnostics.iter().take(MAX_DIAGNOSTICS) {
+        message.push_str(&format!("\nLine {}: {}", diagnostic.line, diagnostic.message));
+    }
+    if diagnostics.len() > MAX_DIAGNOSTICS {
+        message.push_str(&format!("\n...and {} more", diagnostics.len() - MAX_DIAGNOSTICS));
+    }
+    let message: SharedString = message.into();
+    show_app_notification(notification_id, cx, move |cx| {
+        cx.new(|cx| MessageNotification::new(message.clone(), cx))
+    });
+}
+
 fn show_markdown_app_notification<F>(
     notification_id: NotificationId,
     message: MarkdownString,
@@ -1417,6 +1503,9 @@ fn show_markdown_app_notification<F>(
 fn reload_keymaps(cx: &mut App, user_key_bindings: Vec<KeyBinding>) {
     cx.clear_key_bindings();
     load_default_keymap(cx);
+    if VimModeSetting::get_global(cx).0 {
+        cx.bind_keys(vim::vimrc_key_bindings(cx));
+    }
     cx.bind_keys(user_key_bindings);
     cx.set_menus(app_menus());
     cx.set_dock_menu(vec![MenuItem::action("New Window", workspace::NewWindow)]);
@@ -5,6 +5,7 @@ use cli::{CliRequest, CliResponse, ipc::IpcSender};
 use cli::{IpcHandshake, ipc};
 use client::parse_zed_link;
 use collections::HashMap;
+use context_server::transport::ChannelTransport;
 use db::kvp::KEY_VALUE_STORE;
 use editor::Editor;
 use fs::Fs;
@@ -297,10 +298,90 @@ pub async fn handle_cli_connection(
                 let status = if open_workspace_result.is_err() { 1 } else { 0 };
                 responses.send(CliResponse::Exit { status }).log_err();
             }
+            CliRequest::Mcp { path } => {
+                let status = match serve_mcp(path, requests, &responses, app_state, cx).await {
+                    Ok(()) => 0,
+                    Err(error) => {
+                        responses
+                            .send(CliResponse::Stderr {
+                                message: format!("{error:#}"),
+                            })
+                            .log_err();
+                        1
+                    }
+                };
+                responses.send(CliResponse::Exit { status }).log_err();
+            }
+            CliRequest::McpMessage { .. } => {
+                responses
+                    .send(CliResponse::Stderr {
+                        message: "no MCP session was started".into(),
+                    })
+                    .log_err();
+                responses.send(CliResponse::Exit { status: 1 }).log_err();
+            }
         }
     }
 }
 
+/// Serves the notes of a local project over MCP, relaying the messages of the
+/// client connected to the CLI until it closes its input.
+async fn serve_mcp(
+    path: Option<String>,
+    mut requests: mpsc::Receiver<CliRequest>,
+    responses: &IpcSender<CliResponse>,
+    app_state: Arc<AppState>,
+    cx: &mut AsyncApp,
+) -> Result<()> {
+    let path = path.map(PathBuf::from);
+    let project = cx.update(|cx| {
+        workspace::local_workspace_windows(cx)
+            .into_iter()
+            .find_map(|window| {
+                let project = window.read(cx).ok()?.project().clone();
+                let contains_path = path.as_ref().map_or(true, |path| {
+                    project
+                        .read(cx)
+                        .visible_worktrees(cx)
+                        .any(|worktree| path.starts_with(worktree.read(cx).abs_path()))
+                });
+                contains_path.then_some(project)
+            })
+    })?;
+    let project = match (project, path) {
+        (Some(project), _) => project,
+        (None, Some(path)) => {
+            let (workspace, _) = cx
+                .update(|cx| workspace::open_paths(&[path], app_state, OpenOptions::default(), cx))?
+                .await?;
+            workspace.update(cx, |workspace, _, _| workspace.project().clone())?
+        }
+        (None, None) => return Err(anyhow!("no project is open in Zed")),
+    };
+
+    let (incoming_tx, incoming_rx) = smol::channel::unbounded();
+    let (outgoing_tx, outgoing_rx) = smol::channel::unbounded();
+    let transport = Arc::new(ChannelTransport::new(outgoing_tx, incoming_rx));
+    let server = cx.update(|cx| vault_mcp::serve(project, transport, cx))?;
+
+    let relay_requests = async move {
+        while let Some(request) = requests.next().await {
+            if let CliRequest::McpMessage { message } = request {
+                if incoming_tx.send(message).await.is_err() {
+                    break;
+                }
+            }
+        }
+    };
+    let relay_responses = async {
+        while let Ok(message) = outgoing_rx.recv().await {
+            responses.send(CliResponse::Stdout { message }).log_err();
+        }
+    };
+    futures::join!(relay_requests, relay_responses, server);
+    Ok(())
+}
+
 async fn open_workspaces(
     paths: Vec<String>,
     open_new_workspace: Option<bool>,